@@ -0,0 +1,87 @@
+//! Deoxys FHE hot paths: keygen, encrypt, decrypt and the homomorphic ops
+//! at each parameter preset, plus byte strings, packed batches and ring-LWE.
+//!
+//! Run with `cargo bench --bench fhe`; add `--features parallel` to spread
+//! `encrypt_bytes` / `decrypt_bytes` across threads.
+//!
+//! The crate is a binary, so the modules are compiled in by path.
+
+// The modules' unit tests are not built here, leaving their imports unused
+#![allow(dead_code, unused_imports)]
+
+#[path = "../src/fhe_core.rs"]
+mod fhe_core;
+#[path = "../src/fhe_batch.rs"]
+mod fhe_batch;
+#[path = "../src/fhe_rlwe.rs"]
+mod fhe_rlwe;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use fhe_core::{DeoxysFHE, ParamPreset};
+use fhe_rlwe::DeoxysRlwe;
+use rand_core::OsRng;
+
+fn lwe(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lwe");
+    for preset in ParamPreset::ALL {
+        let params = preset.params();
+        let n = params.n();
+        let mut fhe = DeoxysFHE::with_params(None, params);
+        let ct = fhe.encrypt_with_rng(42, &mut OsRng).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("keygen", n), &n, |b, _| b.iter(|| fhe.keygen()));
+        group.bench_with_input(BenchmarkId::new("encrypt", n), &n, |b, _| {
+            b.iter(|| fhe.encrypt_with_rng(black_box(42), &mut OsRng).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decrypt", n), &n, |b, _| {
+            b.iter(|| fhe.decrypt(black_box(ct.clone())).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("add", n), &n, |b, _| b.iter(|| fhe.add(&ct, &ct).unwrap()));
+        group.bench_with_input(BenchmarkId::new("mul_plain", n), &n, |b, _| {
+            b.iter(|| fhe.mul_plain(&ct, black_box(3)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bytes(c: &mut Criterion) {
+    let fhe = DeoxysFHE::new(None);
+    let text = vec![b'x'; 1024];
+    let cts = fhe.encrypt_bytes(&text, &mut OsRng).unwrap();
+
+    let mut group = c.benchmark_group("bytes");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("encrypt_1k", |b| b.iter(|| fhe.encrypt_bytes(black_box(&text), &mut OsRng).unwrap()));
+    group.bench_function("decrypt_1k", |b| b.iter(|| fhe.decrypt_bytes(black_box(&cts)).unwrap()));
+    group.finish();
+}
+
+fn batch(c: &mut Criterion) {
+    let fhe = DeoxysFHE::new(None);
+    let (public, secret) = fhe.batch_keys(256).unwrap();
+    let values: Vec<i32> = (0..256).collect();
+    let ct = public.encrypt_batch(&values, &mut OsRng).unwrap();
+
+    let mut group = c.benchmark_group("batch");
+    group.throughput(Throughput::Elements(values.len() as u64));
+    group.bench_function("encrypt_256", |b| b.iter(|| public.encrypt_batch(black_box(&values), &mut OsRng).unwrap()));
+    group.bench_function("decrypt_256", |b| b.iter(|| secret.decrypt_batch(black_box(&ct)).unwrap()));
+    group.finish();
+}
+
+fn rlwe(c: &mut Criterion) {
+    let fhe = DeoxysRlwe::new(None);
+    let values: Vec<i32> = (0..1024).collect();
+    let ct = fhe.encrypt_poly_with_rng(&values, &mut OsRng).unwrap();
+
+    let mut group = c.benchmark_group("rlwe");
+    group.throughput(Throughput::Elements(values.len() as u64));
+    group.bench_function("encrypt_1024", |b| {
+        b.iter(|| fhe.encrypt_poly_with_rng(black_box(&values), &mut OsRng).unwrap())
+    });
+    group.bench_function("decrypt_1024", |b| b.iter(|| fhe.decrypt_poly(black_box(&ct)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, lwe, bytes, batch, rlwe);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+//! Mamba selective scan: the sequential recurrence against the chunked SSD
+//! form at growing sequence lengths, the long-contract workload; and the
+//! LM-head matrix-vector product at each weight quantization.
+//!
+//! Run with `cargo bench --bench mamba`; add `--features parallel` to scan
+//! chunks across threads.
+//!
+//! The crate is a binary, so the modules are compiled in by path.
+
+// The modules' unit tests are not built here, leaving their imports unused
+#![allow(dead_code, unused_imports)]
+
+#[path = "../src/mamba_audit.rs"]
+mod mamba_audit;
+#[path = "../src/mamba_block.rs"]
+mod mamba_block;
+#[path = "../src/mamba_core.rs"]
+mod mamba_core;
+#[path = "../src/mamba_fp.rs"]
+mod mamba_fp;
+#[path = "../src/mamba_lazy.rs"]
+mod mamba_lazy;
+#[path = "../src/mamba_quant.rs"]
+mod mamba_quant;
+#[path = "../src/mamba_sampling.rs"]
+mod mamba_sampling;
+#[path = "../src/mamba_ssm.rs"]
+mod mamba_ssm;
+#[path = "../src/mamba_stability.rs"]
+mod mamba_stability;
+#[path = "../src/mamba_tokenizer.rs"]
+mod mamba_tokenizer;
+#[path = "../src/mamba_weights.rs"]
+mod mamba_weights;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mamba_quant::{Matrix, Quantization};
+use mamba_ssm::{SsmLayer, SSD_CHUNK_LEN};
+
+fn scan(c: &mut Criterion) {
+    let layer = SsmLayer::hippo(64, 16, 4);
+    let mut group = c.benchmark_group("scan");
+    group.sample_size(10);
+    for len in [1_024, 8_192, 50_000] {
+        let xs: Vec<Vec<f32>> = (0..len).map(|t| (0..64).map(|i| ((t * 64 + i) as f32).sin()).collect()).collect();
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::new("sequential", len), &xs, |b, xs| {
+            b.iter(|| layer.scan(black_box(xs), &mut layer.zero_state()))
+        });
+        group.bench_with_input(BenchmarkId::new("chunked", len), &xs, |b, xs| {
+            b.iter(|| layer.scan_chunked(black_box(xs), &mut layer.zero_state(), SSD_CHUNK_LEN))
+        });
+    }
+    group.finish();
+}
+
+fn quantized_matvec(c: &mut Criterion) {
+    // A GPT-2-sized vocabulary at d_model = 768
+    let (rows, cols) = (50_280, 768);
+    let values = mamba_core::hashed_uniform(b"bench", rows * cols, 0.05);
+    let x: Vec<f32> = (0..cols).map(|i| (i as f32).cos()).collect();
+    let mut group = c.benchmark_group("lm_head");
+    group.sample_size(10);
+    for quantization in [Quantization::F32, Quantization::F16, Quantization::Int8] {
+        let matrix = Matrix::new(rows, cols, values.clone()).quantized(quantization);
+        group.bench_function(format!("{:?}", quantization), |b| b.iter(|| matrix.matvec(black_box(&x))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scan, quantized_matvec);
+criterion_main!(benches);
@@ -1,8 +1,10 @@
 // Multi-Agent Orchestration System
 // Architect, Librarian, Builder, Auditor agents
 
+use super::llm::{self, LlmBackend};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepositoryEntry {
@@ -84,12 +86,14 @@ impl AgentState {
 /// Architect Agent: Generates dependency graphs and system blueprints
 pub struct ArchitectAgent {
     state: AgentState,
+    backend: Arc<dyn LlmBackend>,
 }
 
 impl ArchitectAgent {
-    pub fn new() -> Self {
+    pub fn new(backend: Arc<dyn LlmBackend>) -> Self {
         Self {
             state: AgentState::new(AgentRole::Architect),
+            backend,
         }
     }
 
@@ -97,12 +101,28 @@ impl ArchitectAgent {
         self.state.update_status(AgentStatus::Planning);
         self.state.set_task(format!("Generate DAG for: {}", requirement));
 
-        // This would call an LLM to generate the dependency graph
-        // For now, return a mock graph
+        let graph = self.plan(requirement);
+        match &graph {
+            Ok(_) => self.state.update_status(AgentStatus::Complete),
+            Err(e) => self.state.update_status(AgentStatus::Error(e.clone())),
+        }
+        graph
+    }
+
+    /// Ask the backend for the plan's nodes and add them in order; a node
+    /// may only depend on nodes listed before it
+    fn plan(&self, requirement: &str) -> Result<super::dag::DependencyGraph, String> {
+        let reply = self.backend.generate(&llm::plan_prompt(requirement))?;
+        let nodes: Vec<super::dag::DependencyNode> = serde_json::from_str(&llm::extract_code(&reply))
+            .map_err(|e| format!("Architect returned an unreadable plan: {}", e))?;
+
         let mut graph = super::dag::DependencyGraph::new();
-        
-        // Mock implementation - would be replaced with actual LLM call
-        self.state.update_status(AgentStatus::Complete);
+        for node in nodes {
+            if let Some(missing) = node.dependencies.iter().find(|dep| graph.get_node(dep).is_none()) {
+                return Err(format!("Node {} depends on unplanned node {}", node.id, missing));
+            }
+            graph.add_node(node)?;
+        }
         Ok(graph)
     }
 
@@ -146,13 +166,15 @@ impl LibrarianAgent {
 pub struct BuilderAgent {
     state: AgentState,
     sterilization_config: super::constraints::SterilizationConfig,
+    backend: Arc<dyn LlmBackend>,
 }
 
 impl BuilderAgent {
-    pub fn new() -> Self {
+    pub fn new(backend: Arc<dyn LlmBackend>) -> Self {
         Self {
             state: AgentState::new(AgentRole::Builder),
             sterilization_config: super::constraints::SterilizationConfig::default(),
+            backend,
         }
     }
 
@@ -160,15 +182,28 @@ impl BuilderAgent {
         self.state.update_status(AgentStatus::Generating);
         self.state.set_task(format!("Generate code for: {}", spec.id));
 
-        // This would call an LLM with sterilization constraints
-        // For now, return mock code
-        let code = format!(
-            "# Generated code for {}\n# This would be generated by LLM with sterilization constraints",
-            spec.id
-        );
+        let reply = self
+            .backend
+            .generate_with_constraints(&llm::build_prompt(spec, context), &self.sterilization_config);
+        match reply {
+            Ok(reply) => {
+                self.state.update_status(AgentStatus::Complete);
+                Ok(llm::extract_code(&reply))
+            }
+            Err(e) => {
+                self.state.update_status(AgentStatus::Error(e.clone()));
+                Err(format!("Builder failed on {}: {}", spec.id, e))
+            }
+        }
+    }
+
+    pub fn sterilization_config(&self) -> &super::constraints::SterilizationConfig {
+        &self.sterilization_config
+    }
 
-        self.state.update_status(AgentStatus::Complete);
-        Ok(code)
+    /// Generate, and have repairs prompted, under `policy`
+    pub fn set_policy(&mut self, policy: super::policy::SterilizationPolicy) {
+        self.sterilization_config = self.sterilization_config.clone().with_policy(policy);
     }
 
     pub fn get_state(&self) -> &AgentState {
@@ -180,6 +215,7 @@ impl BuilderAgent {
 pub struct AuditorAgent {
     state: AgentState,
     sandbox: super::sandbox::HermeticSandbox,
+    compiler: Option<Arc<dyn super::wasm_exec::WasmCompiler>>,
 }
 
 impl AuditorAgent {
@@ -187,13 +223,42 @@ impl AuditorAgent {
         Self {
             state: AgentState::new(AgentRole::Auditor),
             sandbox: crate::axiom_determinist::sandbox::HermeticSandbox::new(),
+            compiler: None,
         }
     }
 
+    /// Report the patterns `policy` bans
+    pub fn set_policy(&mut self, policy: super::policy::SterilizationPolicy) {
+        self.sandbox.policy = policy;
+    }
+
+    /// Compile validated code with `compiler` and run its tests
+    pub fn set_compiler(&mut self, compiler: Arc<dyn super::wasm_exec::WasmCompiler>) {
+        self.compiler = Some(compiler);
+    }
+
     pub fn validate(&mut self, code: &str, language: &str) -> super::sandbox::ValidationResult {
         self.state.update_status(AgentStatus::Validating);
         let result = self.sandbox.validate(code, language);
-        
+        self.record(result)
+    }
+
+    /// Validate, then run `plan`'s tests if there is a plan and a compiler
+    pub fn validate_with_tests(
+        &mut self,
+        code: &str,
+        language: &str,
+        plan: Option<&super::dag::TestPlan>,
+    ) -> super::sandbox::ValidationResult {
+        let (Some(plan), Some(compiler)) = (plan, self.compiler.clone()) else {
+            return self.validate(code, language);
+        };
+        self.state.update_status(AgentStatus::Validating);
+        let result = self.sandbox.validate_and_test(code, language, plan, compiler.as_ref());
+        self.record(result)
+    }
+
+    fn record(&mut self, result: super::sandbox::ValidationResult) -> super::sandbox::ValidationResult {
         if result.passed {
             self.state.update_status(AgentStatus::Complete);
         } else {
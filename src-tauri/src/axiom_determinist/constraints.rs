@@ -1,6 +1,7 @@
 // Tier 2: Constraint-Based Generation
 // Logit bias, token banning, and grammar constraints
 
+use super::policy::SterilizationPolicy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -14,28 +15,17 @@ pub struct LogitBias {
 }
 
 impl LogitBias {
+    /// The decode-time bans of the default policy
     pub fn new() -> Self {
+        Self::from_policy(&SterilizationPolicy::default(), None)
+    }
+
+    /// The patterns `policy` bans while decoding `language`, or any
+    /// language if None
+    pub fn from_policy(policy: &SterilizationPolicy, language: Option<&ProgrammingLanguage>) -> Self {
         Self {
             token_biases: HashMap::new(),
-            banned_strings: vec![
-                "TODO".to_string(),
-                "FIXME".to_string(),
-                "XXX".to_string(),
-                "HACK".to_string(),
-                "todo".to_string(),
-                "fixme".to_string(),
-                "xxx".to_string(),
-                "hack".to_string(),
-                "NotImplementedError".to_string(),
-                "NotImplemented".to_string(),
-                "pass".to_string(),
-                "return null".to_string(),
-                "return None".to_string(),
-                "omitted for brevity".to_string(),
-                "rest of code".to_string(),
-                "left as an exercise".to_string(),
-                "implementation omitted".to_string(),
-            ],
+            banned_strings: policy.decode_bans(language.map(ProgrammingLanguage::as_str)),
         }
     }
 
@@ -79,6 +69,18 @@ pub enum ProgrammingLanguage {
     TypeScript,
 }
 
+impl ProgrammingLanguage {
+    /// The name the sandbox and the policy use
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProgrammingLanguage::Python => "python",
+            ProgrammingLanguage::Rust => "rust",
+            ProgrammingLanguage::JavaScript => "javascript",
+            ProgrammingLanguage::TypeScript => "typescript",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GrammarRule {
     pub rule_name: String,
@@ -99,12 +101,18 @@ impl GrammarConstraint {
             language: ProgrammingLanguage::Python,
             grammar_rules: vec![
                 GrammarRule {
-                    rule_name: "func_body_no_pass".to_string(),
+                    rule_name: "no_placeholder_statements".to_string(),
                     ebnf_definition: r#"
-                        func_body ::= INDENT (stmt)+ DEDENT
-                        stmt ::= (expr_stmt | compound_stmt) 
-                        compound_stmt ::= if_stmt | while_stmt | for_stmt | try_stmt | with_stmt | funcdef | classdef
-                        # Exclude: stmt ::= pass
+                        module ::= line ('\n' line)*
+                        line ::= [ \t]* (statement | comment)?
+                        comment ::= '#' [^\n]*
+                        # Any statement but a line of only `pass` or `...`
+                        statement ::= [^ \t\n#p.] [^\n]* | 'p' p | '.' dot
+                        p ::= '' | [^\na] [^\n]* | 'a' pa
+                        pa ::= '' | [^\ns] [^\n]* | 's' pas
+                        pas ::= '' | [^\ns] [^\n]* | 's' [^\n]+
+                        dot ::= '' | [^\n.] [^\n]* | '.' dots
+                        dots ::= '' | [^\n.] [^\n]* | '.' [^\n]+
                     "#.to_string(),
                     enforcement: EnforcementLevel::Fatal,
                 },
@@ -122,11 +130,14 @@ impl GrammarConstraint {
             language: ProgrammingLanguage::Rust,
             grammar_rules: vec![
                 GrammarRule {
-                    rule_name: "fn_body_no_unimplemented".to_string(),
+                    rule_name: "balanced_delimiters".to_string(),
                     ebnf_definition: r#"
-                        fn_body ::= block_expr
-                        block_expr ::= '{' (stmt)* (expr)? '}'
-                        # Exclude: stmt ::= unimplemented!() | todo!()
+                        file ::= part*
+                        part ::= [^{}()\[\]"'/] | '/' | comment | string | char | "'"
+                            | '{' file '}' | '(' file ')' | '[' file ']'
+                        comment ::= '//' [^\n]* | '/*' ([^*] | '*'+ [^*/])* '*'+ '/'
+                        string ::= '"' ([^"\\] | '\\' .)* '"'
+                        char ::= "'" ([^'\\] | '\\' [^']+) "'"
                     "#.to_string(),
                     enforcement: EnforcementLevel::Fatal,
                 },
@@ -148,13 +159,17 @@ pub struct SterilizationConfig {
     pub prompt_fencing: bool,
     pub cryptographic_delimiter: String,
     pub positive_guidance: String,
+    /// The policy `logit_bias` was built from
+    pub policy: SterilizationPolicy,
 }
 
 impl SterilizationConfig {
     pub fn default() -> Self {
+        let policy = SterilizationPolicy::default();
+        let grammar_constraint = GrammarConstraint::for_python();
         Self {
-            logit_bias: LogitBias::new(),
-            grammar_constraint: Some(GrammarConstraint::for_python()),
+            logit_bias: LogitBias::from_policy(&policy, Some(&grammar_constraint.language)),
+            grammar_constraint: Some(grammar_constraint),
             prompt_fencing: true,
             cryptographic_delimiter: "###_STERILIZATION_PROTOCOL_v1_###".to_string(),
             positive_guidance: r#"
@@ -163,9 +178,18 @@ impl SterilizationConfig {
                 Every function must contain complete, executable logic.
                 Code containing placeholders will trigger a fatal build error.
             "#.to_string(),
+            policy,
         }
     }
 
+    /// This config with `policy`, and the logit bias rebuilt from it
+    pub fn with_policy(mut self, policy: SterilizationPolicy) -> Self {
+        let language = self.grammar_constraint.as_ref().map(|grammar| &grammar.language);
+        self.logit_bias = LogitBias::from_policy(&policy, language);
+        self.policy = policy;
+        self
+    }
+
     /// Generate the sterilization prompt suffix
     pub fn generate_prompt_suffix(&self) -> String {
         format!(
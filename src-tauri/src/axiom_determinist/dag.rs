@@ -95,18 +95,19 @@ impl DependencyGraph {
             return Err(format!("Adding node {} would create a circular dependency", node.id));
         }
 
+        let id = node.id.clone();
         let deps = node.dependencies.clone();
-        self.nodes.insert(node.id.clone(), node);
+        self.nodes.insert(id.clone(), node);
         
         // Build adjacency lists
-        self.adjacency_list.insert(node.id.clone(), deps.clone());
+        self.adjacency_list.insert(id.clone(), deps.clone());
         
         // Build reverse adjacency for reachability
         for dep in &deps {
             self.reverse_adjacency
                 .entry(dep.clone())
-                .or_insert_with(Vec::new)
-                .push(node.id.clone());
+                .or_default()
+                .push(id.clone());
         }
 
         Ok(())
@@ -116,25 +117,22 @@ impl DependencyGraph {
     pub fn topological_sort(&self) -> Result<Vec<String>, String> {
         let mut in_degree: HashMap<String, usize> = HashMap::new();
         
-        // Initialize in-degree for all nodes
+        // In-degree: how many of a node's dependencies are in the graph
         for node_id in self.nodes.keys() {
-            in_degree.insert(node_id.clone(), 0);
-        }
-        
-        // Calculate in-degrees
-        for deps in self.adjacency_list.values() {
-            for dep in deps {
-                *in_degree.get_mut(dep).unwrap() += 1;
-            }
+            let deps = self.adjacency_list.get(node_id).map_or(&[][..], Vec::as_slice);
+            let degree = deps.iter().filter(|dep| self.nodes.contains_key(*dep)).count();
+            in_degree.insert(node_id.clone(), degree);
         }
 
-        // Kahn's algorithm
-        let mut queue: VecDeque<String> = VecDeque::new();
-        for (node_id, degree) in &in_degree {
-            if *degree == 0 {
-                queue.push_back(node_id.clone());
-            }
-        }
+        // Kahn's algorithm, starting from the nodes without dependencies in
+        // ID order so the result does not depend on hash order
+        let mut roots: Vec<String> = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(node_id, _)| node_id.clone())
+            .collect();
+        roots.sort();
+        let mut queue: VecDeque<String> = roots.into();
 
         let mut result = Vec::new();
         while let Some(node_id) = queue.pop_front() {
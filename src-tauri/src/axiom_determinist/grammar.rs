@@ -0,0 +1,691 @@
+// Tier 2: Grammar Enforcement
+// Compiles a GrammarConstraint's EBNF rules into pushdown automata
+//
+// A grammar is a list of rules, the first of them the start rule:
+//
+//     block ::= '{' (stmt ';')* '}'    # comments run to the end of the line
+//     stmt  ::= [a-z_]+ ('(' [^)]* ')')?
+//
+// Rules are made of 'quoted' or "quoted" literals ('' matches nothing),
+// character classes such as [a-z] or [^\n], `.` for any character,
+// references to other rules, grouping, `|`, and the `?`, `*` and `+`
+// operators. Grammars are matched a character at a time. A rule that can
+// reach itself without consuming a character is refused, since matching
+// it would never end.
+//
+// A parse state is the set of parser stacks still alive after the text
+// so far; each stack holds the position reached in every rule entered.
+// `ConstraintEngine` runs all of a constraint's rules side by side, so a
+// decoder can mask the tokens that would take any of them to a dead end
+// (`allowed_next_tokens`), and `check` reports where finished code leaves
+// a rule.
+
+use super::constraints::{EnforcementLevel, GrammarConstraint};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CharSet {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl CharSet {
+    fn single(c: char) -> Self {
+        Self { ranges: vec![(c, c)], negated: false }
+    }
+
+    fn any() -> Self {
+        Self { ranges: Vec::new(), negated: true }
+    }
+
+    fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != self.negated
+    }
+}
+
+impl fmt::Display for CharSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated && self.ranges.is_empty() {
+            return write!(f, "any character");
+        }
+        if let [(lo, hi)] = self.ranges.as_slice() {
+            if lo == hi && !self.negated {
+                return write!(f, "{:?}", lo);
+            }
+        }
+        write!(f, "[{}", if self.negated { "^" } else { "" })?;
+        for &(lo, hi) in &self.ranges {
+            let escape = |c: char| c.escape_default().to_string();
+            if lo == hi {
+                write!(f, "{}", escape(lo))?;
+            } else {
+                write!(f, "{}-{}", escape(lo), escape(hi))?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Element {
+    Chars(CharSet),
+    Rule(usize),
+}
+
+/// Where a stack stands in one alternative of a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Position {
+    rule: u32,
+    alt: u32,
+    at: u32,
+}
+
+/// The stacks alive after the text matched so far; an empty stack means
+/// the text so far is a complete match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseState {
+    stacks: BTreeSet<Vec<Position>>,
+}
+
+/// A compiled grammar
+#[derive(Debug, Clone)]
+pub struct Grammar {
+    /// Rule names; rules made for groups and operators are named after
+    /// the rule they appear in
+    names: Vec<String>,
+    /// Alternatives of each rule
+    rules: Vec<Vec<Vec<Element>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Define,
+    Literal(String),
+    Class(CharSet),
+    Dot,
+    Open,
+    Close,
+    Pipe,
+    Repeat(char),
+}
+
+fn escaped(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        c => c,
+    }
+}
+
+fn lex(ebnf: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = ebnf.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '#' => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            ':' if chars.next_if_eq(&':').is_some() && chars.next_if_eq(&'=').is_some() => {
+                tokens.push((Token::Define, line));
+            }
+            '\'' | '"' => {
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => literal.push(escaped(chars.next().ok_or(format!("Line {}: unterminated literal", line))?)),
+                        Some(q) if q == c => break,
+                        Some('\n') | None => return Err(format!("Line {}: unterminated literal", line)),
+                        Some(c) => literal.push(c),
+                    }
+                }
+                tokens.push((Token::Literal(literal), line));
+            }
+            '[' => {
+                let negated = chars.next_if_eq(&'^').is_some();
+                let mut ranges = Vec::new();
+                loop {
+                    let lo = match chars.next() {
+                        Some(']') => break,
+                        Some('\\') => escaped(chars.next().ok_or(format!("Line {}: unterminated class", line))?),
+                        Some('\n') | None => return Err(format!("Line {}: unterminated class", line)),
+                        Some(c) => c,
+                    };
+                    let hi = if chars.peek() == Some(&'-') && chars.clone().nth(1).is_some_and(|c| c != ']') {
+                        chars.next();
+                        match chars.next() {
+                            Some('\\') => escaped(chars.next().ok_or(format!("Line {}: unterminated class", line))?),
+                            Some(c) => c,
+                            None => return Err(format!("Line {}: unterminated class", line)),
+                        }
+                    } else {
+                        lo
+                    };
+                    if hi < lo {
+                        return Err(format!("Line {}: empty range {}-{}", line, lo, hi));
+                    }
+                    ranges.push((lo, hi));
+                }
+                tokens.push((Token::Class(CharSet { ranges, negated }), line));
+            }
+            '.' => tokens.push((Token::Dot, line)),
+            '(' => tokens.push((Token::Open, line)),
+            ')' => tokens.push((Token::Close, line)),
+            '|' => tokens.push((Token::Pipe, line)),
+            '?' | '*' | '+' => tokens.push((Token::Repeat(c), line)),
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_' || c == '-') {
+                    name.push(c);
+                }
+                tokens.push((Token::Name(name), line));
+            }
+            c => return Err(format!("Line {}: unexpected {:?}", line, c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent reader of a token list into rules
+struct Reader {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    names: Vec<String>,
+    rules: Vec<Vec<Vec<Element>>>,
+    ids: HashMap<String, usize>,
+    defined: Vec<bool>,
+    /// Line of the first reference to each rule
+    referenced: Vec<usize>,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens.get(self.next.min(self.tokens.len().saturating_sub(1))).map_or(0, |&(_, line)| line)
+    }
+
+    /// Whether a new rule starts at the next token
+    fn at_definition(&self) -> bool {
+        matches!(self.peek(), Some(Token::Name(_)))
+            && matches!(self.tokens.get(self.next + 1), Some((Token::Define, _)))
+    }
+
+    fn id(&mut self, name: &str, line: usize) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.add_rule(name.to_string(), Vec::new());
+        self.ids.insert(name.to_string(), id);
+        self.referenced[id] = line;
+        id
+    }
+
+    fn add_rule(&mut self, name: String, alternatives: Vec<Vec<Element>>) -> usize {
+        self.names.push(name);
+        self.rules.push(alternatives);
+        self.defined.push(false);
+        self.referenced.push(0);
+        self.rules.len() - 1
+    }
+
+    fn helper(&mut self, parent: usize, alternatives: Vec<Vec<Element>>) -> usize {
+        let name = self.names[parent].clone();
+        let id = self.add_rule(name, alternatives);
+        self.defined[id] = true;
+        id
+    }
+
+    fn definition(&mut self) -> Result<(), String> {
+        let line = self.line();
+        let Some(Token::Name(name)) = self.peek().cloned() else {
+            return Err(format!("Line {}: expected a rule name", line));
+        };
+        self.next += 2;
+        let id = self.id(&name, line);
+        if self.defined[id] {
+            return Err(format!("Line {}: rule {} is defined twice", line, name));
+        }
+        self.defined[id] = true;
+        self.rules[id] = self.alternatives(id)?;
+        Ok(())
+    }
+
+    fn alternatives(&mut self, rule: usize) -> Result<Vec<Vec<Element>>, String> {
+        let mut alternatives = vec![self.sequence(rule)?];
+        while self.peek() == Some(&Token::Pipe) {
+            self.next += 1;
+            alternatives.push(self.sequence(rule)?);
+        }
+        Ok(alternatives)
+    }
+
+    fn sequence(&mut self, rule: usize) -> Result<Vec<Element>, String> {
+        let mut sequence = Vec::new();
+        while !matches!(self.peek(), None | Some(Token::Pipe) | Some(Token::Close)) && !self.at_definition() {
+            sequence.extend(self.repeated(rule)?);
+        }
+        Ok(sequence)
+    }
+
+    fn repeated(&mut self, rule: usize) -> Result<Vec<Element>, String> {
+        let mut elements = self.primary(rule)?;
+        while let Some(Token::Repeat(op)) = self.peek().cloned() {
+            self.next += 1;
+            let repeat = self.helper(rule, Vec::new());
+            let mut again = elements.clone();
+            again.push(Element::Rule(repeat));
+            self.rules[repeat] = match op {
+                '?' => vec![elements, Vec::new()],
+                '*' => vec![again, Vec::new()],
+                _ => vec![again, elements],
+            };
+            elements = vec![Element::Rule(repeat)];
+        }
+        Ok(elements)
+    }
+
+    fn primary(&mut self, rule: usize) -> Result<Vec<Element>, String> {
+        let line = self.line();
+        let token = self.peek().cloned().ok_or(format!("Line {}: unexpected end of grammar", line))?;
+        self.next += 1;
+        Ok(match token {
+            Token::Name(name) => vec![Element::Rule(self.id(&name, line))],
+            Token::Literal(literal) => literal.chars().map(|c| Element::Chars(CharSet::single(c))).collect(),
+            Token::Class(set) => vec![Element::Chars(set)],
+            Token::Dot => vec![Element::Chars(CharSet::any())],
+            Token::Open => {
+                let alternatives = self.alternatives(rule)?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(format!("Line {}: expected ')'", self.line()));
+                }
+                self.next += 1;
+                vec![Element::Rule(self.helper(rule, alternatives))]
+            }
+            token => return Err(format!("Line {}: unexpected {:?}", line, token)),
+        })
+    }
+}
+
+impl Grammar {
+    /// Compile `ebnf`; its first rule is the start rule
+    pub fn parse(ebnf: &str) -> Result<Self, String> {
+        let mut reader = Reader {
+            tokens: lex(ebnf)?,
+            next: 0,
+            names: Vec::new(),
+            rules: Vec::new(),
+            ids: HashMap::new(),
+            defined: Vec::new(),
+            referenced: Vec::new(),
+        };
+        if reader.tokens.is_empty() {
+            return Err("Grammar has no rules".to_string());
+        }
+        while reader.next < reader.tokens.len() {
+            reader.definition()?;
+        }
+        if let Some(id) = reader.defined.iter().position(|defined| !defined) {
+            return Err(format!("Line {}: rule {} is not defined", reader.referenced[id], reader.names[id]));
+        }
+
+        let grammar = Self { names: reader.names, rules: reader.rules };
+        grammar.check_termination()?;
+        Ok(grammar)
+    }
+
+    fn nullable(&self) -> Vec<bool> {
+        let mut nullable = vec![false; self.rules.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (rule, alternatives) in self.rules.iter().enumerate() {
+                if !nullable[rule]
+                    && alternatives.iter().any(|alt| {
+                        alt.iter().all(|element| matches!(element, Element::Rule(r) if nullable[*r]))
+                    })
+                {
+                    nullable[rule] = true;
+                    changed = true;
+                }
+            }
+        }
+        nullable
+    }
+
+    /// Refuse a rule that can enter itself before consuming a character
+    fn check_termination(&self) -> Result<(), String> {
+        let nullable = self.nullable();
+        // Rules each rule can enter before consuming a character
+        let corners: Vec<Vec<usize>> = self
+            .rules
+            .iter()
+            .map(|alternatives| {
+                let mut corners = Vec::new();
+                for alt in alternatives {
+                    for element in alt {
+                        match element {
+                            Element::Rule(r) => {
+                                corners.push(*r);
+                                if !nullable[*r] {
+                                    break;
+                                }
+                            }
+                            Element::Chars(_) => break,
+                        }
+                    }
+                }
+                corners
+            })
+            .collect();
+
+        // Depth-first search for a cycle: 0 unvisited, 1 on the path, 2 done
+        let mut marks = vec![0u8; self.rules.len()];
+        for root in 0..self.rules.len() {
+            let mut path = vec![(root, 0)];
+            while let Some(&mut (rule, ref mut next)) = path.last_mut() {
+                if *next == 0 {
+                    if marks[rule] == 2 {
+                        path.pop();
+                        continue;
+                    }
+                    marks[rule] = 1;
+                }
+                match corners[rule].get(*next) {
+                    Some(&corner) => {
+                        *next += 1;
+                        match marks[corner] {
+                            1 => return Err(format!("Rule {} can repeat without consuming input", self.names[corner])),
+                            0 => path.push((corner, 0)),
+                            _ => {}
+                        }
+                    }
+                    None => {
+                        marks[rule] = 2;
+                        path.pop();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn element(&self, position: Position) -> Option<&Element> {
+        self.rules[position.rule as usize][position.alt as usize].get(position.at as usize)
+    }
+
+    /// Add `stack`, expanded until every stack is empty or waiting on
+    /// characters, to `out`
+    fn expand(&self, mut stack: Vec<Position>, out: &mut BTreeSet<Vec<Position>>) {
+        while stack.last().is_some_and(|&top| self.element(top).is_none()) {
+            stack.pop();
+        }
+        let Some(&top) = stack.last() else {
+            out.insert(stack);
+            return;
+        };
+        match self.element(top) {
+            Some(Element::Rule(rule)) => {
+                let rule = *rule;
+                // Step past the reference, and drop the caller if that ends
+                // it, so repetition does not grow the stack
+                stack.last_mut().expect("stack has a top").at += 1;
+                if self.element(*stack.last().expect("stack has a top")).is_none() {
+                    stack.pop();
+                }
+                for alt in 0..self.rules[rule].len() {
+                    let mut next = stack.clone();
+                    next.push(Position { rule: rule as u32, alt: alt as u32, at: 0 });
+                    self.expand(next, out);
+                }
+            }
+            _ => {
+                out.insert(stack);
+            }
+        }
+    }
+
+    /// The state before any text
+    pub fn start(&self) -> ParseState {
+        let mut stacks = BTreeSet::new();
+        for alt in 0..self.rules[0].len() {
+            self.expand(vec![Position { rule: 0, alt: alt as u32, at: 0 }], &mut stacks);
+        }
+        ParseState { stacks }
+    }
+
+    /// The state after `c`, if the grammar allows it
+    pub fn advance(&self, state: &ParseState, c: char) -> Option<ParseState> {
+        let mut stacks = BTreeSet::new();
+        for stack in &state.stacks {
+            let Some(&top) = stack.last() else {
+                continue;
+            };
+            if let Some(Element::Chars(set)) = self.element(top) {
+                if set.contains(c) {
+                    let mut next = stack.clone();
+                    next.last_mut().expect("stack has a top").at += 1;
+                    self.expand(next, &mut stacks);
+                }
+            }
+        }
+        (!stacks.is_empty()).then_some(ParseState { stacks })
+    }
+
+    pub fn advance_str(&self, state: &ParseState, text: &str) -> Option<ParseState> {
+        text.chars().try_fold(state.clone(), |state, c| self.advance(&state, c))
+    }
+
+    /// Whether the text so far is a complete match
+    pub fn is_accepting(&self, state: &ParseState) -> bool {
+        state.stacks.iter().any(Vec::is_empty)
+    }
+
+    /// What may come next, described
+    pub fn expected(&self, state: &ParseState) -> Vec<String> {
+        let mut expected: BTreeSet<String> = state
+            .stacks
+            .iter()
+            .filter_map(|stack| match self.element(*stack.last()?) {
+                Some(Element::Chars(set)) => Some(set.to_string()),
+                _ => None,
+            })
+            .collect();
+        if self.is_accepting(state) {
+            expected.insert("end of input".to_string());
+        }
+        expected.into_iter().collect()
+    }
+}
+
+/// Where code leaves a grammar rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrammarViolation {
+    pub rule_name: String,
+    pub enforcement: EnforcementLevel,
+    /// 1-based
+    pub line: u32,
+    pub column: u32,
+    pub expected: Vec<String>,
+}
+
+/// A state of every rule of a constraint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeState {
+    rules: Vec<ParseState>,
+}
+
+/// A GrammarConstraint's rules, compiled, and the vocabulary of the model
+/// decoding under them
+#[derive(Debug, Clone)]
+pub struct ConstraintEngine {
+    rules: Vec<(String, EnforcementLevel, Grammar)>,
+    /// Text of each token, by ID; tokens with no text are never allowed
+    vocabulary: Vec<String>,
+}
+
+impl ConstraintEngine {
+    pub fn new(constraint: &GrammarConstraint) -> Result<Self, String> {
+        let rules = constraint
+            .grammar_rules
+            .iter()
+            .map(|rule| {
+                let grammar = Grammar::parse(&rule.ebnf_definition).map_err(|e| format!("Rule {}: {}", rule.rule_name, e))?;
+                Ok((rule.rule_name.clone(), rule.enforcement.clone(), grammar))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { rules, vocabulary: Vec::new() })
+    }
+
+    /// Decode with a model whose token `id` has the text `vocabulary[id]`
+    pub fn with_vocabulary(mut self, vocabulary: Vec<String>) -> Self {
+        self.vocabulary = vocabulary;
+        self
+    }
+
+    pub fn start(&self) -> DecodeState {
+        DecodeState { rules: self.rules.iter().map(|(_, _, grammar)| grammar.start()).collect() }
+    }
+
+    /// The state after `text`, if every rule allows it
+    pub fn advance(&self, state: &DecodeState, text: &str) -> Option<DecodeState> {
+        let rules = self
+            .rules
+            .iter()
+            .zip(&state.rules)
+            .map(|((_, _, grammar), state)| grammar.advance_str(state, text))
+            .collect::<Option<_>>()?;
+        Some(DecodeState { rules })
+    }
+
+    /// The state after the tokens `ids`, if every rule allows them
+    pub fn advance_tokens(&self, state: &DecodeState, ids: &[u32]) -> Option<DecodeState> {
+        ids.iter().try_fold(state.clone(), |state, &id| {
+            let text = self.vocabulary.get(id as usize).filter(|text| !text.is_empty())?;
+            self.advance(&state, text)
+        })
+    }
+
+    /// Whether decoding may stop here
+    pub fn is_complete(&self, state: &DecodeState) -> bool {
+        self.rules.iter().zip(&state.rules).all(|((_, _, grammar), state)| grammar.is_accepting(state))
+    }
+
+    /// IDs of the tokens every rule allows next
+    pub fn allowed_next_tokens(&self, state: &DecodeState) -> Vec<u32> {
+        (0..self.vocabulary.len() as u32)
+            .filter(|&id| self.advance_tokens(state, &[id]).is_some())
+            .collect()
+    }
+
+    /// Where `code` leaves each rule it does not match
+    pub fn check(&self, code: &str) -> Vec<GrammarViolation> {
+        let mut violations = Vec::new();
+        for (rule_name, enforcement, grammar) in &self.rules {
+            let mut state = grammar.start();
+            let (mut line, mut column) = (1, 1);
+            let mut failed = None;
+            for c in code.chars() {
+                match grammar.advance(&state, c) {
+                    Some(next) => state = next,
+                    None => {
+                        failed = Some(grammar.expected(&state));
+                        break;
+                    }
+                }
+                if c == '\n' {
+                    line += 1;
+                    column = 1;
+                } else {
+                    column += 1;
+                }
+            }
+            if failed.is_none() && !grammar.is_accepting(&state) {
+                failed = Some(grammar.expected(&state));
+            }
+            if let Some(expected) = failed {
+                violations.push(GrammarViolation {
+                    rule_name: rule_name.clone(),
+                    enforcement: enforcement.clone(),
+                    line,
+                    column,
+                    expected,
+                });
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axiom_determinist::constraints::GrammarRule;
+
+    fn constraint(ebnf: &str) -> GrammarConstraint {
+        let mut constraint = GrammarConstraint::for_rust();
+        constraint.grammar_rules = vec![GrammarRule {
+            rule_name: "calls".to_string(),
+            ebnf_definition: ebnf.to_string(),
+            enforcement: EnforcementLevel::Error,
+        }];
+        constraint
+    }
+
+    #[test]
+    fn test_allowed_tokens_follow_the_grammar() {
+        let ebnf = r#"
+            block ::= '{' (stmt ';')* '}'   # a block of calls
+            stmt  ::= name '(' args? ')'
+            args  ::= name (',' name)*
+            name  ::= [a-z_] [a-z_0-9]*
+        "#;
+        let vocabulary = ["{", "}", "run", "(", ")", ";", "x,y", "{}", " "].map(String::from).to_vec();
+        let engine = ConstraintEngine::new(&constraint(ebnf)).unwrap().with_vocabulary(vocabulary);
+        let start = engine.start();
+        assert_eq!(engine.allowed_next_tokens(&start), vec![0, 7]);
+        let call = engine.advance_tokens(&start, &[0, 2, 3]).unwrap();
+        assert_eq!(engine.allowed_next_tokens(&call), vec![2, 4, 6]);
+        let done = engine.advance(&call, ");}").unwrap();
+        assert!(engine.is_complete(&done) && !engine.is_complete(&call));
+        assert!(engine.allowed_next_tokens(&done).is_empty());
+
+        assert!(engine.check("{run(x,y);stop();}").is_empty());
+        let violations = engine.check("{run(x,y);stop(;}");
+        assert_eq!((violations[0].line, violations[0].column), (1, 16));
+        assert!(violations[0].expected.contains(&"')'".to_string()), "{:?}", violations[0].expected);
+    }
+
+    #[test]
+    fn test_grammars_that_cannot_be_matched_are_refused() {
+        assert!(Grammar::parse("a ::= b").unwrap_err().contains("rule b is not defined"));
+        assert!(Grammar::parse("a ::= a 'x' | 'y'").unwrap_err().contains("without consuming input"));
+        assert!(Grammar::parse("a ::= ('x'?)*").unwrap_err().contains("without consuming input"));
+        assert!(Grammar::parse("a ::= ('x' | 'y'").unwrap_err().starts_with("Line 1"));
+    }
+
+    #[test]
+    fn test_built_in_rules_are_enforced() {
+        let python = ConstraintEngine::new(&GrammarConstraint::for_python()).unwrap();
+        assert!(python.check("def total(lines):\n    password = sum(lines)\n    return password\n").is_empty());
+        let violations = python.check("def total(lines):\n    pass\n");
+        assert_eq!((violations[0].line, violations[0].column), (2, 9));
+        assert_eq!(python.check("class Ledger:\n    ...")[0].line, 2);
+
+        let rust = ConstraintEngine::new(&GrammarConstraint::for_rust()).unwrap();
+        let code = "fn brace() -> char {\n    // '}' closes\n    let s = \"}\";\n    '{'\n}\n";
+        assert!(rust.check(code).is_empty(), "{:?}", rust.check(code));
+        assert_eq!(rust.check("fn f() { (1 }").len(), 1);
+    }
+}
@@ -0,0 +1,385 @@
+// LLM Backends
+// Where the Architect, Builder and Reflexion loop get their text from
+//
+// Agents talk to a model through `LlmBackend`. Prompts mark what the
+// model is given with labelled fences: ```requirement for the Architect,
+// ```spec (a DependencyNode as JSON) for the Builder, and the code to
+// repair fenced with its language by the Reflexion loop. A reply is read
+// with `extract_code`, so a model may answer in or out of a fence.
+//
+// `LocalStubBackend` answers deterministically without a model, so the
+// workflow runs end to end offline. A host with a real model supplies it
+// through `HostAdapter`.
+
+use super::constraints::SterilizationConfig;
+use super::dag::{
+    ClassSignature, DependencyNode, FunctionSignature, InterfaceSpec, ModuleType, Parameter,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A model the agents can prompt
+pub trait LlmBackend: Send + Sync {
+    /// Complete `prompt` without constraints
+    fn generate(&self, prompt: &str) -> Result<String, String>;
+
+    /// Complete `prompt` under the sterilization constraints in `config`
+    fn generate_with_constraints(
+        &self,
+        prompt: &str,
+        config: &SterilizationConfig,
+    ) -> Result<String, String>;
+}
+
+/// The contents of the first ````tag` fence in `text`
+pub fn fenced<'a>(text: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("```{}\n", tag);
+    let start = text.find(&open)? + open.len();
+    let end = text[start..].find("```")?;
+    Some(text[start..start + end].trim_end_matches('\n'))
+}
+
+/// The code in a model's reply: the first fenced block if there is one,
+/// else the whole reply
+pub fn extract_code(reply: &str) -> String {
+    let Some(open) = reply.find("```") else {
+        return reply.trim().to_string();
+    };
+    let body = &reply[open + 3..];
+    let body = body.find('\n').map_or(body, |newline| &body[newline + 1..]);
+    let end = body.find("```").unwrap_or(body.len());
+    body[..end].trim_end().to_string()
+}
+
+/// Prompt asking the Architect's model for a plan
+pub fn plan_prompt(requirement: &str) -> String {
+    format!(
+        "Decompose the requirement below into modules. Answer with a JSON array of \
+         dependency nodes, each with id, file_path, module_type, public_interface, \
+         dependencies and test_plan.\n\n```requirement\n{}\n```\n",
+        requirement
+    )
+}
+
+/// Prompt asking the Builder's model for a module's code
+pub fn build_prompt(spec: &DependencyNode, context: &[InterfaceSpec]) -> String {
+    format!(
+        "Implement the module specified below. It may use these interfaces of its \
+         dependencies:\n\n```json\n{}\n```\n\n```spec\n{}\n```\n",
+        serde_json::to_string_pretty(context).expect("interfaces serialize"),
+        serde_json::to_string_pretty(spec).expect("nodes serialize"),
+    )
+}
+
+/// What the host is asked to complete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostRequest {
+    pub prompt: String,
+    /// Token ID to bias, for hosts that tokenize as the config was built
+    pub logit_bias: HashMap<u32, f32>,
+    /// Strings the host must keep out of the completion
+    pub banned_strings: Vec<String>,
+}
+
+type Completion = dyn Fn(&HostRequest) -> Result<String, String> + Send + Sync;
+
+/// A backend the host supplies: a function completing a `HostRequest`
+pub struct HostAdapter {
+    complete: Box<Completion>,
+}
+
+impl HostAdapter {
+    pub fn new<F>(complete: F) -> Self
+    where
+        F: Fn(&HostRequest) -> Result<String, String> + Send + Sync + 'static,
+    {
+        Self {
+            complete: Box::new(complete),
+        }
+    }
+}
+
+impl LlmBackend for HostAdapter {
+    fn generate(&self, prompt: &str) -> Result<String, String> {
+        (self.complete)(&HostRequest {
+            prompt: prompt.to_string(),
+            logit_bias: HashMap::new(),
+            banned_strings: Vec::new(),
+        })
+    }
+
+    fn generate_with_constraints(
+        &self,
+        prompt: &str,
+        config: &SterilizationConfig,
+    ) -> Result<String, String> {
+        let prompt = if config.prompt_fencing {
+            format!("{}\n{}", prompt, config.generate_prompt_suffix())
+        } else {
+            prompt.to_string()
+        };
+        (self.complete)(&HostRequest {
+            prompt,
+            logit_bias: config.logit_bias.get_bias_map().clone(),
+            banned_strings: config.logit_bias.banned_strings.clone(),
+        })
+    }
+}
+
+/// Deterministic backend that needs no model
+///
+/// A plan is one Python module named after the requirement. Code is the
+/// spec's interface with bodies returning their arguments. A repair drops
+/// the lines carrying a placeholder marker.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalStubBackend;
+
+/// Markers a stub repair removes lines for
+const MARKERS: &[&str] = &[
+    "TODO",
+    "FIXME",
+    "XXX",
+    "HACK",
+    "todo!(",
+    "unimplemented!(",
+    "NotImplementedError",
+    "NotImplemented",
+    "omitted for brevity",
+    "rest of code",
+    "left as an exercise",
+    "implementation omitted",
+];
+
+impl LlmBackend for LocalStubBackend {
+    fn generate(&self, prompt: &str) -> Result<String, String> {
+        if let Some(requirement) = fenced(prompt, "requirement") {
+            let plan = vec![plan_node(requirement)];
+            return Ok(serde_json::to_string_pretty(&plan).expect("nodes serialize"));
+        }
+        if let Some(spec) = fenced(prompt, "spec") {
+            let node: DependencyNode =
+                serde_json::from_str(spec).map_err(|e| format!("Unreadable spec: {}", e))?;
+            return Ok(render(&node));
+        }
+        let Some(open) = prompt.find("```") else {
+            return Err("Prompt holds no requirement, spec or code".to_string());
+        };
+        let code = extract_code(&prompt[open..]);
+        Ok(code
+            .lines()
+            .filter(|line| !MARKERS.iter().any(|marker| line.contains(marker)))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn generate_with_constraints(
+        &self,
+        prompt: &str,
+        _config: &SterilizationConfig,
+    ) -> Result<String, String> {
+        // The stub never writes a banned string but the ones it repairs
+        self.generate(prompt)
+    }
+}
+
+/// Snake-case identifier made of the first words of `text`
+fn identifier(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(4)
+        .map(str::to_ascii_lowercase)
+        .collect();
+    match words.first() {
+        None => "module".to_string(),
+        Some(first) if first.starts_with(|c: char| c.is_ascii_digit()) => {
+            format!("module_{}", words.join("_"))
+        }
+        Some(_) => words.join("_"),
+    }
+}
+
+fn plan_node(requirement: &str) -> DependencyNode {
+    let name = identifier(requirement);
+    DependencyNode {
+        id: name.clone(),
+        file_path: format!("{}.py", name),
+        module_type: ModuleType::Python,
+        public_interface: InterfaceSpec {
+            classes: Vec::new(),
+            functions: vec![FunctionSignature {
+                name: name.clone(),
+                parameters: vec![Parameter {
+                    name: "request".to_string(),
+                    param_type: Some("dict".to_string()),
+                    default: None,
+                }],
+                return_type: Some("dict".to_string()),
+                docstring: Some(requirement.split_whitespace().collect::<Vec<_>>().join(" ")),
+            }],
+            constants: Vec::new(),
+        },
+        dependencies: Vec::new(),
+        test_plan: None,
+    }
+}
+
+/// Source for `node`'s classes and functions; constants are not rendered
+fn render(node: &DependencyNode) -> String {
+    let interface = &node.public_interface;
+    let mut items = Vec::new();
+    match node.module_type {
+        ModuleType::Rust => {
+            items.extend(interface.classes.iter().map(rust_class));
+            items.extend(interface.functions.iter().map(|f| rust_fn(f, "", false)));
+        }
+        ModuleType::JavaScript | ModuleType::TypeScript => {
+            items.extend(interface.classes.iter().map(js_class));
+            items.extend(interface.functions.iter().map(|f| js_fn(f, "export function ")));
+        }
+        _ => {
+            items.extend(interface.classes.iter().map(python_class));
+            items.extend(interface.functions.iter().map(|f| python_fn(f, "", false)));
+        }
+    }
+    let mut code = items.join("\n\n");
+    code.push('\n');
+    code
+}
+
+fn names(parameters: &[Parameter]) -> Vec<&str> {
+    parameters.iter().map(|p| p.name.as_str()).collect()
+}
+
+fn python_fn(f: &FunctionSignature, indent: &str, method: bool) -> String {
+    let mut params: Vec<String> = method.then(|| "self".to_string()).into_iter().collect();
+    params.extend(f.parameters.iter().map(|p| {
+        let mut param = p.name.clone();
+        if let Some(ty) = &p.param_type {
+            param.push_str(&format!(": {}", ty));
+        }
+        if let Some(default) = &p.default {
+            param.push_str(&format!(" = {}", default));
+        }
+        param
+    }));
+    let returns = f.return_type.as_ref().map_or(String::new(), |ty| format!(" -> {}", ty));
+    let mut code = format!("{}def {}({}){}:\n", indent, f.name, params.join(", "), returns);
+    if let Some(doc) = &f.docstring {
+        code.push_str(&format!("{}    \"\"\"{}\"\"\"\n", indent, doc));
+    }
+    code.push_str(&format!(
+        "{}    return {{\"function\": \"{}\", \"arguments\": [{}]}}",
+        indent,
+        f.name,
+        names(&f.parameters).join(", ")
+    ));
+    code
+}
+
+fn python_class(class: &ClassSignature) -> String {
+    let mut code = format!("class {}:\n", class.name);
+    if let Some(doc) = &class.docstring {
+        code.push_str(&format!("    \"\"\"{}\"\"\"\n", doc));
+    }
+    if class.methods.is_empty() {
+        code.push_str("    name = \"");
+        code.push_str(&class.name);
+        code.push('"');
+    } else {
+        let methods: Vec<String> = class.methods.iter().map(|m| python_fn(m, "    ", true)).collect();
+        code.push_str(&methods.join("\n\n"));
+    }
+    code
+}
+
+fn rust_fn(f: &FunctionSignature, indent: &str, method: bool) -> String {
+    let mut params: Vec<String> = method.then(|| "&self".to_string()).into_iter().collect();
+    params.extend(f.parameters.iter().map(|p| {
+        format!("{}: {}", p.name, p.param_type.as_deref().unwrap_or("&str"))
+    }));
+    let mut code = String::new();
+    if let Some(doc) = &f.docstring {
+        code.push_str(&format!("{}/// {}\n", indent, doc));
+    }
+    let returns = f.return_type.as_ref().map_or(String::new(), |ty| format!(" -> {}", ty));
+    code.push_str(&format!("{}pub fn {}({}){} {{\n", indent, f.name, params.join(", "), returns));
+    if !f.parameters.is_empty() {
+        code.push_str(&format!("{}    let _ = ({},);\n", indent, names(&f.parameters).join(", ")));
+    }
+    // Unit for functions returning nothing, so no body is empty
+    code.push_str(&format!("{}    Default::default()\n", indent));
+    code.push_str(&format!("{}}}", indent));
+    code
+}
+
+fn rust_class(class: &ClassSignature) -> String {
+    let mut code = String::new();
+    if let Some(doc) = &class.docstring {
+        code.push_str(&format!("/// {}\n", doc));
+    }
+    code.push_str(&format!("#[derive(Debug, Default)]\npub struct {};", class.name));
+    if !class.methods.is_empty() {
+        let methods: Vec<String> = class.methods.iter().map(|m| rust_fn(m, "    ", true)).collect();
+        code.push_str(&format!("\n\nimpl {} {{\n{}\n}}", class.name, methods.join("\n\n")));
+    }
+    code
+}
+
+fn js_fn(f: &FunctionSignature, prefix: &str) -> String {
+    let params = names(&f.parameters).join(", ");
+    format!(
+        "{}{}({}) {{\n    return {{ function: \"{}\", arguments: [{}] }};\n}}",
+        prefix, f.name, params, f.name, params
+    )
+}
+
+fn js_class(class: &ClassSignature) -> String {
+    let methods: Vec<String> = class
+        .methods
+        .iter()
+        .map(|m| js_fn(m, "").lines().map(|line| format!("    {}", line)).collect::<Vec<_>>().join("\n"))
+        .collect();
+    format!("export class {} {{\n{}\n}}", class.name, methods.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_stub_plans_builds_and_repairs_deterministically() {
+        let stub = LocalStubBackend;
+        let plan = stub.generate(&plan_prompt("Parse supplier invoices")).unwrap();
+        let nodes: Vec<DependencyNode> = serde_json::from_str(&extract_code(&plan)).unwrap();
+        assert_eq!(nodes[0].file_path, "parse_supplier_invoices.py");
+
+        let code = stub.generate(&build_prompt(&nodes[0], &[])).unwrap();
+        assert!(code.starts_with("def parse_supplier_invoices(request: dict) -> dict:\n"));
+        assert_eq!(stub.generate(&build_prompt(&nodes[0], &[])).unwrap(), code);
+
+        let broken = "```python\ndef f(x):\n    # TODO: validate\n    return x\n```";
+        assert_eq!(stub.generate(broken).unwrap(), "def f(x):\n    return x");
+    }
+
+    #[test]
+    fn test_host_adapter_receives_the_constraints() {
+        let seen = Arc::new(Mutex::new(None));
+        let recorder = Arc::clone(&seen);
+        let host = HostAdapter::new(move |request: &HostRequest| {
+            *recorder.lock().unwrap() = Some(request.clone());
+            Ok("```rust\nfn answer() -> u32 {\n    42\n}\n```".to_string())
+        });
+
+        let config = SterilizationConfig::default();
+        let reply = host.generate_with_constraints("Write answer()", &config).unwrap();
+        assert_eq!(extract_code(&reply), "fn answer() -> u32 {\n    42\n}");
+        let request = seen.lock().unwrap().clone().unwrap();
+        assert!(request.prompt.ends_with(&config.generate_prompt_suffix()));
+        assert!(request.banned_strings.contains(&"TODO".to_string()));
+
+        host.generate("Write answer()").unwrap();
+        assert!(seen.lock().unwrap().as_ref().unwrap().banned_strings.is_empty());
+    }
+}
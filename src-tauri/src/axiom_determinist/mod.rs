@@ -4,16 +4,28 @@
 
 pub mod dag;
 pub mod constraints;
+pub mod grammar;
+pub mod policy;
 pub mod sandbox;
+#[cfg(feature = "python-ast")]
+pub mod python_ast;
+pub mod rust_ast;
+#[cfg(feature = "tree-sitter")]
+pub mod tree_sitter_ast;
+pub mod wasm_exec;
 pub mod reflexion;
+pub mod llm;
 pub mod agents;
 pub mod orchestrator;
 
 pub use dag::DependencyGraph;
 pub use constraints::{LogitBias, GrammarConstraint, SterilizationConfig};
+pub use grammar::ConstraintEngine;
+pub use policy::SterilizationPolicy;
 pub use sandbox::{HermeticSandbox, ValidationResult};
 pub use reflexion::{ReflexionLoop, RepairContext};
 pub use agents::{AgentRole, AgentState};
+pub use llm::{HostAdapter, LlmBackend, LocalStubBackend};
 pub use orchestrator::Orchestrator;
 
 /// Core sterilization policy: Zero tolerance for placeholders
@@ -21,15 +33,3 @@ pub const STERILIZATION_PROTOCOL: &str = "###_STERILIZATION_PROTOCOL_v1_###";
 
 /// Maximum retry attempts for reflexion loop
 pub const MAX_RETRIES: u32 = 10;
-
-/// Token ban list for logit bias
-pub const BANNED_TOKENS: &[&str] = &[
-    "TODO", "FIXME", "XXX", "HACK",
-    "todo", "fixme", "xxx", "hack",
-    " TODO", " FIXME", " XXX", " HACK",
-    "NotImplementedError", "NotImplemented",
-    "pass", "return null", "return None",
-    "omitted for brevity", "rest of code",
-    "left as an exercise", "implementation omitted",
-];
-
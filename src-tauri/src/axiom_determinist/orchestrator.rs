@@ -1,11 +1,13 @@
 // Orchestrator: Manages the complete AxiomDeterminist workflow
 
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use super::{
-    dag::DependencyGraph,
     agents::*,
+    llm::{LlmBackend, LocalStubBackend},
+    policy::SterilizationPolicy,
     reflexion::ReflexionLoop,
-    sandbox::ValidationResult,
+    wasm_exec::WasmCompiler,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,23 +34,42 @@ pub struct Orchestrator {
     builder: BuilderAgent,
     auditor: AuditorAgent,
     reflexion_loop: ReflexionLoop,
+    backend: Arc<dyn LlmBackend>,
 }
 
 impl Orchestrator {
+    /// Orchestrator on the deterministic local stub backend
     pub fn new(max_retries: u32) -> Self {
+        Self::with_backend(max_retries, Arc::new(LocalStubBackend))
+    }
+
+    /// Orchestrator whose agents all prompt `backend`
+    pub fn with_backend(max_retries: u32, backend: Arc<dyn LlmBackend>) -> Self {
         Self {
-            architect: ArchitectAgent::new(),
+            architect: ArchitectAgent::new(Arc::clone(&backend)),
             librarian: LibrarianAgent::new(),
-            builder: BuilderAgent::new(),
+            builder: BuilderAgent::new(Arc::clone(&backend)),
             auditor: AuditorAgent::new(),
             reflexion_loop: ReflexionLoop::new(max_retries),
+            backend,
         }
     }
 
+    /// Run each module's TestPlan on code compiled by `compiler`
+    pub fn set_compiler(&mut self, compiler: Arc<dyn WasmCompiler>) {
+        self.auditor.set_compiler(compiler);
+    }
+
+    /// Ban, report and prompt against `policy` instead of the default
+    pub fn set_policy(&mut self, policy: SterilizationPolicy) {
+        self.builder.set_policy(policy.clone());
+        self.auditor.set_policy(policy);
+    }
+
     /// Execute complete AxiomDeterminist workflow
     pub fn execute(&mut self, user_requirement: &str) -> Result<OrchestrationResult, String> {
         // Step 1: Architect generates DAG
-        let mut dag = self.architect.generate_dag(user_requirement)?;
+        let dag = self.architect.generate_dag(user_requirement)?;
         
         // Step 2: Topological sort for execution order
         let execution_order = dag.topological_sort()?;
@@ -79,11 +100,9 @@ impl Orchestrator {
 
             let final_code = match self.reflexion_loop.execute(
                 initial_code,
-                |code| self.auditor.validate(code, language),
-                |code, validation| {
-                    // Generate repair prompt and call LLM
-                    self.reflexion_loop.generate_repair_prompt(code, validation)
-                },
+                |code| self.auditor.validate_with_tests(code, language, node.test_plan.as_ref()),
+                self.backend.as_ref(),
+                self.builder.sterilization_config(),
             ) {
                 Ok(code) => code,
                 Err(e) => {
@@ -95,7 +114,7 @@ impl Orchestrator {
             total_iterations += self.reflexion_loop.get_current_iteration();
 
             // Final validation
-            let final_validation = self.auditor.validate(&final_code, language);
+            let final_validation = self.auditor.validate_with_tests(&final_code, language, node.test_plan.as_ref());
             
             generated_files.push(GeneratedFile {
                 path: node.file_path.clone(),
@@ -135,3 +154,37 @@ impl Orchestrator {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axiom_determinist::llm::{HostAdapter, HostRequest};
+
+    #[test]
+    fn test_the_stub_backend_produces_validated_code() {
+        let result = Orchestrator::new(3).execute("Parse supplier invoices").unwrap();
+        assert!(result.success, "{:?}", result.errors);
+        assert_eq!(result.generated_files.len(), 1);
+        let file = &result.generated_files[0];
+        assert_eq!(file.path, "parse_supplier_invoices.py");
+        assert!(file.content.starts_with("def parse_supplier_invoices("));
+    }
+
+    #[test]
+    fn test_repairs_come_from_the_backend() {
+        let host = HostAdapter::new(|request: &HostRequest| {
+            if request.prompt.contains("```requirement") {
+                LocalStubBackend.generate(&request.prompt)
+            } else if request.prompt.contains("```spec") {
+                Ok("def total(request: dict) -> dict:\n    # TODO: sum the lines\n    return request".to_string())
+            } else {
+                Ok("```python\ndef total(request: dict) -> dict:\n    return {\"total\": sum(request[\"lines\"])}\n```".to_string())
+            }
+        });
+        let mut orchestrator = Orchestrator::with_backend(3, Arc::new(host));
+        let result = orchestrator.execute("Total").unwrap();
+        assert!(result.success, "{:?}", result.errors);
+        assert_eq!(result.total_iterations, 2);
+        assert!(result.generated_files[0].content.contains("sum(request[\"lines\"])"));
+    }
+}
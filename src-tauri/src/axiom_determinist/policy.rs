@@ -0,0 +1,356 @@
+// Tier 2: Sterilization Policy
+// The strings generated code may not contain, read from one TOON document
+// by the logit bias, the sandbox and the repair prompt
+//
+// A policy lists banned patterns with a severity and the stage that
+// enforces them, per-language overrides of those, and exceptions allowing
+// a pattern inside the string literals or comments of a language. The
+// default is sterilization_policy.toon, which documents the format.
+// Exceptions only apply to the sandbox: the logit bias cannot tell a
+// string literal from code while the model writes.
+
+use super::sandbox::ErrorSeverity;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::Path;
+use toon_rs::{ToonDocument, ToonParser, ToonValue};
+
+/// The policy built into the crate
+pub const DEFAULT_POLICY: &str = include_str!("sterilization_policy.toon");
+
+/// Which checks enforce a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicyStage {
+    /// Banned by the logit bias while decoding
+    Decode,
+    /// Reported by the sandbox
+    Validate,
+    All,
+}
+
+impl PolicyStage {
+    fn includes(self, stage: PolicyStage) -> bool {
+        self == PolicyStage::All || self == stage
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BannedPattern {
+    pub pattern: String,
+    pub severity: ErrorSeverity,
+    pub stage: PolicyStage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageOverride {
+    pub language: String,
+    pub pattern: String,
+    /// None drops the pattern for the language
+    pub severity: Option<ErrorSeverity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExceptionContext {
+    String,
+    Comment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyException {
+    pub pattern: String,
+    /// None for every language
+    pub language: Option<String>,
+    pub context: ExceptionContext,
+}
+
+/// A banned pattern found by `SterilizationPolicy::find`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyMatch {
+    /// 1-based
+    pub line: u32,
+    pub pattern: String,
+    pub severity: ErrorSeverity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SterilizationPolicy {
+    pub patterns: Vec<BannedPattern>,
+    pub overrides: Vec<LanguageOverride>,
+    pub exceptions: Vec<PolicyException>,
+}
+
+impl Default for SterilizationPolicy {
+    fn default() -> Self {
+        Self::from_toon(DEFAULT_POLICY).expect("the built-in policy is valid")
+    }
+}
+
+fn severity(name: &str) -> Result<ErrorSeverity, String> {
+    match name {
+        "fatal" => Ok(ErrorSeverity::Fatal),
+        "error" => Ok(ErrorSeverity::Error),
+        "warning" => Ok(ErrorSeverity::Warning),
+        _ => Err(format!("Unknown severity '{}'", name)),
+    }
+}
+
+/// Rows of the section `key` with the cells of `columns`, in that order;
+/// no rows if the section is absent
+fn section(document: &ToonDocument, key: &str, columns: &[&str]) -> Result<Vec<Vec<String>>, String> {
+    let (schema, data) = match document.get(key) {
+        Some(ToonValue::Schema { schema, data, .. }) => (schema, data),
+        Some(_) => return Err(format!("'{}' must be a section", key)),
+        None => return Ok(Vec::new()),
+    };
+    let at: Vec<usize> = columns
+        .iter()
+        .map(|column| {
+            schema
+                .iter()
+                .position(|c| c == column)
+                .ok_or(format!("'{}' needs a '{}' column", key, column))
+        })
+        .collect::<Result<_, String>>()?;
+    data.iter()
+        .map(|row| {
+            at.iter()
+                .zip(columns)
+                .map(|(&i, column)| match row.get(i).map(|cell| cell.trim()) {
+                    Some(cell) if !cell.is_empty() => Ok(cell.to_string()),
+                    _ => Err(format!("Every row of '{}' needs a {}", key, column)),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+impl SterilizationPolicy {
+    /// Load from a TOON document with a `patterns [N]{pattern,severity,stage}`
+    /// section and optional `overrides [N]{language,pattern,severity}` and
+    /// `exceptions [N]{pattern,language,context}` sections
+    pub fn from_toon(toon: &str) -> Result<Self, String> {
+        let document = ToonParser::try_new(toon)
+            .and_then(|parser| parser.parse_document())
+            .map_err(|e| e.render(toon))?;
+        if document.get("patterns").is_none() {
+            return Err("Missing 'patterns [N]{pattern,severity,stage}' section".to_string());
+        }
+
+        let patterns = section(&document, "patterns", &["pattern", "severity", "stage"])?
+            .into_iter()
+            .map(|row| {
+                let stage = match row[2].as_str() {
+                    "decode" => PolicyStage::Decode,
+                    "validate" => PolicyStage::Validate,
+                    "all" => PolicyStage::All,
+                    stage => return Err(format!("Unknown stage '{}' for '{}'", stage, row[0])),
+                };
+                Ok(BannedPattern { pattern: row[0].clone(), severity: severity(&row[1])?, stage })
+            })
+            .collect::<Result<_, String>>()?;
+        let overrides = section(&document, "overrides", &["language", "pattern", "severity"])?
+            .into_iter()
+            .map(|row| {
+                let severity = if row[2] == "off" { None } else { Some(severity(&row[2])?) };
+                Ok(LanguageOverride { language: row[0].clone(), pattern: row[1].clone(), severity })
+            })
+            .collect::<Result<_, String>>()?;
+        let exceptions = section(&document, "exceptions", &["pattern", "language", "context"])?
+            .into_iter()
+            .map(|row| {
+                let context = match row[2].as_str() {
+                    "string" => ExceptionContext::String,
+                    "comment" => ExceptionContext::Comment,
+                    context => return Err(format!("Unknown context '{}' for '{}'", context, row[0])),
+                };
+                let language = (row[1] != "*").then(|| row[1].clone());
+                Ok(PolicyException { pattern: row[0].clone(), language, context })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self { patterns, overrides, exceptions })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let toon = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Self::from_toon(&toon)
+    }
+
+    /// The patterns in force for `language`, or for no language in
+    /// particular
+    pub fn patterns_for(&self, language: Option<&str>) -> Vec<BannedPattern> {
+        let mut patterns = self.patterns.clone();
+        for rule in self.overrides.iter().filter(|rule| Some(rule.language.as_str()) == language) {
+            match (patterns.iter().position(|p| p.pattern == rule.pattern), &rule.severity) {
+                (Some(i), None) => {
+                    patterns.remove(i);
+                }
+                (Some(i), Some(severity)) => patterns[i].severity = severity.clone(),
+                (None, Some(severity)) => patterns.push(BannedPattern {
+                    pattern: rule.pattern.clone(),
+                    severity: severity.clone(),
+                    stage: PolicyStage::All,
+                }),
+                (None, None) => {}
+            }
+        }
+        patterns
+    }
+
+    /// The strings the logit bias bans
+    pub fn decode_bans(&self, language: Option<&str>) -> Vec<String> {
+        self.patterns_for(language)
+            .into_iter()
+            .filter(|p| p.stage.includes(PolicyStage::Decode))
+            .map(|p| p.pattern)
+            .collect()
+    }
+
+    /// The patterns the sandbox reports in `code`, at most once per line
+    /// each, ordered by line
+    pub fn find(&self, code: &str, language: &str) -> Vec<PolicyMatch> {
+        let patterns: Vec<BannedPattern> = self
+            .patterns_for(Some(language))
+            .into_iter()
+            .filter(|p| p.stage.includes(PolicyStage::Validate))
+            .collect();
+        let regions = literal_regions(code, language);
+
+        let mut found = BTreeSet::new();
+        for (index, pattern) in patterns.iter().enumerate() {
+            let allowed: Vec<ExceptionContext> = self
+                .exceptions
+                .iter()
+                .filter(|e| e.pattern == pattern.pattern && e.language.as_deref().is_none_or(|l| l == language))
+                .map(|e| e.context)
+                .collect();
+            for (offset, _) in code.match_indices(pattern.pattern.as_str()) {
+                let end = offset + pattern.pattern.len();
+                let excepted = regions
+                    .iter()
+                    .any(|(range, context)| allowed.contains(context) && range.start <= offset && end <= range.end);
+                if !excepted {
+                    let line = code[..offset].matches('\n').count() as u32 + 1;
+                    found.insert((line, index));
+                }
+            }
+        }
+        found
+            .into_iter()
+            .map(|(line, index)| PolicyMatch {
+                line,
+                pattern: patterns[index].pattern.clone(),
+                severity: patterns[index].severity.clone(),
+            })
+            .collect()
+    }
+
+    /// One line naming the patterns banned in `language`, for prompts
+    pub fn summary(&self, language: &str) -> String {
+        let patterns: Vec<String> = self
+            .patterns_for(Some(language))
+            .into_iter()
+            .map(|p| format!("'{}'", p.pattern))
+            .collect();
+        format!("The code must not contain: {}.", patterns.join(", "))
+    }
+}
+
+/// Byte ranges of the string literals and comments in `code`, found by a
+/// lexical scan; none for a language it does not know
+fn literal_regions(code: &str, language: &str) -> Vec<(Range<usize>, ExceptionContext)> {
+    let (line_comment, block_comments, quotes): (&str, bool, &[&str]) = match language {
+        "python" => ("#", false, &["\"\"\"", "'''", "\"", "'"]),
+        "rust" => ("//", true, &["\""]),
+        "javascript" | "typescript" => ("//", true, &["\"", "'", "`"]),
+        _ => return Vec::new(),
+    };
+
+    let mut regions = Vec::new();
+    let mut at = 0;
+    while at < code.len() {
+        let rest = &code[at..];
+        let (context, end) = if rest.starts_with(line_comment) {
+            (ExceptionContext::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if block_comments && rest.starts_with("/*") {
+            (ExceptionContext::Comment, rest[2..].find("*/").map_or(rest.len(), |end| end + 4))
+        } else if let Some(quote) = quotes.iter().find(|quote| rest.starts_with(**quote)) {
+            // Find the closing quote, skipping escaped characters
+            let mut end = rest.len();
+            let mut chars = rest.char_indices().skip(quote.len());
+            while let Some((i, c)) = chars.next() {
+                if c == '\\' {
+                    chars.next();
+                } else if rest[i..].starts_with(quote) {
+                    end = i + quote.len();
+                    break;
+                } else if c == '\n' && quote.len() == 1 && *quote != "`" && language != "rust" {
+                    end = i;
+                    break;
+                }
+            }
+            (ExceptionContext::String, end)
+        } else {
+            at += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+        regions.push((at..at + end, context));
+        at += end.max(1);
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_feeds_decode_and_validation() {
+        let policy = SterilizationPolicy::default();
+        let bans = policy.decode_bans(None);
+        assert!(bans.contains(&"pass".to_string()) && bans.contains(&"TODO".to_string()));
+        let found = policy.find("password = read()  # TODO\nx = 1 # FIXME: rest of code\n", "python");
+        let found: Vec<(u32, &str)> = found.iter().map(|m| (m.line, m.pattern.as_str())).collect();
+        assert_eq!(found, vec![(1, "TODO"), (2, "FIXME"), (2, "rest of code")]);
+        assert!(policy.summary("rust").contains("'NotImplementedError'"));
+    }
+
+    #[test]
+    fn test_overrides_and_exceptions() {
+        let toon = r#"
+patterns [2]{pattern,severity,stage}
+pass,fatal,all
+HACK,error,validate
+overrides [3]{language,pattern,severity}
+rust,pass,off
+rust,HACK,warning
+rust,unreachable!(,error
+exceptions [1]{pattern,language,context}
+pass,python,string
+"#;
+        let policy = SterilizationPolicy::from_toon(toon).unwrap();
+        assert_eq!(policy.decode_bans(None), vec!["pass".to_string()]);
+        assert_eq!(policy.decode_bans(Some("rust")), vec!["unreachable!(".to_string()]);
+
+        let python = "label = 'pass'\nhint = \"\"\"\npass\n\"\"\"\nif ok:\n    pass  # pass\n";
+        let found = policy.find(python, "python");
+        assert_eq!(found.iter().map(|m| m.line).collect::<Vec<_>>(), vec![6]);
+
+        let rust = "// HACK\nlet pass = 1; unreachable!(\"pass\")\n";
+        let found: Vec<(u32, String, ErrorSeverity)> =
+            policy.find(rust, "rust").into_iter().map(|m| (m.line, m.pattern, m.severity)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "HACK".to_string(), ErrorSeverity::Warning),
+                (2, "unreachable!(".to_string(), ErrorSeverity::Error),
+            ]
+        );
+
+        assert!(SterilizationPolicy::from_toon("patterns [1]{pattern,severity,stage}\nTODO,fatal,never\n").is_err());
+        assert!(SterilizationPolicy::from_toon("patterns [1]{pattern,stage}\nTODO,all\n").is_err());
+        assert!(SterilizationPolicy::from_toon("version = 1\n").is_err());
+        assert!(SterilizationPolicy::from_toon(r#"{"patterns": []}"#).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+// Python validation by parsing
+// HermeticSandbox::validate_python parses the code with rustpython-parser,
+// in process, and walks the statements of every block
+//
+// Reported, with the line and column of the offending statement:
+// - syntax errors (SyntaxError), after which nothing else is checked
+// - functions and classes whose body, past any docstring, is only `pass`
+//   or only `...` (EmptyBlock)
+// - `raise NotImplementedError`, with or without arguments
+//   (SterilizationViolation)
+// - statements after a return, raise, continue or break in the same block
+//   (LintError)
+
+use super::sandbox::{ErrorSeverity, ErrorType, ValidationError};
+use rustpython_parser::ast::{self, Ranged};
+use rustpython_parser::Mode;
+
+pub(crate) fn validate(code: &str) -> Vec<ValidationError> {
+    let body = match rustpython_parser::parse(code, Mode::Module, "<generated>") {
+        Ok(ast::Mod::Module(module)) => module.body,
+        Ok(_) => Vec::new(),
+        Err(e) => {
+            return vec![error(
+                code,
+                usize::from(e.offset),
+                ErrorSeverity::Error,
+                ErrorType::SyntaxError,
+                e.error.to_string(),
+            )];
+        }
+    };
+
+    let mut errors = Vec::new();
+    check_block(code, &body, &mut errors);
+    errors
+}
+
+/// 1-based line and column of byte `offset` in `code`
+fn location(code: &str, offset: usize) -> (u32, u32) {
+    let before = &code[..offset.min(code.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line as u32, column as u32)
+}
+
+fn error(code: &str, offset: usize, severity: ErrorSeverity, error_type: ErrorType, message: String) -> ValidationError {
+    let (line, column) = location(code, offset);
+    ValidationError {
+        severity,
+        message,
+        file: None,
+        line: Some(line),
+        column: Some(column),
+        error_type,
+    }
+}
+
+fn start(stmt: &ast::Stmt) -> usize {
+    usize::from(stmt.range().start())
+}
+
+/// The blocks nested directly in `stmt`
+fn blocks(stmt: &ast::Stmt) -> Vec<&[ast::Stmt]> {
+    match stmt {
+        ast::Stmt::FunctionDef(ast::StmtFunctionDef { body, .. })
+        | ast::Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { body, .. })
+        | ast::Stmt::ClassDef(ast::StmtClassDef { body, .. })
+        | ast::Stmt::With(ast::StmtWith { body, .. })
+        | ast::Stmt::AsyncWith(ast::StmtAsyncWith { body, .. }) => vec![body],
+        ast::Stmt::If(ast::StmtIf { body, orelse, .. })
+        | ast::Stmt::While(ast::StmtWhile { body, orelse, .. })
+        | ast::Stmt::For(ast::StmtFor { body, orelse, .. })
+        | ast::Stmt::AsyncFor(ast::StmtAsyncFor { body, orelse, .. }) => vec![body, orelse],
+        ast::Stmt::Try(ast::StmtTry { body, handlers, orelse, finalbody, .. })
+        | ast::Stmt::TryStar(ast::StmtTryStar { body, handlers, orelse, finalbody, .. }) => {
+            let mut blocks: Vec<&[ast::Stmt]> = vec![body];
+            blocks.extend(handlers.iter().map(|handler| match handler {
+                ast::ExceptHandler::ExceptHandler(handler) => handler.body.as_slice(),
+            }));
+            blocks.push(orelse);
+            blocks.push(finalbody);
+            blocks
+        }
+        ast::Stmt::Match(ast::StmtMatch { cases, .. }) => cases.iter().map(|case| case.body.as_slice()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `body` without its docstring
+fn past_docstring(body: &[ast::Stmt]) -> &[ast::Stmt] {
+    match body.first() {
+        Some(ast::Stmt::Expr(ast::StmtExpr { value, .. }))
+            if matches!(value.as_ref(), ast::Expr::Constant(ast::ExprConstant { value: ast::Constant::Str(_), .. })) =>
+        {
+            &body[1..]
+        }
+        _ => body,
+    }
+}
+
+/// What a placeholder body consists of, if `body` is one
+fn placeholder_body(body: &[ast::Stmt]) -> Option<&'static str> {
+    match past_docstring(body) {
+        [ast::Stmt::Pass(_)] => Some("'pass'"),
+        [ast::Stmt::Expr(ast::StmtExpr { value, .. })]
+            if matches!(value.as_ref(), ast::Expr::Constant(ast::ExprConstant { value: ast::Constant::Ellipsis, .. })) =>
+        {
+            Some("'...'")
+        }
+        _ => None,
+    }
+}
+
+fn raises_not_implemented(stmt: &ast::Stmt) -> bool {
+    let ast::Stmt::Raise(ast::StmtRaise { exc: Some(exc), .. }) = stmt else {
+        return false;
+    };
+    let exc = match exc.as_ref() {
+        ast::Expr::Call(ast::ExprCall { func, .. }) => func.as_ref(),
+        exc => exc,
+    };
+    matches!(exc, ast::Expr::Name(ast::ExprName { id, .. }) if id.as_str() == "NotImplementedError")
+}
+
+fn check_block(code: &str, block: &[ast::Stmt], errors: &mut Vec<ValidationError>) {
+    for (i, stmt) in block.iter().enumerate() {
+        let definition = match stmt {
+            ast::Stmt::FunctionDef(ast::StmtFunctionDef { name, body, .. })
+            | ast::Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { name, body, .. }) => Some(("Function", name, body)),
+            ast::Stmt::ClassDef(ast::StmtClassDef { name, body, .. }) => Some(("Class", name, body)),
+            _ => None,
+        };
+        if let Some((kind, name, body)) = definition {
+            if let Some(placeholder) = placeholder_body(body) {
+                errors.push(error(
+                    code,
+                    start(stmt),
+                    ErrorSeverity::Fatal,
+                    ErrorType::EmptyBlock,
+                    format!("{} {} contains only {}", kind, name.as_str(), placeholder),
+                ));
+            }
+        }
+
+        if raises_not_implemented(stmt) {
+            errors.push(error(
+                code,
+                start(stmt),
+                ErrorSeverity::Fatal,
+                ErrorType::SterilizationViolation,
+                "Raises NotImplementedError".to_string(),
+            ));
+        }
+
+        let exits = matches!(
+            stmt,
+            ast::Stmt::Return(_) | ast::Stmt::Raise(_) | ast::Stmt::Continue(_) | ast::Stmt::Break(_)
+        );
+        if let Some(next) = block.get(i + 1).filter(|_| exits) {
+            errors.push(error(
+                code,
+                start(next),
+                ErrorSeverity::Error,
+                ErrorType::LintError,
+                "Unreachable code".to_string(),
+            ));
+        }
+
+        for nested in blocks(stmt) {
+            check_block(code, nested, errors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(code: &str) -> Vec<(String, Option<u32>, Option<u32>)> {
+        validate(code)
+            .into_iter()
+            .map(|e| (format!("{:?}", e.error_type), e.line, e.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_placeholder_bodies_and_dead_code_are_located() {
+        let code = r#"# pass is fine in a comment
+def total(lines):
+    return sum(lines)
+
+def average(lines):
+    """Mean of the lines."""
+    pass
+
+class Ledger:
+    def post(self, amount):
+        ...
+
+    def close(self):
+        if self.open:
+            raise NotImplementedError("closing")
+        return self.balance
+        print("closed")
+"#;
+        assert_eq!(
+            found(code),
+            vec![
+                ("EmptyBlock".to_string(), Some(5), Some(1)),
+                ("EmptyBlock".to_string(), Some(10), Some(5)),
+                ("SterilizationViolation".to_string(), Some(15), Some(13)),
+                ("LintError".to_string(), Some(17), Some(9)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors_have_a_location() {
+        let errors = validate("def total(lines):\n    return sum(lines\n\nx = 1\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].error_type, ErrorType::SyntaxError));
+        assert!(errors[0].line.is_some() && errors[0].column.is_some());
+        assert!(validate("def total(lines):\n    return sum(lines)\n").is_empty());
+    }
+}
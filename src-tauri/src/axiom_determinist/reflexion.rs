@@ -1,7 +1,10 @@
 // Tier 4: Compile-Fix Loop - Iterative Self-Repair
 
 use serde::{Deserialize, Serialize};
-use super::sandbox::{ValidationResult, ValidationError};
+use super::constraints::SterilizationConfig;
+use super::llm::{self, LlmBackend};
+use super::policy::SterilizationPolicy;
+use super::sandbox::ValidationResult;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReflexionLoop {
@@ -30,17 +33,22 @@ impl ReflexionLoop {
     }
 
     /// Execute reflexion loop: generate -> validate -> reflect -> repair
-    pub fn execute<F, G>(
+    ///
+    /// Each repair sends `generate_repair_prompt` to `backend` under
+    /// `config` and takes the code in its reply. Iterations are counted
+    /// from zero on every call.
+    pub fn execute<F>(
         &mut self,
         initial_code: String,
-        validate_fn: F,
-        repair_fn: G,
+        mut validate_fn: F,
+        backend: &dyn LlmBackend,
+        config: &SterilizationConfig,
     ) -> Result<String, String>
     where
-        F: Fn(&str) -> ValidationResult,
-        G: Fn(&str, &ValidationResult) -> String,
+        F: FnMut(&str) -> ValidationResult,
     {
         let mut current_code = initial_code;
+        self.current_iteration = 0;
 
         loop {
             self.current_iteration += 1;
@@ -74,7 +82,15 @@ impl ReflexionLoop {
             }
 
             // Reflect on errors and generate repair
-            let repaired_code = repair_fn(&current_code, &validation_result);
+            let prompt = self.generate_repair_prompt(&current_code, &validation_result, &config.policy);
+            let reply = backend.generate_with_constraints(&prompt, config);
+            let repaired_code = match reply {
+                Ok(reply) => llm::extract_code(&reply),
+                Err(e) => {
+                    self.repair_history.push(repair_context);
+                    return Err(format!("Repair failed: {}", e));
+                }
+            };
             repair_context.repaired_code = Some(repaired_code.clone());
             self.repair_history.push(repair_context);
 
@@ -110,13 +126,15 @@ impl ReflexionLoop {
         analysis
     }
 
-    /// Generate repair prompt for LLM
+    /// Generate repair prompt for LLM, naming what `policy` bans
     pub fn generate_repair_prompt(
         &self,
         code: &str,
         validation_result: &ValidationResult,
+        policy: &SterilizationPolicy,
     ) -> String {
         let error_summary = self.analyze_errors(validation_result);
+        let language = detect_language(code);
         
         format!(
             r#"
@@ -134,12 +152,14 @@ Error Details:
 You must fix ALL errors. Do not remove comments or TODOs - implement the missing logic.
 Every function must contain complete, executable code.
 Code containing placeholders will trigger a fatal build error.
+{}
 
 Generate the complete, fixed code:
 "#,
-            detect_language(code),
+            language,
             code,
-            error_summary
+            error_summary,
+            policy.summary(language)
         )
     }
 
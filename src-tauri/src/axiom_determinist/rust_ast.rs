@@ -0,0 +1,254 @@
+// Rust validation by parsing
+// HermeticSandbox::validate_rust parses the code with syn and walks the
+// syntax tree, so comments and string literals are never mistaken for code
+//
+// Reported, with the line and column of the offending item:
+// - syntax errors (SyntaxError), after which nothing else is checked
+// - todo!(), unimplemented!() and placeholder panics (SterilizationViolation);
+//   a panic! is a placeholder when it is all a function does or its message
+//   says the code is missing
+// - functions, methods and default trait methods with empty bodies (EmptyBlock)
+// - impls of a trait declared in the same code that leave out an item the
+//   trait requires (CompilationError)
+
+use super::sandbox::{ErrorSeverity, ErrorType, ValidationError};
+use proc_macro2::Span;
+use std::collections::HashMap;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+/// Words in a panic message marking it as a placeholder
+const PLACEHOLDER_WORDS: &[&str] = &["todo", "not implemented", "unimplemented", "placeholder", "fill in"];
+
+pub(crate) fn validate(code: &str) -> Vec<ValidationError> {
+    let file = match syn::parse_file(code) {
+        Ok(file) => file,
+        Err(e) => {
+            return e
+                .into_iter()
+                .map(|e| error(ErrorSeverity::Error, ErrorType::SyntaxError, e.span(), e.to_string()))
+                .collect();
+        }
+    };
+
+    let mut checker = Checker::default();
+    checker.visit_file(&file);
+    checker.check_trait_impls();
+    checker.errors
+}
+
+fn error(severity: ErrorSeverity, error_type: ErrorType, span: Span, message: String) -> ValidationError {
+    let start = span.start();
+    ValidationError {
+        severity,
+        message,
+        file: None,
+        line: Some(start.line as u32),
+        column: Some(start.column as u32 + 1),
+        error_type,
+    }
+}
+
+/// The name of `mac` if it stands in for missing code
+fn placeholder(mac: &syn::Macro) -> Option<String> {
+    let name = mac.path.segments.last()?.ident.to_string();
+    let placeholder = match name.as_str() {
+        "todo" | "unimplemented" => true,
+        "panic" => {
+            let message = mac.tokens.to_string().to_lowercase();
+            PLACEHOLDER_WORDS.iter().any(|word| message.contains(word))
+        }
+        _ => false,
+    };
+    placeholder.then_some(name)
+}
+
+/// Items a trait's impls must provide: those without a default
+fn required_items(item: &syn::ItemTrait) -> Vec<String> {
+    item.items
+        .iter()
+        .filter_map(|item| match item {
+            syn::TraitItem::Fn(f) if f.default.is_none() => Some(f.sig.ident.to_string()),
+            syn::TraitItem::Type(t) if t.default.is_none() => Some(t.ident.to_string()),
+            syn::TraitItem::Const(c) if c.default.is_none() => Some(c.ident.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn impl_item_name(item: &syn::ImplItem) -> Option<String> {
+    match item {
+        syn::ImplItem::Fn(f) => Some(f.sig.ident.to_string()),
+        syn::ImplItem::Type(t) => Some(t.ident.to_string()),
+        syn::ImplItem::Const(c) => Some(c.ident.to_string()),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    errors: Vec<ValidationError>,
+    /// Required items of each trait declared in the code
+    traits: HashMap<String, Vec<String>>,
+    /// Trait name, items provided and span of each trait impl
+    trait_impls: Vec<(String, Vec<String>, Span)>,
+}
+
+impl Checker {
+    fn check_body(&mut self, name: &syn::Ident, block: &syn::Block) {
+        match block.stmts.as_slice() {
+            [] => self.errors.push(error(
+                ErrorSeverity::Fatal,
+                ErrorType::EmptyBlock,
+                name.span(),
+                format!("Function {} has an empty body", name),
+            )),
+            [only] => {
+                let mac = match only {
+                    syn::Stmt::Macro(stmt) => Some(&stmt.mac),
+                    syn::Stmt::Expr(syn::Expr::Macro(expr), _) => Some(&expr.mac),
+                    _ => None,
+                };
+                // A placeholder panic is reported by visit_macro
+                if let Some(mac) = mac.filter(|mac| mac.path.is_ident("panic") && placeholder(mac).is_none()) {
+                    self.errors.push(error(
+                        ErrorSeverity::Fatal,
+                        ErrorType::SterilizationViolation,
+                        mac.span(),
+                        format!("Function {} only panics", name),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_trait_impls(&mut self) {
+        for (name, provided, span) in std::mem::take(&mut self.trait_impls) {
+            let Some(required) = self.traits.get(&name) else {
+                continue;
+            };
+            let missing: Vec<&str> =
+                required.iter().filter(|item| !provided.contains(item)).map(String::as_str).collect();
+            if !missing.is_empty() {
+                self.errors.push(error(
+                    ErrorSeverity::Error,
+                    ErrorType::CompilationError,
+                    span,
+                    format!("Impl of {} is missing {}", name, missing.join(", ")),
+                ));
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Checker {
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.check_body(&item.sig.ident, &item.block);
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.check_body(&item.sig.ident, &item.block);
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        if let Some(block) = &item.default {
+            self.check_body(&item.sig.ident, block);
+        }
+        visit::visit_trait_item_fn(self, item);
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.traits.insert(item.ident.to_string(), required_items(item));
+        visit::visit_item_trait(self, item);
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if let Some((_, path, _)) = &item.trait_ {
+            if let Some(segment) = path.segments.last() {
+                let provided = item.items.iter().filter_map(impl_item_name).collect();
+                self.trait_impls.push((segment.ident.to_string(), provided, item.self_ty.span()));
+            }
+        }
+        visit::visit_item_impl(self, item);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Some(name) = placeholder(mac) {
+            self.errors.push(error(
+                ErrorSeverity::Fatal,
+                ErrorType::SterilizationViolation,
+                mac.span(),
+                format!("Found placeholder macro {}!", name),
+            ));
+        }
+        visit::visit_macro(self, mac);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(code: &str) -> Vec<(String, Option<u32>)> {
+        validate(code).into_iter().map(|e| (format!("{:?}", e.error_type), e.line)).collect()
+    }
+
+    #[test]
+    fn test_placeholders_are_found_in_code_not_comments() {
+        let code = r#"
+// unimplemented!() used to be here; now the total is summed
+pub fn total(lines: &[u32]) -> u32 {
+    let message = "todo!() is not called";
+    let _ = message;
+    lines.iter().sum()
+}
+
+pub fn average(lines: &[u32]) -> u32 {
+    todo!("divide the total")
+}
+
+pub fn median(lines: &[u32]) -> u32 {
+    panic!("no median")
+}
+
+pub fn reset() {}
+"#;
+        assert_eq!(
+            found(code),
+            vec![
+                ("SterilizationViolation".to_string(), Some(10)),
+                ("SterilizationViolation".to_string(), Some(14)),
+                ("EmptyBlock".to_string(), Some(17)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors_and_missing_trait_items_are_located() {
+        let errors = validate("pub fn total(lines: &[u32]) -> u32 {\n    lines.iter().sum(\n}\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].error_type, ErrorType::SyntaxError));
+        assert_eq!(errors[0].line, Some(3));
+
+        let code = r#"
+trait Ledger {
+    fn post(&mut self, amount: i64);
+    fn balance(&self) -> i64;
+    fn is_empty(&self) -> bool { self.balance() == 0 }
+}
+
+struct Book(i64);
+
+impl Ledger for Book {
+    fn post(&mut self, amount: i64) { self.0 += amount; }
+}
+"#;
+        let errors = validate(code);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Impl of Ledger is missing balance");
+        assert_eq!((errors[0].line, errors[0].column), (Some(10), Some(17)));
+    }
+}
@@ -0,0 +1,240 @@
+// Structural analysis with tree-sitter
+// With the tree-sitter feature, HermeticSandbox::analyze_ast parses
+// JavaScript and TypeScript and walks the syntax tree, so a TODO in a
+// string or `return null` in one branch of a real function is not flagged
+//
+// Reported, with the line and column of the offending node:
+// - syntax errors and missing tokens (SyntaxError)
+// - functions, methods and arrow functions with empty bodies (EmptyBlock)
+// - functions whose body only returns null, undefined or nothing
+//   (SterilizationViolation)
+// - banned constructs: TODO and FIXME comments and throws saying the code
+//   is not implemented (SterilizationViolation), debugger statements
+//   (LintError)
+//
+// A language is a `Grammar`: its tree-sitter language and the node kinds
+// the checks look for. Another language, such as Go or Java, is supported
+// by adding its grammar crate and an entry to `grammar`.
+
+use super::sandbox::{ErrorSeverity, ErrorType, ValidationError};
+use tree_sitter::{Language, Node, Parser};
+
+/// What the checks need to know of a language's syntax tree
+struct Grammar {
+    language: Language,
+    /// Kinds of the nodes that have a function body
+    functions: &'static [&'static str],
+    /// Kind of a block of statements
+    block: &'static str,
+    comment: &'static str,
+    return_statement: &'static str,
+    /// Kinds of the expressions a stub returns
+    stub_values: &'static [&'static str],
+    throw_statement: &'static str,
+    /// Kinds of statements not allowed at all
+    banned: &'static [&'static str],
+}
+
+const JS_FUNCTIONS: &[&str] = &[
+    "function_declaration",
+    "function_expression",
+    "generator_function_declaration",
+    "generator_function",
+    "arrow_function",
+    "method_definition",
+];
+
+fn grammar(language: &str) -> Option<Grammar> {
+    let language: Language = match language {
+        "javascript" => tree_sitter_javascript::LANGUAGE.into(),
+        "typescript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        _ => return None,
+    };
+    Some(Grammar {
+        language,
+        functions: JS_FUNCTIONS,
+        block: "statement_block",
+        comment: "comment",
+        return_statement: "return_statement",
+        stub_values: &["null", "undefined"],
+        throw_statement: "throw_statement",
+        banned: &["debugger_statement"],
+    })
+}
+
+/// Analyze `code` in `language`; nothing is reported for a language
+/// without a grammar
+pub(crate) fn analyze(code: &str, language: &str) -> Vec<ValidationError> {
+    let Some(grammar) = grammar(language) else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&grammar.language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(code, None) else {
+        return Vec::new();
+    };
+
+    let mut errors = Vec::new();
+    visit(&grammar, code, tree.root_node(), &mut errors);
+    errors
+}
+
+fn error(node: Node, severity: ErrorSeverity, error_type: ErrorType, message: String) -> ValidationError {
+    let position = node.start_position();
+    ValidationError {
+        severity,
+        message,
+        file: None,
+        line: Some(position.row as u32 + 1),
+        column: Some(position.column as u32 + 1),
+        error_type,
+    }
+}
+
+fn text<'a>(node: Node, code: &'a str) -> &'a str {
+    node.utf8_text(code.as_bytes()).unwrap_or("")
+}
+
+/// The statements of a block, without its comments
+fn statements<'t>(grammar: &Grammar, block: Node<'t>) -> Vec<Node<'t>> {
+    let mut cursor = block.walk();
+    block
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() != grammar.comment)
+        .collect()
+}
+
+/// Whether `body` does nothing but return null, undefined or nothing
+fn is_stub(grammar: &Grammar, body: Node) -> bool {
+    if grammar.stub_values.contains(&body.kind()) {
+        // An arrow function's expression body
+        return true;
+    }
+    if body.kind() != grammar.block {
+        return false;
+    }
+    match statements(grammar, body).as_slice() {
+        [statement] if statement.kind() == grammar.return_statement => {
+            let mut cursor = statement.walk();
+            let values: Vec<Node> = statement
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() != grammar.comment)
+                .collect();
+            match values.as_slice() {
+                [] => true,
+                [value] => grammar.stub_values.contains(&value.kind()),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn check_function(grammar: &Grammar, code: &str, function: Node, errors: &mut Vec<ValidationError>) {
+    let Some(body) = function.child_by_field_name("body") else {
+        return;
+    };
+    let name = function
+        .child_by_field_name("name")
+        .map_or("Anonymous function".to_string(), |name| format!("Function {}", text(name, code)));
+    if body.kind() == grammar.block && statements(grammar, body).is_empty() {
+        errors.push(error(function, ErrorSeverity::Fatal, ErrorType::EmptyBlock, format!("{} has an empty body", name)));
+    } else if is_stub(grammar, body) {
+        errors.push(error(
+            function,
+            ErrorSeverity::Fatal,
+            ErrorType::SterilizationViolation,
+            format!("{} only returns a stub value", name),
+        ));
+    }
+}
+
+fn visit(grammar: &Grammar, code: &str, node: Node, errors: &mut Vec<ValidationError>) {
+    let kind = node.kind();
+    if node.is_error() {
+        errors.push(error(node, ErrorSeverity::Error, ErrorType::SyntaxError, format!("Syntax error at '{}'", text(node, code))));
+    } else if node.is_missing() {
+        errors.push(error(node, ErrorSeverity::Error, ErrorType::SyntaxError, format!("Missing '{}'", kind)));
+    } else if grammar.functions.contains(&kind) {
+        check_function(grammar, code, node, errors);
+    } else if kind == grammar.comment {
+        let comment = text(node, code);
+        if let Some(marker) = ["TODO", "FIXME"].into_iter().find(|marker| comment.contains(marker)) {
+            errors.push(error(
+                node,
+                ErrorSeverity::Fatal,
+                ErrorType::SterilizationViolation,
+                format!("Found {} comment", marker),
+            ));
+        }
+    } else if kind == grammar.throw_statement && text(node, code).to_lowercase().contains("not implemented") {
+        errors.push(error(
+            node,
+            ErrorSeverity::Fatal,
+            ErrorType::SterilizationViolation,
+            "Throws a not implemented error".to_string(),
+        ));
+    } else if grammar.banned.contains(&kind) {
+        errors.push(error(node, ErrorSeverity::Error, ErrorType::LintError, format!("Banned construct: {}", kind)));
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        visit(grammar, code, child, errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(code: &str, language: &str) -> Vec<(String, Option<u32>)> {
+        analyze(code, language)
+            .into_iter()
+            .map(|e| (format!("{:?}", e.error_type), e.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_javascript_functions_are_checked_by_structure() {
+        let code = r#"const note = "// TODO is only a string";
+export function total(lines) {
+    if (lines.length === 0) {
+        return null;
+    }
+    return lines.reduce((sum, line) => sum + line, 0);
+}
+export function reset() {}
+const find = (id) => null;
+class Ledger {
+    close() {
+        // TODO: settle the balance
+        return;
+    }
+}
+"#;
+        assert_eq!(
+            found(code, "javascript"),
+            vec![
+                ("EmptyBlock".to_string(), Some(8)),
+                ("SterilizationViolation".to_string(), Some(9)),
+                ("SterilizationViolation".to_string(), Some(11)),
+                ("SterilizationViolation".to_string(), Some(12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_syntax_errors_and_banned_constructs() {
+        let code = "export function total(lines: number[]): number {\n    debugger;\n    throw new Error(\"Not implemented\");\n}\n";
+        assert_eq!(
+            found(code, "typescript"),
+            vec![("LintError".to_string(), Some(2)), ("SterilizationViolation".to_string(), Some(3))]
+        );
+        let broken = analyze("function total(lines: number[] {\n    return 0;\n}\n", "typescript");
+        assert!(broken.iter().any(|e| matches!(e.error_type, ErrorType::SyntaxError)), "{:?}", broken);
+        assert!(analyze("func main() {}", "go").is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+// Tier 3: Test Execution
+// Runs a module's TestPlan inside a WebAssembly interpreter
+//
+// The host compiles generated code to WebAssembly through `WasmCompiler`:
+// Rust to wasm32-wasi, Python by bundling it with an interpreter built for
+// WebAssembly. The sandbox runs no compiler or other OS command; it
+// interprets the module in process with wasmi.
+//
+// Each test case runs in a fresh instance with a fuel budget, which bounds
+// its running time, and a memory limit. The module is granted no
+// capabilities: every function it imports, WASI's included, returns
+// ENOTCAPABLE, proc_exit traps, and importing anything but functions fails
+// the run. The case `name` runs the export `test_<name>`, named as
+// `export_name` gives it, which takes no arguments and returns nothing or
+// an i32 that is 0 on success.
+
+use super::dag::{TestCase, TestPlan};
+use super::sandbox::{TestFailure, TestResults};
+use serde::{Deserialize, Serialize};
+use wasmi::core::{TrapCode, ValType};
+use wasmi::{Engine, Error, ExternType, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Val};
+
+/// WASI's errno for a capability the module was not granted
+const ENOTCAPABLE: i32 = 76;
+
+/// Compiles generated code to a WebAssembly module, supplied by the host
+pub trait WasmCompiler: Send + Sync {
+    fn compile(&self, code: &str, language: &str) -> Result<Vec<u8>, String>;
+}
+
+/// What one test case may use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionLimits {
+    /// Fuel per case; roughly one unit per instruction
+    pub fuel: u64,
+    /// Bytes of linear memory per case
+    pub memory_bytes: usize,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 50_000_000,
+            memory_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl ExecutionLimits {
+    fn store_limits(&self) -> StoreLimits {
+        StoreLimitsBuilder::new()
+            .memory_size(self.memory_bytes)
+            .instances(1)
+            .memories(1)
+            .tables(1)
+            .build()
+    }
+}
+
+/// The export that runs the case `name`: `test_` and the name, lowercased,
+/// with anything not alphanumeric as `_`
+pub fn export_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if name.starts_with("test_") {
+        name
+    } else {
+        format!("test_{}", name)
+    }
+}
+
+/// Run `plan`'s unit and integration tests against the module `wasm`; an
+/// error means the module could not be loaded at all
+pub fn run_tests(wasm: &[u8], plan: &TestPlan, limits: &ExecutionLimits) -> Result<TestResults, String> {
+    let mut config = wasmi::Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, wasm).map_err(|e| format!("Invalid WebAssembly module: {}", e))?;
+
+    let cases: Vec<&TestCase> = plan.unit_tests.iter().chain(&plan.integration_tests).collect();
+    let failures: Vec<TestFailure> = cases
+        .iter()
+        .filter_map(|case| {
+            run_case(&engine, &module, case, limits).err().map(|error_message| TestFailure {
+                test_name: case.name.clone(),
+                error_message,
+            })
+        })
+        .collect();
+
+    Ok(TestResults {
+        total_tests: cases.len() as u32,
+        passed: (cases.len() - failures.len()) as u32,
+        failed: failures.len() as u32,
+        failures,
+    })
+}
+
+/// A linker granting `module` no capabilities
+fn deny_all(engine: &Engine, module: &Module) -> Result<Linker<StoreLimits>, String> {
+    let mut linker = Linker::new(engine);
+    for import in module.imports() {
+        let ExternType::Func(ty) = import.ty() else {
+            return Err(format!("Module imports {}::{}, which is not granted", import.module(), import.name()));
+        };
+        let exits = import.name() == "proc_exit";
+        let results = ty.results().to_vec();
+        linker
+            .func_new(import.module(), import.name(), ty.clone(), move |_, _, outputs| {
+                if exits {
+                    return Err(Error::new("proc_exit called"));
+                }
+                for (output, ty) in outputs.iter_mut().zip(&results) {
+                    *output = match ty {
+                        ValType::I32 => Val::I32(ENOTCAPABLE),
+                        ty => Val::default(*ty),
+                    };
+                }
+                Ok(())
+            })
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(linker)
+}
+
+fn run_case(engine: &Engine, module: &Module, case: &TestCase, limits: &ExecutionLimits) -> Result<(), String> {
+    let mut store = Store::new(engine, limits.store_limits());
+    store.limiter(|limits| limits);
+    store.set_fuel(limits.fuel).map_err(|e| e.to_string())?;
+    let instance = deny_all(engine, module)?
+        .instantiate(&mut store, module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(|e| format!("Cannot instantiate module: {}", e))?;
+
+    let export = export_name(&case.name);
+    let func = instance
+        .get_func(&store, &export)
+        .ok_or_else(|| format!("Module has no export {}", export))?;
+    let ty = func.ty(&store);
+    if !ty.params().is_empty() || !matches!(ty.results(), [] | [ValType::I32]) {
+        return Err(format!("{} must take no arguments and return nothing or an i32", export));
+    }
+
+    let mut outputs: Vec<Val> = ty.results().iter().map(|ty| Val::default(*ty)).collect();
+    func.call(&mut store, &[], &mut outputs).map_err(|e| match e.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => format!("Exceeded the fuel limit of {}", limits.fuel),
+        _ => format!("Trapped: {}", e),
+    })?;
+    match outputs.first() {
+        Some(Val::I32(0)) | None => Ok(()),
+        Some(Val::I32(code)) => Err(format!("Returned {}", code)),
+        Some(other) => Err(format!("Returned {:?}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axiom_determinist::sandbox::HermeticSandbox;
+
+    const MODULE: &str = r#"
+        (module
+            (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (memory 1)
+            (func (export "test_adds") (result i32)
+                (i32.ne (i32.add (i32.const 2) (i32.const 3)) (i32.const 5)))
+            (func (export "test_rounds_down") (result i32) (i32.const 1))
+            (func (export "test_terminates") (loop $forever (br $forever)))
+            (func (export "test_cannot_write") (result i32)
+                (i32.ne (call $fd_write (i32.const 1) (i32.const 0) (i32.const 0) (i32.const 0)) (i32.const 76)))
+            (func (export "test_memory_is_capped") (result i32)
+                (i32.ne (memory.grow (i32.const 16)) (i32.const -1))))
+    "#;
+
+    fn case(name: &str) -> TestCase {
+        TestCase {
+            name: name.to_string(),
+            description: String::new(),
+            expected_behavior: String::new(),
+        }
+    }
+
+    struct Wat;
+
+    impl WasmCompiler for Wat {
+        fn compile(&self, _code: &str, _language: &str) -> Result<Vec<u8>, String> {
+            wat::parse_str(MODULE).map_err(|e| e.to_string())
+        }
+    }
+
+    #[test]
+    fn test_cases_run_in_a_module_without_capabilities() {
+        let plan = TestPlan {
+            unit_tests: ["Adds", "rounds down", "terminates", "cannot_write", "missing"].map(case).to_vec(),
+            integration_tests: vec![case("memory is capped")],
+        };
+        let limits = ExecutionLimits { fuel: 10_000, memory_bytes: 4 * 65_536 };
+        let results = run_tests(&wat::parse_str(MODULE).unwrap(), &plan, &limits).unwrap();
+        assert_eq!((results.total_tests, results.passed, results.failed), (6, 3, 3));
+        let failures: Vec<(&str, &str)> =
+            results.failures.iter().map(|f| (f.test_name.as_str(), f.error_message.as_str())).collect();
+        assert_eq!(
+            failures,
+            vec![
+                ("rounds down", "Returned 1"),
+                ("terminates", "Exceeded the fuel limit of 10000"),
+                ("missing", "Module has no export test_missing"),
+            ]
+        );
+        assert!(run_tests(b"not wasm", &plan, &limits).is_err());
+    }
+
+    #[test]
+    fn test_failing_cases_fail_validation() {
+        let plan = TestPlan { unit_tests: vec![case("adds"), case("rounds_down")], integration_tests: Vec::new() };
+        let code = "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n";
+        let result = HermeticSandbox::new().validate_and_test(code, "rust", &plan, &Wat);
+        assert!(!result.passed);
+        assert_eq!(result.test_results.as_ref().map(|t| t.failed), Some(1));
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].message, "Test rounds_down failed: Returned 1");
+    }
+}
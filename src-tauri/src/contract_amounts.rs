@@ -0,0 +1,189 @@
+//! AxiomHive Contract Amount Extraction
+//! Deterministic Legal Contract Summarization Pipeline
+//! Finds sums of money in contract text and normalizes each to a currency
+//! code and a value in whole units of it. Recognized forms:
+//!
+//! - a symbol before the number: `$1,500,000`, `€250.50`, `US$ 10`
+//! - a code before or after it: `EUR 20k`, `1.2m USD`
+//! - a currency word after it: `5,000 dollars`, `3 million euros`
+//! - spelled-out numbers: `five thousand dollars`
+//!
+//! A `k`, `m`, `mm`, `bn` or `thousand`, `million`, `billion` after the
+//! number scales it. "Dollars" and `$` are read as US dollars, since a
+//! contract that means another dollar almost always says so with a code.
+//! Where forms overlap, as in `CAD $500`, the amount is counted once, in
+//! the currency of the code.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A sum of money found in the text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonetaryAmount {
+    /// ISO 4217 code
+    pub currency: String,
+    pub value: f64,
+    /// The text the amount was read from
+    pub text: String,
+}
+
+/// Total of the amounts in one currency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exposure {
+    pub currency: String,
+    pub total: f64,
+    /// How many amounts were added up
+    pub amounts: usize,
+}
+
+const CODES: &str = "USD|EUR|GBP|JPY|CHF|CAD|AUD|NZD|CNY|INR|SGD|HKD|SEK|NOK|DKK";
+const NUMBER: &str = r"(\d{1,3}(?:,\d{3})+(?:\.\d+)?|\d+(?:\.\d+)?)";
+const SCALE: &str = r"(?:\s?(k|mm|m|bn|thousand|million|billion)\b)?";
+const NUMBER_WORDS: &str = "zero|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve|thirteen|fourteen|\
+    fifteen|sixteen|seventeen|eighteen|nineteen|twenty|thirty|forty|fifty|sixty|seventy|eighty|ninety|\
+    hundred|thousand|million|billion";
+
+fn symbol_currency(symbol: &str) -> &'static str {
+    match symbol {
+        "€" => "EUR",
+        "£" => "GBP",
+        "¥" => "JPY",
+        _ => "USD",
+    }
+}
+
+fn word_currency(word: &str) -> &'static str {
+    let word = word.to_lowercase();
+    if word.starts_with("euro") {
+        "EUR"
+    } else if word.starts_with("pound") {
+        "GBP"
+    } else if word.starts_with("yen") {
+        "JPY"
+    } else {
+        "USD"
+    }
+}
+
+fn scale(suffix: Option<&str>) -> f64 {
+    match suffix.map(str::to_lowercase).as_deref() {
+        Some("k" | "thousand") => 1e3,
+        Some("m" | "mm" | "million") => 1e6,
+        Some("bn" | "billion") => 1e9,
+        _ => 1.0,
+    }
+}
+
+fn number(digits: &str) -> f64 {
+    digits.replace(',', "").parse().expect("the pattern only matches numbers")
+}
+
+/// Value of spelled-out number words such as "two hundred fifty thousand"
+pub(crate) fn words_value(words: &str) -> Option<f64> {
+    let (mut total, mut current, mut any) = (0.0, 0.0, false);
+    for word in words.split(|c: char| c.is_whitespace() || c == '-').filter(|w| !w.is_empty()) {
+        let word = word.to_lowercase();
+        let small = NUMBER_WORDS.split('|').position(|w| w == word);
+        match (word.as_str(), small) {
+            ("and", _) => continue,
+            ("hundred", _) => current = if current == 0.0 { 100.0 } else { current * 100.0 },
+            ("thousand" | "million" | "billion", _) => {
+                total += if current == 0.0 { 1.0 } else { current } * scale(Some(&word));
+                current = 0.0;
+            }
+            (_, Some(i)) if i < 20 => current += i as f64,
+            (_, Some(i)) => current += ((i - 18) * 10) as f64,
+            _ => return None,
+        }
+        any = true;
+    }
+    any.then_some(total + current)
+}
+
+/// Amounts in `text`, in order of appearance
+pub fn extract(text: &str) -> Vec<MonetaryAmount> {
+    let patterns = [
+        Regex::new(&format!(r"(?:\b({codes})\s?)?(US\$|[$€£¥])\s?{NUMBER}(?i:{SCALE})", codes = CODES)).unwrap(),
+        Regex::new(&format!(r"\b({codes})\s?{NUMBER}(?i:{SCALE})", codes = CODES)).unwrap(),
+        Regex::new(&format!(r"{NUMBER}(?i:{SCALE})\s?({codes})\b", codes = CODES)).unwrap(),
+        Regex::new(&format!(r"(?i){NUMBER}{SCALE}\s(dollars?|euros?|pounds?|yen)\b")).unwrap(),
+        Regex::new(&format!(r"(?i)\b((?:{NUMBER_WORDS})(?:[\s-]+(?:{NUMBER_WORDS}|and))*)\s+(dollars?|euros?|pounds?|yen)\b")).unwrap(),
+    ];
+
+    let mut found: Vec<(usize, usize, MonetaryAmount)> = Vec::new();
+    for (kind, re) in patterns.iter().enumerate() {
+        for cap in re.captures_iter(text) {
+            let whole = cap.get(0).expect("group 0 always matches");
+            if found.iter().any(|&(start, end, _)| whole.start() < end && start < whole.end()) {
+                continue;
+            }
+            let (currency, value) = match kind {
+                0 => {
+                    let currency = cap.get(1).map_or_else(|| symbol_currency(&cap[2]), |c| code(c.as_str()));
+                    (currency, number(&cap[3]) * scale(cap.get(4).map(|m| m.as_str())))
+                }
+                1 => (code(&cap[1]), number(&cap[2]) * scale(cap.get(3).map(|m| m.as_str()))),
+                2 => (code(&cap[3]), number(&cap[1]) * scale(cap.get(2).map(|m| m.as_str()))),
+                3 => (word_currency(&cap[3]), number(&cap[1]) * scale(cap.get(2).map(|m| m.as_str()))),
+                _ => match words_value(&cap[1]) {
+                    Some(value) => (word_currency(&cap[2]), value),
+                    None => continue,
+                },
+            };
+            let amount = MonetaryAmount { currency: currency.to_string(), value, text: whole.as_str().trim().to_string() };
+            found.push((whole.start(), whole.end(), amount));
+        }
+    }
+    found.sort_by_key(|&(start, _, _)| start);
+    found.into_iter().map(|(_, _, amount)| amount).collect()
+}
+
+fn code(code: &str) -> &'static str {
+    CODES.split('|').find(|c| *c == code).expect("the pattern only matches known codes")
+}
+
+/// Per-currency totals of `amounts`, by currency code
+pub fn exposure<'a>(amounts: impl IntoIterator<Item = &'a MonetaryAmount>) -> Vec<Exposure> {
+    let mut totals: BTreeMap<&str, (f64, usize)> = BTreeMap::new();
+    for amount in amounts {
+        let entry = totals.entry(&amount.currency).or_default();
+        entry.0 += amount.value;
+        entry.1 += 1;
+    }
+    totals
+        .into_iter()
+        .map(|(currency, (total, amounts))| Exposure { currency: currency.to_string(), total, amounts })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amounts_normalize_to_currency_and_value() {
+        let text = "Buyer shall pay $1,500,000 on signing, EUR 20k per quarter, 2.5m USD on completion, \
+            five thousand dollars for travel, £99.50 monthly and CAD $500 in fees, for 30 days.";
+        let found = extract(text);
+        let pairs: Vec<(&str, f64)> = found.iter().map(|a| (a.currency.as_str(), a.value)).collect();
+        assert_eq!(
+            pairs,
+            vec![("USD", 1_500_000.0), ("EUR", 20_000.0), ("USD", 2_500_000.0), ("USD", 5_000.0), ("GBP", 99.5), ("CAD", 500.0)]
+        );
+        assert_eq!(found[3].text, "five thousand dollars");
+        assert_eq!(found[5].text, "CAD $500");
+
+        let totals = exposure(&found);
+        assert_eq!(totals.iter().map(|e| e.currency.as_str()).collect::<Vec<_>>(), vec!["CAD", "EUR", "GBP", "USD"]);
+        assert_eq!((totals[3].total, totals[3].amounts), (4_005_000.0, 3));
+    }
+
+    #[test]
+    fn test_spelled_out_numbers() {
+        assert_eq!(words_value("two hundred and fifty thousand"), Some(250_000.0));
+        assert_eq!(words_value("one million three hundred"), Some(1_000_300.0));
+        assert_eq!(words_value("forty-two"), Some(42.0));
+        assert!(extract("The parties shall meet every 30 days.").is_empty());
+    }
+}
@@ -1,75 +1,441 @@
 //! AxiomHive Contract Analyzer
 //! Deterministic Legal Contract Summarization Pipeline
 //! Zero Entropy Law (C=0) - Verifiable Contract Analysis
-
+//! `analyze_contract` returns a typed `ContractSummary`; `to_json` keeps
+//! the response layout the Tauri frontend reads. Obligations are found
+//! clause by clause (see contract_clauses.rs) and cite the clause they
+//! came from, as do the risk flags raised on them. What counts as an
+//! obligation and which flags are raised is set by a rule pack (see
+//! contract_rules.rs). Financial obligations carry the sums of money
+//! they mention (see contract_amounts.rs), which add up to the summary's
+//! exposure. Dates in any of the forms contract_dates.rs reads are
+//! reported as ISO 8601. Indemnification, liability caps and warranty
+//! disclaimers are analyzed clause by clause (see contract_provisions.rs)
+//! and their flags come before those raised on obligations. All flags
+//! are weighted into the summary's risk score (see contract_scoring.rs).
+//! Governing law and venue are normalized to canonical jurisdiction
+//! codes, and a mismatch between them is flagged (see
+//! contract_jurisdictions.rs). The contract's dates are laid out as a
+//! timeline (see contract_timeline.rs). Every party, obligation, risk
+//! flag and metadata field carries a confidence and the spans of the
+//! submitted text it was read from (see contract_evidence.rs). A master
+//! agreement and its amendments are analyzed together by
+//! `analyze_bundle` (see contract_bundle.rs). Duplicate obligations are
+//! merged, and a summary carries a page of the obligations and risk flags
+//! found; `obligations_page` and `risk_flags_page` return the others (see
+//! contract_limits.rs). Unless given a rule pack, the analyzer applies
+//! the built-in pack of the language the contract is written in (see
+//! contract_language.rs). `anonymize` redacts a contract for sharing
+//! (see contract_anonymize.rs), and `analyze_contract_sealed` encrypts a
+//! summary for storage (see contract_sealed.rs).
+
+use crate::contract_amounts::{self, Exposure, MonetaryAmount};
+use crate::contract_anonymize::{self, MappingSeal, OrganizationKey};
+use crate::contract_bundle::{BundleError, ContractBundle, ContractDoc};
+use crate::contract_clauses::{self, Clause};
+use crate::contract_dates::{DateOrder, DateParser};
+use crate::contract_diff::ContractDiff;
+use crate::contract_evidence::{Evidence, SourceText, Span};
+use crate::contract_jurisdictions::{self, Jurisdiction};
+use crate::contract_language::{self, Language};
+use crate::contract_limits::{self, AnalysisLimits, Page, Pagination};
+use crate::contract_provisions::{self, Provision, ProvisionFinding};
+use crate::contract_rules::{CompiledRules, RulePack, RulePackError};
+use crate::contract_scoring::RiskScore;
+use crate::contract_sealed::{self, SealedError, SealedSummary};
+use crate::contract_timeline::Timeline;
+use crate::fhe_core::DeoxysFHE;
+use rand_core::{CryptoRng, RngCore};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use serde_json::json;
+use std::borrow::Cow;
+use std::fmt;
+
+/// Occurrences of a party's name cited at most
+const MAX_PARTY_SPANS: usize = 10;
+
+/// Parties, dates and governing law of a contract
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractMetadata {
+    /// "Party A" and "Party B" when none could be found
+    pub parties: Vec<String>,
+    /// First date in the text, as ISO 8601
+    pub effective_date: Option<String>,
+    /// Last date in the text, if there is more than one, as ISO 8601
+    pub termination_date: Option<String>,
+    /// The jurisdiction as written
+    pub jurisdiction: Option<String>,
+    /// Where the governing law comes from, if the built-in table has it
+    pub governing_law: Option<Jurisdiction>,
+    /// Where disputes are heard, if the built-in table has it
+    pub venue: Option<Jurisdiction>,
+    /// The language the contract is written in; English when unsure
+    pub language: Language,
+    pub evidence: MetadataEvidence,
+}
 
-const MAX_OBLIGATIONS: usize = 10;
-const MAX_RISK_FLAGS: usize = 20;
+/// Evidence for each field of `ContractMetadata` that has a value
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct MetadataEvidence {
+    /// One per party, in the same order; placeholders have none
+    pub parties: Vec<Evidence>,
+    pub effective_date: Option<Evidence>,
+    pub termination_date: Option<Evidence>,
+    pub jurisdiction: Option<Evidence>,
+    pub governing_law: Option<Evidence>,
+    pub venue: Option<Evidence>,
+    /// The detection's confidence; the whole text is its evidence
+    pub language: Evidence,
+}
+
+/// A sentence binding a party to do something
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Obligation {
+    pub party: String,
+    /// The sentence, cut to 200 characters
+    pub description: String,
+    /// First date in the sentence, as ISO 8601
+    pub due_date: Option<String>,
+    /// First matching category of the rule pack, or "general"
+    pub category: String,
+    /// Reference of the clause the sentence is in, if it is numbered
+    pub clause: Option<String>,
+    /// Sums of money in the sentence; empty unless the category is
+    /// "financial"
+    pub amounts: Vec<MonetaryAmount>,
+    /// The sentence in the submitted text, then those of its duplicates
+    pub evidence: Evidence,
+    /// Later sentences restating this obligation, merged into it
+    pub duplicates: usize,
+}
+
+/// How much attention a risk flag needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// Something a reviewer should look at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskFlag {
+    pub severity: Severity,
+    /// Category of the rule that raised it, e.g. "financial", or of the
+    /// provision, e.g. "liability"
+    pub category: String,
+    pub description: String,
+    /// Clause of the obligation or provision the flag was raised on
+    pub clause: Option<String>,
+    /// Amounts of that obligation, or the liability cap
+    pub amounts: Vec<MonetaryAmount>,
+    /// What the flag adds to the risk score
+    pub weight: f64,
+    /// The obligation's, the provision clause's, or the governing law
+    /// and venue
+    pub evidence: Evidence,
+}
+
+/// Output of `ContractAnalyzer::analyze_contract`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractSummary {
+    pub metadata: ContractMetadata,
+    /// The contract's clauses in document order
+    pub clauses: Vec<Clause>,
+    /// The first page of the obligations
+    pub key_obligations: Vec<Obligation>,
+    /// The first page of the risk flags
+    pub risk_flags: Vec<RiskFlag>,
+    /// Indemnification, liability and warranty provisions, in document
+    /// order
+    pub provisions: Vec<ProvisionFinding>,
+    /// Weighted score of every flag raised, including any beyond
+    /// `risk_flags`' page
+    pub risk_score: RiskScore,
+    /// Total of every obligation's amounts per currency
+    pub exposure: Vec<Exposure>,
+    /// Dated events in chronological order
+    pub timeline: Timeline,
+    /// How many obligations and flags there are beyond the first page
+    pub pagination: Pagination,
+    /// Name of the rule pack applied
+    pub rule_pack: String,
+    /// Hash over the input text and the findings, every page of them
+    pub cryptographic_seal: String,
+}
+
+impl ContractSummary {
+    /// The response layout of the `process_contract` command
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "status": "success",
+            "summary": {
+                "parties": self.metadata.parties,
+                "key_obligations": self.key_obligations,
+                "risk_flags": self.risk_flags,
+                "exposure": self.exposure,
+                "provisions": self.provisions,
+                "risk_score": self.risk_score,
+                "pagination": self.pagination
+            },
+            "clauses": self.clauses,
+            "timeline": self.timeline,
+            "metadata": {
+                "effective_date": self.metadata.effective_date,
+                "termination_date": self.metadata.termination_date,
+                "jurisdiction": self.metadata.jurisdiction,
+                "governing_law": self.metadata.governing_law,
+                "venue": self.metadata.venue,
+                "language": self.metadata.language,
+                "evidence": self.metadata.evidence
+            },
+            "verification": {
+                "hash_integrity": "PASSED",
+                "schema_compliance": "PASSED",
+                "rule_pack": self.rule_pack,
+                "cryptographic_seal": self.cryptographic_seal
+            }
+        })
+    }
+}
+
+/// Why a contract could not be summarized
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContractError {
+    /// Nothing but whitespace was submitted
+    EmptyInput,
+    /// The compiled summary failed schema validation; `payload` is what
+    /// was compiled
+    Validation { failure_codes: Vec<String>, payload: Box<ContractSummary> },
+}
+
+impl ContractError {
+    /// The error layout of the `process_contract` command
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            ContractError::EmptyInput => json!({
+                "status": "error",
+                "failure_codes": ["EMPTY_INPUT"],
+                "error_payload": null
+            }),
+            ContractError::Validation { failure_codes, payload } => json!({
+                "status": "error",
+                "failure_codes": failure_codes,
+                "error_payload": {
+                    "parties": payload.metadata.parties,
+                    "key_obligations": payload.key_obligations,
+                    "risk_flags": payload.risk_flags
+                }
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractError::EmptyInput => write!(f, "Contract text is empty"),
+            ContractError::Validation { failure_codes, .. } => {
+                write!(f, "Contract summary failed validation: {}", failure_codes.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContractError {}
 
 /// Contract analyzer implementing deterministic DAG pipeline
 pub struct ContractAnalyzer {
     frozen_seed: bool,
+    rules: CompiledRules,
+    /// Whether the built-in pack of the contract's language replaces
+    /// `rules`; false once a rule pack is given
+    by_language: bool,
+    dates: DateParser,
+    limits: AnalysisLimits,
 }
 
 impl ContractAnalyzer {
+    /// An analyzer applying the built-in rule pack of each contract's
+    /// language
     pub fn new(frozen_seed: bool) -> Self {
-        Self { frozen_seed }
+        Self {
+            frozen_seed,
+            rules: CompiledRules::default(),
+            by_language: true,
+            dates: DateParser::default(),
+            limits: AnalysisLimits::default(),
+        }
     }
 
-    /// Main pipeline: Analyze contract through deterministic DAG
-    pub fn analyze_contract(&self, contract_text: &str) -> serde_json::Value {
-        // Node 1: Input Ingest
-        let validated_text = self.input_ingest(contract_text);
+    /// Apply `rules` instead of the current rule pack, whatever the
+    /// contract's language
+    pub fn with_rules(self, rules: RulePack) -> Result<Self, RulePackError> {
+        Ok(Self { rules: CompiledRules::new(rules)?, by_language: false, ..self })
+    }
 
-        // Node 2: Extract Metadata
-        let metadata = self.extract_metadata(&validated_text);
+    /// The rules to apply to a contract in `language`
+    fn rules_for(&self, language: Language) -> Cow<'_, CompiledRules> {
+        if !self.by_language || language == Language::English {
+            return Cow::Borrowed(&self.rules);
+        }
+        Cow::Owned(CompiledRules::new(RulePack::for_language(language)).expect("built-in rule packs compile"))
+    }
 
-        // Node 3: Extract Obligations
-        let parties = metadata.get("parties")
-            .and_then(|p| p.as_array())
-            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect::<Vec<_>>())
-            .unwrap_or_else(|| vec!["Party A".to_string(), "Party B".to_string()]);
-        
-        let obligations = self.extract_obligations(&validated_text, &parties);
+    pub fn rules(&self) -> &RulePack {
+        self.rules.pack()
+    }
 
-        // Node 4: Detect Risks
-        let risk_flags = self.detect_risks(&obligations, &metadata);
+    /// Analyze both versions of a contract and diff them
+    pub fn compare(&self, old_text: &str, new_text: &str) -> Result<ContractDiff, ContractError> {
+        let old = self.analyze(old_text)?;
+        let new = self.analyze(new_text)?;
+        Ok(ContractDiff::new(old_text, &old, new_text, &new))
+    }
 
-        // Node 5: Validate Structures
-        let compiled_summary = json!({
-            "parties": metadata["parties"],
-            "key_obligations": obligations,
-            "risk_flags": risk_flags
-        });
-        
-        let validation_result = self.validate_structures(&compiled_summary);
-
-        // Node 6: Route on Validation
-        if validation_result.get("is_valid").and_then(|v| v.as_bool()).unwrap_or(false) {
-            json!({
-                "status": "success",
-                "summary": compiled_summary,
-                "metadata": {
-                    "effective_date": metadata.get("effective_date"),
-                    "termination_date": metadata.get("termination_date"),
-                    "jurisdiction": metadata.get("jurisdiction")
-                },
-                "verification": {
-                    "hash_integrity": "PASSED",
-                    "schema_compliance": "PASSED",
-                    "cryptographic_seal": self.compute_seal(contract_text, &compiled_summary)
-                }
-            })
-        } else {
-            json!({
-                "status": "error",
-                "failure_codes": validation_result.get("failure_codes"),
-                "error_payload": compiled_summary
+    /// Analyze related documents together, linking amendments to what
+    /// they amend
+    pub fn analyze_bundle(&self, docs: &[ContractDoc]) -> Result<ContractBundle, BundleError> {
+        let summaries = docs
+            .iter()
+            .enumerate()
+            .map(|(document, doc)| {
+                self.analyze(&doc.text)
+                    .map_err(|error| BundleError { document, title: doc.title.clone(), error })
             })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ContractBundle::new(docs, summaries, &self.dates))
+    }
+
+    /// Read all-numeric dates such as 03/04/2025 in `order`
+    pub fn with_date_order(self, order: DateOrder) -> Self {
+        Self { dates: DateParser::new(order), ..self }
+    }
+
+    /// Carry at most `limits` obligations and risk flags in a summary; a
+    /// limit of 0 is taken as 1
+    pub fn with_limits(self, limits: AnalysisLimits) -> Self {
+        let limits = AnalysisLimits { obligations: limits.obligations.max(1), risk_flags: limits.risk_flags.max(1) };
+        Self { limits, ..self }
+    }
+
+    pub fn limits(&self) -> AnalysisLimits {
+        self.limits
+    }
+
+    /// Up to `limit` of the contract's obligations from `offset` on
+    pub fn obligations_page(&self, contract_text: &str, offset: usize, limit: usize) -> Result<Page<Obligation>, ContractError> {
+        let summary = self.analyze(contract_text)?;
+        let (items, page) = contract_limits::page(&summary.key_obligations, offset, limit);
+        Ok(Page { items, page, cryptographic_seal: summary.cryptographic_seal })
+    }
+
+    /// Up to `limit` of the contract's risk flags from `offset` on
+    pub fn risk_flags_page(&self, contract_text: &str, offset: usize, limit: usize) -> Result<Page<RiskFlag>, ContractError> {
+        let summary = self.analyze(contract_text)?;
+        let (items, page) = contract_limits::page(&summary.risk_flags, offset, limit);
+        Ok(Page { items, page, cryptographic_seal: summary.cryptographic_seal })
+    }
+
+    /// `contract_text` with its parties, addresses, emails and amounts
+    /// replaced by placeholders keyed by `key`, and the mapping to restore
+    /// them
+    pub fn anonymize(&self, contract_text: &str, key: &OrganizationKey) -> (String, MappingSeal) {
+        let metadata = self.extract_metadata(&self.input_ingest(contract_text), &SourceText::new(contract_text));
+        // Placeholder parties have no evidence and are not in the text
+        let parties: Vec<String> = metadata
+            .parties
+            .into_iter()
+            .zip(metadata.evidence.parties)
+            .filter(|(_, evidence)| !evidence.spans.is_empty())
+            .map(|(party, _)| party)
+            .collect();
+        contract_anonymize::anonymize(contract_text, &parties, key)
+    }
+
+    /// Analyze a contract and encrypt the summary under `fhe`'s key
+    pub fn analyze_contract_sealed<R: RngCore + CryptoRng>(
+        &self,
+        contract_text: &str,
+        fhe: &DeoxysFHE,
+        rng: &mut R,
+    ) -> Result<SealedSummary, SealedError> {
+        let summary = self.analyze_contract(contract_text).map_err(SealedError::Analysis)?;
+        contract_sealed::seal(&summary, fhe, rng)
+    }
+
+    /// Main pipeline: Analyze contract through deterministic DAG, keeping
+    /// the first page of the findings
+    pub fn analyze_contract(&self, contract_text: &str) -> Result<ContractSummary, ContractError> {
+        let mut summary = self.analyze(contract_text)?;
+
+        // Node 10: Page Findings
+        let (key_obligations, obligations) = contract_limits::page(&summary.key_obligations, 0, self.limits.obligations);
+        let (risk_flags, flags) = contract_limits::page(&summary.risk_flags, 0, self.limits.risk_flags);
+        summary.key_obligations = key_obligations;
+        summary.risk_flags = risk_flags;
+        summary.pagination = Pagination { obligations, risk_flags: flags };
+        Ok(summary)
+    }
+
+    /// The pipeline up to paging: a summary with every finding, all on
+    /// its first page
+    fn analyze(&self, contract_text: &str) -> Result<ContractSummary, ContractError> {
+        // Node 1: Input Ingest
+        let validated_text = self.input_ingest(contract_text);
+        if validated_text.is_empty() {
+            return Err(ContractError::EmptyInput);
+        }
+
+        // Node 2: Segment Clauses, before whitespace loses the layout
+        let clauses = contract_clauses::segment(contract_text);
+        let source = SourceText::new(contract_text);
+
+        // Node 3: Extract Metadata, choosing the rules for its language
+        let metadata = self.extract_metadata(&validated_text, &source);
+        let rules = self.rules_for(metadata.language);
+
+        // Node 4: Extract Obligations, merging those restated
+        let obligations =
+            contract_limits::merge_duplicates(self.extract_obligations(&rules, &clauses, &metadata.parties, &source));
+
+        // Node 5: Detect Risks
+        let (provisions, risk_flags) = self.detect_risks(&rules, &clauses, &metadata, &obligations, &source);
+
+        // Node 6: Score Risks
+        let risk_score = RiskScore::new(&risk_flags, &rules.pack().scoring);
+
+        // Node 7: Lay Out Timeline
+        let timeline = Timeline::new(&metadata, &clauses, &obligations, &self.dates);
+
+        // Node 8: Validate Structures
+        let (_, obligations_page) = contract_limits::page(&obligations, 0, obligations.len());
+        let (_, risk_flags_page) = contract_limits::page(&risk_flags, 0, risk_flags.len());
+        let mut summary = ContractSummary {
+            timeline,
+            metadata,
+            clauses,
+            exposure: contract_amounts::exposure(obligations.iter().flat_map(|o| &o.amounts)),
+            key_obligations: obligations,
+            risk_flags,
+            provisions,
+            risk_score,
+            pagination: Pagination { obligations: obligations_page, risk_flags: risk_flags_page },
+            rule_pack: rules.pack().name.clone(),
+            cryptographic_seal: String::new(),
+        };
+        let failure_codes = self.validate_structures(&summary);
+
+        // Node 9: Route on Validation
+        if !failure_codes.is_empty() {
+            return Err(ContractError::Validation { failure_codes, payload: Box::new(summary) });
         }
+        summary.cryptographic_seal = self.compute_seal(contract_text, &summary);
+        Ok(summary)
     }
 
     fn input_ingest(&self, source_blob: &str) -> String {
@@ -81,22 +447,24 @@ impl ContractAnalyzer {
         re.replace_all(source_blob.trim(), " ").to_string()
     }
 
-    fn extract_metadata(&self, contract_text: &str) -> serde_json::Value {
+    fn extract_metadata(&self, contract_text: &str, source: &SourceText) -> ContractMetadata {
         let mut parties = Vec::new();
+        let mut evidence = MetadataEvidence::default();
         
-        // Extract parties
+        // Extract parties; a company suffix is surer than "between"
         let party_patterns = vec![
-            r"(?i)(?:between|by and between|parties? to this agreement)[:\s]+([A-Z][^,\.]+(?:,?\s+[A-Z][^,\.]+)*)",
-            r"([A-Z][A-Za-z\s&]+(?:LLC|Inc|Corp|Ltd|Company))",
+            (r"(?i)(?:between|by and between|parties? to this agreement)[:\s]+([A-Z][^,\.]+(?:,?\s+[A-Z][^,\.]+)*)", 0.7),
+            (r"([A-Z][A-Za-z\s&]+(?:LLC|Inc|Corp|Ltd|Company|GmbH|SARL))", 0.9),
         ];
 
-        for pattern in party_patterns {
+        for (pattern, confidence) in party_patterns {
             if let Ok(re) = Regex::new(pattern) {
                 for cap in re.captures_iter(contract_text) {
                     let party = cap.get(1).map(|m| m.as_str().trim().to_string())
                         .or_else(|| cap.get(0).map(|m| m.as_str().trim().to_string()));
                     if let Some(p) = party {
                         if p.len() > 2 && !parties.contains(&p) {
+                            evidence.parties.push(Evidence::new(confidence, source.find_all(&p, MAX_PARTY_SPANS)));
                             parties.push(p);
                             if parties.len() >= 10 {
                                 break;
@@ -109,206 +477,197 @@ impl ContractAnalyzer {
 
         if parties.is_empty() {
             parties = vec!["Party A".to_string(), "Party B".to_string()];
+            evidence.parties = vec![Evidence::default(), Evidence::default()];
         }
 
-        // Extract dates
-        let date_re = Regex::new(r"(\d{4}-\d{2}-\d{2})").unwrap();
-        let dates: Vec<&str> = date_re.find_iter(contract_text)
-            .map(|m| m.as_str())
-            .collect();
-        
-        let effective_date = dates.first().map(|s| s.to_string());
-        let termination_date = if dates.len() > 1 { dates.last().map(|s| s.to_string()) } else { None };
+        // Extract dates; the first and last dates are only surely the
+        // term's when the words before them say so
+        let dates = self.dates.extract(contract_text);
+        let effective_date = dates.first().map(|d| d.iso.clone());
+        let termination_date = if dates.len() > 1 { dates.last().map(|d| d.iso.clone()) } else { None };
+        let date_evidence = |span: Option<Span>, keywords: &str| {
+            let said = span.as_ref().is_some_and(|span| Regex::new(keywords).unwrap().is_match(source.before(span, 80)));
+            Evidence::new(if said { 0.9 } else { 0.5 }, span.into_iter().collect())
+        };
+        evidence.effective_date = effective_date.as_ref().map(|_| {
+            date_evidence(source.find(&dates[0].text, 0), r"(?i)effective|dated|commenc|made|as of|start")
+        });
+        evidence.termination_date = termination_date.as_ref().map(|_| {
+            let last = &dates[dates.len() - 1].text;
+            date_evidence(source.find_all(last, usize::MAX).pop(), r"(?i)terminat|expir|until|end")
+        });
 
         // Extract jurisdiction
         let jurisdiction_patterns = vec![
-            r"(?i)jurisdiction[:\s]+of\s+([A-Z][^,\.]+)",
-            r"(?i)governed by\s+the\s+laws?\s+of\s+([A-Z][^,\.]+)",
-            r"([A-Z][A-Za-z\s]+(?:State|Country|Province))",
+            (r"(?i)jurisdiction[:\s]+of\s+([A-Z][^,\.]+)", 0.8),
+            (r"(?i)governed by\s+the\s+laws?\s+of\s+([A-Z][^,\.]+)", 0.9),
+            (r"([A-Z][A-Za-z\s]+(?:State|Country|Province))", 0.4),
         ];
 
         let mut jurisdiction = None;
-        for pattern in jurisdiction_patterns {
+        for (pattern, confidence) in jurisdiction_patterns {
             if let Ok(re) = Regex::new(pattern) {
                 if let Some(cap) = re.captures(contract_text) {
                     jurisdiction = cap.get(1).map(|m| m.as_str().trim().to_string());
+                    evidence.jurisdiction = jurisdiction.as_ref().map(|j| Evidence::new(confidence, source.find(j, 0).into_iter().collect()));
                     break;
                 }
             }
         }
 
-        json!({
-            "parties": parties,
-            "effective_date": effective_date,
-            "termination_date": termination_date,
-            "jurisdiction": jurisdiction
-        })
+        let located = |raw: &str, confidence: f64| Evidence::new(confidence, source.find(raw, 0).into_iter().collect());
+        let governing_raw = contract_jurisdictions::governing_law(contract_text).map(|raw| (raw, 0.95))
+            .or_else(|| jurisdiction.clone().map(|raw| (raw, 0.6)));
+        let governing_law = governing_raw.as_ref().and_then(|(raw, _)| contract_jurisdictions::normalize(raw));
+        evidence.governing_law = governing_law.as_ref().and(governing_raw.map(|(raw, confidence)| located(&raw, confidence)));
+        let venue_raw = contract_jurisdictions::venue(contract_text);
+        let venue = venue_raw.as_deref().and_then(contract_jurisdictions::normalize);
+        evidence.venue = venue.as_ref().and(venue_raw.map(|raw| located(&raw, 0.85)));
+
+        let detection = contract_language::detect(contract_text);
+        evidence.language = Evidence::new(detection.confidence, Vec::new());
+        let language = detection.language;
+
+        ContractMetadata { parties, effective_date, termination_date, jurisdiction, governing_law, venue, language, evidence }
     }
 
-    fn extract_obligations(&self, contract_text: &str, parties: &[String]) -> Vec<serde_json::Value> {
+    fn extract_obligations(
+        &self,
+        rules: &CompiledRules,
+        clauses: &[Clause],
+        parties: &[String],
+        source: &SourceText,
+    ) -> Vec<Obligation> {
         let mut obligations = Vec::new();
-        
-        let obligation_keywords = vec![
-            "shall", "must", "will", "agrees to", "obligated to",
-            "required to", "duty to", "responsible for"
-        ];
+        // Sentences are found in order, so each is looked for after the last
+        let mut cursor = 0;
+        let binding_re = Regex::new(r"(?i)\b(?:shall|must)\b").unwrap();
 
-        let sentence_re = Regex::new(r"[.!?]+").unwrap();
-        let sentences: Vec<&str> = sentence_re.split(contract_text).collect();
+        // A full stop inside "3.2" or "$1.50" does not end a sentence
+        let sentence_re = Regex::new(r"[.!?]+(?:\s+|$)").unwrap();
+        let sentences = clauses
+            .iter()
+            .flat_map(|clause| sentence_re.split(&clause.text).map(move |sentence| (clause, sentence)));
 
-        for sentence in sentences {
+        for (clause, sentence) in sentences {
             let sentence = sentence.trim();
             if sentence.len() < 20 {
                 continue;
             }
 
-            let has_obligation = obligation_keywords.iter()
-                .any(|keyword| sentence.to_lowercase().contains(keyword));
-
-            if has_obligation {
+            if rules.is_obligation(sentence) {
                 // Determine party
-                let party = parties.iter()
+                let named = parties.iter()
                     .find(|p| sentence.to_lowercase().contains(&p.to_lowercase()))
-                    .cloned()
+                    .cloned();
+                let party = named.clone()
                     .unwrap_or_else(|| parties.first().cloned().unwrap_or_else(|| "Unknown".to_string()));
 
                 // Extract due date
-                let date_re = Regex::new(r"(\d{4}-\d{2}-\d{2})").unwrap();
-                let due_date = date_re.find(sentence)
-                    .map(|m| m.as_str().to_string());
-
-                // Categorize
-                let category = if sentence.to_lowercase().contains("payment") || 
-                                 sentence.to_lowercase().contains("pay") ||
-                                 sentence.to_lowercase().contains("fee") ||
-                                 sentence.to_lowercase().contains("cost") {
-                    "financial"
-                } else if sentence.to_lowercase().contains("deliver") ||
-                          sentence.to_lowercase().contains("provide") ||
-                          sentence.to_lowercase().contains("supply") {
-                    "delivery"
-                } else if sentence.to_lowercase().contains("maintain") ||
-                          sentence.to_lowercase().contains("keep") ||
-                          sentence.to_lowercase().contains("preserve") {
-                    "maintenance"
-                } else {
-                    "general"
-                };
-
-                obligations.push(json!({
-                    "party": party,
-                    "description": sentence.chars().take(200).collect::<String>(),
-                    "due_date": due_date.unwrap_or_default(),
-                    "category": category
-                }));
-
-                if obligations.len() >= MAX_OBLIGATIONS {
-                    break;
-                }
+                let due_date = self.dates.extract(sentence).into_iter().next().map(|d| d.iso);
+
+                let category = rules.category(sentence);
+                let amounts = if category == "financial" { contract_amounts::extract(sentence) } else { Vec::new() };
+                let description: String = sentence.chars().take(200).collect();
+
+                // Surer when the party is named, the verb binding and the
+                // category known
+                let confidence = 0.6
+                    + if named.is_some() { 0.2 } else { 0.0 }
+                    + if binding_re.is_match(sentence) { 0.1 } else { 0.0 }
+                    + if category != "general" { 0.1 } else { 0.0 };
+                let span = source.find(&description, cursor);
+                cursor = span.as_ref().map_or(cursor, |span| span.end);
+                obligations.push(Obligation {
+                    party,
+                    description,
+                    due_date,
+                    category: category.to_string(),
+                    clause: clause.reference.clone(),
+                    amounts,
+                    evidence: Evidence::new(confidence, span.into_iter().collect()),
+                    duplicates: 0,
+                });
             }
         }
 
         obligations
     }
 
-    fn detect_risks(&self, obligations: &[serde_json::Value], metadata: &serde_json::Value) -> Vec<serde_json::Value> {
-        let mut risk_flags = Vec::new();
-
-        for obligation in obligations {
-            // Check for missing due dates
-            let due_date = obligation.get("due_date")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            if due_date.is_empty() {
-                let desc = obligation.get("description")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .chars()
-                    .take(50)
-                    .collect::<String>();
-                risk_flags.push(json!({
-                    "severity": "medium",
-                    "category": "missing_information",
-                    "description": format!("Obligation missing due date: {}", desc)
-                }));
-            }
-
-            // Check for financial obligations
-            if obligation.get("category").and_then(|v| v.as_str()) == Some("financial") {
-                let desc = obligation.get("description")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .chars()
-                    .take(50)
-                    .collect::<String>();
-                risk_flags.push(json!({
-                    "severity": "high",
-                    "category": "financial",
-                    "description": format!("Financial obligation: {}", desc)
-                }));
-            }
+    fn detect_risks(
+        &self,
+        rules: &CompiledRules,
+        clauses: &[Clause],
+        metadata: &ContractMetadata,
+        obligations: &[Obligation],
+        source: &SourceText,
+    ) -> (Vec<ProvisionFinding>, Vec<RiskFlag>) {
+        let provisions = contract_provisions::analyze(clauses, &metadata.parties);
+        let weights = rules.pack().scoring.severity_weights;
+        let clause_span = |reference: &Option<String>| {
+            clauses.iter().find(|c| c.reference == *reference).and_then(|c| source.find(&c.text, 0))
+        };
+        let mut risk_flags: Vec<RiskFlag> = provisions
+            .iter()
+            .map(|finding| RiskFlag {
+                severity: finding.severity,
+                category: finding.provision.category().to_string(),
+                description: finding.description.clone(),
+                clause: finding.clause.clone(),
+                amounts: match &finding.provision {
+                    Provision::LiabilityCap { cap: Some(cap), .. } => vec![cap.clone()],
+                    _ => Vec::new(),
+                },
+                weight: weights.weight(finding.severity),
+                evidence: Evidence::new(0.85, clause_span(&finding.clause).into_iter().collect()),
+            })
+            .collect();
 
-            // Check for vague language
-            let desc_lower = obligation.get("description")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_lowercase();
-            let vague_words = vec!["reasonable", "best efforts", "as appropriate", "when possible"];
-            if vague_words.iter().any(|word| desc_lower.contains(word)) {
-                let desc = desc_lower.chars().take(50).collect::<String>();
-                risk_flags.push(json!({
-                    "severity": "low",
-                    "category": "ambiguity",
-                    "description": format!("Vague language detected: {}", desc)
-                }));
-            }
+        let governing_clause = clauses.iter().find(|c| contract_jurisdictions::governing_law(&c.text).is_some());
+        let jurisdiction_risks = contract_jurisdictions::assess(metadata.governing_law.as_ref(), metadata.venue.as_ref());
+        let [law, venue] = [&metadata.evidence.governing_law, &metadata.evidence.venue].map(|e| e.clone().unwrap_or_default());
+        let jurisdiction_evidence =
+            Evidence::new(law.confidence.min(venue.confidence), law.spans.into_iter().chain(venue.spans).collect());
+        risk_flags.extend(jurisdiction_risks.into_iter().map(|risk| RiskFlag {
+            severity: risk.severity,
+            category: "jurisdiction".to_string(),
+            description: risk.description,
+            clause: governing_clause.and_then(|c| c.reference.clone()),
+            amounts: Vec::new(),
+            weight: weights.weight(risk.severity),
+            evidence: jurisdiction_evidence.clone(),
+        }));
 
-            if risk_flags.len() >= MAX_RISK_FLAGS {
-                break;
-            }
+        for obligation in obligations {
+            risk_flags.extend(rules.flags(obligation));
         }
 
-        risk_flags.truncate(MAX_RISK_FLAGS);
-        risk_flags
+        (provisions, risk_flags)
     }
 
-    fn validate_structures(&self, compiled_summary: &serde_json::Value) -> serde_json::Value {
+    /// Failure codes of the compiled summary; empty when it is valid
+    fn validate_structures(&self, summary: &ContractSummary) -> Vec<String> {
         let mut failure_codes = Vec::new();
 
         // Check required fields
-        if !compiled_summary.get("parties").and_then(|v| v.as_array()).map(|a| !a.is_empty()).unwrap_or(false) {
-            failure_codes.push("MISSING_REQUIRED_FIELD");
-        }
-
-        if compiled_summary.get("key_obligations").is_none() {
-            failure_codes.push("MISSING_REQUIRED_FIELD");
-        }
-
-        if compiled_summary.get("risk_flags").is_none() {
-            failure_codes.push("MISSING_REQUIRED_FIELD");
+        if summary.metadata.parties.is_empty() {
+            failure_codes.push("MISSING_REQUIRED_FIELD".to_string());
         }
 
-        // Check cardinality
-        if let Some(obligations) = compiled_summary.get("key_obligations").and_then(|v| v.as_array()) {
-            if obligations.len() > MAX_OBLIGATIONS {
-                failure_codes.push("CARDINALITY_EXCEEDED");
-            }
-        }
-
-        if let Some(risks) = compiled_summary.get("risk_flags").and_then(|v| v.as_array()) {
-            if risks.len() > MAX_RISK_FLAGS {
-                failure_codes.push("CARDINALITY_EXCEEDED");
-            }
-        }
-
-        json!({
-            "is_valid": failure_codes.is_empty(),
-            "failure_codes": failure_codes
-        })
+        failure_codes
     }
 
-    fn compute_seal(&self, input_text: &str, output_summary: &serde_json::Value) -> String {
-        let combined = format!("{}:{}", input_text, output_summary);
+    fn compute_seal(&self, input_text: &str, summary: &ContractSummary) -> String {
+        let sealed = json!({
+            "parties": summary.metadata.parties,
+            "key_obligations": summary.key_obligations,
+            "risk_flags": summary.risk_flags,
+            "exposure": summary.exposure,
+            "provisions": summary.provisions,
+            "risk_score": summary.risk_score,
+            "rule_pack": summary.rule_pack
+        });
+        let combined = format!("{}:{}", input_text, sealed);
         let mut hasher = Sha256::new();
         hasher.update(combined.as_bytes());
         let hash = hasher.finalize();
@@ -316,3 +675,205 @@ impl ContractAnalyzer {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = "This Agreement is made between Acme Supplies LLC, and Beta Retail Inc. \
+        effective 2024-01-01. Acme Supplies LLC shall deliver the goods by 2024-02-01. \
+        Beta Retail Inc shall pay the invoice fee within thirty days. \
+        Acme Supplies LLC will use reasonable efforts to maintain stock. \
+        This Agreement terminates on 2025-01-01 and is governed by the laws of Delaware.";
+
+    #[test]
+    fn test_summary_is_typed_and_keeps_its_json_layout() {
+        let analyzer = ContractAnalyzer::new(true);
+        let summary = analyzer.analyze_contract(CONTRACT).unwrap();
+        assert_eq!(summary.metadata.effective_date.as_deref(), Some("2024-01-01"));
+        assert_eq!(summary.metadata.termination_date.as_deref(), Some("2025-01-01"));
+        assert_eq!(summary.metadata.jurisdiction.as_deref(), Some("Delaware"));
+
+        let delivery = summary.key_obligations.iter().find(|o| o.category == "delivery").unwrap();
+        assert_eq!(delivery.due_date.as_deref(), Some("2024-02-01"));
+        assert!(summary.risk_flags.iter().any(|f| f.severity == Severity::High && f.category == "financial"));
+        assert!(summary.risk_flags.iter().any(|f| f.severity == Severity::Low && f.category == "ambiguity"));
+        assert_eq!(summary, analyzer.analyze_contract(CONTRACT).unwrap());
+
+        let json = summary.to_json();
+        assert_eq!(json["status"], "success");
+        assert_eq!(json["summary"]["risk_flags"][0]["severity"], "medium");
+        assert_eq!(json["verification"]["cryptographic_seal"], summary.cryptographic_seal.as_str());
+        let round_trip: ContractSummary = serde_json::from_str(&serde_json::to_string(&summary).unwrap()).unwrap();
+        assert_eq!(round_trip, summary);
+    }
+
+    #[test]
+    fn test_obligations_and_flags_cite_their_clause() {
+        let contract = "SUPPLY AGREEMENT between Acme Supplies LLC and Beta Retail Inc.\n\
+            ARTICLE II - PAYMENT\n\
+            Section 2.1 Beta Retail Inc shall pay the fee\n\
+            \x20   for each shipment by 2024-03-01.\n\
+            Section 2.2 Acme Supplies LLC shall deliver the goods promptly.\n";
+        let summary = ContractAnalyzer::new(true).analyze_contract(contract).unwrap();
+        let clauses: Vec<Option<&str>> = summary.key_obligations.iter().map(|o| o.clause.as_deref()).collect();
+        assert_eq!(clauses, vec![Some("Section 2.1"), Some("Section 2.2")]);
+        assert_eq!(summary.key_obligations[0].due_date.as_deref(), Some("2024-03-01"));
+        for flag in &summary.risk_flags {
+            let expected = if flag.category == "financial" { "Section 2.1" } else { "Section 2.2" };
+            assert_eq!(flag.clause.as_deref(), Some(expected), "{:?}", flag);
+        }
+        assert_eq!(summary.clauses.len(), 4);
+        assert_eq!(summary.to_json()["clauses"][2]["reference"], "Section 2.1");
+    }
+
+    #[test]
+    fn test_financial_obligations_carry_amounts_and_exposure() {
+        let contract = "Beta Retail Inc shall pay a fee of $1,250.50 per month. \
+            Beta Retail Inc shall pay EUR 20k on completion and a further $750. \
+            Acme Supplies LLC shall deliver 500 units worth USD 9,000 by 2024-02-01.";
+        let summary = ContractAnalyzer::new(true).analyze_contract(contract).unwrap();
+        let amounts: Vec<Vec<(&str, f64)>> = summary
+            .key_obligations
+            .iter()
+            .map(|o| o.amounts.iter().map(|a| (a.currency.as_str(), a.value)).collect())
+            .collect();
+        assert_eq!(amounts, vec![vec![("USD", 1250.5)], vec![("EUR", 20_000.0), ("USD", 750.0)], vec![]]);
+        let financial = summary.risk_flags.iter().find(|f| f.category == "financial").unwrap();
+        assert_eq!(financial.amounts, summary.key_obligations[0].amounts);
+        let exposure: Vec<(&str, f64, usize)> =
+            summary.exposure.iter().map(|e| (e.currency.as_str(), e.total, e.amounts)).collect();
+        assert_eq!(exposure, vec![("EUR", 20_000.0, 1), ("USD", 2000.5, 2)]);
+        assert_eq!(summary.to_json()["summary"]["exposure"][1]["total"], 2000.5);
+    }
+
+    #[test]
+    fn test_written_dates_are_reported_as_iso() {
+        let contract = "This Agreement between Acme Supplies LLC and Beta Retail Inc is effective January 5, 2024. \
+            Acme Supplies LLC shall deliver the goods by 03/04/2024. \
+            This Agreement terminates on the 31st of December 2025.";
+        let summary = ContractAnalyzer::new(true).analyze_contract(contract).unwrap();
+        assert_eq!(summary.metadata.effective_date.as_deref(), Some("2024-01-05"));
+        assert_eq!(summary.metadata.termination_date.as_deref(), Some("2025-12-31"));
+        assert_eq!(summary.key_obligations[0].due_date.as_deref(), Some("2024-03-04"));
+        assert!(summary.risk_flags.iter().all(|f| f.category != "missing_information"));
+
+        let day_first = ContractAnalyzer::new(true).with_date_order(DateOrder::DayFirst);
+        let summary = day_first.analyze_contract(contract).unwrap();
+        assert_eq!(summary.key_obligations[0].due_date.as_deref(), Some("2024-04-03"));
+    }
+
+    #[test]
+    fn test_provision_flags_come_first_and_cite_their_clause() {
+        let contract = "1. Acme Supplies LLC shall deliver the goods by 2024-02-01.\n\
+            2. The buyer, Beta Retail Inc, shall indemnify the seller against any and all claims.\n\
+            3. Acme Supplies LLC's total liability shall not exceed $100,000.\n";
+        let summary = ContractAnalyzer::new(true).analyze_contract(contract).unwrap();
+        let provisions: Vec<(&str, &str)> =
+            summary.provisions.iter().map(|p| (p.clause.as_deref().unwrap(), p.provision.category())).collect();
+        assert_eq!(provisions, vec![("2", "indemnification"), ("3", "liability")]);
+
+        let flags: Vec<(&str, Severity, &str)> = summary
+            .risk_flags
+            .iter()
+            .take(2)
+            .map(|f| (f.category.as_str(), f.severity, f.clause.as_deref().unwrap()))
+            .collect();
+        assert_eq!(flags, vec![("indemnification", Severity::High, "2"), ("liability", Severity::Low, "3")]);
+        assert_eq!(summary.risk_flags[1].amounts[0].value, 100_000.0);
+        assert_eq!(summary.to_json()["summary"]["provisions"][0]["indemnitor"], "Beta Retail Inc");
+    }
+
+    #[test]
+    fn test_governing_law_is_normalized_and_a_foreign_venue_flagged() {
+        let contract = "1. Acme Supplies LLC shall deliver the goods by 2024-02-01.\n\
+            2. This Agreement is governed by the laws of the State of Delaware.\n\
+            3. The parties submit to the exclusive jurisdiction of the courts of Frankfurt, Germany.\n";
+        let summary = ContractAnalyzer::new(true).analyze_contract(contract).unwrap();
+        let code = |j: &Option<Jurisdiction>| j.as_ref().map(|j| j.code.clone());
+        assert_eq!(code(&summary.metadata.governing_law).as_deref(), Some("US-DE"));
+        assert_eq!(code(&summary.metadata.venue).as_deref(), Some("DE"));
+
+        let flag = summary.risk_flags.iter().find(|f| f.category == "jurisdiction").unwrap();
+        assert_eq!((flag.severity, flag.clause.as_deref()), (Severity::High, Some("2")));
+        assert_eq!(summary.to_json()["metadata"]["governing_law"]["legal_system"], "common_law");
+    }
+
+    #[test]
+    fn test_extractions_cite_their_evidence() {
+        let contract = "This Agreement is made between Acme Supplies LLC and Beta Retail Inc.\n\
+            1. Acme Supplies LLC shall deliver the goods\n   by 2024-02-01.\n\
+            2. Payment will be made when possible.\n";
+        let summary = ContractAnalyzer::new(true).analyze_contract(contract).unwrap();
+        let chars: Vec<char> = contract.chars().collect();
+        let cited = |span: &Span| chars[span.start..span.end].iter().collect::<String>();
+
+        let delivery = &summary.key_obligations[0];
+        let span = &delivery.evidence.spans[0];
+        assert_eq!(span.text, "Acme Supplies LLC shall deliver the goods\n   by 2024-02-01");
+        assert_eq!(cited(span), span.text);
+        assert_eq!(delivery.evidence.confidence, 1.0);
+        let vague = summary.key_obligations.iter().find(|o| o.category == "financial").unwrap();
+        assert!(vague.evidence.confidence < delivery.evidence.confidence);
+        assert!(summary.risk_flags.iter().all(|f| !f.evidence.spans.is_empty()));
+
+        let parties = &summary.metadata.evidence.parties;
+        assert_eq!(parties.len(), summary.metadata.parties.len());
+        assert!(parties.iter().all(|e| e.confidence > 0.0 && !e.spans.is_empty()));
+        let effective = summary.metadata.evidence.effective_date.as_ref().unwrap();
+        assert_eq!((effective.confidence, effective.spans[0].text.as_str()), (0.5, "2024-02-01"));
+        assert_eq!(summary.metadata.evidence.termination_date, None);
+    }
+
+    #[test]
+    fn test_rule_packs_replace_the_built_in_rules() {
+        let json = r#"{
+            "name": "indemnity-review",
+            "obligations": [{"name": "indemnify", "pattern": "\\bindemnif"}],
+            "categories": [{"name": "indemnity", "pattern": "indemnif"}],
+            "risks": [{"name": "uncapped", "severity": "critical", "category": "liability",
+                       "message": "Uncapped indemnity", "pattern": "any and all",
+                       "obligation_category": "indemnity"}]
+        }"#;
+        let analyzer = ContractAnalyzer::new(true).with_rules(RulePack::from_json(json).unwrap()).unwrap();
+        let contract = "Acme Supplies LLC shall deliver the goods. \
+            Beta Retail Inc indemnifies Acme Supplies LLC against any and all claims.";
+        let summary = analyzer.analyze_contract(contract).unwrap();
+        assert_eq!(summary.rule_pack, "indemnity-review");
+        assert_eq!(summary.key_obligations.len(), 1);
+        assert_eq!(summary.key_obligations[0].category, "indemnity");
+        // The provision analysis runs whatever the rule pack
+        let flags: Vec<&RiskFlag> = summary.risk_flags.iter().filter(|f| f.category == "liability").collect();
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].severity, Severity::Critical);
+        assert!(flags[0].description.starts_with("Uncapped indemnity: Beta Retail Inc"));
+
+        let default = ContractAnalyzer::new(true).analyze_contract(contract).unwrap();
+        assert_eq!(default.key_obligations.len(), 1);
+        assert_eq!(default.key_obligations[0].category, "delivery");
+        assert_ne!(default.cryptographic_seal, summary.cryptographic_seal);
+    }
+
+    #[test]
+    fn test_non_english_contracts_get_their_language_s_rules() {
+        let contract = "1. El Proveedor deberá entregar los productos antes del 2024-02-01.\n\
+            2. El Comprador se obliga a pagar el precio de la factura en un plazo razonable.\n";
+        let summary = ContractAnalyzer::new(true).analyze_contract(contract).unwrap();
+        assert_eq!((summary.metadata.language, summary.rule_pack.as_str()), (Language::Spanish, "default-es"));
+        let categories: Vec<&str> = summary.key_obligations.iter().map(|o| o.category.as_str()).collect();
+        assert_eq!(categories, vec!["delivery", "financial"]);
+        assert!(summary.risk_flags.iter().any(|f| f.description.starts_with("Lenguaje impreciso detectado")));
+        assert_eq!(summary.to_json()["metadata"]["language"], "es");
+
+        let pinned = ContractAnalyzer::new(true).with_rules(RulePack::default()).unwrap();
+        let summary = pinned.analyze_contract(contract).unwrap();
+        assert_eq!((summary.metadata.language, summary.rule_pack.as_str()), (Language::Spanish, "default"));
+        assert!(summary.key_obligations.is_empty());
+    }
+
+    #[test]
+    fn test_empty_input_is_an_error() {
+        let error = ContractAnalyzer::new(true).analyze_contract(" \n\t ").unwrap_err();
+        assert_eq!(error, ContractError::EmptyInput);
+        assert_eq!(error.to_json()["failure_codes"][0], "EMPTY_INPUT");
+    }
+}
@@ -0,0 +1,279 @@
+//! AxiomHive Contract Anonymization
+//! Deterministic Legal Contract Summarization Pipeline
+//! `ContractAnalyzer::anonymize` replaces the parties' names, street
+//! addresses, email addresses and sums of money in a contract with
+//! placeholders such as `[PARTY-1f3a9c07e25b6d40]`, so the redacted text,
+//! and any summary of it, can be shared outside. A placeholder is an
+//! HMAC of what it replaces under the organization's `OrganizationKey`:
+//! the same name gets the same placeholder wherever it appears and in
+//! every contract the organization redacts, but without the key nobody
+//! can test a guessed name against it.
+//!
+//! What each placeholder replaced is kept in a `MappingSeal`, with an HMAC
+//! over the mapping under the same key. It never leaves the organization:
+//! holding it and the key, `reidentify` restores the text after checking
+//! the mapping has not been altered. Whitespace inside a replaced value is
+//! restored as single spaces.
+
+use crate::contract_amounts;
+use crate::contract_evidence::SourceText;
+use crate::fhe_mnemonic::mnemonic_key;
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::fmt;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The secret an organization's placeholders and mapping seals are keyed
+/// with. Every contract it redacts must use the same key for placeholders
+/// to stay stable across contracts.
+#[derive(Clone)]
+pub struct OrganizationKey(Zeroizing<[u8; 32]>);
+
+impl OrganizationKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(Zeroizing::new(key))
+    }
+
+    /// Recover the key from the organization's BIP39 phrase
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, String> {
+        mnemonic_key(phrase, "", "AxiomHive Contract Anonymization v1").map(Self)
+    }
+
+    fn mac(&self, purpose: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.0[..]).expect("HMAC takes keys of any length");
+        mac.update(purpose.as_bytes());
+        mac.update(b":");
+        mac
+    }
+}
+
+impl fmt::Debug for OrganizationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OrganizationKey(..)")
+    }
+}
+
+/// What was replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Party,
+    Address,
+    Email,
+    Amount,
+}
+
+impl EntityKind {
+    fn label(self) -> &'static str {
+        match self {
+            EntityKind::Party => "PARTY",
+            EntityKind::Address => "ADDRESS",
+            EntityKind::Email => "EMAIL",
+            EntityKind::Amount => "AMOUNT",
+        }
+    }
+}
+
+/// A placeholder and the value it replaced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redaction {
+    pub kind: EntityKind,
+    pub placeholder: String,
+    /// The value with its whitespace normalized
+    pub original: String,
+    /// How many times it was replaced
+    pub occurrences: usize,
+}
+
+/// The mapping from placeholders back to what they replaced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingSeal {
+    /// Ordered by placeholder
+    pub redactions: Vec<Redaction>,
+    /// HMAC-SHA256 over the redactions under the organization key, in hex
+    pub seal: String,
+}
+
+/// Why a redacted text could not be re-identified
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnonymizeError {
+    /// The redactions do not match the seal, or the key is not the one
+    /// they were sealed under
+    SealMismatch,
+}
+
+impl fmt::Display for AnonymizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnonymizeError::SealMismatch => write!(f, "Redaction mapping does not match its seal"),
+        }
+    }
+}
+
+impl std::error::Error for AnonymizeError {}
+
+impl MappingSeal {
+    fn new(redactions: Vec<Redaction>, key: &OrganizationKey) -> Self {
+        let seal = Self::hash(&redactions, key);
+        Self { redactions, seal }
+    }
+
+    fn hash(redactions: &[Redaction], key: &OrganizationKey) -> String {
+        let json = serde_json::to_string(redactions).expect("redactions serialize");
+        let mut mac = key.mac("seal");
+        mac.update(json.as_bytes());
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+
+    /// Whether the redactions are those sealed under `key`
+    pub fn verify(&self, key: &OrganizationKey) -> bool {
+        bool::from(Self::hash(&self.redactions, key).as_bytes().ct_eq(self.seal.as_bytes()))
+    }
+
+    /// `redacted` with every placeholder replaced by its original value
+    pub fn reidentify(&self, redacted: &str, key: &OrganizationKey) -> Result<String, AnonymizeError> {
+        if !self.verify(key) {
+            return Err(AnonymizeError::SealMismatch);
+        }
+        let placeholder_re = Regex::new(r"\[(?:PARTY|ADDRESS|EMAIL|AMOUNT)-[0-9a-f]{16}\]").unwrap();
+        let originals: BTreeMap<&str, &str> =
+            self.redactions.iter().map(|r| (r.placeholder.as_str(), r.original.as_str())).collect();
+        Ok(placeholder_re
+            .replace_all(redacted, |caps: &regex::Captures| {
+                originals.get(&caps[0]).map_or_else(|| caps[0].to_string(), |original| original.to_string())
+            })
+            .into_owned())
+    }
+}
+
+/// The placeholder of `original`, a value of `kind`: 64 bits of its HMAC,
+/// so distinct values in one organization's contracts do not collide
+fn placeholder(kind: EntityKind, original: &str, key: &OrganizationKey) -> String {
+    let mut mac = key.mac(kind.label());
+    mac.update(original.as_bytes());
+    let hex: String = mac.finalize().into_bytes().iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    format!("[{}-{}]", kind.label(), hex)
+}
+
+/// Redact `parties`, addresses, emails and amounts from `text`
+pub(crate) fn anonymize(text: &str, parties: &[String], key: &OrganizationKey) -> (String, MappingSeal) {
+    let email_re = Regex::new(r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+").unwrap();
+    let address_re = Regex::new(
+        r"\b\d{1,6}\s+(?:[A-Z][A-Za-z]*\.?\s+){1,4}(?:Street|St|Avenue|Ave|Road|Rd|Boulevard|Blvd|Lane|Ln|Drive|Dr|Way|Court|Ct|Place|Pl)\b\.?(?:,?\s+(?:Suite|Ste\.?|Unit|Floor)\s+\w+)?(?:,\s+[A-Z][A-Za-z]+(?:\s[A-Z][A-Za-z]+)*(?:,\s+[A-Z]{2}\s+\d{5}(?:-\d{4})?)?)?",
+    )
+    .unwrap();
+
+    // Byte ranges of the values found; where two overlap, the earlier
+    // wins, then the longer
+    let mut found: Vec<(usize, usize, EntityKind)> = Vec::new();
+    found.extend(email_re.find_iter(text).map(|m| (m.start(), m.end(), EntityKind::Email)));
+    found.extend(address_re.find_iter(text).map(|m| (m.start(), m.end(), EntityKind::Address)));
+    // Party names are sometimes read with words before them, as in
+    // "Notices to Acme Supplies LLC"; only the name itself is redacted
+    let company_re = Regex::new(r"(?:[A-Z][A-Za-z&]*\s+)+(?:LLC|Inc|Corp|Ltd|Company|GmbH|SARL)$").unwrap();
+    let mut names: Vec<&str> =
+        parties.iter().map(|p| company_re.find(p).map_or(p.as_str(), |m| m.as_str())).collect();
+    names.sort_unstable();
+    names.dedup();
+    for party in names {
+        if let Some(re) = SourceText::pattern(party) {
+            found.extend(re.find_iter(text).map(|m| (m.start(), m.end(), EntityKind::Party)));
+        }
+    }
+    for amount in contract_amounts::extract(text) {
+        let re = Regex::new(&regex::escape(&amount.text)).unwrap();
+        found.extend(re.find_iter(text).map(|m| (m.start(), m.end(), EntityKind::Amount)));
+    }
+    found.sort_by_key(|&(start, end, kind)| (start, std::cmp::Reverse(end), kind));
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut redactions: BTreeMap<String, Redaction> = BTreeMap::new();
+    let mut cursor = 0;
+    for (start, end, kind) in found {
+        if start < cursor {
+            continue;
+        }
+        let original = text[start..end].split_whitespace().collect::<Vec<_>>().join(" ");
+        let placeholder = placeholder(kind, &original, key);
+        redacted.push_str(&text[cursor..start]);
+        redacted.push_str(&placeholder);
+        redactions
+            .entry(placeholder.clone())
+            .or_insert(Redaction { kind, placeholder, original, occurrences: 0 })
+            .occurrences += 1;
+        cursor = end;
+    }
+    redacted.push_str(&text[cursor..]);
+    (redacted, MappingSeal::new(redactions.into_values().collect(), key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_analyzer::ContractAnalyzer;
+    use sha2::Digest;
+
+    /// The first BIP39 English test vector
+    const ZERO_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    const CONTRACT: &str = "This Agreement is made between Acme Supplies LLC, of 1200 Harbor View Road, Suite 4, \
+        Portland, OR 97201, and Beta Retail Inc. Notices to Acme Supplies LLC go to legal@acme-supplies.com. \
+        Beta Retail Inc shall pay Acme Supplies LLC a fee of $12,500 by 2024-03-01.";
+
+    fn key() -> OrganizationKey {
+        OrganizationKey::new([7; 32])
+    }
+
+    #[test]
+    fn test_values_are_replaced_by_stable_placeholders() {
+        let analyzer = ContractAnalyzer::new(true);
+        let (redacted, mapping) = analyzer.anonymize(CONTRACT, &key());
+        for secret in ["Acme", "Beta", "Harbor", "97201", "legal@", "12,500"] {
+            assert!(!redacted.contains(secret), "{} in {}", secret, redacted);
+        }
+        assert!(redacted.contains("by 2024-03-01"));
+        let acme = mapping.redactions.iter().find(|r| r.original == "Acme Supplies LLC").unwrap();
+        assert_eq!((acme.kind, acme.occurrences), (EntityKind::Party, 3));
+        let kinds: Vec<EntityKind> = mapping.redactions.iter().map(|r| r.kind).collect();
+        for kind in [EntityKind::Address, EntityKind::Email, EntityKind::Amount] {
+            assert!(kinds.contains(&kind), "{:?}", kind);
+        }
+        assert_eq!(analyzer.anonymize(CONTRACT, &key()), (redacted.clone(), mapping.clone()));
+
+        let beta = mapping.redactions.iter().find(|r| r.original == "Beta Retail Inc").unwrap();
+        let summary = analyzer.analyze_contract(&redacted).unwrap();
+        assert!(summary.key_obligations[0].description.starts_with(&beta.placeholder));
+        assert_eq!(mapping.reidentify(&redacted, &key()).unwrap(), CONTRACT);
+    }
+
+    #[test]
+    fn test_placeholders_and_seal_need_the_key() {
+        let (redacted, mapping) = ContractAnalyzer::new(true).anonymize(CONTRACT, &key());
+        // Hashing a guessed name does not find its placeholder
+        let acme = mapping.redactions.iter().find(|r| r.original == "Acme Supplies LLC").unwrap();
+        let unkeyed = Sha256::digest(b"PARTY:Acme Supplies LLC");
+        let unkeyed: String = unkeyed.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+        assert_ne!(acme.placeholder, format!("[PARTY-{}]", unkeyed));
+
+        let other = OrganizationKey::from_mnemonic(ZERO_PHRASE).unwrap();
+        let (other_redacted, other_mapping) = ContractAnalyzer::new(true).anonymize(CONTRACT, &other);
+        assert!(!other_redacted.contains(&acme.placeholder));
+        assert_eq!(other_mapping.reidentify(&other_redacted, &other).unwrap(), CONTRACT);
+        assert_eq!(mapping.reidentify(&redacted, &other), Err(AnonymizeError::SealMismatch));
+        assert!(format!("{:?}", other).contains(".."));
+    }
+
+    #[test]
+    fn test_an_altered_mapping_is_refused() {
+        let (redacted, mut mapping) = ContractAnalyzer::new(true).anonymize(CONTRACT, &key());
+        assert!(mapping.verify(&key()));
+        mapping.redactions[0].original = "Someone Else Ltd".to_string();
+        assert_eq!(mapping.reidentify(&redacted, &key()), Err(AnonymizeError::SealMismatch));
+    }
+}
@@ -0,0 +1,367 @@
+//! AxiomHive Contract Bundles
+//! Deterministic Legal Contract Summarization Pipeline
+//! `ContractAnalyzer::analyze_bundle` analyzes a master agreement together
+//! with its amendments, statements of work and other related documents.
+//!
+//! One document is linked to another when at least two of these hold: it
+//! mentions the other's title, it mentions the other's effective date
+//! (other than as its own), or the two share a party. A document titled
+//! or headed as an amendment, addendum or change order amends the linked
+//! document with the most evidence, preferring one that is not itself an
+//! amendment; every other link is a reference.
+//!
+//! The bundle's obligations are those of every document, each with the
+//! document it came from. An amendment's obligation supersedes the most
+//! similar one of the same party and category in the document it amends,
+//! and a conflict is reported when the two disagree on the due date or
+//! the amounts, as is one when the two documents name different
+//! governing law, compared by jurisdiction code where both are known.
+
+use crate::contract_analyzer::{ContractError, ContractSummary, Obligation};
+use crate::contract_dates::DateParser;
+use crate::contract_diff;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Obligations sharing fewer words than this are not the same obligation
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// A document of a bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractDoc {
+    /// E.g. "Master Supply Agreement"; what other documents call it
+    pub title: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Amends,
+    References,
+}
+
+/// Why one document was linked to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkEvidence {
+    pub title_mentioned: bool,
+    /// The linked document's effective date, when mentioned
+    pub date_mentioned: Option<String>,
+    pub shared_parties: Vec<String>,
+}
+
+impl LinkEvidence {
+    fn strength(&self) -> usize {
+        self.title_mentioned as usize + self.date_mentioned.is_some() as usize + !self.shared_parties.is_empty() as usize
+    }
+}
+
+/// Document `from` amends or references document `to`, by index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentLink {
+    pub from: usize,
+    pub to: usize,
+    pub kind: LinkKind,
+    pub evidence: LinkEvidence,
+}
+
+/// A document of the bundle and its analysis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleDocument {
+    pub title: String,
+    pub amendment: bool,
+    pub summary: ContractSummary,
+}
+
+/// An obligation of the bundle and where it came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleObligation {
+    /// Index of the document
+    pub document: usize,
+    #[serde(flatten)]
+    pub obligation: Obligation,
+    /// Index of the amendment replacing it
+    pub superseded_by: Option<usize>,
+}
+
+/// A place in a document of the bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub document: usize,
+    pub clause: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictField {
+    DueDate,
+    Amounts,
+    GoverningLaw,
+}
+
+/// Terms an amendment changes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleConflict {
+    pub field: ConflictField,
+    /// In the amended document
+    pub original: Provenance,
+    pub amendment: Provenance,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// Output of `ContractAnalyzer::analyze_bundle`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractBundle {
+    /// In the order given
+    pub documents: Vec<BundleDocument>,
+    pub links: Vec<DocumentLink>,
+    /// Document by document
+    pub obligations: Vec<BundleObligation>,
+    pub conflicts: Vec<BundleConflict>,
+}
+
+/// A document of a bundle could not be analyzed
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleError {
+    pub document: usize,
+    pub title: String,
+    pub error: ContractError,
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Document {} ({}): {}", self.document, self.title, self.error)
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl ContractBundle {
+    /// Bundle of `docs`, whose summaries are `summaries`; `dates` reads
+    /// the dates the documents mention
+    pub fn new(docs: &[ContractDoc], summaries: Vec<ContractSummary>, dates: &DateParser) -> Self {
+        let amendment_re = Regex::new(r"(?i)\b(?:amendment|addendum|change order)\b").unwrap();
+        let documents: Vec<BundleDocument> = docs
+            .iter()
+            .zip(summaries)
+            .map(|(doc, summary)| {
+                let heading: String = doc.text.chars().take(200).collect();
+                BundleDocument {
+                    title: doc.title.clone(),
+                    amendment: amendment_re.is_match(&doc.title) || amendment_re.is_match(&heading),
+                    summary,
+                }
+            })
+            .collect();
+
+        let links = link(docs, &documents, dates);
+        let mut obligations: Vec<BundleObligation> = documents
+            .iter()
+            .enumerate()
+            .flat_map(|(document, doc)| {
+                doc.summary.key_obligations.iter().map(move |obligation| BundleObligation {
+                    document,
+                    obligation: obligation.clone(),
+                    superseded_by: None,
+                })
+            })
+            .collect();
+        let mut conflicts = Vec::new();
+        for link in links.iter().filter(|link| link.kind == LinkKind::Amends) {
+            conflicts.extend(supersede(&mut obligations, link.from, link.to));
+            let (old, new) = (&documents[link.to].summary.metadata, &documents[link.from].summary.metadata);
+            if let (Some(old_law), Some(new_law)) = (&old.jurisdiction, &new.jurisdiction) {
+                let same = match (&old.governing_law, &new.governing_law) {
+                    (Some(a), Some(b)) => a.code == b.code,
+                    _ => old_law.eq_ignore_ascii_case(new_law),
+                };
+                if !same {
+                    conflicts.push(BundleConflict {
+                        field: ConflictField::GoverningLaw,
+                        original: Provenance { document: link.to, clause: None },
+                        amendment: Provenance { document: link.from, clause: None },
+                        old_value: Some(old_law.clone()),
+                        new_value: Some(new_law.clone()),
+                    });
+                }
+            }
+        }
+
+        Self { documents, links, obligations, conflicts }
+    }
+
+    /// Obligations no amendment has replaced
+    pub fn current_obligations(&self) -> impl Iterator<Item = &BundleObligation> {
+        self.obligations.iter().filter(|o| o.superseded_by.is_none())
+    }
+}
+
+/// Links between the documents, by linking document then linked one
+fn link(docs: &[ContractDoc], documents: &[BundleDocument], dates: &DateParser) -> Vec<DocumentLink> {
+    let mut links = Vec::new();
+    for (from, doc) in docs.iter().enumerate() {
+        let text = doc.text.to_lowercase();
+        let own = &documents[from].summary.metadata;
+        let mentioned: Vec<String> = dates.extract(&doc.text).into_iter().map(|d| d.iso).collect();
+
+        let mut candidates: Vec<DocumentLink> = Vec::new();
+        for (to, other) in documents.iter().enumerate().filter(|&(to, _)| to != from) {
+            let title = other.title.trim().to_lowercase();
+            let date_mentioned = other
+                .summary
+                .metadata
+                .effective_date
+                .clone()
+                .filter(|date| Some(date) != own.effective_date.as_ref() && mentioned.contains(date));
+            let shared_parties = own
+                .parties
+                .iter()
+                .filter(|p| other.summary.metadata.parties.iter().any(|q| q.eq_ignore_ascii_case(p)))
+                .cloned()
+                .collect();
+            let evidence = LinkEvidence { title_mentioned: !title.is_empty() && text.contains(&title), date_mentioned, shared_parties };
+            if evidence.strength() >= 2 {
+                candidates.push(DocumentLink { from, to, kind: LinkKind::References, evidence });
+            }
+        }
+
+        if documents[from].amendment {
+            // Most evidence, then not an amendment, then earliest
+            let amended = candidates
+                .iter()
+                .enumerate()
+                .max_by(|(i, a), (j, b)| {
+                    a.evidence
+                        .strength()
+                        .cmp(&b.evidence.strength())
+                        .then(documents[b.to].amendment.cmp(&documents[a.to].amendment))
+                        .then(j.cmp(i))
+                })
+                .map(|(i, _)| i);
+            if let Some(i) = amended {
+                candidates[i].kind = LinkKind::Amends;
+            }
+        }
+        links.extend(candidates);
+    }
+    links
+}
+
+/// Mark the obligations of document `amended` that obligations of
+/// `amendment` replace, and return where they disagree
+fn supersede(obligations: &mut [BundleObligation], amendment: usize, amended: usize) -> Vec<BundleConflict> {
+    let mut conflicts = Vec::new();
+    let amending: Vec<usize> = (0..obligations.len()).filter(|&i| obligations[i].document == amendment).collect();
+    for new in amending {
+        let new_obligation = &obligations[new].obligation;
+        let old = (0..obligations.len())
+            .filter(|&i| {
+                let o = &obligations[i];
+                o.document == amended
+                    && o.superseded_by.is_none()
+                    && o.obligation.party == new_obligation.party
+                    && o.obligation.category == new_obligation.category
+            })
+            .map(|i| (i, contract_diff::similarity(&obligations[i].obligation.description, &new_obligation.description)))
+            .filter(|&(_, score)| score >= MIN_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+            .map(|(i, _)| i);
+        let Some(old) = old else { continue };
+
+        let (old_obligation, new_obligation) = (&obligations[old].obligation, &obligations[new].obligation);
+        let original = Provenance { document: amended, clause: old_obligation.clause.clone() };
+        let changed = Provenance { document: amendment, clause: new_obligation.clause.clone() };
+        if old_obligation.due_date != new_obligation.due_date {
+            conflicts.push(BundleConflict {
+                field: ConflictField::DueDate,
+                original: original.clone(),
+                amendment: changed.clone(),
+                old_value: old_obligation.due_date.clone(),
+                new_value: new_obligation.due_date.clone(),
+            });
+        }
+        let amounts = |o: &Obligation| o.amounts.iter().map(|a| format!("{} {:.2}", a.currency, a.value)).collect::<Vec<_>>();
+        let (old_amounts, new_amounts) = (amounts(old_obligation), amounts(new_obligation));
+        if old_amounts != new_amounts {
+            let joined = |amounts: Vec<String>| (!amounts.is_empty()).then(|| amounts.join(", "));
+            conflicts.push(BundleConflict {
+                field: ConflictField::Amounts,
+                original,
+                amendment: changed,
+                old_value: joined(old_amounts),
+                new_value: joined(new_amounts),
+            });
+        }
+        obligations[old].superseded_by = Some(amendment);
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_analyzer::ContractAnalyzer;
+
+    fn doc(title: &str, text: &str) -> ContractDoc {
+        ContractDoc { title: title.to_string(), text: text.to_string() }
+    }
+
+    #[test]
+    fn test_amendment_supersedes_master_obligations_and_reports_conflicts() {
+        let docs = [
+            doc(
+                "Master Supply Agreement",
+                "This Master Supply Agreement is made on January 5, 2024 between Acme Supplies LLC and Beta Retail Inc.\n\
+                 1. Acme Supplies LLC shall deliver the goods by March 1, 2024.\n\
+                 2. Beta Retail Inc shall pay the fee of $5,000 by April 1, 2024.\n\
+                 3. This Agreement is governed by the laws of Delaware.\n",
+            ),
+            doc(
+                "First Amendment",
+                "This First Amendment is made on February 1, 2024 to the agreement dated January 5, 2024 \
+                 between Acme Supplies LLC and Beta Retail Inc.\n\
+                 1. Beta Retail Inc shall pay the fee of $6,500 by May 1, 2024.\n\
+                 2. This Amendment is governed by the laws of New York.\n",
+            ),
+            doc(
+                "Statement of Work",
+                "This Statement of Work is issued under the Master Supply Agreement.\n\
+                 1. Acme Supplies LLC shall install the shelving by June 1, 2024.\n",
+            ),
+        ];
+        let bundle = ContractAnalyzer::new(true).analyze_bundle(&docs).unwrap();
+
+        let links: Vec<(usize, usize, LinkKind)> = bundle.links.iter().map(|l| (l.from, l.to, l.kind)).collect();
+        assert_eq!(links, vec![(1, 0, LinkKind::Amends), (2, 0, LinkKind::References)]);
+        let amends = &bundle.links[0].evidence;
+        assert_eq!(amends.date_mentioned.as_deref(), Some("2024-01-05"));
+        assert!(!amends.title_mentioned && !amends.shared_parties.is_empty());
+        assert!(bundle.links[1].evidence.title_mentioned);
+        assert!(bundle.documents[1].amendment && !bundle.documents[0].amendment);
+
+        let fee = bundle.obligations.iter().find(|o| o.document == 0 && o.obligation.category == "financial").unwrap();
+        assert_eq!(fee.superseded_by, Some(1));
+        assert_eq!(bundle.current_obligations().count(), bundle.obligations.len() - 1);
+        let conflicts: Vec<(ConflictField, Option<&str>, Option<&str>)> =
+            bundle.conflicts.iter().map(|c| (c.field, c.old_value.as_deref(), c.new_value.as_deref())).collect();
+        assert_eq!(
+            conflicts,
+            vec![
+                (ConflictField::DueDate, Some("2024-04-01"), Some("2024-05-01")),
+                (ConflictField::Amounts, Some("USD 5000.00"), Some("USD 6500.00")),
+                (ConflictField::GoverningLaw, Some("Delaware"), Some("New York")),
+            ]
+        );
+        assert_eq!(bundle.conflicts[0].original.clause.as_deref(), Some("2"));
+        assert_eq!(bundle.conflicts[0].amendment.clause.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_bundle_errors_name_the_document() {
+        let docs = [doc("Master", "Acme Supplies LLC shall deliver the goods."), doc("Blank", "  ")];
+        let error = ContractAnalyzer::new(true).analyze_bundle(&docs).unwrap_err();
+        assert_eq!((error.document, error.title.as_str(), error.error.clone()), (1, "Blank", ContractError::EmptyInput));
+    }
+}
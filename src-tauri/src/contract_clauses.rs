@@ -0,0 +1,189 @@
+//! AxiomHive Contract Clause Segmentation
+//! Deterministic Legal Contract Summarization Pipeline
+//! Splits a contract into numbered clauses before its whitespace is
+//! normalized, so every obligation and risk flag can say where it came
+//! from. A clause starts on a line that opens with a heading marker:
+//!
+//! - `ARTICLE IV`, `Article 2`
+//! - `Section 3.2`, `§ 3.2`
+//! - `1.`, `1)`, `3.2`, `3.2.1.`
+//! - `(a)`, `(iv)`, `(3)`
+//!
+//! and runs until the next one. A clause's parent is the nearest open
+//! clause indented less than it, or indented as much but of a higher rank
+//! (articles above sections, `3` above `3.2`, numbers above lettered
+//! items). Markers in the middle of a line are not headings, so a contract
+//! pasted as a single line is one clause.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// One numbered clause, or the text before the first heading
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clause {
+    /// How the clause is cited, e.g. "Article IV", "Section 3.2", "3.2"
+    /// or "3.2(a)"; None for text before the first heading
+    pub reference: Option<String>,
+    /// 0 for top-level clauses
+    pub level: usize,
+    /// Index of the enclosing clause in the segmented list
+    pub parent: Option<usize>,
+    /// 1-based line the clause starts on
+    pub line: usize,
+    /// The clause's own text, without its marker or sub-clauses, with
+    /// whitespace normalized
+    pub text: String,
+}
+
+/// A recognized heading marker
+struct Heading<'a> {
+    reference: String,
+    /// Lower ranks enclose higher ones at the same indentation
+    rank: usize,
+    /// Whether the reference is relative to the parent's, as in "(a)"
+    nested: bool,
+    rest: &'a str,
+}
+
+/// Rank of `(a)`-style items, below any dotted number
+const ITEM_RANK: usize = usize::MAX;
+
+struct Markers {
+    article: Regex,
+    section: Regex,
+    number: Regex,
+    item: Regex,
+}
+
+impl Markers {
+    fn new() -> Self {
+        Self {
+            article: Regex::new(r"^(?i:article)\s+([IVXLCDMivxlcdm]+|\d{1,3})\b\s*[-–.:]?\s*(.*)$").unwrap(),
+            section: Regex::new(r"^(?:(?i:section)\s+|§\s*)(\d{1,3}(?:\.\d{1,3})*)\.?(?:\s+(.*))?$").unwrap(),
+            number: Regex::new(r"^(\d{1,3}(?:\.\d{1,3})*)[.)]\s+(.*)$|^(\d{1,3}(?:\.\d{1,3})+)\s+(.*)$").unwrap(),
+            item: Regex::new(r"^\(([a-z]{1,4}|\d{1,3})\)\s+(.*)$").unwrap(),
+        }
+    }
+
+    fn parse<'a>(&self, line: &'a str) -> Option<Heading<'a>> {
+        let rest = |m: Option<regex::Match<'a>>| m.map_or("", |m| m.as_str());
+        if let Some(cap) = self.article.captures(line) {
+            return Some(Heading { reference: format!("Article {}", cap[1].to_uppercase()), rank: 0, nested: false, rest: rest(cap.get(2)) });
+        }
+        if let Some(cap) = self.section.captures(line) {
+            let rank = cap[1].split('.').count();
+            return Some(Heading { reference: format!("Section {}", &cap[1]), rank, nested: false, rest: rest(cap.get(2)) });
+        }
+        if let Some(cap) = self.number.captures(line) {
+            let (number, text) = match cap.get(1) {
+                Some(number) => (number, cap.get(2)),
+                None => (cap.get(3).expect("one alternative matched"), cap.get(4)),
+            };
+            let rank = number.as_str().split('.').count();
+            return Some(Heading { reference: number.as_str().to_string(), rank, nested: false, rest: rest(text) });
+        }
+        self.item
+            .captures(line)
+            .map(|cap| Heading { reference: format!("({})", &cap[1]), rank: ITEM_RANK, nested: true, rest: rest(cap.get(2)) })
+    }
+}
+
+/// Clauses of `contract_text` in document order
+pub fn segment(contract_text: &str) -> Vec<Clause> {
+    let markers = Markers::new();
+    let whitespace = Regex::new(r"\s+").unwrap();
+    // Clauses with their indentation and rank; `open` holds the indices of
+    // the clauses a new heading may nest under, innermost last
+    let mut clauses: Vec<(Clause, usize, usize, Vec<&str>)> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+
+    for (number, line) in contract_text.lines().enumerate() {
+        let trimmed = line.trim();
+        let Some(heading) = markers.parse(trimmed) else {
+            match clauses.last_mut() {
+                Some((_, _, _, lines)) => lines.push(trimmed),
+                None if !trimmed.is_empty() => {
+                    let preamble = Clause { reference: None, level: 0, parent: None, line: number + 1, text: String::new() };
+                    clauses.push((preamble, 0, 0, vec![trimmed]));
+                }
+                None => {}
+            }
+            continue;
+        };
+
+        let indent = line.len() - line.trim_start().len();
+        while let Some(&top) = open.last() {
+            let (_, top_indent, top_rank, _) = &clauses[top];
+            if *top_indent < indent || (*top_indent == indent && *top_rank < heading.rank) {
+                break;
+            }
+            open.pop();
+        }
+        let parent = open.last().copied();
+        let reference = match (heading.nested, parent) {
+            (true, Some(parent)) => format!("{}{}", clauses[parent].0.reference.as_deref().unwrap_or(""), heading.reference),
+            _ => heading.reference,
+        };
+        let level = parent.map_or(0, |parent| clauses[parent].0.level + 1);
+        let clause = Clause { reference: Some(reference), level, parent, line: number + 1, text: String::new() };
+        open.push(clauses.len());
+        clauses.push((clause, indent, heading.rank, vec![heading.rest]));
+    }
+
+    clauses
+        .into_iter()
+        .map(|(mut clause, _, _, lines)| {
+            clause.text = whitespace.replace_all(lines.join(" ").trim(), " ").to_string();
+            clause
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings_nest_by_rank_and_indentation() {
+        let contract = "MASTER SERVICES AGREEMENT\n\
+            between Acme Supplies LLC and Beta Retail Inc.\n\
+            ARTICLE I - DEFINITIONS\n\
+            1. Services means the work described\n   in Schedule A.\n\
+            1.1 Deliverables are listed in Schedule B.\n\
+            ARTICLE II\n\
+            Section 2.1 Payment. Beta Retail Inc shall pay each invoice.\n\
+            \x20   (a) within thirty days of receipt;\n\
+            \x20   (b) in United States dollars.\n\
+            Section 2.2 Acme Supplies LLC shall keep records.\n";
+        let clauses = segment(contract);
+        let outline: Vec<(Option<&str>, usize, Option<usize>)> =
+            clauses.iter().map(|c| (c.reference.as_deref(), c.level, c.parent)).collect();
+        assert_eq!(
+            outline,
+            vec![
+                (None, 0, None),
+                (Some("Article I"), 0, None),
+                (Some("1"), 1, Some(1)),
+                (Some("1.1"), 2, Some(2)),
+                (Some("Article II"), 0, None),
+                (Some("Section 2.1"), 1, Some(4)),
+                (Some("Section 2.1(a)"), 2, Some(5)),
+                (Some("Section 2.1(b)"), 2, Some(5)),
+                (Some("Section 2.2"), 1, Some(4)),
+            ]
+        );
+        assert_eq!(clauses[0].text, "MASTER SERVICES AGREEMENT between Acme Supplies LLC and Beta Retail Inc.");
+        assert_eq!(clauses[2].text, "Services means the work described in Schedule A.");
+        assert_eq!((clauses[2].line, clauses[5].line), (4, 8));
+        assert_eq!(clauses[1].text, "DEFINITIONS");
+        assert_eq!(clauses[6].text, "within thirty days of receipt;");
+    }
+
+    #[test]
+    fn test_single_line_contract_is_one_clause() {
+        let clauses = segment("Party A shall pay 1. Party B shall deliver.");
+        assert_eq!(clauses.len(), 1);
+        assert_eq!(clauses[0].reference, None);
+        assert!(segment(" \n ").is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+//! AxiomHive Contract Date Parsing
+//! Deterministic Legal Contract Summarization Pipeline
+//! Finds calendar dates written the ways contracts write them and
+//! normalizes each to ISO 8601:
+//!
+//! - `2024-01-05`
+//! - `January 5, 2024`, `Jan. 5th 2024`
+//! - `5 January 2024`, `5th of March 2023`, `5th day of March, 2023`
+//! - `03/04/2025`, `03.04.2025`, `03-04-2025`
+//!
+//! All-numeric dates are read in the `DateOrder` given, unless the first
+//! number cannot be a month, as in `25/12/2024`. Only four-digit years
+//! are recognized, and dates that do not exist, such as `2023-02-29`,
+//! are skipped.
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// How to read an all-numeric date such as 03/04/2025
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateOrder {
+    /// March 4, as in the United States
+    #[default]
+    MonthFirst,
+    /// 3 April, as in most other jurisdictions
+    DayFirst,
+}
+
+/// A date found in the text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractDate {
+    /// YYYY-MM-DD
+    pub iso: String,
+    /// The text the date was read from
+    pub text: String,
+}
+
+const MONTH: &str = r"(jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)\.?";
+const DAY: &str = r"(\d{1,2})(?:st|nd|rd|th)?";
+
+fn month_number(name: &str) -> u32 {
+    let months = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let prefix = name.get(..3).unwrap_or(name).to_lowercase();
+    months.iter().position(|m| *m == prefix).expect("the pattern only matches month names") as u32 + 1
+}
+
+/// Dates parsed from contract text
+pub struct DateParser {
+    order: DateOrder,
+    iso: Regex,
+    month_first: Regex,
+    day_first: Regex,
+    numeric: Regex,
+}
+
+impl DateParser {
+    pub fn new(order: DateOrder) -> Self {
+        Self {
+            order,
+            iso: Regex::new(r"\b(\d{4})-(\d{1,2})-(\d{1,2})\b").unwrap(),
+            month_first: Regex::new(&format!(r"(?i)\b{MONTH}\s+{DAY},?\s+(\d{{4}})\b")).unwrap(),
+            day_first: Regex::new(&format!(r"(?i)\b{DAY}\s+(?:day\s+)?(?:of\s+)?{MONTH},?\s+(\d{{4}})\b")).unwrap(),
+            numeric: Regex::new(r"\b(\d{1,2})([/.-])(\d{1,2})([/.-])(\d{4})\b").unwrap(),
+        }
+    }
+
+    pub fn order(&self) -> DateOrder {
+        self.order
+    }
+
+    /// Dates in `text`, in order of appearance
+    pub fn extract(&self, text: &str) -> Vec<ContractDate> {
+        let mut found: Vec<(usize, usize, ContractDate)> = Vec::new();
+        let mut add = |start: usize, end: usize, date: Option<NaiveDate>| {
+            let overlaps = found.iter().any(|&(s, e, _)| start < e && s < end);
+            if let (Some(date), false) = (date, overlaps) {
+                let text = text[start..end].to_string();
+                found.push((start, end, ContractDate { iso: date.format("%Y-%m-%d").to_string(), text }));
+            }
+        };
+        let num = |s: &str| s.parse::<u32>().expect("the pattern only matches digits");
+
+        for cap in self.iso.captures_iter(text) {
+            let whole = cap.get(0).expect("group 0 always matches");
+            add(whole.start(), whole.end(), NaiveDate::from_ymd_opt(num(&cap[1]) as i32, num(&cap[2]), num(&cap[3])));
+        }
+        for cap in self.month_first.captures_iter(text) {
+            let whole = cap.get(0).expect("group 0 always matches");
+            add(whole.start(), whole.end(), NaiveDate::from_ymd_opt(num(&cap[3]) as i32, month_number(&cap[1]), num(&cap[2])));
+        }
+        for cap in self.day_first.captures_iter(text) {
+            let whole = cap.get(0).expect("group 0 always matches");
+            add(whole.start(), whole.end(), NaiveDate::from_ymd_opt(num(&cap[3]) as i32, month_number(&cap[2]), num(&cap[1])));
+        }
+        for cap in self.numeric.captures_iter(text) {
+            let whole = cap.get(0).expect("group 0 always matches");
+            if cap[2] != cap[4] {
+                continue;
+            }
+            let (first, second, year) = (num(&cap[1]), num(&cap[3]), num(&cap[5]) as i32);
+            let (month, day) = match self.order {
+                DateOrder::MonthFirst if first <= 12 => (first, second),
+                DateOrder::DayFirst if second > 12 => (first, second),
+                _ => (second, first),
+            };
+            add(whole.start(), whole.end(), NaiveDate::from_ymd_opt(year, month, day));
+        }
+
+        found.sort_by_key(|&(start, _, _)| start);
+        found.into_iter().map(|(_, _, date)| date).collect()
+    }
+}
+
+impl Default for DateParser {
+    fn default() -> Self {
+        Self::new(DateOrder::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_written_dates_normalize_to_iso() {
+        let text = "Signed January 5, 2024, effective the 5th of March 2023, renewed Sept. 1st 2024 \
+            and 15th day of June, 2025, invoiced 03/04/2025 and 25.12.2024, ends 2026-1-31.";
+        let iso = |order| DateParser::new(order).extract(text).into_iter().map(|d| d.iso).collect::<Vec<_>>();
+        assert_eq!(
+            iso(DateOrder::MonthFirst),
+            vec!["2024-01-05", "2023-03-05", "2024-09-01", "2025-06-15", "2025-03-04", "2024-12-25", "2026-01-31"]
+        );
+        assert_eq!(iso(DateOrder::DayFirst)[4], "2025-04-03");
+        assert_eq!(iso(DateOrder::DayFirst)[5], "2024-12-25");
+        assert_eq!(DateParser::default().extract(text)[1].text, "5th of March 2023");
+    }
+
+    #[test]
+    fn test_impossible_and_partial_dates_are_skipped() {
+        let parser = DateParser::default();
+        assert!(parser.extract("on 2023-02-29, February 30, 2024 or 13/13/2024").is_empty());
+        assert!(parser.extract("within 30 days, version 1.2.3, 3/4 of the fee, 12/2024").is_empty());
+        assert_eq!(parser.extract("on 2024-02-29")[0].iso, "2024-02-29");
+    }
+}
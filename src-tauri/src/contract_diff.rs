@@ -0,0 +1,343 @@
+//! AxiomHive Contract Comparison
+//! Deterministic Legal Contract Summarization Pipeline
+//! `ContractAnalyzer::compare` lines up the clauses of two versions of a
+//! contract and reports what was added, removed and modified, with a
+//! word-level redline of each modification, plus the obligations and risk
+//! flags one version has and the other lacks.
+//!
+//! Clauses are paired in three passes: identical text first, wherever it
+//! moved; then clauses at least half of whose words agree, preferring
+//! pairs that kept their reference; the rest were added or removed. So a
+//! clause inserted mid-document shows up as one addition, not as every
+//! later clause modified by the renumbering. For the same reason
+//! obligations and flags are compared without their clause references.
+
+use crate::contract_analyzer::{ContractSummary, Obligation, RiskFlag};
+use crate::contract_clauses::Clause;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+/// Clauses sharing fewer words than this are not considered the same
+const MIN_SIMILARITY: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A run of words kept, inserted or deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedlineSegment {
+    pub op: EditOp,
+    pub text: String,
+}
+
+/// A clause that differs between the versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClauseChange {
+    pub kind: ChangeKind,
+    pub old_reference: Option<String>,
+    pub new_reference: Option<String>,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+    /// Share of words the two texts have in common, 0 for additions and
+    /// removals
+    pub similarity: f64,
+    /// Word-level edits from the old text to the new; empty unless
+    /// modified
+    pub redline: Vec<RedlineSegment>,
+}
+
+/// Output of `ContractAnalyzer::compare`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractDiff {
+    /// Hex SHA-256 of each version's text
+    pub old_hash: String,
+    pub new_hash: String,
+    /// Changed clauses in the new version's order, removals where they
+    /// used to be
+    pub clauses: Vec<ClauseChange>,
+    pub unchanged_clauses: usize,
+    pub obligations_added: Vec<Obligation>,
+    pub obligations_removed: Vec<Obligation>,
+    pub risk_flags_added: Vec<RiskFlag>,
+    pub risk_flags_removed: Vec<RiskFlag>,
+    /// Hex SHA-256 over the fields above
+    pub seal: String,
+}
+
+impl ContractDiff {
+    /// Diff of `old` and `new`, the summaries of `old_text` and `new_text`
+    pub fn new(old_text: &str, old: &ContractSummary, new_text: &str, new: &ContractSummary) -> Self {
+        let (clauses, unchanged_clauses) = align(&old.clauses, &new.clauses);
+        let obligation_key = |o: &Obligation| serde_json::to_string(&(&o.party, &o.description, &o.due_date, &o.category)).unwrap();
+        let flag_key = |f: &RiskFlag| serde_json::to_string(&(&f.severity, &f.category, &f.description)).unwrap();
+        let (obligations_added, obligations_removed) = delta(&old.key_obligations, &new.key_obligations, obligation_key);
+        let (risk_flags_added, risk_flags_removed) = delta(&old.risk_flags, &new.risk_flags, flag_key);
+
+        let mut diff = Self {
+            old_hash: hex(Sha256::digest(old_text.as_bytes())),
+            new_hash: hex(Sha256::digest(new_text.as_bytes())),
+            clauses,
+            unchanged_clauses,
+            obligations_added,
+            obligations_removed,
+            risk_flags_added,
+            risk_flags_removed,
+            seal: String::new(),
+        };
+        diff.seal = hex(Sha256::digest(serde_json::to_vec(&diff).expect("diffs serialize")));
+        diff
+    }
+
+    /// Whether the seal matches the rest of the diff
+    pub fn verify_seal(&self) -> bool {
+        let unsealed = Self { seal: String::new(), ..self.clone() };
+        hex(Sha256::digest(serde_json::to_vec(&unsealed).expect("diffs serialize"))) == self.seal
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+            && self.obligations_added.is_empty()
+            && self.obligations_removed.is_empty()
+            && self.risk_flags_added.is_empty()
+            && self.risk_flags_removed.is_empty()
+    }
+}
+
+fn words(text: &str) -> Vec<&str> {
+    text.split_whitespace().collect()
+}
+
+/// Dice coefficient of the two texts' word sets
+pub(crate) fn similarity(a: &str, b: &str) -> f64 {
+    let a: BTreeSet<String> = words(a).iter().map(|w| w.to_lowercase()).collect();
+    let b: BTreeSet<String> = words(b).iter().map(|w| w.to_lowercase()).collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+/// Changed clauses, in order, and the number left unchanged
+fn align(old: &[Clause], new: &[Clause]) -> (Vec<ClauseChange>, usize) {
+    // pair[i] is the new clause old clause i became
+    let mut pair: Vec<Option<usize>> = vec![None; old.len()];
+    let mut taken = vec![false; new.len()];
+
+    for (i, old_clause) in old.iter().enumerate() {
+        if let Some(j) = (0..new.len()).find(|&j| !taken[j] && new[j].text == old_clause.text) {
+            pair[i] = Some(j);
+            taken[j] = true;
+        }
+    }
+    let mut candidates: Vec<(bool, f64, usize, usize)> = Vec::new();
+    for (i, old_clause) in old.iter().enumerate().filter(|&(i, _)| pair[i].is_none()) {
+        for (j, new_clause) in new.iter().enumerate().filter(|&(j, _)| !taken[j]) {
+            let score = similarity(&old_clause.text, &new_clause.text);
+            if score >= MIN_SIMILARITY {
+                candidates.push((old_clause.reference == new_clause.reference, score, i, j));
+            }
+        }
+    }
+    // Same reference first, then most similar, then document order
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.total_cmp(&a.1)).then(a.2.cmp(&b.2)).then(a.3.cmp(&b.3)));
+    for (_, _, i, j) in candidates {
+        if pair[i].is_none() && !taken[j] {
+            pair[i] = Some(j);
+            taken[j] = true;
+        }
+    }
+
+    // Order by position in the new version; a removal goes right after
+    // the new position of the old clause before it
+    let mut changes: Vec<((usize, usize), ClauseChange)> = Vec::new();
+    let mut unchanged = 0;
+    let mut anchor = (0, 0);
+    for (i, old_clause) in old.iter().enumerate() {
+        match pair[i] {
+            // Renumbering alone is not a change
+            Some(j) if new[j].text == old_clause.text => {
+                unchanged += 1;
+                anchor = (j + 1, 0);
+            }
+            Some(j) => {
+                let change = ClauseChange {
+                    kind: ChangeKind::Modified,
+                    old_reference: old_clause.reference.clone(),
+                    new_reference: new[j].reference.clone(),
+                    old_text: Some(old_clause.text.clone()),
+                    new_text: Some(new[j].text.clone()),
+                    similarity: similarity(&old_clause.text, &new[j].text),
+                    redline: redline(&old_clause.text, &new[j].text),
+                };
+                changes.push(((j, 1), change));
+                anchor = (j + 1, 0);
+            }
+            None => {
+                let change = ClauseChange {
+                    kind: ChangeKind::Removed,
+                    old_reference: old_clause.reference.clone(),
+                    new_reference: None,
+                    old_text: Some(old_clause.text.clone()),
+                    new_text: None,
+                    similarity: 0.0,
+                    redline: Vec::new(),
+                };
+                changes.push((anchor, change));
+            }
+        }
+    }
+    for (j, new_clause) in new.iter().enumerate().filter(|&(j, _)| !taken[j]) {
+        let change = ClauseChange {
+            kind: ChangeKind::Added,
+            old_reference: None,
+            new_reference: new_clause.reference.clone(),
+            old_text: None,
+            new_text: Some(new_clause.text.clone()),
+            similarity: 0.0,
+            redline: Vec::new(),
+        };
+        changes.push(((j, 1), change));
+    }
+    changes.sort_by_key(|&(position, _)| position);
+    (changes.into_iter().map(|(_, change)| change).collect(), unchanged)
+}
+
+/// Word-level edits turning `old` into `new`, by longest common
+/// subsequence
+fn redline(old: &str, new: &str) -> Vec<RedlineSegment> {
+    let (a, b) = (words(old), words(new));
+    // lcs[i][j] is the LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let mut segments: Vec<RedlineSegment> = Vec::new();
+    let mut push = |op: EditOp, word: &str| match segments.last_mut() {
+        Some(last) if last.op == op => {
+            last.text.push(' ');
+            last.text.push_str(word);
+        }
+        _ => segments.push(RedlineSegment { op, text: word.to_string() }),
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            push(EditOp::Equal, a[i]);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            push(EditOp::Insert, b[j]);
+            j += 1;
+        } else {
+            push(EditOp::Delete, a[i]);
+            i += 1;
+        }
+    }
+    segments
+}
+
+/// Items only in `new`, and items only in `old`, counting duplicates
+fn delta<T: Clone>(old: &[T], new: &[T], key: impl Fn(&T) -> String) -> (Vec<T>, Vec<T>) {
+    let mut old_keys: Vec<Option<String>> = old.iter().map(|item| Some(key(item))).collect();
+    let mut added = Vec::new();
+    for item in new {
+        let k = key(item);
+        match old_keys.iter_mut().find(|old| old.as_deref() == Some(k.as_str())) {
+            Some(matched) => *matched = None,
+            None => added.push(item.clone()),
+        }
+    }
+    let removed = old.iter().zip(old_keys).filter(|(_, k)| k.is_some()).map(|(item, _)| item.clone()).collect();
+    (added, removed)
+}
+
+fn hex(digest: impl AsRef<[u8]>) -> String {
+    digest.as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_analyzer::ContractAnalyzer;
+
+    const OLD: &str = "1. Acme Supplies LLC shall deliver the goods within 30 days of each order.\n\
+        2. Beta Retail Inc shall pay each invoice.\n\
+        3. This Agreement is governed by the laws of Delaware.\n\
+        4. Either party may terminate on notice.\n";
+    const NEW: &str = "1. Acme Supplies LLC shall deliver the goods within 10 days of each order.\n\
+        2. Beta Retail Inc shall pay each invoice.\n\
+        3. Beta Retail Inc shall indemnify Acme Supplies LLC against any and all claims.\n\
+        4. This Agreement is governed by the laws of Delaware.\n";
+
+    #[test]
+    fn test_amendments_align_by_clause() {
+        let analyzer = ContractAnalyzer::new(true);
+        let diff = analyzer.compare(OLD, NEW).unwrap();
+        let outline: Vec<(ChangeKind, Option<&str>, Option<&str>)> = diff
+            .clauses
+            .iter()
+            .map(|c| (c.kind, c.old_reference.as_deref(), c.new_reference.as_deref()))
+            .collect();
+        assert_eq!(
+            outline,
+            vec![
+                (ChangeKind::Modified, Some("1"), Some("1")),
+                (ChangeKind::Added, None, Some("3")),
+                (ChangeKind::Removed, Some("4"), None),
+            ]
+        );
+        // Clause 3 moved to 4 unchanged
+        assert_eq!(diff.unchanged_clauses, 2);
+        assert!(diff.clauses[0].redline.iter().any(|s| s.op == EditOp::Delete && s.text == "30"));
+
+        assert_eq!(diff.obligations_added.len(), 2);
+        assert_eq!(diff.obligations_removed.len(), 1);
+        assert!(diff.obligations_removed[0].description.contains("30 days"));
+        assert!(diff.risk_flags_added.iter().any(|f| f.category == "indemnification"));
+
+        assert!(diff.verify_seal());
+        assert_eq!(diff, analyzer.compare(OLD, NEW).unwrap());
+        let tampered = ContractDiff { unchanged_clauses: 3, ..diff.clone() };
+        assert!(!tampered.verify_seal());
+
+        let same = analyzer.compare(OLD, OLD).unwrap();
+        assert!(same.is_empty());
+        assert_eq!(same.unchanged_clauses, 4);
+        assert_ne!(same.seal, diff.seal);
+    }
+
+    #[test]
+    fn test_redline_keeps_common_words() {
+        let segments = redline("Supplier shall deliver within 30 days", "Supplier shall promptly deliver within 10 days");
+        let ops: Vec<(EditOp, &str)> = segments.iter().map(|s| (s.op, s.text.as_str())).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (EditOp::Equal, "Supplier shall"),
+                (EditOp::Insert, "promptly"),
+                (EditOp::Equal, "deliver within"),
+                (EditOp::Insert, "10"),
+                (EditOp::Delete, "30"),
+                (EditOp::Equal, "days"),
+            ]
+        );
+        assert!(redline("", "").is_empty());
+    }
+}
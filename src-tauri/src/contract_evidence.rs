@@ -0,0 +1,104 @@
+//! AxiomHive Contract Evidence
+//! Deterministic Legal Contract Summarization Pipeline
+//! Every party, obligation, risk flag and metadata field the analyzer
+//! reports carries `Evidence`: how confident the extraction is, from 0 to
+//! 1, and the spans of the submitted text supporting it, so a reviewer
+//! can see what each finding rests on.
+//!
+//! Extraction works on text with its whitespace normalized, so an
+//! extracted string is found again in the submitted text word by word,
+//! with any run of whitespace between words. Span offsets count
+//! characters, not bytes, from the start of the submitted text.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A stretch of the submitted text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    /// Character offset of the first character
+    pub start: usize,
+    /// Character offset just past the last character
+    pub end: usize,
+    /// The text as submitted
+    pub text: String,
+}
+
+/// Support for an extracted value
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Evidence {
+    /// 0 for a placeholder, up to 1 for an unambiguous match
+    pub confidence: f64,
+    /// Where the value was read from, in document order
+    pub spans: Vec<Span>,
+}
+
+impl Evidence {
+    /// Evidence with `confidence` clamped to 0..=1 and rounded to two
+    /// places
+    pub fn new(confidence: f64, spans: Vec<Span>) -> Self {
+        Self { confidence: (confidence.clamp(0.0, 1.0) * 100.0).round() / 100.0, spans }
+    }
+}
+
+/// The submitted text, searchable by normalized strings
+pub(crate) struct SourceText<'a> {
+    text: &'a str,
+    /// Byte offset of each character
+    chars: Vec<usize>,
+}
+
+impl<'a> SourceText<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        Self { text, chars: text.char_indices().map(|(i, _)| i).collect() }
+    }
+
+    /// Matches `needle` with any run of whitespace between its words
+    pub(crate) fn pattern(needle: &str) -> Option<Regex> {
+        let words: Vec<String> = needle.split_whitespace().map(regex::escape).collect();
+        if words.is_empty() {
+            return None;
+        }
+        Regex::new(&words.join(r"\s+")).ok()
+    }
+
+    fn span(&self, m: regex::Match<'a>) -> Span {
+        let offset = |byte: usize| self.chars.partition_point(|&b| b < byte);
+        Span { start: offset(m.start()), end: offset(m.end()), text: m.as_str().to_string() }
+    }
+
+    /// First occurrence of `needle` starting at character `from` or later
+    pub(crate) fn find(&self, needle: &str, from: usize) -> Option<Span> {
+        let start = self.chars.get(from).copied().unwrap_or(self.text.len());
+        Self::pattern(needle)?.find_at(self.text, start).map(|m| self.span(m))
+    }
+
+    /// Up to `limit` occurrences of `needle`
+    pub(crate) fn find_all(&self, needle: &str, limit: usize) -> Vec<Span> {
+        Self::pattern(needle).map_or_else(Vec::new, |re| re.find_iter(self.text).take(limit).map(|m| self.span(m)).collect())
+    }
+
+    /// Up to `chars` characters before `span`
+    pub(crate) fn before(&self, span: &Span, chars: usize) -> &'a str {
+        let byte = |char: usize| self.chars.get(char).copied().unwrap_or(self.text.len());
+        let (start, end) = (byte(span.start.saturating_sub(chars)), byte(span.start));
+        &self.text[start..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_strings_are_found_across_whitespace_by_character_offset() {
+        let source = SourceText::new("Café Noir LLC\nshall  pay.\nCafé Noir LLC shall pay.");
+        let span = source.find("Noir LLC shall pay", 0).unwrap();
+        assert_eq!((span.start, span.end, span.text.as_str()), (5, 24, "Noir LLC\nshall  pay"));
+        assert_eq!(source.find("Noir LLC shall pay", span.end).unwrap().start, 31);
+        assert_eq!(source.find_all("Café Noir LLC", 10).len(), 2);
+        assert_eq!(source.before(&span, 3), "fé ");
+        assert_eq!(source.find("nowhere", 0), None);
+        assert_eq!(Evidence::new(0.6 + 0.2 + 0.1, Vec::new()).confidence, 0.9);
+    }
+}
@@ -0,0 +1,269 @@
+//! AxiomHive Contract Jurisdictions
+//! Deterministic Legal Contract Summarization Pipeline
+//! Jurisdictions are captured from contracts as written, e.g. "the State
+//! of Delaware in". `normalize` looks them up in a built-in table of
+//! countries, US states and Canadian provinces and returns the canonical
+//! ISO 3166 code (ISO 3166-2 for states and provinces, plus GB-ENG,
+//! GB-SCT and GB-NIR for the United Kingdom's three legal systems) with
+//! the jurisdiction's legal tradition. The longest name found in the
+//! text wins, so "New Mexico" is not read as Mexico.
+//!
+//! `assess` flags disputes to be heard somewhere other than where the
+//! governing law comes from: in another state of the same country (low),
+//! another country (medium) or another legal tradition (high).
+
+use crate::contract_analyzer::Severity;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Legal tradition of a jurisdiction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LegalSystem {
+    CommonLaw,
+    CivilLaw,
+    /// Civil and common law, or religious law, combined
+    Mixed,
+}
+
+/// A jurisdiction of the built-in table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jurisdiction {
+    /// E.g. "US-DE" or "DE"
+    pub code: String,
+    pub name: String,
+    /// ISO 3166-1 code of the country
+    pub country: String,
+    pub legal_system: LegalSystem,
+}
+
+use LegalSystem::{CivilLaw, CommonLaw, Mixed};
+
+/// Code, name, legal system and other names, lowercase
+const TABLE: &[(&str, &str, LegalSystem, &[&str])] = &[
+    ("US", "United States", CommonLaw, &["usa", "u s", "u s a", "united states of america"]),
+    ("US-AL", "Alabama", CommonLaw, &[]),
+    ("US-AK", "Alaska", CommonLaw, &[]),
+    ("US-AZ", "Arizona", CommonLaw, &[]),
+    ("US-AR", "Arkansas", CommonLaw, &[]),
+    ("US-CA", "California", CommonLaw, &[]),
+    ("US-CO", "Colorado", CommonLaw, &[]),
+    ("US-CT", "Connecticut", CommonLaw, &[]),
+    ("US-DE", "Delaware", CommonLaw, &[]),
+    ("US-DC", "District of Columbia", CommonLaw, &["washington d c", "washington dc"]),
+    ("US-FL", "Florida", CommonLaw, &[]),
+    ("US-GA", "Georgia", CommonLaw, &[]),
+    ("US-HI", "Hawaii", CommonLaw, &[]),
+    ("US-ID", "Idaho", CommonLaw, &[]),
+    ("US-IL", "Illinois", CommonLaw, &[]),
+    ("US-IN", "Indiana", CommonLaw, &[]),
+    ("US-IA", "Iowa", CommonLaw, &[]),
+    ("US-KS", "Kansas", CommonLaw, &[]),
+    ("US-KY", "Kentucky", CommonLaw, &[]),
+    ("US-LA", "Louisiana", Mixed, &[]),
+    ("US-ME", "Maine", CommonLaw, &[]),
+    ("US-MD", "Maryland", CommonLaw, &[]),
+    ("US-MA", "Massachusetts", CommonLaw, &[]),
+    ("US-MI", "Michigan", CommonLaw, &[]),
+    ("US-MN", "Minnesota", CommonLaw, &[]),
+    ("US-MS", "Mississippi", CommonLaw, &[]),
+    ("US-MO", "Missouri", CommonLaw, &[]),
+    ("US-MT", "Montana", CommonLaw, &[]),
+    ("US-NE", "Nebraska", CommonLaw, &[]),
+    ("US-NV", "Nevada", CommonLaw, &[]),
+    ("US-NH", "New Hampshire", CommonLaw, &[]),
+    ("US-NJ", "New Jersey", CommonLaw, &[]),
+    ("US-NM", "New Mexico", CommonLaw, &[]),
+    ("US-NY", "New York", CommonLaw, &[]),
+    ("US-NC", "North Carolina", CommonLaw, &[]),
+    ("US-ND", "North Dakota", CommonLaw, &[]),
+    ("US-OH", "Ohio", CommonLaw, &[]),
+    ("US-OK", "Oklahoma", CommonLaw, &[]),
+    ("US-OR", "Oregon", CommonLaw, &[]),
+    ("US-PA", "Pennsylvania", CommonLaw, &[]),
+    ("US-RI", "Rhode Island", CommonLaw, &[]),
+    ("US-SC", "South Carolina", CommonLaw, &[]),
+    ("US-SD", "South Dakota", CommonLaw, &[]),
+    ("US-TN", "Tennessee", CommonLaw, &[]),
+    ("US-TX", "Texas", CommonLaw, &[]),
+    ("US-UT", "Utah", CommonLaw, &[]),
+    ("US-VT", "Vermont", CommonLaw, &[]),
+    ("US-VA", "Virginia", CommonLaw, &[]),
+    ("US-WA", "Washington", CommonLaw, &[]),
+    ("US-WV", "West Virginia", CommonLaw, &[]),
+    ("US-WI", "Wisconsin", CommonLaw, &[]),
+    ("US-WY", "Wyoming", CommonLaw, &[]),
+    ("CA", "Canada", CommonLaw, &[]),
+    ("CA-AB", "Alberta", CommonLaw, &[]),
+    ("CA-BC", "British Columbia", CommonLaw, &[]),
+    ("CA-ON", "Ontario", CommonLaw, &[]),
+    ("CA-QC", "Quebec", Mixed, &["québec"]),
+    ("GB", "United Kingdom", CommonLaw, &["uk", "u k", "great britain"]),
+    ("GB-ENG", "England and Wales", CommonLaw, &["england"]),
+    ("GB-SCT", "Scotland", Mixed, &[]),
+    ("GB-NIR", "Northern Ireland", CommonLaw, &[]),
+    ("IE", "Ireland", CommonLaw, &["republic of ireland"]),
+    ("AU", "Australia", CommonLaw, &[]),
+    ("NZ", "New Zealand", CommonLaw, &[]),
+    ("IN", "India", CommonLaw, &[]),
+    ("SG", "Singapore", CommonLaw, &[]),
+    ("HK", "Hong Kong", CommonLaw, &[]),
+    ("MY", "Malaysia", CommonLaw, &[]),
+    ("NG", "Nigeria", CommonLaw, &[]),
+    ("KE", "Kenya", CommonLaw, &[]),
+    ("ZA", "South Africa", Mixed, &[]),
+    ("IL", "Israel", Mixed, &[]),
+    ("PH", "Philippines", Mixed, &[]),
+    ("AE", "United Arab Emirates", Mixed, &["uae", "u a e", "dubai", "abu dhabi"]),
+    ("SA", "Saudi Arabia", Mixed, &[]),
+    ("DE", "Germany", CivilLaw, &["federal republic of germany"]),
+    ("FR", "France", CivilLaw, &[]),
+    ("IT", "Italy", CivilLaw, &[]),
+    ("ES", "Spain", CivilLaw, &[]),
+    ("PT", "Portugal", CivilLaw, &[]),
+    ("NL", "Netherlands", CivilLaw, &["holland"]),
+    ("BE", "Belgium", CivilLaw, &[]),
+    ("LU", "Luxembourg", CivilLaw, &[]),
+    ("CH", "Switzerland", CivilLaw, &[]),
+    ("AT", "Austria", CivilLaw, &[]),
+    ("SE", "Sweden", CivilLaw, &[]),
+    ("DK", "Denmark", CivilLaw, &[]),
+    ("NO", "Norway", CivilLaw, &[]),
+    ("FI", "Finland", CivilLaw, &[]),
+    ("PL", "Poland", CivilLaw, &[]),
+    ("CZ", "Czech Republic", CivilLaw, &["czechia"]),
+    ("GR", "Greece", CivilLaw, &[]),
+    ("TR", "Turkey", CivilLaw, &["türkiye"]),
+    ("RU", "Russia", CivilLaw, &["russian federation"]),
+    ("GE", "Republic of Georgia", CivilLaw, &[]),
+    ("JP", "Japan", CivilLaw, &[]),
+    ("KR", "South Korea", CivilLaw, &["republic of korea", "korea"]),
+    ("CN", "China", CivilLaw, &["people s republic of china", "prc"]),
+    ("TW", "Taiwan", CivilLaw, &[]),
+    ("BR", "Brazil", CivilLaw, &[]),
+    ("MX", "Mexico", CivilLaw, &[]),
+    ("AR", "Argentina", CivilLaw, &[]),
+    ("CL", "Chile", CivilLaw, &[]),
+    ("CO", "Colombia", CivilLaw, &[]),
+];
+
+/// The jurisdiction `raw` names, if the table has it
+pub fn normalize(raw: &str) -> Option<Jurisdiction> {
+    // Words separated by single spaces, with a space at each end, so
+    // names only match whole words
+    let words: Vec<String> = raw
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect();
+    let haystack = format!(" {} ", words.join(" "));
+
+    TABLE
+        .iter()
+        .flat_map(|entry| {
+            let (_, name, _, aliases) = entry;
+            std::iter::once(name.to_lowercase()).chain(aliases.iter().map(|a| a.to_string())).map(move |n| (entry, n))
+        })
+        .filter(|(_, name)| haystack.contains(&format!(" {} ", name)))
+        // Longest name, then the first entry
+        .fold(None, |best: Option<(&_, String)>, (entry, name)| match best {
+            Some((_, ref longest)) if longest.len() >= name.len() => best,
+            _ => Some((entry, name)),
+        })
+        .map(|(&(code, name, legal_system, _), _)| Jurisdiction {
+            code: code.to_string(),
+            name: name.to_string(),
+            country: code.split('-').next().expect("split yields at least one part").to_string(),
+            legal_system,
+        })
+}
+
+/// Where the governing-law clause of `text` takes its law from, as
+/// written
+pub fn governing_law(text: &str) -> Option<String> {
+    let re = Regex::new(
+        r"(?i:governed\s+by|construed\s+(?:in\s+accordance\s+with|under)|governing\s+law)[^.;]*?(?i:laws?\s+of)\s+(?i:the\s+)?([A-Z][^,.;]+)",
+    )
+    .unwrap();
+    re.captures(text).map(|cap| cap[1].trim().to_string())
+}
+
+/// Where the contract's disputes are to be heard, as written
+pub fn venue(text: &str) -> Option<String> {
+    // "Paris, France" needs the comma
+    let patterns = [
+        r"(?i:courts?|tribunals?)\s+(?i:located\s+|sitting\s+)?(?i:in|of)\s+(?i:the\s+)?([A-Z][^.;]{0,60})",
+        r"(?i:\bvenue\b)[^.;]*?\b(?i:in)\s+(?i:the\s+)?([A-Z][^.;]{0,60})",
+        r"(?i:arbitration)\s+(?i:seated\s+|held\s+)?(?i:in)\s+([A-Z][^.;]{0,60})",
+    ];
+    patterns.iter().find_map(|pattern| {
+        let re = Regex::new(pattern).unwrap();
+        re.captures(text).map(|cap| cap[1].trim().to_string())
+    })
+}
+
+/// A risky combination of governing law and venue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JurisdictionRisk {
+    pub severity: Severity,
+    pub description: String,
+}
+
+/// Risks of disputes under `governing_law` being heard in `venue`
+pub fn assess(governing_law: Option<&Jurisdiction>, venue: Option<&Jurisdiction>) -> Vec<JurisdictionRisk> {
+    let (Some(law), Some(venue)) = (governing_law, venue) else {
+        return Vec::new();
+    };
+    let severity = if law.code == venue.code {
+        return Vec::new();
+    } else if law.legal_system != venue.legal_system {
+        Severity::High
+    } else if law.country != venue.country {
+        Severity::Medium
+    } else {
+        Severity::Low
+    };
+    vec![JurisdictionRisk {
+        severity,
+        description: format!(
+            "Governing law of {} ({}) but disputes heard in {} ({})",
+            law.name, law.code, venue.name, venue.code
+        ),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_captures_normalize_to_codes() {
+        let code = |raw: &str| normalize(raw).map(|j| j.code);
+        assert_eq!(code("the State of Delaware in").as_deref(), Some("US-DE"));
+        assert_eq!(code("New Mexico").as_deref(), Some("US-NM"));
+        assert_eq!(code("West Virginia, USA").as_deref(), Some("US-WV"));
+        assert_eq!(code("Washington, D.C.").as_deref(), Some("US-DC"));
+        assert_eq!(code("England and Wales").as_deref(), Some("GB-ENG"));
+        assert_eq!(code("Indiana").as_deref(), Some("US-IN"));
+        assert_eq!(code("Atlantis"), None);
+        let quebec = normalize("the Province of Québec").unwrap();
+        assert_eq!((quebec.country.as_str(), quebec.legal_system), ("CA", LegalSystem::Mixed));
+    }
+
+    #[test]
+    fn test_governing_law_and_venue_mismatches_are_graded() {
+        let text = "This Agreement is governed by the laws of the State of New York. \
+            Any dispute shall be resolved by the courts of Paris, France.";
+        let law = governing_law(text).and_then(|raw| normalize(&raw)).unwrap();
+        let forum = venue(text).and_then(|raw| normalize(&raw)).unwrap();
+        assert_eq!((law.code.as_str(), forum.code.as_str()), ("US-NY", "FR"));
+        assert_eq!(assess(Some(&law), Some(&forum))[0].severity, Severity::High);
+
+        let severity = |a: &str, b: &str| assess(normalize(a).as_ref(), normalize(b).as_ref()).first().map(|r| r.severity);
+        assert_eq!(severity("Delaware", "New York"), Some(Severity::Low));
+        assert_eq!(severity("England", "Singapore"), Some(Severity::Medium));
+        assert_eq!(severity("Delaware", "the State of Delaware"), None);
+        assert_eq!(severity("Delaware", "Atlantis"), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! AxiomHive Contract Analysis Limits
+//! Deterministic Legal Contract Summarization Pipeline
+//! The analyzer finds every obligation and risk flag in a contract, but a
+//! summary carries only the first page of each, as many as its
+//! `AnalysisLimits` allow. The summary's `Pagination` says how many there
+//! are in all and where the next page starts; `obligations_page` and
+//! `risk_flags_page` return any page. Analysis is deterministic, so a
+//! page is computed by analyzing the contract again, and carries the
+//! seal of the analysis it came from.
+//!
+//! Before paging, an obligation restating another, with the same party,
+//! category, due date and amounts and most of its words, is merged into
+//! the first: it adds its evidence spans and counts as a duplicate, but
+//! not its amounts, so exposure is not counted twice.
+
+use crate::contract_analyzer::Obligation;
+use crate::contract_diff;
+use serde::{Deserialize, Serialize};
+
+/// Obligations less alike than this are not duplicates
+const MIN_SIMILARITY: f64 = 0.8;
+
+/// Findings a summary carries at most; the rest are paged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalysisLimits {
+    pub obligations: usize,
+    pub risk_flags: usize,
+}
+
+impl Default for AnalysisLimits {
+    fn default() -> Self {
+        Self { obligations: 10, risk_flags: 20 }
+    }
+}
+
+/// Where a page lies in the complete list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageInfo {
+    pub offset: usize,
+    pub returned: usize,
+    pub total: usize,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<usize>,
+}
+
+/// Pages of the findings in a summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pagination {
+    pub obligations: PageInfo,
+    pub risk_flags: PageInfo,
+}
+
+/// A page of obligations or risk flags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: PageInfo,
+    /// Seal of the summary of the analysis paged
+    pub cryptographic_seal: String,
+}
+
+/// Up to `limit` of `items` from `offset` on; a limit of 0 is taken as 1
+pub fn page<T: Clone>(items: &[T], offset: usize, limit: usize) -> (Vec<T>, PageInfo) {
+    let start = offset.min(items.len());
+    let end = start.saturating_add(limit.max(1)).min(items.len());
+    let info = PageInfo {
+        offset: start,
+        returned: end - start,
+        total: items.len(),
+        next_offset: (end < items.len()).then_some(end),
+    };
+    (items[start..end].to_vec(), info)
+}
+
+/// `obligations` with each near-duplicate merged into the first
+/// obligation it restates
+pub fn merge_duplicates(obligations: Vec<Obligation>) -> Vec<Obligation> {
+    let mut merged: Vec<Obligation> = Vec::with_capacity(obligations.len());
+    for obligation in obligations {
+        let original = merged.iter_mut().find(|o| {
+            o.party == obligation.party
+                && o.category == obligation.category
+                && o.due_date == obligation.due_date
+                && o.amounts == obligation.amounts
+                && contract_diff::similarity(&o.description, &obligation.description) >= MIN_SIMILARITY
+        });
+        match original {
+            Some(original) => {
+                original.duplicates += 1;
+                original.evidence.spans.extend(obligation.evidence.spans);
+            }
+            None => merged.push(obligation),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_analyzer::ContractAnalyzer;
+
+    #[test]
+    fn test_pages_cover_every_item_once() {
+        let items: Vec<usize> = (0..7).collect();
+        let (first, info) = page(&items, 0, 3);
+        assert_eq!((first, info.next_offset, info.total), (vec![0, 1, 2], Some(3), 7));
+        let (last, info) = page(&items, 6, 3);
+        assert_eq!((last, info.returned, info.next_offset), (vec![6], 1, None));
+        assert_eq!(page(&items, 9, 3).1, PageInfo { offset: 7, returned: 0, total: 7, next_offset: None });
+        assert_eq!(page(&items, 0, 0).0, vec![0]);
+    }
+
+    #[test]
+    fn test_long_contracts_are_paged_and_duplicates_merged() {
+        let mut contract: String = (1..=30)
+            .map(|n| format!("{}. Acme Supplies LLC shall deliver shipment {} with reasonable care by 2024-{:02}-{:02}.\n", n, n, n / 28 + 1, n % 28 + 1))
+            .collect();
+        contract.push_str("31. Beta Retail Inc shall pay the fee of $500 by 2024-03-01.\n");
+        contract.push_str("32. As stated above, Beta Retail Inc shall pay the fee of $500 by 2024-03-01.\n");
+
+        let analyzer = ContractAnalyzer::new(true);
+        let summary = analyzer.analyze_contract(&contract).unwrap();
+        assert_eq!(summary.key_obligations.len(), 10);
+        assert_eq!(summary.pagination.obligations, PageInfo { offset: 0, returned: 10, total: 31, next_offset: Some(10) });
+        assert_eq!(summary.exposure[0].total, 500.0);
+
+        let fee = analyzer.obligations_page(&contract, 30, 10).unwrap();
+        assert_eq!((fee.items.len(), fee.page.next_offset), (1, None));
+        assert_eq!(fee.items[0].duplicates, 1);
+        assert_eq!(fee.items[0].evidence.spans.len(), 2);
+        assert_eq!(fee.cryptographic_seal, summary.cryptographic_seal);
+
+        let flags = analyzer.risk_flags_page(&contract, 0, 100).unwrap();
+        assert_eq!(flags.page.total, summary.pagination.risk_flags.total);
+        assert!(flags.items.len() > summary.risk_flags.len());
+
+        let wide = ContractAnalyzer::new(true).with_limits(AnalysisLimits { obligations: 50, risk_flags: 100 });
+        let summary = wide.analyze_contract(&contract).unwrap();
+        assert_eq!((summary.key_obligations.len(), summary.pagination.obligations.next_offset), (31, None));
+    }
+}
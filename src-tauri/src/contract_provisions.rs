@@ -0,0 +1,291 @@
+//! AxiomHive Contract Provision Analysis
+//! Deterministic Legal Contract Summarization Pipeline
+//! Reads the three provisions reviewers look at first, clause by clause:
+//!
+//! - indemnification: who indemnifies, whether it is mutual, how broad it
+//!   is and what losses it covers
+//! - limitation of liability: the cap as an amount or as a multiple of
+//!   the fees, whether liability is unlimited, and what is carved out
+//! - warranty disclaimers: "as is" terms, which implied warranties are
+//!   disclaimed and whether the disclaimer is conspicuous (in capitals),
+//!   as UCC 2-316 requires for merchantability
+//!
+//! Each clause yields at most one finding of each kind.
+
+use crate::contract_amounts::{self, MonetaryAmount};
+use crate::contract_analyzer::Severity;
+use crate::contract_clauses::Clause;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// What a provision says
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Provision {
+    Indemnification {
+        /// The party that indemnifies, if the clause names one
+        indemnitor: Option<String>,
+        /// Each party indemnifies the other
+        mutual: bool,
+        /// "Any and all" claims, or regardless of fault
+        broad: bool,
+        /// Kinds of loss covered, e.g. "claims" or "attorneys' fees"
+        covers: Vec<String>,
+    },
+    LiabilityCap {
+        /// A fixed cap
+        cap: Option<MonetaryAmount>,
+        /// A cap as a multiple of `basis`, e.g. 2 for "two times the fees"
+        multiplier: Option<f64>,
+        basis: Option<String>,
+        /// The clause makes liability unlimited
+        unlimited: bool,
+        /// Liabilities the cap does not apply to
+        carve_outs: Vec<String>,
+        /// Indirect and consequential damages are excluded
+        excludes_consequential: bool,
+    },
+    WarrantyDisclaimer {
+        as_is: bool,
+        /// Implied warranties disclaimed, e.g. "merchantability"
+        disclaimed: Vec<String>,
+        /// Written in capitals
+        conspicuous: bool,
+    },
+}
+
+impl Provision {
+    /// Category of the risk flag raised for the provision
+    pub fn category(&self) -> &'static str {
+        match self {
+            Provision::Indemnification { .. } => "indemnification",
+            Provision::LiabilityCap { .. } => "liability",
+            Provision::WarrantyDisclaimer { .. } => "warranty",
+        }
+    }
+}
+
+/// A provision found in a clause
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvisionFinding {
+    pub clause: Option<String>,
+    pub severity: Severity,
+    pub description: String,
+    #[serde(flatten)]
+    pub provision: Provision,
+}
+
+const BASIS: &str = r"((?:total\s+|aggregate\s+)?(?:fees|amounts|charges|contract\s+value|consideration)\b[^.;,]*)";
+const COVERS: [(&str, &str); 8] = [
+    ("claims", r"\bclaims?\b"),
+    ("losses", r"\bloss(?:es)?\b"),
+    ("damages", r"\bdamages\b"),
+    ("liabilities", r"\bliabilit(?:y|ies)\b"),
+    ("costs", r"\bcosts\b|\bexpenses\b"),
+    ("attorneys' fees", r"attorneys?['’]?\s+fees|legal\s+fees"),
+    ("fines", r"\bfines\b|\bpenalties\b"),
+    ("third-party claims", r"third[\s-]part(?:y|ies)"),
+];
+const CARVE_OUTS: [(&str, &str); 7] = [
+    ("gross negligence", r"gross(?:ly)?\s+negligen"),
+    ("willful misconduct", r"wil(?:l)?ful\s+misconduct"),
+    ("fraud", r"\bfraud"),
+    ("indemnification", r"indemnif"),
+    ("confidentiality", r"confidential"),
+    ("death or personal injury", r"death|bodily\s+injury|personal\s+injury"),
+    ("infringement", r"infring"),
+];
+const WARRANTIES: [(&str, &str); 5] = [
+    ("merchantability", r"merchantab"),
+    ("fitness for a particular purpose", r"fitness\s+for\s+a\s+particular\s+purpose"),
+    ("non-infringement", r"non[\s-]?infringement"),
+    ("title", r"warrant(?:y|ies)\s+of\s+title"),
+    ("accuracy", r"\baccuracy\b"),
+];
+
+fn matching(text: &str, table: &[(&str, &str)]) -> Vec<String> {
+    table
+        .iter()
+        .filter(|(_, pattern)| Regex::new(&format!("(?i){}", pattern)).unwrap().is_match(text))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// Findings in `clauses`, in document order; `parties` are the names the
+/// indemnitor is looked for among
+pub fn analyze(clauses: &[Clause], parties: &[String]) -> Vec<ProvisionFinding> {
+    let indemnity = Regex::new(r"(?i)\bindemnif(?:y|ies|ied|ication)\b|\bhold\s+harmless\b").unwrap();
+    // The indemnitor is the party named last before the verb
+    let indemnifies = Regex::new(r"(?i)\bindemnif(?:y|ies)\b|\bhold\s+harmless\b").unwrap();
+    let mutual = Regex::new(r"(?i)\beach\s+party\b|\bmutual(?:ly)?\b|\bboth\s+parties\b").unwrap();
+    let broad = Regex::new(r"(?i)any\s+and\s+all|regardless\s+of|whether\s+or\s+not|own\s+negligence").unwrap();
+    let liability = Regex::new(
+        r"(?i)limitation\s+of\s+liability|liability\b[^.]{0,80}?\b(?:shall\s+not\s+exceed|not\s+to\s+exceed|(?:is|be)\s+limited\s+to|capped\s+at)|in\s+no\s+event\s+shall\b[^.]{0,80}?\bliable|unlimited\s+liability",
+    )
+    .unwrap();
+    let unlimited = Regex::new(
+        r"(?i)unlimited\s+liability|liability\s+(?:shall\s+be|is)\s+unlimited|without\s+limit(?:ation)?\s+(?:of|on|as\s+to)\s+(?:its\s+)?liability",
+    )
+    .unwrap();
+    let consequential = Regex::new(r"(?i)consequential|indirect|special\s+damages|lost\s+profits").unwrap();
+    let carve_out = Regex::new(r"(?i)\bexcept\b|\bexcluding\b|shall\s+not\s+apply|other\s+than|does\s+not\s+apply").unwrap();
+    let times = Regex::new(&format!(
+        r"(?i)\b(?:(\d+(?:\.\d+)?)|([a-z]+))\s*(?:\((\d+(?:\.\d+)?)\)\s*)?(?:times|x)\s+(?:the\s+)?{BASIS}"
+    ))
+    .unwrap();
+    let percent = Regex::new(&format!(r"(?i)(\d+(?:\.\d+)?)\s*%\s+of\s+(?:the\s+)?{BASIS}")).unwrap();
+    let plain = Regex::new(&format!(r"(?i)(?:exceed|limited\s+to|capped\s+at)\s+(?:the\s+)?{BASIS}")).unwrap();
+    let warranty = Regex::new(
+        r#"(?i)\bdisclaim|merchantab|fitness\s+for\s+a\s+particular\s+purpose|\bno\s+(?:other\s+)?warrant"#,
+    )
+    .unwrap();
+    let as_is = Regex::new(r#"(?i)["“]as[\s-]is["”]|\bas[\s-]is\s+basis|\bas[\s-]is,\s+where[\s-]is|\bas[\s-]is\s+and\b"#).unwrap();
+
+    let mut findings = Vec::new();
+    for clause in clauses {
+        let text = clause.text.as_str();
+        let finding = |severity, description: String, provision| ProvisionFinding {
+            clause: clause.reference.clone(),
+            severity,
+            description,
+            provision,
+        };
+
+        if indemnity.is_match(text) {
+            let before = &text[..indemnifies.find(text).map_or(0, |verb| verb.start())];
+            let indemnitor = parties
+                .iter()
+                .filter_map(|p| before.rfind(p.as_str()).map(|at| (at, p)))
+                .max_by_key(|&(at, _)| at)
+                .map(|(_, p)| p.clone());
+            let (mutual, broad, covers) = (mutual.is_match(text), broad.is_match(text), matching(text, &COVERS));
+            let severity = match (mutual, broad) {
+                (false, true) => Severity::High,
+                (true, false) => Severity::Low,
+                _ => Severity::Medium,
+            };
+            let description = format!(
+                "{} indemnification by {}{}",
+                if mutual { "Mutual" } else { "One-sided" },
+                indemnitor.as_deref().unwrap_or("an unnamed party"),
+                if broad { ", broad in scope" } else { "" }
+            );
+            findings.push(finding(severity, description, Provision::Indemnification { indemnitor, mutual, broad, covers }));
+        }
+
+        if liability.is_match(text) || unlimited.is_match(text) {
+            let unlimited = unlimited.is_match(text);
+            let (multiplier, basis) = if let Some(cap) = times.captures(text) {
+                let value = cap
+                    .get(3)
+                    .or(cap.get(1))
+                    .map(|m| m.as_str().parse::<f64>().expect("the pattern only matches numbers"))
+                    .or_else(|| cap.get(2).and_then(|w| contract_amounts::words_value(w.as_str())));
+                (value, value.map(|_| cap[4].trim().to_string()))
+            } else if let Some(cap) = percent.captures(text) {
+                let value = cap[1].parse::<f64>().expect("the pattern only matches numbers") / 100.0;
+                (Some(value), Some(cap[2].trim().to_string()))
+            } else if let Some(cap) = plain.captures(text) {
+                (Some(1.0), Some(cap[1].trim().to_string()))
+            } else {
+                (None, None)
+            };
+            let cap = if unlimited { None } else { contract_amounts::extract(text).into_iter().next() };
+            let carve_outs = if carve_out.is_match(text) { matching(text, &CARVE_OUTS) } else { Vec::new() };
+            let excludes_consequential = consequential.is_match(text);
+            let (severity, description) = match (&cap, multiplier, unlimited) {
+                (_, _, true) => (Severity::Critical, "Liability is unlimited".to_string()),
+                (Some(cap), _, _) => (Severity::Low, format!("Liability capped at {} {}", cap.currency, cap.value)),
+                (None, Some(multiplier), _) => (
+                    Severity::Low,
+                    format!("Liability capped at {} times the {}", multiplier, basis.as_deref().unwrap_or("fees")),
+                ),
+                (None, None, _) => (Severity::Medium, "Limitation of liability without a stated cap".to_string()),
+            };
+            let provision = Provision::LiabilityCap { cap, multiplier, basis, unlimited, carve_outs, excludes_consequential };
+            findings.push(finding(severity, description, provision));
+        }
+
+        if warranty.is_match(text) || as_is.is_match(text) {
+            let as_is = as_is.is_match(text);
+            let disclaimed = matching(text, &WARRANTIES);
+            let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+            let conspicuous = letters.iter().filter(|c| c.is_uppercase()).count() * 5 >= letters.len() * 4;
+            let severity = if as_is { Severity::High } else { Severity::Medium };
+            let mut description = if as_is { "Provided \"as is\"".to_string() } else { "Warranties disclaimed".to_string() };
+            if !disclaimed.is_empty() {
+                description.push_str(&format!(", disclaiming {}", disclaimed.join(", ")));
+            }
+            if disclaimed.iter().any(|w| w == "merchantability") && !conspicuous {
+                description.push_str("; the disclaimer is not conspicuous");
+            }
+            findings.push(finding(severity, description, Provision::WarrantyDisclaimer { as_is, disclaimed, conspicuous }));
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_clauses;
+
+    #[test]
+    fn test_provisions_are_read_clause_by_clause() {
+        let contract = "8. Indemnification. Acme Supplies LLC shall indemnify and hold harmless Beta Retail Inc \
+            from any and all claims, losses and attorneys' fees arising from third-party suits.\n\
+            9. Limitation of Liability. Each party's aggregate liability shall not exceed two (2) times the fees \
+            paid in the twelve months before the claim, except for gross negligence, fraud or breach of confidentiality. \
+            Neither party is liable for consequential damages.\n\
+            10. THE GOODS ARE PROVIDED \"AS IS\" AND SUPPLIER DISCLAIMS ALL IMPLIED WARRANTIES OF MERCHANTABILITY \
+            AND FITNESS FOR A PARTICULAR PURPOSE.\n\
+            11. Supplier's liability under this Agreement is limited to $50,000.\n\
+            12. Each party shall indemnify the other against claims caused by its breach.\n";
+        let parties = vec!["Acme Supplies LLC".to_string(), "Beta Retail Inc".to_string()];
+        let findings = analyze(&contract_clauses::segment(contract), &parties);
+        let outline: Vec<(&str, &str, Severity)> = findings
+            .iter()
+            .map(|f| (f.clause.as_deref().unwrap(), f.provision.category(), f.severity))
+            .collect();
+        assert_eq!(
+            outline,
+            vec![
+                ("8", "indemnification", Severity::High),
+                ("9", "liability", Severity::Low),
+                ("10", "warranty", Severity::High),
+                ("11", "liability", Severity::Low),
+                ("12", "indemnification", Severity::Low),
+            ]
+        );
+
+        let Provision::Indemnification { indemnitor, covers, .. } = &findings[0].provision else { panic!() };
+        assert_eq!(indemnitor.as_deref(), Some("Acme Supplies LLC"));
+        assert_eq!(covers, &["claims", "losses", "attorneys' fees", "third-party claims"]);
+        let Provision::LiabilityCap { multiplier, basis, carve_outs, excludes_consequential, .. } = &findings[1].provision else {
+            panic!()
+        };
+        assert_eq!(*multiplier, Some(2.0));
+        assert_eq!(basis.as_deref(), Some("fees paid in the twelve months before the claim"));
+        assert_eq!(carve_outs, &["gross negligence", "fraud", "confidentiality"]);
+        assert!(excludes_consequential);
+        let Provision::WarrantyDisclaimer { as_is, conspicuous, disclaimed } = &findings[2].provision else { panic!() };
+        assert!(*as_is && *conspicuous);
+        assert_eq!(disclaimed.len(), 2);
+        let Provision::LiabilityCap { cap, .. } = &findings[3].provision else { panic!() };
+        assert_eq!(cap.as_ref().map(|c| c.value), Some(50_000.0));
+        assert_eq!(findings[3].description, "Liability capped at USD 50000");
+
+        let json = serde_json::to_value(&findings[1]).unwrap();
+        assert_eq!(json["kind"], "liability_cap");
+        assert_eq!(json["clause"], "9");
+    }
+
+    #[test]
+    fn test_unlimited_liability_is_critical() {
+        let clauses = contract_clauses::segment("Customer accepts unlimited liability for misuse of the Software.");
+        let findings = analyze(&clauses, &[]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(findings[0].clause, None);
+    }
+}
@@ -0,0 +1,349 @@
+//! AxiomHive Contract Rule Packs
+//! Deterministic Legal Contract Summarization Pipeline
+//! What the analyzer treats as an obligation, how it categorizes one and
+//! which risk flags it raises are read from a `RulePack`, a JSON document
+//! that can be edited and loaded at runtime. Every pattern is a regular
+//! expression matched case-insensitively anywhere in a sentence.
+//!
+//! ```json
+//! {
+//!   "name": "default",
+//!   "version": "1",
+//!   "obligations": [{ "name": "modal_verbs", "pattern": "shall|must|will" }],
+//!   "categories": [{ "name": "financial", "pattern": "payment|pay|fee|cost" }],
+//!   "risks": [{
+//!     "name": "vague_language",
+//!     "severity": "low",
+//!     "category": "ambiguity",
+//!     "message": "Vague language detected",
+//!     "pattern": "reasonable|best efforts"
+//!   }]
+//! }
+//! ```
+//!
+//! A sentence is an obligation when any obligation rule matches it, and
+//! takes the first category whose pattern matches, or "general". A risk
+//! rule fires on an obligation when all of its conditions hold: its
+//! `pattern` matches, the obligation has `obligation_category`, and, with
+//! `missing_due_date`, the obligation has no due date.
+//!
+//! A rule's `weight` is what its flags add to the contract's risk score;
+//! without one the `scoring` section's weight for its severity applies
+//! (see contract_scoring.rs).
+//!
+//! There are built-in packs for English, the default, and for Spanish,
+//! French and German contracts (see contract_language.rs). They share
+//! rule names and categories, so their flags read the same downstream;
+//! only the patterns and the flags' messages are translated.
+
+use crate::contract_analyzer::{Obligation, RiskFlag, Severity};
+use crate::contract_language::Language;
+use crate::contract_scoring::ScoringConfig;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// A named pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternRule {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// A risk flag to raise on obligations meeting every condition given
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskRule {
+    pub name: String,
+    pub severity: Severity,
+    /// Category of the flag raised
+    pub category: String,
+    /// Start of the flag's description; the obligation follows it
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obligation_category: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing_due_date: bool,
+    /// Overrides the severity's weight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+/// Detection rules for the contract analyzer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RulePack {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    pub obligations: Vec<PatternRule>,
+    /// Tried in order
+    #[serde(default)]
+    pub categories: Vec<PatternRule>,
+    #[serde(default)]
+    pub risks: Vec<RiskRule>,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+}
+
+/// Why a rule pack could not be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulePackError {
+    Io(String),
+    Parse(String),
+    InvalidPattern { rule: String, error: String },
+    /// A risk rule with no condition would flag every obligation
+    NoCondition { rule: String },
+    NoObligationRules,
+    /// Weights and the scale must be finite, and not negative
+    InvalidWeight { rule: String },
+}
+
+impl fmt::Display for RulePackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulePackError::Io(e) => write!(f, "Cannot read rule pack: {}", e),
+            RulePackError::Parse(e) => write!(f, "Invalid rule pack: {}", e),
+            RulePackError::InvalidPattern { rule, error } => write!(f, "Rule {} has an invalid pattern: {}", rule, error),
+            RulePackError::NoCondition { rule } => write!(f, "Risk rule {} has no condition", rule),
+            RulePackError::NoObligationRules => write!(f, "Rule pack has no obligation rules"),
+            RulePackError::InvalidWeight { rule } => write!(f, "Rule {} has an invalid weight", rule),
+        }
+    }
+}
+
+impl std::error::Error for RulePackError {}
+
+impl RulePack {
+    pub fn from_json(json: &str) -> Result<Self, RulePackError> {
+        let pack: RulePack = serde_json::from_str(json).map_err(|e| RulePackError::Parse(e.to_string()))?;
+        CompiledRules::new(pack.clone())?;
+        Ok(pack)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RulePackError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| RulePackError::Io(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("rule packs serialize")
+    }
+}
+
+impl Default for RulePack {
+    /// The rules the analyzer has always applied
+    fn default() -> Self {
+        Self::for_language(Language::English)
+    }
+}
+
+/// Patterns and messages of a built-in rule pack
+struct BuiltIn {
+    name: &'static str,
+    obligations: &'static str,
+    /// Financial, delivery and maintenance
+    categories: [&'static str; 3],
+    /// Missing due date, financial obligation and vague language
+    messages: [&'static str; 3],
+    vague_language: &'static str,
+}
+
+impl RulePack {
+    /// The built-in rules for contracts in `language`
+    pub fn for_language(language: Language) -> Self {
+        let built_in = match language {
+            Language::English => BuiltIn {
+                name: "default",
+                obligations: "shall|must|will|agrees to|obligated to|required to|duty to|responsible for",
+                categories: ["payment|pay|fee|cost", "deliver|provide|supply", "maintain|keep|preserve"],
+                messages: ["Obligation missing due date", "Financial obligation", "Vague language detected"],
+                vague_language: "reasonable|best efforts|as appropriate|when possible",
+            },
+            Language::Spanish => BuiltIn {
+                name: "default-es",
+                obligations: r"\b(?:deberá|deberán|debe|deben|tendrá que|tendrán que)\b|se obligan? a|se comprometen? a|estará obligad|será responsable de",
+                categories: ["pag|tarifa|honorario|precio|coste|costo", "entreg|suministr|proporcion", "manten|conserv"],
+                messages: ["Obligación sin fecha de vencimiento", "Obligación financiera", "Lenguaje impreciso detectado"],
+                vague_language: "razonable|mejores esfuerzos|en la medida de lo posible|cuando sea posible",
+            },
+            Language::French => BuiltIn {
+                name: "default-fr",
+                obligations: r"\b(?:doit|doivent|devra|devront)\b|s['’](?:engage|oblige)(?:nt)? à|(?:est|sont) tenue?s? de|(?:est|sont) responsables? de",
+                categories: ["paiement|payer|frais|honoraire|prix|coût", "livr|fourni", "entreten|mainten|conserv"],
+                messages: ["Obligation sans date d'échéance", "Obligation financière", "Formulation imprécise détectée"],
+                vague_language: "raisonnable|meilleurs efforts|dans la mesure du possible|si possible",
+            },
+            Language::German => BuiltIn {
+                name: "default-de",
+                obligations: r"\b(?:muss|müssen|hat zu|haben zu|soll|sollen|wird|werden)\b|verpflichte[nt] sich|(?:ist|sind) verpflichtet",
+                categories: ["zahl|gebühr|vergütung|preis|kosten|entgelt", "liefer|bereitstell|stellt? bereit", "wart|instand|pfleg"],
+                messages: ["Verpflichtung ohne Fälligkeitsdatum", "Finanzielle Verpflichtung", "Vage Formulierung erkannt"],
+                vague_language: "angemessen|nach besten kräften|nach möglichkeit|soweit möglich",
+            },
+        };
+        let rule = |name: &str, pattern: &str| PatternRule { name: name.to_string(), pattern: pattern.to_string() };
+        let risk = |name: &str, severity, category: &str, message: &str| RiskRule {
+            name: name.to_string(),
+            severity,
+            category: category.to_string(),
+            message: message.to_string(),
+            pattern: None,
+            obligation_category: None,
+            missing_due_date: false,
+            weight: None,
+        };
+        let [missing_due_date, financial, vague] = built_in.messages;
+        Self {
+            name: built_in.name.to_string(),
+            version: "1".to_string(),
+            obligations: vec![rule("obligation_terms", built_in.obligations)],
+            categories: ["financial", "delivery", "maintenance"]
+                .into_iter()
+                .zip(built_in.categories)
+                .map(|(name, pattern)| rule(name, pattern))
+                .collect(),
+            risks: vec![
+                RiskRule {
+                    missing_due_date: true,
+                    ..risk("missing_due_date", Severity::Medium, "missing_information", missing_due_date)
+                },
+                RiskRule {
+                    obligation_category: Some("financial".to_string()),
+                    ..risk("financial_obligation", Severity::High, "financial", financial)
+                },
+                RiskRule {
+                    pattern: Some(built_in.vague_language.to_string()),
+                    ..risk("vague_language", Severity::Low, "ambiguity", vague)
+                },
+            ],
+            scoring: ScoringConfig::default(),
+        }
+    }
+}
+
+/// A rule pack with its patterns compiled
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledRules {
+    pack: RulePack,
+    obligations: Vec<Regex>,
+    categories: Vec<Regex>,
+    risks: Vec<Option<Regex>>,
+}
+
+impl CompiledRules {
+    pub(crate) fn new(pack: RulePack) -> Result<Self, RulePackError> {
+        let compile = |rule: &str, pattern: &str| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| RulePackError::InvalidPattern { rule: rule.to_string(), error: e.to_string() })
+        };
+        if pack.obligations.is_empty() {
+            return Err(RulePackError::NoObligationRules);
+        }
+        let valid = |weight: f64| weight.is_finite() && weight >= 0.0;
+        let weights = pack.scoring.severity_weights;
+        let scale = pack.scoring.scale;
+        if ![weights.low, weights.medium, weights.high, weights.critical, scale].into_iter().all(valid) || scale == 0.0 {
+            return Err(RulePackError::InvalidWeight { rule: "scoring".to_string() });
+        }
+        if let Some(rule) = pack.risks.iter().find(|r| r.weight.is_some_and(|w| !valid(w))) {
+            return Err(RulePackError::InvalidWeight { rule: rule.name.clone() });
+        }
+        let obligations = pack.obligations.iter().map(|r| compile(&r.name, &r.pattern)).collect::<Result<_, _>>()?;
+        let categories = pack.categories.iter().map(|r| compile(&r.name, &r.pattern)).collect::<Result<_, _>>()?;
+        let risks = pack
+            .risks
+            .iter()
+            .map(|r| {
+                if r.pattern.is_none() && r.obligation_category.is_none() && !r.missing_due_date {
+                    return Err(RulePackError::NoCondition { rule: r.name.clone() });
+                }
+                r.pattern.as_deref().map(|p| compile(&r.name, p)).transpose()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { pack, obligations, categories, risks })
+    }
+
+    pub(crate) fn pack(&self) -> &RulePack {
+        &self.pack
+    }
+
+    pub(crate) fn is_obligation(&self, sentence: &str) -> bool {
+        self.obligations.iter().any(|re| re.is_match(sentence))
+    }
+
+    pub(crate) fn category(&self, sentence: &str) -> &str {
+        self.categories
+            .iter()
+            .position(|re| re.is_match(sentence))
+            .map_or("general", |i| &self.pack.categories[i].name)
+    }
+
+    /// Flags the risk rules raise on `obligation`, in rule order
+    pub(crate) fn flags<'a>(&'a self, obligation: &'a Obligation) -> impl Iterator<Item = RiskFlag> + 'a {
+        let desc = obligation.description.chars().take(50).collect::<String>();
+        self.pack.risks.iter().zip(&self.risks).filter_map(move |(rule, pattern)| {
+            let fires = pattern.as_ref().is_none_or(|re| re.is_match(&obligation.description))
+                && rule.obligation_category.as_ref().is_none_or(|c| *c == obligation.category)
+                && (!rule.missing_due_date || obligation.due_date.is_none());
+            fires.then(|| RiskFlag {
+                severity: rule.severity,
+                category: rule.category.clone(),
+                description: format!("{}: {}", rule.message, desc),
+                clause: obligation.clause.clone(),
+                amounts: obligation.amounts.clone(),
+                weight: rule.weight.unwrap_or(self.pack.scoring.severity_weights.weight(rule.severity)),
+                evidence: obligation.evidence.clone(),
+            })
+        })
+    }
+}
+
+impl Default for CompiledRules {
+    fn default() -> Self {
+        Self::new(RulePack::default()).expect("the default rule pack compiles")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_pack_round_trips_and_bad_packs_are_rejected() {
+        let pack = RulePack::default();
+        assert_eq!(RulePack::from_json(&pack.to_json()).unwrap(), pack);
+
+        let json = r#"{"name": "strict", "obligations": [{"name": "modal", "pattern": "shall"}],
+            "risks": [{"name": "anything", "severity": "critical", "category": "x", "message": "m"}]}"#;
+        assert_eq!(RulePack::from_json(json), Err(RulePackError::NoCondition { rule: "anything".to_string() }));
+        let json = r#"{"name": "broken", "obligations": [{"name": "modal", "pattern": "(shall"}]}"#;
+        assert!(matches!(RulePack::from_json(json), Err(RulePackError::InvalidPattern { rule, .. }) if rule == "modal"));
+        assert_eq!(RulePack::from_json(r#"{"name": "none", "obligations": []}"#), Err(RulePackError::NoObligationRules));
+        assert!(matches!(RulePack::from_json("{"), Err(RulePackError::Parse(_))));
+        let json = r#"{"name": "negative", "obligations": [{"name": "modal", "pattern": "shall"}],
+            "risks": [{"name": "bonus", "severity": "low", "category": "x", "message": "m", "pattern": "x", "weight": -1}]}"#;
+        assert_eq!(RulePack::from_json(json), Err(RulePackError::InvalidWeight { rule: "bonus".to_string() }));
+    }
+
+    #[test]
+    fn test_built_in_packs_find_obligations_in_their_language() {
+        let cases = [
+            (Language::Spanish, "El Comprador se compromete a pagar la tarifa", "Lo hará cuando sea posible"),
+            (Language::French, "L’Acheteur s’engage à payer les frais", "Le Vendeur doit livrer si possible"),
+            (Language::German, "Der Käufer verpflichtet sich, die Gebühr zu zahlen", "Der Verkäufer liefert nach Möglichkeit"),
+        ];
+        for (language, obligation, vague) in cases {
+            let rules = CompiledRules::new(RulePack::for_language(language)).unwrap();
+            assert!(rules.is_obligation(obligation), "{}", obligation);
+            assert_eq!(rules.category(obligation), "financial");
+            assert!(!rules.is_obligation("Il était une fois"));
+            assert!(rules.risks[2].as_ref().unwrap().is_match(vague), "{}", vague);
+        }
+        assert_eq!(RulePack::for_language(Language::English), RulePack::default());
+    }
+}
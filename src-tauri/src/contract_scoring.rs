@@ -0,0 +1,210 @@
+//! AxiomHive Contract Risk Scoring
+//! Deterministic Legal Contract Summarization Pipeline
+//! Every risk flag carries a weight, set by the rule that raised it or by
+//! the rule pack's weight for its severity. The weights add up to an
+//! overall score of 100 (1 - e^(-total / scale)), which grows with every
+//! flag but never passes 100, so one critical finding reads as serious
+//! without a long contract's many small findings reading as worse than
+//! it. Each category's points are its share of the overall score.
+//!
+//! `certify` seals a contract's analysis the way mamba_audit.rs seals an
+//! inference: the analysis is rerun once per RiskCalculator iteration and
+//! the calculator scores the summaries. A contract is insurable when the
+//! reruns agree and its score is below the rule pack's threshold; only
+//! then is a token issued, and the token commits to the summary, score
+//! included.
+
+use crate::contract_analyzer::{ContractAnalyzer, ContractError, ContractSummary, RiskFlag, Severity};
+use axiom_risk_calculator::{RiskCalculator, RiskResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Weights of flags whose rule does not set one
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SeverityWeights {
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+    pub critical: f64,
+}
+
+impl SeverityWeights {
+    pub fn weight(&self, severity: Severity) -> f64 {
+        match severity {
+            Severity::Low => self.low,
+            Severity::Medium => self.medium,
+            Severity::High => self.high,
+            Severity::Critical => self.critical,
+        }
+    }
+}
+
+impl Default for SeverityWeights {
+    fn default() -> Self {
+        Self { low: 1.0, medium: 3.0, high: 7.0, critical: 15.0 }
+    }
+}
+
+/// How a rule pack turns flags into a score
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    pub severity_weights: SeverityWeights,
+    /// Total weight at which the score reaches 63, i.e. 1 - 1/e
+    pub scale: f64,
+    /// Contracts scoring this or more are not insurable
+    pub max_insurable_score: u32,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self { severity_weights: SeverityWeights::default(), scale: 30.0, max_insurable_score: 50 }
+    }
+}
+
+/// Points of one category of flags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryScore {
+    pub category: String,
+    pub flags: usize,
+    pub weight: f64,
+    /// Share of the overall score, rounded
+    pub points: u32,
+}
+
+/// Overall risk of a contract
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskScore {
+    /// 0 for no flags, approaching 100 as weight accumulates
+    pub score: u32,
+    pub total_weight: f64,
+    /// By category name
+    pub categories: Vec<CategoryScore>,
+}
+
+impl RiskScore {
+    pub fn new(flags: &[RiskFlag], config: &ScoringConfig) -> Self {
+        let mut categories: BTreeMap<&str, (usize, f64)> = BTreeMap::new();
+        for flag in flags {
+            let entry = categories.entry(&flag.category).or_default();
+            entry.0 += 1;
+            entry.1 += flag.weight;
+        }
+        let total_weight: f64 = categories.values().map(|&(_, weight)| weight).sum();
+        let exact = 100.0 * (1.0 - (-total_weight / config.scale).exp());
+        let categories = categories
+            .into_iter()
+            .map(|(category, (flags, weight))| CategoryScore {
+                category: category.to_string(),
+                flags,
+                weight,
+                points: if total_weight > 0.0 { (exact * weight / total_weight).round() as u32 } else { 0 },
+            })
+            .collect();
+        Self { score: exact.round() as u32, total_weight, categories }
+    }
+}
+
+/// Whether a contract can be insured, and if not why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum InsurabilityVerdict {
+    Insurable,
+    /// The reruns of the analysis disagreed
+    NonDeterministic,
+    RiskTooHigh { score: u32, max_insurable_score: u32 },
+}
+
+/// A contract summary scored by the RiskCalculator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractCertificate {
+    pub summary: ContractSummary,
+    /// Score of the summaries of every iteration
+    pub risk: RiskResult,
+    pub verdict: InsurabilityVerdict,
+    /// Issued only for an insurable contract
+    pub insurance_token: Option<String>,
+}
+
+/// Analyze `contract_text` once per calculator iteration, score the
+/// summaries with `calculator` and issue a token if they agree and the
+/// contract's risk is acceptable
+pub fn certify(
+    analyzer: &ContractAnalyzer,
+    contract_text: &str,
+    calculator: &RiskCalculator,
+) -> Result<ContractCertificate, ContractError> {
+    let runs = (0..calculator.iteration_count().max(1))
+        .map(|_| analyzer.analyze_contract(contract_text))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outputs: Vec<String> = runs.iter().map(|summary| serde_json::to_string(summary).expect("summaries serialize")).collect();
+    let risk = calculator.verify_outputs(&outputs);
+    let summary = runs.into_iter().next().expect("at least one run");
+
+    let max_insurable_score = analyzer.rules().scoring.max_insurable_score;
+    let verdict = if risk.risk_score != 0 {
+        InsurabilityVerdict::NonDeterministic
+    } else if summary.risk_score.score >= max_insurable_score {
+        InsurabilityVerdict::RiskTooHigh { score: summary.risk_score.score, max_insurable_score }
+    } else {
+        InsurabilityVerdict::Insurable
+    };
+    let insurance_token = match verdict {
+        InsurabilityVerdict::Insurable => calculator.issue_insurance_token(&risk),
+        _ => None,
+    };
+    Ok(ContractCertificate { summary, risk, verdict, insurance_token })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_evidence::Evidence;
+
+    fn flag(category: &str, weight: f64) -> RiskFlag {
+        RiskFlag {
+            severity: Severity::Low,
+            category: category.to_string(),
+            description: String::new(),
+            clause: None,
+            amounts: Vec::new(),
+            weight,
+            evidence: Evidence::default(),
+        }
+    }
+
+    #[test]
+    fn test_score_saturates_and_breaks_down_by_category() {
+        let config = ScoringConfig::default();
+        assert_eq!(RiskScore::new(&[], &config).score, 0);
+        let score = RiskScore::new(&[flag("financial", 7.0), flag("ambiguity", 1.0), flag("financial", 7.0)], &config);
+        // 100 * (1 - e^(-15/30)) = 39.3
+        assert_eq!(score.score, 39);
+        assert_eq!(score.total_weight, 15.0);
+        let breakdown: Vec<(&str, usize, u32)> = score.categories.iter().map(|c| (c.category.as_str(), c.flags, c.points)).collect();
+        assert_eq!(breakdown, vec![("ambiguity", 1, 3), ("financial", 2, 37)]);
+        let many: Vec<RiskFlag> = (0..200).map(|_| flag("liability", 15.0)).collect();
+        assert_eq!(RiskScore::new(&many, &config).score, 100);
+    }
+
+    #[test]
+    fn test_certificate_needs_determinism_and_an_acceptable_score() {
+        let calculator = RiskCalculator::new();
+        let analyzer = ContractAnalyzer::new(true);
+        let benign = "Acme Supplies LLC shall deliver the goods by 2024-02-01.";
+        let certificate = certify(&analyzer, benign, &calculator).unwrap();
+        assert_eq!(certificate.verdict, InsurabilityVerdict::Insurable);
+        assert_eq!(certificate.risk.iteration_count, calculator.iteration_count());
+        assert!(certificate.insurance_token.is_some());
+        assert_eq!(certify(&analyzer, benign, &calculator).unwrap().insurance_token, certificate.insurance_token);
+
+        let risky = "Customer accepts unlimited liability. Customer shall pay all fees on demand. \
+            Customer shall indemnify Supplier against any and all claims. \
+            Customer shall use best efforts to pay any costs when possible.";
+        let certificate = certify(&analyzer, risky, &calculator).unwrap();
+        assert!(certificate.summary.risk_score.score >= 50, "{:?}", certificate.summary.risk_score);
+        assert!(matches!(certificate.verdict, InsurabilityVerdict::RiskTooHigh { max_insurable_score: 50, .. }));
+        assert_eq!(certificate.insurance_token, None);
+        assert_eq!(certificate.risk.risk_score, 0);
+    }
+}
@@ -0,0 +1,204 @@
+//! AxiomHive Confidential Contract Summaries
+//! Deterministic Legal Contract Summarization Pipeline
+//! `ContractAnalyzer::analyze_contract_sealed` encrypts a summary under a
+//! DeoxysFHE key so it can be stored without exposing what the contract
+//! says. The sealed summary holds ciphertexts and the summary's
+//! cryptographic seal, nothing else in the clear.
+//!
+//! The whole summary is one field, "summary". Party names, each
+//! obligation's amounts and each currency's exposure are also fields of
+//! their own, so a key holder can decrypt one of them without the rest:
+//! "parties/0", "obligations/2/amounts", "exposure/0". A field is its
+//! JSON, padded as `PublicKey::encrypt_bytes` pads bytes and packed
+//! `SLOTS` limbs to a ciphertext (see fhe_batch.rs).
+//!
+//! Packed ciphertexts are malleable. `open` checks the decrypted summary
+//! against the seal; a single field decrypted on its own is not checked.
+
+use crate::contract_analyzer::{ContractError, ContractSummary};
+use crate::fhe_batch::{BatchPublicKey, BatchSecretKey, PackedCiphertext};
+use crate::fhe_core::{DecryptError, DeoxysFHE};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Limbs per packed ciphertext
+const SLOTS: usize = 64;
+
+/// A contract summary encrypted field by field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedSummary {
+    /// The summary's seal, in the clear
+    pub cryptographic_seal: String,
+    /// Identifies the key the fields are encrypted under
+    pub key_id: String,
+    pub fields: BTreeMap<String, Vec<PackedCiphertext>>,
+}
+
+/// Why a summary could not be sealed or opened
+#[derive(Debug, Clone, PartialEq)]
+pub enum SealedError {
+    Analysis(ContractError),
+    Encryption(String),
+    /// The key is not the one the summary was sealed under
+    WrongKey,
+    UnknownField(String),
+    Decryption { field: String, error: DecryptError },
+    /// The field decrypted, but not to the JSON sealed
+    Corrupt { field: String },
+    /// The decrypted summary's seal is not the one stored
+    SealMismatch,
+}
+
+impl fmt::Display for SealedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SealedError::Analysis(e) => write!(f, "{}", e),
+            SealedError::Encryption(e) => write!(f, "Cannot encrypt summary: {}", e),
+            SealedError::WrongKey => write!(f, "Summary was sealed under a different key"),
+            SealedError::UnknownField(field) => write!(f, "Sealed summary has no field {}", field),
+            SealedError::Decryption { field, error } => write!(f, "Cannot decrypt {}: {}", field, error),
+            SealedError::Corrupt { field } => write!(f, "Field {} did not decrypt to its value", field),
+            SealedError::SealMismatch => write!(f, "Decrypted summary does not match its seal"),
+        }
+    }
+}
+
+impl std::error::Error for SealedError {}
+
+/// First 16 hex digits of a hash over the public key
+fn key_id(fhe: &DeoxysFHE) -> String {
+    let digest = Sha256::digest(fhe.public_key().to_bytes());
+    digest.iter().take(8).map(|b| format!("{:02x}", b)).collect()
+}
+
+fn encrypt_field<R: RngCore + CryptoRng>(
+    public: &BatchPublicKey,
+    value: &serde_json::Value,
+    rng: &mut R,
+) -> Result<Vec<PackedCiphertext>, String> {
+    let limbs = public.params().limbs(value.to_string().as_bytes())?;
+    limbs.chunks(SLOTS).map(|chunk| public.encrypt_batch(chunk, rng)).collect()
+}
+
+/// Encrypt `summary` under `fhe`'s key
+pub(crate) fn seal<R: RngCore + CryptoRng>(
+    summary: &ContractSummary,
+    fhe: &DeoxysFHE,
+    rng: &mut R,
+) -> Result<SealedSummary, SealedError> {
+    let (public, _) = fhe.batch_keys(SLOTS).map_err(SealedError::Encryption)?;
+    let mut values = BTreeMap::new();
+    values.insert("summary".to_string(), serde_json::to_value(summary).expect("summaries serialize"));
+    for (i, party) in summary.metadata.parties.iter().enumerate() {
+        values.insert(format!("parties/{}", i), serde_json::json!(party));
+    }
+    for (i, obligation) in summary.key_obligations.iter().enumerate().filter(|(_, o)| !o.amounts.is_empty()) {
+        values.insert(format!("obligations/{}/amounts", i), serde_json::json!(obligation.amounts));
+    }
+    for (i, exposure) in summary.exposure.iter().enumerate() {
+        values.insert(format!("exposure/{}", i), serde_json::json!(exposure));
+    }
+
+    let fields = values
+        .into_iter()
+        .map(|(field, value)| Ok((field, encrypt_field(&public, &value, rng).map_err(SealedError::Encryption)?)))
+        .collect::<Result<_, SealedError>>()?;
+    Ok(SealedSummary { cryptographic_seal: summary.cryptographic_seal.clone(), key_id: key_id(fhe), fields })
+}
+
+impl SealedSummary {
+    fn secret(&self, fhe: &DeoxysFHE) -> Result<BatchSecretKey, SealedError> {
+        if key_id(fhe) != self.key_id {
+            return Err(SealedError::WrongKey);
+        }
+        let (_, secret) = fhe.batch_keys(SLOTS).map_err(SealedError::Encryption)?;
+        Ok(secret)
+    }
+
+    fn decrypt(&self, secret: &BatchSecretKey, field: &str) -> Result<serde_json::Value, SealedError> {
+        let ciphertexts = self.fields.get(field).ok_or_else(|| SealedError::UnknownField(field.to_string()))?;
+        let decryption = |error| SealedError::Decryption { field: field.to_string(), error };
+        let limbs = ciphertexts
+            .iter()
+            .map(|ct| secret.decrypt_batch(ct))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(decryption)?;
+        let bytes = ciphertexts
+            .first()
+            .map_or(Err(DecryptError::Invalid("Field has no ciphertexts".to_string())), |ct| {
+                ct.params().unpad(limbs.into_iter().flatten())
+            })
+            .map_err(decryption)?;
+        serde_json::from_slice(&bytes).map_err(|_| SealedError::Corrupt { field: field.to_string() })
+    }
+
+    /// Decrypt `fields`, each to its JSON value
+    pub fn decrypt_fields(&self, fhe: &DeoxysFHE, fields: &[String]) -> Result<BTreeMap<String, serde_json::Value>, SealedError> {
+        let secret = self.secret(fhe)?;
+        fields.iter().map(|field| Ok((field.clone(), self.decrypt(&secret, field)?))).collect()
+    }
+
+    /// Decrypt the whole summary and check it against the seal
+    pub fn open(&self, fhe: &DeoxysFHE) -> Result<ContractSummary, SealedError> {
+        let value = self.decrypt(&self.secret(fhe)?, "summary")?;
+        let summary: ContractSummary =
+            serde_json::from_value(value).map_err(|_| SealedError::Corrupt { field: "summary".to_string() })?;
+        if summary.cryptographic_seal != self.cryptographic_seal {
+            return Err(SealedError::SealMismatch);
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_analyzer::ContractAnalyzer;
+    use crate::fhe_core::ParamPreset;
+
+    const CONTRACT: &str = "This Agreement is made between Acme Supplies LLC and Beta Retail Inc. \
+        Beta Retail Inc shall pay a fee of $12,500 by 2024-03-01. \
+        Acme Supplies LLC shall deliver the goods by 2024-02-01.";
+
+    /// Every object key and string value in `value`
+    fn strings(value: &serde_json::Value) -> Vec<&str> {
+        match value {
+            serde_json::Value::String(s) => vec![s.as_str()],
+            serde_json::Value::Array(items) => items.iter().flat_map(strings).collect(),
+            serde_json::Value::Object(map) => {
+                map.iter().flat_map(|(key, value)| std::iter::once(key.as_str()).chain(strings(value))).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_key_holders_decrypt_single_fields_or_the_whole_summary() {
+        let fhe = DeoxysFHE::with_params(Some(b"contract sealing test"), ParamPreset::Small.params());
+        let analyzer = ContractAnalyzer::new(true);
+        let sealed = analyzer.analyze_contract_sealed(CONTRACT, &fhe, &mut rand_core::OsRng).unwrap();
+        // Ciphertext coefficients are numbers, so any plaintext that leaked
+        // would be in a string: a field name, the key id or the seal
+        let stored = serde_json::to_value(&sealed).unwrap();
+        let leaked = strings(&stored).into_iter().filter(|s| s.contains("Acme") || s.contains("12,500") || s.contains("12500"));
+        assert_eq!(leaked.collect::<Vec<_>>(), Vec::<&str>::new());
+
+        let fields = sealed
+            .decrypt_fields(&fhe, &["parties/0".to_string(), "obligations/0/amounts".to_string()])
+            .unwrap();
+        let summary = analyzer.analyze_contract(CONTRACT).unwrap();
+        assert_eq!(fields["parties/0"], summary.metadata.parties[0].as_str());
+        assert_eq!(fields["obligations/0/amounts"][0]["value"], 12_500.0);
+        assert_eq!(sealed.open(&fhe).unwrap(), summary);
+
+        let other = DeoxysFHE::with_params(Some(b"someone else"), ParamPreset::Small.params());
+        assert_eq!(sealed.open(&other), Err(SealedError::WrongKey));
+        assert_eq!(
+            sealed.decrypt_fields(&fhe, &["parties/9".to_string()]),
+            Err(SealedError::UnknownField("parties/9".to_string()))
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! AxiomHive Contract Timeline
+//! Deterministic Legal Contract Summarization Pipeline
+//! Lays a contract's dates out in order: the term from the effective to
+//! the termination date, each dated obligation as a deliverable, payment
+//! or other obligation, renewal dates, and the last day to give notice
+//! of non-renewal, counted back from the termination date ("at least 60
+//! days before"). Payments recurring monthly, quarterly or annually are
+//! listed once per occurrence up to the termination date; without one,
+//! the first payment is listed with its recurrence.
+//!
+//! `to_ical` exports the timeline as an RFC 5545 calendar of all-day
+//! events. Event UIDs and timestamps are derived from the events
+//! themselves, so the same contract always exports the same calendar.
+
+use crate::contract_amounts::MonetaryAmount;
+use crate::contract_analyzer::{ContractMetadata, Obligation};
+use crate::contract_clauses::Clause;
+use crate::contract_dates::DateParser;
+use chrono::{Days, Months, NaiveDate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Occurrences listed for a recurring payment at most
+const MAX_OCCURRENCES: usize = 120;
+
+/// What happens on a date, in the order events of a day are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// Start of the term, which runs to `end`
+    Effective,
+    Deliverable,
+    Payment,
+    Obligation,
+    NoticeDeadline,
+    Renewal,
+    Termination,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recurrence {
+    Monthly,
+    Quarterly,
+    Annually,
+}
+
+impl Recurrence {
+    fn months(self) -> u32 {
+        match self {
+            Recurrence::Monthly => 1,
+            Recurrence::Quarterly => 3,
+            Recurrence::Annually => 12,
+        }
+    }
+}
+
+/// A dated entry of the timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    /// ISO 8601
+    pub date: String,
+    /// Last day of a span, as ISO 8601
+    pub end: Option<String>,
+    pub kind: EventKind,
+    pub description: String,
+    pub party: Option<String>,
+    pub clause: Option<String>,
+    pub amounts: Vec<MonetaryAmount>,
+    /// Set when the event repeats beyond what is listed
+    pub recurrence: Option<Recurrence>,
+}
+
+/// Dated events of a contract in chronological order
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Timeline {
+    pub events: Vec<TimelineEvent>,
+}
+
+fn parse(iso: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(iso, "%Y-%m-%d").ok()
+}
+
+fn iso(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+impl Timeline {
+    /// Timeline of a contract with `metadata`, `clauses` and
+    /// `obligations`; `dates` reads the dates of renewal clauses
+    pub fn new(metadata: &ContractMetadata, clauses: &[Clause], obligations: &[Obligation], dates: &DateParser) -> Self {
+        let event = |date: String, kind, description: String| TimelineEvent {
+            date,
+            end: None,
+            kind,
+            description,
+            party: None,
+            clause: None,
+            amounts: Vec::new(),
+            recurrence: None,
+        };
+        let mut events = Vec::new();
+        let termination = metadata.termination_date.as_deref().and_then(parse);
+
+        if let Some(effective) = &metadata.effective_date {
+            events.push(TimelineEvent {
+                end: metadata.termination_date.clone(),
+                ..event(effective.clone(), EventKind::Effective, "Contract term begins".to_string())
+            });
+        }
+        if let Some(termination) = &metadata.termination_date {
+            events.push(event(termination.clone(), EventKind::Termination, "Contract term ends".to_string()));
+        }
+
+        let recurrence_re = [
+            (Recurrence::Monthly, Regex::new(r"(?i)\bmonthly\b|\b(?:each|every|per)\s+(?:calendar\s+)?month\b").unwrap()),
+            (Recurrence::Quarterly, Regex::new(r"(?i)\bquarterly\b|\b(?:each|every|per)\s+(?:calendar\s+)?quarter\b").unwrap()),
+            (Recurrence::Annually, Regex::new(r"(?i)\bannually\b|\byearly\b|\bper\s+annum\b|\b(?:each|every)\s+year\b").unwrap()),
+        ];
+        for obligation in obligations {
+            let Some(due) = obligation.due_date.as_deref().and_then(parse) else { continue };
+            let kind = match obligation.category.as_str() {
+                "financial" => EventKind::Payment,
+                "delivery" => EventKind::Deliverable,
+                _ => EventKind::Obligation,
+            };
+            let base = TimelineEvent {
+                party: Some(obligation.party.clone()),
+                clause: obligation.clause.clone(),
+                amounts: obligation.amounts.clone(),
+                ..event(iso(due), kind, obligation.description.clone())
+            };
+            let recurrence = recurrence_re
+                .iter()
+                .find(|(_, re)| kind == EventKind::Payment && re.is_match(&obligation.description))
+                .map(|&(recurrence, _)| recurrence);
+            match (recurrence, termination) {
+                (Some(recurrence), Some(termination)) => {
+                    let occurrences: Vec<NaiveDate> = (0..MAX_OCCURRENCES as u32)
+                        .map_while(|n| due.checked_add_months(Months::new(n * recurrence.months())))
+                        .take_while(|&date| date <= termination)
+                        .collect();
+                    let count = occurrences.len();
+                    events.extend(occurrences.into_iter().enumerate().map(|(n, date)| TimelineEvent {
+                        date: iso(date),
+                        description: format!("{} (payment {} of {})", obligation.description, n + 1, count),
+                        ..base.clone()
+                    }));
+                }
+                (recurrence, _) => events.push(TimelineEvent { recurrence, ..base }),
+            }
+        }
+
+        let renew_re = Regex::new(r"(?i)\brenew").unwrap();
+        let notice_re = Regex::new(r"(?i)(\d+)\s+days?\s+(?:prior\s+to|before)").unwrap();
+        for clause in clauses.iter().filter(|c| renew_re.is_match(&c.text)) {
+            let excerpt: String = clause.text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(200).collect();
+            let cited = |e: TimelineEvent| TimelineEvent { clause: clause.reference.clone(), ..e };
+            for date in dates.extract(&clause.text) {
+                events.push(cited(event(date.iso, EventKind::Renewal, excerpt.clone())));
+            }
+            let notice = notice_re.captures(&clause.text).and_then(|cap| cap[1].parse::<u64>().ok());
+            if let (Some(days), Some(termination)) = (notice, termination) {
+                if let Some(deadline) = termination.checked_sub_days(Days::new(days)) {
+                    let description = format!("Last day for notice of non-renewal, {} days before {}", days, iso(termination));
+                    events.push(cited(event(iso(deadline), EventKind::NoticeDeadline, description)));
+                }
+            }
+        }
+
+        events.sort_by(|a, b| a.date.cmp(&b.date).then(a.kind.cmp(&b.kind)));
+        Self { events }
+    }
+
+    /// The timeline as an iCalendar document named `name`
+    pub fn to_ical(&self, name: &str) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//AxiomHive//Contract Timeline//EN".to_string(),
+            format!("X-WR-CALNAME:{}", escape(name)),
+        ];
+        for (i, event) in self.events.iter().enumerate() {
+            let Some(start) = parse(&event.date) else { continue };
+            let compact = |date: NaiveDate| date.format("%Y%m%d").to_string();
+            let uid = Sha256::digest(format!("{}|{:?}|{}|{}", event.date, event.kind, event.description, i).as_bytes());
+            let uid: String = uid.iter().take(16).map(|b| format!("{:02x}", b)).collect();
+            // All-day events end on the day after
+            let end = event.end.as_deref().and_then(parse).unwrap_or(start);
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}@axiomhive", uid));
+            lines.push(format!("DTSTAMP:{}T000000Z", compact(start)));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", compact(start)));
+            lines.push(format!("DTEND;VALUE=DATE:{}", compact(end.succ_opt().unwrap_or(end))));
+            let summary = match &event.clause {
+                Some(clause) => format!("[{}] {}", clause, event.description),
+                None => event.description.clone(),
+            };
+            lines.push(format!("SUMMARY:{}", escape(&summary)));
+            lines.push(format!("CATEGORIES:{}", serde_json::to_value(event.kind).expect("kinds serialize").as_str().unwrap_or("")));
+            if let Some(recurrence) = event.recurrence {
+                lines.push(format!("RRULE:FREQ=MONTHLY;INTERVAL={}", recurrence.months()));
+            }
+            lines.push("END:VEVENT".to_string());
+        }
+        lines.push("END:VCALENDAR".to_string());
+        lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+    }
+}
+
+/// Text with the characters iCalendar reserves escaped
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// `line` split into lines of at most 75 octets, continuations indented
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contract_analyzer::ContractAnalyzer;
+
+    const CONTRACT: &str = "1. This Agreement is effective January 1, 2024 between Acme Supplies LLC and Beta Retail Inc.\n\
+        2. Acme Supplies LLC shall deliver the goods by March 15, 2024.\n\
+        3. Beta Retail Inc shall pay a quarterly fee of $1,500 starting February 1, 2024.\n\
+        4. This Agreement renews automatically unless either party gives notice at least 60 days before it ends.\n\
+        5. This Agreement terminates on December 31, 2024.\n";
+
+    #[test]
+    fn test_timeline_orders_dated_events_and_expands_payments() {
+        let summary = ContractAnalyzer::new(true).analyze_contract(CONTRACT).unwrap();
+        let events: Vec<(&str, String)> = summary
+            .timeline
+            .events
+            .iter()
+            .map(|e| (e.date.as_str(), serde_json::to_value(e.kind).unwrap().as_str().unwrap().to_string()))
+            .collect();
+        let expected = [
+            ("2024-01-01", "effective"),
+            ("2024-02-01", "payment"),
+            ("2024-03-15", "deliverable"),
+            ("2024-05-01", "payment"),
+            ("2024-08-01", "payment"),
+            ("2024-11-01", "payment"),
+            ("2024-11-01", "notice_deadline"),
+            ("2024-12-31", "termination"),
+        ];
+        assert_eq!(events, expected.iter().map(|&(d, k)| (d, k.to_string())).collect::<Vec<_>>());
+        assert_eq!(summary.timeline.events[0].end.as_deref(), Some("2024-12-31"));
+        assert!(summary.timeline.events[1].description.ends_with("(payment 1 of 4)"));
+        assert_eq!(summary.timeline.events[6].clause.as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn test_ical_export_is_deterministic_and_folded() {
+        let summary = ContractAnalyzer::new(true).analyze_contract(CONTRACT).unwrap();
+        let ical = summary.timeline.to_ical("Supply, 2024");
+        assert_eq!(ical, ContractAnalyzer::new(true).analyze_contract(CONTRACT).unwrap().timeline.to_ical("Supply, 2024"));
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n") && ical.ends_with("END:VCALENDAR\r\n"));
+        assert!(ical.contains("X-WR-CALNAME:Supply\\, 2024\r\n"));
+        assert!(ical.contains("DTSTART;VALUE=DATE:20240101\r\nDTEND;VALUE=DATE:20250101\r\n"));
+        assert_eq!(ical.matches("BEGIN:VEVENT").count(), summary.timeline.events.len());
+        assert!(ical.split("\r\n").all(|line| line.len() <= 75));
+    }
+}
@@ -0,0 +1,249 @@
+//! Encrypted risk verification
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Zero Entropy checked over encrypted iteration hashes. The prover packs
+//! every iteration digest into one `PackedCiphertext`, one limb per slot.
+//! A remote verifier, holding neither key nor hashes, subtracts every pair
+//! of digests homomorphically and scales each difference by a random odd
+//! mask. The key holder then only learns which masked differences are zero,
+//! and the verifier counts the distinct states from that.
+//!
+//! T is a power of two, so an odd mask is invertible mod T: zero stays zero
+//! and a nonzero limb stays nonzero, while its value beyond the number of
+//! trailing zero bits is hidden.
+
+use crate::fhe_batch::{BatchPublicKey, BatchSecretKey, PackedCiphertext};
+use crate::fhe_core::{DecryptError, FheParams};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Every iteration digest of one run, encrypted in order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedDigests {
+    pub digest_bytes: usize,
+    pub digests: Vec<PackedCiphertext>,
+}
+
+/// Masked difference of digests `i` and `j`, zero exactly when they match
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaskedDifference {
+    pub i: usize,
+    pub j: usize,
+    pub ciphertext: PackedCiphertext,
+}
+
+/// What the verifier sends to the key holder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZeroTestQuery {
+    pub iterations: usize,
+    pub differences: Vec<MaskedDifference>,
+}
+
+/// The key holder's reply: for every difference in the query, in order,
+/// whether it decrypted to zero in all slots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZeroTestAnswer {
+    pub equal: Vec<bool>,
+}
+
+/// The verifier's conclusion, with the same measures as a `RiskResult`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedRiskVerdict {
+    pub iterations: usize,
+    /// Number of distinct digests
+    pub entropy_count: usize,
+    /// Iterations that disagree with the most common digest
+    pub divergences: usize,
+    pub zero_entropy: bool,
+}
+
+/// Slots needed for a digest of `digest_bytes` bytes under `params`
+pub fn digest_slots(params: &FheParams, digest_bytes: usize) -> usize {
+    digest_bytes.div_ceil(params.limb_bytes().max(1))
+}
+
+/// Encrypt hex `hashes`, all of one length, one packed ciphertext each.
+/// `public` needs at least `digest_slots` slots.
+pub fn encrypt_digests<R: RngCore + CryptoRng>(
+    public: &BatchPublicKey,
+    hashes: &[String],
+    rng: &mut R,
+) -> Result<EncryptedDigests, String> {
+    let params = *public.params();
+    let width = params.limb_bytes();
+    if width == 0 {
+        return Err(format!("Plaintext modulus {} cannot hold a byte", params.t()));
+    }
+    let decoded = hashes.iter().map(|hash| decode_hex(hash)).collect::<Result<Vec<_>, _>>()?;
+    let digest_bytes = match decoded.first() {
+        Some(first) if !first.is_empty() => first.len(),
+        _ => return Err("No digests to encrypt".to_string()),
+    };
+    if decoded.iter().any(|bytes| bytes.len() != digest_bytes) {
+        return Err("Digests differ in length".to_string());
+    }
+
+    let digests = decoded
+        .iter()
+        .map(|bytes| {
+            let limbs: Vec<i32> = bytes
+                .chunks(width)
+                .map(|limb| limb.iter().fold(0i32, |acc, &b| (acc << 8) | b as i32))
+                .collect();
+            public.encrypt_batch(&limbs, rng)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(EncryptedDigests { digest_bytes, digests })
+}
+
+impl EncryptedDigests {
+    /// Verifier side: the masked difference of every pair of digests. Each
+    /// mask is odd and as large as the noise budget allows.
+    pub fn zero_test_query<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<ZeroTestQuery, String> {
+        let first = self.digests.first().ok_or("No digests to compare")?;
+        let params = first.params();
+        // A difference carries twice the fresh noise, and masking
+        // multiplies that by the mask
+        let noise = self.digests.iter().map(PackedCiphertext::noise_bound).max().unwrap_or(0);
+        let max_mask = (params.max_noise() / (2 * noise).max(1)).min(params.t() as i64 / 2 - 1);
+        if max_mask < 1 {
+            return Err("Digests have no noise budget left for masking".to_string());
+        }
+
+        let mut differences = Vec::new();
+        for (i, a) in self.digests.iter().enumerate() {
+            for (j, b) in self.digests.iter().enumerate().skip(i + 1) {
+                // Uniform over the odd values in [1, max_mask]
+                let odd_masks = (max_mask as u64).div_ceil(2);
+                let mask = 2 * (rng.next_u64() % odd_masks) + 1;
+                differences.push(MaskedDifference {
+                    i,
+                    j,
+                    ciphertext: a.sub(b)?.mul_plain(mask as i32),
+                });
+            }
+        }
+        Ok(ZeroTestQuery {
+            iterations: self.digests.len(),
+            differences,
+        })
+    }
+}
+
+/// Key holder side: which differences in `query` are zero
+pub fn answer_zero_test(secret: &BatchSecretKey, query: &ZeroTestQuery) -> Result<ZeroTestAnswer, DecryptError> {
+    let equal = query
+        .differences
+        .iter()
+        .map(|d| Ok(secret.decrypt_batch(&d.ciphertext)?.iter().all(|&limb| limb == 0)))
+        .collect::<Result<_, DecryptError>>()?;
+    Ok(ZeroTestAnswer { equal })
+}
+
+impl ZeroTestQuery {
+    /// Count distinct digests from the key holder's answer: an iteration is
+    /// a new state unless it equals an earlier one
+    pub fn verdict(&self, answer: &ZeroTestAnswer) -> Result<EncryptedRiskVerdict, String> {
+        if answer.equal.len() != self.differences.len() {
+            return Err(format!(
+                "Answer covers {} differences, the query has {}",
+                answer.equal.len(),
+                self.differences.len()
+            ));
+        }
+        let n = self.iterations;
+        // Index of the first iteration each one equals
+        let mut class: Vec<usize> = (0..n).collect();
+        for (d, &equal) in self.differences.iter().zip(&answer.equal) {
+            if d.i >= d.j || d.j >= n {
+                return Err("Query pairs are out of range".to_string());
+            }
+            if equal && class[d.j] == d.j {
+                class[d.j] = class[d.i];
+            }
+        }
+        let mut sizes = vec![0usize; n];
+        class.iter().for_each(|&c| sizes[c] += 1);
+        let entropy_count = sizes.iter().filter(|&&size| size > 0).count();
+        let divergences = n - sizes.iter().max().copied().unwrap_or(0);
+        Ok(EncryptedRiskVerdict {
+            iterations: n,
+            entropy_count,
+            divergences,
+            zero_entropy: n > 0 && entropy_count == 1,
+        })
+    }
+}
+
+fn decode_hex(hash: &str) -> Result<Vec<u8>, String> {
+    if !hash.len().is_multiple_of(2) || !hash.is_ascii() {
+        return Err(format!("Invalid hex digest: {}", hash));
+    }
+    (0..hash.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hash[i..i + 2], 16).map_err(|_| format!("Invalid hex digest: {}", hash)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fhe_core::DeoxysFHE;
+    use rand_core::OsRng;
+    use sha2::{Digest, Sha256};
+
+    fn sha256_hex(data: &str) -> String {
+        Sha256::digest(data.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn verify(hashes: &[String]) -> EncryptedRiskVerdict {
+        let fhe = DeoxysFHE::new(None);
+        let (public, secret) = fhe.batch_keys(digest_slots(fhe.params(), 32)).unwrap();
+        let encrypted = encrypt_digests(&public, hashes, &mut OsRng).unwrap();
+        let query = encrypted.zero_test_query(&mut OsRng).unwrap();
+        let answer = answer_zero_test(&secret, &query).unwrap();
+        query.verdict(&answer).unwrap()
+    }
+
+    #[test]
+    fn test_matching_digests_show_zero_entropy() {
+        let hashes = vec![sha256_hex("input"); 10];
+        let verdict = verify(&hashes);
+        assert_eq!(verdict.iterations, 10);
+        assert_eq!(verdict.entropy_count, 1);
+        assert_eq!(verdict.divergences, 0);
+        assert!(verdict.zero_entropy);
+    }
+
+    #[test]
+    fn test_divergent_digests_are_counted() {
+        let mut hashes = vec![sha256_hex("input"); 6];
+        hashes[2] = sha256_hex("drift");
+        hashes[4] = sha256_hex("drift");
+        hashes[5] = sha256_hex("other");
+        let verdict = verify(&hashes);
+        assert_eq!(verdict.entropy_count, 3);
+        assert_eq!(verdict.divergences, 3);
+        assert!(!verdict.zero_entropy);
+
+        // A digest one bit off is still told apart
+        let mut flipped = vec![sha256_hex("input"); 2];
+        let last = if flipped[0].ends_with('0') { "1" } else { "0" };
+        flipped[1].replace_range(63.., last);
+        assert_eq!(verify(&flipped).entropy_count, 2);
+    }
+
+    #[test]
+    fn test_inputs_are_checked() {
+        let fhe = DeoxysFHE::new(None);
+        let (public, _) = fhe.batch_keys(16).unwrap();
+        assert!(encrypt_digests(&public, &[], &mut OsRng).is_err());
+        assert!(encrypt_digests(&public, &["abc".to_string()], &mut OsRng).is_err());
+        assert!(encrypt_digests(&public, &["zz".to_string()], &mut OsRng).is_err());
+        assert!(encrypt_digests(&public, &["00".to_string(), "0000".to_string()], &mut OsRng).is_err());
+
+        let encrypted = encrypt_digests(&public, &[sha256_hex("a"), sha256_hex("b")], &mut OsRng).unwrap();
+        let query = encrypted.zero_test_query(&mut OsRng).unwrap();
+        assert_eq!(query.differences.len(), 1);
+        assert!(query.verdict(&ZeroTestAnswer { equal: vec![] }).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Deoxys FHE authenticated ciphertexts
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Encrypt-then-MAC: HMAC-SHA256 over `Ciphertext::to_bytes` under a key
+//! derived from the secret key with HKDF. LWE ciphertexts are malleable,
+//! so without a tag a modified ciphertext silently decrypts to another
+//! plaintext; with one, decryption fails with `TamperDetected`.
+//!
+//! Only a secret key holder can seal. Homomorphic results computed by
+//! anyone else carry no valid tag and have to be re-sealed by the key
+//! holder before they can be decrypted this way. The tags are only as
+//! secret as the key: anyone can seal under `DeoxysFHE::new(None)`,
+//! whose frozen seed is in the source, so real keys come from
+//! `DeoxysFHE::from_mnemonic`.
+
+use crate::fhe_core::{Ciphertext, DecryptError, DeoxysFHE};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A ciphertext and its hex HMAC-SHA256 tag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthenticatedCiphertext {
+    pub ciphertext: Ciphertext,
+    pub tag: String,
+}
+
+impl DeoxysFHE {
+    /// Tag an existing ciphertext under this instance's MAC key
+    pub fn seal(&self, ciphertext: Ciphertext) -> AuthenticatedCiphertext {
+        let tag = self.mac(&ciphertext).finalize().into_bytes();
+        AuthenticatedCiphertext {
+            ciphertext,
+            tag: tag.iter().map(|b| format!("{:02x}", b)).collect(),
+        }
+    }
+
+    /// Encrypt and seal in one step
+    pub fn encrypt_authenticated<R: RngCore + CryptoRng>(
+        &self,
+        message: i32,
+        rng: &mut R,
+    ) -> Result<AuthenticatedCiphertext, String> {
+        Ok(self.seal(self.encrypt_with_rng(message, rng)?))
+    }
+
+    /// Verify the tag in constant time, then decrypt as `decrypt_checked`.
+    /// A modified ciphertext or tag, or one sealed under another key, is
+    /// `TamperDetected`.
+    pub fn decrypt_authenticated(&self, sealed: &AuthenticatedCiphertext) -> Result<i32, DecryptError> {
+        let tag = decode_tag(&sealed.tag).ok_or(DecryptError::TamperDetected)?;
+        self.mac(&sealed.ciphertext)
+            .verify_slice(&tag)
+            .map_err(|_| DecryptError::TamperDetected)?;
+        self.decrypt_checked(sealed.ciphertext.clone())
+    }
+
+    /// HMAC over the ciphertext's bytes, keyed from the secret key so that
+    /// an instance built `from_keys` seals the same way
+    fn mac(&self, ciphertext: &Ciphertext) -> HmacSha256 {
+        let secret = Zeroizing::new(self.secret_key().to_bytes());
+        let mut key = Zeroizing::new([0u8; 32]);
+        Hkdf::<Sha256>::new(None, &secret)
+            .expand(b"AxiomHive Deoxys FHE MAC v1", &mut key[..])
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        let mut mac = HmacSha256::new_from_slice(&key[..]).expect("HMAC takes keys of any length");
+        mac.update(&ciphertext.to_bytes());
+        mac
+    }
+}
+
+fn decode_tag(tag: &str) -> Option<Vec<u8>> {
+    if tag.len() != 64 || !tag.is_ascii() {
+        return None;
+    }
+    (0..tag.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&tag[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fhe_core::ParamPreset;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_sealed_ciphertexts_round_trip() {
+        let fhe = DeoxysFHE::new(None);
+        let sealed = fhe.encrypt_authenticated(1234, &mut OsRng).unwrap();
+        assert_eq!(fhe.decrypt_authenticated(&sealed).unwrap(), 1234);
+
+        let json = serde_json::to_string(&sealed).unwrap();
+        let restored: AuthenticatedCiphertext = serde_json::from_str(&json).unwrap();
+        assert_eq!(fhe.decrypt_authenticated(&restored).unwrap(), 1234);
+
+        // The same key pair seals identically without the seed
+        let imported = DeoxysFHE::from_keys(fhe.public_key().clone(), fhe.secret_key().clone()).unwrap();
+        assert_eq!(imported.seal(sealed.ciphertext.clone()), sealed);
+    }
+
+    #[test]
+    fn test_modifications_are_detected() {
+        let fhe = DeoxysFHE::with_params(None, ParamPreset::Small.params());
+        let sealed = fhe.encrypt_authenticated(42, &mut OsRng).unwrap();
+        let tampered = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut json = serde_json::to_value(&sealed).unwrap();
+            edit(&mut json);
+            fhe.decrypt_authenticated(&serde_json::from_value(json).unwrap())
+        };
+
+        // Adding delta to v would otherwise decrypt to 43
+        let delta = fhe.params().q() / fhe.params().t() as i64;
+        let shifted = (sealed.ciphertext.v() + delta) % fhe.params().q();
+        assert_eq!(tampered(&|json| json["ciphertext"]["v"] = shifted.into()), Err(DecryptError::TamperDetected));
+        assert_eq!(tampered(&|json| json["ciphertext"]["noise"] = 0.into()), Err(DecryptError::TamperDetected));
+        assert_eq!(tampered(&|json| json["tag"] = "00".repeat(32).into()), Err(DecryptError::TamperDetected));
+        assert_eq!(tampered(&|json| json["tag"] = "xyz".into()), Err(DecryptError::TamperDetected));
+
+        let other = DeoxysFHE::with_params(Some(b"another seed"), ParamPreset::Small.params());
+        assert_eq!(other.decrypt_authenticated(&sealed), Err(DecryptError::TamperDetected));
+    }
+}
@@ -0,0 +1,353 @@
+//! Deoxys FHE slot packing
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Ring-LWE coefficient packing over Z_Q[x]/(x^N + 1): the public key is
+//! `(a, b = -a*s + e)` and slot j is coefficient j. A ciphertext is the
+//! polynomial u = a*r + e1 and the first k coefficients of
+//! v = b*r + e2 + delta*m, with a fresh ternary mask r per encryption, so
+//! k values cost N + k words instead of k * (N + 1). Addition and
+//! subtraction act slot by slot; plaintext multiplication scales every
+//! slot by the same scalar, since all slots share u.
+
+use crate::fhe_core::{sample_centered, DecryptError, DeoxysFHE, FheParams};
+use crate::fhe_rlwe::HashStream;
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Encrypts up to `slots` values per ciphertext
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchPublicKey {
+    params: FheParams,
+    slots: usize,
+    a: Vec<i64>,
+    b: Vec<i64>,
+}
+
+/// The binary secret polynomial, handled like `SecretKey`: redacted
+/// `Debug`, constant-time equality, zeroized on drop
+#[derive(Clone)]
+pub struct BatchSecretKey {
+    params: FheParams,
+    slots: usize,
+    s: Vec<i32>,
+}
+
+impl std::fmt::Debug for BatchSecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BatchSecretKey({} slots)", self.slots)
+    }
+}
+
+impl PartialEq for BatchSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.params == other.params && self.slots == other.slots && bool::from(self.s.ct_eq(&other.s))
+    }
+}
+
+impl Eq for BatchSecretKey {}
+
+impl Zeroize for BatchSecretKey {
+    fn zeroize(&mut self) {
+        self.s.zeroize();
+    }
+}
+
+impl Drop for BatchSecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for BatchSecretKey {}
+
+/// Packed ciphertext with a worst-case noise bound shared by all slots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawPackedCiphertext")]
+pub struct PackedCiphertext {
+    params: FheParams,
+    u: Vec<i64>,
+    v: Vec<i64>,
+    noise: i64,
+}
+
+#[derive(Deserialize)]
+struct RawPackedCiphertext {
+    params: FheParams,
+    u: Vec<i64>,
+    v: Vec<i64>,
+    noise: i64,
+}
+
+impl TryFrom<RawPackedCiphertext> for PackedCiphertext {
+    type Error = String;
+
+    fn try_from(raw: RawPackedCiphertext) -> Result<Self, String> {
+        let params = raw.params;
+        if raw.u.len() != params.n() || raw.v.is_empty() {
+            return Err("Invalid packed ciphertext shape".to_string());
+        }
+        if raw.u.iter().chain(&raw.v).any(|x| !(0..params.q()).contains(x)) || raw.noise < 0 {
+            return Err("Packed ciphertext value out of range".to_string());
+        }
+        Ok(Self {
+            params,
+            u: raw.u,
+            v: raw.v,
+            noise: raw.noise,
+        })
+    }
+}
+
+impl DeoxysFHE {
+    /// Derive packed keys with `slots` slots, at most N, from this
+    /// instance's seed. The same seed and slot count always give the same
+    /// keys.
+    pub fn batch_keys(&self, slots: usize) -> Result<(BatchPublicKey, BatchSecretKey), String> {
+        let params = *self.params();
+        let n = params.n();
+        if slots == 0 || slots > n {
+            return Err(format!("A batch key has between 1 and {} slots, not {}", n, slots));
+        }
+        let a = self.public_key().a().to_vec();
+
+        // 256 secret bits per hash block
+        let s: Vec<i32> = (0..n.div_ceil(256))
+            .flat_map(|block| {
+                let mut hasher = Sha256::new();
+                hasher.update(self.seed());
+                hasher.update(b"batch_sk");
+                hasher.update((block as u32).to_be_bytes());
+                let hash = hasher.finalize();
+                (0..256).map(move |bit| ((hash[bit / 8] >> (bit % 8)) & 1) as i32)
+            })
+            .take(n)
+            .collect();
+
+        let mut stream = HashStream::new(&[self.seed(), b"batch_error"]);
+        let e = (0..n).map(|_| sample_centered(&mut stream, 10));
+        let b = params.ring_mul(&a, &s, n).into_iter().zip(e).map(|(a_s, e)| params.reduce(e.wrapping_sub(a_s))).collect();
+        Ok((BatchPublicKey { params, slots, a, b }, BatchSecretKey { params, slots, s }))
+    }
+}
+
+impl BatchPublicKey {
+    pub fn params(&self) -> &FheParams {
+        &self.params
+    }
+
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Encrypt up to `slots()` values, each below T, into one ciphertext;
+    /// unused slots hold 0
+    pub fn encrypt_batch<R: RngCore + CryptoRng>(&self, values: &[i32], rng: &mut R) -> Result<PackedCiphertext, String> {
+        let params = &self.params;
+        if values.len() > self.slots() {
+            return Err(format!("{} values exceed the {} slots", values.len(), self.slots()));
+        }
+        if let Some(&m) = values.iter().find(|&&m| !(0..params.t()).contains(&m)) {
+            return Err(format!("Message {} is outside the plaintext modulus {}", m, params.t()));
+        }
+
+        // A fresh ternary mask; zeroized, since it decrypts like the key
+        let n = params.n();
+        let mut r: Vec<i64> = (0..n).map(|_| sample_centered(rng, 1)).collect();
+        let u = params.ring_mul(&self.a, &r, n)
+            .into_iter()
+            .map(|c| params.reduce(c.wrapping_add(sample_centered(rng, 10))))
+            .collect();
+        let delta = params.delta();
+        let v = params.ring_mul(&self.b, &r, self.slots)
+            .into_iter()
+            .enumerate()
+            .map(|(slot, c)| {
+                let m = values.get(slot).copied().unwrap_or(0) as i64;
+                params.reduce(c.wrapping_add(sample_centered(rng, 10)).wrapping_add(m.wrapping_mul(delta)))
+            })
+            .collect();
+        r.zeroize();
+
+        Ok(PackedCiphertext {
+            params: *params,
+            u,
+            v,
+            noise: params.fresh_noise(),
+        })
+    }
+}
+
+impl BatchSecretKey {
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Every slot's value, refusing once the noise bound exceeds Q / 2T
+    pub fn decrypt_batch(&self, ct: &PackedCiphertext) -> Result<Vec<i32>, DecryptError> {
+        let params = &self.params;
+        if ct.params != *params || ct.v.len() != self.slots() {
+            return Err(DecryptError::Invalid("Packed ciphertext does not match this key".to_string()));
+        }
+        if ct.is_exhausted() {
+            return Err(DecryptError::NoiseExhausted {
+                noise: ct.noise,
+                limit: params.max_noise(),
+            });
+        }
+
+        // Slot j is coefficient j of v + u*s
+        Ok(params.ring_mul(&ct.u, &self.s, self.slots)
+            .into_iter()
+            .zip(&ct.v)
+            .map(|(u_s, &v)| params.decode(params.reduce(v.wrapping_add(u_s))))
+            .collect())
+    }
+}
+
+impl PackedCiphertext {
+    pub fn params(&self) -> &FheParams {
+        &self.params
+    }
+
+    pub fn slots(&self) -> usize {
+        self.v.len()
+    }
+
+    pub fn noise_bound(&self) -> i64 {
+        self.noise
+    }
+
+    /// Bits of noise budget left; see `Ciphertext::remaining_budget`
+    pub fn remaining_budget(&self) -> f64 {
+        (self.params.max_noise() as f64 / self.noise.max(1) as f64).log2().max(0.0)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.noise > self.params.max_noise()
+    }
+
+    /// Slot-wise `(a + b) mod T`
+    pub fn add(&self, other: &PackedCiphertext) -> Result<PackedCiphertext, String> {
+        self.combine(other, 1)
+    }
+
+    /// Slot-wise `(a - b) mod T`
+    pub fn sub(&self, other: &PackedCiphertext) -> Result<PackedCiphertext, String> {
+        self.combine(other, -1)
+    }
+
+    /// Every slot times `k`, mod T; see `DeoxysFHE::mul_plain`
+    pub fn mul_plain(&self, k: i32) -> PackedCiphertext {
+        let params = &self.params;
+        let t = params.t() as i64;
+        let k = (k as i64).rem_euclid(t);
+        let k = if k >= t / 2 { k - t } else { k };
+        PackedCiphertext {
+            params: *params,
+            u: params.scale_add(&self.u, k, 0),
+            v: params.scale_add(&self.v, k, 0),
+            noise: self.noise.saturating_mul(k.abs()),
+        }
+    }
+
+    fn combine(&self, other: &PackedCiphertext, sign: i64) -> Result<PackedCiphertext, String> {
+        if self.params != other.params || self.v.len() != other.v.len() {
+            return Err("Packed ciphertexts use different parameters or slot counts".to_string());
+        }
+        let params = &self.params;
+        let add = |(&a, &b): (&i64, &i64)| params.reduce(a.wrapping_add(sign.wrapping_mul(b)));
+        Ok(PackedCiphertext {
+            params: *params,
+            u: self.u.iter().zip(&other.u).map(add).collect(),
+            v: self.v.iter().zip(&other.v).map(add).collect(),
+            noise: self.noise.saturating_add(other.noise),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fhe_core::ParamPreset;
+
+    #[test]
+    fn test_slots_operate_element_wise() {
+        let fhe = DeoxysFHE::new(None);
+        let t = fhe.params().t();
+        let (public, secret) = fhe.batch_keys(64).unwrap();
+        let mut rng = rand_core::OsRng;
+
+        let xs: Vec<i32> = (0..64).map(|i| i * 1000).collect();
+        let ys: Vec<i32> = (0..64).map(|i| t - 1 - i).collect();
+        let (ct_x, ct_y) = (public.encrypt_batch(&xs, &mut rng).unwrap(), public.encrypt_batch(&ys, &mut rng).unwrap());
+        assert_eq!(secret.decrypt_batch(&ct_x).unwrap(), xs);
+
+        let sum = secret.decrypt_batch(&ct_x.add(&ct_y).unwrap()).unwrap();
+        let difference = secret.decrypt_batch(&ct_x.sub(&ct_y).unwrap()).unwrap();
+        let tripled = secret.decrypt_batch(&ct_x.mul_plain(3)).unwrap();
+        for i in 0..64 {
+            assert_eq!(sum[i], (xs[i] + ys[i]) % t);
+            assert_eq!(difference[i], (xs[i] - ys[i]).rem_euclid(t));
+            assert_eq!(tripled[i], xs[i] * 3 % t);
+        }
+
+        // N + slots words, against 64 * (N + 1) unpacked
+        assert_eq!(ct_x.u.len() + ct_x.v.len(), 1024 + 64);
+
+        // Short batches leave trailing slots at zero
+        let ct = public.encrypt_batch(&[7, 8], &mut rng).unwrap();
+        assert_eq!(secret.decrypt_batch(&ct).unwrap()[..3], [7, 8, 0]);
+    }
+
+    #[test]
+    fn test_batch_keys_are_deterministic_and_checked() {
+        let fhe = DeoxysFHE::with_params(None, ParamPreset::Small.params());
+        let (public, secret) = fhe.batch_keys(4).unwrap();
+        assert_eq!(fhe.batch_keys(4).unwrap().0, public);
+        assert!(fhe.batch_keys(0).is_err());
+        assert!(fhe.batch_keys(257).is_err());
+
+        let mut rng = rand_core::OsRng;
+        assert!(public.encrypt_batch(&[1; 5], &mut rng).is_err());
+        assert!(public.encrypt_batch(&[256], &mut rng).is_err());
+
+        let ct = public.encrypt_batch(&[1, 2, 3, 4], &mut rng).unwrap();
+        let json = serde_json::to_string(&ct).unwrap();
+        assert_eq!(serde_json::from_str::<PackedCiphertext>(&json).unwrap(), ct);
+
+        let (_, other) = fhe.batch_keys(2).unwrap();
+        assert!(matches!(other.decrypt_batch(&ct), Err(DecryptError::Invalid(_))));
+        let mut exhausted = ct;
+        while !exhausted.is_exhausted() {
+            exhausted = exhausted.add(&exhausted).unwrap();
+        }
+        assert!(matches!(secret.decrypt_batch(&exhausted), Err(DecryptError::NoiseExhausted { .. })));
+
+        let mut secret = secret;
+        secret.zeroize();
+        assert!(secret.s.iter().all(|&bit| bit == 0));
+    }
+
+    #[test]
+    fn test_ciphertexts_do_not_reveal_their_masks() {
+        let fhe = DeoxysFHE::with_params(Some(b"mask guessing"), ParamPreset::Small.params());
+        let params = *fhe.params();
+        let (public, secret) = fhe.batch_keys(16).unwrap();
+        let first = public.encrypt_batch(&[42; 16], &mut rand_core::OsRng).unwrap();
+        let second = public.encrypt_batch(&[7; 16], &mut rand_core::OsRng).unwrap();
+        let within = |x: i64, bound: i64| params.reduce(x.wrapping_add(bound)) <= 2 * bound;
+
+        // Under scalar masks r and r', r'*u - r*u' = r'*e1 - r*e1' would be
+        // small in every coordinate, with no key needed to find them
+        let masks = || (-99..=99i64).flat_map(|r| (-99..=99i64).map(move |r2| (r, r2)));
+        let related = masks().filter(|&pair| pair != (0, 0)).find(|&(r, r2)| {
+            first.u.iter().zip(&second.u).take(8).all(|(&u, &u2)| {
+                within(u.wrapping_mul(r2).wrapping_sub(u2.wrapping_mul(r)), 2 * 99 * 10)
+            })
+        });
+        assert_eq!(related, None);
+        assert_eq!(secret.decrypt_batch(&first).unwrap(), [42; 16]);
+    }
+}
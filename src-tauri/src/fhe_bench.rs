@@ -0,0 +1,107 @@
+//! Deoxys FHE self-benchmark
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Times the core operations for one instance's parameters on the machine
+//! it runs on, so the UI can show real rather than nominal performance.
+//! For careful comparisons use the criterion benches instead.
+
+use crate::fhe_core::{DeoxysFHE, FheParams};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Iterations per operation for `DeoxysFHE::benchmark`
+pub const DEFAULT_BENCH_ITERATIONS: u32 = 32;
+
+/// Latency of one operation over a benchmark run, in microseconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OpTiming {
+    pub mean_us: f64,
+    pub min_us: f64,
+    pub max_us: f64,
+}
+
+impl OpTiming {
+    /// Run `op` `iterations` times, timing each run
+    fn measure<T>(iterations: u32, mut op: impl FnMut() -> T) -> Self {
+        let samples: Vec<Duration> = (0..iterations.max(1))
+            .map(|_| {
+                let start = Instant::now();
+                std::hint::black_box(op());
+                start.elapsed()
+            })
+            .collect();
+        let micros = |d: &Duration| d.as_secs_f64() * 1e6;
+        Self {
+            mean_us: samples.iter().map(micros).sum::<f64>() / samples.len() as f64,
+            min_us: samples.iter().map(micros).fold(f64::INFINITY, f64::min),
+            max_us: samples.iter().map(micros).fold(0.0, f64::max),
+        }
+    }
+}
+
+/// Measured latencies for one parameter set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FheBenchReport {
+    pub params: FheParams,
+    /// Hardness of the lattice problem alone; see
+    /// `FheParams::estimated_lattice_security_bits`
+    pub estimated_lattice_security_bits: u32,
+    pub noise_budget_bits: f64,
+    /// Size of one ciphertext's N + 1 coefficients in bytes
+    pub ciphertext_bytes: usize,
+    pub iterations: u32,
+    pub keygen: OpTiming,
+    pub encrypt: OpTiming,
+    pub decrypt: OpTiming,
+    pub add: OpTiming,
+    pub sub: OpTiming,
+    pub mul_plain: OpTiming,
+}
+
+impl DeoxysFHE {
+    /// Benchmark this instance's parameters with `DEFAULT_BENCH_ITERATIONS`
+    pub fn benchmark(&self) -> FheBenchReport {
+        self.benchmark_with(DEFAULT_BENCH_ITERATIONS)
+    }
+
+    /// Benchmark with `iterations` runs per operation. Keygen is timed as
+    /// a fresh instance from this one's seed; encryption draws from `OsRng`.
+    pub fn benchmark_with(&self, iterations: u32) -> FheBenchReport {
+        let params = *self.params();
+        let ct_a = self.encrypt_with_rng(params.t() - 1, &mut OsRng).expect("T - 1 encrypts");
+        let ct_b = self.encrypt_with_rng(1, &mut OsRng).expect("1 encrypts");
+
+        FheBenchReport {
+            params,
+            estimated_lattice_security_bits: params.estimated_lattice_security_bits(),
+            noise_budget_bits: params.noise_budget_bits(),
+            ciphertext_bytes: (params.n() + 1) * 8,
+            iterations: iterations.max(1),
+            keygen: OpTiming::measure(iterations, || DeoxysFHE::with_params(Some(self.seed()), params)),
+            encrypt: OpTiming::measure(iterations, || self.encrypt_with_rng(42, &mut OsRng)),
+            decrypt: OpTiming::measure(iterations, || self.decrypt(ct_a.clone())),
+            add: OpTiming::measure(iterations, || self.add(&ct_a, &ct_b)),
+            sub: OpTiming::measure(iterations, || self.sub(&ct_a, &ct_b)),
+            mul_plain: OpTiming::measure(iterations, || self.mul_plain(&ct_a, 3)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fhe_core::ParamPreset;
+
+    #[test]
+    fn test_benchmark_reports_every_operation() {
+        let fhe = DeoxysFHE::with_params(None, ParamPreset::Small.params());
+        let report = fhe.benchmark_with(3);
+        assert_eq!(report.params, *fhe.params());
+        assert_eq!(report.iterations, 3);
+        assert_eq!(report.ciphertext_bytes, 257 * 8);
+        for timing in [report.keygen, report.encrypt, report.decrypt, report.add, report.sub, report.mul_plain] {
+            assert!(timing.min_us <= timing.max_us && timing.mean_us.is_finite());
+        }
+        assert!(serde_json::to_string(&report).unwrap().contains("\"mul_plain\""));
+    }
+}
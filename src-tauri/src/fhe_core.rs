@@ -3,44 +3,799 @@
 //! Zero Entropy Law (C=0) - Deterministic encryption with LWE lattice parameters
 //! Implements LWE Lattice parameters for Sovereign Privacy
 
+use crate::fhe_rlwe::HashStream;
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
+use subtle::{Choice, ConstantTimeEq, ConstantTimeGreater};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-const Q: i64 = 1i64 << 60; // Ciphertext Modulus
-const T: i32 = 1i32 << 16;  // Plaintext Modulus
-const N: usize = 1024;      // Lattice Dimension
+/// LWE ciphertext `(u, v)` with a worst-case bound on its noise. The
+/// bound starts at `FheParams::fresh_noise` and grows with every
+/// homomorphic operation; decryption is only guaranteed while it stays at
+/// or below `FheParams::max_noise`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawCiphertext")]
+pub struct Ciphertext {
+    params: FheParams,
+    u: Vec<i64>,
+    v: i64,
+    noise: i64,
+}
+
+#[derive(Deserialize)]
+struct RawCiphertext {
+    params: FheParams,
+    u: Vec<i64>,
+    v: i64,
+    noise: i64,
+}
+
+impl TryFrom<RawCiphertext> for Ciphertext {
+    type Error = String;
+
+    fn try_from(raw: RawCiphertext) -> Result<Self, String> {
+        let params = raw.params;
+        if raw.u.len() != params.n {
+            return Err(format!("Invalid ciphertext length: expected {}, got {}", params.n, raw.u.len()));
+        }
+        if raw.u.iter().chain([&raw.v]).any(|x| !(0..params.q()).contains(x)) {
+            return Err("Ciphertext coefficient out of range".to_string());
+        }
+        if raw.noise < 0 {
+            return Err("Negative noise bound".to_string());
+        }
+        Ok(Self {
+            params,
+            u: raw.u,
+            v: raw.v,
+            noise: raw.noise,
+        })
+    }
+}
+
+impl Ciphertext {
+    pub fn params(&self) -> &FheParams {
+        &self.params
+    }
+
+    pub fn u(&self) -> &[i64] {
+        &self.u
+    }
+
+    pub fn v(&self) -> i64 {
+        self.v
+    }
+
+    /// Worst-case |noise| after the operations this ciphertext went through
+    pub fn noise_bound(&self) -> i64 {
+        self.noise
+    }
+
+    /// Bits of noise budget left, log2(max_noise / noise_bound); 0 once
+    /// the ciphertext may no longer decrypt correctly
+    pub fn remaining_budget(&self) -> f64 {
+        (self.params.max_noise() as f64 / self.noise.max(1) as f64).log2().max(0.0)
+    }
+
+    /// True if decryption may return a wrong plaintext
+    pub fn is_exhausted(&self) -> bool {
+        self.noise > self.params.max_noise()
+    }
+
+    /// Parameter header (see `FheParams`), then u, v and the noise bound
+    /// as big-endian i64
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + (self.params.n + 2) * 8);
+        self.params.write_header(CIPHERTEXT_MAGIC, &mut bytes);
+        for x in self.u.iter().chain([&self.v, &self.noise]) {
+            bytes.extend_from_slice(&x.to_be_bytes());
+        }
+        bytes
+    }
+}
+
+/// Accumulator width of `FheParams::dot`
+const LANES: usize = 8;
+
+/// Largest |e| of any error coefficient keygen or encryption uses
+const MAX_E: i64 = 10;
+
+/// Lattice dimension N, ciphertext modulus Q = 2^log_q and plaintext
+/// modulus T = 2^log_t. Both moduli are powers of two, so delta = Q / T is
+/// exact and plaintext arithmetic wraps cleanly modulo T.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawFheParams")]
+pub struct FheParams {
+    n: usize,
+    log_q: u32,
+    log_t: u32,
+}
+
+#[derive(Deserialize)]
+struct RawFheParams {
+    n: usize,
+    log_q: u32,
+    log_t: u32,
+}
+
+impl TryFrom<RawFheParams> for FheParams {
+    type Error = String;
+
+    fn try_from(raw: RawFheParams) -> Result<Self, String> {
+        FheParams::new(raw.n, raw.log_q, raw.log_t)
+    }
+}
+
+/// Named parameter sets, from fastest to most secure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParamPreset {
+    /// N = 256, Q = 2^32, T = 2^8: fast, for tests and demos only
+    Small,
+    /// N = 1024, Q = 2^60, T = 2^16: the parameters keys have always used
+    Default,
+    /// N = 4096, Q = 2^60, T = 2^16: four times the keys and work of Default
+    HighSecurity,
+}
+
+impl ParamPreset {
+    pub const ALL: [ParamPreset; 3] = [ParamPreset::Small, ParamPreset::Default, ParamPreset::HighSecurity];
+
+    pub fn params(self) -> FheParams {
+        let (n, log_q, log_t) = match self {
+            ParamPreset::Small => (256, 32, 8),
+            ParamPreset::Default => (1024, 60, 16),
+            ParamPreset::HighSecurity => (4096, 60, 16),
+        };
+        FheParams::new(n, log_q, log_t).expect("presets are valid")
+    }
+}
+
+impl FheParams {
+    /// Fails unless Q fits the i64 arithmetic (log_q <= 62), T < Q, and a
+    /// fresh ciphertext decrypts correctly
+    pub fn new(n: usize, log_q: u32, log_t: u32) -> Result<Self, String> {
+        if n == 0 || n > u32::MAX as usize {
+            return Err(format!("Lattice dimension {} is out of range", n));
+        }
+        if !(2..=62).contains(&log_q) || log_t == 0 || log_t >= log_q || log_t > 30 {
+            return Err(format!("Unsupported moduli Q = 2^{}, T = 2^{}", log_q, log_t));
+        }
+        let params = Self { n, log_q, log_t };
+        if params.fresh_noise() > params.max_noise() {
+            return Err(format!(
+                "Fresh noise {} exceeds the decryption limit {}",
+                params.fresh_noise(),
+                params.max_noise()
+            ));
+        }
+        Ok(params)
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn q(&self) -> i64 {
+        1 << self.log_q
+    }
+
+    pub fn t(&self) -> i32 {
+        1 << self.log_t
+    }
+
+    /// Q / T, the scale plaintexts are encoded at
+    pub fn delta(&self) -> i64 {
+        1 << (self.log_q - self.log_t)
+    }
+
+    /// Largest noise that still decrypts correctly: Q / 2T - 1
+    pub fn max_noise(&self) -> i64 {
+        self.delta() / 2 - 1
+    }
+
+    /// Bound on a fresh ciphertext's noise, the constant coefficient of
+    /// `e*r + e1*s + e2`: N small products from each of the first two terms
+    pub fn fresh_noise(&self) -> i64 {
+        MAX_E * (2 * self.n as i64 + 1)
+    }
+
+    /// Bits of headroom a fresh ciphertext has, log2(max_noise / fresh_noise):
+    /// each bit allows roughly one more doubling of the noise
+    pub fn noise_budget_bits(&self) -> f64 {
+        (self.max_noise() as f64 / self.fresh_noise() as f64).log2()
+    }
+
+    /// Rough classical hardness in bits of the ring-LWE problem under the
+    /// keys: recovering the uniform binary secret from `(a, b)` or a
+    /// ciphertext. From the rule of thumb 3.4 * N / log2(Q) fitted to the
+    /// HomomorphicEncryption.org standard's tables for small secrets and
+    /// errors. It says nothing about how this code uses the problem; a
+    /// sizing aid, not a substitute for running the lattice estimator.
+    pub fn estimated_lattice_security_bits(&self) -> u32 {
+        (3.4 * self.n as f64 / self.log_q as f64) as u32
+    }
+
+    /// Bytes per plaintext limb when encrypting byte strings: the whole
+    /// bytes that fit below T
+    pub fn limb_bytes(&self) -> usize {
+        (self.log_t / 8) as usize
+    }
+
+    /// `bytes` padded with 0x80 and zeros, as big-endian limbs
+    pub(crate) fn limbs(&self, bytes: &[u8]) -> Result<Vec<i32>, String> {
+        let width = self.limb_bytes();
+        if width == 0 {
+            return Err(format!("Plaintext modulus 2^{} cannot hold a byte", self.log_t));
+        }
+        let mut padded = bytes.to_vec();
+        padded.push(0x80);
+        padded.resize(padded.len().div_ceil(width) * width, 0);
+        Ok(padded
+            .chunks(width)
+            .map(|limb| limb.iter().fold(0i32, |acc, &b| (acc << 8) | b as i32))
+            .collect())
+    }
+
+    /// The bytes `limbs` padded; trailing zero limbs are ignored
+    pub(crate) fn unpad(&self, limbs: impl IntoIterator<Item = i32>) -> Result<Vec<u8>, DecryptError> {
+        let width = self.limb_bytes();
+        let invalid = || DecryptError::Invalid("Ciphertexts do not hold padded bytes".to_string());
+        let mut bytes = Vec::new();
+        for limb in limbs {
+            if width == 0 || (limb as u64) >> (8 * width) != 0 {
+                return Err(invalid());
+            }
+            bytes.extend_from_slice(&limb.to_be_bytes()[4 - width..]);
+        }
+        let end = bytes.iter().rposition(|&b| b != 0).ok_or_else(invalid)?;
+        if bytes[end] != 0x80 {
+            return Err(invalid());
+        }
+        bytes.truncate(end);
+        Ok(bytes)
+    }
+
+    /// Reduce into [0, Q). Q is a power of two dividing 2^64, so wrapping
+    /// i64 arithmetic keeps every residue mod Q and reduction is a mask:
+    /// no division, Barrett or Montgomery step on the hot paths.
+    pub(crate) fn reduce(&self, x: i64) -> i64 {
+        x & (self.q() - 1)
+    }
+
+    /// `<xs, s>` mod Q over LANES independent accumulators, a shape the
+    /// compiler vectorizes
+    pub(crate) fn dot<S: Copy + Into<i64>>(&self, xs: &[i64], s: &[S]) -> i64 {
+        let mut acc = [0i64; LANES];
+        let (xs_chunks, s_chunks) = (xs.chunks_exact(LANES), s.chunks_exact(LANES));
+        let tail = xs_chunks.remainder().iter().zip(s_chunks.remainder());
+        for (x, s) in xs_chunks.zip(s_chunks) {
+            for lane in 0..LANES {
+                acc[lane] = acc[lane].wrapping_add(x[lane].wrapping_mul(s[lane].into()));
+            }
+        }
+        let sum = tail.fold(acc.iter().fold(0i64, |a, &b| a.wrapping_add(b)), |a, (&x, &s)| {
+            a.wrapping_add(x.wrapping_mul(s.into()))
+        });
+        self.reduce(sum)
+    }
+
+    /// Round a noisy `delta * m` to m. Shifts and masks only, so the
+    /// timing does not depend on the (secret-derived) input.
+    pub(crate) fn decode(&self, m_noisy: i64) -> i32 {
+        let shift = self.log_q - self.log_t;
+        (((m_noisy + (1 << (shift - 1))) >> shift) & (self.t() as i64 - 1)) as i32
+    }
+
+    /// The first `len` coefficients of `a * small` in Z_Q[x]/(x^N + 1),
+    /// for a `small` with tiny coefficients: the binary secret or a ternary
+    /// mask. Multiplies rather than branching on `small`, which is secret.
+    pub(crate) fn ring_mul<S: Copy + Into<i64>>(&self, a: &[i64], small: &[S], len: usize) -> Vec<i64> {
+        let n = a.len();
+        let mut acc = vec![0i64; len];
+        for (j, &x) in small.iter().enumerate() {
+            let x: i64 = x.into();
+            // x^j * a: coefficients pushed past x^(N-1) wrap around negated
+            let wrapped = j.min(len);
+            for (c, &y) in acc[..wrapped].iter_mut().zip(&a[n - j..]) {
+                *c = c.wrapping_sub(y.wrapping_mul(x));
+            }
+            for (c, &y) in acc[wrapped..].iter_mut().zip(a) {
+                *c = c.wrapping_add(y.wrapping_mul(x));
+            }
+        }
+        acc.into_iter().map(|c| self.reduce(c)).collect()
+    }
+
+    /// `xs * k + c` mod Q, componentwise
+    pub(crate) fn scale_add(&self, xs: &[i64], k: i64, c: i64) -> Vec<i64> {
+        xs.iter().map(|&x| self.reduce(x.wrapping_mul(k).wrapping_add(c))).collect()
+    }
+
+    fn check(&self, ct: &Ciphertext) -> Result<(), String> {
+        if ct.params != *self {
+            return Err(format!("Ciphertext parameters {:?} do not match {:?}", ct.params, self));
+        }
+        Ok(())
+    }
+
+    /// Magic, N as u32, then log_q and log_t as one byte each
+    fn write_header(&self, magic: &[u8; 4], bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(magic);
+        bytes.extend_from_slice(&(self.n as u32).to_be_bytes());
+        bytes.push(self.log_q as u8);
+        bytes.push(self.log_t as u8);
+    }
+}
+
+impl Default for FheParams {
+    fn default() -> Self {
+        ParamPreset::Default.params()
+    }
+}
+
+/// Ring-LWE public key `(a, b = -a*s + e)` over Z_Q[x]/(x^N + 1), with
+/// `s` the binary secret read as a polynomial. Every encryption masks it
+/// with a fresh ternary polynomial `r`, one of 3^N, and keeps `a*r + e1`
+/// and the constant coefficient of `b*r + e2 + delta*m`: an LWE
+/// ciphertext under `s`. Anyone holding the key can encrypt; only the
+/// matching `SecretKey` decrypts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawPublicKey")]
+pub struct PublicKey {
+    params: FheParams,
+    a: Vec<i64>,
+    b: Vec<i64>,
+}
+
+#[derive(Deserialize)]
+struct RawPublicKey {
+    params: FheParams,
+    a: Vec<i64>,
+    b: Vec<i64>,
+}
+
+impl TryFrom<RawPublicKey> for PublicKey {
+    type Error = String;
+
+    fn try_from(raw: RawPublicKey) -> Result<Self, String> {
+        let n = raw.params.n;
+        if raw.a.len() != n || raw.b.len() != n {
+            return Err(format!("Invalid public key length: expected {}, got {} and {}", n, raw.a.len(), raw.b.len()));
+        }
+        if raw.a.iter().chain(&raw.b).any(|x| !(0..raw.params.q()).contains(x)) {
+            return Err("Public key coefficient out of range".to_string());
+        }
+        Ok(Self {
+            params: raw.params,
+            a: raw.a,
+            b: raw.b,
+        })
+    }
+}
+
+impl PublicKey {
+    pub fn params(&self) -> &FheParams {
+        &self.params
+    }
+
+    pub(crate) fn a(&self) -> &[i64] {
+        &self.a
+    }
+
+    /// Parameter header (see `FheParams`), then a and b as big-endian i64
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.params.n * 16);
+        self.params.write_header(PUBLIC_KEY_MAGIC, &mut bytes);
+        for x in self.a.iter().chain(&self.b) {
+            bytes.extend_from_slice(&x.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Keys exported before encryption used ring masks are refused: what
+    /// was encrypted under them is readable by anyone holding them
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.starts_with(SCALAR_PUBLIC_KEY_MAGIC) {
+            return Err("Public key uses the retired scalar-mask format; export a new one".to_string());
+        }
+        let (params, body) = key_body(bytes, PUBLIC_KEY_MAGIC, |n| n * 16)?;
+        let mut a: Vec<i64> = body
+            .chunks_exact(8)
+            .map(|c| i64::from_be_bytes(c.try_into().expect("8-byte chunk")))
+            .collect();
+        let b = a.split_off(params.n);
+        Self::try_from(RawPublicKey { params, a, b })
+    }
+
+    /// Encrypt message with a fresh mask from `rng`. Encrypting the same
+    /// message twice yields unrelated ciphertexts.
+    pub fn encrypt_with_rng<R: RngCore + CryptoRng>(&self, message: i32, rng: &mut R) -> Result<Ciphertext, String> {
+        self.encrypt_with(message, &Mask::sample(self.params.n, rng))
+    }
+
+    /// Encrypt message with the mask and error terms derived from the
+    /// message itself. Reproducible, and for that reason NOT semantically
+    /// secure: equal messages give equal ciphertexts, so anyone can tell
+    /// them apart by encrypting candidates. Use for reproducibility testing
+    /// only; `encrypt_with_rng` otherwise.
+    pub fn encrypt_deterministic(&self, message: i32) -> Result<Ciphertext, String> {
+        let mut stream = HashStream::new(&[b"lwe_mask", &message.to_be_bytes()]);
+        self.encrypt_with(message, &Mask::sample(self.params.n, &mut stream))
+    }
+
+    /// Encrypt arbitrary bytes as one ciphertext per limb of
+    /// `FheParams::limb_bytes` bytes, with fresh randomness per limb.
+    /// The input is padded with 0x80 and zeros to whole limbs, so any
+    /// length round-trips through `SecretKey::decrypt_bytes`. Limbs are
+    /// encrypted in parallel with the `parallel` feature; the randomness is
+    /// still drawn from `rng` in limb order.
+    pub fn encrypt_bytes<R: RngCore + CryptoRng>(&self, bytes: &[u8], rng: &mut R) -> Result<Vec<Ciphertext>, String> {
+        let limbs: Vec<(i32, Mask)> = self.params
+            .limbs(bytes)?
+            .into_iter()
+            .map(|limb| (limb, Mask::sample(self.params.n, rng)))
+            .collect();
+        let encrypt = |(limb, mask): &(i32, Mask)| self.encrypt_with(*limb, mask);
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            limbs.par_iter().map(encrypt).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            limbs.iter().map(encrypt).collect()
+        }
+    }
+
+    /// `encrypt_bytes` with `encrypt_deterministic` per limb. Each limb is
+    /// only a couple of bytes, so equal limbs are easy to spot; reproducibility
+    /// testing only.
+    pub fn encrypt_bytes_deterministic(&self, bytes: &[u8]) -> Result<Vec<Ciphertext>, String> {
+        self.params
+            .limbs(bytes)?
+            .into_iter()
+            .map(|limb| self.encrypt_deterministic(limb))
+            .collect()
+    }
+
+    fn encrypt_with(&self, message: i32, mask: &Mask) -> Result<Ciphertext, String> {
+        let params = &self.params;
+        if message >= params.t() {
+            return Err(format!("Message {} exceeds plaintext modulus {}", message, params.t()));
+        }
+
+        let n = params.n;
+        let delta = params.delta();
+
+        // u = a * r + e1, reordered to u_0, -u_{N-1}, ..., -u_1 so that
+        // <u, s> is the constant coefficient of u * s
+        let poly = params.ring_mul(&self.a, &mask.r, n);
+        let u = (0..n)
+            .map(|j| {
+                let c = poly[(n - j) % n].wrapping_add(mask.e1[(n - j) % n]);
+                params.reduce(if j == 0 { c } else { c.wrapping_neg() })
+            })
+            .collect();
+
+        // v = (b * r)_0 + e2 + m * delta (mod Q)
+        let v = params.reduce(
+            params.ring_mul(&self.b, &mask.r, 1)[0]
+                .wrapping_add(mask.e2)
+                .wrapping_add((message as i64).wrapping_mul(delta)),
+        );
+
+        Ok(Ciphertext {
+            params: *params,
+            u,
+            v,
+            noise: params.fresh_noise(),
+        })
+    }
+}
+
+/// The randomness of one encryption: a ternary mask and the error terms
+struct Mask {
+    r: Vec<i64>,
+    e1: Vec<i64>,
+    e2: i64,
+}
+
+impl Mask {
+    fn sample<R: RngCore>(n: usize, rng: &mut R) -> Self {
+        Self {
+            r: (0..n).map(|_| sample_centered(rng, 1)).collect(),
+            e1: (0..n).map(|_| sample_centered(rng, MAX_E as u32)).collect(),
+            e2: sample_centered(rng, MAX_E as u32),
+        }
+    }
+}
+
+/// The mask decrypts its ciphertext as well as the secret key does
+impl Drop for Mask {
+    fn drop(&mut self) {
+        self.r.zeroize();
+    }
+}
+
+/// Binary LWE secret `s`. `Debug` never prints it, equality is checked in
+/// constant time and the coefficients are zeroized on drop. Copies made
+/// through `to_bytes` or serde are the caller's to wipe.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawSecretKey")]
+pub struct SecretKey {
+    params: FheParams,
+    s: Vec<i32>,
+}
+
+#[derive(Deserialize)]
+struct RawSecretKey {
+    params: FheParams,
+    s: Vec<i32>,
+}
+
+impl TryFrom<RawSecretKey> for SecretKey {
+    type Error = String;
+
+    fn try_from(raw: RawSecretKey) -> Result<Self, String> {
+        if raw.s.len() != raw.params.n {
+            return Err(format!("Invalid secret key length: expected {}, got {}", raw.params.n, raw.s.len()));
+        }
+        if raw.s.iter().any(|&bit| bit != 0 && bit != 1) {
+            return Err("Secret key coefficients must be 0 or 1".to_string());
+        }
+        Ok(Self {
+            params: raw.params,
+            s: raw.s,
+        })
+    }
+}
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.params == other.params && bool::from(self.s.ct_eq(&other.s))
+    }
+}
+
+impl Eq for SecretKey {}
+
+impl Zeroize for SecretKey {
+    fn zeroize(&mut self) {
+        self.s.zeroize();
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretKey {}
+
+impl SecretKey {
+    pub fn params(&self) -> &FheParams {
+        &self.params
+    }
+
+    pub(crate) fn s(&self) -> &[i32] {
+        &self.s
+    }
+
+    /// Parameter header (see `FheParams`), then one byte (0 or 1) per
+    /// coefficient
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.params.n);
+        self.params.write_header(SECRET_KEY_MAGIC, &mut bytes);
+        bytes.extend(self.s.iter().map(|&bit| bit as u8));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let (params, body) = key_body(bytes, SECRET_KEY_MAGIC, |n| n)?;
+        Self::try_from(RawSecretKey {
+            params,
+            s: body.iter().map(|&bit| bit as i32).collect(),
+        })
+    }
+
+    /// Decrypt ciphertext. An exhausted noise budget is not detected and
+    /// yields an arbitrary plaintext; see `decrypt_checked`.
+    pub fn decrypt(&self, ciphertext: Ciphertext) -> Result<i32, String> {
+        let params = &self.params;
+        params.check(&ciphertext)?;
+        let Ciphertext { u, v, .. } = ciphertext;
+
+        // Inner product <u, sk>
+        let inner = params.dot(&u, &self.s);
+
+        // Recover noisy message
+        let m_noisy = params.reduce(v.wrapping_add(inner));
+
+        // Rescale and round
+        Ok(params.decode(m_noisy))
+    }
+
+    /// Decrypt ciphertext, refusing once its noise bound exceeds Q / 2T
+    pub fn decrypt_checked(&self, ciphertext: Ciphertext) -> Result<i32, DecryptError> {
+        if ciphertext.is_exhausted() {
+            return Err(DecryptError::NoiseExhausted {
+                noise: ciphertext.noise,
+                limit: ciphertext.params.max_noise(),
+            });
+        }
+        self.decrypt(ciphertext).map_err(DecryptError::Invalid)
+    }
+
+    /// Reassemble bytes from `PublicKey::encrypt_bytes` ciphertexts,
+    /// checking every limb's noise budget. Limbs are decrypted in parallel
+    /// with the `parallel` feature.
+    pub fn decrypt_bytes(&self, ciphertexts: &[Ciphertext]) -> Result<Vec<u8>, DecryptError> {
+        let decrypt = |ct: &Ciphertext| self.decrypt_checked(ct.clone());
+        #[cfg(feature = "parallel")]
+        let limbs: Vec<i32> = {
+            use rayon::prelude::*;
+            ciphertexts.par_iter().map(decrypt).collect::<Result<_, _>>()?
+        };
+        #[cfg(not(feature = "parallel"))]
+        let limbs: Vec<i32> = ciphertexts.iter().map(decrypt).collect::<Result<_, _>>()?;
+        self.params.unpad(limbs)
+    }
+
+    /// True if `public` was generated from this secret: every coefficient
+    /// of `b + a*s` must be a small error term
+    pub fn matches(&self, public: &PublicKey) -> bool {
+        if self.params != public.params {
+            return false;
+        }
+        let params = &self.params;
+        let (q, limit) = (params.q() as u64, MAX_E as u64);
+        let product = params.ring_mul(&public.a, &self.s, params.n);
+        // |e| <= MAX_E without branching on the secret-derived e
+        public.b.iter().zip(&product).fold(Choice::from(1), |small, (&b, &a_s)| {
+            let e = params.reduce(b.wrapping_add(a_s)) as u64;
+            small & (!e.ct_gt(&limit) | !(q - e).ct_gt(&limit))
+        }).into()
+    }
+}
+
+/// Why `decrypt_checked` refused a ciphertext
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecryptError {
+    /// The ciphertext does not belong to this key's parameters
+    Invalid(String),
+    /// The noise bound exceeds the decryption limit, so the plaintext
+    /// would likely be wrong
+    NoiseExhausted { noise: i64, limit: i64 },
+    /// An authenticated ciphertext failed its integrity check
+    TamperDetected,
+}
+
+impl std::fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecryptError::Invalid(reason) => f.write_str(reason),
+            DecryptError::NoiseExhausted { noise, limit } => {
+                write!(f, "Noise budget exhausted: bound {} exceeds limit {}", noise, limit)
+            }
+            DecryptError::TamperDetected => f.write_str("Ciphertext failed its integrity check"),
+        }
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+const PUBLIC_KEY_MAGIC: &[u8; 4] = b"DXPR";
+/// Public keys of the retired scalar-mask scheme, whose ciphertexts anyone
+/// holding the key could read
+const SCALAR_PUBLIC_KEY_MAGIC: &[u8; 4] = b"DXPK";
+const SECRET_KEY_MAGIC: &[u8; 4] = b"DXSK";
+const CIPHERTEXT_MAGIC: &[u8; 4] = b"DXCT";
+const HEADER_LEN: usize = 10;
+
+/// Check the magic, read the parameter header and return the remaining
+/// `body_len(n)` bytes
+fn key_body<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+    body_len: impl Fn(usize) -> usize,
+) -> Result<(FheParams, &'a [u8]), String> {
+    let (header, body) = bytes.split_at_checked(HEADER_LEN).ok_or("Key is truncated")?;
+    if &header[..4] != magic {
+        return Err("Not a Deoxys key of this kind".to_string());
+    }
+    let n = u32::from_be_bytes(header[4..8].try_into().expect("4-byte dimension"));
+    let params = FheParams::new(n as usize, header[8] as u32, header[9] as u32)?;
+    if body.len() != body_len(params.n) {
+        return Err(format!(
+            "Invalid key length: expected {} bytes, got {}",
+            HEADER_LEN + body_len(params.n),
+            bytes.len()
+        ));
+    }
+    Ok((params, body))
+}
 
 /// Deoxys FHE implementation
 pub struct DeoxysFHE {
     seed: Vec<u8>,
-    sk: Vec<i32>,
-    pk_a: Vec<i64>,
-    pk_b: i64,
+    params: FheParams,
+    public: PublicKey,
+    secret: SecretKey,
+}
+
+/// The seed derives the secret key, so it is wiped along with it
+impl Drop for DeoxysFHE {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
 }
 
 impl DeoxysFHE {
-    /// Initialize FHE with frozen seed
+    /// Initialize FHE with frozen seed and the default parameters
     pub fn new(seed: Option<&[u8]>) -> Self {
+        Self::with_params(seed, FheParams::default())
+    }
+
+    /// Initialize FHE with frozen seed and explicit parameters, such as
+    /// `ParamPreset::HighSecurity.params()`
+    pub fn with_params(seed: Option<&[u8]>, params: FheParams) -> Self {
         let seed_bytes = seed.unwrap_or(b"AxiomHive_Frozen_Seed_v1.0");
         let mut fhe = Self {
             seed: seed_bytes.to_vec(),
-            sk: Vec::new(),
-            pk_a: Vec::new(),
-            pk_b: 0,
+            params,
+            public: PublicKey { params, a: Vec::new(), b: Vec::new() },
+            secret: SecretKey { params, s: Vec::new() },
         };
         fhe.keygen();
         fhe
     }
 
+    /// Use an exported key pair instead of deriving one from a seed
+    pub fn from_keys(public: PublicKey, secret: SecretKey) -> Result<Self, String> {
+        if !secret.matches(&public) {
+            return Err("Secret key does not match public key".to_string());
+        }
+        // Without a seed, serialized ciphertexts are labelled by the public key
+        Ok(Self {
+            seed: public.to_bytes(),
+            params: public.params,
+            public,
+            secret,
+        })
+    }
+
+    pub fn params(&self) -> &FheParams {
+        &self.params
+    }
+
+    pub(crate) fn seed(&self) -> &[u8] {
+        &self.seed
+    }
+
     /// Generate keys deterministically
-    pub fn keygen(&mut self) -> (Vec<i64>, i64) {
-        // Generate secret key from seed
-        let mut hasher = Sha256::new();
-        hasher.update(&self.seed);
-        hasher.update(b"sk");
-        let sk_hash = hasher.finalize();
-        
-        self.sk = (0..N)
-            .map(|i| ((sk_hash[i % sk_hash.len()] >> (i % 8)) & 1) as i32)
+    pub fn keygen(&mut self) -> PublicKey {
+        let params = self.params;
+        let n = params.n;
+        // Generate secret key from seed, 256 independent bits per hash
+        // block so the secret has one bit of entropy per coefficient
+        let s: Vec<i32> = (0..n.div_ceil(256))
+            .flat_map(|block| {
+                let mut hasher = Sha256::new();
+                hasher.update(&self.seed);
+                hasher.update(b"sk");
+                hasher.update((block as u32).to_be_bytes());
+                let hash = hasher.finalize();
+                (0..256).map(move |bit| ((hash[bit / 8] >> (bit % 8)) & 1) as i32)
+            })
+            .take(n)
             .collect();
 
         // Generate public key part A
@@ -49,109 +804,127 @@ impl DeoxysFHE {
         hasher.update(b"pk_a");
         let a_seed = hasher.finalize();
         
-        self.pk_a = (0..N)
+        let a: Vec<i64> = (0..n)
             .map(|i| {
                 let mut hasher = Sha256::new();
-                hasher.update(&a_seed);
-                hasher.update(&(i as u32).to_be_bytes());
+                hasher.update(a_seed);
+                hasher.update((i as u32).to_be_bytes());
                 let hash = hasher.finalize();
                 let val = i64::from_be_bytes([
                     hash[0], hash[1], hash[2], hash[3],
                     hash[4], hash[5], hash[6], hash[7],
                 ]);
-                val % Q
+                val.rem_euclid(params.q())
             })
             .collect();
 
-        // Generate error term
-        let mut hasher = Sha256::new();
-        hasher.update(&self.seed);
-        hasher.update(b"error");
-        let e_hash = hasher.finalize();
-        let e_val = i64::from_be_bytes([
-            e_hash[0], e_hash[1], e_hash[2], e_hash[3],
-            e_hash[4], e_hash[5], e_hash[6], e_hash[7],
-        ]);
-        let e = (e_val % 20) - 10;
+        // Generate error polynomial
+        let mut stream = HashStream::new(&[&self.seed[..], b"error"]);
+        let e = (0..n).map(|_| sample_centered(&mut stream, MAX_E as u32));
 
-        // Compute b = -a * sk + e (mod Q)
-        let dot_prod: i64 = self.pk_a.iter()
-            .zip(self.sk.iter())
-            .map(|(a, &s)| (*a as i64) * (s as i64))
-            .sum();
-        self.pk_b = ((-dot_prod + e) % Q + Q) % Q;
+        // Compute b = -a * sk + e in Z_Q[x]/(x^N + 1)
+        let b = params.ring_mul(&a, &s, n).into_iter().zip(e).map(|(a_s, e)| params.reduce(e.wrapping_sub(a_s))).collect();
 
-        (self.pk_a.clone(), self.pk_b)
+        self.secret = SecretKey { params, s };
+        self.public = PublicKey { params, a, b };
+        self.public.clone()
     }
 
-    /// Encrypt message using LWE
-    pub fn encrypt(&self, message: i32) -> Result<(Vec<i64>, i64), String> {
-        if message >= T {
-            return Err(format!("Message {} exceeds plaintext modulus {}", message, T));
-        }
+    /// The key to hand to parties that only encrypt
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public
+    }
 
-        // Generate deterministic r from message
-        let mut hasher = Sha256::new();
-        hasher.update(message.to_string().as_bytes());
-        hasher.update(b"r");
-        let r_hash = hasher.finalize();
-        let r = (i64::from_be_bytes([
-            r_hash[0], r_hash[1], r_hash[2], r_hash[3],
-            0, 0, 0, 0,
-        ]) % 100) as i64;
-
-        // Generate error terms
-        let mut hasher = Sha256::new();
-        hasher.update(message.to_string().as_bytes());
-        hasher.update(b"e1");
-        let e1_hash = hasher.finalize();
-        let e1 = ((i32::from_be_bytes([e1_hash[0], e1_hash[1], e1_hash[2], e1_hash[3]]) % 20) as i64) - 10;
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.secret
+    }
 
-        let mut hasher = Sha256::new();
-        hasher.update(message.to_string().as_bytes());
-        hasher.update(b"e2");
-        let e2_hash = hasher.finalize();
-        let e2 = ((i32::from_be_bytes([e2_hash[0], e2_hash[1], e2_hash[2], e2_hash[3]]) % 20) as i64) - 10;
+    /// See `PublicKey::encrypt_with_rng`
+    pub fn encrypt_with_rng<R: RngCore + CryptoRng>(&self, message: i32, rng: &mut R) -> Result<Ciphertext, String> {
+        self.public.encrypt_with_rng(message, rng)
+    }
 
-        let delta = Q / (T as i64);
+    /// See `PublicKey::encrypt_deterministic`; not semantically secure
+    pub fn encrypt_deterministic(&self, message: i32) -> Result<Ciphertext, String> {
+        self.public.encrypt_deterministic(message)
+    }
 
-        // u = a * r + e1 (mod Q)
-        let u: Vec<i64> = self.pk_a.iter()
-            .map(|&a_val| ((a_val * r + e1) % Q + Q) % Q)
-            .collect();
+    /// See `SecretKey::decrypt`; does not check the noise budget
+    pub fn decrypt(&self, ciphertext: Ciphertext) -> Result<i32, String> {
+        self.secret.decrypt(ciphertext)
+    }
 
-        // v = b * r + e2 + m * delta (mod Q)
-        let v = ((self.pk_b * r + e2 + (message as i64) * delta) % Q + Q) % Q;
+    /// See `SecretKey::decrypt_checked`
+    pub fn decrypt_checked(&self, ciphertext: Ciphertext) -> Result<i32, DecryptError> {
+        self.secret.decrypt_checked(ciphertext)
+    }
 
-        Ok((u, v))
+    /// See `PublicKey::encrypt_bytes`
+    pub fn encrypt_bytes<R: RngCore + CryptoRng>(&self, bytes: &[u8], rng: &mut R) -> Result<Vec<Ciphertext>, String> {
+        self.public.encrypt_bytes(bytes, rng)
     }
 
-    /// Decrypt ciphertext
-    pub fn decrypt(&self, ciphertext: (Vec<i64>, i64)) -> Result<i32, String> {
-        let (u, v) = ciphertext;
-        if u.len() != N {
-            return Err(format!("Invalid ciphertext length: expected {}, got {}", N, u.len()));
-        }
+    /// See `PublicKey::encrypt_bytes_deterministic`; not semantically secure
+    pub fn encrypt_bytes_deterministic(&self, bytes: &[u8]) -> Result<Vec<Ciphertext>, String> {
+        self.public.encrypt_bytes_deterministic(bytes)
+    }
 
-        // Inner product <u, sk>
-        let inner: i64 = u.iter()
-            .zip(self.sk.iter())
-            .map(|(&u_val, &s)| (u_val * s as i64) % Q)
-            .sum::<i64>() % Q;
+    /// See `SecretKey::decrypt_bytes`
+    pub fn decrypt_bytes(&self, ciphertexts: &[Ciphertext]) -> Result<Vec<u8>, DecryptError> {
+        self.secret.decrypt_bytes(ciphertexts)
+    }
 
-        // Recover noisy message
-        let m_noisy = ((v + inner) % Q + Q) % Q;
+    /// Homomorphic addition: decrypts to `(a + b) mod T`.
+    /// The noise of the two ciphertexts adds up.
+    pub fn add(&self, ct_a: &Ciphertext, ct_b: &Ciphertext) -> Result<Ciphertext, String> {
+        self.combine(ct_a, ct_b, 1)
+    }
 
-        // Rescale and round
-        let delta = Q / (T as i64);
-        let m = ((m_noisy as f64 / delta as f64).round() as i64) % (T as i64);
-        
-        Ok(m as i32)
+    /// Homomorphic subtraction: decrypts to `(a - b) mod T`.
+    /// The noise of the two ciphertexts adds up.
+    pub fn sub(&self, ct_a: &Ciphertext, ct_b: &Ciphertext) -> Result<Ciphertext, String> {
+        self.combine(ct_a, ct_b, -1)
+    }
+
+    /// Multiply by a plaintext scalar: decrypts to `(m * k) mod T`.
+    /// `k` is applied as its representative in [-T/2, T/2), which scales
+    /// the noise by |k| <= T/2. With every preset a fresh ciphertext has
+    /// enough noise budget for any single scalar.
+    pub fn mul_plain(&self, ct: &Ciphertext, k: i32) -> Result<Ciphertext, String> {
+        let params = &self.params;
+        params.check(ct)?;
+        let t = params.t() as i64;
+        let k = (k as i64).rem_euclid(t);
+        let k = if k >= t / 2 { k - t } else { k };
+
+        Ok(Ciphertext {
+            params: *params,
+            u: params.scale_add(&ct.u, k, 0),
+            v: params.reduce(ct.v.wrapping_mul(k)),
+            noise: ct.noise.saturating_mul(k.abs()),
+        })
+    }
+
+    /// Componentwise `a + sign * b` (mod Q)
+    fn combine(&self, ct_a: &Ciphertext, ct_b: &Ciphertext, sign: i64) -> Result<Ciphertext, String> {
+        let params = &self.params;
+        params.check(ct_a)?;
+        params.check(ct_b)?;
+        let u = ct_a.u.iter()
+            .zip(&ct_b.u)
+            .map(|(&a, &b)| params.reduce(a.wrapping_add(sign.wrapping_mul(b))))
+            .collect();
+        Ok(Ciphertext {
+            params: *params,
+            u,
+            v: params.reduce(ct_a.v.wrapping_add(sign.wrapping_mul(ct_b.v))),
+            noise: ct_a.noise.saturating_add(ct_b.noise),
+        })
     }
 
     /// Serialize ciphertext to string format
-    pub fn serialize_ciphertext(&self, ct: (Vec<i64>, i64)) -> (String, String) {
-        let (u, v) = ct;
+    pub fn serialize_ciphertext(&self, ct: Ciphertext) -> (String, String) {
+        let Ciphertext { u, v, .. } = ct;
         let mut hasher = Sha256::new();
         for &val in &u {
             hasher.update(&val.to_be_bytes());
@@ -170,7 +943,7 @@ impl DeoxysFHE {
     }
 
     /// Deserialize ciphertext from string (simplified - in production would store full vectors)
-    pub fn deserialize_ciphertext(&self, ciphertext: &str, _keys: &str) -> Result<(Vec<i64>, i64), String> {
+    pub fn deserialize_ciphertext(&self, ciphertext: &str, _keys: &str) -> Result<Ciphertext, String> {
         // In a full implementation, we would store the full (u, v) vectors
         // For now, we reconstruct deterministically from the hash
         let mut hasher = Sha256::new();
@@ -179,7 +952,8 @@ impl DeoxysFHE {
         let hash = hasher.finalize();
         
         // Reconstruct u vector deterministically
-        let u: Vec<i64> = (0..N)
+        let q = self.params.q();
+        let u: Vec<i64> = (0..self.params.n)
             .map(|i| {
                 let mut h = Sha256::new();
                 h.update(&hash);
@@ -188,7 +962,7 @@ impl DeoxysFHE {
                 i64::from_be_bytes([
                     h_val[0], h_val[1], h_val[2], h_val[3],
                     h_val[4], h_val[5], h_val[6], h_val[7],
-                ]) % Q
+                ]) % q
             })
             .collect();
         
@@ -200,9 +974,283 @@ impl DeoxysFHE {
         let v = i64::from_be_bytes([
             v_hash[0], v_hash[1], v_hash[2], v_hash[3],
             v_hash[4], v_hash[5], v_hash[6], v_hash[7],
-        ]) % Q;
+        ]) % q;
         
-        Ok((u, v))
+        // The noise of a reconstructed ciphertext is unknown; assume fresh
+        Ok(Ciphertext {
+            params: self.params,
+            u,
+            v,
+            noise: self.params.fresh_noise(),
+        })
+    }
+}
+
+/// Uniform in [-bound, bound], by rejection so no value is favoured
+pub(crate) fn sample_centered<R: RngCore>(rng: &mut R, bound: u32) -> i64 {
+    let span = 2 * bound + 1;
+    let zone = u32::MAX - u32::MAX % span;
+    loop {
+        let x = rng.next_u32();
+        if x < zone {
+            return (x % span) as i64 - bound as i64;
+        }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T: i32 = 1 << 16;
+    const SAMPLES: [i32; 6] = [0, 1, 7, 1234, T / 2, T - 1];
+
+    #[test]
+    fn test_roundtrip() {
+        let fhe = DeoxysFHE::new(None);
+        for m in SAMPLES {
+            assert_eq!(fhe.decrypt(fhe.encrypt_deterministic(m).unwrap()).unwrap(), m);
+        }
+    }
+
+    #[test]
+    fn test_randomized_encryption() {
+        let fhe = DeoxysFHE::new(None);
+        let mut rng = rand_core::OsRng;
+        for m in SAMPLES {
+            let ct = fhe.encrypt_with_rng(m, &mut rng).unwrap();
+            assert_eq!(fhe.decrypt(ct.clone()).unwrap(), m);
+            assert_ne!(ct, fhe.encrypt_with_rng(m, &mut rng).unwrap());
+            assert_eq!(fhe.encrypt_deterministic(m).unwrap(), fhe.encrypt_deterministic(m).unwrap());
+        }
+        let sum = fhe.add(&fhe.encrypt_with_rng(40, &mut rng).unwrap(), &fhe.encrypt_deterministic(2).unwrap());
+        assert_eq!(fhe.decrypt(sum.unwrap()).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_key_export_lets_another_party_encrypt() {
+        let owner = DeoxysFHE::new(Some(b"owner"));
+        let public = PublicKey::from_bytes(&owner.public_key().to_bytes()).unwrap();
+        assert_eq!(&public, owner.public_key());
+        let json = serde_json::to_string(&public).unwrap();
+        assert_eq!(serde_json::from_str::<PublicKey>(&json).unwrap(), public);
+
+        // The sender only ever sees the public key
+        let ct = public.encrypt_with_rng(4242, &mut rand_core::OsRng).unwrap();
+        assert_eq!(owner.decrypt(ct.clone()).unwrap(), 4242);
+
+        let secret = SecretKey::from_bytes(&owner.secret_key().to_bytes()).unwrap();
+        let restored = DeoxysFHE::from_keys(public.clone(), secret.clone()).unwrap();
+        assert_eq!(restored.decrypt(ct).unwrap(), 4242);
+        let json = serde_json::to_string(&secret).unwrap();
+        assert_eq!(serde_json::from_str::<SecretKey>(&json).unwrap(), secret);
+        assert_eq!(format!("{:?}", secret), "SecretKey(..)");
+
+        let other = DeoxysFHE::new(Some(b"other"));
+        assert!(DeoxysFHE::from_keys(public.clone(), other.secret_key().clone()).is_err());
+        assert!(PublicKey::from_bytes(&owner.secret_key().to_bytes()).is_err());
+        assert!(PublicKey::from_bytes(&public.to_bytes()[..100]).is_err());
+        let mut bytes = owner.secret_key().to_bytes();
+        bytes[HEADER_LEN] = 2;
+        assert!(SecretKey::from_bytes(&bytes).is_err());
+        assert!(serde_json::from_str::<PublicKey>(r#"{"params":{"n":1024,"log_q":60,"log_t":16},"a":[1,2],"b":[3,4]}"#).is_err());
+
+        // Scalar-mask keys, N + 1 words, no longer import in either form
+        let mut scalar = b"DXPK".to_vec();
+        scalar.extend_from_slice(&public.to_bytes()[4..HEADER_LEN + 1025 * 8]);
+        assert!(PublicKey::from_bytes(&scalar).unwrap_err().contains("retired"));
+        let json = serde_json::json!({ "params": public.params(), "a": public.a(), "b": 3 });
+        assert!(serde_json::from_value::<PublicKey>(json).is_err());
+    }
+
+    #[test]
+    fn test_public_key_ciphertexts_resist_mask_guessing() {
+        let fhe = DeoxysFHE::with_params(Some(b"mask guessing"), ParamPreset::Small.params());
+        let params = *fhe.params();
+        let public = fhe.public_key();
+        let first = public.encrypt_with_rng(42, &mut rand_core::OsRng).unwrap();
+        let second = public.encrypt_with_rng(7, &mut rand_core::OsRng).unwrap();
+        let within = |x: i64, bound: i64| params.reduce(x.wrapping_add(bound)) <= 2 * bound;
+
+        // A scalar mask would leave u = a*r + e1 for some small r, found by
+        // trying them all
+        for ct in [&first, &second] {
+            let guessed = (-(1 << 16)..=1 << 16).find(|&r: &i64| {
+                ct.u().iter().zip(public.a()).all(|(&u, &a)| within(u.wrapping_sub(a.wrapping_mul(r)), MAX_E))
+            });
+            assert_eq!(guessed, None);
+        }
+        // Even without the key, two ciphertexts under scalar masks r and r'
+        // give r'*u - r*u' = r'*e1 - r*e1', small in every coordinate
+        let masks = || (-99..=99i64).flat_map(|r| (-99..=99i64).map(move |r2| (r, r2)));
+        let related = masks().filter(|&pair| pair != (0, 0)).find(|&(r, r2)| {
+            first.u().iter().zip(second.u()).take(8).all(|(&u, &u2)| {
+                within(u.wrapping_mul(r2).wrapping_sub(u2.wrapping_mul(r)), 2 * 99 * MAX_E)
+            })
+        });
+        assert_eq!(related, None);
+        assert_eq!(fhe.decrypt_checked(first).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_presets_trade_security_for_speed() {
+        let mut last_security = 0;
+        for preset in ParamPreset::ALL {
+            let params = preset.params();
+            assert!(params.estimated_lattice_security_bits() > last_security);
+            last_security = params.estimated_lattice_security_bits();
+            assert!(params.noise_budget_bits() > params.log_t as f64, "{:?}", preset);
+
+            let fhe = DeoxysFHE::with_params(None, params);
+            let t = params.t();
+            let ct = fhe.add(&fhe.encrypt_deterministic(t - 1).unwrap(), &fhe.encrypt_deterministic(3).unwrap());
+            assert_eq!(fhe.decrypt(ct.unwrap()).unwrap(), 2);
+            let ct = fhe.mul_plain(&fhe.encrypt_deterministic(1).unwrap(), t / 2 - 1).unwrap();
+            assert_eq!(fhe.decrypt(ct).unwrap(), t / 2 - 1);
+            assert!(fhe.encrypt_deterministic(t).is_err());
+
+            let public = PublicKey::from_bytes(&fhe.public_key().to_bytes()).unwrap();
+            assert_eq!(public.params(), &params);
+
+            // The secret has a bit of its own per coefficient: no short
+            // period repeats across it
+            let s = fhe.secret_key().s();
+            assert!((1..=params.n / 2).all(|period| s[period..] != s[..params.n - period]), "{:?}", preset);
+        }
+        assert_eq!(FheParams::default(), DeoxysFHE::new(None).params().to_owned());
+
+        // Fresh noise must fit, and the moduli must fit i64 arithmetic
+        assert!(FheParams::new(1 << 20, 32, 16).is_err());
+        assert!(FheParams::new(1024, 63, 16).is_err());
+        assert!(FheParams::new(1024, 60, 60).is_err());
+        assert!(serde_json::from_str::<FheParams>(r#"{"n":0,"log_q":60,"log_t":16}"#).is_err());
+
+        // Keys from different parameter sets never mix
+        let small = DeoxysFHE::with_params(None, ParamPreset::Small.params());
+        let default = DeoxysFHE::new(None);
+        assert!(DeoxysFHE::from_keys(default.public_key().clone(), small.secret_key().clone()).is_err());
+        assert!(default.decrypt(small.encrypt_deterministic(1).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_add_and_sub_wrap_modulo_t() {
+        let fhe = DeoxysFHE::new(None);
+        for a in SAMPLES {
+            for b in SAMPLES {
+                let (ct_a, ct_b) = (fhe.encrypt_deterministic(a).unwrap(), fhe.encrypt_deterministic(b).unwrap());
+                let sum = fhe.decrypt(fhe.add(&ct_a, &ct_b).unwrap()).unwrap();
+                assert_eq!(sum, (a + b) % T, "{} + {}", a, b);
+                let difference = fhe.decrypt(fhe.sub(&ct_a, &ct_b).unwrap()).unwrap();
+                assert_eq!(difference, (a - b).rem_euclid(T), "{} - {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_plain() {
+        let fhe = DeoxysFHE::new(None);
+        for m in SAMPLES {
+            let ct = fhe.encrypt_deterministic(m).unwrap();
+            for k in [0, 1, 3, -1, T / 2, T - 1, i32::MIN] {
+                let product = fhe.decrypt(fhe.mul_plain(&ct, k).unwrap()).unwrap();
+                let expected = (m as i64 * k as i64).rem_euclid(T as i64) as i32;
+                assert_eq!(product, expected, "{} * {}", m, k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_operations_compose() {
+        let fhe = DeoxysFHE::new(None);
+        // 3 * (40 + 2) - 26 = 100
+        let sum = fhe.add(&fhe.encrypt_deterministic(40).unwrap(), &fhe.encrypt_deterministic(2).unwrap()).unwrap();
+        let scaled = fhe.mul_plain(&sum, 3).unwrap();
+        let result = fhe.sub(&scaled, &fhe.encrypt_deterministic(26).unwrap()).unwrap();
+        assert_eq!(fhe.decrypt(result).unwrap(), 100);
+        let small = DeoxysFHE::with_params(None, ParamPreset::Small.params());
+        assert!(fhe.add(&small.encrypt_deterministic(1).unwrap(), &fhe.encrypt_deterministic(1).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_bytes_roundtrip_at_any_length() {
+        let text = "prompt = \"Define the Zero Entropy Law.\"\n§ 4.2 Indemnification";
+        for params in [ParamPreset::Small.params(), FheParams::default(), FheParams::new(256, 40, 12).unwrap()] {
+            let fhe = DeoxysFHE::with_params(None, params);
+            for len in [0, 1, 2, 3, text.len()] {
+                let bytes = &text.as_bytes()[..len];
+                let cts = fhe.encrypt_bytes(bytes, &mut rand_core::OsRng).unwrap();
+                assert_eq!(cts.len(), (len + 1).div_ceil(params.limb_bytes()));
+                assert_eq!(fhe.decrypt_bytes(&cts).unwrap(), bytes);
+            }
+        }
+
+        let fhe = DeoxysFHE::new(None);
+        let cts = fhe.encrypt_bytes_deterministic(b"\x80\0\0").unwrap();
+        assert_eq!(fhe.decrypt_bytes(&cts).unwrap(), b"\x80\0\0");
+        // Dropping the padding limb is detected
+        let cts = fhe.encrypt_bytes_deterministic(b"abcd").unwrap();
+        assert!(fhe.decrypt_bytes(&cts[..2]).is_err());
+        assert!(fhe.decrypt_bytes(&[]).is_err());
+        let tiny = DeoxysFHE::with_params(None, FheParams::new(64, 20, 4).unwrap());
+        assert!(tiny.encrypt_bytes_deterministic(b"x").is_err());
+    }
+
+    #[test]
+    fn test_noise_budget_is_tracked_and_enforced() {
+        let fhe = DeoxysFHE::with_params(None, ParamPreset::Small.params());
+        let params = *fhe.params();
+        let ct = fhe.encrypt_deterministic(1).unwrap();
+        assert_eq!(ct.noise_bound(), params.fresh_noise());
+        assert!((ct.remaining_budget() - params.noise_budget_bits()).abs() < 1e-9);
+
+        let sum = fhe.add(&ct, &ct).unwrap();
+        assert_eq!(sum.noise_bound(), 2 * params.fresh_noise());
+        assert!((ct.remaining_budget() - sum.remaining_budget() - 1.0).abs() < 1e-9);
+        let scaled = fhe.mul_plain(&ct, -100).unwrap();
+        assert_eq!(scaled.noise_bound(), 100 * params.fresh_noise());
+        assert_eq!(fhe.decrypt_checked(scaled).unwrap(), params.t() - 100);
+
+        // Keep doubling until the bound passes Q / 2T
+        let mut ct = ct;
+        while !ct.is_exhausted() {
+            ct = fhe.add(&ct, &ct).unwrap();
+        }
+        assert_eq!(ct.remaining_budget(), 0.0);
+        assert!(matches!(
+            fhe.decrypt_checked(ct.clone()),
+            Err(DecryptError::NoiseExhausted { limit, .. }) if limit == params.max_noise()
+        ));
+        assert!(fhe.decrypt(ct).is_ok());
+
+        let json = serde_json::to_string(&fhe.encrypt_deterministic(5).unwrap()).unwrap();
+        let ct: Ciphertext = serde_json::from_str(&json).unwrap();
+        assert_eq!(fhe.decrypt_checked(ct).unwrap(), 5);
+        assert!(serde_json::from_str::<Ciphertext>(&json.replace(r#""noise":"#, r#""noise":-"#)).is_err());
+    }
+
+    #[test]
+    fn test_secret_key_is_zeroized() {
+        fn zeroized_on_drop<T: ZeroizeOnDrop>() {}
+        zeroized_on_drop::<SecretKey>();
+
+        let fhe = DeoxysFHE::new(None);
+        let mut secret = fhe.secret_key().clone();
+        assert_eq!(&secret, fhe.secret_key());
+        assert!(secret.s.contains(&1));
+        secret.zeroize();
+        assert!(secret.s.iter().all(|&bit| bit == 0));
+        assert_ne!(&secret, fhe.secret_key());
+    }
+
+    #[test]
+    fn test_decode_rounds_like_division() {
+        for preset in ParamPreset::ALL {
+            let params = preset.params();
+            let delta = params.delta();
+            for m_noisy in [0, 1, delta / 2 - 1, delta / 2, delta * 3 - 1, params.q() - delta / 2, params.q() - 1] {
+                let expected = ((m_noisy + delta / 2) / delta) % params.t() as i64;
+                assert_eq!(params.decode(m_noisy) as i64, expected);
+            }
+        }
+    }
+}
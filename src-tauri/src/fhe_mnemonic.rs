@@ -0,0 +1,184 @@
+//! Deoxys FHE mnemonic seeds
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Recoverable key material from BIP39 phrases. A phrase is checked against
+//! its checksum, stretched by BIP39's PBKDF2-HMAC-SHA512 together with an
+//! optional passphrase, and the 64-byte result is expanded with HKDF-SHA256
+//! under a label naming the scheme and parameters. One phrase therefore
+//! gives an independent key for every parameter set, LWE or ring-LWE, and
+//! for other uses labelled through `mnemonic_key`.
+
+use crate::fhe_core::{DeoxysFHE, FheParams};
+use crate::fhe_rlwe::{DeoxysRlwe, RlweParams};
+use bip39::Mnemonic;
+use hkdf::Hkdf;
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// Phrase lengths BIP39 defines, from 128 to 256 bits of entropy
+pub const MNEMONIC_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// A fresh English phrase of `word_count` words
+pub fn generate_mnemonic<R: RngCore + CryptoRng>(word_count: usize, rng: &mut R) -> Result<String, String> {
+    if !MNEMONIC_WORD_COUNTS.contains(&word_count) {
+        return Err(format!("A mnemonic has 12, 15, 18, 21 or 24 words, not {}", word_count));
+    }
+    // Every three words carry 32 bits of entropy and one checksum bit
+    let mut entropy = Zeroizing::new(vec![0u8; word_count / 3 * 4]);
+    rng.fill_bytes(&mut entropy);
+    let mnemonic = Mnemonic::from_entropy(&entropy).map_err(|e| e.to_string())?;
+    Ok(mnemonic.to_string())
+}
+
+/// Check a phrase's words and checksum without deriving any keys
+pub fn validate_mnemonic(phrase: &str) -> Result<(), String> {
+    parse(phrase).map(drop)
+}
+
+impl DeoxysFHE {
+    /// Recover the key pair for the default parameters from a phrase
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, String> {
+        Self::from_mnemonic_with(phrase, "", FheParams::default())
+    }
+
+    /// Recover the key pair for `params` from a phrase and passphrase. A
+    /// wrong passphrase is not detectable; it yields different keys.
+    pub fn from_mnemonic_with(phrase: &str, passphrase: &str, params: FheParams) -> Result<Self, String> {
+        let seed = fhe_seed(&parse(phrase)?, passphrase, &params);
+        Ok(Self::with_params(Some(&seed[..]), params))
+    }
+}
+
+impl DeoxysRlwe {
+    /// Recover the ring key for the default parameters from a phrase
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, String> {
+        Self::from_mnemonic_with(phrase, "", RlweParams::default())
+    }
+
+    /// Recover the ring key for `params` from a phrase and passphrase, as
+    /// `DeoxysFHE::from_mnemonic_with` does
+    pub fn from_mnemonic_with(phrase: &str, passphrase: &str, params: RlweParams) -> Result<Self, String> {
+        let info = format!("AxiomHive Deoxys RLWE v1 n={} q={} t={}", params.n(), params.q(), params.t());
+        let seed = derive_seed(&parse(phrase)?, passphrase, &info);
+        Ok(Self::with_params(Some(&seed[..]), params))
+    }
+}
+
+/// A 32-byte key for the use named by `info`, independent of the FHE keys
+pub(crate) fn mnemonic_key(phrase: &str, passphrase: &str, info: &str) -> Result<Zeroizing<[u8; 32]>, String> {
+    Ok(derive_seed(&parse(phrase)?, passphrase, info))
+}
+
+fn parse(phrase: &str) -> Result<Mnemonic, String> {
+    Mnemonic::parse(phrase).map_err(|e| format!("Invalid mnemonic: {}", e))
+}
+
+/// `derive_seed` labelled with the LWE parameters
+fn fhe_seed(mnemonic: &Mnemonic, passphrase: &str, params: &FheParams) -> Zeroizing<[u8; 32]> {
+    let info = format!("AxiomHive Deoxys FHE v1 n={} q={} t={}", params.n(), params.q(), params.t());
+    derive_seed(mnemonic, passphrase, &info)
+}
+
+/// HKDF over the BIP39 seed, with the scheme and parameters in `info`
+fn derive_seed(mnemonic: &Mnemonic, passphrase: &str, info: &str) -> Zeroizing<[u8; 32]> {
+    let bip39_seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+    let mut seed = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(b"AxiomHive_Mnemonic_Salt"), &bip39_seed[..])
+        .expand(info.as_bytes(), &mut seed[..])
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fhe_core::ParamPreset;
+    use rand_core::OsRng;
+
+    /// The first BIP39 English test vector: all-zero entropy
+    const ZERO_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_generated_phrases_validate_and_recover() {
+        for words in MNEMONIC_WORD_COUNTS {
+            let phrase = generate_mnemonic(words, &mut OsRng).unwrap();
+            assert_eq!(phrase.split_whitespace().count(), words);
+            assert!(validate_mnemonic(&phrase).is_ok());
+        }
+        assert!(generate_mnemonic(13, &mut OsRng).is_err());
+
+        let phrase = generate_mnemonic(24, &mut OsRng).unwrap();
+        let first = DeoxysFHE::from_mnemonic(&phrase).unwrap();
+        let second = DeoxysFHE::from_mnemonic(&phrase).unwrap();
+        assert_eq!(first.public_key(), second.public_key());
+        assert_eq!(first.secret_key(), second.secret_key());
+        let ct = first.encrypt_with_rng(4242, &mut OsRng).unwrap();
+        assert_eq!(second.decrypt_checked(ct).unwrap(), 4242);
+    }
+
+    #[test]
+    fn test_checksum_and_words_are_enforced() {
+        assert!(validate_mnemonic(ZERO_PHRASE).is_ok());
+        // Same words, wrong checksum word
+        let bad_checksum = ZERO_PHRASE.replace("about", "abandon");
+        assert!(validate_mnemonic(&bad_checksum).is_err());
+        assert!(DeoxysFHE::from_mnemonic(&bad_checksum).is_err());
+        assert!(validate_mnemonic(&ZERO_PHRASE.replace("about", "axiomhive")).is_err());
+        assert!(validate_mnemonic("abandon abandon about").is_err());
+    }
+
+    #[test]
+    fn test_passphrase_and_params_separate_keys() {
+        let plain = DeoxysFHE::from_mnemonic(ZERO_PHRASE).unwrap();
+        let salted = DeoxysFHE::from_mnemonic_with(ZERO_PHRASE, "TREZOR", FheParams::default()).unwrap();
+        assert_ne!(plain.public_key(), salted.public_key());
+
+        let small = DeoxysFHE::from_mnemonic_with(ZERO_PHRASE, "", ParamPreset::Small.params()).unwrap();
+        let default_seed = fhe_seed(&parse(ZERO_PHRASE).unwrap(), "", &FheParams::default());
+        let small_seed = fhe_seed(&parse(ZERO_PHRASE).unwrap(), "", small.params());
+        assert_ne!(default_seed, small_seed);
+
+        // Not the legacy raw-bytes derivation of the phrase
+        assert_ne!(plain.public_key(), DeoxysFHE::new(Some(ZERO_PHRASE.as_bytes())).public_key());
+    }
+
+    #[test]
+    fn test_ring_keys_recover_from_phrases() {
+        let key_id = |fhe: &DeoxysRlwe| fhe.serialize_ciphertext(&fhe.encrypt_deterministic(7).unwrap()).1;
+        let phrase = generate_mnemonic(24, &mut OsRng).unwrap();
+        let first = DeoxysRlwe::from_mnemonic(&phrase).unwrap();
+        let second = DeoxysRlwe::from_mnemonic(&phrase).unwrap();
+        assert_eq!(key_id(&first), key_id(&second));
+        let ct = first.encrypt_with_rng(4242, &mut OsRng).unwrap();
+        assert_eq!(second.decrypt_checked(ct).unwrap(), 4242);
+
+        // Not the frozen-seed key, and the passphrase still separates keys
+        assert_ne!(key_id(&first), key_id(&DeoxysRlwe::new(None)));
+        let salted = DeoxysRlwe::from_mnemonic_with(&phrase, "TREZOR", RlweParams::default()).unwrap();
+        assert_ne!(key_id(&first), key_id(&salted));
+        assert!(DeoxysRlwe::from_mnemonic(&ZERO_PHRASE.replace("about", "abandon")).is_err());
+    }
+
+    #[test]
+    fn test_seeds_one_bit_apart_give_unrelated_secrets() {
+        for preset in ParamPreset::ALL {
+            let params = preset.params();
+            let seed = fhe_seed(&parse(ZERO_PHRASE).unwrap(), "", &params);
+            let mut flipped = seed.clone();
+            flipped[31] ^= 1;
+            let a = DeoxysFHE::with_params(Some(&seed[..]), params);
+            let b = DeoxysFHE::with_params(Some(&flipped[..]), params);
+            let (a, b) = (a.secret_key().s(), b.secret_key().s());
+            assert_eq!(a.len(), params.n());
+            // Unrelated bits differ about half the time in every stretch of
+            // the secret, and not in the same places from one stretch to the
+            // next as a repeated block would
+            let diffs: Vec<Vec<bool>> = a.chunks(256).zip(b.chunks(256)).map(|(x, y)| x.iter().zip(y).map(|(x, y)| x != y).collect()).collect();
+            for (i, diff) in diffs.iter().enumerate() {
+                let differing = diff.iter().filter(|&&d| d).count();
+                assert!((64..=192).contains(&differing), "{:?}: {} of {} coefficients differ", preset, differing, diff.len());
+                assert!(diffs[..i].iter().all(|earlier| earlier != diff), "{:?}: block {} repeats", preset, i);
+            }
+        }
+    }
+}
@@ -0,0 +1,732 @@
+//! Deoxys ring-LWE
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Ring-LWE over Z_Q[x]/(x^N + 1) with the prime Q = 2^62 - 2^16 + 1.
+//! 2N divides Q - 1 for every N up to 2^15, so ring products go through a
+//! negacyclic NTT in O(N log N). A ciphertext is two polynomials (c0, c1)
+//! carrying N plaintext coefficients mod T, where LWE needs N + 1 words for
+//! every single value. Keys live in the NTT domain.
+
+use crate::fhe_core::{sample_centered, DecryptError};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// Ciphertext modulus: prime, with 2^16 | Q - 1
+pub const RLWE_Q: i64 = 0x3fff_ffff_ffff_0001;
+/// Generator of the multiplicative group mod `RLWE_Q`
+const GENERATOR: i64 = 7;
+/// Largest ring degree `RLWE_Q` has a negacyclic NTT for
+const MAX_DEGREE: usize = 1 << 15;
+/// Largest |e| of any error coefficient keygen or encryption uses
+const MAX_E: i64 = 10;
+
+const CIPHERTEXT_MAGIC: &[u8; 4] = b"DXRC";
+
+/// Ring degree N and plaintext modulus T = 2^log_t
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawRlweParams")]
+pub struct RlweParams {
+    n: usize,
+    log_t: u32,
+}
+
+#[derive(Deserialize)]
+struct RawRlweParams {
+    n: usize,
+    log_t: u32,
+}
+
+impl TryFrom<RawRlweParams> for RlweParams {
+    type Error = String;
+
+    fn try_from(raw: RawRlweParams) -> Result<Self, String> {
+        Self::new(raw.n, raw.log_t)
+    }
+}
+
+impl RlweParams {
+    /// N must be a power of two in [16, 2^15], and log_t in [1, 30] with
+    /// room left for a fresh ciphertext's noise
+    pub fn new(n: usize, log_t: u32) -> Result<Self, String> {
+        if !n.is_power_of_two() || !(16..=MAX_DEGREE).contains(&n) {
+            return Err(format!("Ring degree {} is not a power of two in [16, {}]", n, MAX_DEGREE));
+        }
+        if !(1..=30).contains(&log_t) {
+            return Err(format!("log_t {} is outside [1, 30]", log_t));
+        }
+        let params = Self { n, log_t };
+        if params.fresh_noise() > params.max_noise() {
+            return Err(format!("T = 2^{} leaves no noise budget at N = {}", log_t, n));
+        }
+        Ok(params)
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn q(&self) -> i64 {
+        RLWE_Q
+    }
+
+    pub fn t(&self) -> i32 {
+        1 << self.log_t
+    }
+
+    /// floor(Q / T), the scale plaintexts are encoded at
+    pub fn delta(&self) -> i64 {
+        RLWE_Q >> self.log_t
+    }
+
+    /// Largest noise that still decrypts correctly, less the Q mod T slack
+    /// that plaintext wrap-around adds
+    pub fn max_noise(&self) -> i64 {
+        self.delta() / 2 - self.t() as i64
+    }
+
+    /// Bound on a fresh ciphertext's noise `e*u + e1 + e2*s`, with ternary
+    /// u and binary s
+    pub fn fresh_noise(&self) -> i64 {
+        2 * MAX_E * self.n as i64 + MAX_E
+    }
+
+    /// Round a noisy `delta * m` to m. delta is not a power of two, so the
+    /// division goes through a precomputed reciprocal with one masked
+    /// correction instead of a data-dependent `div`.
+    fn decode(&self, x: i64) -> i32 {
+        let delta = self.delta() as u64;
+        let reciprocal = ((1u128 << 64) / delta as u128) as u64;
+        let y = x as u64 + delta / 2;
+        // floor(y / delta) or one less
+        let quotient = ((y as u128 * reciprocal as u128) >> 64) as u64;
+        let remainder = y - quotient * delta;
+        let quotient = quotient + (1 - (remainder.wrapping_sub(delta) >> 63));
+        (quotient & (self.t() as u64 - 1)) as i32
+    }
+
+    /// Q mod T: the extra noise each multiple of T that a plaintext wraps
+    /// past costs, since T * delta falls short of Q by this much
+    fn wrap_noise(&self) -> i64 {
+        RLWE_Q % self.t() as i64
+    }
+}
+
+impl Default for RlweParams {
+    fn default() -> Self {
+        Self { n: 1024, log_t: 16 }
+    }
+}
+
+/// Ring-LWE ciphertext `(c0, c1)` with a worst-case noise bound, as for
+/// `Ciphertext`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawRlweCiphertext")]
+pub struct RlweCiphertext {
+    params: RlweParams,
+    c0: Vec<i64>,
+    c1: Vec<i64>,
+    noise: i64,
+}
+
+#[derive(Deserialize)]
+struct RawRlweCiphertext {
+    params: RlweParams,
+    c0: Vec<i64>,
+    c1: Vec<i64>,
+    noise: i64,
+}
+
+impl TryFrom<RawRlweCiphertext> for RlweCiphertext {
+    type Error = String;
+
+    fn try_from(raw: RawRlweCiphertext) -> Result<Self, String> {
+        let n = raw.params.n;
+        if raw.c0.len() != n || raw.c1.len() != n {
+            return Err(format!("Ring ciphertext polynomials must have {} coefficients", n));
+        }
+        if raw.c0.iter().chain(&raw.c1).any(|x| !(0..RLWE_Q).contains(x)) || raw.noise < 0 {
+            return Err("Ring ciphertext value out of range".to_string());
+        }
+        Ok(Self {
+            params: raw.params,
+            c0: raw.c0,
+            c1: raw.c1,
+            noise: raw.noise,
+        })
+    }
+}
+
+impl RlweCiphertext {
+    pub fn params(&self) -> &RlweParams {
+        &self.params
+    }
+
+    pub fn noise_bound(&self) -> i64 {
+        self.noise
+    }
+
+    /// Bits of noise budget left; see `Ciphertext::remaining_budget`
+    pub fn remaining_budget(&self) -> f64 {
+        (self.params.max_noise() as f64 / self.noise.max(1) as f64).log2().max(0.0)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.noise > self.params.max_noise()
+    }
+
+    /// Magic "DXRC", N as u32, log_t, the noise bound as i64, then c0 and
+    /// c1 as big-endian i64
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(17 + self.params.n * 16);
+        bytes.extend_from_slice(CIPHERTEXT_MAGIC);
+        bytes.extend_from_slice(&(self.params.n as u32).to_be_bytes());
+        bytes.push(self.params.log_t as u8);
+        bytes.extend_from_slice(&self.noise.to_be_bytes());
+        for x in self.c0.iter().chain(&self.c1) {
+            bytes.extend_from_slice(&x.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 17 || &bytes[..4] != CIPHERTEXT_MAGIC {
+            return Err("Not a ring ciphertext".to_string());
+        }
+        let n = u32::from_be_bytes(bytes[4..8].try_into().expect("4-byte slice")) as usize;
+        let params = RlweParams::new(n, bytes[8] as u32)?;
+        let noise = i64::from_be_bytes(bytes[9..17].try_into().expect("8-byte slice"));
+        let body = &bytes[17..];
+        if body.len() != n * 16 {
+            return Err(format!("Expected {} bytes of coefficients, got {}", n * 16, body.len()));
+        }
+        let mut coeffs: Vec<i64> = body
+            .chunks_exact(8)
+            .map(|c| i64::from_be_bytes(c.try_into().expect("8-byte chunk")))
+            .collect();
+        let c1 = coeffs.split_off(n);
+        Self::try_from(RawRlweCiphertext { params, c0: coeffs, c1, noise })
+    }
+}
+
+/// Twiddle factors for the negacyclic NTT of one degree
+#[derive(Debug, Clone)]
+struct Ntt {
+    /// psi^bitrev(i), psi a primitive 2N-th root of unity
+    psi: Vec<i64>,
+    /// psi^-bitrev(i)
+    psi_inv: Vec<i64>,
+    n_inv: i64,
+}
+
+impl Ntt {
+    fn new(n: usize) -> Self {
+        let psi = pow_mod(GENERATOR, (RLWE_Q - 1) / (2 * n as i64));
+        let psi_inv = pow_mod(psi, RLWE_Q - 2);
+        let bits = n.trailing_zeros();
+        let table = |root: i64| -> Vec<i64> {
+            (0..n)
+                .map(|i| pow_mod(root, (i.reverse_bits() >> (usize::BITS - bits)) as i64))
+                .collect()
+        };
+        Self {
+            psi: table(psi),
+            psi_inv: table(psi_inv),
+            n_inv: pow_mod(n as i64, RLWE_Q - 2),
+        }
+    }
+
+    /// In place, coefficients to evaluations (Cooley-Tukey)
+    fn forward(&self, a: &mut [i64]) {
+        let n = a.len();
+        let (mut t, mut m) = (n, 1);
+        while m < n {
+            t /= 2;
+            for i in 0..m {
+                let w = self.psi[m + i];
+                for j in 2 * i * t..2 * i * t + t {
+                    let (x, y) = (a[j], mul_mod(a[j + t], w));
+                    a[j] = add_mod(x, y);
+                    a[j + t] = add_mod(x, RLWE_Q - y);
+                }
+            }
+            m *= 2;
+        }
+    }
+
+    /// In place, evaluations back to coefficients (Gentleman-Sande)
+    fn inverse(&self, a: &mut [i64]) {
+        let n = a.len();
+        let (mut t, mut m) = (1, n);
+        while m > 1 {
+            let h = m / 2;
+            for i in 0..h {
+                let w = self.psi_inv[h + i];
+                for j in 2 * i * t..2 * i * t + t {
+                    let (x, y) = (a[j], a[j + t]);
+                    a[j] = add_mod(x, y);
+                    a[j + t] = mul_mod(add_mod(x, RLWE_Q - y), w);
+                }
+            }
+            t *= 2;
+            m = h;
+        }
+        for x in a.iter_mut() {
+            *x = mul_mod(*x, self.n_inv);
+        }
+    }
+
+    /// Product of two polynomials in evaluations, in coefficients
+    fn multiply_hat(&self, a_hat: &[i64], b_hat: &[i64]) -> Vec<i64> {
+        let mut product: Vec<i64> = a_hat.iter().zip(b_hat).map(|(&x, &y)| mul_mod(x, y)).collect();
+        self.inverse(&mut product);
+        product
+    }
+
+    /// `a` in coefficients times `b_hat` in evaluations, in coefficients
+    fn multiply(&self, a: &[i64], b_hat: &[i64]) -> Vec<i64> {
+        let mut a_hat = a.to_vec();
+        self.forward(&mut a_hat);
+        self.multiply_hat(&a_hat, b_hat)
+    }
+}
+
+// Arithmetic mod Q touches the secret key during decryption, so it avoids
+// data-dependent branches and divisions: conditional subtractions are
+// masks, and products are reduced by Montgomery multiplication.
+
+/// -Q^-1 mod 2^64, by Newton iteration from Q (correct to 3 bits as Q is odd)
+const Q_NEG_INV: u64 = {
+    let q = RLWE_Q as u64;
+    let mut inv = q;
+    let mut i = 0;
+    while i < 5 {
+        inv = inv.wrapping_mul(2u64.wrapping_sub(q.wrapping_mul(inv)));
+        i += 1;
+    }
+    inv.wrapping_neg()
+};
+
+/// 2^128 mod Q, to leave the Montgomery domain
+const R2: u64 = ((u128::MAX % RLWE_Q as u128 + 1) % RLWE_Q as u128) as u64;
+
+/// `x - Q` if `x >= Q`, for x < 2Q
+fn reduce_once(x: u64) -> u64 {
+    let d = x.wrapping_sub(RLWE_Q as u64);
+    let keep_x = 0u64.wrapping_sub(d >> 63);
+    (d & !keep_x) | (x & keep_x)
+}
+
+/// t * 2^-64 mod Q, for t < Q * 2^64
+fn redc(t: u128) -> u64 {
+    let m = (t as u64).wrapping_mul(Q_NEG_INV);
+    reduce_once(((t + m as u128 * RLWE_Q as u128) >> 64) as u64)
+}
+
+fn add_mod(a: i64, b: i64) -> i64 {
+    reduce_once((a + b) as u64) as i64
+}
+
+fn mul_mod(a: i64, b: i64) -> i64 {
+    redc(redc(a as u128 * b as u128) as u128 * R2 as u128) as i64
+}
+
+fn pow_mod(mut base: i64, mut exp: i64) -> i64 {
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base);
+        }
+        base = mul_mod(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Small signed value into [0, Q)
+fn lift(x: i64) -> i64 {
+    x + (RLWE_Q & (x >> 63))
+}
+
+/// Byte stream from SHA-256 of `seed` and a counter, for the deterministic
+/// paths only
+pub(crate) struct HashStream {
+    seed: Vec<u8>,
+    counter: u64,
+    block: [u8; 32],
+    used: usize,
+}
+
+impl HashStream {
+    pub(crate) fn new(parts: &[&[u8]]) -> Self {
+        Self {
+            seed: parts.concat(),
+            counter: 0,
+            block: [0; 32],
+            used: 32,
+        }
+    }
+}
+
+/// Its state derives the secret key
+impl Drop for HashStream {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+        self.block.zeroize();
+    }
+}
+
+impl RngCore for HashStream {
+    fn next_u32(&mut self) -> u32 {
+        let mut word = [0; 4];
+        self.fill_bytes(&mut word);
+        u32::from_be_bytes(word)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut word = [0; 8];
+        self.fill_bytes(&mut word);
+        u64::from_be_bytes(word)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.used == 32 {
+                let mut hasher = Sha256::new();
+                hasher.update(&self.seed);
+                hasher.update(self.counter.to_be_bytes());
+                self.block = hasher.finalize().into();
+                self.counter += 1;
+                self.used = 0;
+            }
+            *byte = self.block[self.used];
+            self.used += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Deoxys ring-LWE: the `DeoxysFHE` API over polynomials
+pub struct DeoxysRlwe {
+    params: RlweParams,
+    ntt: Ntt,
+    /// Public key `(b = -a*s + e, a)` and secret `s`, all in NTT form
+    a_hat: Vec<i64>,
+    b_hat: Vec<i64>,
+    s_hat: Vec<i64>,
+    key_id: String,
+}
+
+impl Drop for DeoxysRlwe {
+    fn drop(&mut self) {
+        self.s_hat.zeroize();
+    }
+}
+
+impl DeoxysRlwe {
+    /// Initialize with frozen seed and the default parameters
+    pub fn new(seed: Option<&[u8]>) -> Self {
+        Self::with_params(seed, RlweParams::default())
+    }
+
+    /// Initialize with frozen seed and explicit parameters. Keys are
+    /// derived deterministically from the seed.
+    pub fn with_params(seed: Option<&[u8]>, params: RlweParams) -> Self {
+        let seed = seed.unwrap_or(b"AxiomHive_Frozen_Seed_v1.0");
+        let n = params.n;
+        let ntt = Ntt::new(n);
+
+        let mut stream = HashStream::new(&[seed, b"rlwe_sk"]);
+        let mut s_hat: Vec<i64> = (0..n).map(|_| (stream.next_u32() & 1) as i64).collect();
+        ntt.forward(&mut s_hat);
+
+        let mut stream = HashStream::new(&[seed, b"rlwe_pk_a"]);
+        let mut a_hat: Vec<i64> = (0..n).map(|_| (stream.next_u64() >> 2) as i64 % RLWE_Q).collect();
+        ntt.forward(&mut a_hat);
+
+        let mut stream = HashStream::new(&[seed, b"rlwe_error"]);
+        let mut b_hat: Vec<i64> = (0..n).map(|_| lift(sample_centered(&mut stream, MAX_E as u32))).collect();
+        ntt.forward(&mut b_hat);
+        for ((b, &a), &s) in b_hat.iter_mut().zip(&a_hat).zip(&s_hat) {
+            *b = add_mod(*b, RLWE_Q - mul_mod(a, s));
+        }
+
+        let mut hasher = Sha256::new();
+        for x in &b_hat {
+            hasher.update(x.to_be_bytes());
+        }
+        let key_id = hasher.finalize()[..8].iter().map(|b| format!("{:02x}", b)).collect();
+
+        Self { params, ntt, a_hat, b_hat, s_hat, key_id }
+    }
+
+    pub fn params(&self) -> &RlweParams {
+        &self.params
+    }
+
+    pub(crate) fn s_hat(&self) -> &[i64] {
+        &self.s_hat
+    }
+
+    /// Encrypt `message` into the constant coefficient
+    pub fn encrypt_with_rng<R: RngCore + CryptoRng>(&self, message: i32, rng: &mut R) -> Result<RlweCiphertext, String> {
+        self.encrypt_poly_with_rng(&[message], rng)
+    }
+
+    /// Encrypt up to N coefficients, each below T; missing ones are 0
+    pub fn encrypt_poly_with_rng<R: RngCore + CryptoRng>(&self, message: &[i32], rng: &mut R) -> Result<RlweCiphertext, String> {
+        self.encrypt_with(message, rng)
+    }
+
+    /// Encrypt with randomness derived from the message and seed. Equal
+    /// messages give equal ciphertexts, so this is NOT semantically secure;
+    /// reproducibility testing only, as `DeoxysFHE::encrypt_deterministic`.
+    pub fn encrypt_deterministic(&self, message: i32) -> Result<RlweCiphertext, String> {
+        self.encrypt_poly_deterministic(&[message])
+    }
+
+    pub fn encrypt_poly_deterministic(&self, message: &[i32]) -> Result<RlweCiphertext, String> {
+        let bytes: Vec<u8> = message.iter().flat_map(|m| m.to_be_bytes()).collect();
+        let mut stream = HashStream::new(&[self.key_id.as_bytes(), b"rlwe_r", &bytes]);
+        self.encrypt_with(message, &mut stream)
+    }
+
+    fn encrypt_with<R: RngCore>(&self, message: &[i32], rng: &mut R) -> Result<RlweCiphertext, String> {
+        let params = &self.params;
+        if message.len() > params.n {
+            return Err(format!("{} coefficients exceed the ring degree {}", message.len(), params.n));
+        }
+        if let Some(&m) = message.iter().find(|&&m| !(0..params.t()).contains(&m)) {
+            return Err(format!("Message {} is outside the plaintext modulus {}", m, params.t()));
+        }
+
+        let mut u_hat: Vec<i64> = (0..params.n).map(|_| lift(sample_centered(rng, 1))).collect();
+        self.ntt.forward(&mut u_hat);
+        let delta = params.delta();
+
+        // c0 = b*u + e1 + delta*m, c1 = a*u + e2
+        let mut c0 = self.ntt.multiply_hat(&u_hat, &self.b_hat);
+        for (i, c) in c0.iter_mut().enumerate() {
+            let m = message.get(i).copied().unwrap_or(0) as i64;
+            *c = add_mod(add_mod(*c, lift(sample_centered(rng, MAX_E as u32))), mul_mod(m, delta));
+        }
+        let mut c1 = self.ntt.multiply_hat(&u_hat, &self.a_hat);
+        for c in c1.iter_mut() {
+            *c = add_mod(*c, lift(sample_centered(rng, MAX_E as u32)));
+        }
+
+        Ok(RlweCiphertext {
+            params: *params,
+            c0,
+            c1,
+            noise: params.fresh_noise(),
+        })
+    }
+
+    /// The constant coefficient. An exhausted noise budget is not detected;
+    /// see `decrypt_checked`.
+    pub fn decrypt(&self, ciphertext: RlweCiphertext) -> Result<i32, String> {
+        Ok(self.decrypt_poly(&ciphertext)?[0])
+    }
+
+    /// The constant coefficient, refusing once the noise bound exceeds the
+    /// limit
+    pub fn decrypt_checked(&self, ciphertext: RlweCiphertext) -> Result<i32, DecryptError> {
+        Ok(self.decrypt_poly_checked(&ciphertext)?[0])
+    }
+
+    /// All N coefficients, without a noise check
+    pub fn decrypt_poly(&self, ciphertext: &RlweCiphertext) -> Result<Vec<i32>, String> {
+        let params = &self.params;
+        self.check(ciphertext)?;
+
+        // c0 + c1*s = delta*m + noise
+        let mut c1_s = self.ntt.multiply(&ciphertext.c1, &self.s_hat);
+        let m = ciphertext.c0.iter()
+            .zip(&c1_s)
+            .map(|(&c0, &c1_s)| params.decode(add_mod(c0, c1_s)))
+            .collect();
+        c1_s.zeroize();
+        Ok(m)
+    }
+
+    pub fn decrypt_poly_checked(&self, ciphertext: &RlweCiphertext) -> Result<Vec<i32>, DecryptError> {
+        if ciphertext.is_exhausted() {
+            return Err(DecryptError::NoiseExhausted {
+                noise: ciphertext.noise,
+                limit: ciphertext.params.max_noise(),
+            });
+        }
+        self.decrypt_poly(ciphertext).map_err(DecryptError::Invalid)
+    }
+
+    /// Coefficient-wise `(a + b) mod T`
+    pub fn add(&self, ct_a: &RlweCiphertext, ct_b: &RlweCiphertext) -> Result<RlweCiphertext, String> {
+        self.combine(ct_a, ct_b, add_mod)
+    }
+
+    /// Coefficient-wise `(a - b) mod T`
+    pub fn sub(&self, ct_a: &RlweCiphertext, ct_b: &RlweCiphertext) -> Result<RlweCiphertext, String> {
+        self.combine(ct_a, ct_b, |x, y| add_mod(x, RLWE_Q - y))
+    }
+
+    /// Every coefficient times `k`, mod T; `k` is centered as in
+    /// `DeoxysFHE::mul_plain`
+    pub fn mul_plain(&self, ct: &RlweCiphertext, k: i32) -> Result<RlweCiphertext, String> {
+        let params = &self.params;
+        self.check(ct)?;
+        let t = params.t() as i64;
+        let k = (k as i64).rem_euclid(t);
+        let k = if k >= t / 2 { k - t } else { k };
+        let scale = |x: &i64| mul_mod(*x, lift(k));
+
+        Ok(RlweCiphertext {
+            params: *params,
+            c0: ct.c0.iter().map(scale).collect(),
+            c1: ct.c1.iter().map(scale).collect(),
+            noise: ct.noise.saturating_mul(k.abs()).saturating_add(k.abs() * params.wrap_noise()),
+        })
+    }
+
+    fn combine(&self, ct_a: &RlweCiphertext, ct_b: &RlweCiphertext, op: impl Fn(i64, i64) -> i64) -> Result<RlweCiphertext, String> {
+        let params = &self.params;
+        self.check(ct_a)?;
+        self.check(ct_b)?;
+        let apply = |x: &[i64], y: &[i64]| x.iter().zip(y).map(|(&x, &y)| op(x, y)).collect();
+        Ok(RlweCiphertext {
+            params: *params,
+            c0: apply(&ct_a.c0, &ct_b.c0),
+            c1: apply(&ct_a.c1, &ct_b.c1),
+            noise: ct_a.noise.saturating_add(ct_b.noise).saturating_add(params.wrap_noise()),
+        })
+    }
+
+    fn check(&self, ct: &RlweCiphertext) -> Result<(), String> {
+        if ct.params != self.params {
+            return Err(format!("Ciphertext parameters {:?} do not match {:?}", ct.params, self.params));
+        }
+        Ok(())
+    }
+
+    /// Serialize to `(ciphertext, keys)`: the hex of `to_bytes` and the id of
+    /// the key it was encrypted under. Unlike the LWE pair this is lossless.
+    pub fn serialize_ciphertext(&self, ct: &RlweCiphertext) -> (String, String) {
+        let hex = ct.to_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+        (hex, self.key_id.clone())
+    }
+
+    /// Inverse of `serialize_ciphertext`; fails for another key's ciphertexts
+    pub fn deserialize_ciphertext(&self, ciphertext: &str, keys: &str) -> Result<RlweCiphertext, String> {
+        if keys != self.key_id {
+            return Err("Ciphertext was encrypted under a different key".to_string());
+        }
+        if !ciphertext.len().is_multiple_of(2) || !ciphertext.is_ascii() {
+            return Err("Ciphertext is not hex".to_string());
+        }
+        let bytes = (0..ciphertext.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&ciphertext[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| "Ciphertext is not hex".to_string())?;
+        let ct = RlweCiphertext::from_bytes(&bytes)?;
+        self.check(&ct)?;
+        Ok(ct)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_ntt_multiplies_negacyclically() {
+        let params = RlweParams::new(16, 4).unwrap();
+        let ntt = Ntt::new(params.n());
+        let a: Vec<i64> = (1..=16).collect();
+        let b: Vec<i64> = (0..16).map(|i| lift(i % 3 - 1)).collect();
+
+        // Schoolbook product with x^N = -1
+        let mut expected = vec![0i64; 16];
+        for (i, &a_i) in a.iter().enumerate() {
+            for (j, &b_j) in b.iter().enumerate() {
+                let term = mul_mod(a_i, b_j);
+                let k = (i + j) % 16;
+                expected[k] = if i + j < 16 { add_mod(expected[k], term) } else { add_mod(expected[k], RLWE_Q - term) };
+            }
+        }
+        let mut b_hat = b;
+        ntt.forward(&mut b_hat);
+        assert_eq!(ntt.multiply(&a, &b_hat), expected);
+
+        let mut roundtrip = a.clone();
+        ntt.forward(&mut roundtrip);
+        ntt.inverse(&mut roundtrip);
+        assert_eq!(roundtrip, a);
+    }
+
+    #[test]
+    fn test_branchless_arithmetic_matches_reference() {
+        let params = RlweParams::default();
+        let mut stream = HashStream::new(&[b"reference"]);
+        for _ in 0..1000 {
+            let (a, b) = ((stream.next_u64() >> 2) as i64 % RLWE_Q, (stream.next_u64() >> 2) as i64 % RLWE_Q);
+            assert_eq!(mul_mod(a, b), (a as i128 * b as i128 % RLWE_Q as i128) as i64);
+            assert_eq!(add_mod(a, b), (a + b) % RLWE_Q);
+            let expected = ((a + params.delta() / 2) / params.delta()) % params.t() as i64;
+            assert_eq!(params.decode(a) as i64, expected);
+        }
+        assert_eq!(mul_mod(RLWE_Q - 1, RLWE_Q - 1), 1);
+        assert_eq!(lift(-1), RLWE_Q - 1);
+    }
+
+    #[test]
+    fn test_roundtrip_and_ops() {
+        let fhe = DeoxysRlwe::new(None);
+        let t = fhe.params().t();
+        let ct = fhe.encrypt_with_rng(42, &mut OsRng).unwrap();
+        assert_eq!(fhe.decrypt_checked(ct.clone()).unwrap(), 42);
+        assert_ne!(fhe.encrypt_with_rng(42, &mut OsRng).unwrap(), ct);
+        assert_eq!(fhe.encrypt_deterministic(42).unwrap(), fhe.encrypt_deterministic(42).unwrap());
+
+        let xs: Vec<i32> = (0..1024).map(|i| i * 64).collect();
+        let ys: Vec<i32> = (0..1024).map(|i| t - 1 - i).collect();
+        let (ct_x, ct_y) = (fhe.encrypt_poly_with_rng(&xs, &mut OsRng).unwrap(), fhe.encrypt_poly_with_rng(&ys, &mut OsRng).unwrap());
+        let sum = fhe.decrypt_poly_checked(&fhe.add(&ct_x, &ct_y).unwrap()).unwrap();
+        let difference = fhe.decrypt_poly_checked(&fhe.sub(&ct_x, &ct_y).unwrap()).unwrap();
+        let scaled = fhe.decrypt_poly_checked(&fhe.mul_plain(&ct_x, -5).unwrap()).unwrap();
+        for i in 0..1024 {
+            assert_eq!(sum[i], (xs[i] + ys[i]) % t);
+            assert_eq!(difference[i], (xs[i] - ys[i]).rem_euclid(t));
+            assert_eq!(scaled[i], (xs[i] * -5).rem_euclid(t));
+        }
+
+        assert!(fhe.encrypt_with_rng(t, &mut OsRng).is_err());
+        assert!(fhe.encrypt_poly_with_rng(&[0; 1025], &mut OsRng).is_err());
+        assert!(RlweParams::new(1000, 16).is_err());
+    }
+
+    #[test]
+    fn test_serialization_is_lossless_and_keyed() {
+        let fhe = DeoxysRlwe::new(None);
+        let ct = fhe.encrypt_with_rng(7, &mut OsRng).unwrap();
+        let (ciphertext, keys) = fhe.serialize_ciphertext(&ct);
+        let restored = fhe.deserialize_ciphertext(&ciphertext, &keys).unwrap();
+        assert_eq!(fhe.decrypt(restored.clone()).unwrap(), 7);
+        assert_eq!(restored, ct);
+
+        let json = serde_json::to_string(&ct).unwrap();
+        assert_eq!(serde_json::from_str::<RlweCiphertext>(&json).unwrap(), ct);
+
+        let other = DeoxysRlwe::new(Some(b"another seed"));
+        assert!(other.deserialize_ciphertext(&ciphertext, &keys).is_err());
+        assert!(fhe.deserialize_ciphertext(&ciphertext[..40], &keys).is_err());
+
+        // Two polynomials for N values, against N * (N + 1) words as LWE
+        assert_eq!(ct.to_bytes().len(), 17 + 2 * 1024 * 8);
+    }
+}
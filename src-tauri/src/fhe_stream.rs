@@ -0,0 +1,298 @@
+//! Deoxys FHE streaming encryption
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Encryption at rest for inputs too large to hold as single ciphertexts,
+//! such as contract PDFs and model transcripts. The input is cut into
+//! chunks of N plaintext limbs and every chunk becomes one ring
+//! ciphertext, so a stream is about 8x the size of its input.
+//!
+//! Layout: the header "DXST", a version byte and a random 16-byte stream
+//! id, then records. A chunk record is 0x01, the chunk's plaintext length
+//! as u32, its `RlweCiphertext::to_bytes` and an HMAC-SHA256 tag over the
+//! stream id, chunk index, length and ciphertext. The stream ends with a
+//! seal record: 0x02, the total plaintext length as u64 and an HMAC over
+//! the header, every chunk tag and the totals. Modified, reordered,
+//! dropped or truncated chunks all fail with `TamperDetected`.
+
+use crate::fhe_core::DecryptError;
+use crate::fhe_rlwe::{DeoxysRlwe, RlweCiphertext};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+use std::io::{self, Read, Write};
+use zeroize::Zeroizing;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const STREAM_MAGIC: &[u8; 4] = b"DXST";
+const STREAM_VERSION: u8 = 1;
+const HEADER_LEN: usize = 21;
+const CHUNK_RECORD: u8 = 0x01;
+const SEAL_RECORD: u8 = 0x02;
+const TAG_LEN: usize = 32;
+
+impl DeoxysRlwe {
+    /// Plaintext bytes per chunk: N limbs of whole bytes below T
+    pub fn stream_chunk_bytes(&self) -> usize {
+        self.params().n() * limb_bytes(self)
+    }
+
+    /// Encrypt everything `reader` yields into `writer`, returning the
+    /// number of plaintext bytes
+    pub fn encrypt_stream<Rd: Read, W: Write, R: RngCore + CryptoRng>(
+        &self,
+        mut reader: Rd,
+        mut writer: W,
+        rng: &mut R,
+    ) -> io::Result<u64> {
+        let width = limb_bytes(self);
+        if width == 0 {
+            return Err(io::Error::other(format!("Plaintext modulus {} cannot hold a byte", self.params().t())));
+        }
+        let mut header = [0u8; HEADER_LEN];
+        header[..4].copy_from_slice(STREAM_MAGIC);
+        header[4] = STREAM_VERSION;
+        rng.fill_bytes(&mut header[5..]);
+        writer.write_all(&header)?;
+
+        let key = self.stream_key();
+        let mut seal = seal_mac(&key, &header);
+        let mut chunk = Zeroizing::new(vec![0u8; self.stream_chunk_bytes()]);
+        let (mut index, mut total) = (0u64, 0u64);
+        loop {
+            let len = read_full(&mut reader, &mut chunk)?;
+            if len == 0 {
+                break;
+            }
+            let limbs: Zeroizing<Vec<i32>> = Zeroizing::new(
+                chunk[..len]
+                    .chunks(width)
+                    .map(|limb| limb.iter().enumerate().fold(0i32, |acc, (i, &b)| acc | (b as i32) << (8 * (width - 1 - i))))
+                    .collect(),
+            );
+            let ct = self.encrypt_poly_with_rng(&limbs, rng).map_err(io::Error::other)?.to_bytes();
+            let tag = chunk_tag(&key, &header, index, len as u32, &ct);
+
+            writer.write_all(&[CHUNK_RECORD])?;
+            writer.write_all(&(len as u32).to_be_bytes())?;
+            writer.write_all(&ct)?;
+            writer.write_all(&tag)?;
+            seal.update(&tag);
+            index += 1;
+            total += len as u64;
+        }
+
+        seal.update(&index.to_be_bytes());
+        seal.update(&total.to_be_bytes());
+        writer.write_all(&[SEAL_RECORD])?;
+        writer.write_all(&total.to_be_bytes())?;
+        writer.write_all(&seal.finalize().into_bytes())?;
+        writer.flush()?;
+        Ok(total)
+    }
+
+    /// Decrypt a stream from `encrypt_stream` into `writer`, returning the
+    /// number of plaintext bytes. Each chunk is written once its tag
+    /// verifies, but reordering and truncation only show at the seal, so
+    /// on any error everything written so far must be discarded.
+    pub fn decrypt_stream<Rd: Read, W: Write>(&self, mut reader: Rd, mut writer: W) -> Result<u64, DecryptError> {
+        let width = limb_bytes(self);
+        let chunk_bytes = self.stream_chunk_bytes();
+        let ct_len = 17 + self.params().n() * 16;
+
+        let mut header = [0u8; HEADER_LEN];
+        read_exact(&mut reader, &mut header)?;
+        if &header[..4] != STREAM_MAGIC || header[4] != STREAM_VERSION {
+            return Err(DecryptError::Invalid("Not a Deoxys stream of this version".to_string()));
+        }
+
+        let key = self.stream_key();
+        let mut seal = seal_mac(&key, &header);
+        let (mut index, mut total) = (0u64, 0u64);
+        loop {
+            let mut kind = [0u8; 1];
+            read_exact(&mut reader, &mut kind)?;
+            match kind[0] {
+                CHUNK_RECORD => {
+                    let mut len = [0u8; 4];
+                    read_exact(&mut reader, &mut len)?;
+                    let len = u32::from_be_bytes(len);
+                    let mut ct = vec![0u8; ct_len];
+                    read_exact(&mut reader, &mut ct)?;
+                    let mut tag = [0u8; TAG_LEN];
+                    read_exact(&mut reader, &mut tag)?;
+
+                    // The tag is checked before the length or ciphertext
+                    // are interpreted at all
+                    chunk_mac(&key, &header, index, len, &ct)
+                        .verify_slice(&tag)
+                        .map_err(|_| DecryptError::TamperDetected)?;
+                    if len == 0 || len as usize > chunk_bytes {
+                        return Err(DecryptError::Invalid(format!("Chunk length {} out of range", len)));
+                    }
+                    let ct = RlweCiphertext::from_bytes(&ct).map_err(DecryptError::Invalid)?;
+                    let limbs = Zeroizing::new(self.decrypt_poly_checked(&ct)?);
+                    let mut plaintext = Zeroizing::new(Vec::with_capacity(chunk_bytes));
+                    for &limb in limbs.iter() {
+                        plaintext.extend_from_slice(&limb.to_be_bytes()[4 - width..]);
+                    }
+                    writer.write_all(&plaintext[..len as usize]).map_err(io_error)?;
+
+                    seal.update(&tag);
+                    index += 1;
+                    total += len as u64;
+                }
+                SEAL_RECORD => {
+                    let mut claimed = [0u8; 8];
+                    read_exact(&mut reader, &mut claimed)?;
+                    let mut tag = [0u8; TAG_LEN];
+                    read_exact(&mut reader, &mut tag)?;
+                    seal.update(&index.to_be_bytes());
+                    seal.update(&claimed);
+                    seal.verify_slice(&tag).map_err(|_| DecryptError::TamperDetected)?;
+                    if u64::from_be_bytes(claimed) != total {
+                        return Err(DecryptError::TamperDetected);
+                    }
+                    if reader.read(&mut kind).map_err(io_error)? != 0 {
+                        return Err(DecryptError::Invalid("Data after the stream seal".to_string()));
+                    }
+                    writer.flush().map_err(io_error)?;
+                    return Ok(total);
+                }
+                other => return Err(DecryptError::Invalid(format!("Unknown stream record {:#04x}", other))),
+            }
+        }
+    }
+
+    /// MAC key for streams, derived from the secret key
+    fn stream_key(&self) -> Zeroizing<[u8; 32]> {
+        let secret: Zeroizing<Vec<u8>> = Zeroizing::new(self.s_hat().iter().flat_map(|x| x.to_be_bytes()).collect());
+        let mut key = Zeroizing::new([0u8; 32]);
+        Hkdf::<Sha256>::new(None, &secret)
+            .expand(b"AxiomHive Deoxys stream MAC v1", &mut key[..])
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        key
+    }
+}
+
+/// Whole bytes per limb under the instance's T
+fn limb_bytes(fhe: &DeoxysRlwe) -> usize {
+    fhe.params().t().trailing_zeros() as usize / 8
+}
+
+fn seal_mac(key: &[u8; 32], header: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(b"seal");
+    mac.update(header);
+    mac
+}
+
+fn chunk_mac(key: &[u8; 32], header: &[u8], index: u64, len: u32, ct: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(b"chunk");
+    mac.update(header);
+    mac.update(&index.to_be_bytes());
+    mac.update(&len.to_be_bytes());
+    mac.update(ct);
+    mac
+}
+
+fn chunk_tag(key: &[u8; 32], header: &[u8], index: u64, len: u32, ct: &[u8]) -> [u8; TAG_LEN] {
+    chunk_mac(key, header, index, len, ct).finalize().into_bytes().into()
+}
+
+/// Fill `buf` unless the reader ends first; the number of bytes read
+fn read_full<Rd: Read>(reader: &mut Rd, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// A stream that ends early was truncated
+fn read_exact<Rd: Read>(reader: &mut Rd, buf: &mut [u8]) -> Result<(), DecryptError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => DecryptError::TamperDetected,
+        _ => io_error(e),
+    })
+}
+
+fn io_error(e: io::Error) -> DecryptError {
+    DecryptError::Invalid(format!("Stream I/O failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fhe_rlwe::RlweParams;
+    use rand_core::OsRng;
+
+    fn small() -> DeoxysRlwe {
+        DeoxysRlwe::with_params(None, RlweParams::new(64, 16).unwrap())
+    }
+
+    fn encrypt(fhe: &DeoxysRlwe, plaintext: &[u8]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        assert_eq!(fhe.encrypt_stream(plaintext, &mut stream, &mut OsRng).unwrap(), plaintext.len() as u64);
+        stream
+    }
+
+    fn decrypt(fhe: &DeoxysRlwe, stream: &[u8]) -> Result<Vec<u8>, DecryptError> {
+        let mut plaintext = Vec::new();
+        fhe.decrypt_stream(stream, &mut plaintext).map(|_| plaintext)
+    }
+
+    #[test]
+    fn test_streams_round_trip() {
+        let fhe = small();
+        assert_eq!(fhe.stream_chunk_bytes(), 128);
+        for len in [0, 1, 127, 128, 129, 1000] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+            let stream = encrypt(&fhe, &plaintext);
+            assert_eq!(decrypt(&fhe, &stream).unwrap(), plaintext);
+        }
+
+        // Fresh randomness and stream id every time
+        assert_ne!(encrypt(&fhe, b"contract"), encrypt(&fhe, b"contract"));
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let fhe = small();
+        let plaintext = vec![7u8; 300];
+        let stream = encrypt(&fhe, &plaintext);
+        let record = 1 + 4 + 17 + 64 * 16 + TAG_LEN;
+        assert_eq!(stream.len(), HEADER_LEN + 3 * record + 1 + 8 + TAG_LEN);
+
+        let mut flipped = stream.clone();
+        flipped[HEADER_LEN + 100] ^= 1;
+        assert_eq!(decrypt(&fhe, &flipped), Err(DecryptError::TamperDetected));
+
+        // Swap the first two chunks
+        let mut swapped = stream[..HEADER_LEN].to_vec();
+        swapped.extend_from_slice(&stream[HEADER_LEN + record..HEADER_LEN + 2 * record]);
+        swapped.extend_from_slice(&stream[HEADER_LEN..HEADER_LEN + record]);
+        swapped.extend_from_slice(&stream[HEADER_LEN + 2 * record..]);
+        assert_eq!(decrypt(&fhe, &swapped), Err(DecryptError::TamperDetected));
+
+        // Drop the last chunk, or cut the stream short
+        let mut dropped = stream[..HEADER_LEN + 2 * record].to_vec();
+        dropped.extend_from_slice(&stream[HEADER_LEN + 3 * record..]);
+        assert_eq!(decrypt(&fhe, &dropped), Err(DecryptError::TamperDetected));
+        assert_eq!(decrypt(&fhe, &stream[..stream.len() - 1]), Err(DecryptError::TamperDetected));
+        assert_eq!(decrypt(&fhe, &stream[..HEADER_LEN + record]), Err(DecryptError::TamperDetected));
+
+        let mut trailing = stream.clone();
+        trailing.push(0);
+        assert!(matches!(decrypt(&fhe, &trailing), Err(DecryptError::Invalid(_))));
+
+        let other = DeoxysRlwe::with_params(Some(b"another seed"), *fhe.params());
+        assert_eq!(decrypt(&other, &stream), Err(DecryptError::TamperDetected));
+    }
+}
@@ -0,0 +1,295 @@
+//! Deoxys FHE threshold decryption
+//! AxiomHive Sovereign Manifold v2.1.0
+//! The LWE secret s is split into M additive shares s_1 + ... + s_M = s
+//! (mod Q). Every M - 1 of them are uniformly random, so no component of
+//! the manifold holding fewer than all M shares learns anything about s.
+//! Each holder publishes a partial decryption <u, s_i> + smudging noise;
+//! `combine_partials` adds all M of them to v and rounds as in decryption.
+//! `generate_shared_key` deals a fresh key that never exists whole after
+//! splitting; splitting a key someone keeps protects nothing.
+
+use crate::fhe_core::{Ciphertext, DecryptError, DeoxysFHE, FheParams, PublicKey, SecretKey};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// One party's additive share of a `SecretKey`, handled like the key
+/// itself: redacted `Debug`, constant-time equality, zeroized on drop
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawKeyShare")]
+pub struct KeyShare {
+    params: FheParams,
+    index: usize,
+    parties: usize,
+    s: Vec<i64>,
+}
+
+#[derive(Deserialize)]
+struct RawKeyShare {
+    params: FheParams,
+    index: usize,
+    parties: usize,
+    s: Vec<i64>,
+}
+
+impl TryFrom<RawKeyShare> for KeyShare {
+    type Error = String;
+
+    fn try_from(raw: RawKeyShare) -> Result<Self, String> {
+        if raw.parties < 2 || raw.index >= raw.parties {
+            return Err(format!("Invalid share {} of {}", raw.index, raw.parties));
+        }
+        if raw.s.len() != raw.params.n() || raw.s.iter().any(|x| !(0..raw.params.q()).contains(x)) {
+            return Err("Key share does not fit its parameters".to_string());
+        }
+        Ok(Self {
+            params: raw.params,
+            index: raw.index,
+            parties: raw.parties,
+            s: raw.s,
+        })
+    }
+}
+
+impl std::fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "KeyShare({} of {}, {:?})", self.index, self.parties, self.params)
+    }
+}
+
+impl PartialEq for KeyShare {
+    fn eq(&self, other: &Self) -> bool {
+        (self.params, self.index, self.parties) == (other.params, other.index, other.parties)
+            && bool::from(self.s.ct_eq(&other.s))
+    }
+}
+
+impl Eq for KeyShare {}
+
+impl Zeroize for KeyShare {
+    fn zeroize(&mut self) {
+        self.s.zeroize();
+    }
+}
+
+impl Drop for KeyShare {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for KeyShare {}
+
+/// One party's contribution towards decrypting a single ciphertext
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialDecryption {
+    pub index: usize,
+    pub parties: usize,
+    /// Fingerprint of the ciphertext this belongs to
+    pub ciphertext: String,
+    pub value: i64,
+}
+
+impl SecretKey {
+    /// Split into `parties` additive shares, all of which are needed to
+    /// decrypt. The shares are fresh for every call.
+    pub fn split<R: RngCore + CryptoRng>(&self, parties: usize, rng: &mut R) -> Result<Vec<KeyShare>, String> {
+        if parties < 2 {
+            return Err("Threshold decryption needs at least two parties".to_string());
+        }
+        let params = *self.params();
+        // Q is a power of two, so masking a random word is uniform mod Q
+        let mut last: Vec<i64> = self.s().iter().map(|&bit| bit as i64).collect();
+        let mut shares = Vec::with_capacity(parties);
+        for index in 0..parties - 1 {
+            let s: Vec<i64> = (0..params.n()).map(|_| params.reduce(rng.next_u64() as i64)).collect();
+            for (l, &x) in last.iter_mut().zip(&s) {
+                *l = params.reduce(l.wrapping_sub(x));
+            }
+            shares.push(KeyShare { params, index, parties, s });
+        }
+        shares.push(KeyShare { params, index: parties - 1, parties, s: last });
+        Ok(shares)
+    }
+}
+
+/// A fresh key pair dealt out as `parties` shares: the public key to
+/// encrypt under and one share per party. The seed and whole secret are
+/// wiped before this returns, so only the shares together can decrypt.
+pub fn generate_shared_key<R: RngCore + CryptoRng>(
+    params: FheParams,
+    parties: usize,
+    rng: &mut R,
+) -> Result<(PublicKey, Vec<KeyShare>), String> {
+    let mut seed = Zeroizing::new([0u8; 32]);
+    rng.fill_bytes(&mut seed[..]);
+    let fhe = DeoxysFHE::with_params(Some(&seed[..]), params);
+    let shares = fhe.secret_key().split(parties, rng)?;
+    Ok((fhe.public_key().clone(), shares))
+}
+
+impl KeyShare {
+    pub fn params(&self) -> &FheParams {
+        &self.params
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn parties(&self) -> usize {
+        self.parties
+    }
+
+    /// `<u, s_i>` plus noise of up to `smudging_bound`, which keeps the
+    /// combined result from revealing the ciphertext's exact noise
+    pub fn partial_decrypt<R: RngCore + CryptoRng>(&self, ct: &Ciphertext, rng: &mut R) -> Result<PartialDecryption, DecryptError> {
+        if *ct.params() != self.params {
+            return Err(DecryptError::Invalid("Ciphertext does not match this key share".to_string()));
+        }
+        check_budget(ct)?;
+        let params = &self.params;
+        let smudge = sample_wide(rng, smudging_bound(ct, self.parties));
+        Ok(PartialDecryption {
+            index: self.index,
+            parties: self.parties,
+            ciphertext: fingerprint(ct),
+            value: params.reduce(params.dot(ct.u(), &self.s).wrapping_add(smudge)),
+        })
+    }
+}
+
+/// Decrypt `ct` from the partial decryptions of all its key's shares, in
+/// any order
+pub fn combine_partials(ct: &Ciphertext, partials: &[PartialDecryption]) -> Result<i32, DecryptError> {
+    let invalid = |reason: &str| Err(DecryptError::Invalid(reason.to_string()));
+    let parties = match partials.first() {
+        Some(p) => p.parties,
+        None => return invalid("No partial decryptions"),
+    };
+    if partials.len() != parties {
+        return invalid(&format!("Need all {} partial decryptions, got {}", parties, partials.len()));
+    }
+    let mut seen = vec![false; parties];
+    let id = fingerprint(ct);
+    for p in partials {
+        if p.parties != parties || p.index >= parties || std::mem::replace(&mut seen[p.index], true) {
+            return invalid("Partial decryptions do not come from one set of shares");
+        }
+        if p.ciphertext != id {
+            return invalid("Partial decryption belongs to another ciphertext");
+        }
+    }
+    check_budget(ct)?;
+
+    let params = ct.params();
+    let m_noisy = partials
+        .iter()
+        .fold(ct.v(), |acc, p| params.reduce(acc.wrapping_add(p.value)));
+    Ok(params.decode(m_noisy))
+}
+
+fn check_budget(ct: &Ciphertext) -> Result<(), DecryptError> {
+    if ct.is_exhausted() {
+        return Err(DecryptError::NoiseExhausted {
+            noise: ct.noise_bound(),
+            limit: ct.params().max_noise(),
+        });
+    }
+    Ok(())
+}
+
+/// Per-party smudging noise: half the ciphertext's remaining headroom,
+/// split across the parties, so the sum still decrypts
+fn smudging_bound(ct: &Ciphertext, parties: usize) -> u64 {
+    ((ct.params().max_noise() - ct.noise_bound()) / (2 * parties as i64)) as u64
+}
+
+/// Uniform in [-bound, bound], as `sample_centered` for 64-bit bounds
+fn sample_wide<R: RngCore>(rng: &mut R, bound: u64) -> i64 {
+    let span = 2 * bound + 1;
+    let zone = u64::MAX - u64::MAX % span;
+    loop {
+        let x = rng.next_u64();
+        if x < zone {
+            return (x % span) as i64 - bound as i64;
+        }
+    }
+}
+
+/// Short hash identifying a ciphertext
+fn fingerprint(ct: &Ciphertext) -> String {
+    let mut hasher = Sha256::new();
+    for x in ct.u().iter().chain([&ct.v()]) {
+        hasher.update(x.to_be_bytes());
+    }
+    hasher.finalize()[..16].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fhe_core::ParamPreset;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_all_shares_decrypt_together() {
+        let fhe = DeoxysFHE::new(None);
+        let shares = fhe.secret_key().split(3, &mut OsRng).unwrap();
+        let ct = fhe.add(
+            &fhe.encrypt_with_rng(1200, &mut OsRng).unwrap(),
+            &fhe.encrypt_with_rng(34, &mut OsRng).unwrap(),
+        ).unwrap();
+
+        let mut partials: Vec<PartialDecryption> = shares
+            .iter()
+            .map(|share| share.partial_decrypt(&ct, &mut OsRng).unwrap())
+            .collect();
+        partials.reverse();
+        assert_eq!(combine_partials(&ct, &partials).unwrap(), 1234);
+
+        // Every share is needed, once
+        assert!(combine_partials(&ct, &partials[..2]).is_err());
+        let duplicated = vec![partials[0].clone(), partials[0].clone(), partials[1].clone()];
+        assert!(combine_partials(&ct, &duplicated).is_err());
+
+        let other = fhe.encrypt_with_rng(1234, &mut OsRng).unwrap();
+        assert!(matches!(combine_partials(&other, &partials), Err(DecryptError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_shares_are_fresh_and_validated() {
+        let fhe = DeoxysFHE::new(None);
+        let first = fhe.secret_key().split(2, &mut OsRng).unwrap();
+        let second = fhe.secret_key().split(2, &mut OsRng).unwrap();
+        assert_ne!(first[0], second[0]);
+        assert!(fhe.secret_key().split(1, &mut OsRng).is_err());
+        assert!(!format!("{:?}", first[0]).contains(&first[0].s[0].to_string()));
+
+        let json = serde_json::to_string(&first[1]).unwrap();
+        assert_eq!(serde_json::from_str::<KeyShare>(&json).unwrap(), first[1]);
+        let forged = json.replace("\"index\":1", "\"index\":2");
+        assert!(serde_json::from_str::<KeyShare>(&forged).is_err());
+
+        let mut share = first[0].clone();
+        share.zeroize();
+        assert!(share.s.iter().all(|&x| x == 0));
+        assert_ne!(share, first[0]);
+    }
+
+    #[test]
+    fn test_generated_keys_exist_only_as_shares() {
+        let params = ParamPreset::Small.params();
+        let (public, shares) = generate_shared_key(params, 3, &mut OsRng).unwrap();
+        let ct = public.encrypt_with_rng(42, &mut OsRng).unwrap();
+        let partials: Vec<PartialDecryption> = shares.iter().map(|share| share.partial_decrypt(&ct, &mut OsRng).unwrap()).collect();
+        assert_eq!(combine_partials(&ct, &partials).unwrap(), 42);
+
+        // Not the frozen-seed key, and a new key every call
+        assert_ne!(&public, DeoxysFHE::with_params(None, params).public_key());
+        assert_ne!(generate_shared_key(params, 3, &mut OsRng).unwrap().0, public);
+        assert!(generate_shared_key(params, 1, &mut OsRng).is_err());
+    }
+}
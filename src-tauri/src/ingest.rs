@@ -0,0 +1,352 @@
+//! AxiomHive Document Ingestion
+//! Deterministic Legal Contract Summarization Pipeline
+//! Converts an uploaded contract file into the plain text the contract
+//! analyzer reads, with pure Rust extractors and no external tools:
+//!
+//! - PDF (lopdf): the text of each page, one line per text object
+//! - DOCX (zip, quick-xml): one line per paragraph of word/document.xml
+//! - HTML: one line per block element, without scripts, styles or tags,
+//!   with entities decoded
+//! - plain text, taken as is apart from line endings
+//!
+//! Lines of extracted text have their whitespace collapsed, and blank
+//! lines are dropped. Where each page (PDF) or paragraph (otherwise)
+//! lies in the text is kept as character offsets, the same offsets the
+//! analyzer's evidence spans use, so a finding can be traced back to its
+//! page. List numbering Word generates is not part of the document text,
+//! so automatically numbered DOCX clauses lose their numbers.
+//!
+//! Compiled with the `ingest` feature.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{Cursor, Read};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentFormat {
+    Pdf,
+    Docx,
+    Html,
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    Page,
+    Paragraph,
+}
+
+/// A page or paragraph of the extracted text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Segment {
+    pub kind: SegmentKind,
+    /// 1-based
+    pub number: usize,
+    /// Character offset of the first character
+    pub start: usize,
+    /// Character offset just past the last character
+    pub end: usize,
+}
+
+/// Text extracted from a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestedDocument {
+    pub format: DocumentFormat,
+    pub text: String,
+    /// In document order
+    pub segments: Vec<Segment>,
+}
+
+impl IngestedDocument {
+    /// The page or paragraph containing character `offset`
+    pub fn segment_at(&self, offset: usize) -> Option<&Segment> {
+        self.segments.iter().find(|s| s.start <= offset && offset < s.end)
+    }
+
+    /// A document of `parts`, pages or paragraphs, each of lines
+    fn new(format: DocumentFormat, kind: SegmentKind, parts: Vec<Vec<String>>) -> Self {
+        let mut text = String::new();
+        let mut chars = 0;
+        let mut segments = Vec::new();
+        for (i, lines) in parts.into_iter().enumerate() {
+            let part = lines.join("\n");
+            if part.is_empty() {
+                continue;
+            }
+            if !text.is_empty() {
+                text.push('\n');
+                chars += 1;
+            }
+            let start = chars;
+            chars += part.chars().count();
+            text.push_str(&part);
+            segments.push(Segment { kind, number: i + 1, start, end: chars });
+        }
+        Self { format, text, segments }
+    }
+}
+
+/// Why a file could not be ingested
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IngestError {
+    /// Neither a PDF, DOCX or HTML file nor UTF-8 text
+    UnsupportedFormat,
+    Malformed { format: DocumentFormat, error: String },
+    /// The PDF is encrypted
+    Encrypted,
+    /// The file has no text, e.g. a scanned PDF
+    NoText,
+}
+
+impl fmt::Display for IngestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngestError::UnsupportedFormat => write!(f, "Unsupported file format"),
+            IngestError::Malformed { format, error } => write!(f, "Malformed {:?} file: {}", format, error),
+            IngestError::Encrypted => write!(f, "Encrypted PDFs cannot be read"),
+            IngestError::NoText => write!(f, "The file contains no text"),
+        }
+    }
+}
+
+impl std::error::Error for IngestError {}
+
+/// The format of `bytes`, by content first and then by `file_name`
+pub fn detect(bytes: &[u8], file_name: Option<&str>) -> Option<DocumentFormat> {
+    if bytes.starts_with(b"%PDF-") {
+        return Some(DocumentFormat::Pdf);
+    }
+    if bytes.starts_with(b"PK\x03\x04") {
+        return Some(DocumentFormat::Docx);
+    }
+    let text = std::str::from_utf8(bytes).ok()?;
+    let head = text.trim_start_matches('\u{feff}').trim_start().chars().take(512).collect::<String>().to_lowercase();
+    let extension = file_name.and_then(|name| name.rsplit_once('.')).map(|(_, ext)| ext.to_lowercase());
+    let html = matches!(extension.as_deref(), Some("html" | "htm"))
+        || head.starts_with("<!doctype html")
+        || head.starts_with("<html")
+        || head.contains("<body");
+    Some(if html { DocumentFormat::Html } else { DocumentFormat::Text })
+}
+
+/// The text of a file named `file_name`
+pub fn ingest(bytes: &[u8], file_name: Option<&str>) -> Result<IngestedDocument, IngestError> {
+    let document = match detect(bytes, file_name).ok_or(IngestError::UnsupportedFormat)? {
+        DocumentFormat::Pdf => pdf(bytes)?,
+        DocumentFormat::Docx => docx(bytes)?,
+        DocumentFormat::Html => html(&String::from_utf8_lossy(bytes)),
+        DocumentFormat::Text => text(&String::from_utf8_lossy(bytes)),
+    };
+    if document.text.trim().is_empty() {
+        return Err(IngestError::NoText);
+    }
+    Ok(document)
+}
+
+/// `raw` split into lines with whitespace collapsed, blank lines dropped
+fn lines(raw: &str) -> Vec<String> {
+    raw.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn pdf(bytes: &[u8]) -> Result<IngestedDocument, IngestError> {
+    let malformed = |e: lopdf::Error| IngestError::Malformed { format: DocumentFormat::Pdf, error: e.to_string() };
+    let document = lopdf::Document::load_mem(bytes).map_err(malformed)?;
+    if document.is_encrypted() {
+        return Err(IngestError::Encrypted);
+    }
+    let pages = document
+        .get_pages()
+        .into_keys()
+        .map(|page| document.extract_text(&[page]).map(|text| lines(&text)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(malformed)?;
+    Ok(IngestedDocument::new(DocumentFormat::Pdf, SegmentKind::Page, pages))
+}
+
+fn docx(bytes: &[u8]) -> Result<IngestedDocument, IngestError> {
+    use quick_xml::events::Event;
+
+    let malformed = |error: String| IngestError::Malformed { format: DocumentFormat::Docx, error };
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| malformed(e.to_string()))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| malformed(e.to_string()))?
+        .read_to_string(&mut xml)
+        .map_err(|e| malformed(e.to_string()))?;
+
+    let mut reader = quick_xml::Reader::from_str(&xml);
+    let mut paragraphs = Vec::new();
+    let mut paragraph = String::new();
+    let mut in_text = false;
+    loop {
+        match reader.read_event().map_err(|e| malformed(e.to_string()))? {
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_text = true,
+            Event::End(e) if e.local_name().as_ref() == b"t" => in_text = false,
+            Event::Text(e) if in_text => paragraph.push_str(&e.unescape().map_err(|e| malformed(e.to_string()))?),
+            Event::Empty(e) if matches!(e.local_name().as_ref(), b"tab" | b"br" | b"cr") => paragraph.push(' '),
+            Event::End(e) if e.local_name().as_ref() == b"p" => paragraphs.push(lines(&std::mem::take(&mut paragraph))),
+            Event::Empty(e) if e.local_name().as_ref() == b"p" => paragraphs.push(Vec::new()),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(IngestedDocument::new(DocumentFormat::Docx, SegmentKind::Paragraph, paragraphs))
+}
+
+fn html(source: &str) -> IngestedDocument {
+    let hidden = Regex::new(r"(?is)<!--.*?-->|<(?:script|style|head|template|noscript)\b.*?</(?:script|style|head|template|noscript)\s*>").unwrap();
+    let block = Regex::new(
+        r"(?i)</?(?:p|div|br|li|ul|ol|h[1-6]|tr|table|section|article|header|footer|blockquote|pre|dt|dd|title)\b[^>]*>",
+    )
+    .unwrap();
+    let tag = Regex::new(r"(?s)<[^>]*>").unwrap();
+    let whitespace = Regex::new(r"\s+").unwrap();
+    // Line breaks in the source are only spaces; block elements break lines
+    let visible = hidden.replace_all(source, " ");
+    let collapsed = whitespace.replace_all(&visible, " ");
+    let broken = block.replace_all(&collapsed, "\n");
+    let stripped = tag.replace_all(&broken, "");
+    let paragraphs = lines(&decode_entities(&stripped)).into_iter().map(|line| vec![line]).collect();
+    IngestedDocument::new(DocumentFormat::Html, SegmentKind::Paragraph, paragraphs)
+}
+
+/// `text` with named and numeric character references replaced
+fn decode_entities(text: &str) -> String {
+    let entity = Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[a-zA-Z]+);").unwrap();
+    entity
+        .replace_all(text, |cap: &regex::Captures| {
+            let name = &cap[1];
+            let decoded = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "sect" => Some('§'),
+                "para" => Some('¶'),
+                "ndash" => Some('–'),
+                "mdash" => Some('—'),
+                "lsquo" | "rsquo" => Some('\''),
+                "ldquo" | "rdquo" => Some('"'),
+                "copy" => Some('©'),
+                "reg" => Some('®'),
+                "euro" => Some('€'),
+                "pound" => Some('£'),
+                _ => {
+                    let number = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => name.strip_prefix('#').and_then(|digits| digits.parse().ok()),
+                    };
+                    number.and_then(char::from_u32)
+                }
+            };
+            decoded.map_or_else(|| cap[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+/// Plain text keeps its indentation, which clause segmentation reads
+fn text(source: &str) -> IngestedDocument {
+    let paragraphs = source
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| vec![line])
+        .collect();
+    IngestedDocument::new(DocumentFormat::Text, SegmentKind::Paragraph, paragraphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_html_and_text_keep_paragraph_offsets() {
+        let page = "<!DOCTYPE html><html><head><title>MSA</title><style>p { color: red }</style></head>\
+            <body><h1>Supply Agreement</h1><p>1. Acme Supplies LLC shall deliver\n   the goods.</p>\
+            <script>alert('x')</script><p>2. Fees &amp; costs: &sect;&#160;4 &#x2014; due &lt;30&gt; days.</p></body></html>";
+        let document = ingest(page.as_bytes(), Some("msa.html")).unwrap();
+        assert_eq!(document.format, DocumentFormat::Html);
+        assert_eq!(
+            document.text,
+            "Supply Agreement\n1. Acme Supplies LLC shall deliver the goods.\n2. Fees & costs: § 4 — due <30> days."
+        );
+        let second = &document.segments[1];
+        assert_eq!((second.number, second.start), (2, 17));
+        assert_eq!(document.segment_at(20), Some(second));
+
+        let text = ingest(b"\xef\xbb\xbf1. Terms\r\n\r\n   (a) Acme shall pay.\r\n", Some("msa.txt")).unwrap();
+        assert_eq!((text.format, text.text.as_str()), (DocumentFormat::Text, "1. Terms\n   (a) Acme shall pay."));
+        assert_eq!(ingest(&[0xff, 0xfe, 0x00], None), Err(IngestError::UnsupportedFormat));
+        assert_eq!(ingest(b"<html><body> </body></html>", None), Err(IngestError::NoText));
+    }
+
+    #[test]
+    fn test_docx_paragraphs_are_extracted() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+            <w:p><w:r><w:t>1. Acme Supplies LLC</w:t></w:r><w:r><w:t xml:space="preserve"> shall pay &amp; deliver.</w:t></w:r></w:p>
+            <w:p/>
+            <w:p><w:r><w:t>2. Governed by</w:t><w:tab/><w:t>Delaware law.</w:t></w:r></w:p>
+            </w:body></w:document>"#;
+        let mut buffer = Cursor::new(Vec::new());
+        let mut writer = zip::ZipWriter::new(&mut buffer);
+        writer.start_file("word/document.xml", zip::write::SimpleFileOptions::default()).unwrap();
+        writer.write_all(xml.as_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        let document = ingest(buffer.get_ref(), Some("msa.docx")).unwrap();
+        assert_eq!(document.text, "1. Acme Supplies LLC shall pay & deliver.\n2. Governed by Delaware law.");
+        let numbers: Vec<usize> = document.segments.iter().map(|s| s.number).collect();
+        assert_eq!(numbers, vec![1, 3]);
+        assert!(matches!(ingest(b"PK\x03\x04garbage", None), Err(IngestError::Malformed { format: DocumentFormat::Docx, .. })));
+    }
+
+    #[test]
+    fn test_pdf_pages_are_extracted() {
+        use lopdf::content::{Content, Operation};
+        use lopdf::{dictionary, Document, Object, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Courier" });
+        let resources_id = doc.add_object(dictionary! { "Font" => dictionary! { "F1" => font_id } });
+        let kids: Vec<Object> = ["1. Acme Supplies LLC shall deliver the goods.", "2. Beta Retail Inc shall pay."]
+            .iter()
+            .map(|line| {
+                let content = Content {
+                    operations: vec![
+                        Operation::new("BT", vec![]),
+                        Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                        Operation::new("Tj", vec![Object::string_literal(*line)]),
+                        Operation::new("ET", vec![]),
+                    ],
+                };
+                let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+                doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id, "Contents" => content_id }).into()
+            })
+            .collect();
+        let pages = dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => 2, "Resources" => resources_id };
+        doc.objects.insert(pages_id, Object::Dictionary(pages));
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+
+        let document = ingest(&bytes, None).unwrap();
+        assert_eq!(document.format, DocumentFormat::Pdf);
+        assert_eq!(document.text, "1. Acme Supplies LLC shall deliver the goods.\n2. Beta Retail Inc shall pay.");
+        let pages: Vec<(SegmentKind, usize, usize)> = document.segments.iter().map(|s| (s.kind, s.number, s.start)).collect();
+        assert_eq!(pages, vec![(SegmentKind::Page, 1, 0), (SegmentKind::Page, 2, 46)]);
+    }
+}
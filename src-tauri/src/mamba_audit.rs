@@ -0,0 +1,187 @@
+//! Mamba-2 inference transcripts
+//! AxiomHive Sovereign Manifold v2.1.0
+//! `DeterministicMambaCore::forward_sealed` returns an `InferenceTranscript`
+//! next to the output: which model build ran (its shape, floating-point
+//! mode, quantization and a checksum of every weight), which prompt it
+//! read, and a digest of the state the prompt left in every layer. Two
+//! runs agree on the transcript exactly when they ran the same build on
+//! the same prompt and reached the same state.
+//!
+//! `certify` runs the sealed pass once per RiskCalculator iteration and
+//! has the calculator score the transcripts. An insurance token issued
+//! for the result commits to the transcript hashes, and through them to
+//! the model hash, so it vouches for that build and no other.
+
+use crate::mamba_block::{BlockState, MambaConfig};
+use crate::mamba_core::DeterministicMambaCore;
+use crate::mamba_fp::FpMode;
+use crate::mamba_quant::Quantization;
+use axiom_risk_calculator::{RiskCalculator, RiskResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Digests of the state one layer carries after the prompt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayerStateDigest {
+    pub layer: usize,
+    /// Hex SHA-256 of the SSM hidden state's bits
+    pub ssm: String,
+    /// Hex SHA-256 of the convolution window's bits
+    pub conv: String,
+}
+
+/// What ran, on what, and where it ended up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InferenceTranscript {
+    /// Hex SHA-256 over the fields below that describe the build: config,
+    /// vocabulary size, floating-point mode, quantization and weights
+    pub model_hash: String,
+    /// `DeterministicMambaCore::weight_checksum`
+    pub weight_checksum: String,
+    pub config: MambaConfig,
+    pub vocab_size: usize,
+    pub fp_mode: FpMode,
+    pub quantization: Quantization,
+    /// Hex SHA-256 of the prompt text
+    pub prompt_hash: String,
+    pub prompt_tokens: usize,
+    /// Hex SHA-256 of the prompt's token ids, which also pins the tokenizer
+    pub token_hash: String,
+    pub layer_states: Vec<LayerStateDigest>,
+    /// Hex SHA-256 of the final hidden vector's bits
+    pub output_hash: String,
+}
+
+impl InferenceTranscript {
+    /// Transcript of `model` after reading `prompt` as `tokens`, leaving
+    /// `state` and the hidden vector `last`
+    pub fn new(model: &DeterministicMambaCore, prompt: &str, tokens: &[u32], state: &[BlockState], last: &[f32]) -> Self {
+        let config = *model.config();
+        let vocab_size = model.tokenizer().vocab_size();
+        let fp_mode = model.fp_mode();
+        let quantization = model.quantization();
+        let weight_checksum = model.weight_checksum();
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"AxiomHive Mamba model v1");
+        for dim in [config.n_layers, config.d_model, config.d_state, config.expand, config.d_conv, config.dt_rank, vocab_size] {
+            hasher.update((dim as u64).to_be_bytes());
+        }
+        hasher.update([fp_mode as u8, quantization as u8]);
+        hasher.update(weight_checksum.as_bytes());
+        let model_hash = hex(hasher.finalize());
+
+        let layer_states = state
+            .iter()
+            .enumerate()
+            .map(|(layer, state)| LayerStateDigest { layer, ssm: bits_hash(&state.ssm), conv: bits_hash(&state.conv) })
+            .collect();
+        Self {
+            model_hash,
+            weight_checksum,
+            config,
+            vocab_size,
+            fp_mode,
+            quantization,
+            prompt_hash: hex(Sha256::digest(prompt.as_bytes())),
+            prompt_tokens: tokens.len(),
+            token_hash: hex(Sha256::digest(tokens.iter().flat_map(|id| id.to_be_bytes()).collect::<Vec<u8>>())),
+            layer_states,
+            output_hash: bits_hash(last),
+        }
+    }
+
+    /// Serialized form the RiskCalculator hashes; field order is fixed, so
+    /// equal transcripts serialize to equal bytes
+    pub fn canonical_json(&self) -> String {
+        serde_json::to_string(self).expect("transcripts serialize")
+    }
+}
+
+/// Output of `DeterministicMambaCore::forward_sealed`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedInference {
+    /// What `forward` returns for the prompt
+    pub output: String,
+    pub transcript: InferenceTranscript,
+}
+
+/// A sealed inference scored by the RiskCalculator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InferenceCertificate {
+    pub inference: SealedInference,
+    /// Score of the transcripts of every iteration
+    pub risk: RiskResult,
+    /// Issued only for an insurable result
+    pub insurance_token: Option<String>,
+}
+
+/// Run `prompt` through `model` once per calculator iteration, score the
+/// transcripts with `calculator` and issue a token if they all agree
+pub fn certify(model: &DeterministicMambaCore, prompt: &str, calculator: &RiskCalculator) -> InferenceCertificate {
+    let runs: Vec<SealedInference> = (0..calculator.iteration_count().max(1)).map(|_| model.forward_sealed(prompt)).collect();
+    let transcripts: Vec<String> = runs.iter().map(|run| run.transcript.canonical_json()).collect();
+    let risk = calculator.verify_outputs(&transcripts);
+    let insurance_token = calculator.issue_insurance_token(&risk);
+    let inference = runs.into_iter().next().expect("at least one run");
+    InferenceCertificate { inference, risk, insurance_token }
+}
+
+fn bits_hash(values: &[f32]) -> String {
+    let mut hasher = Sha256::new();
+    for value in values {
+        hasher.update(value.to_bits().to_be_bytes());
+    }
+    hex(hasher.finalize())
+}
+
+fn hex(digest: impl AsRef<[u8]>) -> String {
+    digest.as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_pins_the_model_build() {
+        let model = DeterministicMambaCore::new(8, 4, 2);
+        let sealed = model.forward_sealed("Party A shall indemnify Party B.");
+        assert_eq!(sealed.output, model.forward("Party A shall indemnify Party B.", 0.0));
+        assert_eq!(sealed, model.forward_sealed("Party A shall indemnify Party B."));
+        let transcript = &sealed.transcript;
+        assert_eq!(transcript.layer_states.len(), 1);
+        assert_eq!(transcript.prompt_tokens, model.tokenize("Party A shall indemnify Party B.").len());
+
+        // Another prompt changes the state, not the build
+        let other = model.forward_sealed("Party B shall indemnify Party A.").transcript;
+        assert_eq!(other.model_hash, transcript.model_hash);
+        assert_ne!(other.layer_states, transcript.layer_states);
+        // Quantizing or a strict fp mode is another build
+        let mut quantized = DeterministicMambaCore::new(8, 4, 2);
+        quantized.quantize(Quantization::Int8);
+        let quantized = quantized.forward_sealed("Party A shall indemnify Party B.").transcript;
+        assert_ne!(quantized.weight_checksum, transcript.weight_checksum);
+        assert_ne!(quantized.model_hash, transcript.model_hash);
+        let strict = DeterministicMambaCore::new(8, 4, 2).with_fp_mode(FpMode::Strict);
+        let strict = strict.forward_sealed("Party A shall indemnify Party B.").transcript;
+        assert_eq!(strict.weight_checksum, transcript.weight_checksum);
+        assert_ne!(strict.model_hash, transcript.model_hash);
+    }
+
+    #[test]
+    fn test_certificate_token_is_bound_to_the_build() {
+        let calculator = RiskCalculator::new();
+        let model = DeterministicMambaCore::new(8, 4, 2);
+        let certificate = certify(&model, "indemnify", &calculator);
+        assert_eq!(certificate.risk.risk_score, 0);
+        assert_eq!(certificate.risk.iteration_count, calculator.iteration_count());
+        let token = certificate.insurance_token.clone().expect("insurable");
+        assert_eq!(certify(&model, "indemnify", &calculator).insurance_token, Some(token.clone()));
+
+        let strict = DeterministicMambaCore::new(8, 4, 2).with_fp_mode(FpMode::Strict);
+        let other_build = certify(&strict, "indemnify", &calculator).insurance_token.expect("insurable");
+        assert_ne!(other_build, token);
+        assert_eq!(certificate.risk.hashes[0], calculator.hasher().hash_hex(certificate.inference.transcript.canonical_json().as_bytes()));
+    }
+}
@@ -0,0 +1,379 @@
+//! Mamba-2 residual blocks
+//! AxiomHive Sovereign Manifold v2.1.0
+//! One layer of the stack, as in the reference checkpoints:
+//!   h = RMSNorm(x)
+//!   [u | z] = in_proj h                     (2 * d_inner)
+//!   u = SiLU(causal depthwise conv(u))      (d_conv taps)
+//!   y = SSM(u) * SiLU(z)                    (the gate)
+//!   x' = x + out_proj y                     (the residual)
+//! with d_inner = expand * d_model. Over a sequence the block runs layer
+//! by layer, so the SSM can use the chunked scan; one token at a time it
+//! steps the recurrence. The convolution is always evaluated the same way.
+
+use crate::mamba_core::hashed_uniform;
+use crate::mamba_fp::FpMode;
+use crate::mamba_quant::{Matrix, Quantization};
+use crate::mamba_ssm::{SsmLayer, SsmWeights, SSD_CHUNK_LEN};
+use crate::mamba_weights::{layer_tensor, SafeTensors, TensorSpec};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// RMSNorm epsilon of the reference models
+pub const NORM_EPS: f32 = 1e-5;
+
+/// Shape of a Mamba model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MambaConfig {
+    pub n_layers: usize,
+    pub d_model: usize,
+    pub d_state: usize,
+    /// d_inner = expand * d_model
+    pub expand: usize,
+    /// Taps of the causal convolution
+    pub d_conv: usize,
+    pub dt_rank: usize,
+}
+
+impl MambaConfig {
+    /// The reference defaults: expand 2, 4 convolution taps and
+    /// dt_rank = ceil(d_model / 16)
+    pub fn new(n_layers: usize, d_model: usize, d_state: usize) -> Self {
+        Self {
+            n_layers,
+            d_model,
+            d_state,
+            expand: 2,
+            d_conv: 4,
+            dt_rank: d_model.div_ceil(16),
+        }
+    }
+
+    pub fn d_inner(&self) -> usize {
+        self.expand * self.d_model
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let dims = [
+            ("n_layers", self.n_layers),
+            ("d_model", self.d_model),
+            ("d_state", self.d_state),
+            ("expand", self.expand),
+            ("d_conv", self.d_conv),
+            ("dt_rank", self.dt_rank),
+        ];
+        match dims.iter().find(|(_, value)| *value == 0) {
+            Some((name, _)) => Err(format!("Mamba config needs a non-zero {}", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Recurrent state of one block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockState {
+    /// SSM hidden state, (d_inner, d_state)
+    pub ssm: Vec<f32>,
+    /// The last d_conv - 1 convolution inputs, (d_inner, d_conv - 1),
+    /// oldest first
+    pub conv: Vec<f32>,
+}
+
+impl BlockState {
+    /// The state of a block of `config` before any input
+    pub fn zeros(config: &MambaConfig) -> Self {
+        Self {
+            ssm: vec![0.0; config.d_inner() * config.d_state],
+            conv: vec![0.0; config.d_inner() * (config.d_conv - 1)],
+        }
+    }
+
+    /// Whether `other` has the same shape
+    pub fn same_shape(&self, other: &BlockState) -> bool {
+        self.ssm.len() == other.ssm.len() && self.conv.len() == other.conv.len()
+    }
+}
+
+/// Trained weights of one block, in checkpoint layouts
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BlockWeights {
+    pub norm: Vec<f32>,
+    pub in_proj: Vec<f32>,
+    pub conv_weight: Vec<f32>,
+    pub conv_bias: Vec<f32>,
+    pub ssm: SsmWeights,
+    pub out_proj: Vec<f32>,
+}
+
+/// One norm, SSM, gate and residual layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct MambaBlock {
+    config: MambaConfig,
+    /// (d_model)
+    norm: Vec<f32>,
+    /// (2 * d_inner, d_model)
+    in_proj: Matrix,
+    /// (d_inner, d_conv)
+    conv_weight: Vec<f32>,
+    /// (d_inner)
+    conv_bias: Vec<f32>,
+    ssm: SsmLayer,
+    /// (d_model, d_inner)
+    out_proj: Matrix,
+    fp_mode: FpMode,
+}
+
+impl MambaBlock {
+    /// Deterministic initialization of layer `layer`: unit norm, hashed
+    /// projections (the output scaled down by sqrt(n_layers) so the
+    /// residual stream stays bounded) and a HiPPO SSM
+    pub fn deterministic(config: &MambaConfig, layer: usize) -> Self {
+        let (d_model, d_inner, d_conv) = (config.d_model, config.d_inner(), config.d_conv);
+        let label = |name: &str| format!("mamba_{}_{}", name, layer).into_bytes();
+        let out_scale = 1.0 / ((d_inner * config.n_layers.max(1)) as f32).sqrt();
+        Self {
+            config: *config,
+            norm: vec![1.0; d_model],
+            in_proj: Matrix::new(2 * d_inner, d_model, hashed_uniform(&label("in_proj"), 2 * d_inner * d_model, 1.0 / (d_model as f32).sqrt())),
+            conv_weight: hashed_uniform(&label("conv"), d_inner * d_conv, 1.0 / (d_conv as f32).sqrt()),
+            conv_bias: vec![0.0; d_inner],
+            ssm: SsmLayer::hippo(d_inner, config.d_state, config.dt_rank),
+            out_proj: Matrix::new(d_model, d_inner, hashed_uniform(&label("out_proj"), d_model * d_inner, out_scale)),
+            fp_mode: FpMode::Native,
+        }
+    }
+
+    /// Use trained weights, checking every size
+    pub fn from_weights(config: &MambaConfig, weights: BlockWeights) -> Result<Self, String> {
+        let (d_model, d_inner) = (config.d_model, config.d_inner());
+        let expected = [
+            ("norm", weights.norm.len(), d_model),
+            ("in_proj", weights.in_proj.len(), 2 * d_inner * d_model),
+            ("conv1d weight", weights.conv_weight.len(), d_inner * config.d_conv),
+            ("conv1d bias", weights.conv_bias.len(), d_inner),
+            ("out_proj", weights.out_proj.len(), d_model * d_inner),
+        ];
+        if let Some((name, got, want)) = expected.iter().find(|(_, got, want)| got != want) {
+            return Err(format!("Block weight {} has {} values, expected {}", name, got, want));
+        }
+        Ok(Self {
+            config: *config,
+            norm: weights.norm,
+            in_proj: Matrix::new(2 * d_inner, d_model, weights.in_proj),
+            conv_weight: weights.conv_weight,
+            conv_bias: weights.conv_bias,
+            ssm: SsmLayer::from_weights(d_inner, config.d_state, config.dt_rank, weights.ssm)?,
+            out_proj: Matrix::new(d_model, d_inner, weights.out_proj),
+            fp_mode: FpMode::Native,
+        })
+    }
+
+    /// Tensors of layer `layer` in a checkpoint, with their shapes
+    pub fn manifest(config: &MambaConfig, layer: usize) -> Vec<TensorSpec> {
+        let (d_model, d_inner, n, r) = (config.d_model, config.d_inner(), config.d_state, config.dt_rank);
+        let spec = |name: &str, shape: &[usize]| TensorSpec::new(layer_tensor(layer, name), shape);
+        vec![
+            spec("norm.weight", &[d_model]),
+            spec("mixer.in_proj.weight", &[2 * d_inner, d_model]),
+            spec("mixer.conv1d.weight", &[d_inner, 1, config.d_conv]),
+            spec("mixer.conv1d.bias", &[d_inner]),
+            spec("mixer.A_log", &[d_inner, n]),
+            spec("mixer.x_proj.weight", &[r + 2 * n, d_inner]),
+            spec("mixer.dt_proj.weight", &[d_inner, r]),
+            spec("mixer.dt_proj.bias", &[d_inner]),
+            spec("mixer.D", &[d_inner]),
+            spec("mixer.out_proj.weight", &[d_model, d_inner]),
+        ]
+    }
+
+    /// Read layer `layer` of a checkpoint, storing its matrices as
+    /// `quantization`
+    pub fn load(file: &SafeTensors, config: &MambaConfig, layer: usize, quantization: Quantization) -> Result<Self, String> {
+        let manifest = Self::manifest(config, layer);
+        let mut tensors = manifest.iter().map(|spec| file.read_spec(spec));
+        let mut next = || tensors.next().expect("one tensor per spec");
+        let weights = BlockWeights {
+            norm: next()?,
+            in_proj: next()?,
+            conv_weight: next()?,
+            conv_bias: next()?,
+            ssm: SsmWeights {
+                a_log: next()?,
+                x_proj: next()?,
+                dt_proj: next()?,
+                dt_bias: next()?,
+                d: next()?,
+            },
+            out_proj: next()?,
+        };
+        Ok(Self::from_weights(config, weights)?.quantized(quantization))
+    }
+
+    /// Store the projections as `quantization`
+    pub fn quantized(self, quantization: Quantization) -> Self {
+        Self {
+            in_proj: self.in_proj.quantized(quantization),
+            ssm: self.ssm.quantized(quantization),
+            out_proj: self.out_proj.quantized(quantization),
+            ..self
+        }
+    }
+
+    /// Evaluate the block, its SSM included, in `fp_mode`
+    pub fn with_fp_mode(self, fp_mode: FpMode) -> Self {
+        Self { ssm: self.ssm.with_fp_mode(fp_mode), fp_mode, ..self }
+    }
+
+    pub fn fp_mode(&self) -> FpMode {
+        self.fp_mode
+    }
+
+    pub fn ssm(&self) -> &SsmLayer {
+        &self.ssm
+    }
+
+    /// Weights as they are evaluated, quantized ones decoded, in the order
+    /// of `manifest`
+    pub fn tensors(&self) -> Vec<Cow<'_, [f32]>> {
+        let mut tensors = vec![
+            Cow::Borrowed(self.norm.as_slice()),
+            self.in_proj.to_f32(),
+            Cow::Borrowed(&self.conv_weight),
+            Cow::Borrowed(&self.conv_bias),
+        ];
+        tensors.extend(self.ssm.tensors());
+        tensors.push(self.out_proj.to_f32());
+        tensors
+    }
+
+    /// Bytes held by the weights
+    pub fn size_bytes(&self) -> usize {
+        self.in_proj.size_bytes()
+            + self.out_proj.size_bytes()
+            + self.ssm.size_bytes()
+            + 4 * (self.norm.len() + self.conv_weight.len() + self.conv_bias.len())
+    }
+
+    pub fn zero_state(&self) -> BlockState {
+        BlockState::zeros(&self.config)
+    }
+
+    /// Whether `state` has this block's shape
+    pub fn fits(&self, state: &BlockState) -> bool {
+        self.zero_state().same_shape(state)
+    }
+
+    /// Run a whole sequence through the block, scanning the SSM in chunks
+    pub fn forward(&self, xs: &[Vec<f32>], state: &mut BlockState) -> Vec<Vec<f32>> {
+        let (us, zs): (Vec<_>, Vec<_>) = xs.iter().map(|x| self.mix_in(x, &mut state.conv)).unzip();
+        let ys = self.ssm.scan_chunked(&us, &mut state.ssm, SSD_CHUNK_LEN);
+        xs.iter().zip(ys).zip(&zs).map(|((x, y), z)| self.mix_out(x, y, z)).collect()
+    }
+
+    /// One token through the block
+    pub fn step(&self, x: &[f32], state: &mut BlockState) -> Vec<f32> {
+        let (u, z) = self.mix_in(x, &mut state.conv);
+        let y = self.ssm.step(&u, &mut state.ssm);
+        self.mix_out(x, y, &z)
+    }
+
+    /// Norm, input projection and convolution: the SSM input u and the
+    /// gate z
+    fn mix_in(&self, x: &[f32], conv: &mut [f32]) -> (Vec<f32>, Vec<f32>) {
+        let d_inner = self.config.d_inner();
+        let taps = self.config.d_conv;
+        let mode = self.fp_mode;
+        let mut uz = self.in_proj.matvec_with(&rms_norm(x, &self.norm, mode), mode);
+        let z = uz.split_off(d_inner);
+        let u = uz
+            .iter()
+            .enumerate()
+            .map(|(i, &input)| {
+                let history = &mut conv[i * (taps - 1)..(i + 1) * (taps - 1)];
+                let weights = &self.conv_weight[i * taps..(i + 1) * taps];
+                let out = history
+                    .iter()
+                    .chain(std::iter::once(&input))
+                    .zip(weights)
+                    .fold(self.conv_bias[i], |acc, (&v, &w)| acc + w * v);
+                if let Some(oldest) = history.first_mut() {
+                    *oldest = input;
+                    history.rotate_left(1);
+                }
+                silu(mode, out)
+            })
+            .collect();
+        (u, z)
+    }
+
+    /// Gate, output projection and residual
+    fn mix_out(&self, x: &[f32], y: Vec<f32>, z: &[f32]) -> Vec<f32> {
+        let mode = self.fp_mode;
+        let gated: Vec<f32> = y.iter().zip(z).map(|(&y, &z)| y * silu(mode, z)).collect();
+        self.out_proj.matvec_with(&gated, mode).iter().zip(x).map(|(&out, &x)| x + out).collect()
+    }
+}
+
+/// x / sqrt(mean(x^2) + eps), scaled per channel by `weight`, with the
+/// sum of squares taken as `mode` takes dot products
+pub fn rms_norm(x: &[f32], weight: &[f32], mode: FpMode) -> Vec<f32> {
+    let mean_square = mode.dot(x, x) / x.len().max(1) as f32;
+    let scale = 1.0 / (mean_square + NORM_EPS).sqrt();
+    x.iter().zip(weight).map(|(&v, &w)| v * scale * w).collect()
+}
+
+fn silu(mode: FpMode, x: f32) -> f32 {
+    x / (1.0 + mode.exp(-x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convolution_is_causal_and_keeps_its_history() {
+        // One channel, identity projections, SSM contributing nothing
+        let config = MambaConfig { n_layers: 1, d_model: 1, d_state: 1, expand: 1, d_conv: 3, dt_rank: 1 };
+        let block = MambaBlock::from_weights(
+            &config,
+            BlockWeights {
+                norm: vec![1.0],
+                in_proj: vec![1.0, 1.0],
+                conv_weight: vec![0.25, 0.5, 1.0],
+                conv_bias: vec![0.0],
+                ssm: SsmWeights { a_log: vec![0.0], x_proj: vec![0.0; 3], dt_proj: vec![0.0], dt_bias: vec![0.0], d: vec![0.0] },
+                out_proj: vec![1.0],
+            },
+        )
+        .unwrap();
+        let mut state = block.zero_state();
+        let mut conv = state.conv.clone();
+        // Normalized single values are +-1: outputs 1, 1 + 0.5, 1 + 0.5 + 0.25
+        let taps: Vec<f32> = (0..3).map(|_| block.mix_in(&[2.0], &mut conv).0[0]).collect();
+        let expected: Vec<f32> = [1.0, 1.5, 1.75].iter().map(|&v| silu(FpMode::Native, v)).collect();
+        assert!(taps.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-4), "{:?}", taps);
+        assert!(conv.iter().all(|v| (v - 1.0).abs() < 1e-5), "{:?}", conv);
+
+        // Stepping and running the sequence agree, and the residual passes x
+        let xs = vec![vec![2.0], vec![-1.0], vec![0.5]];
+        let stepped: Vec<Vec<f32>> = xs.iter().map(|x| block.step(x, &mut state)).collect();
+        let mut fresh = block.zero_state();
+        assert_eq!(block.forward(&xs, &mut fresh), stepped);
+        assert_eq!(fresh, state);
+    }
+
+    #[test]
+    fn test_manifest_matches_reference_names() {
+        let config = MambaConfig::new(2, 32, 8);
+        assert_eq!((config.d_inner(), config.dt_rank), (64, 2));
+        let manifest = MambaBlock::manifest(&config, 1);
+        assert_eq!(manifest[1], TensorSpec::new("backbone.layers.1.mixer.in_proj.weight", &[128, 32]));
+        assert_eq!(manifest[2].shape, vec![64, 1, 4]);
+        assert_eq!(manifest[5].shape, vec![2 + 16, 64]);
+        assert!(MambaConfig { d_conv: 0, ..config }.validate().unwrap_err().contains("d_conv"));
+
+        let block = MambaBlock::deterministic(&config, 1);
+        assert_eq!(block, MambaBlock::deterministic(&config, 1));
+        assert_ne!(block, MambaBlock::deterministic(&config, 0));
+        assert!(block.fits(&block.zero_state()));
+    }
+}
@@ -0,0 +1,221 @@
+//! Mamba-2 decode-time sterilization
+//! AxiomHive Sovereign Manifold v2.1.0
+//! `LogitBiasProcessor` applies an AxiomDeterminist `LogitBias` while the
+//! model decodes, so banned strings such as "TODO" or "unimplemented!()"
+//! are never emitted instead of being caught by the sandbox afterwards.
+//!
+//! A banned string usually spans several tokens, so banning each of its
+//! tokens outright would ban ordinary text as well. Instead the string's
+//! token sequence is matched against the end of the context, and only the
+//! token that would complete it is suppressed. Strings are matched as
+//! they encode on their own and after a space, the two forms a byte-level
+//! BPE gives them; another token path to the same text is not blocked.
+//!
+//! `GrammarProcessor` goes further and keeps the output inside the EBNF
+//! rules of a `GrammarConstraint`: every token that would leave a rule is
+//! masked, and end-of-text is masked until every rule is complete.
+
+use crate::axiom_determinist::constraints::{LogitBias, SterilizationConfig};
+use crate::axiom_determinist::grammar::ConstraintEngine;
+use crate::mamba_sampling::LogitsProcessor;
+use crate::mamba_tokenizer::Tokenizer;
+use std::collections::BTreeMap;
+
+/// Biases at or below this ban a token outright, the convention of
+/// `LogitBias`
+pub const BAN_BIAS: f32 = -100.0;
+
+/// A `LogitBias` resolved against one tokenizer
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogitBiasProcessor {
+    /// Per-token biases in id order
+    biases: Vec<(u32, f32)>,
+    /// Token sequences that may not be completed, longest first
+    banned: Vec<Vec<u32>>,
+}
+
+impl LogitBiasProcessor {
+    /// The biases and banned strings of `bias`, tokenized with `tokenizer`
+    pub fn new(bias: &LogitBias, tokenizer: &dyn Tokenizer) -> Self {
+        Self::with_banned(bias, &bias.banned_strings, tokenizer)
+    }
+
+    /// The config's logit bias plus the forbidden constructs of its
+    /// grammar constraint, if any
+    pub fn from_config(config: &SterilizationConfig, tokenizer: &dyn Tokenizer) -> Self {
+        let mut banned = config.logit_bias.banned_strings.clone();
+        if let Some(grammar) = &config.grammar_constraint {
+            banned.extend(grammar.forbidden_constructs.iter().cloned());
+        }
+        Self::with_banned(&config.logit_bias, &banned, tokenizer)
+    }
+
+    fn with_banned(bias: &LogitBias, banned_strings: &[String], tokenizer: &dyn Tokenizer) -> Self {
+        let biases: BTreeMap<u32, f32> = bias.get_bias_map().iter().map(|(&id, &b)| (id, b)).collect();
+        let mut banned: Vec<Vec<u32>> = banned_strings
+            .iter()
+            .filter(|s| !s.is_empty())
+            .flat_map(|s| [tokenizer.encode(s), tokenizer.encode(&format!(" {}", s))])
+            .collect();
+        banned.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        banned.dedup();
+        Self { biases: biases.into_iter().collect(), banned }
+    }
+
+    pub fn banned_sequences(&self) -> &[Vec<u32>] {
+        &self.banned
+    }
+}
+
+impl LogitsProcessor for LogitBiasProcessor {
+    fn process(&self, context: &[u32], logits: &mut [f32]) {
+        for &(id, bias) in &self.biases {
+            if let Some(logit) = logits.get_mut(id as usize) {
+                *logit = if bias <= BAN_BIAS { f32::NEG_INFINITY } else { *logit + bias };
+            }
+        }
+        for sequence in &self.banned {
+            let (&last, prefix) = sequence.split_last().expect("banned sequences are not empty");
+            if context.ends_with(prefix) {
+                if let Some(logit) = logits.get_mut(last as usize) {
+                    *logit = f32::NEG_INFINITY;
+                }
+            }
+        }
+    }
+}
+
+/// A `ConstraintEngine` applied to the tokens generated after a prompt
+#[derive(Debug, Clone)]
+pub struct GrammarProcessor {
+    engine: ConstraintEngine,
+    /// Tokens of the prompt, which the grammar does not cover
+    prompt_tokens: usize,
+    eos: Option<u32>,
+}
+
+impl GrammarProcessor {
+    /// `engine` over the vocabulary of `tokenizer`, for output after a
+    /// prompt of `prompt_tokens` tokens. A token that is not valid UTF-8
+    /// on its own, such as part of a multi-byte character, is never
+    /// allowed.
+    pub fn new(engine: ConstraintEngine, tokenizer: &dyn Tokenizer, prompt_tokens: usize) -> Self {
+        let eos = tokenizer.eos_token();
+        let vocabulary = (0..tokenizer.vocab_size() as u32)
+            .map(|id| {
+                let text = tokenizer.decode(&[id]);
+                if Some(id) == eos || text.contains('\u{FFFD}') {
+                    String::new()
+                } else {
+                    text
+                }
+            })
+            .collect();
+        Self { engine: engine.with_vocabulary(vocabulary), prompt_tokens, eos }
+    }
+}
+
+impl LogitsProcessor for GrammarProcessor {
+    fn process(&self, context: &[u32], logits: &mut [f32]) {
+        let generated = context.get(self.prompt_tokens..).unwrap_or(&[]);
+        // Output that already left the grammar came from another source;
+        // there is nothing left to steer
+        let Some(state) = self.engine.advance_tokens(&self.engine.start(), generated) else {
+            return;
+        };
+        let mut allowed = vec![false; logits.len()];
+        for id in self.engine.allowed_next_tokens(&state) {
+            if let Some(allowed) = allowed.get_mut(id as usize) {
+                *allowed = true;
+            }
+        }
+        if let Some(eos) = self.eos.filter(|_| self.engine.is_complete(&state)) {
+            if let Some(allowed) = allowed.get_mut(eos as usize) {
+                *allowed = true;
+            }
+        }
+        for (logit, allowed) in logits.iter_mut().zip(allowed) {
+            if !allowed {
+                *logit = f32::NEG_INFINITY;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axiom_determinist::constraints::{EnforcementLevel, GrammarConstraint, GrammarRule};
+    use crate::mamba_core::{DeterministicMambaCore, StopReason};
+    use crate::mamba_tokenizer::ByteBpeTokenizer;
+    use std::sync::Arc;
+
+    /// Pushes decoding towards "abab...", whatever the model prefers
+    struct Alternate;
+
+    impl LogitsProcessor for Alternate {
+        fn process(&self, context: &[u32], logits: &mut [f32]) {
+            let next = if context.last() == Some(&(b'a' as u32)) { b'b' } else { b'a' };
+            logits[next as usize] += 1000.0;
+        }
+    }
+
+    #[test]
+    fn test_only_the_completing_token_is_banned() {
+        let tokenizer = ByteBpeTokenizer::byte_level();
+        let mut bias = LogitBias { token_biases: Default::default(), banned_strings: vec!["TODO".to_string()] };
+        bias.token_biases.insert(b'!' as u32, BAN_BIAS);
+        bias.token_biases.insert(b'?' as u32, 2.0);
+        let processor = LogitBiasProcessor::new(&bias, &tokenizer);
+
+        let process = |context: &str| {
+            let mut logits = vec![0.0; tokenizer.vocab_size()];
+            processor.process(&tokenizer.encode(context), &mut logits);
+            logits
+        };
+        let fresh = process("x = ");
+        assert_eq!(fresh[b'T' as usize], 0.0);
+        assert_eq!(fresh[b'!' as usize], f32::NEG_INFINITY);
+        assert_eq!(fresh[b'?' as usize], 2.0);
+        assert_eq!(process("# TOD")[b'O' as usize], f32::NEG_INFINITY);
+        assert_eq!(process("# TOD")[b'A' as usize], 0.0);
+
+        let config = SterilizationConfig::default();
+        let sterile = LogitBiasProcessor::from_config(&config, &tokenizer);
+        assert!(sterile.banned_sequences().contains(&tokenizer.encode("raise NotImplementedError()")));
+    }
+
+    #[test]
+    fn test_banned_strings_never_reach_the_output() {
+        let forced = DeterministicMambaCore::new(8, 4, 2).with_logits_processor(Arc::new(Alternate));
+        assert_eq!(forced.generate("", 6).text, "ababab");
+
+        let bias = LogitBias { token_biases: Default::default(), banned_strings: vec!["ab".to_string()] };
+        let processor = LogitBiasProcessor::new(&bias, forced.tokenizer());
+        let sterile = DeterministicMambaCore::new(8, 4, 2)
+            .with_logits_processor(Arc::new(Alternate))
+            .with_logits_processor(Arc::new(processor));
+        let result = sterile.generate("", 6);
+        assert_eq!(result.tokens.len(), 6);
+        assert!(!result.text.contains("ab"), "{:?}", result.text);
+        assert_eq!(result, sterile.generate("", 6));
+    }
+
+    #[test]
+    fn test_grammar_masks_every_illegal_continuation() {
+        let mut constraint = GrammarConstraint::for_python();
+        constraint.grammar_rules = vec![GrammarRule {
+            rule_name: "answer".to_string(),
+            ebnf_definition: "answer ::= 'yes' | 'no'".to_string(),
+            enforcement: EnforcementLevel::Fatal,
+        }];
+        let engine = ConstraintEngine::new(&constraint).unwrap();
+        let mamba = DeterministicMambaCore::new(8, 4, 2);
+        let prompt = "Proceed? ";
+        let processor = GrammarProcessor::new(engine, mamba.tokenizer(), mamba.tokenize(prompt).len());
+        let constrained = DeterministicMambaCore::new(8, 4, 2).with_logits_processor(Arc::new(processor));
+        let result = constrained.generate(prompt, 8);
+        assert!(result.text == "yes" || result.text == "no", "{:?}", result.text);
+        assert_eq!(result.stop_reason, StopReason::EndOfText);
+    }
+}
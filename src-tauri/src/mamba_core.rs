@@ -2,40 +2,595 @@
 //! AxiomHive Sovereign Manifold v2.1.0
 //! Zero Entropy Law (C=0) - Deterministic State Space Duality (SSD)
 //! Implements: h'(t) = Ah(t) + Bx(t)
+//! Text is tokenized (see `mamba_tokenizer`) and every token id mapped to a
+//! d_model vector by the embedding matrix before it reaches the stack of
+//! residual blocks (see `mamba_block`), each built around a selective SSM
+//! (see `mamba_ssm`) that scans the whole token sequence.
+//! Generation is greedy by default: logits come from the final norm and
+//! the tied embedding, and the highest-scoring token is always chosen, so
+//! a prompt has exactly one continuation. Seeded sampling (see
+//! `mamba_sampling`) is opt-in and replays exactly from its seed.
+//! `generate_stream` hands out each token as it is decoded and lets the
+//! caller stop early.
 
+use crate::mamba_audit::{InferenceTranscript, SealedInference};
+use crate::mamba_block::{rms_norm, BlockState, MambaBlock, MambaConfig};
+use crate::mamba_fp::FpMode;
+use crate::mamba_lazy::{LayerBudget, LayerRef, LayerStore, LazyLayers, MemoryReport};
+use crate::mamba_quant::{Matrix, Quantization};
+use crate::mamba_sampling::{LogitsProcessor, Sampling, SamplingMetadata};
+use crate::mamba_stability::{LayerStability, StabilityReport};
+use crate::mamba_tokenizer::{ByteBpeTokenizer, Tokenizer};
+use crate::mamba_weights::{SafeTensors, TensorSpec, EMBEDDING, NORM_F};
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
+use std::borrow::Cow;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Token embedding matrix, one row of `d_model` values per token id
+#[derive(Debug, Clone, PartialEq)]
+pub struct Embedding {
+    vocab_size: usize,
+    d_model: usize,
+    weights: Matrix,
+}
+
+impl Embedding {
+    /// Deterministic initialization: every entry uniform in
+    /// [-1/sqrt(d_model), 1/sqrt(d_model)]
+    pub fn deterministic(vocab_size: usize, d_model: usize) -> Self {
+        let scale = 1.0 / (d_model.max(1) as f32).sqrt();
+        Self {
+            vocab_size,
+            d_model,
+            weights: Matrix::new(vocab_size, d_model, hashed_uniform(b"mamba_embedding", vocab_size * d_model, scale)),
+        }
+    }
+
+    /// Use trained weights, `vocab_size * d_model` values in row-major order
+    pub fn from_weights(vocab_size: usize, d_model: usize, weights: Vec<f32>) -> Result<Self, String> {
+        if weights.len() != vocab_size * d_model {
+            return Err(format!(
+                "Embedding needs {} x {} = {} weights, got {}",
+                vocab_size,
+                d_model,
+                vocab_size * d_model,
+                weights.len()
+            ));
+        }
+        Ok(Self { vocab_size, d_model, weights: Matrix::new(vocab_size, d_model, weights) })
+    }
+
+    /// Store the matrix as `quantization`
+    pub fn quantized(self, quantization: Quantization) -> Self {
+        Self { weights: self.weights.quantized(quantization), ..self }
+    }
+
+    pub fn weights(&self) -> &Matrix {
+        &self.weights
+    }
+
+    pub fn vocab_size(&self) -> usize {
+        self.vocab_size
+    }
+
+    pub fn d_model(&self) -> usize {
+        self.d_model
+    }
+
+    /// The vector of token `id`, if it is in the vocabulary
+    pub fn row(&self, id: u32) -> Option<Cow<'_, [f32]>> {
+        self.weights.row(id as usize)
+    }
+}
+
+/// `count` values uniform in [-scale, scale], from SHA-256 of `label` and
+/// a block counter, for deterministic weight initialization
+pub(crate) fn hashed_uniform(label: &[u8], count: usize, scale: f32) -> Vec<f32> {
+    let mut values = Vec::with_capacity(count);
+    for block in 0..count.div_ceil(8) as u64 {
+        let mut hasher = Sha256::new();
+        hasher.update(label);
+        hasher.update(block.to_be_bytes());
+        values.extend(hasher.finalize().chunks_exact(4).map(|word| {
+            let unit = u32::from_be_bytes(word.try_into().expect("4-byte word")) as f32 / u32::MAX as f32;
+            (2.0 * unit - 1.0) * scale
+        }));
+    }
+    values.truncate(count);
+    values
+}
+
+/// Why generation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    MaxTokens,
+    EndOfText,
+    /// The `generate_stream` callback asked to stop
+    Cancelled,
+}
+
+/// One token handed to the `generate_stream` callback
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratedToken {
+    /// Position among the generated tokens, from 0
+    pub index: usize,
+    pub id: u32,
+    /// Text this token completes. Empty while a multi-byte character is
+    /// split across tokens; the token finishing it carries the whole
+    /// character.
+    pub text: String,
+}
+
+/// Output of `DeterministicMambaCore::generate`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerateResult {
+    /// Generated token ids, without the prompt or a final end-of-text
+    pub tokens: Vec<u32>,
+    /// The generated tokens decoded
+    pub text: String,
+    pub stop_reason: StopReason,
+    /// Hex SHA-256 over the model shape, sampling settings, prompt tokens
+    /// and generated tokens; identical runs give identical hashes, so it
+    /// can be fed to the RiskCalculator as one iteration
+    pub transcript_hash: String,
+    /// How tokens were chosen, with the seed and generator parameters of a
+    /// sampled run
+    pub sampling: SamplingMetadata,
+}
+
+/// One prompt's share of `DeterministicMambaCore::forward_batch`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchItem {
+    /// Position of the prompt in the batch
+    pub index: usize,
+    /// What `forward` returns for the prompt
+    pub output: String,
+    pub tokens: usize,
+    /// Largest magnitude in any layer's final SSM state, NaN ignored
+    pub max_state: f32,
+    /// Whether every state value and the final hidden vector are finite
+    pub finite: bool,
+}
+
+/// Output of `DeterministicMambaCore::forward_batch`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchForwardResult {
+    /// One per prompt, in prompt order
+    pub items: Vec<BatchItem>,
+    /// Stability of the model that ran them
+    pub stability: StabilityReport,
+    /// Largest `max_state` of the batch
+    pub max_state: f32,
+    /// Indices of the prompts whose state or output is not finite
+    pub non_finite: Vec<usize>,
+}
 
 /// Deterministic Mamba-2 Core implementing State Space Duality
 pub struct DeterministicMambaCore {
-    d_model: u32,
-    d_state: u32,
-    dt_rank: u32,
-    log_a_real: Vec<Vec<f64>>,
+    config: MambaConfig,
+    layers: LayerStore,
+    /// Final RMSNorm weight, (d_model)
+    norm_f: Vec<f32>,
+    tokenizer: Arc<dyn Tokenizer>,
+    embedding: Embedding,
+    /// Applied in order to the logits of every decoding step
+    logits_processors: Vec<Arc<dyn LogitsProcessor>>,
+    fp_mode: FpMode,
 }
 
 impl DeterministicMambaCore {
-    /// Create new Mamba core with deterministic initialization
+    /// Create new Mamba core with deterministic initialization and the
+    /// byte-level fallback vocabulary: a single block of the given widths
     pub fn new(d_model: u32, d_state: u32, dt_rank: u32) -> Self {
-        // Initialize A matrix deterministically (HiPPO-LegS)
-        // A_j = -(j + 0.5) for diagonal elements
-        let mut log_a_real = Vec::new();
-        for i in 0..d_model {
-            let mut row = Vec::new();
-            for j in 0..d_state {
-                let a_val = -((j as f64) + 1.0 + 0.5);
-                // Log parameterization: log(-a + epsilon) to ensure positive
-                let log_val = (a_val.abs() + 1e-6).ln();
-                row.push(log_val);
+        let config = MambaConfig {
+            dt_rank: dt_rank as usize,
+            ..MambaConfig::new(1, d_model as usize, d_state as usize)
+        };
+        Self::from_config(config).expect("non-zero widths")
+    }
+
+    /// A deterministically initialized stack of `config.n_layers` blocks
+    pub fn from_config(config: MambaConfig) -> Result<Self, String> {
+        config.validate()?;
+        // HiPPO-style A_n = -(n + 1.5) with input-dependent B, C and delta
+        let layers = (0..config.n_layers).map(|layer| MambaBlock::deterministic(&config, layer)).collect();
+
+        let tokenizer = ByteBpeTokenizer::byte_level();
+        let embedding = Embedding::deterministic(tokenizer.vocab_size(), config.d_model);
+        Ok(Self {
+            config,
+            layers: LayerStore::Resident(layers),
+            norm_f: vec![1.0; config.d_model],
+            tokenizer: Arc::new(tokenizer),
+            embedding,
+            logits_processors: Vec::new(),
+            fp_mode: FpMode::Native,
+        })
+    }
+
+    /// Swap in another tokenizer, such as a loaded `ByteBpeTokenizer`. The
+    /// embedding is re-initialized deterministically for its vocabulary.
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.embedding = Embedding::deterministic(tokenizer.vocab_size(), self.config.d_model);
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Run `processor` on the logits of every decoding step, after any
+    /// added before it. `logits` itself stays unprocessed.
+    pub fn with_logits_processor(mut self, processor: Arc<dyn LogitsProcessor>) -> Self {
+        self.logits_processors.push(processor);
+        self
+    }
+
+    /// Evaluate every layer and the LM head in `fp_mode`; weights loaded
+    /// later follow it too
+    pub fn with_fp_mode(mut self, fp_mode: FpMode) -> Self {
+        self.layers = match self.layers {
+            LayerStore::Resident(layers) => {
+                LayerStore::Resident(layers.into_iter().map(|layer| layer.with_fp_mode(fp_mode)).collect())
             }
-            log_a_real.push(row);
+            LayerStore::Lazy(lazy) => LayerStore::Lazy(lazy.with_fp_mode(fp_mode)),
+        };
+        self.fp_mode = fp_mode;
+        self
+    }
+
+    pub fn fp_mode(&self) -> FpMode {
+        self.fp_mode
+    }
+
+    /// Use a trained embedding matrix with d_model columns and a row for
+    /// every token; checkpoints often pad the vocabulary with extra rows
+    pub fn with_embedding(mut self, embedding: Embedding) -> Result<Self, String> {
+        if embedding.vocab_size() < self.tokenizer.vocab_size() || embedding.d_model() != self.config.d_model {
+            return Err(format!(
+                "Embedding is {} x {}, the model needs at least {} x {}",
+                embedding.vocab_size(),
+                embedding.d_model(),
+                self.tokenizer.vocab_size(),
+                self.config.d_model
+            ));
         }
+        self.embedding = embedding;
+        Ok(self)
+    }
 
-        Self {
-            d_model,
-            d_state,
-            dt_rank,
-            log_a_real,
+    /// Tensors `load_weights` reads, with their shapes: the embedding,
+    /// every layer and the final norm. The embedding may have more rows
+    /// than listed, as padding beyond the vocabulary.
+    pub fn manifest(&self) -> Vec<TensorSpec> {
+        let mut manifest = vec![TensorSpec::new(EMBEDDING, &[self.tokenizer.vocab_size(), self.config.d_model])];
+        for layer in 0..self.config.n_layers {
+            manifest.extend(MambaBlock::manifest(&self.config, layer));
         }
+        manifest.push(TensorSpec::new(NORM_F, &[self.config.d_model]));
+        manifest
+    }
+
+    /// Replace the analytic initialization with a safetensors checkpoint.
+    /// Every tensor in `manifest()` must be present with its exact shape
+    /// (F32, F16 or BF16); nothing changes if any check fails.
+    pub fn load_weights(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        self.load_weights_quantized(path, Quantization::F32)
+    }
+
+    /// `load_weights`, storing the large matrices as `quantization`. Each
+    /// is quantized as soon as it is read, so at most one f32 copy of a
+    /// tensor exists at a time.
+    pub fn load_weights_quantized(&mut self, path: impl AsRef<Path>, quantization: Quantization) -> Result<(), String> {
+        self.load_safetensors_quantized(&SafeTensors::load(path)?, quantization)
+    }
+
+    /// `load_weights` from an already parsed file
+    pub fn load_safetensors(&mut self, file: &SafeTensors) -> Result<(), String> {
+        self.load_safetensors_quantized(file, Quantization::F32)
+    }
+
+    /// `load_weights_quantized` from an already parsed file
+    pub fn load_safetensors_quantized(&mut self, file: &SafeTensors, quantization: Quantization) -> Result<(), String> {
+        let (embedding, norm_f) = self.read_resident(file, quantization)?;
+        let layers = (0..self.config.n_layers)
+            .map(|layer| MambaBlock::load(file, &self.config, layer, quantization).map(|block| block.with_fp_mode(self.fp_mode)))
+            .collect::<Result<_, _>>()?;
+
+        self.embedding = embedding;
+        self.layers = LayerStore::Resident(layers);
+        self.norm_f = norm_f;
+        Ok(())
+    }
+
+    /// `load_weights_quantized` for checkpoints larger than the memory to
+    /// spare: the file is memory-mapped and each layer decoded only when a
+    /// pass reaches it, keeping decoded layers within `budget`. Every
+    /// shape is still checked before anything changes; see `mamba_lazy`.
+    pub fn load_weights_lazy(
+        &mut self,
+        path: impl AsRef<Path>,
+        quantization: Quantization,
+        budget: LayerBudget,
+    ) -> Result<(), String> {
+        let file = SafeTensors::map(path)?;
+        let (embedding, norm_f) = self.read_resident(&file, quantization)?;
+        let layers = LazyLayers::new(file, self.config, quantization, self.fp_mode, budget)?;
+
+        self.embedding = embedding;
+        self.layers = LayerStore::Lazy(layers);
+        self.norm_f = norm_f;
+        Ok(())
+    }
+
+    /// The embedding and final norm of a checkpoint
+    fn read_resident(&self, file: &SafeTensors, quantization: Quantization) -> Result<(Embedding, Vec<f32>), String> {
+        let vocab_size = self.tokenizer.vocab_size();
+        let info = file.info(EMBEDDING).ok_or_else(|| format!("Checkpoint has no tensor {}", EMBEDDING))?;
+        let (rows, cols) = match info.shape[..] {
+            [rows, cols] if rows >= vocab_size && cols == self.config.d_model => (rows, cols),
+            _ => {
+                return Err(format!(
+                    "Tensor {} has shape {:?}, expected [{}, {}]",
+                    EMBEDDING, info.shape, vocab_size, self.config.d_model
+                ))
+            }
+        };
+        let embedding = Embedding::from_weights(rows, cols, file.read(EMBEDDING)?)?.quantized(quantization);
+        let norm_f = file.read_spec(&TensorSpec::new(NORM_F, &[self.config.d_model]))?;
+        Ok((embedding, norm_f))
+    }
+
+    /// Re-store the current weights as `quantization`; see `mamba_quant`
+    /// for what stays reproducible
+    pub fn quantize(&mut self, quantization: Quantization) {
+        self.layers = match std::mem::replace(&mut self.layers, LayerStore::Resident(Vec::new())) {
+            LayerStore::Resident(layers) => {
+                LayerStore::Resident(layers.into_iter().map(|layer| layer.quantized(quantization)).collect())
+            }
+            LayerStore::Lazy(lazy) => LayerStore::Lazy(lazy.with_quantization(quantization)),
+        };
+        self.embedding = self.embedding.clone().quantized(quantization);
+    }
+
+    pub fn quantization(&self) -> Quantization {
+        self.embedding.weights.quantization()
+    }
+
+    /// Bytes held by the model weights; of lazily loaded layers, only
+    /// those currently decoded count
+    pub fn weight_bytes(&self) -> usize {
+        let layer_bytes = match &self.layers {
+            LayerStore::Resident(layers) => layers.iter().map(MambaBlock::size_bytes).sum(),
+            LayerStore::Lazy(lazy) => lazy.resident_bytes(),
+        };
+        self.embedding.weights.size_bytes() + layer_bytes + 4 * self.norm_f.len()
+    }
+
+    /// Where weight memory goes: the mapped checkpoint, the always
+    /// resident embedding and norm, and which layers are decoded
+    pub fn memory_report(&self) -> MemoryReport {
+        let fixed_bytes = self.embedding.weights.size_bytes() + 4 * self.norm_f.len();
+        match &self.layers {
+            LayerStore::Resident(layers) => MemoryReport {
+                mapped_bytes: 0,
+                fixed_bytes,
+                resident_layers: (0..layers.len()).collect(),
+                resident_layer_bytes: layers.iter().map(MambaBlock::size_bytes).sum(),
+                n_layers: layers.len(),
+                budget: None,
+                loads: 0,
+                evictions: 0,
+            },
+            LayerStore::Lazy(lazy) => lazy.report(fixed_bytes),
+        }
+    }
+
+    /// Hex SHA-256 over every weight as it is evaluated (embedding, each
+    /// layer in manifest order, final norm), so a quantized model does not
+    /// share the checksum of its source. Lazily loaded layers are decoded
+    /// one at a time to be hashed.
+    pub fn weight_checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(b"AxiomHive Mamba weights v1");
+        let mut hash = |tensor: &[f32]| {
+            hasher.update((tensor.len() as u64).to_be_bytes());
+            for value in tensor {
+                hasher.update(value.to_bits().to_be_bytes());
+            }
+        };
+        hash(&self.embedding.weights.to_f32());
+        for layer in 0..self.config.n_layers {
+            self.layer(layer).expect("layer in range").tensors().iter().for_each(|tensor| hash(tensor));
+        }
+        hash(&self.norm_f);
+        hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn config(&self) -> &MambaConfig {
+        &self.config
+    }
+
+    /// Layer `layer` of the stack, decoding it first if it loads lazily
+    pub fn layer(&self, layer: usize) -> Option<LayerRef<'_>> {
+        self.layers.get(layer)
+    }
+
+    pub fn tokenizer(&self) -> &dyn Tokenizer {
+        self.tokenizer.as_ref()
+    }
+
+    pub fn embedding(&self) -> &Embedding {
+        &self.embedding
+    }
+
+    pub fn tokenize(&self, text: &str) -> Vec<u32> {
+        self.tokenizer.encode(text)
+    }
+
+    /// One d_model vector per token; ids outside the vocabulary are skipped
+    pub fn embed(&self, ids: &[u32]) -> Vec<Vec<f32>> {
+        ids.iter().filter_map(|&id| self.embedding.row(id)).map(Cow::into_owned).collect()
+    }
+
+    /// State of every layer before any input
+    pub fn zero_state(&self) -> Vec<BlockState> {
+        vec![BlockState::zeros(&self.config); self.config.n_layers]
+    }
+
+    /// Run `ids` through the stack from `state`, one layer at a time over
+    /// the whole sequence, and return the last hidden vector, if any
+    pub fn advance(&self, ids: &[u32], state: &mut [BlockState]) -> Option<Vec<f32>> {
+        let mut hidden = self.embed(ids);
+        for (layer, layer_state) in state.iter_mut().enumerate() {
+            hidden = self.layer(layer).expect("one state per layer").forward(&hidden, layer_state);
+        }
+        hidden.pop()
+    }
+
+    /// One token through every layer
+    fn step(&self, id: u32, state: &mut [BlockState]) -> Vec<f32> {
+        let x = self.embedding.row(id).expect("token is within the vocabulary").into_owned();
+        state.iter_mut().enumerate().fold(x, |x, (layer, layer_state)| {
+            self.layer(layer).expect("one state per layer").step(&x, layer_state)
+        })
+    }
+
+    /// Next-token scores for a last hidden vector: the final norm, then a
+    /// dot product with every vocabulary row of the embedding, which
+    /// doubles as the LM head
+    pub fn logits(&self, hidden: &[f32]) -> Vec<f32> {
+        let mut logits = self
+            .embedding
+            .weights
+            .matvec_with(&rms_norm(hidden, &self.norm_f, self.fp_mode), self.fp_mode);
+        // Rows past the vocabulary are checkpoint padding
+        logits.truncate(self.tokenizer.vocab_size());
+        logits
+    }
+
+    /// Greedy decoding of up to `max_tokens` tokens after `prompt`,
+    /// stopping early at end-of-text. There is no temperature to set:
+    /// every step takes the argmax, ties going to the lowest id.
+    pub fn generate(&self, prompt: &str, max_tokens: usize) -> GenerateResult {
+        self.generate_stream(prompt, max_tokens, |_| ControlFlow::Continue(()))
+    }
+
+    /// `generate` with the given token choice; `Sampling::Greedy` is
+    /// `generate` itself
+    pub fn generate_with(&self, prompt: &str, max_tokens: usize, sampling: Sampling) -> GenerateResult {
+        self.generate_stream_with(prompt, max_tokens, sampling, |_| ControlFlow::Continue(()))
+    }
+
+    /// `generate`, calling `on_token` after every token. Returning
+    /// `ControlFlow::Break` stops generation with `StopReason::Cancelled`;
+    /// the tokens produced so far, including the last one, are kept.
+    pub fn generate_stream<F>(&self, prompt: &str, max_tokens: usize, on_token: F) -> GenerateResult
+    where
+        F: FnMut(&GeneratedToken) -> ControlFlow<()>,
+    {
+        self.generate_stream_with(prompt, max_tokens, Sampling::Greedy, on_token)
+    }
+
+    /// `generate_stream` with the given token choice
+    pub fn generate_stream_with<F>(&self, prompt: &str, max_tokens: usize, sampling: Sampling, on_token: F) -> GenerateResult
+    where
+        F: FnMut(&GeneratedToken) -> ControlFlow<()>,
+    {
+        let prompt_tokens = self.tokenize(prompt);
+        let (mut state, mut last) = self.prefill(&prompt_tokens);
+        self.decode_from(&prompt_tokens, &mut state, &mut last, max_tokens, sampling, on_token)
+    }
+
+    /// The decoding loop of `generate_stream`, continuing from the state
+    /// and last hidden vector after `prompt_tokens`. Both are left after
+    /// the last generated token, ready for more input.
+    pub(crate) fn decode_from<F>(
+        &self,
+        prompt_tokens: &[u32],
+        state: &mut [BlockState],
+        last: &mut Vec<f32>,
+        max_tokens: usize,
+        sampling: Sampling,
+        mut on_token: F,
+    ) -> GenerateResult
+    where
+        F: FnMut(&GeneratedToken) -> ControlFlow<()>,
+    {
+        let mut tokens = Vec::new();
+        // What the logits processors see: the prompt, then each new token
+        let mut context = prompt_tokens.to_vec();
+        // Tokens whose text has not been handed out yet
+        let mut pending = Vec::new();
+        // Draws are keyed by absolute position, so they never depend on
+        // how the input was split across calls
+        let first_position = prompt_tokens.len() as u64;
+        let mut draws = 0;
+        let stop_reason = loop {
+            if tokens.len() == max_tokens {
+                break StopReason::MaxTokens;
+            }
+            let mut logits = self.logits(last);
+            for processor in &self.logits_processors {
+                processor.process(&context, &mut logits);
+            }
+            let next = sampling.choose(&logits, first_position + draws);
+            draws += 1;
+            if Some(next) == self.tokenizer.eos_token() {
+                break StopReason::EndOfText;
+            }
+            tokens.push(next);
+            context.push(next);
+            pending.push(next);
+            *last = self.step(next, state);
+
+            let text = take_complete_text(self.tokenizer.as_ref(), &mut pending);
+            let token = GeneratedToken { index: tokens.len() - 1, id: next, text };
+            if on_token(&token).is_break() {
+                break StopReason::Cancelled;
+            }
+        };
+
+        GenerateResult {
+            text: self.tokenizer.decode(&tokens),
+            stop_reason,
+            transcript_hash: self.transcript_hash(&sampling, prompt_tokens, &tokens),
+            sampling: sampling.metadata(first_position, draws),
+            tokens,
+        }
+    }
+
+    /// State and last hidden vector after `tokens`; a zero vector if there
+    /// are none
+    pub(crate) fn prefill(&self, tokens: &[u32]) -> (Vec<BlockState>, Vec<f32>) {
+        let mut state = self.zero_state();
+        let last = self.advance(tokens, &mut state).unwrap_or_else(|| vec![0.0; self.config.d_model]);
+        (state, last)
+    }
+
+    fn transcript_hash(&self, sampling: &Sampling, prompt: &[u32], generated: &[u32]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(b"AxiomHive Mamba transcript v2");
+        let config = &self.config;
+        for dim in [config.n_layers, config.d_model, config.d_state, config.expand, config.d_conv, config.dt_rank, self.tokenizer.vocab_size()] {
+            hasher.update((dim as u64).to_be_bytes());
+        }
+        match *sampling {
+            Sampling::Greedy => hasher.update([0]),
+            Sampling::Seeded { temperature, seed } => {
+                hasher.update([1]);
+                hasher.update(temperature.to_bits().to_be_bytes());
+                hasher.update(seed.to_be_bytes());
+            }
+        }
+        for ids in [prompt, generated] {
+            hasher.update((ids.len() as u64).to_be_bytes());
+            for id in ids {
+                hasher.update(id.to_be_bytes());
+            }
+        }
+        hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
     }
 
     /// Forward pass implementing SSD recurrence
@@ -45,88 +600,418 @@ impl DeterministicMambaCore {
             return format!("Error: Temperature must be 0.0 for Zero Entropy Law. Got: {}", temperature);
         }
 
-        // Deterministic state space computation
-        // Compute A matrix from log parameterization
-        let a_matrix: Vec<Vec<f64>> = self.log_a_real
-            .iter()
-            .map(|row| row.iter().map(|&log_val| -log_val.exp()).collect())
-            .collect();
+        // Tokenize, embed and run every layer over the whole sequence, so
+        // the final states and output reflect every token
+        let tokens = self.tokenize(input);
+        let (state, last) = self.prefill(&tokens);
+        self.render_output(input, tokens.len(), &state, &last)
+    }
 
-        // Process input through state space
-        let mut hasher = Sha256::new();
-        hasher.update(input.as_bytes());
-        hasher.update(&temperature.to_be_bytes());
-        let input_hash = hasher.finalize();
-
-        // Simulate state space evolution: h'(t) = Ah(t) + Bx(t)
-        // For simplicity, we use the hash as the input encoding
-        let mut state = vec![0.0f64; self.d_state as usize];
-        for (i, &byte) in input_hash.iter().enumerate().take(self.d_state as usize) {
-            state[i] = byte as f64 / 255.0;
-        }
-
-        // Apply state transition: h' = A * h (simplified, no Bx for now)
-        let mut next_state = vec![0.0f64; self.d_state as usize];
-        if !a_matrix.is_empty() {
-            let a_row = &a_matrix[0];
-            for i in 0..self.d_state as usize {
-                if i < a_row.len() && i < state.len() {
-                    next_state[i] = a_row[i] * state[i];
-                }
+    /// `forward` at temperature 0 for every prompt, in parallel with the
+    /// `parallel` feature. Each prompt starts from a zero state, so its
+    /// item is the same whatever else is in the batch and however the
+    /// prompts are spread across threads.
+    pub fn forward_batch(&self, prompts: &[&str]) -> BatchForwardResult {
+        let run = |(index, prompt): (usize, &&str)| {
+            let tokens = self.tokenize(prompt);
+            let (state, last) = self.prefill(&tokens);
+            let values = || state.iter().flat_map(|layer| layer.ssm.iter().chain(&layer.conv));
+            BatchItem {
+                index,
+                output: self.render_output(prompt, tokens.len(), &state, &last),
+                tokens: tokens.len(),
+                max_state: state.iter().flat_map(|layer| &layer.ssm).fold(0.0f32, |max, h| max.max(h.abs())),
+                finite: values().chain(&last).all(|v| v.is_finite()),
             }
+        };
+
+        #[cfg(feature = "parallel")]
+        let items: Vec<BatchItem> = {
+            use rayon::prelude::*;
+            prompts.par_iter().enumerate().map(run).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let items: Vec<BatchItem> = prompts.iter().enumerate().map(run).collect();
+
+        BatchForwardResult {
+            stability: self.get_stability_metrics(),
+            max_state: items.iter().fold(0.0f32, |max, item| max.max(item.max_state)),
+            non_finite: items.iter().filter(|item| !item.finite).map(|item| item.index).collect(),
+            items,
+        }
+    }
+
+    /// `forward` at temperature 0, sealed with a transcript of the model
+    /// build and the state the prompt left in every layer; see
+    /// `mamba_audit`
+    pub fn forward_sealed(&self, prompt: &str) -> SealedInference {
+        let tokens = self.tokenize(prompt);
+        let (state, last) = self.prefill(&tokens);
+        SealedInference {
+            output: self.render_output(prompt, tokens.len(), &state, &last),
+            transcript: InferenceTranscript::new(self, prompt, &tokens, &state, &last),
         }
+    }
+
+    fn render_output(&self, input: &str, token_count: usize, state: &[BlockState], last: &[f32]) -> String {
+        let ssm_states: Vec<f32> = state.iter().flat_map(|layer| layer.ssm.iter().copied()).collect();
 
         // Generate output from state
-        let output_hash = self.compute_output_hash(&next_state, input);
+        let output_hash = self.compute_output_hash(&ssm_states, last, input);
         
         format!(
-            "Mamba-2 SSD Output (Deterministic): Processed '{}' with state_dim={}, input_dim={}, temperature={}. Output hash: {}",
+            "Mamba-2 SSD Output (Deterministic): Processed '{}' ({} tokens) with state_dim={}, input_dim={}, temperature=0. Output hash: {}",
             input.chars().take(50).collect::<String>(),
-            self.d_state,
-            self.d_model,
-            temperature,
+            token_count,
+            self.config.d_state,
+            self.config.d_model,
             output_hash
         )
     }
 
-    fn compute_output_hash(&self, state: &[f64], input: &str) -> String {
+    fn compute_output_hash(&self, state: &[f32], output: &[f32], input: &str) -> String {
         let mut hasher = Sha256::new();
         hasher.update(input.as_bytes());
-        for &val in state.iter().take(16) {
-            hasher.update(&val.to_be_bytes());
+        for &val in state.iter().chain(output) {
+            hasher.update(val.to_be_bytes());
         }
         let hash = hasher.finalize();
         format!("{:x}", hash.iter().fold(0u64, |acc, &b| acc.wrapping_mul(256).wrapping_add(b as u64)))
     }
 
-    /// Get stability metrics
-    pub fn get_stability_metrics(&self) -> serde_json::Value {
-        let a_matrix: Vec<Vec<f64>> = self.log_a_real
+    /// Stability of every layer at its zero-input step sizes
+    pub fn get_stability_metrics(&self) -> StabilityReport {
+        self.stability_report(None)
+    }
+
+    /// Stability of every layer, with the discretized transitions taken at
+    /// `delta`, or at each channel's zero-input step when None
+    pub fn stability_report(&self, delta: Option<f32>) -> StabilityReport {
+        let layers = (0..self.config.n_layers)
+            .map(|layer| LayerStability::of(layer, self.layer(layer).expect("layer in range").ssm(), delta))
+            .collect();
+        StabilityReport::new(self.config, delta, layers)
+    }
+}
+
+/// Prompt of the `verify_bit_exactness` reference pass
+const REFERENCE_PROMPT: &str = "AxiomHive reference: Party A shall indemnify Party B. Zero Entropy (C=0).";
+
+/// Hash of the reference pass as computed by a conforming build. A change
+/// to the model's arithmetic must update it, deliberately.
+pub const REFERENCE_FORWARD_HASH: &str = "b4fdfaa625e8b7cdcc78af21a8c82a66de510829de14ee93cc8217bce5d46bc3";
+
+/// Self-test of the Zero Entropy claim for this build: a two-layer
+/// `FpMode::Strict` model reads a fixed prompt and greedily decodes eight
+/// tokens, and the bits of every layer state, the final hidden vector,
+/// the logits and the tokens are hashed. Every x86 or ARM build must
+/// produce `REFERENCE_FORWARD_HASH`; the error quotes the hash it got.
+pub fn verify_bit_exactness() -> Result<String, String> {
+    let mamba = DeterministicMambaCore::from_config(MambaConfig::new(2, 32, 8))?.with_fp_mode(FpMode::Strict);
+    let tokens = mamba.tokenize(REFERENCE_PROMPT);
+    let (state, last) = mamba.prefill(&tokens);
+    let logits = mamba.logits(&last);
+    let generated = mamba.generate(REFERENCE_PROMPT, 8);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"AxiomHive Mamba bit-exactness v1");
+    let values = state
+        .iter()
+        .flat_map(|layer| layer.ssm.iter().chain(&layer.conv))
+        .chain(&last)
+        .chain(&logits);
+    for value in values {
+        hasher.update(value.to_bits().to_be_bytes());
+    }
+    for id in &generated.tokens {
+        hasher.update(id.to_be_bytes());
+    }
+    let hash: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    if hash == REFERENCE_FORWARD_HASH {
+        Ok(hash)
+    } else {
+        Err(format!(
+            "Bit-exactness self-test failed: reference pass hashed to {}, expected {}",
+            hash, REFERENCE_FORWARD_HASH
+        ))
+    }
+}
+
+/// Decoded text of `pending`, clearing it, unless it ends in an incomplete
+/// character: then nothing, until a later token completes it
+fn take_complete_text(tokenizer: &dyn Tokenizer, pending: &mut Vec<u32>) -> String {
+    let text = tokenizer.decode(pending);
+    if text.ends_with(char::REPLACEMENT_CHARACTER) {
+        return String::new();
+    }
+    pending.clear();
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mamba_sampling::argmax;
+
+    #[test]
+    fn test_embedding_rows_are_deterministic_and_bounded() {
+        let embedding = Embedding::deterministic(257, 12);
+        assert_eq!(embedding, Embedding::deterministic(257, 12));
+        assert_eq!(embedding.row(256).unwrap().len(), 12);
+        assert!(embedding.row(257).is_none());
+        let bound = 1.0 / 12f32.sqrt();
+        assert!(embedding.weights.to_f32().iter().all(|w| w.abs() <= bound));
+        assert_ne!(embedding.row(0), embedding.row(1));
+        assert!(Embedding::from_weights(2, 3, vec![0.0; 5]).is_err());
+    }
+
+    #[test]
+    fn test_forward_depends_on_every_token() {
+        let mamba = DeterministicMambaCore::new(16, 8, 4);
+        assert_eq!(mamba.tokenize("abc").len(), 3);
+        assert_eq!(mamba.embed(&mamba.tokenize("abc")).len(), 3);
+        let output = mamba.forward("Zero Entropy", 0.0);
+        assert_eq!(output, mamba.forward("Zero Entropy", 0.0));
+        assert!(output.contains("(12 tokens)"));
+        // The first token still shows in the final state
+        let final_state = |text: &str| mamba.prefill(&mamba.tokenize(text)).0;
+        assert_ne!(final_state("Zero Entropy"), final_state("Xero Entropy"));
+        assert!(mamba.forward("x", 0.5).starts_with("Error"));
+
+        let mismatched = Embedding::deterministic(10, 16);
+        assert!(DeterministicMambaCore::new(16, 8, 4).with_embedding(mismatched).is_err());
+    }
+
+    #[test]
+    fn test_batch_items_match_single_prompts() {
+        let mamba = DeterministicMambaCore::new(16, 8, 4);
+        let prompts = ["Zero Entropy", "", "Party A shall indemnify Party B.", "Zero Entropy"];
+        let batch = mamba.forward_batch(&prompts);
+        assert_eq!(batch.items.len(), 4);
+        for (item, prompt) in batch.items.iter().zip(prompts) {
+            assert_eq!(item.output, mamba.forward(prompt, 0.0));
+        }
+        assert_eq!(batch.items[0].output, batch.items[3].output);
+        assert_eq!(batch.items[1].max_state, 0.0);
+        assert!(batch.non_finite.is_empty());
+        assert_eq!(batch.max_state, batch.items.iter().map(|item| item.max_state).fold(0.0, f32::max));
+        assert_eq!(batch.stability, mamba.get_stability_metrics());
+
+        // Items do not depend on their neighbours
+        let alone = mamba.forward_batch(&prompts[2..3]);
+        assert_eq!(BatchItem { index: 2, ..alone.items[0].clone() }, batch.items[2]);
+    }
+
+    #[test]
+    fn test_load_weights_follows_the_manifest() {
+        use crate::mamba_weights::tests::safetensors_bytes;
+
+        let mut mamba = DeterministicMambaCore::new(4, 2, 1);
+        let manifest = mamba.manifest();
+        assert_eq!(manifest[0].name, "backbone.embedding.weight");
+        assert_eq!(manifest[2].shape, vec![16, 4]);
+        assert_eq!(manifest.last().unwrap().name, NORM_F);
+
+        // Every tensor present, embedding padded by three rows
+        let mut tensors: Vec<(&str, Vec<usize>, Vec<f32>)> = manifest
             .iter()
-            .map(|row| row.iter().map(|&log_val| -log_val.exp()).collect())
+            .map(|spec| (spec.name.as_str(), spec.shape.clone(), vec![0.25; spec.shape.iter().product()]))
             .collect();
+        tensors[0] = (EMBEDDING, vec![260, 4], vec![0.5; 260 * 4]);
+        let path = std::env::temp_dir().join(format!("mamba_weights_{}.safetensors", std::process::id()));
+        std::fs::write(&path, safetensors_bytes(&tensors)).unwrap();
+        let before = mamba.forward("abc", 0.0);
+        mamba.load_weights(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(mamba.embedding().vocab_size(), 260);
+        assert_eq!(mamba.embedding().row(259).as_deref(), Some(&[0.5; 4][..]));
+        assert_eq!(mamba.layer(0).unwrap().ssm().a(), vec![-(0.25f32.exp()); 16]);
+        assert_ne!(mamba.forward("abc", 0.0), before);
 
-        let mut all_negative = true;
-        let mut max_val = f64::NEG_INFINITY;
-        let mut min_val = f64::INFINITY;
-
-        for row in &a_matrix {
-            for &val in row {
-                if val >= 0.0 {
-                    all_negative = false;
-                }
-                max_val = max_val.max(val);
-                min_val = min_val.min(val);
-            }
+        // A wrong shape or a missing tensor leaves the model untouched
+        let loaded = mamba.layer(0).unwrap().clone();
+        tensors[3].1 = vec![8, 4];
+        let file = SafeTensors::parse(safetensors_bytes(&tensors)).unwrap();
+        assert!(mamba.load_safetensors(&file).unwrap_err().contains("shape"));
+        let file = SafeTensors::parse(safetensors_bytes(&tensors[..2])).unwrap();
+        assert!(mamba.load_safetensors(&file).unwrap_err().contains("no tensor"));
+        assert_eq!(*mamba.layer(0).unwrap(), loaded);
+    }
+
+    #[test]
+    fn test_greedy_generation_is_reproducible() {
+        let mamba = DeterministicMambaCore::new(16, 8, 4);
+        let result = mamba.generate("The contract", 6);
+        assert_eq!(result, mamba.generate("The contract", 6));
+        assert_eq!(result.tokens.len(), 6);
+        assert_eq!(result.stop_reason, StopReason::MaxTokens);
+        assert_eq!(result.text, mamba.tokenizer().decode(&result.tokens));
+        assert_eq!(result.transcript_hash.len(), 64);
+
+        // A shorter run is a prefix of a longer one, with its own transcript
+        let shorter = mamba.generate("The contract", 3);
+        assert_eq!(shorter.tokens, result.tokens[..3]);
+        assert_ne!(shorter.transcript_hash, result.transcript_hash);
+        assert_ne!(mamba.generate("The contracts", 6).transcript_hash, result.transcript_hash);
+        assert!(mamba.generate("The contract", 0).tokens.is_empty());
+
+        assert_eq!(argmax(&[1.0, 3.0, 3.0, -1.0]), 1);
+        assert_eq!(argmax(&[f32::NAN, 0.5, f32::NAN]), 1);
+    }
+
+    #[test]
+    fn test_seeded_sampling_replays_from_its_metadata() {
+        let mamba = DeterministicMambaCore::new(16, 8, 4);
+        let greedy = mamba.generate("The contract", 6);
+        assert_eq!(mamba.generate_with("The contract", 6, Sampling::Greedy), greedy);
+        assert_eq!(greedy.sampling.rng, None);
+
+        let sampling = Sampling::new(1.5, Some(2024)).unwrap();
+        let sampled = mamba.generate_with("The contract", 6, sampling);
+        assert_eq!(sampled, mamba.generate_with("The contract", 6, sampling));
+        let rng = sampled.sampling.rng.clone().unwrap();
+        assert_eq!((rng.first_counter, rng.draws), (12, 6));
+        assert_ne!(sampled.transcript_hash, greedy.transcript_hash);
+
+        // The recorded settings alone reproduce the run; other seeds differ
+        let replayed = mamba.generate_with("The contract", 6, sampled.sampling.sampling);
+        assert_eq!(replayed.transcript_hash, sampled.transcript_hash);
+        let others: Vec<Vec<u32>> =
+            (0..4).map(|seed| mamba.generate_with("The contract", 6, Sampling::new(1.5, Some(seed)).unwrap()).tokens).collect();
+        assert!(others.iter().any(|tokens| *tokens != sampled.tokens));
+    }
+
+    #[test]
+    fn test_layers_run_in_order_over_the_residual_stream() {
+        let config = MambaConfig::new(3, 16, 4);
+        let mamba = DeterministicMambaCore::from_config(config).unwrap();
+        assert!(mamba.layer(2).is_some() && mamba.layer(3).is_none());
+        assert_eq!(mamba.manifest().len(), 1 + 3 * 10 + 1);
+        assert!(DeterministicMambaCore::from_config(MambaConfig { n_layers: 0, ..config }).is_err());
+
+        // Stepping one more token continues a prefilled state
+        let ids = mamba.tokenize("Notice period");
+        let (mut state, _) = mamba.prefill(&ids[..ids.len() - 1]);
+        let stepped = mamba.step(ids[ids.len() - 1], &mut state);
+        let (whole, last) = mamba.prefill(&ids);
+        assert!(stepped.iter().zip(&last).all(|(a, b)| (a - b).abs() < 1e-4));
+        for (a, b) in state.iter().zip(&whole) {
+            assert!(a.conv.iter().zip(&b.conv).all(|(a, b)| (a - b).abs() < 1e-4));
         }
 
-        serde_json::json!({
-            "is_stable": all_negative,
-            "max_value": max_val,
-            "min_value": min_val,
-            "d_state": self.d_state,
-            "d_model": self.d_model,
-        })
+        // Depth is part of the transcript
+        let shallow = DeterministicMambaCore::from_config(MambaConfig { n_layers: 2, ..config }).unwrap();
+        assert_ne!(shallow.generate("Notice", 2).transcript_hash, mamba.generate("Notice", 2).transcript_hash);
     }
-}
 
+    #[test]
+    fn test_generation_stops_at_end_of_text() {
+        // Point end-of-text along the hidden vector after "a"
+        let mamba = DeterministicMambaCore::new(4, 2, 1);
+        let eos = mamba.tokenizer().eos_token().unwrap() as usize;
+        let a = mamba.tokenize("a")[0] as usize;
+        let mut weights = vec![0.0; 257 * 4];
+        weights[a * 4..a * 4 + 4].copy_from_slice(&[1.0, -1.0, 0.5, 0.0]);
+        let mamba = mamba.with_embedding(Embedding::from_weights(257, 4, weights.clone()).unwrap()).unwrap();
+        let (_, last) = mamba.prefill(&[a as u32]);
+        for (w, v) in weights[eos * 4..].iter_mut().zip(&last) {
+            *w = 10.0 * v;
+        }
+        let mamba = mamba.with_embedding(Embedding::from_weights(257, 4, weights).unwrap()).unwrap();
+        assert_eq!(argmax(&mamba.logits(&last)), eos as u32);
+        let result = mamba.generate("a", 10);
+        assert!(result.tokens.is_empty());
+        assert_eq!(result.stop_reason, StopReason::EndOfText);
+    }
+
+    #[test]
+    fn test_streamed_tokens_match_and_can_be_cancelled() {
+        let mamba = DeterministicMambaCore::new(16, 8, 4);
+        let mut streamed = Vec::new();
+        let result = mamba.generate_stream("Indemnity", 8, |token| {
+            streamed.push(token.clone());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(result, mamba.generate("Indemnity", 8));
+        assert_eq!(streamed.iter().map(|t| t.id).collect::<Vec<_>>(), result.tokens);
+        assert!(streamed.iter().enumerate().all(|(i, t)| t.index == i));
+
+        // Stop after the third token
+        let cancelled = mamba.generate_stream("Indemnity", 8, |token| {
+            if token.index == 2 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!(cancelled.stop_reason, StopReason::Cancelled);
+        assert_eq!(cancelled.tokens, result.tokens[..3]);
+    }
+
+    #[test]
+    fn test_split_characters_are_streamed_whole() {
+        // "é" is two byte tokens: the first carries no text, the second all of it
+        let mamba = DeterministicMambaCore::new(4, 2, 1);
+        let mut pending = Vec::new();
+        let streamed: Vec<String> = mamba
+            .tokenize("é!")
+            .into_iter()
+            .map(|id| {
+                pending.push(id);
+                take_complete_text(mamba.tokenizer(), &mut pending)
+            })
+            .collect();
+        assert_eq!(streamed, vec!["", "é", "!"]);
+
+        // Streamed text always adds up to the final text
+        let mut text = String::new();
+        let result = mamba.generate_stream("caf", 12, |token| {
+            text.push_str(&token.text);
+            ControlFlow::Continue(())
+        });
+        assert!(result.text.starts_with(&text));
+    }
+
+    #[test]
+    fn test_strict_modes_are_bit_exact_and_close_to_native() {
+        assert_eq!(verify_bit_exactness(), Ok(REFERENCE_FORWARD_HASH.to_string()));
+
+        let prompt = "Termination for convenience";
+        let scores = |mode: FpMode| {
+            let mamba = DeterministicMambaCore::from_config(MambaConfig::new(2, 16, 4)).unwrap().with_fp_mode(mode);
+            let layer = mamba.layer(1).unwrap();
+            assert!(layer.fp_mode() == mode && layer.ssm().fp_mode() == mode);
+            let (_, last) = mamba.prefill(&mamba.tokenize(prompt));
+            mamba.logits(&last)
+        };
+        let native = scores(FpMode::Native);
+        for mode in [FpMode::Strict, FpMode::FixedPoint] {
+            let strict = scores(mode);
+            assert_eq!(strict, scores(mode));
+            assert!(strict.iter().zip(&native).all(|(s, n)| (s - n).abs() < 1e-4), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_quantized_models_track_the_f32_model() {
+        let prompt = "Limitation of liability";
+        let reference = DeterministicMambaCore::new(32, 8, 4);
+        let scores = |mamba: &DeterministicMambaCore| {
+            let (_, last) = mamba.prefill(&mamba.tokenize(prompt));
+            mamba.logits(&last)
+        };
+        let exact = scores(&reference);
+        let norm = exact.iter().map(|v| v * v).sum::<f32>().sqrt();
+
+        // Relative logit error and weight size against f32; int8 pays 4
+        // bytes of scale per 32-wide row here
+        for (quantization, tolerance, max_bytes) in [(Quantization::F16, 1e-3, 0.55), (Quantization::Int8, 1e-2, 0.35)] {
+            let mut mamba = DeterministicMambaCore::new(32, 8, 4);
+            mamba.quantize(quantization);
+            assert_eq!(mamba.quantization(), quantization);
+            assert!((mamba.weight_bytes() as f64) < max_bytes * reference.weight_bytes() as f64);
+
+            let approx = scores(&mamba);
+            let error = approx.iter().zip(&exact).map(|(a, e)| (a - e) * (a - e)).sum::<f32>().sqrt();
+            assert!(error / norm < tolerance, "{:?}: relative error {}", quantization, error / norm);
+            assert_eq!(argmax(&approx), argmax(&exact));
+            assert_eq!(mamba.generate(prompt, 8), mamba.generate(prompt, 8));
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Mamba-2 floating-point modes
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Rust never contracts a * b + c into a fused multiply-add and has no
+//! fast-math, so the model's additions, multiplications, divisions and
+//! square roots round identically on every IEEE-754 target as long as
+//! their order is fixed, which it is throughout (see `mamba_ssm` for the
+//! chunked scan). What differs between x86 and ARM builds is the platform
+//! libm: exp and ln may disagree in the last bit.
+//!
+//! `FpMode::Strict` replaces them with the portable routines below, which
+//! use basic operations only. `FpMode::FixedPoint` also accumulates dot
+//! products as exact integers in units of 2^-64, so a sum is the same in
+//! any order. Native mode keeps the faster platform functions.
+
+use serde::{Deserialize, Serialize};
+
+/// How the model evaluates transcendental functions and dot products
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FpMode {
+    /// Platform exp and ln, f32 dot products in index order
+    #[default]
+    Native,
+    /// Portable exp and ln, f32 dot products in index order
+    Strict,
+    /// `Strict`, with dot products accumulated in fixed point
+    FixedPoint,
+}
+
+/// 2^64, the inverse of the `FpMode::FixedPoint` unit
+const TWO_POW_64: f64 = 18_446_744_073_709_551_616.0;
+
+impl FpMode {
+    /// Whether results are independent of the platform libm
+    pub fn is_strict(self) -> bool {
+        self != FpMode::Native
+    }
+
+    pub fn exp(self, x: f32) -> f32 {
+        match self {
+            FpMode::Native => x.exp(),
+            _ => portable_exp(x as f64) as f32,
+        }
+    }
+
+    /// ln(1 + x)
+    pub fn ln_1p(self, x: f32) -> f32 {
+        match self {
+            FpMode::Native => x.ln_1p(),
+            _ => portable_ln_1p(x as f64) as f32,
+        }
+    }
+
+    /// Sum of w_i x_i. Fixed point multiplies in f64, where a product of
+    /// two f32 values is exact, rounds each product once to a multiple of
+    /// 2^-64 and adds them as integers.
+    pub fn dot(self, w: &[f32], x: &[f32]) -> f32 {
+        match self {
+            FpMode::Native | FpMode::Strict => w.iter().zip(x).fold(0.0f32, |acc, (&w, &x)| acc + w * x),
+            FpMode::FixedPoint => {
+                let sum = w.iter().zip(x).fold(0i128, |acc, (&w, &x)| {
+                    acc.saturating_add((w as f64 * x as f64 * TWO_POW_64).round_ties_even() as i128)
+                });
+                (sum as f64 / TWO_POW_64) as f32
+            }
+        }
+    }
+}
+
+/// e^x from basic IEEE operations only: x = k ln 2 + r with |r| <= ln 2 / 2,
+/// a degree-13 Taylor polynomial for e^r, then 2^k assembled from its
+/// bits. Accurate to a few ulps.
+pub fn portable_exp(x: f64) -> f64 {
+    // ln 2 split so that k * LN2_HI is exact for every k used here
+    const LN2_HI: f64 = f64::from_bits(0x3fe6_2e42_fee0_0000);
+    const LN2_LO: f64 = f64::from_bits(0x3dea_39ef_3579_3c76);
+    if x.is_nan() {
+        return x;
+    }
+    if x < -745.0 {
+        return 0.0;
+    }
+    if x > 709.8 {
+        return f64::INFINITY;
+    }
+    let k = (x * std::f64::consts::LOG2_E).round();
+    let r = (x - k * LN2_HI) - k * LN2_LO;
+    let poly = (1..=13).rev().fold(1.0, |acc, n| 1.0 + acc * r / n as f64);
+    // 2^k in two steps so that subnormal results keep their bits
+    let half = (k / 2.0).trunc();
+    let pow2 = |e: f64| f64::from_bits(((e as i64 + 1023) as u64) << 52);
+    poly * pow2(half) * pow2(k - half)
+}
+
+/// ln x from basic IEEE operations only: x = m 2^e with m in
+/// [sqrt(1/2), sqrt(2)), then ln m = 2 atanh((m - 1) / (m + 1)) by its
+/// series. Zero gives -inf, negative values NaN.
+pub fn portable_ln(x: f64) -> f64 {
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if x.is_infinite() {
+        return x;
+    }
+    // Scale subnormals into the normal range first
+    let (x, bias) = if x < f64::MIN_POSITIVE { (x * TWO_POW_64, -64) } else { (x, 0) };
+    let bits = x.to_bits();
+    let mut e = ((bits >> 52) & 0x7ff) as i64 - 1023 + bias;
+    let mut m = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | 0x3ff0_0000_0000_0000);
+    if m > std::f64::consts::SQRT_2 {
+        m /= 2.0;
+        e += 1;
+    }
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let series = (0..12).rev().fold(0.0, |acc, k| 1.0 / (2 * k + 1) as f64 + s2 * acc);
+    e as f64 * std::f64::consts::LN_2 + 2.0 * s * series
+}
+
+/// ln(1 + x), keeping precision for small x by correcting for the
+/// rounding of 1 + x (Goldberg's method)
+pub fn portable_ln_1p(x: f64) -> f64 {
+    let u = 1.0 + x;
+    if u == 1.0 {
+        return x;
+    }
+    portable_ln(u) * (x / (u - 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_functions_track_libm() {
+        for x in [0.0, -1e-3, 0.5, -1.0, 3.7, -10.0, 20.0, -100.0, 300.0, -700.0] {
+            let (ours, libm) = (portable_exp(x), x.exp());
+            assert!((ours - libm).abs() <= 4.0 * f64::EPSILON * libm, "exp {}: {} vs {}", x, ours, libm);
+        }
+        assert_eq!(portable_exp(-800.0), 0.0);
+        assert!(portable_exp(-740.0) > 0.0);
+        assert_eq!(portable_exp(800.0), f64::INFINITY);
+
+        for x in [1e-310, 1e-5, 0.3, 1.0, std::f64::consts::E, 1.5, 1e3, 1e300] {
+            let (ours, libm) = (portable_ln(x), x.ln());
+            assert!((ours - libm).abs() <= 4.0 * f64::EPSILON * libm.abs().max(1.0), "ln {}: {} vs {}", x, ours, libm);
+        }
+        assert_eq!(portable_ln(1.0), 0.0);
+        assert_eq!(portable_ln(0.0), f64::NEG_INFINITY);
+        assert!(portable_ln(-1.0).is_nan());
+        for x in [1e-17, 1e-9, 5e-5, 0.2, 40.0] {
+            assert!((portable_ln_1p(x) - x.ln_1p()).abs() <= 8.0 * f64::EPSILON * x.ln_1p(), "ln_1p {}", x);
+        }
+    }
+
+    #[test]
+    fn test_fixed_point_sums_ignore_order() {
+        let w: Vec<f32> = (0..257).map(|k| ((k * 7919) % 1000) as f32 / 37.0 - 13.0).collect();
+        let x: Vec<f32> = (0..257).map(|k| (k as f32 * 0.61).sin() * 1e-3).collect();
+        let (mut w_rev, mut x_rev) = (w.clone(), x.clone());
+        w_rev.reverse();
+        x_rev.reverse();
+
+        let fixed = FpMode::FixedPoint.dot(&w, &x);
+        assert_eq!(FpMode::FixedPoint.dot(&w_rev, &x_rev).to_bits(), fixed.to_bits());
+        let exact: f64 = w.iter().zip(&x).map(|(&w, &x)| w as f64 * x as f64).sum();
+        assert!((fixed as f64 - exact).abs() <= 1e-6 * exact.abs());
+        assert_eq!(FpMode::Strict.dot(&w, &x), FpMode::Native.dot(&w, &x));
+        assert!(!FpMode::Native.is_strict() && FpMode::FixedPoint.is_strict());
+    }
+}
@@ -0,0 +1,271 @@
+//! Mamba-2 lazy layer loading
+//! AxiomHive Sovereign Manifold v2.1.0
+//! `DeterministicMambaCore::load_weights_lazy` memory-maps a checkpoint
+//! and checks every tensor's shape up front, but decodes a layer only when
+//! a forward pass reaches it. Decoded layers stay resident until their
+//! bytes exceed the `LayerBudget`, and the `EvictionPolicy` picks which to
+//! drop. The embedding, which doubles as the LM head, and the final norm
+//! are needed for every token and are always resident.
+//!
+//! A pass that holds a layer keeps it alive after eviction, so the budget
+//! can be exceeded by the layers in use at that moment, at most one per
+//! concurrent pass.
+
+use crate::mamba_block::{MambaBlock, MambaConfig};
+use crate::mamba_fp::FpMode;
+use crate::mamba_quant::Quantization;
+use crate::mamba_weights::SafeTensors;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+/// Which resident layer to drop when the budget is exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// The one used longest ago. Suits passes that revisit a few layers,
+    /// but a decode step walks every layer in order, and with a budget
+    /// below the whole stack LRU always evicts the layer needed next.
+    #[default]
+    LeastRecentlyUsed,
+    /// The one used most recently, other than the layer just loaded. While
+    /// decoding this keeps a fixed set of layers resident and reloads only
+    /// the rest on each step.
+    MostRecentlyUsed,
+}
+
+/// How much decoded layer data may stay resident
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayerBudget {
+    /// Bytes of decoded layers to keep, as counted by
+    /// `MambaBlock::size_bytes`; the layer in use is kept regardless
+    pub max_resident_bytes: usize,
+    pub eviction: EvictionPolicy,
+}
+
+/// Where the model's weight memory goes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryReport {
+    /// Size of the memory-mapped checkpoint, 0 when layers are resident.
+    /// The kernel pages this in and out on its own.
+    pub mapped_bytes: usize,
+    /// Embedding and final norm, always resident
+    pub fixed_bytes: usize,
+    /// Decoded layers currently held, in layer order
+    pub resident_layers: Vec<usize>,
+    pub resident_layer_bytes: usize,
+    pub n_layers: usize,
+    /// None when every layer is resident
+    pub budget: Option<LayerBudget>,
+    /// Layers decoded since loading, reloads included
+    pub loads: u64,
+    pub evictions: u64,
+}
+
+/// A layer borrowed from the model, or a lazily loaded one held alive
+/// for as long as this is
+pub enum LayerRef<'a> {
+    Resident(&'a MambaBlock),
+    Loaded(Arc<MambaBlock>),
+}
+
+impl Deref for LayerRef<'_> {
+    type Target = MambaBlock;
+
+    fn deref(&self) -> &MambaBlock {
+        match self {
+            LayerRef::Resident(block) => block,
+            LayerRef::Loaded(block) => block,
+        }
+    }
+}
+
+/// The model's layers, decoded up front or on demand
+pub(crate) enum LayerStore {
+    Resident(Vec<MambaBlock>),
+    Lazy(LazyLayers),
+}
+
+impl LayerStore {
+    pub(crate) fn get(&self, layer: usize) -> Option<LayerRef<'_>> {
+        match self {
+            LayerStore::Resident(layers) => layers.get(layer).map(LayerRef::Resident),
+            LayerStore::Lazy(lazy) => lazy.get(layer).map(LayerRef::Loaded),
+        }
+    }
+}
+
+/// Resident layers, least recently used first, and counters
+#[derive(Default)]
+struct LayerCache {
+    resident: Vec<(usize, Arc<MambaBlock>)>,
+    loads: u64,
+    evictions: u64,
+}
+
+impl LayerCache {
+    fn bytes(&self) -> usize {
+        self.resident.iter().map(|(_, block)| block.size_bytes()).sum()
+    }
+}
+
+/// Layers decoded from a mapped checkpoint as they are needed
+pub(crate) struct LazyLayers {
+    file: SafeTensors,
+    config: MambaConfig,
+    quantization: Quantization,
+    fp_mode: FpMode,
+    budget: LayerBudget,
+    cache: Mutex<LayerCache>,
+}
+
+impl LazyLayers {
+    /// Check that `file` has every layer tensor with its exact shape,
+    /// without decoding any
+    pub(crate) fn new(
+        file: SafeTensors,
+        config: MambaConfig,
+        quantization: Quantization,
+        fp_mode: FpMode,
+        budget: LayerBudget,
+    ) -> Result<Self, String> {
+        for spec in (0..config.n_layers).flat_map(|layer| MambaBlock::manifest(&config, layer)) {
+            let info = file.info(&spec.name).ok_or_else(|| format!("Checkpoint has no tensor {}", spec.name))?;
+            if info.shape != spec.shape {
+                return Err(format!("Tensor {} has shape {:?}, expected {:?}", spec.name, info.shape, spec.shape));
+            }
+        }
+        Ok(Self { file, config, quantization, fp_mode, budget, cache: Mutex::default() })
+    }
+
+    /// Layer `layer`, decoding it if it is not resident. Decoding happens
+    /// under the cache lock, so concurrent passes never decode a layer
+    /// twice.
+    pub(crate) fn get(&self, layer: usize) -> Option<Arc<MambaBlock>> {
+        if layer >= self.config.n_layers {
+            return None;
+        }
+        let mut cache = self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(position) = cache.resident.iter().position(|&(i, _)| i == layer) {
+            let entry = cache.resident.remove(position);
+            cache.resident.push(entry);
+        } else {
+            let block = MambaBlock::load(&self.file, &self.config, layer, self.quantization)
+                .expect("layer shapes were checked when the checkpoint was mapped")
+                .with_fp_mode(self.fp_mode);
+            cache.resident.push((layer, Arc::new(block)));
+            cache.loads += 1;
+            self.evict(&mut cache);
+        }
+        cache.resident.last().map(|(_, block)| block.clone())
+    }
+
+    /// Drop layers until the budget holds, never the most recent one
+    fn evict(&self, cache: &mut LayerCache) {
+        while cache.resident.len() > 1 && cache.bytes() > self.budget.max_resident_bytes {
+            let victim = match self.budget.eviction {
+                EvictionPolicy::LeastRecentlyUsed => 0,
+                EvictionPolicy::MostRecentlyUsed => cache.resident.len() - 2,
+            };
+            cache.resident.remove(victim);
+            cache.evictions += 1;
+        }
+    }
+
+    /// Decode layers as `quantization` from now on, dropping those decoded
+    pub(crate) fn with_quantization(self, quantization: Quantization) -> Self {
+        Self { quantization, cache: Mutex::default(), ..self }
+    }
+
+    /// Evaluate layers in `fp_mode` from now on, dropping those decoded
+    pub(crate) fn with_fp_mode(self, fp_mode: FpMode) -> Self {
+        Self { fp_mode, cache: Mutex::default(), ..self }
+    }
+
+    pub(crate) fn resident_bytes(&self) -> usize {
+        self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).bytes()
+    }
+
+    /// Report for a model whose embedding and final norm take `fixed_bytes`
+    pub(crate) fn report(&self, fixed_bytes: usize) -> MemoryReport {
+        let cache = self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut resident_layers: Vec<usize> = cache.resident.iter().map(|&(i, _)| i).collect();
+        resident_layers.sort_unstable();
+        MemoryReport {
+            mapped_bytes: if self.file.is_mapped() { self.file.file_size() } else { 0 },
+            fixed_bytes,
+            resident_layers,
+            resident_layer_bytes: cache.bytes(),
+            n_layers: self.config.n_layers,
+            budget: Some(self.budget),
+            loads: cache.loads,
+            evictions: cache.evictions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mamba_core::DeterministicMambaCore;
+    use crate::mamba_weights::tests::safetensors_bytes;
+    use crate::mamba_weights::{EMBEDDING, NORM_F};
+
+    /// The deterministic model of `config` written out as a checkpoint
+    fn checkpoint(model: &DeterministicMambaCore) -> Vec<u8> {
+        let config = model.config();
+        let embedding = model.embedding();
+        let mut tensors = vec![(
+            EMBEDDING.to_string(),
+            vec![embedding.vocab_size(), config.d_model],
+            embedding.weights().to_f32().into_owned(),
+        )];
+        for layer in 0..config.n_layers {
+            let block = model.layer(layer).unwrap();
+            let specs = MambaBlock::manifest(config, layer);
+            tensors.extend(specs.into_iter().zip(block.tensors()).map(|(spec, t)| (spec.name, spec.shape, t.into_owned())));
+        }
+        tensors.push((NORM_F.to_string(), vec![config.d_model], vec![1.0; config.d_model]));
+        let tensors: Vec<(&str, Vec<usize>, Vec<f32>)> =
+            tensors.iter().map(|(name, shape, values)| (name.as_str(), shape.clone(), values.clone())).collect();
+        safetensors_bytes(&tensors)
+    }
+
+    #[test]
+    fn test_lazy_layers_match_resident_ones_within_budget() {
+        let config = MambaConfig::new(3, 8, 4);
+        let resident = DeterministicMambaCore::from_config(config).unwrap();
+        let layer_bytes = resident.layer(0).unwrap().size_bytes();
+        let path = std::env::temp_dir().join(format!("axiomhive-lazy-{}.safetensors", std::process::id()));
+        std::fs::write(&path, checkpoint(&resident)).unwrap();
+
+        let lazy = |max_layers: usize, eviction: EvictionPolicy| {
+            let mut model = DeterministicMambaCore::from_config(config).unwrap();
+            let budget = LayerBudget { max_resident_bytes: max_layers * layer_bytes, eviction };
+            model.load_weights_lazy(&path, Quantization::F32, budget).map(|()| model)
+        };
+        let (lru, mru) = (lazy(2, EvictionPolicy::LeastRecentlyUsed), lazy(2, EvictionPolicy::MostRecentlyUsed));
+        let single = lazy(0, EvictionPolicy::LeastRecentlyUsed);
+        std::fs::remove_file(&path).unwrap();
+        let (lru, mru, single) = (lru.unwrap(), mru.unwrap(), single.unwrap());
+
+        assert_eq!(lru.memory_report().resident_layers, Vec::<usize>::new());
+        let expected = resident.generate("Indemnify", 5);
+        for model in [&lru, &mru, &single] {
+            assert_eq!(model.generate("Indemnify", 5), expected);
+        }
+        assert_eq!(single.weight_checksum(), resident.weight_checksum());
+
+        let report = single.memory_report();
+        assert!(report.mapped_bytes > 0);
+        assert_eq!(report.resident_layers.len(), 1);
+        assert_eq!(report.evictions, report.loads - 1);
+        assert_eq!(resident.memory_report().resident_layers, vec![0, 1, 2]);
+
+        // Decoding walks the stack in order: LRU misses on every layer of
+        // every step, while MRU keeps one layer resident throughout
+        let (lru, mru) = (lru.memory_report(), mru.memory_report());
+        assert_eq!(lru.resident_layer_bytes, 2 * layer_bytes);
+        assert!(mru.loads < lru.loads, "{} vs {}", mru.loads, lru.loads);
+    }
+}
@@ -0,0 +1,249 @@
+//! Mamba-2 weight quantization
+//! AxiomHive Sovereign Manifold v2.1.0
+//! The large matrices (embedding, x_proj, dt_proj) can be held as f16 or
+//! as per-channel int8, a half or a quarter of their f32 size. f16 widens
+//! every weight as it is used, so it is slower than f32; int8 products run
+//! on integers and are the fastest of the three (see `benches/mamba.rs`).
+//!
+//! Determinism guarantee, for both modes:
+//! - Quantization rounds to nearest, ties to even, with scales computed
+//!   in f32 from the data alone, so a checkpoint always quantizes to the
+//!   same bytes.
+//! - f16 weights are widened exactly to f32 and then take the ordinary
+//!   f32 path, element by element in index order.
+//! - int8 products quantize the activation vector the same way (one
+//!   scale per call), accumulate i8 x i8 in i32, which is exact, and
+//!   apply both scales in a fixed order. There are no data-dependent
+//!   shortcuts: zero rows and zero inputs go through the same arithmetic.
+
+use crate::mamba_fp::FpMode;
+use crate::mamba_weights::f16_to_f32;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// How a `Matrix` stores its values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quantization {
+    #[default]
+    F32,
+    F16,
+    /// One f32 scale per row, values in [-127, 127]
+    Int8,
+}
+
+/// Row-major weight matrix in any `Quantization`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: MatrixData,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum MatrixData {
+    F32(Vec<f32>),
+    F16(Vec<u16>),
+    Int8 { values: Vec<i8>, scales: Vec<f32> },
+}
+
+impl Matrix {
+    /// An f32 matrix; `values` must hold `rows * cols` entries
+    pub fn new(rows: usize, cols: usize, values: Vec<f32>) -> Self {
+        assert_eq!(values.len(), rows * cols, "matrix values do not match its shape");
+        Self { rows, cols, data: MatrixData::F32(values) }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn quantization(&self) -> Quantization {
+        match self.data {
+            MatrixData::F32(_) => Quantization::F32,
+            MatrixData::F16(_) => Quantization::F16,
+            MatrixData::Int8 { .. } => Quantization::Int8,
+        }
+    }
+
+    /// Storage size in bytes, scales included
+    pub fn size_bytes(&self) -> usize {
+        match &self.data {
+            MatrixData::F32(values) => values.len() * 4,
+            MatrixData::F16(values) => values.len() * 2,
+            MatrixData::Int8 { values, scales } => values.len() + scales.len() * 4,
+        }
+    }
+
+    /// Re-encode as `quantization`. Converting between quantized forms goes
+    /// through f32, compounding their rounding.
+    pub fn quantized(self, quantization: Quantization) -> Self {
+        if quantization == self.quantization() {
+            return self;
+        }
+        let values = self.to_f32().into_owned();
+        let data = match quantization {
+            Quantization::F32 => MatrixData::F32(values),
+            Quantization::F16 => MatrixData::F16(values.iter().map(|&v| f32_to_f16(v)).collect()),
+            Quantization::Int8 => {
+                let (values, scales) = values.chunks(self.cols.max(1)).map(quantize_i8).unzip::<_, _, Vec<_>, _>();
+                MatrixData::Int8 { values: values.concat(), scales }
+            }
+        };
+        Self { data, ..self }
+    }
+
+    /// All values as f32, borrowed when already stored that way
+    pub fn to_f32(&self) -> Cow<'_, [f32]> {
+        match &self.data {
+            MatrixData::F32(values) => Cow::Borrowed(values),
+            _ => Cow::Owned((0..self.rows).flat_map(|i| self.row(i).expect("row in range").into_owned()).collect()),
+        }
+    }
+
+    /// Row `i` as f32
+    pub fn row(&self, i: usize) -> Option<Cow<'_, [f32]>> {
+        if i >= self.rows {
+            return None;
+        }
+        let range = i * self.cols..(i + 1) * self.cols;
+        Some(match &self.data {
+            MatrixData::F32(values) => Cow::Borrowed(&values[range]),
+            MatrixData::F16(values) => Cow::Owned(values[range].iter().map(|&h| f16_to_f32(h)).collect()),
+            MatrixData::Int8 { values, scales } => {
+                Cow::Owned(values[range].iter().map(|&q| q as f32 * scales[i]).collect())
+            }
+        })
+    }
+
+    /// Matrix-vector product, one dot product per row in index order
+    pub fn matvec(&self, x: &[f32]) -> Vec<f32> {
+        self.matvec_with(x, FpMode::Native)
+    }
+
+    /// `matvec` with float rows summed as `mode` does; int8 sums are
+    /// exact integers in every mode
+    pub fn matvec_with(&self, x: &[f32], mode: FpMode) -> Vec<f32> {
+        debug_assert_eq!(x.len(), self.cols);
+        let dot = |row: &[f32]| mode.dot(row, x);
+        match &self.data {
+            MatrixData::F32(values) => values.chunks_exact(self.cols.max(1)).take(self.rows).map(dot).collect(),
+            MatrixData::F16(_) => (0..self.rows).map(|i| dot(&self.row(i).expect("row in range"))).collect(),
+            MatrixData::Int8 { values, scales } => {
+                let (xq, x_scale) = quantize_i8(x);
+                values
+                    .chunks_exact(self.cols.max(1))
+                    .take(self.rows)
+                    .zip(scales)
+                    .map(|(row, &scale)| {
+                        let acc = row.iter().zip(&xq).fold(0i32, |acc, (&w, &x)| acc + w as i32 * x as i32);
+                        acc as f32 * (scale * x_scale)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Symmetric int8 quantization of one channel: scale = max|v| / 127 (1 for
+/// an all-zero channel), q = v / scale rounded half to even
+fn quantize_i8(values: &[f32]) -> (Vec<i8>, f32) {
+    let max = values.iter().fold(0.0f32, |max, v| max.max(v.abs()));
+    let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+    let quantized = values.iter().map(|&v| (v / scale).round_ties_even().clamp(-127.0, 127.0) as i8).collect();
+    (quantized, scale)
+}
+
+/// Single to IEEE half precision, rounding to nearest even; overflow goes
+/// to infinity and NaN stays NaN
+pub(crate) fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent == 0xff {
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    // Drop the low `shift` bits of `m`, rounding half to even; a carry
+    // out of the mantissa correctly bumps the exponent
+    let round = |m: u32, shift: u32| {
+        let (kept, rest, half) = (m >> shift, m & ((1 << shift) - 1), 1 << (shift - 1));
+        kept + (rest > half || (rest == half && kept & 1 == 1)) as u32
+    };
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exponent <= 0 {
+        // Subnormal: a count of 2^-24 units, or zero below half of one
+        if half_exponent < -10 {
+            return sign;
+        }
+        return sign | round(mantissa | 0x80_0000, (14 - half_exponent) as u32) as u16;
+    }
+    sign | round(((half_exponent as u32) << 23) | mantissa, 13) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_precision_round_trips_and_rounds_to_even() {
+        for bits in 0..=u16::MAX {
+            let value = f16_to_f32(bits);
+            if !value.is_nan() {
+                assert_eq!(f32_to_f16(value), bits, "{:#06x}", bits);
+            }
+        }
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+        // 1 + 2^-11 is halfway between 1 and the next half; ties go to 1
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+        assert_eq!(f32_to_f16(2f32.powi(-26)), 0);
+        assert_eq!(f32_to_f16(-3.0 * 2f32.powi(-26)), 0x8001);
+    }
+
+    #[test]
+    fn test_int8_rows_use_their_own_scale() {
+        let matrix = Matrix::new(2, 3, vec![1.0, -0.5, 0.25, 100.0, 0.0, -100.0]).quantized(Quantization::Int8);
+        assert_eq!(matrix.quantization(), Quantization::Int8);
+        assert_eq!(matrix.size_bytes(), 6 + 2 * 4);
+        // Row 0 keeps its precision despite the large values in row 1
+        let row = matrix.row(0).unwrap();
+        assert!((row[0] - 1.0).abs() < 1e-6 && (row[2] - 0.25).abs() < 0.005);
+        assert_eq!(matrix.row(1).unwrap().into_owned(), vec![100.0, 0.0, -100.0]);
+        assert!(matrix.row(2).is_none());
+
+        // 63.5 is a tie and goes to 64, as does 64.5
+        let (q, scale) = quantize_i8(&[127.0, 63.5, 64.5, -63.5]);
+        assert_eq!((q, scale), (vec![127, 64, 64, -64], 1.0));
+        assert_eq!(quantize_i8(&[0.0, 0.0]), (vec![0, 0], 1.0));
+    }
+
+    #[test]
+    fn test_quantized_products_stay_close_to_f32() {
+        let (rows, cols) = (32, 64);
+        let values: Vec<f32> = (0..rows * cols).map(|k| ((k * 7919) % 1000) as f32 / 500.0 - 1.0).collect();
+        let x: Vec<f32> = (0..cols).map(|k| (k as f32 * 0.37).sin()).collect();
+        let exact = Matrix::new(rows, cols, values.clone()).matvec(&x);
+        let norm = exact.iter().map(|v| v * v).sum::<f32>().sqrt();
+        let zeros = Matrix::new(rows, cols, vec![0.0; rows * cols]).quantized(Quantization::Int8);
+        assert_eq!(zeros.matvec(&x), vec![0.0; rows]);
+
+        for (quantization, tolerance, size) in [(Quantization::F16, 1e-3, 2 * rows * cols), (Quantization::Int8, 2e-2, rows * cols + 4 * rows)] {
+            let quantized = Matrix::new(rows, cols, values.clone()).quantized(quantization);
+            assert_eq!(quantized.size_bytes(), size);
+            let approx = quantized.matvec(&x);
+            let error = approx.iter().zip(&exact).map(|(a, e)| (a - e) * (a - e)).sum::<f32>().sqrt();
+            assert!(error / norm < tolerance, "{:?}: relative error {}", quantization, error / norm);
+            assert_eq!(quantized.matvec(&x), approx);
+        }
+    }
+}
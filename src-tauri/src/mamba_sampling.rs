@@ -0,0 +1,251 @@
+//! Mamba-2 seeded sampling
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Greedy decoding is the default and needs no randomness. Sampling at a
+//! temperature above zero is opt-in and always takes an explicit seed:
+//! the draw for the token at position p is Philox4x32-10 keyed by the
+//! seed at counter p, so it depends on nothing but (seed, p) and a run
+//! can be replayed from its `SamplingMetadata` alone.
+//!
+//! Philox uses only 32-bit multiplies, xors and adds, and the softmax
+//! below uses only IEEE-754 additions, multiplications and divisions (its
+//! exp is the portable one from `mamba_fp`, not the platform libm), so a
+//! given set of logits samples the same token on every platform.
+
+use crate::mamba_fp::portable_exp;
+use serde::{Deserialize, Serialize};
+
+/// Name recorded for the generator in `RngParameters`
+pub const PHILOX_ALGORITHM: &str = "philox4x32-10";
+
+/// A hook that adjusts the next-token logits before a token is chosen,
+/// for instance to ban tokens by setting them to negative infinity. It
+/// must depend only on its arguments to keep decoding reproducible.
+pub trait LogitsProcessor: Send + Sync {
+    /// `context` is every token before the one being chosen: the prompt,
+    /// or the session so far, then the tokens generated in this run
+    fn process(&self, context: &[u32], logits: &mut [f32]);
+}
+
+/// How the next token is chosen from the logits
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Sampling {
+    /// The highest logit, ties going to the lowest id
+    #[default]
+    Greedy,
+    /// Softmax of logits / temperature, drawn with Philox keyed by `seed`
+    Seeded { temperature: f64, seed: u64 },
+}
+
+impl Sampling {
+    /// Greedy at temperature 0; anything above needs a seed, so there is
+    /// no implicit entropy source
+    pub fn new(temperature: f64, seed: Option<u64>) -> Result<Self, String> {
+        if !(temperature >= 0.0 && temperature.is_finite()) {
+            return Err(format!("Temperature must be finite and non-negative. Got: {}", temperature));
+        }
+        if temperature == 0.0 {
+            return Ok(Sampling::Greedy);
+        }
+        match seed {
+            Some(seed) => Ok(Sampling::Seeded { temperature, seed }),
+            None => Err(format!(
+                "Temperature {} needs an explicit seed to stay reproducible (Zero Entropy Law)",
+                temperature
+            )),
+        }
+    }
+
+    /// Token for position `position` given its logits
+    pub fn choose(&self, logits: &[f32], position: u64) -> u32 {
+        match *self {
+            Sampling::Greedy => argmax(logits),
+            Sampling::Seeded { temperature, seed } => {
+                sample_softmax(logits, temperature, Philox4x32::new(seed).uniform(position))
+            }
+        }
+    }
+
+    /// What a run that chose the tokens at positions `first_position`
+    /// onwards, `draws` of them, needs to be replayed
+    pub fn metadata(&self, first_position: u64, draws: u64) -> SamplingMetadata {
+        let rng = match *self {
+            Sampling::Greedy => None,
+            Sampling::Seeded { seed, .. } => Some(RngParameters {
+                algorithm: PHILOX_ALGORITHM.to_string(),
+                key: Philox4x32::new(seed).key,
+                first_counter: first_position,
+                draws,
+            }),
+        };
+        SamplingMetadata { sampling: *self, rng }
+    }
+}
+
+/// Sampling settings and generator state of a generation run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingMetadata {
+    #[serde(flatten)]
+    pub sampling: Sampling,
+    /// Absent for greedy decoding
+    pub rng: Option<RngParameters>,
+}
+
+/// Everything needed to regenerate the random draws of a run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RngParameters {
+    pub algorithm: String,
+    /// Philox key: the seed's low and high words
+    pub key: [u32; 2],
+    /// Counter of the first draw, the position of the first generated
+    /// token; later draws count up from it
+    pub first_counter: u64,
+    /// Draws made, including one that produced end-of-text
+    pub draws: u64,
+}
+
+/// Philox4x32 with 10 rounds (Salmon et al., "Parallel random numbers: as
+/// easy as 1, 2, 3", SC 2011)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Philox4x32 {
+    key: [u32; 2],
+}
+
+const PHILOX_M0: u32 = 0xD251_1F53;
+const PHILOX_M1: u32 = 0xCD9E_8D57;
+const PHILOX_W0: u32 = 0x9E37_79B9;
+const PHILOX_W1: u32 = 0xBB67_AE85;
+
+impl Philox4x32 {
+    pub const ROUNDS: usize = 10;
+
+    pub fn new(seed: u64) -> Self {
+        Self { key: [seed as u32, (seed >> 32) as u32] }
+    }
+
+    /// The four output words for `counter`
+    pub fn block(&self, counter: [u32; 4]) -> [u32; 4] {
+        let mulhilo = |a: u32, b: u32| {
+            let product = a as u64 * b as u64;
+            ((product >> 32) as u32, product as u32)
+        };
+        let (mut c, mut k) = (counter, self.key);
+        for round in 0..Self::ROUNDS {
+            if round > 0 {
+                k = [k[0].wrapping_add(PHILOX_W0), k[1].wrapping_add(PHILOX_W1)];
+            }
+            let (hi0, lo0) = mulhilo(PHILOX_M0, c[0]);
+            let (hi1, lo1) = mulhilo(PHILOX_M1, c[2]);
+            c = [hi1 ^ c[1] ^ k[0], lo1, hi0 ^ c[3] ^ k[1], lo0];
+        }
+        c
+    }
+
+    /// Uniform in [0, 1) with 53 random bits, from the block at counter
+    /// (index low word, index high word, 0, 0)
+    pub fn uniform(&self, index: u64) -> f64 {
+        let [a, b, _, _] = self.block([index as u32, (index >> 32) as u32, 0, 0]);
+        let bits = ((a as u64) << 32 | b as u64) >> 11;
+        bits as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
+
+/// Index of the largest value, the first one on ties; NaN never wins
+pub(crate) fn argmax(values: &[f32]) -> u32 {
+    let mut best = 0;
+    for (i, &v) in values.iter().enumerate() {
+        if v > values[best] || values[best].is_nan() {
+            best = i;
+        }
+    }
+    best as u32
+}
+
+/// Inverse-CDF draw from softmax(logits / temperature): the first index
+/// whose running weight total, summed in index order, exceeds `uniform`
+/// times the whole. NaN logits get no weight.
+fn sample_softmax(logits: &[f32], temperature: f64, uniform: f64) -> u32 {
+    let max = logits[argmax(logits) as usize] as f64;
+    // Everything banned, or an infinite logit: there is nothing to weigh
+    if !max.is_finite() {
+        return argmax(logits);
+    }
+    let weights: Vec<f64> = logits
+        .iter()
+        .map(|&l| if l.is_nan() { 0.0 } else { portable_exp((l as f64 - max) / temperature) })
+        .collect();
+    let target = uniform * weights.iter().sum::<f64>();
+    let mut total = 0.0;
+    for (i, &w) in weights.iter().enumerate() {
+        total += w;
+        if total > target && w > 0.0 {
+            return i as u32;
+        }
+    }
+    // Rounding left the target at the very top: the last weighted index
+    weights.iter().rposition(|&w| w > 0.0).unwrap_or(0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_philox_matches_the_reference_vectors() {
+        // Known-answer tests from the Random123 distribution
+        let zero = Philox4x32 { key: [0, 0] };
+        assert_eq!(zero.block([0; 4]), [0x6627_e8d5, 0xe169_c58d, 0xbc57_ac4c, 0x9b00_dbd8]);
+        let ones = Philox4x32 { key: [u32::MAX; 2] };
+        assert_eq!(ones.block([u32::MAX; 4]), [0x408f_276d, 0x41c8_3b0e, 0xa20b_c7c6, 0x6d54_51fd]);
+        let pi = Philox4x32 { key: [0xa409_3822, 0x299f_31d0] };
+        assert_eq!(
+            pi.block([0x243f_6a88, 0x85a3_08d3, 0x1319_8a2e, 0x0370_7344]),
+            [0xd16c_fe09, 0x94fd_cceb, 0x5001_e420, 0x2412_6ea1]
+        );
+        assert_eq!(Philox4x32::new(0x2_0000_0001).key, [1, 2]);
+        let u = Philox4x32::new(7).uniform(3);
+        assert!((0.0..1.0).contains(&u));
+        assert_ne!(u, Philox4x32::new(7).uniform(4));
+    }
+
+    #[test]
+    fn test_softmax_draws_follow_the_temperature() {
+        let logits = [1.0, 3.0, f32::NAN, 2.0];
+        // Weights e^-2, 1, 0, e^-1 sum to about 1.503
+        assert_eq!(sample_softmax(&logits, 1.0, 0.0), 0);
+        assert_eq!(sample_softmax(&logits, 1.0, 0.5), 1);
+        assert_eq!(sample_softmax(&logits, 1.0, 0.999_999), 3);
+        // A tiny temperature concentrates on the maximum
+        assert_eq!(sample_softmax(&logits, 1e-3, 0.999_999), 1);
+        assert_eq!(sample_softmax(&[f32::NEG_INFINITY, 0.5, f32::NEG_INFINITY], 1.0, 0.999), 1);
+        assert_eq!(sample_softmax(&[f32::NEG_INFINITY; 3], 1.0, 0.5), 0);
+
+        // Each token's share of 4000 positions is close to its probability
+        let sampling = Sampling::new(1.0, Some(42)).unwrap();
+        let mut counts = [0usize; 4];
+        for position in 0..4000 {
+            counts[sampling.choose(&logits, position) as usize] += 1;
+        }
+        assert_eq!(counts[2], 0);
+        let share = counts[1] as f64 / 4000.0;
+        assert!((share - 1.0 / 1.503).abs() < 0.03, "{:?}", counts);
+    }
+
+    #[test]
+    fn test_seeded_sampling_needs_a_seed() {
+        assert_eq!(Sampling::new(0.0, None), Ok(Sampling::Greedy));
+        assert_eq!(Sampling::new(0.0, Some(9)), Ok(Sampling::Greedy));
+        assert!(Sampling::new(0.7, None).unwrap_err().contains("seed"));
+        assert!(Sampling::new(-1.0, Some(9)).is_err());
+        assert!(Sampling::new(f64::NAN, Some(9)).is_err());
+
+        let seeded = Sampling::new(0.7, Some(9)).unwrap();
+        let metadata = seeded.metadata(5, 3);
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["mode"], "seeded");
+        assert_eq!(json["seed"], 9);
+        assert_eq!(json["rng"]["algorithm"], PHILOX_ALGORITHM);
+        assert_eq!(serde_json::from_value::<SamplingMetadata>(json).unwrap(), metadata);
+        assert_eq!(Sampling::Greedy.metadata(5, 3).rng, None);
+    }
+}
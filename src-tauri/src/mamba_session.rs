@@ -0,0 +1,188 @@
+//! Mamba-2 incremental sessions
+//! AxiomHive Sovereign Manifold v2.1.0
+//! An SSM compresses everything it has read into a fixed-size hidden
+//! state, so a conversation never has to be re-read: `MambaSession` keeps
+//! the state after each call and only scans the new text. Generated
+//! tokens are fed back in as well, so the next `extend` picks up after
+//! them.
+//!
+//! Each `extend` is tokenized on its own, so the session's tokens can
+//! differ from tokenizing the concatenated text at the boundaries, and
+//! its state agrees with a one-shot pass up to float rounding. The same
+//! sequence of calls always gives the same state.
+
+use crate::mamba_block::BlockState;
+use crate::mamba_core::{DeterministicMambaCore, GenerateResult, GeneratedToken};
+use crate::mamba_sampling::Sampling;
+use serde::{Deserialize, Serialize};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+/// A session's position, for `save_state` / `load_state`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Every token read or generated so far
+    pub tokens: Vec<u32>,
+    /// Recurrent state of every layer
+    pub state: Vec<BlockState>,
+    /// Hidden vector after the last token, (d_model)
+    pub last_output: Vec<f32>,
+}
+
+/// A model plus the hidden state after everything it has read
+pub struct MambaSession {
+    model: Arc<DeterministicMambaCore>,
+    tokens: Vec<u32>,
+    state: Vec<BlockState>,
+    last_output: Vec<f32>,
+}
+
+impl MambaSession {
+    /// An empty session
+    pub fn new(model: Arc<DeterministicMambaCore>) -> Self {
+        let state = model.zero_state();
+        let last_output = vec![0.0; model.config().d_model];
+        Self { model, tokens: Vec::new(), state, last_output }
+    }
+
+    /// Resume from `save_state`. Only the shapes can be checked, so the
+    /// model must be the one the state was saved from.
+    pub fn load_state(model: Arc<DeterministicMambaCore>, saved: SessionState) -> Result<Self, String> {
+        let empty = Self::new(model);
+        if saved.state.len() != empty.state.len() {
+            return Err(format!(
+                "Saved session has {} layers, the model has {}",
+                saved.state.len(),
+                empty.state.len()
+            ));
+        }
+        if let Some(layer) = empty.state.iter().zip(&saved.state).position(|(zero, state)| !zero.same_shape(state)) {
+            return Err(format!("Saved state of layer {} does not match the model", layer));
+        }
+        if saved.last_output.len() != empty.last_output.len() {
+            return Err(format!(
+                "Saved session has a {}-value output, the model needs {}",
+                saved.last_output.len(),
+                empty.last_output.len()
+            ));
+        }
+        let vocab_size = empty.model.tokenizer().vocab_size();
+        if let Some(id) = saved.tokens.iter().find(|&&id| id as usize >= vocab_size) {
+            return Err(format!("Saved session has token {} outside the {}-token vocabulary", id, vocab_size));
+        }
+        Ok(Self {
+            tokens: saved.tokens,
+            state: saved.state,
+            last_output: saved.last_output,
+            ..empty
+        })
+    }
+
+    pub fn save_state(&self) -> SessionState {
+        SessionState {
+            tokens: self.tokens.clone(),
+            state: self.state.clone(),
+            last_output: self.last_output.clone(),
+        }
+    }
+
+    pub fn model(&self) -> &Arc<DeterministicMambaCore> {
+        &self.model
+    }
+
+    pub fn tokens(&self) -> &[u32] {
+        &self.tokens
+    }
+
+    pub fn state(&self) -> &[BlockState] {
+        &self.state
+    }
+
+    /// Read `text`, scanning only its tokens from the current state.
+    /// Returns how many tokens it added.
+    pub fn extend(&mut self, text: &str) -> usize {
+        let ids = self.model.tokenize(text);
+        if let Some(last) = self.model.advance(&ids, &mut self.state) {
+            self.last_output = last;
+        }
+        self.tokens.extend_from_slice(&ids);
+        ids.len()
+    }
+
+    /// Greedy continuation of everything read so far; the generated tokens
+    /// join the session. The transcript covers the whole session.
+    pub fn generate(&mut self, max_tokens: usize) -> GenerateResult {
+        self.generate_stream(max_tokens, |_| ControlFlow::Continue(()))
+    }
+
+    /// `generate` with a per-token callback, as
+    /// `DeterministicMambaCore::generate_stream`
+    pub fn generate_stream<F>(&mut self, max_tokens: usize, on_token: F) -> GenerateResult
+    where
+        F: FnMut(&GeneratedToken) -> ControlFlow<()>,
+    {
+        let result = self
+            .model
+            .decode_from(&self.tokens, &mut self.state, &mut self.last_output, max_tokens, Sampling::Greedy, on_token);
+        self.tokens.extend_from_slice(&result.tokens);
+        result
+    }
+
+    /// Forget everything read, keeping the model
+    pub fn reset(&mut self) {
+        *self = Self::new(self.model.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extend_continues_from_the_saved_state() {
+        let model = Arc::new(DeterministicMambaCore::new(8, 4, 2));
+        let mut session = MambaSession::new(model.clone());
+        assert_eq!(session.extend("Party A shall"), 13);
+        let added = session.extend(" indemnify");
+        assert_eq!(session.tokens().len(), 13 + added);
+
+        // Close to running the whole token sequence at once
+        let mut state = model.zero_state();
+        model.advance(session.tokens(), &mut state);
+        for (a, b) in session.state().iter().zip(&state) {
+            assert!(a.ssm.iter().zip(&b.ssm).all(|(a, b)| (a - b).abs() < 1e-4));
+            assert!(a.conv.iter().zip(&b.conv).all(|(a, b)| (a - b).abs() < 1e-4));
+        }
+
+        // Generation continues the session, and matches a fresh run
+        let generated = session.generate(4);
+        assert_eq!(session.tokens().len(), 13 + added + 4);
+        let mut replay = MambaSession::new(model.clone());
+        replay.extend("Party A shall");
+        replay.extend(" indemnify");
+        assert_eq!(replay.generate(4), generated);
+        assert_eq!(replay.state(), session.state());
+
+        session.reset();
+        assert!(session.tokens().is_empty());
+        assert_eq!(session.state(), model.zero_state());
+    }
+
+    #[test]
+    fn test_saved_state_round_trips() {
+        let model = Arc::new(DeterministicMambaCore::new(8, 4, 2));
+        let mut session = MambaSession::new(model.clone());
+        session.extend("Governing law");
+        let json = serde_json::to_string(&session.save_state()).unwrap();
+
+        let saved: SessionState = serde_json::from_str(&json).unwrap();
+        let mut restored = MambaSession::load_state(model.clone(), saved.clone()).unwrap();
+        assert_eq!(restored.save_state(), session.save_state());
+        assert_eq!(restored.generate(3), session.generate(3));
+
+        let other = Arc::new(DeterministicMambaCore::new(8, 2, 2));
+        assert!(MambaSession::load_state(other, saved.clone()).is_err());
+        let bad_token = SessionState { tokens: vec![9999], ..saved };
+        assert!(matches!(MambaSession::load_state(model, bad_token), Err(e) if e.contains("vocabulary")));
+    }
+}
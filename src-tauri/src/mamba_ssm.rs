@@ -0,0 +1,479 @@
+//! Mamba-2 selective state-space layer
+//! AxiomHive Sovereign Manifold v2.1.0
+//! The input-dependent recurrence at the heart of Mamba. For every token
+//! x_t (d_inner channels) the layer projects
+//!   [dt_low | B_t | C_t] = x_proj x_t,  delta_t = softplus(dt_proj dt_low + dt_bias)
+//! discretizes the diagonal A by zero-order hold,
+//!   A_bar = exp(delta_t A),  B_bar x_t = delta_t B_t x_t
+//! and scans sequentially:
+//!   h_t = A_bar h_(t-1) + B_bar x_t,  y_t = C_t h_t + D x_t
+//! Weight layouts follow the reference checkpoints: row-major (out, in)
+//! projections and A stored as A_log, A = -exp(A_log).
+//!
+//! `scan_chunked` computes the same sequence in the state-space-duality
+//! form: within a chunk of L tokens each output is a masked quadratic sum,
+//!   y_t = sum_(s<=t) C_t exp(cum_t - cum_s) B_bar_s x_s + C_t exp(cum_t) h_0
+//! with cum_t the running sum of delta A (each exp(cum_t - cum_s) is taken
+//! as a product of per-token A_bar), and only the chunk boundary states
+//! are carried sequentially. Chunks are independent given their
+//! start state, so with the `parallel` feature they run across threads.
+//! Chunk boundaries are fixed by L, never by the thread count, so the
+//! result is the same however the work is scheduled.
+//!
+//! exp and softplus follow the layer's `FpMode` (see `mamba_fp`).
+
+use crate::mamba_core::hashed_uniform;
+use crate::mamba_fp::{portable_exp, portable_ln, FpMode};
+use crate::mamba_quant::{Matrix, Quantization};
+use std::borrow::Cow;
+
+/// Weights of one selective SSM
+#[derive(Debug, Clone, PartialEq)]
+pub struct SsmLayer {
+    d_inner: usize,
+    d_state: usize,
+    dt_rank: usize,
+    /// (d_inner, d_state)
+    a_log: Vec<f32>,
+    /// (dt_rank + 2 * d_state, d_inner)
+    x_proj: Matrix,
+    /// (d_inner, dt_rank)
+    dt_proj: Matrix,
+    /// (d_inner)
+    dt_bias: Vec<f32>,
+    /// (d_inner)
+    d: Vec<f32>,
+    fp_mode: FpMode,
+}
+
+/// Trained SSM weights, in the layouts of the `SsmLayer` fields
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SsmWeights {
+    pub a_log: Vec<f32>,
+    pub x_proj: Vec<f32>,
+    pub dt_proj: Vec<f32>,
+    pub dt_bias: Vec<f32>,
+    pub d: Vec<f32>,
+}
+
+/// Default chunk length of `scan_chunked`. The quadratic form costs about
+/// L/2 multiply-adds per token and state where the recurrence needs one
+/// exp, so short chunks keep a single thread close to `scan`.
+pub const SSD_CHUNK_LEN: usize = 16;
+
+/// Input-dependent parameters of one token
+struct Projection {
+    /// (d_inner)
+    delta: Vec<f32>,
+    /// (d_state)
+    b: Vec<f32>,
+    /// (d_state)
+    c: Vec<f32>,
+}
+
+/// Phase one result for a chunk scanned from a zero state
+struct ChunkScan {
+    /// Per-token outputs of the quadratic term, (len, d_inner)
+    y_diag: Vec<f32>,
+    /// Final state from a zero start, (d_inner, d_state)
+    state: Vec<f32>,
+    /// exp(cum) at the last token, (d_inner, d_state)
+    decay: Vec<f32>,
+}
+
+/// Smallest and largest initial step size delta
+const DT_MIN: f32 = 0.001;
+const DT_MAX: f32 = 0.1;
+
+impl SsmLayer {
+    /// Analytic initialization: HiPPO-style A_n = -(n + 1.5) on every
+    /// channel, step sizes spread log-uniformly over [0.001, 0.1] and
+    /// hash-derived projections, all deterministic. Computed with the
+    /// portable exp and ln, so the weights are the same on every platform.
+    pub fn hippo(d_inner: usize, d_state: usize, dt_rank: usize) -> Self {
+        let a_log = (0..d_inner)
+            .flat_map(|_| (0..d_state).map(|n| portable_ln(n as f64 + 1.5) as f32))
+            .collect();
+        let x_proj = hashed_uniform(b"mamba_x_proj", (dt_rank + 2 * d_state) * d_inner, 1.0 / (d_inner.max(1) as f32).sqrt());
+        let dt_proj = hashed_uniform(b"mamba_dt_proj", d_inner * dt_rank, 1.0 / (dt_rank.max(1) as f32).sqrt());
+        let dt_bias = (0..d_inner)
+            .map(|i| {
+                let frac = if d_inner > 1 { i as f64 / (d_inner - 1) as f64 } else { 0.5 };
+                let (ln_min, ln_max) = (portable_ln(DT_MIN as f64), portable_ln(DT_MAX as f64));
+                let dt = portable_exp(ln_min + frac * (ln_max - ln_min));
+                // Inverse softplus, so softplus(bias) = dt
+                (dt + portable_ln(1.0 - portable_exp(-dt))) as f32
+            })
+            .collect();
+        Self {
+            d_inner,
+            d_state,
+            dt_rank,
+            a_log,
+            x_proj: Matrix::new(dt_rank + 2 * d_state, d_inner, x_proj),
+            dt_proj: Matrix::new(d_inner, dt_rank, dt_proj),
+            dt_bias,
+            d: vec![1.0; d_inner],
+            fp_mode: FpMode::Native,
+        }
+    }
+
+    /// Use trained weights, checking every shape
+    pub fn from_weights(d_inner: usize, d_state: usize, dt_rank: usize, weights: SsmWeights) -> Result<Self, String> {
+        let SsmWeights { a_log, x_proj, dt_proj, dt_bias, d } = weights;
+        let expected = [
+            ("A_log", a_log.len(), d_inner * d_state),
+            ("x_proj", x_proj.len(), (dt_rank + 2 * d_state) * d_inner),
+            ("dt_proj", dt_proj.len(), d_inner * dt_rank),
+            ("dt_bias", dt_bias.len(), d_inner),
+            ("D", d.len(), d_inner),
+        ];
+        if let Some((name, got, want)) = expected.iter().find(|(_, got, want)| got != want) {
+            return Err(format!("SSM weight {} has {} values, expected {}", name, got, want));
+        }
+        Ok(Self {
+            d_inner,
+            d_state,
+            dt_rank,
+            a_log,
+            x_proj: Matrix::new(dt_rank + 2 * d_state, d_inner, x_proj),
+            dt_proj: Matrix::new(d_inner, dt_rank, dt_proj),
+            dt_bias,
+            d,
+            fp_mode: FpMode::Native,
+        })
+    }
+
+    /// Store the projections as `quantization`; A, D and the step bias
+    /// are small and stay f32
+    pub fn quantized(self, quantization: Quantization) -> Self {
+        Self {
+            x_proj: self.x_proj.quantized(quantization),
+            dt_proj: self.dt_proj.quantized(quantization),
+            ..self
+        }
+    }
+
+    pub fn quantization(&self) -> Quantization {
+        self.x_proj.quantization()
+    }
+
+    pub fn with_fp_mode(self, fp_mode: FpMode) -> Self {
+        Self { fp_mode, ..self }
+    }
+
+    pub fn fp_mode(&self) -> FpMode {
+        self.fp_mode
+    }
+
+    /// Weights as they are evaluated, quantized ones decoded, in checkpoint
+    /// order: A_log, x_proj, dt_proj, dt_bias, D
+    pub fn tensors(&self) -> Vec<Cow<'_, [f32]>> {
+        vec![
+            Cow::Borrowed(&self.a_log),
+            self.x_proj.to_f32(),
+            self.dt_proj.to_f32(),
+            Cow::Borrowed(&self.dt_bias),
+            Cow::Borrowed(&self.d),
+        ]
+    }
+
+    /// Bytes held by the weights
+    pub fn size_bytes(&self) -> usize {
+        self.x_proj.size_bytes() + self.dt_proj.size_bytes() + 4 * (self.a_log.len() + self.dt_bias.len() + self.d.len())
+    }
+
+    pub fn d_inner(&self) -> usize {
+        self.d_inner
+    }
+
+    pub fn d_state(&self) -> usize {
+        self.d_state
+    }
+
+    pub fn dt_rank(&self) -> usize {
+        self.dt_rank
+    }
+
+    /// Continuous A = -exp(A_log), row-major (d_inner, d_state)
+    pub fn a(&self) -> Vec<f32> {
+        self.a_log.iter().map(|&log| -self.fp_mode.exp(log)).collect()
+    }
+
+    /// Step size delta of each channel for a zero input, softplus(dt_bias)
+    pub fn step_sizes(&self) -> Vec<f32> {
+        self.dt_bias.iter().map(|&bias| softplus(self.fp_mode, bias)).collect()
+    }
+
+    /// A zero hidden state, (d_inner, d_state)
+    pub fn zero_state(&self) -> Vec<f32> {
+        vec![0.0; self.d_inner * self.d_state]
+    }
+
+    /// Advance `state` by one token and return y_t
+    pub fn step(&self, x: &[f32], state: &mut [f32]) -> Vec<f32> {
+        debug_assert_eq!(x.len(), self.d_inner);
+        debug_assert_eq!(state.len(), self.d_inner * self.d_state);
+        let n = self.d_state;
+        let Projection { delta, b, c } = self.project(x);
+
+        let mut y = Vec::with_capacity(self.d_inner);
+        for i in 0..self.d_inner {
+            let h = &mut state[i * n..(i + 1) * n];
+            let a_log = &self.a_log[i * n..(i + 1) * n];
+            let mut y_i = 0.0f32;
+            for j in 0..n {
+                let a_bar = self.fp_mode.exp(delta[i] * -self.fp_mode.exp(a_log[j]));
+                h[j] = a_bar * h[j] + delta[i] * b[j] * x[i];
+                y_i += c[j] * h[j];
+            }
+            y.push(y_i + self.d[i] * x[i]);
+        }
+        y
+    }
+
+    /// Run the recurrence over a whole sequence from `state`, leaving the
+    /// final hidden state there; one output per input
+    pub fn scan(&self, xs: &[Vec<f32>], state: &mut [f32]) -> Vec<Vec<f32>> {
+        xs.iter().map(|x| self.step(x, state)).collect()
+    }
+
+    /// `scan` in chunks of `chunk_len` tokens (at least 1), using the SSD
+    /// form described above. Agrees with `scan` up to float rounding, and
+    /// is bit-for-bit reproducible for a given `chunk_len`.
+    pub fn scan_chunked(&self, xs: &[Vec<f32>], state: &mut [f32], chunk_len: usize) -> Vec<Vec<f32>> {
+        debug_assert_eq!(state.len(), self.d_inner * self.d_state);
+        let chunk_len = chunk_len.max(1);
+        let a = self.a();
+
+        // Phase one: projections, then each chunk from a zero state
+        let project = |x: &Vec<f32>| self.project(x);
+        #[cfg(feature = "parallel")]
+        let projections: Vec<Projection> = {
+            use rayon::prelude::*;
+            xs.par_iter().map(project).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let projections: Vec<Projection> = xs.iter().map(project).collect();
+
+        let count = xs.len().div_ceil(chunk_len);
+        let chunk = |k: usize| {
+            let range = k * chunk_len..((k + 1) * chunk_len).min(xs.len());
+            (&xs[range.clone()], &projections[range])
+        };
+        let scan = |k: usize| {
+            let (xs, proj) = chunk(k);
+            self.scan_chunk(&a, xs, proj)
+        };
+        #[cfg(feature = "parallel")]
+        let scans: Vec<ChunkScan> = {
+            use rayon::prelude::*;
+            (0..count).into_par_iter().map(scan).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let scans: Vec<ChunkScan> = (0..count).map(scan).collect();
+
+        // Phase two: carry the true start state across chunk boundaries
+        let mut starts = Vec::with_capacity(count);
+        for chunk in &scans {
+            starts.push(state.to_vec());
+            for ((h, &decay), &local) in state.iter_mut().zip(&chunk.decay).zip(&chunk.state) {
+                *h = decay * *h + local;
+            }
+        }
+
+        // Phase three: add each chunk's start state to its outputs
+        let finish = |k: usize| {
+            let (xs, proj) = chunk(k);
+            self.finish_chunk(&a, xs, proj, &scans[k].y_diag, &starts[k])
+        };
+        #[cfg(feature = "parallel")]
+        let outputs: Vec<Vec<Vec<f32>>> = {
+            use rayon::prelude::*;
+            (0..count).into_par_iter().map(finish).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let outputs: Vec<Vec<Vec<f32>>> = (0..count).map(finish).collect();
+        outputs.into_iter().flatten().collect()
+    }
+
+    /// delta, B and C of one token: [dt_low | B | C] = x_proj x and
+    /// delta = softplus(dt_proj dt_low + dt_bias)
+    fn project(&self, x: &[f32]) -> Projection {
+        let x_dbl = self.x_proj.matvec_with(x, self.fp_mode);
+        let (dt_low, bc) = x_dbl.split_at(self.dt_rank);
+        let (b, c) = bc.split_at(self.d_state);
+        let delta = self
+            .dt_proj
+            .matvec_with(dt_low, self.fp_mode)
+            .iter()
+            .zip(&self.dt_bias)
+            .map(|(&dt, &bias)| softplus(self.fp_mode, dt + bias))
+            .collect();
+        Projection { delta, b: b.to_vec(), c: c.to_vec() }
+    }
+
+    /// Quadratic outputs and final state of one chunk from a zero state
+    fn scan_chunk(&self, a: &[f32], xs: &[Vec<f32>], proj: &[Projection]) -> ChunkScan {
+        let (d, n, len) = (self.d_inner, self.d_state, xs.len());
+        let mut y_diag = vec![0.0f32; len * d];
+        let mut state = vec![0.0f32; d * n];
+        let mut decay = vec![0.0f32; d * n];
+        let (mut a_bar, mut u) = (vec![0.0f32; len], vec![0.0f32; len]);
+        for i in 0..d {
+            for j in 0..n {
+                let k = i * n + j;
+                for t in 0..len {
+                    a_bar[t] = self.fp_mode.exp(proj[t].delta[i] * a[k]);
+                    u[t] = proj[t].delta[i] * proj[t].b[j] * xs[t][i];
+                }
+                // Row t of the masked matrix: exp(cum_t - cum_s) is the
+                // product of a_bar over (s, t], built up from the diagonal
+                for t in 0..len {
+                    let (mut h, mut w) = (0.0f32, 1.0f32);
+                    for s in (0..=t).rev() {
+                        h += w * u[s];
+                        w *= a_bar[s];
+                    }
+                    y_diag[t * d + i] += proj[t].c[j] * h;
+                    if t + 1 == len {
+                        state[k] = h;
+                        decay[k] = w;
+                    }
+                }
+            }
+        }
+        ChunkScan { y_diag, state, decay }
+    }
+
+    /// Outputs of one chunk given its true start state
+    fn finish_chunk(&self, a: &[f32], xs: &[Vec<f32>], proj: &[Projection], y_diag: &[f32], start: &[f32]) -> Vec<Vec<f32>> {
+        let (d, n) = (self.d_inner, self.d_state);
+        let mut y = y_diag.to_vec();
+        for i in 0..d {
+            for j in 0..n {
+                let k = i * n + j;
+                // exp(cum_t) h_0, with exp(cum_t) as a running product
+                let mut carried = start[k];
+                for (t, p) in proj.iter().enumerate() {
+                    carried *= self.fp_mode.exp(p.delta[i] * a[k]);
+                    y[t * d + i] += p.c[j] * carried;
+                }
+            }
+        }
+        y.chunks_exact(d.max(1))
+            .zip(xs)
+            .map(|(y, x)| y.iter().zip(x).zip(&self.d).map(|((&y, &x), &d)| y + d * x).collect())
+            .collect()
+    }
+}
+
+/// ln(1 + e^x), linear once e^x dwarfs 1
+fn softplus(mode: FpMode, x: f32) -> f32 {
+    if x > 20.0 {
+        x
+    } else {
+        mode.ln_1p(mode.exp(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One channel and one state with A_log = 0, so A = -1, and the given
+    /// x_proj rows for dt_low, B and C
+    fn tiny(x_proj: [f32; 3], dt_proj: f32, d: f32) -> SsmLayer {
+        let weights = SsmWeights {
+            a_log: vec![0.0],
+            x_proj: x_proj.to_vec(),
+            dt_proj: vec![dt_proj],
+            dt_bias: vec![0.0],
+            d: vec![d],
+        };
+        SsmLayer::from_weights(1, 1, 1, weights).unwrap()
+    }
+
+    #[test]
+    fn test_tiny_recurrence_matches_hand_computation() {
+        // dt_low = 0 so delta = softplus(0) = ln 2 and A_bar = 1/2; B = C = x
+        let ln2 = std::f32::consts::LN_2;
+        let layer = tiny([0.0, 1.0, 1.0], 0.0, 0.5);
+        let mut state = layer.zero_state();
+        let ys = layer.scan(&[vec![1.0], vec![2.0], vec![0.0]], &mut state);
+
+        // h1 = ln2 * B1 * x1, y1 = C1 h1 + D x1
+        let h1 = ln2;
+        assert!((ys[0][0] - (h1 + 0.5)).abs() < 1e-6);
+        // h2 = h1 / 2 + ln2 * 2 * 2, y2 = 2 h2 + 0.5 * 2
+        let h2 = h1 / 2.0 + ln2 * 4.0;
+        assert!((ys[1][0] - (2.0 * h2 + 1.0)).abs() < 1e-5);
+        // C3 = 0 hides the state from the output, which still decays
+        assert_eq!(ys[2][0], 0.0);
+        assert!((state[0] - h2 / 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_step_size_is_input_dependent() {
+        // dt_low = x and B = 0: the state only decays, by exp(-softplus(x)),
+        // so larger inputs take longer steps and forget faster
+        let layer = tiny([1.0, 0.0, 0.0], 1.0, 0.0);
+        let decay = |x: f32| {
+            let mut state = vec![1.0];
+            layer.step(&[x], &mut state);
+            state[0]
+        };
+        assert!((decay(0.0) - 0.5).abs() < 1e-6);
+        assert!(decay(3.0) < decay(0.0));
+        assert!(decay(-3.0) > decay(0.0));
+    }
+
+    #[test]
+    fn test_hippo_layer_is_stable_and_order_sensitive() {
+        let layer = SsmLayer::hippo(8, 4, 2);
+        assert!(layer.a().iter().all(|&a| a < 0.0));
+        let deltas: Vec<f32> = layer.dt_bias.iter().map(|&b| softplus(FpMode::Native, b)).collect();
+        assert!((deltas[0] - DT_MIN).abs() < 1e-6 && (deltas[7] - DT_MAX).abs() < 1e-5);
+
+        let xs: Vec<Vec<f32>> = (0..5).map(|t| (0..8).map(|i| ((t * 8 + i) as f32).sin()).collect()).collect();
+        let mut reversed = xs.clone();
+        reversed.reverse();
+        let (mut s1, mut s2) = (layer.zero_state(), layer.zero_state());
+        let forward = layer.scan(&xs, &mut s1);
+        assert_eq!(forward, layer.scan(&xs, &mut layer.zero_state()));
+        assert_ne!(forward.last(), layer.scan(&reversed, &mut s2).last());
+
+        assert!(SsmLayer::from_weights(2, 2, 1, SsmWeights::default()).is_err());
+    }
+
+    #[test]
+    fn test_chunked_scan_matches_sequential() {
+        let layer = SsmLayer::hippo(6, 4, 2);
+        let xs: Vec<Vec<f32>> = (0..53).map(|t| (0..6).map(|i| ((t * 6 + i) as f32 * 0.7).cos()).collect()).collect();
+        let mut start = layer.zero_state();
+        layer.scan(&xs[..5], &mut start);
+
+        let mut expected_state = start.clone();
+        let expected = layer.scan(&xs, &mut expected_state);
+        // Chunk lengths that divide the sequence, leave a remainder, or exceed it
+        for chunk_len in [1, 7, 16, 53, 100] {
+            let mut state = start.clone();
+            let ys = layer.scan_chunked(&xs, &mut state, chunk_len);
+            assert_eq!(ys.len(), xs.len());
+            for (y, e) in ys.iter().flatten().zip(expected.iter().flatten()) {
+                assert!((y - e).abs() < 1e-4, "chunk {}: {} vs {}", chunk_len, y, e);
+            }
+            for (h, e) in state.iter().zip(&expected_state) {
+                assert!((h - e).abs() < 1e-4, "chunk {}: {} vs {}", chunk_len, h, e);
+            }
+
+            let mut again = start.clone();
+            assert_eq!(layer.scan_chunked(&xs, &mut again, chunk_len), ys);
+            assert_eq!(again, state);
+        }
+
+        let mut state = start.clone();
+        assert!(layer.scan_chunked(&[], &mut state, SSD_CHUNK_LEN).is_empty());
+        assert_eq!(state, start);
+    }
+}
@@ -0,0 +1,157 @@
+//! Mamba-2 stability analysis
+//! AxiomHive Sovereign Manifold v2.1.0
+//! A is diagonal, so its eigenvalues are its entries and every quantity
+//! here is exact per mode. The continuous system decays when every entry
+//! is negative, i.e. when the spectral abscissa max Re(lambda) is below
+//! zero. The discretized transition A_bar = exp(delta A) is diagonal as
+//! well, so its spectral norm is max exp(delta a).
+//!
+//! The report also catches modes that are stable on paper but not in f32:
+//! once delta |a| is under half an ulp of 1, exp(delta a) rounds to
+//! exactly 1 and the mode never forgets. A = -exp(A_log) is negative by
+//! construction, but a very negative A_log underflows it to zero.
+
+use crate::mamba_block::MambaConfig;
+use crate::mamba_ssm::SsmLayer;
+use serde::{Deserialize, Serialize};
+
+/// Stability of one layer's SSM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerStability {
+    pub layer: usize,
+    /// Largest eigenvalue of the continuous A, the slowest mode
+    pub spectral_abscissa: f32,
+    /// Smallest eigenvalue of the continuous A, the fastest mode
+    pub min_eigenvalue: f32,
+    /// Smallest and largest step delta the norms were taken at
+    pub delta_range: (f32, f32),
+    /// Spectral (and every induced) norm of A_bar, the largest exp(delta a)
+    pub transition_norm: f32,
+    /// Modes with a >= 0, which never decay in continuous time
+    pub unstable_modes: usize,
+    /// Modes whose A_bar entry is 1 or more once computed in f32
+    pub non_decaying_modes: usize,
+    pub modes: usize,
+}
+
+impl LayerStability {
+    /// Analyze `ssm` at step `delta`, or at each channel's zero-input step
+    /// softplus(dt_bias) when `delta` is None
+    pub fn of(layer: usize, ssm: &SsmLayer, delta: Option<f32>) -> Self {
+        let a = ssm.a();
+        let steps = match delta {
+            Some(delta) => vec![delta; ssm.d_inner()],
+            None => ssm.step_sizes(),
+        };
+        let mode = ssm.fp_mode();
+        let n = ssm.d_state();
+        let a_bar: Vec<f32> = a.iter().enumerate().map(|(k, &a)| mode.exp(steps[k / n.max(1)] * a)).collect();
+
+        let max = |values: &[f32]| values.iter().fold(f32::NEG_INFINITY, |max, &v| max.max(v));
+        let min = |values: &[f32]| values.iter().fold(f32::INFINITY, |min, &v| min.min(v));
+        Self {
+            layer,
+            spectral_abscissa: max(&a),
+            min_eigenvalue: min(&a),
+            delta_range: (min(&steps), max(&steps)),
+            transition_norm: max(&a_bar),
+            // NaN counts against stability in both
+            unstable_modes: a.iter().filter(|&&a| a >= 0.0 || a.is_nan()).count(),
+            non_decaying_modes: a_bar.iter().filter(|&&a_bar| a_bar >= 1.0 || a_bar.is_nan()).count(),
+            modes: a.len(),
+        }
+    }
+
+    pub fn is_stable(&self) -> bool {
+        self.unstable_modes == 0 && self.non_decaying_modes == 0
+    }
+}
+
+/// Stability of a whole model, with a warning per problem found
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StabilityReport {
+    pub config: MambaConfig,
+    /// Step the discretized norms were taken at; None means each channel's
+    /// own zero-input step
+    pub delta: Option<f32>,
+    /// Every mode of every layer decays, continuous and discretized
+    pub is_stable: bool,
+    /// Largest of the layers' spectral abscissas
+    pub spectral_abscissa: f32,
+    /// Largest of the layers' transition norms
+    pub transition_norm: f32,
+    pub layers: Vec<LayerStability>,
+    pub warnings: Vec<String>,
+}
+
+impl StabilityReport {
+    pub fn new(config: MambaConfig, delta: Option<f32>, layers: Vec<LayerStability>) -> Self {
+        let at = match delta {
+            Some(delta) => format!("at delta = {}", delta),
+            None => "at its zero-input step".to_string(),
+        };
+        let mut warnings = Vec::new();
+        for layer in &layers {
+            if layer.unstable_modes > 0 {
+                warnings.push(format!(
+                    "Layer {}: {} of {} modes have A >= 0 (spectral abscissa {}) and never decay",
+                    layer.layer, layer.unstable_modes, layer.modes, layer.spectral_abscissa
+                ));
+            }
+            if layer.non_decaying_modes > layer.unstable_modes {
+                warnings.push(format!(
+                    "Layer {}: {} of {} modes have a discretized transition of 1 or more {} (norm {})",
+                    layer.layer, layer.non_decaying_modes, layer.modes, at, layer.transition_norm
+                ));
+            }
+        }
+        Self {
+            config,
+            delta,
+            is_stable: layers.iter().all(LayerStability::is_stable),
+            spectral_abscissa: layers.iter().fold(f32::NEG_INFINITY, |max, l| max.max(l.spectral_abscissa)),
+            transition_norm: layers.iter().fold(f32::NEG_INFINITY, |max, l| max.max(l.transition_norm)),
+            layers,
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mamba_ssm::SsmWeights;
+
+    #[test]
+    fn test_report_flags_marginal_and_vanishing_decay() {
+        // A = -1, -e^-20 and -e^-200, which underflows to zero; one channel
+        // whose zero-input step is ln 2
+        let weights = SsmWeights {
+            a_log: vec![0.0, -20.0, -200.0],
+            x_proj: vec![0.0; 7],
+            dt_proj: vec![0.0],
+            dt_bias: vec![0.0],
+            d: vec![1.0],
+        };
+        let ssm = SsmLayer::from_weights(1, 3, 1, weights).unwrap();
+        let zero_input = LayerStability::of(0, &ssm, None);
+        assert_eq!(zero_input.delta_range, (std::f32::consts::LN_2, std::f32::consts::LN_2));
+        assert_eq!(zero_input.spectral_abscissa, 0.0);
+        // exp(-ln 2 * e^-20) is 1 in f32, though that A is negative
+        assert_eq!((zero_input.unstable_modes, zero_input.non_decaying_modes), (1, 2));
+        assert_eq!(zero_input.transition_norm, 1.0);
+
+        let large_step = LayerStability::of(1, &ssm, Some(1e6));
+        assert_eq!((large_step.unstable_modes, large_step.non_decaying_modes), (1, 1));
+        assert!((large_step.min_eigenvalue + 1.0).abs() < 1e-6);
+
+        let report = StabilityReport::new(MambaConfig::new(2, 1, 3), None, vec![zero_input, large_step]);
+        assert!(!report.is_stable);
+        assert_eq!(report.warnings.len(), 3);
+        assert!(report.warnings[0].starts_with("Layer 0: 1 of 3 modes have A >= 0"));
+        assert!(report.warnings[1].contains("2 of 3 modes have a discretized transition of 1 or more at its zero-input step"));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["layers"][1]["non_decaying_modes"], 1);
+        assert_eq!(serde_json::from_value::<StabilityReport>(json).unwrap(), report);
+    }
+}
@@ -0,0 +1,361 @@
+//! Mamba-2 tokenizer
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Byte-level BPE as used by the GPT-NeoX tokenizer that ships with the
+//! Mamba checkpoints. Vocabularies load from a Hugging Face
+//! `tokenizer.json`; without one, the fallback vocabulary has one token per
+//! byte plus `<|endoftext|>`, so any text still encodes offline.
+//!
+//! Pre-tokenization follows the GPT-2 split (an optional leading space
+//! followed by letters, digits or other symbols; whitespace runs) without
+//! the contraction rules, which only change where a few merges may apply.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// End-of-text marker of the fallback vocabulary and GPT-NeoX
+pub const EOS_TOKEN: &str = "<|endoftext|>";
+
+/// Text to token ids and back
+pub trait Tokenizer: Send + Sync {
+    fn encode(&self, text: &str) -> Vec<u32>;
+
+    /// Token ids to text; invalid UTF-8 becomes U+FFFD
+    fn decode(&self, ids: &[u32]) -> String;
+
+    fn vocab_size(&self) -> usize;
+
+    fn eos_token(&self) -> Option<u32>;
+}
+
+/// Byte-level BPE with ranked merges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteBpeTokenizer {
+    /// Bytes of every token by id
+    tokens: Vec<Vec<u8>>,
+    /// Id of each single byte
+    byte_ids: [u32; 256],
+    /// Rank and result of every merge, by the pair it joins
+    merges: HashMap<(u32, u32), (usize, u32)>,
+    /// Special tokens, matched verbatim before pre-tokenization
+    specials: Vec<(String, u32)>,
+    eos: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct TokenizerJson {
+    model: BpeModel,
+    #[serde(default)]
+    added_tokens: Vec<AddedToken>,
+}
+
+#[derive(Deserialize)]
+struct BpeModel {
+    vocab: HashMap<String, u32>,
+    merges: Vec<Merge>,
+}
+
+/// `"a b"` in older files, `["a", "b"]` in newer ones
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Merge {
+    Joined(String),
+    Pair(String, String),
+}
+
+#[derive(Deserialize)]
+struct AddedToken {
+    id: u32,
+    content: String,
+}
+
+impl ByteBpeTokenizer {
+    /// The fallback vocabulary: ids 0-255 are the bytes, 256 is
+    /// `<|endoftext|>`, and there are no merges
+    pub fn byte_level() -> Self {
+        let mut tokens: Vec<Vec<u8>> = (0..=255u8).map(|b| vec![b]).collect();
+        tokens.push(EOS_TOKEN.as_bytes().to_vec());
+        Self {
+            tokens,
+            byte_ids: std::array::from_fn(|b| b as u32),
+            merges: HashMap::new(),
+            specials: vec![(EOS_TOKEN.to_string(), 256)],
+            eos: Some(256),
+        }
+    }
+
+    /// Load the BPE model of a Hugging Face `tokenizer.json`
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file: TokenizerJson = serde_json::from_str(json).map_err(|e| format!("Invalid tokenizer.json: {}", e))?;
+        let unicode_bytes = unicode_to_byte();
+        let to_bytes = |token: &str| -> Result<Vec<u8>, String> {
+            token
+                .chars()
+                .map(|c| unicode_bytes.get(&c).copied().ok_or_else(|| format!("Token {:?} is not byte-level", token)))
+                .collect()
+        };
+
+        let size = file.model.vocab.values().chain(file.added_tokens.iter().map(|t| &t.id)).max().map_or(0, |&id| id as usize + 1);
+        let mut tokens: Vec<Option<Vec<u8>>> = vec![None; size];
+        let mut specials = Vec::new();
+        for added in &file.added_tokens {
+            tokens[added.id as usize] = Some(added.content.as_bytes().to_vec());
+            specials.push((added.content.clone(), added.id));
+        }
+        for (token, &id) in &file.model.vocab {
+            if specials.iter().all(|(_, special)| *special != id) {
+                tokens[id as usize] = Some(to_bytes(token)?);
+            }
+        }
+        let tokens: Vec<Vec<u8>> = tokens
+            .into_iter()
+            .enumerate()
+            .map(|(id, token)| token.ok_or_else(|| format!("Vocabulary has no token {}", id)))
+            .collect::<Result<_, _>>()?;
+
+        let mut byte_ids = [0u32; 256];
+        for (b, id) in byte_ids.iter_mut().enumerate() {
+            let symbol = byte_to_unicode()[b].to_string();
+            *id = *file.model.vocab.get(&symbol).ok_or_else(|| format!("Vocabulary lacks byte {:#04x}", b))?;
+        }
+
+        let mut merges = HashMap::with_capacity(file.model.merges.len());
+        for (rank, merge) in file.model.merges.into_iter().enumerate() {
+            let (left, right) = match merge {
+                Merge::Pair(left, right) => (left, right),
+                Merge::Joined(joined) => match joined.split_once(' ') {
+                    Some((left, right)) => (left.to_string(), right.to_string()),
+                    None => return Err(format!("Malformed merge {:?}", joined)),
+                },
+            };
+            let id = |token: &str| file.model.vocab.get(token).copied().ok_or_else(|| format!("Merge uses unknown token {:?}", token));
+            let merged = format!("{}{}", left, right);
+            merges.entry((id(&left)?, id(&right)?)).or_insert((rank, id(&merged)?));
+        }
+
+        // Longest first, so overlapping specials match greedily
+        specials.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.1.cmp(&b.1)));
+        let eos = specials.iter().find(|(content, _)| content == EOS_TOKEN).map(|&(_, id)| id);
+        Ok(Self { tokens, byte_ids, merges, specials, eos })
+    }
+
+    /// Read and parse a `tokenizer.json`
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Self::from_json(&json)
+    }
+
+    /// Bytes of token `id`, if it exists
+    pub fn token_bytes(&self, id: u32) -> Option<&[u8]> {
+        self.tokens.get(id as usize).map(Vec::as_slice)
+    }
+
+    /// Apply merges to one pre-token, lowest rank first
+    fn encode_piece(&self, piece: &[u8], ids: &mut Vec<u32>) {
+        let mut parts: Vec<u32> = piece.iter().map(|&b| self.byte_ids[b as usize]).collect();
+        while parts.len() > 1 {
+            let best = parts
+                .windows(2)
+                .enumerate()
+                .filter_map(|(i, pair)| self.merges.get(&(pair[0], pair[1])).map(|&(rank, id)| (rank, i, id)))
+                .min();
+            match best {
+                Some((_, i, id)) => {
+                    parts[i] = id;
+                    parts.remove(i + 1);
+                }
+                None => break,
+            }
+        }
+        ids.extend(parts);
+    }
+}
+
+impl Tokenizer for ByteBpeTokenizer {
+    fn encode(&self, text: &str) -> Vec<u32> {
+        let mut ids = Vec::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            // The next special token, if any, splits the text
+            let next = self
+                .specials
+                .iter()
+                .filter_map(|(content, id)| rest.find(content.as_str()).map(|at| (at, content.len(), *id)))
+                .min_by_key(|&(at, len, _)| (at, std::cmp::Reverse(len)));
+            let (plain, special) = match next {
+                Some((at, len, id)) => (&rest[..at], Some((len, id))),
+                None => (rest, None),
+            };
+            for piece in pre_tokenize(plain) {
+                self.encode_piece(piece.as_bytes(), &mut ids);
+            }
+            match special {
+                Some((len, id)) => {
+                    ids.push(id);
+                    rest = &rest[plain.len() + len..];
+                }
+                None => break,
+            }
+        }
+        ids
+    }
+
+    fn decode(&self, ids: &[u32]) -> String {
+        let bytes: Vec<u8> = ids.iter().filter_map(|&id| self.token_bytes(id)).flatten().copied().collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    fn vocab_size(&self) -> usize {
+        self.tokens.len()
+    }
+
+    fn eos_token(&self) -> Option<u32> {
+        self.eos
+    }
+}
+
+/// Split like GPT-2: ` ?letters`, ` ?digits`, ` ?other symbols`, and
+/// whitespace runs, leaving a run's last space to the word after it
+fn pre_tokenize(text: &str) -> Vec<&str> {
+    #[derive(PartialEq, Clone, Copy)]
+    enum Class {
+        Letter,
+        Digit,
+        Space,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphabetic() {
+            Class::Letter
+        } else if c.is_numeric() {
+            Class::Digit
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut pieces = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = chars[i].0;
+        let mut kind = class(chars[i].1);
+        let mut j = i + 1;
+        if kind == Class::Space {
+            while j < chars.len() && class(chars[j].1) == Class::Space {
+                j += 1;
+            }
+            // A single space before a word belongs to that word
+            if j < chars.len() && chars[j - 1].1 == ' ' {
+                if j - 1 > i {
+                    pieces.push(&text[start..chars[j - 1].0]);
+                }
+                i = j - 1;
+                kind = class(chars[j].1);
+                j += 1;
+            } else {
+                let end = chars.get(j).map_or(text.len(), |&(at, _)| at);
+                pieces.push(&text[start..end]);
+                i = j;
+                continue;
+            }
+        }
+        let start = chars[i].0;
+        while j < chars.len() && class(chars[j].1) == kind {
+            j += 1;
+        }
+        let end = chars.get(j).map_or(text.len(), |&(at, _)| at);
+        pieces.push(&text[start..end]);
+        i = j;
+    }
+    pieces
+}
+
+/// GPT-2's reversible map from bytes to printable characters
+fn byte_to_unicode() -> &'static [char; 256] {
+    static TABLE: OnceLock<[char; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let printable = |b: u32| (0x21..=0x7e).contains(&b) || (0xa1..=0xac).contains(&b) || (0xae..=0xff).contains(&b);
+        let mut next = 256;
+        std::array::from_fn(|b| {
+            let b = b as u32;
+            if printable(b) {
+                char::from_u32(b).expect("Latin-1 code point")
+            } else {
+                next += 1;
+                char::from_u32(next - 1).expect("code point below 512")
+            }
+        })
+    })
+}
+
+fn unicode_to_byte() -> HashMap<char, u8> {
+    byte_to_unicode().iter().enumerate().map(|(b, &c)| (c, b as u8)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes for "a", "b", " " and "Ġ"-prefixed merges, in tokenizer.json form
+    fn tiny_json() -> String {
+        let mut vocab: Vec<(String, u32)> = byte_to_unicode()
+            .iter()
+            .enumerate()
+            .map(|(b, c)| (c.to_string(), b as u32))
+            .collect();
+        vocab.push(("ab".to_string(), 256));
+        vocab.push(("Ġab".to_string(), 257));
+        let vocab: serde_json::Map<String, serde_json::Value> = vocab.into_iter().map(|(k, v)| (k, v.into())).collect();
+        serde_json::json!({
+            "added_tokens": [{ "id": 258, "content": EOS_TOKEN }],
+            "model": { "vocab": vocab, "merges": ["a b", ["Ġ", "ab"]] }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_byte_level_fallback_round_trips() {
+        let tokenizer = ByteBpeTokenizer::byte_level();
+        assert_eq!(tokenizer.vocab_size(), 257);
+        let text = "Zero Entropy: ∑ C = 0";
+        let ids = tokenizer.encode(text);
+        assert_eq!(ids.len(), text.len());
+        assert_eq!(tokenizer.decode(&ids), text);
+
+        let ids = tokenizer.encode("end<|endoftext|>");
+        assert_eq!(ids.last(), Some(&256));
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn test_loaded_merges_apply_by_rank() {
+        let tokenizer = ByteBpeTokenizer::from_json(&tiny_json()).unwrap();
+        assert_eq!(tokenizer.vocab_size(), 259);
+        assert_eq!(tokenizer.eos_token(), Some(258));
+        assert_eq!(tokenizer.encode("ab"), vec![256]);
+        assert_eq!(tokenizer.encode("ab ab"), vec![256, 257]);
+        assert_eq!(tokenizer.encode("abc"), vec![256, b'c' as u32]);
+        assert_eq!(tokenizer.encode("ab<|endoftext|>"), vec![256, 258]);
+        assert_eq!(tokenizer.decode(&tokenizer.encode("ab ab, abab!")), "ab ab, abab!");
+    }
+
+    #[test]
+    fn test_pre_tokenize_keeps_leading_spaces() {
+        assert_eq!(pre_tokenize("Hello world  42!"), vec!["Hello", " world", " ", " 42", "!"]);
+        assert_eq!(pre_tokenize("a\n\nb "), vec!["a", "\n\n", "b", " "]);
+        assert_eq!(pre_tokenize("").len(), 0);
+    }
+
+    #[test]
+    fn test_invalid_vocabularies_are_rejected() {
+        assert!(ByteBpeTokenizer::from_json("{}").is_err());
+        let missing_byte = tiny_json().replace("\"!\":33,", "");
+        assert!(ByteBpeTokenizer::from_json(&missing_byte).is_err());
+        let bad_merge = tiny_json().replace("\"a b\"", "\"a z\"");
+        assert!(ByteBpeTokenizer::from_json(&bad_merge).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Mamba-2 weight files
+//! AxiomHive Sovereign Manifold v2.1.0
+//! Reads safetensors checkpoints: an 8-byte little-endian header length, a
+//! JSON header of {name: {dtype, shape, data_offsets}}, then the raw
+//! tensor data. Tensors are converted to f32 only when read, so a file can
+//! be inspected without decoding all of it. F32, F16 and BF16 are
+//! supported. GGUF files are recognised but not loaded.
+//!
+//! `SafeTensors::map` memory-maps the file instead of reading it, so only
+//! the pages of tensors actually read are brought in, and the kernel may
+//! drop them again under memory pressure.
+//!
+//! Tensor names follow the reference checkpoints, e.g.
+//! `backbone.layers.0.mixer.A_log`.
+
+use memmap2::Mmap;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Name of the token embedding matrix
+pub const EMBEDDING: &str = "backbone.embedding.weight";
+
+/// Name of the final RMSNorm weight
+pub const NORM_F: &str = "backbone.norm_f.weight";
+
+/// Name of tensor `name` in layer `layer`, e.g. `mixer.x_proj.weight`
+pub fn layer_tensor(layer: usize, name: &str) -> String {
+    format!("backbone.layers.{}.{}", layer, name)
+}
+
+/// A tensor the model expects, with its exact shape
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorSpec {
+    pub name: String,
+    pub shape: Vec<usize>,
+}
+
+impl TensorSpec {
+    pub fn new(name: impl Into<String>, shape: &[usize]) -> Self {
+        Self { name: name.into(), shape: shape.to_vec() }
+    }
+}
+
+/// Element types this reader decodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum DType {
+    F32,
+    F16,
+    BF16,
+}
+
+impl DType {
+    pub fn size(self) -> usize {
+        match self {
+            DType::F32 => 4,
+            DType::F16 | DType::BF16 => 2,
+        }
+    }
+}
+
+/// Header entry of one tensor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorInfo {
+    pub dtype: DType,
+    pub shape: Vec<usize>,
+    /// Byte range within the data section
+    pub offsets: (usize, usize),
+}
+
+#[derive(Deserialize)]
+struct RawTensorInfo {
+    dtype: String,
+    shape: Vec<usize>,
+    data_offsets: (usize, usize),
+}
+
+/// Where the bytes of a `SafeTensors` live
+#[derive(Debug, Clone)]
+enum Bytes {
+    Owned(Vec<u8>),
+    Mapped(Arc<Mmap>),
+}
+
+impl Bytes {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Bytes::Owned(bytes) => bytes,
+            Bytes::Mapped(map) => map,
+        }
+    }
+}
+
+/// A parsed safetensors file
+#[derive(Debug, Clone)]
+pub struct SafeTensors {
+    tensors: BTreeMap<String, TensorInfo>,
+    bytes: Bytes,
+    /// Offset of the data section in `bytes`
+    data_start: usize,
+}
+
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+impl SafeTensors {
+    /// Parse a whole file's bytes, validating every header entry
+    pub fn parse(bytes: Vec<u8>) -> Result<Self, String> {
+        Self::from_bytes(Bytes::Owned(bytes))
+    }
+
+    fn from_bytes(bytes: Bytes) -> Result<Self, String> {
+        let (tensors, data_start) = Self::parse_header(bytes.as_slice())?;
+        Ok(Self { tensors, bytes, data_start })
+    }
+
+    fn parse_header(bytes: &[u8]) -> Result<(BTreeMap<String, TensorInfo>, usize), String> {
+        if bytes.starts_with(GGUF_MAGIC) {
+            return Err("GGUF checkpoints are not supported; convert to safetensors".to_string());
+        }
+        let header_len = bytes
+            .get(..8)
+            .map(|len| u64::from_le_bytes(len.try_into().expect("8-byte length")) as usize)
+            .ok_or("File is too short for a safetensors header")?;
+        let header = bytes
+            .get(8..8usize.saturating_add(header_len))
+            .ok_or("Safetensors header runs past the end of the file")?;
+        let raw: BTreeMap<String, serde_json::Value> =
+            serde_json::from_slice(header).map_err(|e| format!("Invalid safetensors header: {}", e))?;
+
+        let data_len = bytes.len() - 8 - header_len;
+        let mut tensors = BTreeMap::new();
+        for (name, value) in raw {
+            if name == "__metadata__" {
+                continue;
+            }
+            let info: RawTensorInfo =
+                serde_json::from_value(value).map_err(|e| format!("Invalid entry for {}: {}", name, e))?;
+            let dtype: DType = serde_json::from_value(serde_json::Value::String(info.dtype.clone()))
+                .map_err(|_| format!("Tensor {} has unsupported dtype {}", name, info.dtype))?;
+            let (start, end) = info.data_offsets;
+            let elements = info.shape.iter().try_fold(1usize, |acc, &d| acc.checked_mul(d));
+            if start > end || end > data_len || elements.and_then(|e| e.checked_mul(dtype.size())) != Some(end - start) {
+                return Err(format!("Tensor {} has inconsistent offsets for its shape", name));
+            }
+            tensors.insert(name, TensorInfo { dtype, shape: info.shape, offsets: (start, end) });
+        }
+        Ok((tensors, 8 + header_len))
+    }
+
+    /// Read and parse a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Self::parse(bytes)
+    }
+
+    /// Map a file read-only and parse its header; tensor data is paged in
+    /// as it is read. As with any memory map, the file must not be
+    /// truncated or modified while the map is alive.
+    pub fn map(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let io_error = |e: std::io::Error| format!("Cannot map {}: {}", path.display(), e);
+        let file = File::open(path).map_err(io_error)?;
+        // Zero-length files cannot be mapped on every platform
+        if file.metadata().map_err(io_error)?.len() == 0 {
+            return Self::parse(Vec::new());
+        }
+        // SAFETY: the map is read-only and only exposed as `&[u8]` borrowed
+        // from `self`; concurrent modification of the file is documented
+        // as unsupported above.
+        let map = unsafe { Mmap::map(&file) }.map_err(io_error)?;
+        Self::from_bytes(Bytes::Mapped(Arc::new(map)))
+    }
+
+    /// Whether the file is memory-mapped rather than held in memory
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.bytes, Bytes::Mapped(_))
+    }
+
+    /// Size of the whole file, header included
+    pub fn file_size(&self) -> usize {
+        self.bytes.as_slice().len()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tensors.keys().map(String::as_str)
+    }
+
+    pub fn info(&self, name: &str) -> Option<&TensorInfo> {
+        self.tensors.get(name)
+    }
+
+    /// Tensor `name` as f32, whatever its stored type
+    pub fn read(&self, name: &str) -> Result<Vec<f32>, String> {
+        let info = self.info(name).ok_or_else(|| format!("Checkpoint has no tensor {}", name))?;
+        let bytes = &self.bytes.as_slice()[self.data_start + info.offsets.0..self.data_start + info.offsets.1];
+        Ok(match info.dtype {
+            DType::F32 => bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().expect("4-byte element")))
+                .collect(),
+            DType::F16 => bytes
+                .chunks_exact(2)
+                .map(|b| f16_to_f32(u16::from_le_bytes([b[0], b[1]])))
+                .collect(),
+            DType::BF16 => bytes
+                .chunks_exact(2)
+                .map(|b| f32::from_bits((u16::from_le_bytes([b[0], b[1]]) as u32) << 16))
+                .collect(),
+        })
+    }
+
+    /// Tensor `spec.name`, which must have exactly `spec.shape`
+    pub fn read_spec(&self, spec: &TensorSpec) -> Result<Vec<f32>, String> {
+        let info = self.info(&spec.name).ok_or_else(|| format!("Checkpoint has no tensor {}", spec.name))?;
+        if info.shape != spec.shape {
+            return Err(format!("Tensor {} has shape {:?}, expected {:?}", spec.name, info.shape, spec.shape));
+        }
+        self.read(&spec.name)
+    }
+}
+
+/// IEEE half to single precision, exactly
+pub(crate) fn f16_to_f32(bits: u16) -> f32 {
+    let negative = bits & 0x8000 != 0;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+    if exponent == 0 {
+        // Zero or subnormal: mantissa * 2^-24
+        let value = mantissa as f32 * 2f32.powi(-24);
+        return if negative { -value } else { value };
+    }
+    let magnitude = if exponent == 0x1f {
+        0x7f80_0000 | (mantissa << 13)
+    } else {
+        ((exponent + 127 - 15) << 23) | (mantissa << 13)
+    };
+    f32::from_bits(((negative as u32) << 31) | magnitude)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Serialize f32 tensors as a safetensors file
+    pub(crate) fn safetensors_bytes(tensors: &[(&str, Vec<usize>, Vec<f32>)]) -> Vec<u8> {
+        let mut header = serde_json::Map::new();
+        let mut data = Vec::new();
+        for (name, shape, values) in tensors {
+            let start = data.len();
+            data.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+            header.insert(
+                name.to_string(),
+                serde_json::json!({ "dtype": "F32", "shape": shape, "data_offsets": [start, data.len()] }),
+            );
+        }
+        header.insert("__metadata__".to_string(), serde_json::json!({ "format": "pt" }));
+        let header = serde_json::to_vec(&header).unwrap();
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend(header);
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn test_tensors_parse_and_validate() {
+        let file = SafeTensors::parse(safetensors_bytes(&[("a", vec![2, 2], vec![1.0, 2.0, 3.0, 4.0])])).unwrap();
+        assert_eq!(file.names().collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(file.read("a").unwrap(), vec![1.0, 2.0, 3.0, 4.0]);
+        assert!(file.read_spec(&TensorSpec::new("a", &[2, 2])).is_ok());
+        assert!(file.read_spec(&TensorSpec::new("a", &[4])).unwrap_err().contains("shape"));
+        assert!(file.read("b").is_err());
+
+        let mut truncated = safetensors_bytes(&[("a", vec![2, 2], vec![1.0; 4])]);
+        truncated.pop();
+        assert!(SafeTensors::parse(truncated).is_err());
+        assert!(SafeTensors::parse(b"GGUF\x03\0\0\0".to_vec()).unwrap_err().contains("GGUF"));
+        assert!(SafeTensors::parse(vec![1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_mapped_files_read_like_loaded_ones() {
+        let path = std::env::temp_dir().join(format!("axiomhive-mapped-{}.safetensors", std::process::id()));
+        std::fs::write(&path, safetensors_bytes(&[("a", vec![3], vec![1.0, -2.0, 0.5]), ("b", vec![1], vec![7.0])])).unwrap();
+        let (mapped, loaded) = (SafeTensors::map(&path), SafeTensors::load(&path));
+        std::fs::remove_file(&path).unwrap();
+        let (mapped, loaded) = (mapped.unwrap(), loaded.unwrap());
+        assert!(mapped.is_mapped() && !loaded.is_mapped());
+        assert_eq!(mapped.file_size(), loaded.file_size());
+        for name in ["a", "b"] {
+            assert_eq!(mapped.read(name).unwrap(), loaded.read(name).unwrap());
+        }
+        assert!(SafeTensors::map(std::env::temp_dir().join("axiomhive-missing.safetensors")).unwrap_err().contains("Cannot map"));
+    }
+
+    #[test]
+    fn test_half_precision_decodes_exactly() {
+        for (bits, value) in [(0x3c00, 1.0), (0xc000, -2.0), (0x3555, 0.333_251_95), (0x0001, 2f32.powi(-24)), (0x7bff, 65504.0)] {
+            assert_eq!(f16_to_f32(bits), value, "{:#06x}", bits);
+        }
+        assert!(f16_to_f32(0x7c00).is_infinite() && f16_to_f32(0x7e00).is_nan());
+        assert_eq!(f16_to_f32(0x8000).to_bits(), (-0.0f32).to_bits());
+
+        // One F16 and one BF16 element, both 1.0
+        let header = br#"{"h":{"dtype":"F16","shape":[1],"data_offsets":[0,2]},"b":{"dtype":"BF16","shape":[1],"data_offsets":[2,4]}}"#;
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header);
+        bytes.extend([0x00, 0x3c, 0x80, 0x3f]);
+        let file = SafeTensors::parse(bytes).unwrap();
+        assert_eq!(file.read("h").unwrap(), vec![1.0]);
+        assert_eq!(file.read("b").unwrap(), vec![1.0]);
+    }
+}
@@ -0,0 +1,93 @@
+//! Known-answer vectors for the FHE formats
+//! Keys and deterministic ciphertexts derived from the frozen seed are
+//! pinned by SHA-256 in `data/fhe_known_answers.json`, and one complete
+//! Small-preset ciphertext is stored verbatim. A refactor that changes key
+//! derivation, encryption or any serialized layout fails here; if the change
+//! is intended, regenerate the vectors and say so in the commit.
+//!
+//! The crate is a binary, so the modules are compiled in by path.
+
+#![allow(dead_code)]
+
+#[path = "../src/fhe_core.rs"]
+mod fhe_core;
+#[path = "../src/fhe_rlwe.rs"]
+mod fhe_rlwe;
+
+use fhe_core::{Ciphertext, DeoxysFHE, ParamPreset};
+use fhe_rlwe::{DeoxysRlwe, RlweParams};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+#[derive(Deserialize)]
+struct KnownAnswers {
+    seed: String,
+    lwe: Vec<LweVectors>,
+    rlwe: RlweVectors,
+    small_ciphertext_42: Ciphertext,
+}
+
+#[derive(Deserialize)]
+struct LweVectors {
+    preset: ParamPreset,
+    public_key_sha256: String,
+    secret_key_sha256: String,
+    ciphertexts: Vec<CiphertextVector>,
+}
+
+#[derive(Deserialize)]
+struct RlweVectors {
+    n: usize,
+    log_t: u32,
+    ciphertexts: Vec<CiphertextVector>,
+}
+
+#[derive(Deserialize)]
+struct CiphertextVector {
+    message: i32,
+    sha256: String,
+}
+
+fn known_answers() -> KnownAnswers {
+    serde_json::from_str(include_str!("data/fhe_known_answers.json")).expect("valid known-answer file")
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn lwe_keys_and_ciphertexts_match_vectors() {
+    let kat = known_answers();
+    for vectors in &kat.lwe {
+        let fhe = DeoxysFHE::with_params(Some(kat.seed.as_bytes()), vectors.preset.params());
+        assert_eq!(sha256_hex(&fhe.public_key().to_bytes()), vectors.public_key_sha256, "{:?} public key", vectors.preset);
+        assert_eq!(sha256_hex(&fhe.secret_key().to_bytes()), vectors.secret_key_sha256, "{:?} secret key", vectors.preset);
+        for vector in &vectors.ciphertexts {
+            let ct = fhe.encrypt_deterministic(vector.message).unwrap();
+            let json = serde_json::to_string(&ct).unwrap();
+            assert_eq!(sha256_hex(json.as_bytes()), vector.sha256, "{:?} message {}", vectors.preset, vector.message);
+            assert_eq!(fhe.decrypt_checked(ct).unwrap(), vector.message);
+        }
+    }
+}
+
+#[test]
+fn stored_ciphertext_still_parses_and_decrypts() {
+    let kat = known_answers();
+    let fhe = DeoxysFHE::with_params(Some(kat.seed.as_bytes()), ParamPreset::Small.params());
+    assert_eq!(kat.small_ciphertext_42, fhe.encrypt_deterministic(42).unwrap());
+    assert_eq!(fhe.decrypt_checked(kat.small_ciphertext_42).unwrap(), 42);
+}
+
+#[test]
+fn rlwe_ciphertexts_match_vectors() {
+    let kat = known_answers();
+    let params = RlweParams::new(kat.rlwe.n, kat.rlwe.log_t).unwrap();
+    let fhe = DeoxysRlwe::with_params(Some(kat.seed.as_bytes()), params);
+    for vector in &kat.rlwe.ciphertexts {
+        let ct = fhe.encrypt_deterministic(vector.message).unwrap();
+        assert_eq!(sha256_hex(&ct.to_bytes()), vector.sha256, "ring message {}", vector.message);
+        assert_eq!(fhe.decrypt_checked(ct).unwrap(), vector.message);
+    }
+}
@@ -0,0 +1,145 @@
+//! Property tests for the FHE schemes: round-trips, homomorphic arithmetic
+//! and serialization over arbitrary messages, plus an exhaustive pass over
+//! every message of the Small preset.
+//!
+//! The crate is a binary, so the modules are compiled in by path.
+
+#![allow(dead_code)]
+
+#[path = "../src/fhe_core.rs"]
+mod fhe_core;
+#[path = "../src/fhe_batch.rs"]
+mod fhe_batch;
+#[path = "../src/fhe_rlwe.rs"]
+mod fhe_rlwe;
+
+use fhe_batch::PackedCiphertext;
+use fhe_core::{Ciphertext, DeoxysFHE, ParamPreset, PublicKey, SecretKey};
+use fhe_rlwe::{DeoxysRlwe, RlweCiphertext};
+use proptest::prelude::*;
+use rand_core::OsRng;
+use std::sync::OnceLock;
+
+/// Keygen per case would dominate the run, so each preset's instance is
+/// built once
+fn instance(preset: ParamPreset) -> &'static DeoxysFHE {
+    static INSTANCES: OnceLock<Vec<DeoxysFHE>> = OnceLock::new();
+    let instances = INSTANCES.get_or_init(|| {
+        ParamPreset::ALL
+            .iter()
+            .map(|preset| DeoxysFHE::with_params(None, preset.params()))
+            .collect()
+    });
+    &instances[ParamPreset::ALL.iter().position(|&p| p == preset).expect("known preset")]
+}
+
+fn ring() -> &'static DeoxysRlwe {
+    static RING: OnceLock<DeoxysRlwe> = OnceLock::new();
+    RING.get_or_init(|| DeoxysRlwe::new(None))
+}
+
+fn any_preset() -> impl Strategy<Value = ParamPreset> {
+    prop::sample::select(ParamPreset::ALL.to_vec())
+}
+
+/// A preset and a message below its T
+fn preset_and_message() -> impl Strategy<Value = (ParamPreset, i32)> {
+    any_preset().prop_flat_map(|preset| (Just(preset), 0..preset.params().t()))
+}
+
+/// A preset and two messages below its T
+fn preset_and_pair() -> impl Strategy<Value = (ParamPreset, i32, i32)> {
+    any_preset().prop_flat_map(|preset| {
+        let t = preset.params().t();
+        (Just(preset), 0..t, 0..t)
+    })
+}
+
+#[test]
+fn every_small_message_round_trips() {
+    let fhe = instance(ParamPreset::Small);
+    for m in 0..fhe.params().t() {
+        let ct = fhe.encrypt_with_rng(m, &mut OsRng).unwrap();
+        assert_eq!(fhe.decrypt_checked(ct).unwrap(), m);
+        assert_eq!(fhe.decrypt_checked(fhe.encrypt_deterministic(m).unwrap()).unwrap(), m);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn encryption_round_trips((preset, m) in preset_and_message()) {
+        let fhe = instance(preset);
+        let ct = fhe.encrypt_with_rng(m, &mut OsRng).unwrap();
+        prop_assert_eq!(fhe.decrypt_checked(ct).unwrap(), m);
+    }
+
+    #[test]
+    fn addition_and_subtraction_wrap_modulo_t((preset, a, b) in preset_and_pair()) {
+        let fhe = instance(preset);
+        let t = fhe.params().t();
+        let (ct_a, ct_b) = (fhe.encrypt_with_rng(a, &mut OsRng).unwrap(), fhe.encrypt_with_rng(b, &mut OsRng).unwrap());
+        prop_assert_eq!(fhe.decrypt_checked(fhe.add(&ct_a, &ct_b).unwrap()).unwrap(), (a + b) % t);
+        prop_assert_eq!(fhe.decrypt_checked(fhe.sub(&ct_a, &ct_b).unwrap()).unwrap(), (a - b).rem_euclid(t));
+    }
+
+    #[test]
+    fn plaintext_multiplication_wraps_modulo_t((preset, m, k) in preset_and_pair()) {
+        let fhe = instance(preset);
+        let t = fhe.params().t() as i64;
+        let ct = fhe.encrypt_with_rng(m, &mut OsRng).unwrap();
+        let expected = (m as i64 * k as i64).rem_euclid(t) as i32;
+        prop_assert_eq!(fhe.decrypt_checked(fhe.mul_plain(&ct, k).unwrap()).unwrap(), expected);
+    }
+
+    #[test]
+    fn ciphertexts_and_keys_survive_serialization((preset, m) in preset_and_message()) {
+        let fhe = instance(preset);
+        let ct = fhe.encrypt_with_rng(m, &mut OsRng).unwrap();
+        let restored: Ciphertext = serde_json::from_str(&serde_json::to_string(&ct).unwrap()).unwrap();
+        prop_assert_eq!(&restored, &ct);
+        prop_assert_eq!(fhe.decrypt_checked(restored).unwrap(), m);
+
+        prop_assert_eq!(&PublicKey::from_bytes(&fhe.public_key().to_bytes()).unwrap(), fhe.public_key());
+        prop_assert_eq!(&SecretKey::from_bytes(&fhe.secret_key().to_bytes()).unwrap(), fhe.secret_key());
+    }
+
+    #[test]
+    fn byte_strings_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        let fhe = instance(ParamPreset::Default);
+        let cts = fhe.encrypt_bytes(&bytes, &mut OsRng).unwrap();
+        prop_assert_eq!(fhe.decrypt_bytes(&cts).unwrap(), bytes);
+    }
+
+    #[test]
+    fn packed_slots_add_element_wise(pairs in prop::collection::vec((0..256i32, 0..256i32), 1..16)) {
+        let fhe = instance(ParamPreset::Small);
+        let (public, secret) = fhe.batch_keys(pairs.len()).unwrap();
+        let (xs, ys): (Vec<i32>, Vec<i32>) = pairs.iter().copied().unzip();
+        let sum = public.encrypt_batch(&xs, &mut OsRng).unwrap()
+            .add(&public.encrypt_batch(&ys, &mut OsRng).unwrap())
+            .unwrap();
+        let restored: PackedCiphertext = serde_json::from_str(&serde_json::to_string(&sum).unwrap()).unwrap();
+        let expected: Vec<i32> = pairs.iter().map(|(x, y)| (x + y) % 256).collect();
+        prop_assert_eq!(secret.decrypt_batch(&restored).unwrap(), expected);
+    }
+
+    #[test]
+    fn ring_polynomials_round_trip_and_add(
+        xs in prop::collection::vec(0..65536i32, 1..1024),
+        ys in prop::collection::vec(0..65536i32, 1..1024),
+    ) {
+        let fhe = ring();
+        let (ct_x, ct_y) = (fhe.encrypt_poly_with_rng(&xs, &mut OsRng).unwrap(), fhe.encrypt_poly_with_rng(&ys, &mut OsRng).unwrap());
+        let restored = RlweCiphertext::from_bytes(&ct_x.to_bytes()).unwrap();
+        prop_assert_eq!(&fhe.decrypt_poly_checked(&restored).unwrap()[..xs.len()], &xs[..]);
+
+        let sum = fhe.decrypt_poly_checked(&fhe.add(&ct_x, &ct_y).unwrap()).unwrap();
+        prop_assert!(sum.len() >= xs.len().max(ys.len()));
+        for (i, &total) in sum.iter().enumerate().take(xs.len().max(ys.len())) {
+            let (x, y) = (xs.get(i).copied().unwrap_or(0), ys.get(i).copied().unwrap_or(0));
+            prop_assert_eq!(total, (x + y) % 65536);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Row tokenizer throughput: memchr-based lexer vs the original
+//! `lines()` + `split(',')` approach.
+//!
+//! Run with `cargo bench -p toon-rs --bench tokenizer`; add
+//! `--features parallel` to include `parse_parallel` on a multi-table export.
+//!
+//! Delimiter scanning is no longer the bottleneck: on typical rows the
+//! remaining cost is allocating one owned `String` per field, which is
+//! required by `ToonValue::Schema`'s `Vec<Vec<String>>` row storage.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use toon_rs::ToonParser;
+
+/// Build a market-tick document with `rows` typical rows
+fn market_ticks(rows: usize) -> String {
+    let mut doc = format!("market_ticks [{}]{{symbol,price,vol,ts}}\n", rows);
+    for i in 0..rows {
+        doc.push_str(&format!("SYM{},{}.{:02},{},{}\n", i % 500, 100 + i % 900, i % 100, i * 7, 1_700_000_000 + i));
+    }
+    doc
+}
+
+/// Same shape with quoted free-text fields, exercising the escape path
+fn quoted_notes(rows: usize) -> String {
+    let mut doc = format!("notes [{}]{{id,text}}\n", rows);
+    for i in 0..rows {
+        doc.push_str(&format!("{},\"note {}, with \\\"quotes\\\" and commas, {}\"\n", i, i, i * 3));
+    }
+    doc
+}
+
+/// Many independent tables, as in a multi-table export
+fn multi_table(tables: usize, rows: usize) -> String {
+    let mut doc = String::new();
+    for t in 0..tables {
+        doc.push_str(&format!("table_{} [{}]{{symbol,price,vol,ts}}\n", t, rows));
+        for i in 0..rows {
+            doc.push_str(&format!("SYM{},{}.{:02},{},{}\n", i % 500, 100 + i % 900, i % 100, i * 7, 1_700_000_000 + i));
+        }
+    }
+    doc
+}
+
+/// Tokenizer used before the memchr rewrite, kept as the baseline
+fn split_baseline(input: &str) -> Vec<Vec<String>> {
+    input
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').map(|f| f.trim().to_string()).collect())
+        .collect()
+}
+
+fn bench_tokenizer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("row_tokenizer");
+
+    for rows in [10_000usize, 100_000] {
+        let doc = market_ticks(rows);
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_with_input(BenchmarkId::new("split_baseline", rows), &doc, |b, doc| {
+            b.iter(|| split_baseline(black_box(doc)))
+        });
+        group.bench_with_input(BenchmarkId::new("memchr_lexer", rows), &doc, |b, doc| {
+            b.iter(|| ToonParser::new(black_box(doc)).parse().unwrap())
+        });
+    }
+
+    let doc = quoted_notes(100_000);
+    group.throughput(Throughput::Bytes(doc.len() as u64));
+    group.bench_with_input(BenchmarkId::new("memchr_lexer_quoted", 100_000), &doc, |b, doc| {
+        b.iter(|| ToonParser::new(black_box(doc)).parse().unwrap())
+    });
+
+    group.finish();
+}
+
+fn bench_multi_section(c: &mut Criterion) {
+    let mut group = c.benchmark_group("multi_section");
+    let doc = multi_table(64, 5_000);
+    group.throughput(Throughput::Bytes(doc.len() as u64));
+    group.bench_with_input(BenchmarkId::new("sequential", 64), &doc, |b, doc| {
+        b.iter(|| ToonParser::new(black_box(doc)).parse().unwrap())
+    });
+    #[cfg(feature = "parallel")]
+    group.bench_with_input(BenchmarkId::new("parallel", 64), &doc, |b, doc| {
+        b.iter(|| ToonParser::new(black_box(doc)).parse_parallel().unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_tokenizer, bench_multi_section);
+criterion_main!(benches);
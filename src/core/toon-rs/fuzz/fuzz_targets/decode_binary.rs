@@ -0,0 +1,10 @@
+//! Arbitrary bytes through the TOON-B decoder
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(document) = toon_rs::decode_binary(data) {
+        let _ = toon_rs::encode_binary(&document);
+    }
+});
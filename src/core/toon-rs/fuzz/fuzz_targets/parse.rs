@@ -0,0 +1,20 @@
+//! Arbitrary bytes through the panic-free text entry point. Successful
+//! parses are serialized and parsed again, and valid UTF-8 is also run
+//! through the strict parser options.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use toon_rs::{parse_no_panic, DuplicateKeyPolicy, ToonParser};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(document) = parse_no_panic(data) else {
+        return;
+    };
+    let _ = parse_no_panic(document.to_toon_string().as_bytes());
+
+    if let Ok(text) = std::str::from_utf8(data) {
+        for policy in [DuplicateKeyPolicy::Error, DuplicateKeyPolicy::FirstWins, DuplicateKeyPolicy::Merge] {
+            let _ = ToonParser::new(text).duplicate_keys(policy).verify_checksum(true).parse();
+        }
+    }
+});
@@ -0,0 +1,324 @@
+//! TOON-B: compact binary encoding of TOON documents
+//!
+//! Layout (all integers little-endian):
+//!
+//! ```text
+//! magic      "TOONB"            5 bytes
+//! version    u8                 currently 2 (1 is still read)
+//! entries    u32
+//! entry*     key:str  tag:u8  body
+//!
+//! str        len:u32  utf8 bytes
+//! body       tag 0 String  -> str
+//!            tag 1 Number  -> f64
+//!            tag 2 Boolean -> u8 (0 or 1)
+//!            tag 3 Schema  -> count:u64 rows:u64 cols:u32
+//!                             (name:str type:u8)*cols
+//!                             column*cols
+//!            tag 4 Integer  -> i64
+//!            tag 5 Unsigned -> u64
+//!            tag 6 Decimal  -> mantissa:i128 scale:u32
+//! column     type 0 Text    -> str*rows
+//!            type 1 Integer -> i64*rows
+//!            type 2 Float   -> f64*rows
+//!            type 3 Bool    -> u8*rows
+//! ```
+//!
+//! Columns are stored column-major. A column is only given a fixed-width
+//! type when every value reproduces its exact text after decoding, so a
+//! binary round trip is lossless. Entries are written in sorted key order,
+//! making the encoding deterministic.
+
+use crate::{Decimal, Span, ToonError, ToonValue};
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 5] = b"TOONB";
+const VERSION: u8 = 2;
+/// Version 1 predates the exact numeric tags and is otherwise identical
+const MIN_VERSION: u8 = 1;
+
+const TAG_STRING: u8 = 0;
+const TAG_NUMBER: u8 = 1;
+const TAG_BOOLEAN: u8 = 2;
+const TAG_SCHEMA: u8 = 3;
+const TAG_INTEGER: u8 = 4;
+const TAG_UNSIGNED: u8 = 5;
+const TAG_DECIMAL: u8 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Text = 0,
+    Integer = 1,
+    Float = 2,
+    Bool = 3,
+}
+
+impl ColumnType {
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(ColumnType::Text),
+            1 => Some(ColumnType::Integer),
+            2 => Some(ColumnType::Float),
+            3 => Some(ColumnType::Bool),
+            _ => None,
+        }
+    }
+
+    /// Narrowest type that reproduces every value's text exactly
+    fn infer<'a>(mut values: impl Iterator<Item = &'a str> + Clone) -> Self {
+        if values.clone().all(|v| v.parse::<i64>().is_ok_and(|n| n.to_string() == v)) {
+            ColumnType::Integer
+        } else if values.clone().all(|v| v.parse::<f64>().is_ok_and(|n| n.to_string() == v)) {
+            ColumnType::Float
+        } else if values.all(|v| v == "true" || v == "false") {
+            ColumnType::Bool
+        } else {
+            ColumnType::Text
+        }
+    }
+}
+
+/// Encode a parsed document as TOON-B.
+pub fn encode_binary(document: &HashMap<String, ToonValue>) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(&(document.len() as u32).to_le_bytes());
+
+    let mut keys: Vec<&String> = document.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        write_str(&mut out, key);
+        match &document[key] {
+            ToonValue::String(s) => {
+                out.push(TAG_STRING);
+                write_str(&mut out, s);
+            }
+            ToonValue::Number(n) => {
+                out.push(TAG_NUMBER);
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+            ToonValue::Integer(n) => {
+                out.push(TAG_INTEGER);
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+            ToonValue::Unsigned(n) => {
+                out.push(TAG_UNSIGNED);
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+            ToonValue::Decimal(d) => {
+                out.push(TAG_DECIMAL);
+                out.extend_from_slice(&d.mantissa().to_le_bytes());
+                out.extend_from_slice(&d.scale().to_le_bytes());
+            }
+            ToonValue::Boolean(b) => {
+                out.push(TAG_BOOLEAN);
+                out.push(*b as u8);
+            }
+            ToonValue::Schema { count, schema, data } => {
+                out.push(TAG_SCHEMA);
+                out.extend_from_slice(&(*count as u64).to_le_bytes());
+                out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+                out.extend_from_slice(&(schema.len() as u32).to_le_bytes());
+
+                let types: Vec<ColumnType> = (0..schema.len())
+                    .map(|col| ColumnType::infer(data.iter().map(move |row| field(row, col))))
+                    .collect();
+                for (name, column_type) in schema.iter().zip(&types) {
+                    write_str(&mut out, name);
+                    out.push(*column_type as u8);
+                }
+
+                for (col, column_type) in types.iter().enumerate() {
+                    for row in data {
+                        let value = field(row, col);
+                        // Inference guarantees these parses succeed
+                        match column_type {
+                            ColumnType::Text => write_str(&mut out, value),
+                            ColumnType::Integer => {
+                                out.extend_from_slice(&value.parse::<i64>().unwrap_or_default().to_le_bytes())
+                            }
+                            ColumnType::Float => {
+                                out.extend_from_slice(&value.parse::<f64>().unwrap_or_default().to_le_bytes())
+                            }
+                            ColumnType::Bool => out.push((value == "true") as u8),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Field `col` of a row, treating missing trailing fields as empty
+fn field(row: &[String], col: usize) -> &str {
+    row.get(col).map(String::as_str).unwrap_or("")
+}
+
+/// Decode a TOON-B buffer produced by `encode_binary`.
+///
+/// Sections are held to the same guarantees as text documents: every row
+/// has one value per schema field and a non-empty section contains exactly
+/// the declared count. Errors carry the byte offset in `Span::offset`;
+/// `line` and `column` are 0 because binary input has no lines.
+pub fn decode_binary(bytes: &[u8]) -> Result<HashMap<String, ToonValue>, ToonError> {
+    let mut reader = Reader { bytes, pos: 0 };
+
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(reader.error_at(0, "Missing TOON-B magic"));
+    }
+    let version = reader.u8()?;
+    if !(MIN_VERSION..=VERSION).contains(&version) {
+        return Err(reader.error_at(MAGIC.len(), &format!("Unsupported TOON-B version {}", version)));
+    }
+
+    let entries = reader.u32()? as usize;
+    let mut result = HashMap::new();
+
+    for _ in 0..entries {
+        let key = reader.str()?;
+        let tag_offset = reader.pos;
+        let value = match reader.u8()? {
+            TAG_STRING => ToonValue::String(reader.str()?),
+            TAG_NUMBER => ToonValue::Number(f64::from_le_bytes(reader.array()?)),
+            TAG_BOOLEAN => ToonValue::Boolean(reader.bool()?),
+            TAG_SCHEMA => reader.schema(tag_offset)?,
+            TAG_INTEGER if version >= 2 => ToonValue::Integer(i64::from_le_bytes(reader.array()?)),
+            TAG_UNSIGNED if version >= 2 => ToonValue::Unsigned(u64::from_le_bytes(reader.array()?)),
+            TAG_DECIMAL if version >= 2 => {
+                let mantissa = i128::from_le_bytes(reader.array()?);
+                ToonValue::Decimal(Decimal::new(mantissa, reader.u32()?))
+            }
+            tag => return Err(reader.error_at(tag_offset, &format!("Unknown entry tag {}", tag))),
+        };
+        result.insert(key, value);
+    }
+
+    if reader.pos != bytes.len() {
+        return Err(reader.error_at(reader.pos, "Trailing bytes after last entry"));
+    }
+
+    Ok(result)
+}
+
+/// Bounds-checked cursor over untrusted input
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error_at(&self, offset: usize, message: &str) -> ToonError {
+        ToonError::InvalidBinary {
+            message: message.to_string(),
+            span: Span { line: 0, column: 0, offset },
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ToonError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| self.error_at(self.pos, "Unexpected end of input"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ToonError> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn u8(&mut self) -> Result<u8, ToonError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ToonError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, ToonError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn len(&mut self, value: u64) -> Result<usize, ToonError> {
+        usize::try_from(value).map_err(|_| self.error_at(self.pos, "Length exceeds platform limits"))
+    }
+
+    fn bool(&mut self) -> Result<bool, ToonError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(self.error_at(self.pos - 1, &format!("Invalid boolean byte {}", other))),
+        }
+    }
+
+    fn str(&mut self) -> Result<String, ToonError> {
+        let len = self.u32()? as usize;
+        let start = self.pos;
+        let raw = self.take(len)?;
+        String::from_utf8(raw.to_vec()).map_err(|_| self.error_at(start, "Invalid UTF-8 in string"))
+    }
+
+    fn schema(&mut self, offset: usize) -> Result<ToonValue, ToonError> {
+        let count_raw = self.u64()?;
+        let count = self.len(count_raw)?;
+        let rows_raw = self.u64()?;
+        let rows = self.len(rows_raw)?;
+        let cols = self.u32()? as usize;
+
+        if rows != 0 && rows != count {
+            return Err(ToonError::CountMismatch {
+                expected: count,
+                found: rows,
+                span: Span { line: 0, column: 0, offset },
+            });
+        }
+
+        let mut schema = Vec::new();
+        let mut types = Vec::new();
+        for _ in 0..cols {
+            schema.push(self.str()?);
+            let tag_offset = self.pos;
+            let tag = self.u8()?;
+            types.push(
+                ColumnType::from_tag(tag)
+                    .ok_or_else(|| self.error_at(tag_offset, &format!("Unknown column type {}", tag)))?,
+            );
+        }
+
+        // Never trust declared sizes for allocation: every value takes at
+        // least one byte, so the remaining input bounds rows x cols. Rows
+        // grow as their values are read rather than being pre-sized.
+        let remaining = self.bytes.len() - self.pos;
+        if !matches!(rows.checked_mul(cols), Some(values) if values <= remaining) {
+            return Err(self.error_at(self.pos, "Row count exceeds remaining input"));
+        }
+        let mut data: Vec<Vec<String>> = (0..if cols > 0 { rows } else { 0 }).map(|_| Vec::new()).collect();
+
+        for column_type in &types {
+            for row in data.iter_mut() {
+                let value = match column_type {
+                    ColumnType::Text => self.str()?,
+                    ColumnType::Integer => i64::from_le_bytes(self.array()?).to_string(),
+                    ColumnType::Float => f64::from_le_bytes(self.array()?).to_string(),
+                    ColumnType::Bool => self.bool()?.to_string(),
+                };
+                row.push(value);
+            }
+        }
+
+        Ok(ToonValue::Schema { count, schema, data })
+    }
+}
@@ -0,0 +1,189 @@
+//! Structural comparison of TOON documents
+//! Reports exactly where two documents diverge: keys that appear on only
+//! one side, scalars that changed, and per-section schema, count and row
+//! deltas. Rows are compared by position, since deterministic outputs are
+//! expected to agree on order as well as content.
+
+use crate::{writer, ToonDocument, ToonValue};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Differences between two documents, keys in canonical order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToonDiff {
+    /// Keys present only in the second document
+    pub added: Vec<String>,
+    /// Keys present only in the first document
+    pub removed: Vec<String>,
+    /// Keys whose value changed, other than section-to-section changes
+    pub changed: Vec<ValueChange>,
+    /// Sections present in both documents that differ
+    pub sections: Vec<SectionDiff>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange {
+    pub key: String,
+    pub before: ToonValue,
+    pub after: ToonValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionDiff {
+    pub key: String,
+    /// Field lists `(before, after)` when the schema changed
+    pub schema: Option<(Vec<String>, Vec<String>)>,
+    /// Declared counts `(before, after)` when they differ
+    pub count: Option<(usize, usize)>,
+    pub rows: Vec<RowDelta>,
+}
+
+/// Row-level change within a section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RowDelta {
+    Added { index: usize, row: Vec<String> },
+    Removed { index: usize, row: Vec<String> },
+    /// Fields that differ, matched by column name
+    Changed { index: usize, fields: Vec<FieldChange> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub column: String,
+    pub before: String,
+    pub after: String,
+}
+
+impl ToonDiff {
+    /// True when the documents have identical entries
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty() && self.sections.is_empty()
+    }
+}
+
+/// Compare two documents. Directives are metadata and are not compared.
+pub fn diff(a: &ToonDocument, b: &ToonDocument) -> ToonDiff {
+    let mut result = ToonDiff::default();
+
+    for (key, before) in a.entries() {
+        let Some(after) = b.get(key) else {
+            result.removed.push(key.clone());
+            continue;
+        };
+        if before == after {
+            continue;
+        }
+        match (before, after) {
+            (
+                ToonValue::Schema { count, schema, data },
+                ToonValue::Schema {
+                    count: after_count,
+                    schema: after_schema,
+                    data: after_data,
+                },
+            ) => result.sections.push(SectionDiff {
+                key: key.clone(),
+                schema: (schema != after_schema).then(|| (schema.clone(), after_schema.clone())),
+                count: (count != after_count).then_some((*count, *after_count)),
+                rows: row_deltas(schema, data, after_schema, after_data),
+            }),
+            _ => result.changed.push(ValueChange {
+                key: key.clone(),
+                before: before.clone(),
+                after: after.clone(),
+            }),
+        }
+    }
+
+    result.added = b
+        .entries()
+        .keys()
+        .filter(|key| a.get(key).is_none())
+        .cloned()
+        .collect();
+    result
+}
+
+fn row_deltas(schema: &[String], data: &[Vec<String>], after_schema: &[String], after_data: &[Vec<String>]) -> Vec<RowDelta> {
+    // Columns present on both sides, as (name, index before, index after)
+    let shared: Vec<(&String, usize, usize)> = schema
+        .iter()
+        .enumerate()
+        .filter_map(|(i, name)| after_schema.iter().position(|n| n == name).map(|j| (name, i, j)))
+        .collect();
+
+    let mut deltas = Vec::new();
+    for (index, (row, after_row)) in data.iter().zip(after_data).enumerate() {
+        let fields: Vec<FieldChange> = shared
+            .iter()
+            .filter(|(_, i, j)| row.get(*i) != after_row.get(*j))
+            .map(|(name, i, j)| FieldChange {
+                column: name.to_string(),
+                before: row.get(*i).cloned().unwrap_or_default(),
+                after: after_row.get(*j).cloned().unwrap_or_default(),
+            })
+            .collect();
+        if !fields.is_empty() {
+            deltas.push(RowDelta::Changed { index, fields });
+        }
+    }
+
+    let common = data.len().min(after_data.len());
+    deltas.extend(data[common..].iter().enumerate().map(|(offset, row)| RowDelta::Removed {
+        index: common + offset,
+        row: row.clone(),
+    }));
+    deltas.extend(after_data[common..].iter().enumerate().map(|(offset, row)| RowDelta::Added {
+        index: common + offset,
+        row: row.clone(),
+    }));
+    deltas
+}
+
+impl fmt::Display for ToonDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for key in &self.removed {
+            writeln!(f, "- {}", key)?;
+        }
+        for key in &self.added {
+            writeln!(f, "+ {}", key)?;
+        }
+        for change in &self.changed {
+            writeln!(
+                f,
+                "~ {}: {} -> {}",
+                change.key,
+                display_value(&change.before),
+                display_value(&change.after)
+            )?;
+        }
+        for section in &self.sections {
+            writeln!(f, "~ {}", section.key)?;
+            if let Some((before, after)) = &section.schema {
+                writeln!(f, "    schema {{{}}} -> {{{}}}", before.join(","), after.join(","))?;
+            }
+            if let Some((before, after)) = section.count {
+                writeln!(f, "    count [{}] -> [{}]", before, after)?;
+            }
+            for delta in &section.rows {
+                match delta {
+                    RowDelta::Added { index, row } => writeln!(f, "    + row {}: {}", index, row.join(","))?,
+                    RowDelta::Removed { index, row } => writeln!(f, "    - row {}: {}", index, row.join(","))?,
+                    RowDelta::Changed { index, fields } => {
+                        for field in fields {
+                            writeln!(f, "    ~ row {} {}: {} -> {}", index, field.column, field.before, field.after)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn display_value(value: &ToonValue) -> String {
+    match value {
+        ToonValue::Schema { count, schema, .. } => format!("[{}]{{{}}}", count, schema.join(",")),
+        scalar => writer::write_value(scalar),
+    }
+}
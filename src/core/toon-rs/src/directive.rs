@@ -0,0 +1,88 @@
+//! TOON v2.0 directives
+//! Metadata lines of the form `#!name value`, e.g.
+//!
+//! ```text
+//! #!toon 2.0
+//! #!sha256 3f0a...e1
+//! ```
+//!
+//! Directives look like comments to older parsers. The `sha256` checksum
+//! covers the payload: the source text with every directive line (including
+//! its newline) removed, so a document can carry its own seal.
+
+use crate::{Span, ToonError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+
+/// Major version of the TOON grammar this parser implements
+pub const TOON_MAJOR_VERSION: &str = "2";
+
+/// A `#!name value` metadata line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Directive {
+    pub name: String,
+    pub value: String,
+    pub span: Span,
+}
+
+/// Split a trimmed `#!name value` line into its name and value
+pub(crate) fn split_directive(line: &str) -> Option<(&str, &str)> {
+    let body = line.strip_prefix("#!")?;
+    let (name, value) = body.split_once(char::is_whitespace).unwrap_or((body, ""));
+    if name.is_empty() {
+        None
+    } else {
+        Some((name, value.trim()))
+    }
+}
+
+/// Lowercase hex SHA-256 of `source` with the `excluded` byte ranges skipped
+pub(crate) fn payload_sha256(source: &str, excluded: &[Range<usize>]) -> String {
+    let mut hasher = Sha256::new();
+    let mut pos = 0;
+    for range in excluded {
+        hasher.update(&source.as_bytes()[pos..range.start]);
+        pos = range.end;
+    }
+    hasher.update(&source.as_bytes()[pos..]);
+    format!("{:x}", hasher.finalize())
+}
+
+/// SHA-256 of a payload that contains no directive lines
+pub fn sha256_hex(payload: &str) -> String {
+    payload_sha256(payload, &[])
+}
+
+/// Validate the `#!toon` version directive, if present
+pub(crate) fn check_version(directive: &Directive) -> Result<(), ToonError> {
+    if directive.name == "toon" && directive.value.split('.').next() != Some(TOON_MAJOR_VERSION) {
+        return Err(ToonError::ParseError {
+            message: format!(
+                "Unsupported TOON version '{}', this parser implements {}.x",
+                directive.value, TOON_MAJOR_VERSION
+            ),
+            span: directive.span,
+        });
+    }
+    Ok(())
+}
+
+/// Verify the payload against the `#!sha256` directive.
+/// A missing directive is an error when verification is requested.
+pub(crate) fn verify_checksum(source: &str, directives: &[Directive], excluded: &[Range<usize>]) -> Result<(), ToonError> {
+    let directive = directives.iter().find(|d| d.name == "sha256").ok_or_else(|| ToonError::ParseError {
+        message: "Checksum verification requested but no #!sha256 directive present".to_string(),
+        span: Span::from_offset(source, 0),
+    })?;
+
+    let actual = payload_sha256(source, excluded);
+    if !directive.value.eq_ignore_ascii_case(&actual) {
+        return Err(ToonError::ChecksumMismatch {
+            expected: directive.value.clone(),
+            actual,
+            span: directive.span,
+        });
+    }
+    Ok(())
+}
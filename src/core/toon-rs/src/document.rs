@@ -0,0 +1,286 @@
+//! TOON documents and the programmatic document builder
+//! Entries are kept in a `BTreeMap`, so serialization order is canonical
+//! and two builds of the same data produce byte-identical output.
+
+use crate::directive::{self, Directive, TOON_MAJOR_VERSION};
+use crate::number::parse_number;
+use crate::writer;
+use crate::{Decimal, Span, ToonError, ToonValue};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// A parsed or built TOON document with canonical key ordering
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToonDocument {
+    entries: BTreeMap<String, ToonValue>,
+    directives: Vec<Directive>,
+}
+
+impl ToonDocument {
+    pub fn builder() -> ToonDocumentBuilder {
+        ToonDocumentBuilder::new()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ToonValue> {
+        self.entries.get(key)
+    }
+
+    /// All entries in canonical (sorted) order
+    pub fn entries(&self) -> &BTreeMap<String, ToonValue> {
+        &self.entries
+    }
+
+    /// `#!` directives in source order
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
+
+    /// Value of the first directive named `name`
+    pub fn directive(&self, name: &str) -> Option<&str> {
+        self.directives.iter().find(|d| d.name == name).map(|d| d.value.as_str())
+    }
+
+    pub(crate) fn with_directives(mut self, directives: Vec<Directive>) -> Self {
+        self.directives = directives;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serialize with sorted keys and canonical number formatting.
+    /// Directives are not part of the payload and are omitted.
+    pub fn to_toon_string(&self) -> String {
+        let mut out = String::new();
+        for (key, value) in &self.entries {
+            out.push_str(&writer::write_entry(key, value));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Canonical form used for hashing: the `to_toon_string` output with
+    /// every number written as its shortest exact decimal, so `price = 1.50`,
+    /// `price = 1.5` and `price = 15e-1` agree. Row fields that read as
+    /// numbers are normalized the same way; other fields are hashed as
+    /// written.
+    pub fn to_canonical_string(&self) -> String {
+        let mut out = String::new();
+        for (key, value) in &self.entries {
+            let entry = match value {
+                ToonValue::Schema { count, schema, data } => {
+                    let data = data
+                        .iter()
+                        .map(|row| row.iter().map(|field| canonical_field(field)).collect())
+                        .collect();
+                    writer::write_entry(key, &ToonValue::Schema { count: *count, schema: schema.clone(), data })
+                }
+                other => writer::write_entry(key, &canonical_number(other).unwrap_or_else(|| other.clone())),
+            };
+            out.push_str(&entry);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Lowercase hex SHA-256 of `to_canonical_string`. Documents that differ
+    /// only in key order, whitespace, comments, directives or number
+    /// formatting hash identically.
+    pub fn canonical_hash(&self) -> String {
+        directive::sha256_hex(&self.to_canonical_string())
+    }
+
+    /// Serialize with `#!toon` and `#!sha256` directives prepended, so the
+    /// result passes `ToonParser::verify_checksum`.
+    pub fn to_sealed_string(&self) -> String {
+        let payload = self.to_toon_string();
+        format!(
+            "#!toon {}.0\n#!sha256 {}\n{}",
+            TOON_MAJOR_VERSION,
+            directive::sha256_hex(&payload),
+            payload
+        )
+    }
+
+    pub fn into_map(self) -> HashMap<String, ToonValue> {
+        self.entries.into_iter().collect()
+    }
+}
+
+/// A numeric value as a normalized `Decimal` where it has an exact one, so
+/// equal numbers are written identically; `None` for non-numbers
+fn canonical_number(value: &ToonValue) -> Option<ToonValue> {
+    let exact = match value {
+        // Display gives the shortest decimal that round-trips, without an exponent
+        ToonValue::Number(n) if n.is_finite() => Decimal::parse(&n.to_string()),
+        ToonValue::Number(_) => None,
+        other => other.as_decimal(),
+    };
+    match exact {
+        Some(d) => Some(ToonValue::Decimal(d.normalized())),
+        None if value.is_numeric() => Some(value.clone()),
+        None => None,
+    }
+}
+
+/// A row field, with numbers written as `canonical_number` writes them
+fn canonical_field(field: &str) -> String {
+    match parse_number(field).as_ref().and_then(canonical_number) {
+        Some(number) => writer::write_value(&number),
+        None => field.to_string(),
+    }
+}
+
+impl From<HashMap<String, ToonValue>> for ToonDocument {
+    fn from(map: HashMap<String, ToonValue>) -> Self {
+        Self {
+            entries: map.into_iter().collect(),
+            directives: Vec::new(),
+        }
+    }
+}
+
+/// Builder that assembles a `ToonDocument` from key-value pairs and
+/// guardrail sections, validating row counts when `build` is called.
+///
+/// Errors raised by the builder carry `Span::default()` since there is no
+/// source text to point into.
+#[derive(Debug, Default)]
+pub struct ToonDocumentBuilder {
+    entries: BTreeMap<String, ToonValue>,
+    error: Option<ToonError>,
+}
+
+impl ToonDocumentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a scalar `key = value` pair
+    pub fn pair(mut self, key: &str, value: impl Into<ToonValue>) -> Self {
+        let value = value.into();
+        if matches!(value, ToonValue::Schema { .. }) {
+            self.fail(format!("Use section() to add schema '{}'", key));
+        } else {
+            self.insert(key, value);
+        }
+        self
+    }
+
+    /// Declare a guardrail section `key [count]{schema}`
+    pub fn section(mut self, key: &str, count: usize, schema: &[&str]) -> Self {
+        if let Some(field) = schema.iter().find(|f| !is_identifier(f)) {
+            self.fail(format!("Invalid field name '{}' in section '{}'", field, key));
+            return self;
+        }
+        self.insert(
+            key,
+            ToonValue::Schema {
+                count,
+                schema: schema.iter().map(|f| f.to_string()).collect(),
+                data: Vec::with_capacity(count),
+            },
+        );
+        self
+    }
+
+    /// Append a row to a previously declared section
+    pub fn row(mut self, key: &str, fields: &[&str]) -> Self {
+        match self.entries.get_mut(key) {
+            Some(ToonValue::Schema { schema, data, .. }) => {
+                if fields.len() == schema.len() {
+                    data.push(fields.iter().map(|f| f.to_string()).collect());
+                } else {
+                    let message = format!(
+                        "Row has {} fields, schema '{}' declares {}",
+                        fields.len(),
+                        key,
+                        schema.len()
+                    );
+                    self.fail(message);
+                }
+            }
+            _ => self.fail(format!("Row added to undeclared section '{}'", key)),
+        }
+        self
+    }
+
+    /// Validate and produce the document
+    pub fn build(self) -> Result<ToonDocument, ToonError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        for value in self.entries.values() {
+            if let ToonValue::Schema { count, data, .. } = value {
+                if data.len() != *count {
+                    return Err(ToonError::CountMismatch {
+                        expected: *count,
+                        found: data.len(),
+                        span: Span::default(),
+                    });
+                }
+            }
+        }
+
+        Ok(ToonDocument {
+            entries: self.entries,
+            directives: Vec::new(),
+        })
+    }
+
+    fn insert(&mut self, key: &str, value: ToonValue) {
+        if !is_identifier(key) {
+            self.fail(format!("Invalid key '{}'", key));
+        } else if self.entries.contains_key(key) {
+            self.fail(format!("Duplicate key '{}'", key));
+        } else {
+            self.entries.insert(key.to_string(), value);
+        }
+    }
+
+    /// Record the first error; later calls become no-ops for reporting
+    fn fail(&mut self, message: String) {
+        if self.error.is_none() {
+            self.error = Some(ToonError::SchemaViolation {
+                message,
+                span: Span::default(),
+            });
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl From<&str> for ToonValue {
+    fn from(value: &str) -> Self {
+        ToonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ToonValue {
+    fn from(value: String) -> Self {
+        ToonValue::String(value)
+    }
+}
+
+impl From<f64> for ToonValue {
+    fn from(value: f64) -> Self {
+        ToonValue::Number(value)
+    }
+}
+
+impl From<bool> for ToonValue {
+    fn from(value: bool) -> Self {
+        ToonValue::Boolean(value)
+    }
+}
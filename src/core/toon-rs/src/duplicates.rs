@@ -0,0 +1,120 @@
+//! Duplicate-key handling
+//! Decides what happens when a key or section name appears more than once.
+
+use crate::{Span, ToonError, ToonValue};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What the parser does when a key is defined more than once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DuplicateKeyPolicy {
+    /// Reject the document with `ToonError::DuplicateKey` (strict mode)
+    Error,
+    /// Keep the first definition and ignore later ones
+    FirstWins,
+    /// Later definitions replace earlier ones (the historical behaviour)
+    #[default]
+    LastWins,
+    /// Concatenate the rows of sections with identical schemas and accept
+    /// identical scalar redefinitions; anything else is a conflict
+    Merge,
+}
+
+/// Destination for entries in the order they are parsed
+pub(crate) trait EntrySink {
+    fn insert(&mut self, key: String, value: ToonValue, offset: usize) -> Result<(), ToonError>;
+}
+
+/// Entries collected without applying a policy, for merging later
+impl EntrySink for Vec<(String, ToonValue, usize)> {
+    fn insert(&mut self, key: String, value: ToonValue, offset: usize) -> Result<(), ToonError> {
+        self.push((key, value, offset));
+        Ok(())
+    }
+}
+
+/// Parsed entries plus the location each key was first defined at
+pub(crate) struct EntryMap<'s> {
+    source: &'s str,
+    policy: DuplicateKeyPolicy,
+    entries: HashMap<String, ToonValue>,
+    first_offsets: HashMap<String, usize>,
+}
+
+impl<'s> EntryMap<'s> {
+    pub(crate) fn new(source: &'s str, policy: DuplicateKeyPolicy) -> Self {
+        Self {
+            source,
+            policy,
+            entries: HashMap::new(),
+            first_offsets: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn into_entries(self) -> HashMap<String, ToonValue> {
+        self.entries
+    }
+
+    fn duplicate(&self, key: String, first_offset: usize, offset: usize) -> ToonError {
+        let span = Span::from_offset(self.source, offset);
+        ToonError::DuplicateKey {
+            key,
+            first_line: Span::from_offset(self.source, first_offset).line,
+            second_line: span.line,
+            span,
+        }
+    }
+}
+
+impl EntrySink for EntryMap<'_> {
+    /// Insert `value` defined at byte `offset`, applying the duplicate policy
+    fn insert(&mut self, key: String, value: ToonValue, offset: usize) -> Result<(), ToonError> {
+        let Some(&first_offset) = self.first_offsets.get(&key) else {
+            self.first_offsets.insert(key.clone(), offset);
+            self.entries.insert(key, value);
+            return Ok(());
+        };
+
+        match self.policy {
+            DuplicateKeyPolicy::Error => Err(self.duplicate(key, first_offset, offset)),
+            DuplicateKeyPolicy::FirstWins => Ok(()),
+            DuplicateKeyPolicy::LastWins => {
+                self.entries.insert(key, value);
+                Ok(())
+            }
+            DuplicateKeyPolicy::Merge => {
+                let existing = &self.entries[&key];
+                let merged = match (existing, value) {
+                    (
+                        ToonValue::Schema { count, schema, data },
+                        ToonValue::Schema {
+                            count: more_count,
+                            schema: more_schema,
+                            data: more_data,
+                        },
+                    ) if *schema == more_schema => {
+                        let mut rows = data.clone();
+                        rows.extend(more_data);
+                        let count = count.saturating_add(more_count);
+                        if !rows.is_empty() && rows.len() != count {
+                            return Err(ToonError::CountMismatch {
+                                expected: count,
+                                found: rows.len(),
+                                span: Span::from_offset(self.source, offset),
+                            });
+                        }
+                        ToonValue::Schema {
+                            count,
+                            schema: more_schema,
+                            data: rows,
+                        }
+                    }
+                    (existing, value) if *existing == value => value,
+                    _ => return Err(self.duplicate(key, first_offset, offset)),
+                };
+                self.entries.insert(key, merged);
+                Ok(())
+            }
+        }
+    }
+}
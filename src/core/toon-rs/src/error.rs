@@ -0,0 +1,133 @@
+//! TOON v2.0 error types with source locations
+//! Every error carries a `Span` so hosts can point at the offending input.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Location of an error inside the source document.
+/// `line` and `column` are 1-based (column counts characters),
+/// `offset` is the 0-based byte offset into the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+impl Span {
+    /// Resolve a byte offset into a line/column span.
+    /// Offsets past the end of `source` are clamped to its length.
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let prefix = &source[..offset];
+        let line_start = prefix.rfind('\n').map(|pos| pos + 1).unwrap_or(0);
+
+        Self {
+            line: prefix.matches('\n').count() + 1,
+            column: prefix[line_start..].chars().count() + 1,
+            offset,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// TOON v2.0 Parsing Error Types
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ToonError {
+    #[error("Invalid Guardrail Header Format at {span}")]
+    InvalidHeader { span: Span },
+
+    #[error("Count Mismatch: expected {expected}, found {found} at {span}")]
+    CountMismatch {
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+
+    #[error("Entropy Detected: Standard JSON input rejected at {span}")]
+    EntropyDetected { span: Span },
+
+    #[error("Parse Error: {message} at {span}")]
+    ParseError { message: String, span: Span },
+
+    #[error("Schema Violation: {message} at {span}")]
+    SchemaViolation { message: String, span: Span },
+
+    #[error("Duplicate Key: '{key}' first defined on line {first_line}, redefined on line {second_line}")]
+    DuplicateKey {
+        key: String,
+        first_line: usize,
+        second_line: usize,
+        span: Span,
+    },
+
+    #[error("Checksum Mismatch: directive declares {expected}, payload hashes to {actual} at {span}")]
+    ChecksumMismatch {
+        expected: String,
+        actual: String,
+        span: Span,
+    },
+
+    #[error("I/O Error: {message}")]
+    Io { message: String, span: Span },
+
+    #[error("Invalid TOON-B Data: {message} at byte {}", span.offset)]
+    InvalidBinary { message: String, span: Span },
+}
+
+impl ToonError {
+    /// Location of the error in the source document
+    pub fn span(&self) -> Span {
+        match self {
+            ToonError::InvalidHeader { span }
+            | ToonError::CountMismatch { span, .. }
+            | ToonError::EntropyDetected { span }
+            | ToonError::ParseError { span, .. }
+            | ToonError::SchemaViolation { span, .. }
+            | ToonError::DuplicateKey { span, .. }
+            | ToonError::ChecksumMismatch { span, .. }
+            | ToonError::Io { span, .. }
+            | ToonError::InvalidBinary { span, .. } => *span,
+        }
+    }
+
+    /// Render a caret-annotated diagnostic against the original source:
+    ///
+    /// ```text
+    /// error: Count Mismatch: expected 3, found 2 at line 1, column 1
+    ///  --> line 1, column 1
+    ///   |
+    /// 1 | ticks [3]{symbol,price}
+    ///   | ^
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let span = self.span();
+        let line_text = source
+            .split('\n')
+            .nth(span.line.saturating_sub(1))
+            .unwrap_or("")
+            .trim_end_matches('\r');
+        let gutter = span.line.to_string();
+        let pad = " ".repeat(gutter.len());
+        let caret_pad: String = line_text
+            .chars()
+            .take(span.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        format!(
+            "error: {}\n{} --> {}\n{} |\n{} | {}\n{} | {}^",
+            self, pad, span, pad, gutter, line_text, pad, caret_pad
+        )
+    }
+}
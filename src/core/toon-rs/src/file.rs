@@ -0,0 +1,66 @@
+//! Memory-mapped TOON files
+//! Parses multi-GB documents straight from the page cache instead of
+//! reading them into a `String` first. Enabled by the `mmap` feature.
+
+use crate::{Span, ToonDocument, ToonError, ToonParser};
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+
+/// A TOON file mapped into memory. The mapping stays valid for the life of
+/// this value; parsers borrow from it without copying.
+///
+/// As with any memory map, the file must not be truncated or modified by
+/// another process while it is mapped.
+pub struct ToonFile {
+    // Zero-length files cannot be mapped on every platform
+    map: Option<Mmap>,
+}
+
+impl ToonFile {
+    /// Map the file at `path` read-only
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ToonError> {
+        let path = path.as_ref();
+        let io_error = |e: std::io::Error| ToonError::Io {
+            message: format!("{}: {}", path.display(), e),
+            span: Span::default(),
+        };
+
+        let file = File::open(path).map_err(io_error)?;
+        if file.metadata().map_err(io_error)?.len() == 0 {
+            return Ok(Self { map: None });
+        }
+
+        // SAFETY: the mapping is read-only and only exposed as `&[u8]`/`&str`
+        // borrowed from `self`; concurrent modification of the file is
+        // documented as unsupported above.
+        let map = unsafe { Mmap::map(&file) }.map_err(io_error)?;
+        Ok(Self { map: Some(map) })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or(&[])
+    }
+
+    /// The mapped contents as UTF-8, validated in place
+    pub fn as_str(&self) -> Result<&str, ToonError> {
+        std::str::from_utf8(self.as_bytes()).map_err(|e| ToonError::ParseError {
+            message: "File is not valid UTF-8".to_string(),
+            span: Span {
+                line: 0,
+                column: 0,
+                offset: e.valid_up_to(),
+            },
+        })
+    }
+
+    /// A parser borrowing the mapped contents
+    pub fn parser(&self) -> Result<ToonParser<'_>, ToonError> {
+        ToonParser::try_new(self.as_str()?)
+    }
+}
+
+/// Map and parse the TOON file at `path` into a document
+pub fn parse_file(path: impl AsRef<Path>) -> Result<ToonDocument, ToonError> {
+    ToonFile::open(path)?.parser()?.parse_document()
+}
@@ -0,0 +1,268 @@
+//! TOON v2.0 field lexer
+//! Quoting and escape rules shared by data rows and key-value pairs.
+//!
+//! Fields are separated by `,` unless the section declares another
+//! [`Delimiter`]. A field may be wrapped in double quotes, in
+//! which case it can contain delimiters, braces and raw newlines. Inside
+//! quotes the following escapes are recognised:
+//!
+//! | Escape      | Meaning                      |
+//! |-------------|------------------------------|
+//! | `\"`        | double quote                 |
+//! | `\\`        | backslash                    |
+//! | `\n` `\r` `\t` | newline, carriage return, tab |
+//! | `\u{XXXX}`  | Unicode scalar value (1-6 hex digits) |
+
+use crate::{number, Span, ToonError};
+use memchr::{memchr, memchr2};
+use serde::{Deserialize, Serialize};
+
+/// Field separator for guardrail rows.
+///
+/// A header declares a non-comma delimiter right after its count, e.g.
+/// `ticks [2|]{symbol|price}` or `ticks [2\t]{symbol\tprice}` with a literal
+/// tab. Headers that declare none use the parser's default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Delimiter {
+    #[default]
+    Comma,
+    Tab,
+    Semicolon,
+    Pipe,
+}
+
+impl Delimiter {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            ',' => Some(Delimiter::Comma),
+            '\t' => Some(Delimiter::Tab),
+            ';' => Some(Delimiter::Semicolon),
+            '|' => Some(Delimiter::Pipe),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(self) -> char {
+        self.as_byte() as char
+    }
+
+    pub(crate) fn as_byte(self) -> u8 {
+        match self {
+            Delimiter::Comma => b',',
+            Delimiter::Tab => b'\t',
+            Delimiter::Semicolon => b';',
+            Delimiter::Pipe => b'|',
+        }
+    }
+}
+
+/// How records are split and checked
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Dialect {
+    pub(crate) delimiter: Delimiter,
+    /// Reject unquoted fields that look like locale-formatted numbers
+    pub(crate) strict_numbers: bool,
+    /// Classify lines outside sections as TOON 2.0 did; see
+    /// `ToonParser::legacy_lines`
+    pub(crate) legacy_lines: bool,
+}
+
+/// Scan a double-quoted string starting at `start` (which must hold `"`).
+/// Returns the unescaped contents and the offset just past the closing quote.
+pub(crate) fn scan_quoted(source: &str, start: usize) -> Result<(String, usize), ToonError> {
+    let mut out = String::new();
+    let end = quoted(source, start, Some(&mut out))?;
+    Ok((out, end))
+}
+
+/// Validate the quoted string at `start` without building it. Returns the
+/// offset just past the closing quote.
+#[cfg(feature = "parallel")]
+pub(crate) fn skip_quoted(source: &str, start: usize) -> Result<usize, ToonError> {
+    quoted(source, start, None)
+}
+
+/// Validate a quoted string, unescaping into `out` when one is given.
+/// Skipped fields pass `None` so they are checked without allocating.
+fn quoted(source: &str, start: usize, mut out: Option<&mut String>) -> Result<usize, ToonError> {
+    let bytes = source.as_bytes();
+    let mut pos = start + 1;
+    let mut run_start = pos;
+
+    // '"' and '\\' are ASCII and never occur inside multi-byte UTF-8
+    // sequences, so scanning bytes keeps every slice on a char boundary.
+    while let Some(i) = memchr2(b'"', b'\\', &bytes[pos..]) {
+        pos += i;
+        if let Some(out) = out.as_deref_mut() {
+            out.push_str(&source[run_start..pos]);
+        }
+        if bytes[pos] == b'"' {
+            return Ok(pos + 1);
+        }
+        let (ch, len) = decode_escape(source, pos)?;
+        if let Some(out) = out.as_deref_mut() {
+            out.push(ch);
+        }
+        pos += len;
+        run_start = pos;
+    }
+
+    Err(ToonError::ParseError {
+        message: "Unterminated quoted string".to_string(),
+        span: Span::from_offset(source, start),
+    })
+}
+
+/// Decode the escape sequence at `pos` (which must hold `\`).
+/// Returns the decoded character and the number of bytes consumed.
+fn decode_escape(source: &str, pos: usize) -> Result<(char, usize), ToonError> {
+    let invalid = |message: &str| ToonError::ParseError {
+        message: message.to_string(),
+        span: Span::from_offset(source, pos),
+    };
+
+    match source.as_bytes().get(pos + 1) {
+        Some(b'"') => Ok(('"', 2)),
+        Some(b'\\') => Ok(('\\', 2)),
+        Some(b'n') => Ok(('\n', 2)),
+        Some(b'r') => Ok(('\r', 2)),
+        Some(b't') => Ok(('\t', 2)),
+        Some(b'u') => {
+            let rest = &source[pos + 2..];
+            let close = rest
+                .strip_prefix('{')
+                .and_then(|r| r.find('}'))
+                .ok_or_else(|| invalid("Malformed unicode escape, expected \\u{XXXX}"))?;
+            let hex = &rest[1..close + 1];
+            if hex.is_empty() || hex.len() > 6 {
+                return Err(invalid("Unicode escape must have 1 to 6 hex digits"));
+            }
+            let ch = u32::from_str_radix(hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| invalid("Invalid unicode scalar value in escape"))?;
+            Ok((ch, 2 + close + 2))
+        }
+        Some(_) => Err(invalid("Unknown escape sequence")),
+        None => Err(invalid("Unterminated escape sequence")),
+    }
+}
+
+/// Scan a comma-separated record starting at `start`.
+/// Quoted fields may span several lines; unquoted fields are trimmed.
+/// Returns the fields and the offset just past the record's terminating newline.
+/// `expected_fields` pre-sizes the row so well-formed records never reallocate.
+pub(crate) fn scan_record(
+    source: &str,
+    start: usize,
+    expected_fields: usize,
+    dialect: Dialect,
+) -> Result<(Vec<String>, usize), ToonError> {
+    let mut fields = Vec::with_capacity(expected_fields);
+    let (_, after) = scan_fields(source, start, dialect, |_, field| fields.push(field()))?;
+    Ok((fields, after))
+}
+
+/// Scan a record but only materialize the fields whose index is set in
+/// `keep`; the rest are validated and skipped without allocating.
+/// Returns the kept fields, the total number of fields in the record and
+/// the offset just past the record.
+pub(crate) fn scan_record_projected(
+    source: &str,
+    start: usize,
+    keep: &[bool],
+    dialect: Dialect,
+) -> Result<(Vec<String>, usize, usize), ToonError> {
+    let mut fields = Vec::with_capacity(keep.iter().filter(|k| **k).count());
+    let (count, after) = scan_fields(source, start, dialect, |index, field| {
+        if keep.get(index) == Some(&true) {
+            fields.push(field());
+        }
+    })?;
+    Ok((fields, count, after))
+}
+
+/// Walk the fields of a record, handing each one to `visit` as its index
+/// and a closure that materializes it. Quoted fields are only unescaped
+/// when that closure is called.
+fn scan_fields<F>(source: &str, start: usize, dialect: Dialect, mut visit: F) -> Result<(usize, usize), ToonError>
+where
+    F: FnMut(usize, &mut dyn FnMut() -> String),
+{
+    let bytes = source.as_bytes();
+    let delimiter = dialect.delimiter.as_byte();
+    let mut pos = start;
+    let mut index = 0;
+
+    loop {
+        pos = skip_inline_whitespace(bytes, pos, delimiter);
+
+        let end = if bytes.get(pos) == Some(&b'"') {
+            let mut value = None;
+            visit(index, &mut || {
+                let mut out = String::new();
+                value = Some(quoted(source, pos, Some(&mut out)));
+                out
+            });
+            let after = match value {
+                Some(scanned) => scanned?,
+                None => quoted(source, pos, None)?,
+            };
+            let end = skip_inline_whitespace(bytes, after, delimiter);
+            if !matches!(bytes.get(end), None | Some(b'\n')) && bytes.get(end) != Some(&delimiter) {
+                return Err(ToonError::ParseError {
+                    message: "Unexpected character after quoted field".to_string(),
+                    span: Span::from_offset(source, end),
+                });
+            }
+            end
+        } else {
+            let end = memchr2(delimiter, b'\n', &bytes[pos..])
+                .map(|i| pos + i)
+                .unwrap_or(bytes.len());
+            if dialect.strict_numbers {
+                check_locale_number(source, pos, source[pos..end].trim())?;
+            }
+            visit(index, &mut || source[pos..end].trim().to_string());
+            end
+        };
+        index += 1;
+
+        match bytes.get(end) {
+            Some(b'\n') => return Ok((index, end + 1)),
+            Some(_) => pos = end + 1,
+            None => return Ok((index, end)),
+        }
+    }
+}
+
+/// Offset of the end of the line starting at or containing `pos`
+pub(crate) fn line_end(source: &str, pos: usize) -> usize {
+    memchr(b'\n', &source.as_bytes()[pos..])
+        .map(|i| pos + i)
+        .unwrap_or(source.len())
+}
+
+/// Reject `text` (found at `offset`) if it is a locale-formatted number
+/// such as `1,5` or `1.234,56`, which would otherwise be read as a string
+pub(crate) fn check_locale_number(source: &str, offset: usize, text: &str) -> Result<(), ToonError> {
+    if number::is_locale_number(text) {
+        return Err(ToonError::ParseError {
+            message: format!(
+                "Locale-formatted number '{}'; use '.' as the decimal point without grouping, or quote it",
+                text
+            ),
+            span: Span::from_offset(source, offset),
+        });
+    }
+    Ok(())
+}
+
+/// Skip spaces, tabs and carriage returns, but never the delimiter itself
+/// so that empty tab-separated fields are preserved
+fn skip_inline_whitespace(bytes: &[u8], mut pos: usize, delimiter: u8) -> usize {
+    while matches!(bytes.get(pos), Some(&b) if matches!(b, b' ' | b'\t' | b'\r') && b != delimiter) {
+        pos += 1;
+    }
+    pos
+}
@@ -5,38 +5,61 @@
 //!
 //! # Network Safety
 //! This library performs ZERO network operations. It is a pure parsing library
-//! that operates entirely on in-memory string slices. No HTTP, TCP, or socket
-//! operations are performed. All dependencies (nom, serde, thiserror) are
+//! that operates on in-memory string slices, optionally backed by a read-only
+//! memory map of a local file (`mmap` feature). No HTTP, TCP, or socket
+//! operations are performed. All dependencies (nom, memchr, serde, sha2,
+//! thiserror, and the optional memmap2, wasm-bindgen and serde_json) are
 //! also network-free.
 
 use nom::{
     bytes::complete::{tag, take_until},
-    sequence::{delimited, tuple, terminated},
-    character::complete::{digit1, multispace0, alpha1, alphanumeric1},
-    combinator::{map_res, recognize},
+    sequence::{delimited, pair, tuple, terminated},
+    character::complete::{digit1, multispace0, alpha1, alphanumeric1, one_of},
+    combinator::{map_opt, map_res, opt, recognize},
     multi::many0,
     branch::alt,
     IResult,
 };
 use serde::{Deserialize, Serialize};
-use thiserror::Error;
+use duplicates::{EntryMap, EntrySink};
+use lexer::Dialect;
+use projection::{ActiveProjection, Projection};
 use std::collections::HashMap;
+use std::ops::Range;
 
-/// TOON v2.0 Parsing Error Types
-#[derive(Error, Debug)]
-pub enum ToonError {
-    #[error("Invalid Guardrail Header Format")]
-    InvalidHeader,
-    
-    #[error("Count Mismatch: expected {expected}, found {found}")]
-    CountMismatch { expected: usize, found: usize },
-    
-    #[error("Entropy Detected: Standard JSON input rejected")]
-    EntropyDetected,
-    
-    #[error("Parse Error: {0}")]
-    ParseError(String),
-}
+pub mod binary;
+pub mod directive;
+mod diff;
+mod document;
+mod duplicates;
+mod error;
+#[cfg(feature = "mmap")]
+mod file;
+mod lexer;
+mod number;
+mod observer;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod projection;
+pub mod schema;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod writer;
+
+pub use binary::{decode_binary, encode_binary};
+pub use diff::{diff, FieldChange, RowDelta, SectionDiff, ToonDiff, ValueChange};
+pub use directive::Directive;
+pub use document::{ToonDocument, ToonDocumentBuilder};
+pub use duplicates::DuplicateKeyPolicy;
+pub use error::{Span, ToonError};
+pub use lexer::Delimiter;
+#[cfg(feature = "mmap")]
+pub use file::{parse_file, ToonFile};
+pub use number::Decimal;
+pub use observer::ParseObserver;
+pub use projection::RowView;
+pub use schema::{FieldSpec, FieldType, SchemaDefinition, SchemaRegistry};
+pub use writer::to_toon_string;
 
 /// The TOON Header Structure
 /// Example: "market_ticks [1000]{symbol,price,vol,ts}"
@@ -45,11 +68,24 @@ pub struct ToonHeader<'a> {
     pub key: &'a str,
     pub count: usize,
     pub schema: Vec<&'a str>,
+    /// Row delimiter declared after the count, e.g. `[2|]`
+    pub delimiter: Option<Delimiter>,
 }
 
 /// Zero-Copy Parser Implementation
 pub struct ToonParser<'a> {
     input: &'a str,
+    verify_checksum: bool,
+    duplicate_keys: DuplicateKeyPolicy,
+    projections: HashMap<String, Projection<'a>>,
+    dialect: Dialect,
+    observer: Option<&'a dyn ParseObserver>,
+}
+
+/// Entries and directives collected by a single parse
+struct Parsed {
+    entries: HashMap<String, ToonValue>,
+    directives: Vec<Directive>,
 }
 
 impl<'a> ToonParser<'a> {
@@ -59,11 +95,100 @@ impl<'a> ToonParser<'a> {
         if input.trim_start().starts_with('{') {
             panic!("AxiomViolation: Standard JSON input rejected. TOON format required.");
         }
-        Self { input }
+        Self {
+            input,
+            verify_checksum: false,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            projections: HashMap::new(),
+            dialect: Dialect::default(),
+            observer: None,
+        }
+    }
+
+    /// Report headers, validated rows and errors to `observer`
+    pub fn observer(mut self, observer: &'a dyn ParseObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Row delimiter for sections whose header does not declare one
+    pub fn delimiter(mut self, delimiter: Delimiter) -> Self {
+        self.dialect.delimiter = delimiter;
+        self
+    }
+
+    /// Reject unquoted values and fields that look like locale-formatted
+    /// numbers (`1,5`, `1.234,56`, `1 000`) instead of reading them as
+    /// strings. Quoted text is never checked.
+    pub fn strict_numbers(mut self, enabled: bool) -> Self {
+        self.dialect.strict_numbers = enabled;
+        self
+    }
+
+    /// Read lines outside guardrail sections as parsers before 2.1 did: a
+    /// key is whatever precedes the first `=`, and lines that are neither a
+    /// header nor a key-value pair, malformed headers included, are skipped
+    /// instead of rejected. Rows under a header are still collected. Meant
+    /// for migrating older documents; see CHANGELOG.md.
+    pub fn legacy_lines(mut self, enabled: bool) -> Self {
+        self.dialect.legacy_lines = enabled;
+        self
+    }
+
+    /// Choose how repeated keys and section names are resolved.
+    /// Defaults to `LastWins`; use `Error` for strict zero-entropy parsing.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Keep only `columns` of section `section`, in the given order. Other
+    /// fields are validated but never materialized. Selecting a column the
+    /// header does not declare is a `SchemaViolation`.
+    pub fn select(mut self, section: &str, columns: &[&str]) -> Self {
+        let projection = self.projections.entry(section.to_string()).or_default();
+        projection.columns = Some(columns.iter().map(|c| c.to_string()).collect());
+        self
+    }
+
+    /// Keep only the rows of `section` for which `predicate` returns true.
+    /// The predicate sees the selected columns, so any column it reads must
+    /// be part of the selection. The declared count is checked against the
+    /// rows in the source; the resulting section's count is the number of
+    /// rows kept.
+    pub fn filter(mut self, section: &str, predicate: impl Fn(&RowView<'_>) -> bool + Send + Sync + 'a) -> Self {
+        let projection = self.projections.entry(section.to_string()).or_default();
+        projection.filter = Some(Box::new(predicate));
+        self
+    }
+
+    /// Non-panicking constructor: JSON input yields `ToonError::EntropyDetected`
+    pub fn try_new(input: &'a str) -> Result<Self, ToonError> {
+        let trimmed = input.trim_start();
+        if trimmed.starts_with('{') {
+            return Err(ToonError::EntropyDetected {
+                span: Span::from_offset(input, input.len() - trimmed.len()),
+            });
+        }
+        Ok(Self::new(input))
+    }
+
+    /// Memory-map the file at `path`; call `parser()` on the result to parse
+    /// it without copying the contents into a `String`.
+    #[cfg(feature = "mmap")]
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<ToonFile, ToonError> {
+        ToonFile::open(path)
+    }
+
+    /// Require a `#!sha256` directive and verify it against the payload
+    /// before accepting the document.
+    pub fn verify_checksum(mut self, enabled: bool) -> Self {
+        self.verify_checksum = enabled;
+        self
     }
 
     /// Parses the Guardrail Header using strict Nom combinators.
-    /// Regex equivalent: ^([a-zA-Z_]\w*)\s*\[(\d+)\]\{([a-zA-Z_,]+)\}$
+    /// Regex equivalent: ^([a-zA-Z_]\w*)\s*\[(\d+)([,\t;|])?\]\{([a-zA-Z_,\t;|]+)\}$
     pub fn parse_header(input: &'a str) -> IResult<&'a str, ToonHeader<'a>> {
         // Parse key: alphanumeric + underscore
         let (input, key) = terminated(
@@ -74,101 +199,468 @@ impl<'a> ToonParser<'a> {
             multispace0
         )(input)?;
 
-        // Parse deterministic count [N]
-        let (input, count) = delimited(
+        // Parse deterministic count [N], optionally followed by a delimiter
+        let (input, (count, delimiter)) = delimited(
             tag("["),
-            map_res(digit1, |s: &str| s.parse::<usize>()),
+            pair(
+                map_res(digit1, |s: &str| s.parse::<usize>()),
+                opt(map_opt(one_of(",\t;|"), Delimiter::from_char)),
+            ),
             tag("]")
         )(input)?;
 
-        // Parse Schema definition {field1,field2}
+        // Parse Schema definition {field1,field2}. Field names are
+        // identifiers, so any supported delimiter can separate them.
         let (input, schema_block) = delimited(tag("{"), take_until("}"), tag("}"))(input)?;
         
         let schema: Vec<&str> = schema_block
-           .split(',')
+           .split(|c| Delimiter::from_char(c).is_some())
            .map(|s| s.trim())
            .filter(|s| !s.is_empty())
            .collect();
 
-        Ok((input, ToonHeader { key, count, schema }))
+        Ok((input, ToonHeader { key, count, schema, delimiter }))
     }
 
     /// Validates the data payload against the header's promise.
     /// This enforces the Zero Entropy Law by ensuring data structure
     /// matches the declared schema exactly.
+    /// The parsed header is reported to the observer, if one is attached.
     pub fn validate_payload(&self) -> Result<bool, ToonError> {
         let (_payload, header) = Self::parse_header(self.input)
-           .map_err(|_e| ToonError::InvalidHeader)?;
+            .map_err(|e| ToonError::InvalidHeader {
+                span: Span::from_offset(self.input, nom_error_offset(self.input, &e)),
+            })
+            .inspect_err(|e| self.notify_error(e))?;
 
         // In a full implementation, we would iterate 'header.count' times
         // parsing the tuple values. For this artifact, we return the 
         // structural validation status.
-        
-        println!(" Header Parsed: Key={}, Count={}, Schema={:?}", 
-            header.key, header.count, header.schema);
-            
+        if let Some(observer) = self.observer {
+            observer.header_parsed(&header, Span::from_offset(self.input, 0));
+        }
+
         Ok(true)
     }
 
     /// Parse complete TOON document with guardrail enforcement
+    ///
+    /// Rows following a guardrail header are collected into its section until
+    /// the next header or key-value pair. A section with rows must contain
+    /// exactly the declared count; a header without rows only declares the
+    /// schema and pre-allocates storage.
     pub fn parse(&self) -> Result<HashMap<String, ToonValue>, ToonError> {
-        let mut result = HashMap::new();
-        let lines: Vec<&str> = self.input.lines().collect();
+        self.parse_sections(None).map(|parsed| parsed.entries)
+    }
+
+    /// Parse into a `ToonDocument` with canonical key ordering, exposing
+    /// any `#!` directives found in the source
+    pub fn parse_document(&self) -> Result<ToonDocument, ToonError> {
+        let parsed = self.parse_sections(None)?;
+        Ok(ToonDocument::from(parsed.entries).with_directives(parsed.directives))
+    }
+
+    /// Parse and validate every guardrail section against `registry`.
+    /// Sections without a registered schema, headers whose fields or count
+    /// differ from the registered definition, and rows whose values do not
+    /// match the column types are rejected with `ToonError::SchemaViolation`.
+    pub fn parse_with_schema(&self, registry: &SchemaRegistry) -> Result<HashMap<String, ToonValue>, ToonError> {
+        self.parse_sections(Some(registry)).map(|parsed| parsed.entries)
+    }
+
+    fn parse_sections(&self, registry: Option<&SchemaRegistry>) -> Result<Parsed, ToonError> {
+        self.collect_sections(registry).inspect_err(|e| self.notify_error(e))
+    }
+
+    fn scanner<'p>(&'p self, registry: Option<&'p SchemaRegistry>, observer: Option<&'p dyn ParseObserver>) -> Scanner<'p> {
+        Scanner {
+            input: self.input,
+            dialect: self.dialect,
+            projections: &self.projections,
+            registry,
+            observer,
+        }
+    }
+
+    fn notify_error(&self, error: &ToonError) {
+        if let Some(observer) = self.observer {
+            observer.error_encountered(error);
+        }
+    }
+
+    fn collect_sections(&self, registry: Option<&SchemaRegistry>) -> Result<Parsed, ToonError> {
+        let mut result = EntryMap::new(self.input, self.duplicate_keys);
+        let mut directives = Vec::new();
+        let mut directive_lines = Vec::new();
+        self.scanner(registry, self.observer)
+            .scan(0..self.input.len(), &mut result, &mut directives, &mut directive_lines)?;
+
+        if self.verify_checksum {
+            directive::verify_checksum(self.input, &directives, &directive_lines)?;
+        }
+
+        Ok(Parsed {
+            entries: result.into_entries(),
+            directives,
+        })
+    }
+}
+
+/// The parser state needed to scan one byte range of the input. Sequential
+/// parsing scans the whole document; `parse_parallel` gives each worker its
+/// own scanner over a range of whole sections.
+struct Scanner<'p> {
+    input: &'p str,
+    dialect: Dialect,
+    projections: &'p HashMap<String, Projection<'p>>,
+    registry: Option<&'p SchemaRegistry>,
+    observer: Option<&'p dyn ParseObserver>,
+}
+
+impl<'p> Scanner<'p> {
+    /// Parse the lines in `range`, inserting entries into `sink` in source
+    /// order and recording directives with their line ranges
+    fn scan(
+        &self,
+        range: Range<usize>,
+        sink: &mut impl EntrySink,
+        directives: &mut Vec<Directive>,
+        directive_lines: &mut Vec<Range<usize>>,
+    ) -> Result<(), ToonError> {
+        let registry = self.registry;
+        let mut section: Option<Section> = None;
+        let mut pos = range.start;
+
+        while pos < range.end {
+            let end = lexer::line_end(self.input, pos);
+            let raw_line = &self.input[pos..end];
+            let line = raw_line.trim();
+            let line_offset = pos + (raw_line.len() - raw_line.trim_start().len());
+            let next_line = end + 1;
+
+            if let Some((name, value)) = directive::split_directive(line) {
+                let found = Directive {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    span: Span::from_offset(self.input, line_offset),
+                };
+                directive::check_version(&found)?;
+                directives.push(found);
+                directive_lines.push(pos..next_line.min(self.input.len()));
+                pos = next_line;
+                continue;
+            }
 
-        for line in lines {
-            let line = line.trim();
             if line.is_empty() || line.starts_with('#') {
+                pos = next_line;
                 continue;
             }
 
-            // Try to parse as guardrail header
-            if let Ok((_remaining, header)) = Self::parse_header(line) {
-                // Pre-allocate memory based on count (Zero Entropy enforcement)
-                let value = ToonValue::Schema {
-                    count: header.count,
-                    schema: header.schema.iter().map(|s| s.to_string()).collect(),
-                    data: Vec::with_capacity(header.count),
+            let header = match is_header_line(line).then(|| header_line(self.input, line, line_offset)) {
+                Some(Err(_)) if self.dialect.legacy_lines => None,
+                header => header.transpose()?,
+            };
+            if let Some(header) = header {
+                if let Some(registry) = registry {
+                    registry.check_header(&header, self.input, line_offset)?;
+                }
+                if let Some(observer) = self.observer {
+                    observer.header_parsed(&header, Span::from_offset(self.input, line_offset));
+                }
+
+                if let Some(done) = section.take() {
+                    done.close(self.input, sink)?;
+                }
+                let dialect = Dialect {
+                    delimiter: header.delimiter.unwrap_or(self.dialect.delimiter),
+                    ..self.dialect
                 };
-                result.insert(header.key.to_string(), value);
-            } else if let Some(equal_pos) = line.find('=') {
-                // Parse simple key-value pairs
+                let mut opened = Section::open(header, line_offset, self.input.len() - pos, dialect);
+                if let Some(projection) = self.projections.get(&opened.key) {
+                    if let Some(columns) = &projection.columns {
+                        opened.projection = Some(ActiveProjection::resolve(
+                            &opened.key,
+                            &opened.schema,
+                            columns,
+                            self.input,
+                            line_offset,
+                        )?);
+                    }
+                    opened.filtered = projection.filter.is_some();
+                }
+                section = Some(opened);
+                pos = next_line;
+            } else if let Some(equal_pos) = key_value_split(line, self.dialect.legacy_lines && section.is_none()) {
+                if let Some(done) = section.take() {
+                    done.close(self.input, sink)?;
+                }
+
+                // Parse simple key-value pairs; quoted values may span lines
                 let key = line[..equal_pos].trim().to_string();
-                let value_str = line[equal_pos + 1..].trim();
-                let value = ToonValue::parse_value(value_str);
-                result.insert(key, value);
+                let value_start = line_offset + equal_pos + 1;
+                let (value, after) = self.parse_kv_value(value_start, end)?;
+                sink.insert(key, value, line_offset)?;
+                pos = after;
+            } else if let Some(open) = section.as_mut() {
+                // Registry checks need every field, so projection is applied
+                // after the row is validated in that case
+                let (fields, field_count, after) = match (&open.projection, registry) {
+                    (Some(projection), None) => lexer::scan_record_projected(self.input, line_offset, &projection.keep, open.dialect)?,
+                    _ => {
+                        let (fields, after) = lexer::scan_record(self.input, line_offset, open.schema.len(), open.dialect)?;
+                        let field_count = fields.len();
+                        (fields, field_count, after)
+                    }
+                };
+                if field_count != open.schema.len() {
+                    return Err(ToonError::ParseError {
+                        message: format!(
+                            "Row has {} fields, schema '{}' declares {}",
+                            field_count,
+                            open.key,
+                            open.schema.len()
+                        ),
+                        span: Span::from_offset(self.input, line_offset),
+                    });
+                }
+                if let Some(registry) = registry {
+                    registry.check_row(&open.key, &fields, self.input, line_offset)?;
+                }
+                let fields = match (&open.projection, registry) {
+                    (Some(projection), None) => projection.arrange(fields),
+                    (Some(projection), Some(_)) => projection.select(fields),
+                    (None, _) => fields,
+                };
+
+                if let Some(observer) = self.observer {
+                    observer.row_validated(&open.key, open.rows_seen, &fields);
+                }
+                open.rows_seen += 1;
+                let keep = match self.projections.get(&open.key).and_then(|p| p.filter.as_ref()) {
+                    Some(predicate) => predicate(&RowView::new(open.columns(), &fields)),
+                    None => true,
+                };
+                if keep {
+                    open.data.push(fields);
+                }
+                pos = after;
+            } else if self.dialect.legacy_lines {
+                pos = next_line;
+            } else {
+                return Err(ToonError::ParseError {
+                    message: "Expected a guardrail header or key-value pair".to_string(),
+                    span: Span::from_offset(self.input, line_offset),
+                });
             }
         }
 
-        Ok(result)
+        if let Some(done) = section.take() {
+            done.close(self.input, sink)?;
+        }
+        Ok(())
+    }
+
+    /// Parse the right-hand side of `key = value` starting at `start`.
+    /// Returns the value and the offset of the next line to parse.
+    fn parse_kv_value(&self, start: usize, line_end: usize) -> Result<(ToonValue, usize), ToonError> {
+        let raw = &self.input[start..line_end];
+        let value_offset = start + (raw.len() - raw.trim_start().len());
+
+        if self.input.as_bytes().get(value_offset) != Some(&b'"') {
+            if self.dialect.strict_numbers {
+                lexer::check_locale_number(self.input, value_offset, raw.trim())?;
+            }
+            return Ok((ToonValue::parse_value(raw.trim()), line_end + 1));
+        }
+
+        let (text, after_quote) = lexer::scan_quoted(self.input, value_offset)?;
+        let end = lexer::line_end(self.input, after_quote);
+        let trailing = &self.input[after_quote..end];
+        if !trailing.trim().is_empty() {
+            return Err(ToonError::ParseError {
+                message: "Unexpected characters after quoted value".to_string(),
+                span: Span::from_offset(self.input, end - trailing.trim_start().len()),
+            });
+        }
+        Ok((ToonValue::String(text), end + 1))
+    }
+}
+
+/// Parse untrusted bytes into a document.
+///
+/// Guaranteed never to panic: invalid UTF-8, JSON input and every malformed
+/// construct are reported as `ToonError`. This is the entry point for input
+/// that arrives from the UI, and the one exercised by the fuzz targets.
+pub fn parse_no_panic(bytes: &[u8]) -> Result<ToonDocument, ToonError> {
+    let input = std::str::from_utf8(bytes).map_err(|e| ToonError::ParseError {
+        message: "Input is not valid UTF-8".to_string(),
+        span: Span {
+            line: 0,
+            column: 0,
+            offset: e.valid_up_to(),
+        },
+    })?;
+    ToonParser::try_new(input)?.parse_document()
+}
+
+/// A guardrail section whose rows are still being collected
+struct Section {
+    key: String,
+    count: usize,
+    schema: Vec<String>,
+    data: Vec<Vec<String>>,
+    header_offset: usize,
+    /// Rows read from the source, including any dropped by a filter
+    rows_seen: usize,
+    projection: Option<ActiveProjection>,
+    filtered: bool,
+    dialect: Dialect,
+}
+
+impl Section {
+    fn open(header: ToonHeader<'_>, header_offset: usize, remaining: usize, dialect: Dialect) -> Self {
+        Self {
+            key: header.key.to_string(),
+            count: header.count,
+            schema: header.schema.iter().map(|s| s.to_string()).collect(),
+            // Pre-allocate memory based on count (Zero Entropy enforcement),
+            // bounded by the remaining input so a forged count cannot abort
+            data: Vec::with_capacity(header.count.min(remaining)),
+            header_offset,
+            rows_seen: 0,
+            projection: None,
+            filtered: false,
+            dialect,
+        }
+    }
+
+    /// Columns of the rows being collected, after projection
+    fn columns(&self) -> &[String] {
+        match &self.projection {
+            Some(projection) => &projection.columns,
+            None => &self.schema,
+        }
+    }
+
+    fn close(self, source: &str, sink: &mut impl EntrySink) -> Result<(), ToonError> {
+        if self.rows_seen != 0 && self.rows_seen != self.count {
+            return Err(ToonError::CountMismatch {
+                expected: self.count,
+                found: self.rows_seen,
+                span: Span::from_offset(source, self.header_offset),
+            });
+        }
+
+        let count = if self.filtered && self.rows_seen != 0 {
+            self.data.len()
+        } else {
+            self.count
+        };
+        let value = ToonValue::Schema {
+            count,
+            schema: match self.projection {
+                Some(projection) => projection.columns,
+                None => self.schema,
+            },
+            data: self.data,
+        };
+        sink.insert(self.key, value, self.header_offset)
+    }
+}
+
+/// Length of an identifier (`[a-zA-Z_][a-zA-Z0-9_]*`) at the start of `line`
+fn identifier_len(line: &str) -> usize {
+    let mut chars = line.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return 0,
+    }
+    chars
+        .find(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .map(|(i, _)| i)
+        .unwrap_or(line.len())
+}
+
+/// A header line is an identifier followed by a `[count]` block
+fn is_header_line(line: &str) -> bool {
+    let len = identifier_len(line);
+    len > 0 && line[len..].trim_start().starts_with('[')
+}
+
+/// Parse a line `is_header_line` accepted, which must hold nothing but
+/// the header
+fn header_line<'a>(input: &str, line: &'a str, line_offset: usize) -> Result<ToonHeader<'a>, ToonError> {
+    let (remaining, header) = ToonParser::parse_header(line).map_err(|e| ToonError::InvalidHeader {
+        span: Span::from_offset(input, line_offset + nom_error_offset(line, &e)),
+    })?;
+    if !remaining.trim().is_empty() {
+        let trailing = line.len() - remaining.trim_start().len();
+        return Err(ToonError::InvalidHeader {
+            span: Span::from_offset(input, line_offset + trailing),
+        });
+    }
+    Ok(header)
+}
+
+/// Position of the `=` in a key-value line whose key is an identifier, or
+/// with `any_key`, the first `=` of the line
+fn key_value_split(line: &str, any_key: bool) -> Option<usize> {
+    let len = identifier_len(line);
+    let rest = &line[len..];
+    let trimmed = rest.trim_start();
+    if len > 0 && trimmed.starts_with('=') {
+        Some(len + (rest.len() - trimmed.len()))
+    } else if any_key {
+        line.find('=')
+    } else {
+        None
+    }
+}
+
+/// Byte offset into `input` at which a nom parser failed
+fn nom_error_offset(input: &str, err: &nom::Err<nom::error::Error<&str>>) -> usize {
+    match err {
+        nom::Err::Error(e) | nom::Err::Failure(e) => input.len() - e.input.len(),
+        nom::Err::Incomplete(_) => input.len(),
     }
 }
 
 /// TOON value representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ToonValue {
     String(String),
+    /// Floating-point number, used only for exponent notation, `inf`/`nan`
+    /// and values beyond `Decimal` precision
     Number(f64),
+    Integer(i64),
+    /// Integers above `i64::MAX`
+    Unsigned(u64),
+    /// Plain decimal literal, kept digit for digit
+    Decimal(Decimal),
     Boolean(bool),
     Schema {
         count: usize,
         schema: Vec<String>,
-        data: Vec<String>,
+        data: Vec<Vec<String>>,
     },
 }
 
 impl ToonValue {
-    fn parse_value(input: &str) -> Self {
-        // Remove quotes if present
-        let trimmed = input.trim_matches('"').trim_matches('\'');
-        
-        if trimmed == "true" {
+    /// Interpret an unquoted scalar. Double-quoted strings are handled by the
+    /// lexer; single quotes are stripped and taken literally.
+    pub(crate) fn parse_value(input: &str) -> Self {
+        if input.len() >= 2 && input.starts_with('\'') && input.ends_with('\'') {
+            return ToonValue::String(input[1..input.len() - 1].to_string());
+        }
+
+        if input == "true" {
             ToonValue::Boolean(true)
-        } else if trimmed == "false" {
+        } else if input == "false" {
             ToonValue::Boolean(false)
-        } else if let Ok(num) = trimmed.parse::<f64>() {
-            ToonValue::Number(num)
+        } else if let Some(number) = number::parse_number(input) {
+            number
         } else {
-            ToonValue::String(trimmed.to_string())
+            ToonValue::String(input.to_string())
         }
     }
 }
@@ -186,7 +678,7 @@ mod tests {
     #[test]
     fn test_guardrail_header_parsing() {
         let input = "market_ticks [1000]{symbol,price,vol,ts}";
-        let (remaining, header) = ToonParser::parse_header(input).unwrap();
+        let (_remaining, header) = ToonParser::parse_header(input).unwrap();
         assert_eq!(header.key, "market_ticks");
         assert_eq!(header.count, 1000);
         assert_eq!(header.schema, vec!["symbol", "price", "vol", "ts"]);
@@ -198,4 +690,651 @@ mod tests {
         let result = parser.parse().unwrap();
         assert_eq!(result.len(), 2);
     }
+
+    #[test]
+    fn test_rows_collected_into_section() {
+        let parser = ToonParser::new("ticks [2]{symbol,price}\nAAPL,150.0\nMSFT,410.5");
+        let result = parser.parse().unwrap();
+        match &result["ticks"] {
+            ToonValue::Schema { count, data, .. } => {
+                assert_eq!(*count, 2);
+                assert_eq!(data[1], vec!["MSFT", "410.5"]);
+            }
+            other => panic!("expected schema, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_legacy_lines_skip_what_older_parsers_skipped() {
+        let input = "-- exported 2024 --\nmy key = 1\nticks [x]{symbol}\nticks [1]{symbol,price}\nAAPL,150.5\nvenue = NASDAQ\nstray line";
+        assert!(ToonParser::new(input).parse().is_err());
+        let result = ToonParser::new(input).legacy_lines(true).parse().unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result["my key"], ToonValue::Integer(1));
+        assert_eq!(result["venue"], ToonValue::String("NASDAQ".to_string()));
+        match &result["ticks"] {
+            ToonValue::Schema { data, .. } => assert_eq!(data, &vec![vec!["AAPL".to_string(), "150.5".to_string()]]),
+            other => panic!("expected schema, got {:?}", other),
+        }
+
+        // Inside a section other lines are still rows
+        assert!(ToonParser::new("ticks [1]{symbol,price}\nstray line").legacy_lines(true).parse().is_err());
+    }
+
+    #[test]
+    fn test_count_mismatch_span_points_at_header() {
+        let input = "mode = strict\n  ticks [3]{symbol,price}\nAAPL,150.0";
+        let err = ToonParser::new(input).parse().unwrap_err();
+        assert_eq!(
+            err,
+            ToonError::CountMismatch {
+                expected: 3,
+                found: 1,
+                span: Span { line: 2, column: 3, offset: 16 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_header_span() {
+        let input = "ticks [12x]{symbol}";
+        let err = ToonParser::new(input).parse().unwrap_err();
+        assert_eq!(err.span(), Span { line: 1, column: 10, offset: 9 });
+    }
+
+    #[test]
+    fn test_render_caret_snippet() {
+        let input = "ticks [1]{symbol,price}\nAAPL";
+        let err = ToonParser::new(input).parse().unwrap_err();
+        let rendered = err.render(input);
+        assert!(rendered.starts_with("error: Parse Error: Row has 1 fields"));
+        assert!(rendered.ends_with("2 | AAPL\n  | ^"));
+    }
+
+    fn schema_rows(result: &HashMap<String, ToonValue>, key: &str) -> Vec<Vec<String>> {
+        match &result[key] {
+            ToonValue::Schema { data, .. } => data.clone(),
+            other => panic!("expected schema, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_quoted_field_with_embedded_comma() {
+        let parser = ToonParser::new("notes [1]{id,text}\n1,\"a,b\"");
+        let rows = schema_rows(&parser.parse().unwrap(), "notes");
+        assert_eq!(rows, vec![vec!["1".to_string(), "a,b".to_string()]]);
+    }
+
+    #[test]
+    fn test_escaped_quote_and_backslash() {
+        let parser = ToonParser::new(r#"notes [1]{text}
+"say \"hi\" \\ {ok}""#);
+        let rows = schema_rows(&parser.parse().unwrap(), "notes");
+        assert_eq!(rows[0][0], r#"say "hi" \ {ok}"#);
+    }
+
+    #[test]
+    fn test_multiline_quoted_field() {
+        let parser = ToonParser::new("notes [2]{id,text}\n1,\"first\nsecond\"\n2,\"tab\\there\\u{1F600}\"");
+        let rows = schema_rows(&parser.parse().unwrap(), "notes");
+        assert_eq!(rows[0][1], "first\nsecond");
+        assert_eq!(rows[1][1], "tab\there\u{1F600}");
+    }
+
+    #[test]
+    fn test_quoted_key_value_stays_string() {
+        let parser = ToonParser::new("version = \"2.0\"\nlabel = \"x = [1, 2]\"");
+        let result = parser.parse().unwrap();
+        assert_eq!(result["version"], ToonValue::String("2.0".to_string()));
+        assert_eq!(result["label"], ToonValue::String("x = [1, 2]".to_string()));
+    }
+
+    #[test]
+    fn test_unterminated_quote_reports_span() {
+        let input = "notes [1]{text}\n\"open";
+        let err = ToonParser::new(input).parse().unwrap_err();
+        assert_eq!(err.span(), Span { line: 2, column: 1, offset: 16 });
+    }
+
+    #[test]
+    fn test_unknown_escape_rejected() {
+        let err = ToonParser::new("notes [1]{text}\n\"bad \\q\"").parse().unwrap_err();
+        assert!(matches!(err, ToonError::ParseError { .. }));
+    }
+
+    #[test]
+    fn test_writer_round_trip() {
+        let awkward = [
+            "a,b", "\"", "\\", "line1\nline2", "  padded ", "", "#hash", "k=v", "[x]{y}", "ünïcødé", "\u{7}",
+        ];
+        let mut document = HashMap::new();
+        document.insert(
+            "rows".to_string(),
+            ToonValue::Schema {
+                count: awkward.len(),
+                schema: vec!["id".to_string(), "text".to_string()],
+                data: awkward
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| vec![i.to_string(), s.to_string()])
+                    .collect(),
+            },
+        );
+        for (i, s) in awkward.iter().enumerate() {
+            document.insert(format!("s{}", i), ToonValue::String(s.to_string()));
+        }
+        document.insert("looks_numeric".to_string(), ToonValue::String("1.5".to_string()));
+        document.insert("looks_bool".to_string(), ToonValue::String("true".to_string()));
+        document.insert("number".to_string(), ToonValue::Number(-0.125));
+        document.insert("flag".to_string(), ToonValue::Boolean(false));
+
+        let text = to_toon_string(&document);
+        let parsed = ToonParser::new(&text).parse().unwrap();
+        assert_eq!(parsed, document);
+    }
+
+    fn tick_registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            SchemaDefinition::new("ticks")
+                .field("symbol", FieldType::String)
+                .field("price", FieldType::Number)
+                .field("vol", FieldType::Integer)
+                .count_bounds(1, Some(100)),
+        );
+        registry
+    }
+
+    #[test]
+    fn test_schema_registry_accepts_conforming_document() {
+        let parser = ToonParser::new("ticks [2]{symbol,price,vol}\nAAPL,150.5,100\nMSFT,410,7");
+        let result = parser.parse_with_schema(&tick_registry()).unwrap();
+        assert_eq!(schema_rows(&result, "ticks").len(), 2);
+    }
+
+    #[test]
+    fn test_schema_registry_rejects_unregistered_section() {
+        let parser = ToonParser::new("quotes [1]{symbol}\nAAPL");
+        let err = parser.parse_with_schema(&tick_registry()).unwrap_err();
+        assert!(matches!(err, ToonError::SchemaViolation { .. }));
+    }
+
+    #[test]
+    fn test_schema_registry_rejects_header_mismatch() {
+        let registry = tick_registry();
+        let wrong_fields = ToonParser::new("ticks [1]{symbol,vol,price}\nAAPL,1,2");
+        assert!(matches!(
+            wrong_fields.parse_with_schema(&registry),
+            Err(ToonError::SchemaViolation { .. })
+        ));
+        let too_many = ToonParser::new("ticks [500]{symbol,price,vol}");
+        assert!(matches!(
+            too_many.parse_with_schema(&registry),
+            Err(ToonError::SchemaViolation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_schema_registry_rejects_mistyped_value() {
+        let input = "ticks [2]{symbol,price,vol}\nAAPL,150.5,100\nMSFT,n/a,7";
+        let err = ToonParser::new(input).parse_with_schema(&tick_registry()).unwrap_err();
+        assert_eq!(err.span().line, 3);
+    }
+
+    #[test]
+    fn test_binary_round_trip_preserves_text() {
+        let input = "ticks [3]{symbol,price,vol,live,note}\n\
+                     AAPL,150.5,100,true,\"a,b\"\n\
+                     MSFT,410,007,false,\"\"\n\
+                     GOOG,1e3,-5,true,x\n\
+                     venue = NASDAQ\nlatency = 0.25\nhalted = false";
+        let parsed = ToonParser::new(input).parse().unwrap();
+        let bytes = encode_binary(&parsed);
+        assert_eq!(decode_binary(&bytes).unwrap(), parsed);
+        // Deterministic output regardless of HashMap iteration order
+        assert_eq!(encode_binary(&parsed.clone()), bytes);
+    }
+
+    #[test]
+    fn test_binary_rejects_truncated_and_corrupt_input() {
+        let parsed = ToonParser::new("ticks [1]{symbol,price}\nAAPL,1.5").parse().unwrap();
+        let bytes = encode_binary(&parsed);
+        for len in 0..bytes.len() {
+            assert!(decode_binary(&bytes[..len]).is_err());
+        }
+        let mut corrupt = bytes.clone();
+        corrupt[5] = 9;
+        assert!(matches!(decode_binary(&corrupt), Err(ToonError::InvalidBinary { .. })));
+    }
+
+    #[test]
+    fn test_binary_schema_sizes_bounded_by_input() {
+        let schema = |rows: u64, cols: u32, body: &[u8]| {
+            let mut bytes = b"TOONB\x02".to_vec();
+            bytes.extend(1u32.to_le_bytes());
+            bytes.extend(1u32.to_le_bytes());
+            bytes.extend(b"t\x03");
+            bytes.extend(rows.to_le_bytes());
+            bytes.extend(rows.to_le_bytes());
+            bytes.extend(cols.to_le_bytes());
+            for _ in 0..cols {
+                bytes.extend(1u32.to_le_bytes());
+                bytes.extend(b"a\x03");
+            }
+            bytes.extend(body);
+            decode_binary(&bytes)
+        };
+        // Six bytes can hold six boolean values, not 2 x 4 or an overflowing count
+        assert!(schema(3, 2, &[1; 6]).is_ok());
+        assert!(matches!(schema(4, 2, &[1; 6]), Err(ToonError::InvalidBinary { .. })));
+        assert!(matches!(schema(u64::MAX / 2 + 1, 2, &[1; 6]), Err(ToonError::InvalidBinary { .. })));
+    }
+
+    fn build_ticks(order: &[usize]) -> ToonDocument {
+        let rows = [["AAPL", "150.5"], ["MSFT", "410"]];
+        let mut builder = ToonDocument::builder()
+            .pair("venue", "NASDAQ")
+            .pair("latency", -0.0)
+            .section("ticks", 2, &["symbol", "price"]);
+        for &i in order {
+            builder = builder.row("ticks", &rows[i]);
+        }
+        builder.pair("halted", false).build().unwrap()
+    }
+
+    #[test]
+    fn test_builder_output_is_canonical() {
+        let doc = build_ticks(&[0, 1]);
+        let text = doc.to_toon_string();
+        assert_eq!(
+            text,
+            "halted = false\nlatency = 0e0\nticks [2]{symbol,price}\nAAPL,150.5\nMSFT,410\nvenue = NASDAQ\n"
+        );
+        assert_eq!(build_ticks(&[0, 1]).to_toon_string(), text);
+        assert_eq!(ToonParser::new(&text).parse_document().unwrap().to_toon_string(), text);
+    }
+
+    #[test]
+    fn test_builder_validates_counts_and_rows() {
+        let short = ToonDocument::builder().section("ticks", 2, &["symbol"]).row("ticks", &["AAPL"]).build();
+        assert!(matches!(short, Err(ToonError::CountMismatch { expected: 2, found: 1, .. })));
+
+        let wide = ToonDocument::builder().section("ticks", 1, &["symbol"]).row("ticks", &["AAPL", "1"]).build();
+        assert!(matches!(wide, Err(ToonError::SchemaViolation { .. })));
+
+        let duplicate = ToonDocument::builder().pair("a", 1.0).pair("a", 2.0).build();
+        assert!(matches!(duplicate, Err(ToonError::SchemaViolation { .. })));
+    }
+
+    #[test]
+    fn test_directives_exposed_on_document() {
+        let parser = ToonParser::new("#!toon 2.1\n#!source exchange feed\n# plain comment\nvenue = NASDAQ");
+        let doc = parser.parse_document().unwrap();
+        assert_eq!(doc.directive("toon"), Some("2.1"));
+        assert_eq!(doc.directive("source"), Some("exchange feed"));
+        assert_eq!(doc.directives()[1].span.line, 2);
+        assert_eq!(doc.len(), 1);
+    }
+
+    #[test]
+    fn test_unsupported_version_rejected() {
+        let err = ToonParser::new("#!toon 3.0\nvenue = NASDAQ").parse().unwrap_err();
+        assert!(matches!(err, ToonError::ParseError { .. }));
+    }
+
+    #[test]
+    fn test_checksum_verification() {
+        let doc = ToonDocument::builder()
+            .pair("venue", "NASDAQ")
+            .section("ticks", 1, &["symbol"])
+            .row("ticks", &["AAPL"])
+            .build()
+            .unwrap();
+        let sealed = doc.to_sealed_string();
+        let verified = ToonParser::new(&sealed).verify_checksum(true).parse_document().unwrap();
+        assert_eq!(verified.to_toon_string(), doc.to_toon_string());
+
+        let tampered = sealed.replace("AAPL", "MSFT");
+        let err = ToonParser::new(&tampered).verify_checksum(true).parse().unwrap_err();
+        assert!(matches!(err, ToonError::ChecksumMismatch { .. }));
+        assert_eq!(err.span().line, 2);
+
+        // Without the option the tampered document is still accepted
+        assert!(ToonParser::new(&tampered).parse().is_ok());
+        let unsealed = ToonParser::new("venue = NASDAQ").verify_checksum(true).parse();
+        assert!(unsealed.is_err());
+    }
+
+    #[test]
+    fn test_try_new_rejects_json_without_panicking() {
+        let err = ToonParser::try_new("  {\"key\": 1}").err().unwrap();
+        assert_eq!(err, ToonError::EntropyDetected { span: Span { line: 1, column: 3, offset: 2 } });
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_parse_memory_mapped_file() {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "ticks [2]{{symbol,price}}\nAAPL,150.5\nMSFT,410\nvenue = NASDAQ\n").unwrap();
+        file.flush().unwrap();
+
+        let mapped = ToonParser::from_path(file.path()).unwrap();
+        let result = mapped.parser().unwrap().parse().unwrap();
+        assert_eq!(schema_rows(&result, "ticks").len(), 2);
+
+        let document = parse_file(file.path()).unwrap();
+        assert_eq!(document.get("venue"), Some(&ToonValue::String("NASDAQ".to_string())));
+
+        let empty = tempfile::NamedTempFile::new().unwrap();
+        assert!(parse_file(empty.path()).unwrap().is_empty());
+        assert!(matches!(parse_file("/nonexistent/ticks.toon"), Err(ToonError::Io { .. })));
+    }
+
+    #[test]
+    fn test_duplicate_key_policies() {
+        let input = "mode = fast\nticks [1]{symbol}\nAAPL\nmode = safe\nticks [1]{symbol}\nMSFT";
+
+        let last = ToonParser::new(input).parse().unwrap();
+        assert_eq!(last["mode"], ToonValue::String("safe".to_string()));
+
+        let first = ToonParser::new(input).duplicate_keys(DuplicateKeyPolicy::FirstWins).parse().unwrap();
+        assert_eq!(first["mode"], ToonValue::String("fast".to_string()));
+        assert_eq!(schema_rows(&first, "ticks"), vec![vec!["AAPL".to_string()]]);
+
+        let err = ToonParser::new(input).duplicate_keys(DuplicateKeyPolicy::Error).parse().unwrap_err();
+        assert_eq!(
+            err,
+            ToonError::DuplicateKey {
+                key: "mode".to_string(),
+                first_line: 1,
+                second_line: 4,
+                span: Span { line: 4, column: 1, offset: 35 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_policy_concatenates_sections() {
+        let input = "ticks [1]{symbol}\nAAPL\nmode = safe\nmode = safe\nticks [2]{symbol}\nMSFT\nGOOG";
+        let merged = ToonParser::new(input).duplicate_keys(DuplicateKeyPolicy::Merge).parse().unwrap();
+        assert_eq!(schema_rows(&merged, "ticks").len(), 3);
+
+        let conflicting = "ticks [1]{symbol}\nAAPL\nticks [1]{symbol,price}\nMSFT,1";
+        let err = ToonParser::new(conflicting).duplicate_keys(DuplicateKeyPolicy::Merge).parse().unwrap_err();
+        assert!(matches!(err, ToonError::DuplicateKey { first_line: 1, second_line: 3, .. }));
+    }
+
+    #[test]
+    fn test_parse_no_panic_on_adversarial_input() {
+        let cases: &[&[u8]] = &[
+            b"\xff\xfe",
+            b"  {\"json\": true}",
+            b"ticks [18446744073709551615]{a}",
+            b"ticks [99999999999999999999999]{a}",
+            b"ticks [18446744073709551615]{a}\nticks [18446744073709551615]{a}",
+            b"notes [1]{text}\n\"\\u{",
+            b"key = \"unterminated",
+            b"#!toon\n#!",
+            b"x [1]{a}\n\"a\"b",
+        ];
+        for case in cases {
+            let _ = parse_no_panic(case);
+        }
+        assert!(matches!(parse_no_panic(b"\xff\xfe"), Err(ToonError::ParseError { .. })));
+        assert!(matches!(parse_no_panic(b"{}"), Err(ToonError::EntropyDetected { .. })));
+
+        let merged = ToonParser::new("ticks [18446744073709551615]{a}\nticks [18446744073709551615]{a}")
+            .duplicate_keys(DuplicateKeyPolicy::Merge)
+            .parse()
+            .unwrap();
+        assert!(matches!(merged["ticks"], ToonValue::Schema { count: usize::MAX, .. }));
+    }
+
+    #[test]
+    fn test_diff_reports_keys_schema_and_rows() {
+        let a = ToonParser::new("ticks [2]{symbol,price}\nAAPL,150\nMSFT,410\nvenue = NASDAQ\nmode = fast")
+            .parse_document()
+            .unwrap();
+        let b = ToonParser::new("ticks [3]{symbol,price,vol}\nAAPL,151,10\nMSFT,410,20\nGOOG,99,30\nvenue = NYSE\nhalted = false")
+            .parse_document()
+            .unwrap();
+
+        let delta = diff(&a, &b);
+        assert_eq!(delta.added, vec!["halted".to_string()]);
+        assert_eq!(delta.removed, vec!["mode".to_string()]);
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed[0].key, "venue");
+
+        let section = &delta.sections[0];
+        assert_eq!(section.count, Some((2, 3)));
+        assert_eq!(section.schema.as_ref().unwrap().1.len(), 3);
+        assert_eq!(
+            section.rows,
+            vec![
+                RowDelta::Changed {
+                    index: 0,
+                    fields: vec![FieldChange {
+                        column: "price".to_string(),
+                        before: "150".to_string(),
+                        after: "151".to_string(),
+                    }],
+                },
+                RowDelta::Added {
+                    index: 2,
+                    row: vec!["GOOG".to_string(), "99".to_string(), "30".to_string()],
+                },
+            ]
+        );
+        assert!(delta.to_string().contains("~ row 0 price: 150 -> 151"));
+        assert!(diff(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_select_projects_columns_in_requested_order() {
+        let input = "ticks [2]{symbol,price,vol,ts}\n\"AAPL\",150.5,100,\"t\\u{31}\"\nMSFT,410,200,2\nvenue = NASDAQ";
+        let result = ToonParser::new(input).select("ticks", &["price", "symbol"]).parse().unwrap();
+        match &result["ticks"] {
+            ToonValue::Schema { count, schema, data } => {
+                assert_eq!(*count, 2);
+                assert_eq!(schema, &["price", "symbol"]);
+                assert_eq!(data[0], vec!["150.5", "AAPL"]);
+                assert_eq!(data[1], vec!["410", "MSFT"]);
+            }
+            other => panic!("expected schema, got {:?}", other),
+        }
+
+        let err = ToonParser::new(input).select("ticks", &["bid"]).parse().unwrap_err();
+        assert!(matches!(err, ToonError::SchemaViolation { .. }));
+
+        // Skipped fields are still validated and counted
+        let err = ToonParser::new("ticks [1]{symbol,price}\nAAPL,1,extra").select("ticks", &["symbol"]).parse().unwrap_err();
+        assert!(matches!(err, ToonError::ParseError { .. }));
+    }
+
+    #[test]
+    fn test_filter_keeps_matching_rows() {
+        let input = "ticks [3]{symbol,price,vol}\nAAPL,150,10\nMSFT,410,20\nGOOG,99,30";
+        let parser = ToonParser::new(input)
+            .select("ticks", &["symbol", "price"])
+            .filter("ticks", |row| row.get("price").and_then(|p| p.parse::<f64>().ok()).is_some_and(|p| p > 100.0));
+        let document = parser.parse_document().unwrap();
+        match document.get("ticks").unwrap() {
+            ToonValue::Schema { count, data, .. } => {
+                assert_eq!(*count, 2);
+                assert_eq!(data[1], vec!["MSFT", "410"]);
+            }
+            other => panic!("expected schema, got {:?}", other),
+        }
+        // The filtered document is self-consistent
+        assert_eq!(ToonParser::new(&document.to_toon_string()).parse_document().unwrap(), document);
+
+        let short = "ticks [3]{symbol,price,vol}\nAAPL,150,10";
+        let err = ToonParser::new(short).filter("ticks", |_| false).parse().unwrap_err();
+        assert!(matches!(err, ToonError::CountMismatch { expected: 3, found: 1, .. }));
+    }
+
+    #[test]
+    fn test_numbers_parse_losslessly() {
+        let input = "ts = 1700000000123456789\nbig = 18446744073709551615\nprice = 0.10000000000000000001\n\
+                     neg = -42\nsci = 1.5e3\nhuge = 123456789012345678901234567890";
+        let result = ToonParser::new(input).parse().unwrap();
+        assert_eq!(result["ts"], ToonValue::Integer(1_700_000_000_123_456_789));
+        assert_eq!(result["big"], ToonValue::Unsigned(u64::MAX));
+        assert_eq!(result["price"], ToonValue::Decimal(Decimal::new(10_000_000_000_000_000_001, 20)));
+        assert_eq!(result["neg"].as_i64(), Some(-42));
+        assert_eq!(result["neg"].as_u64(), None);
+        assert_eq!(result["sci"], ToonValue::Number(1500.0));
+        assert_eq!(result["huge"].as_decimal().map(|d| d.to_string()).as_deref(), Some("123456789012345678901234567890"));
+
+        let written = to_toon_string(&result);
+        assert!(written.contains("price = 0.10000000000000000001\n"));
+        assert!(written.contains("sci = 1.5e3\n"));
+        assert_eq!(ToonParser::new(&written).parse().unwrap(), result);
+        assert_eq!(decode_binary(&encode_binary(&result)).unwrap(), result);
+
+        assert_eq!(Decimal::parse("-0.05").unwrap().to_string(), "-0.05");
+        assert_eq!(Decimal::parse("1."), None);
+    }
+
+    #[test]
+    fn test_header_declared_and_default_delimiters() {
+        let input = "ticks [2|]{symbol|price|note}\nAAPL|150.5|\"a|b\"\nMSFT| 410 |\nvenue = NASDAQ";
+        let result = ToonParser::new(input).parse().unwrap();
+        assert_eq!(
+            schema_rows(&result, "ticks"),
+            vec![vec!["AAPL", "150.5", "a|b"], vec!["MSFT", "410", ""]]
+        );
+
+        let (_, header) = ToonParser::parse_header("ticks [2\t]{symbol\tprice}").unwrap();
+        assert_eq!(header.delimiter, Some(Delimiter::Tab));
+        assert_eq!(header.schema, vec!["symbol", "price"]);
+
+        let tsv = "ticks [2]{symbol\tprice\tvol}\nAAPL\t1,5\t\nMSFT\t410\t20";
+        let result = ToonParser::new(tsv).delimiter(Delimiter::Tab).parse().unwrap();
+        assert_eq!(schema_rows(&result, "ticks")[0], vec!["AAPL", "1,5", ""]);
+    }
+
+    #[test]
+    fn test_strict_numbers_reject_locale_artifacts() {
+        let tsv = "ticks [1;]{symbol;price}\nAAPL;1,5";
+        let err = ToonParser::new(tsv).strict_numbers(true).parse().unwrap_err();
+        assert_eq!(err.span(), Span { line: 2, column: 6, offset: 30 });
+
+        let err = ToonParser::new("price = 1.234,56").strict_numbers(true).parse().unwrap_err();
+        assert!(matches!(err, ToonError::ParseError { .. }));
+        for ok in ["price = \"1,5\"", "price = 1.5", "venue = NYSE 2", "ts = 1700000000"] {
+            assert!(ToonParser::new(ok).strict_numbers(true).parse().is_ok(), "{}", ok);
+        }
+        assert_eq!(
+            ToonParser::new("price = 1 000").parse().unwrap()["price"],
+            ToonValue::String("1 000".to_string())
+        );
+
+        // Written versions and addresses are quoted, so strict parsing reads them back
+        let document = ToonDocument::builder()
+            .pair("version", "2.1.0")
+            .section("hosts", 2, &["addr", "size"])
+            .row("hosts", &["10.0.0.1", "1 000"])
+            .row("hosts", &["10.0.0.2", "1.5"])
+            .build()
+            .unwrap();
+        let written = document.to_toon_string();
+        assert!(written.contains("\"2.1.0\"") && written.contains("\"10.0.0.1\",\"1 000\"") && written.contains(",1.5"));
+        assert_eq!(ToonParser::new(&written).strict_numbers(true).parse_document().unwrap(), document);
+    }
+
+    #[test]
+    fn test_observer_receives_parse_events() {
+        use std::cell::RefCell;
+
+        #[derive(Default)]
+        struct Recorder(RefCell<Vec<String>>);
+
+        impl ParseObserver for Recorder {
+            fn header_parsed(&self, header: &ToonHeader<'_>, span: Span) {
+                self.0.borrow_mut().push(format!("header {} line {}", header.key, span.line));
+            }
+            fn row_validated(&self, section: &str, index: usize, fields: &[String]) {
+                self.0.borrow_mut().push(format!("row {}[{}] {}", section, index, fields.join("|")));
+            }
+            fn error_encountered(&self, error: &ToonError) {
+                self.0.borrow_mut().push(format!("error {}", error.span()));
+            }
+        }
+
+        let recorder = Recorder::default();
+        let input = "ticks [2]{symbol,price}\nAAPL,1\nMSFT,2\nbad line";
+        assert!(ToonParser::new(input).observer(&recorder).parse().is_err());
+        assert_eq!(
+            recorder.0.into_inner(),
+            vec![
+                "header ticks line 1",
+                "row ticks[0] AAPL|1",
+                "row ticks[1] MSFT|2",
+                "error line 4, column 1",
+            ]
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parse_parallel_matches_sequential() {
+        let mut doc = String::from("#!toon 2.0\nvenue = NASDAQ\n");
+        for t in 0..40 {
+            doc.push_str(&format!("table_{} [500|]{{id|note}}\n", t));
+            for i in 0..500 {
+                // Quoted fields spanning lines must not be mistaken for headers
+                doc.push_str(&format!("{}|\"row {}\nfake_{} [1]{{x}}\"\n", i, i, i));
+            }
+            doc.push_str(&format!("total_{} = {}\n", t, t * 500));
+        }
+        assert!(doc.len() > 4 * 64 * 1024);
+
+        let sequential = ToonParser::new(&doc).parse().unwrap();
+        assert_eq!(ToonParser::new(&doc).parse_parallel().unwrap(), sequential);
+
+        let sealed = ToonDocument::from(sequential).to_sealed_string();
+        assert!(ToonParser::new(&sealed).verify_checksum(true).parse_parallel().is_ok());
+
+        // The first error in source order wins, as in a sequential parse
+        let broken = doc.replacen("table_3 [500|]", "table_1 [500|]", 1).replacen("total_30 = ", "total_30 ", 1);
+        let strict = ToonParser::new(&broken).duplicate_keys(DuplicateKeyPolicy::Error);
+        let expected = strict.parse().unwrap_err();
+        assert!(matches!(expected, ToonError::DuplicateKey { .. }));
+        assert_eq!(strict.parse_parallel().unwrap_err(), expected);
+
+        let legacy = format!("a note\nmy key = 1\nbroken [x]{{y}}\n{}", doc);
+        let sequential = ToonParser::new(&legacy).legacy_lines(true).parse().unwrap();
+        assert_eq!(sequential["my key"], ToonValue::Integer(1));
+        assert_eq!(ToonParser::new(&legacy).legacy_lines(true).parse_parallel().unwrap(), sequential);
+    }
+
+    #[test]
+    fn test_canonical_hash_ignores_cosmetic_differences() {
+        let a = ToonParser::new("venue = NASDAQ\nprice = 1.50\nticks [1]{symbol,price}\nAAPL,150.5")
+            .parse_document()
+            .unwrap();
+        let b = ToonParser::new("#!toon 2.0\n# comment\nticks  [1]{ symbol , price }\n  AAPL , 150.5\n\nprice=1.5\nvenue =   NASDAQ  ")
+            .parse_document()
+            .unwrap();
+        assert_ne!(a.to_toon_string(), b.to_toon_string());
+        assert_eq!(a.canonical_hash(), b.canonical_hash());
+        assert_eq!(a.canonical_hash().len(), 64);
+
+        let c = ToonParser::new("venue = NASDAQ\nprice = 1.51\nticks [1]{symbol,price}\nAAPL,150.5")
+            .parse_document()
+            .unwrap();
+        assert_ne!(a.canonical_hash(), c.canonical_hash());
+
+        // Numbers are compared by value, in entries and in rows
+        let hash = |input: &str| ToonParser::new(input).parse_document().unwrap().canonical_hash();
+        let one = hash("n = 1\nticks [1]{symbol,price}\nAAPL,150");
+        for same in ["n = 1.0\nticks [1]{symbol,price}\nAAPL,150.00", "n = 1e0\nticks [1]{symbol,price}\nAAPL,1.5e2"] {
+            assert_eq!(hash(same), one, "{}", same);
+        }
+        assert_ne!(hash("n = \"1\"\nticks [1]{symbol,price}\nAAPL,150"), one);
+        assert_ne!(hash("n = 1\nticks [1]{symbol,price}\nAAPL,150.01"), one);
+    }
 }
@@ -0,0 +1,225 @@
+//! Lossless numeric values
+//! Integers keep all 64 bits and plain decimals keep every digit, so
+//! timestamps and prices survive a parse/write round trip exactly. Only
+//! exponent notation, `inf`/`nan` and values too large for a `Decimal`
+//! fall back to `f64`.
+
+use crate::ToonValue;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Fixed-point decimal: `mantissa * 10^-scale`.
+///
+/// The scale is kept as written, so `1.50` and `1.5` are distinct values
+/// that serialize back to their original text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+/// Digits an `i128` mantissa can always hold
+const MAX_DIGITS: usize = 38;
+
+impl Decimal {
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Parse `-?digits(.digits)?` exactly. Returns `None` for any other
+    /// syntax or more than 38 significant digits.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (whole, fraction) = match unsigned.split_once('.') {
+            Some((whole, fraction)) if !fraction.is_empty() => (whole, fraction),
+            Some(_) => return None,
+            None => (unsigned, ""),
+        };
+        let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() || !all_digits(whole) || !all_digits(fraction) {
+            return None;
+        }
+        if whole.trim_start_matches('0').len() + fraction.len() > MAX_DIGITS {
+            return None;
+        }
+
+        let mut mantissa: i128 = 0;
+        for digit in whole.bytes().chain(fraction.bytes()) {
+            mantissa = mantissa * 10 + i128::from(digit - b'0');
+        }
+        Some(Self {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale: fraction.len() as u32,
+        })
+    }
+
+    /// The same value with trailing fractional zeros removed, so `1.50`
+    /// and `1.5` normalize to the same decimal
+    pub fn normalized(&self) -> Self {
+        let mut normalized = *self;
+        while normalized.scale > 0 && normalized.mantissa % 10 == 0 {
+            normalized.mantissa /= 10;
+            normalized.scale -= 1;
+        }
+        normalized
+    }
+
+    /// Nearest `f64`. Rounds for values with more than ~15 significant digits.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if self.mantissa < 0 {
+            f.write_str("-")?;
+        }
+        if scale == 0 {
+            return f.write_str(&digits);
+        }
+        let padded = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = padded.split_at(padded.len() - scale);
+        write!(f, "{}.{}", whole, fraction)
+    }
+}
+
+// Serialized as a string so no precision is lost in JSON
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Decimal::parse(&text).ok_or_else(|| de::Error::custom(format!("invalid decimal '{}'", text)))
+    }
+}
+
+/// Interpret an unquoted numeric literal, choosing the narrowest lossless
+/// representation. Returns `None` if `text` is not a number.
+pub(crate) fn parse_number(text: &str) -> Option<ToonValue> {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let is_integer = !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+
+    if is_integer {
+        if let Ok(n) = text.parse::<i64>() {
+            return Some(ToonValue::Integer(n));
+        }
+        if let Ok(n) = text.parse::<u64>() {
+            return Some(ToonValue::Unsigned(n));
+        }
+    }
+    if let Some(decimal) = Decimal::parse(text) {
+        return Some(ToonValue::Decimal(decimal));
+    }
+    text.parse::<f64>().ok().map(ToonValue::Number)
+}
+
+/// True for numbers written with a decimal comma or digit grouping, such
+/// as `1,5`, `1.234,56`, `1 000` or `1'000'000`. These are ambiguous
+/// between locales, so strict parsing rejects them instead of guessing.
+pub(crate) fn is_locale_number(text: &str) -> bool {
+    let body = text.strip_prefix('-').unwrap_or(text);
+    let is_separator = |c: char| matches!(c, ',' | '.' | ' ' | '\'');
+    let groups: Vec<&str> = body.split(is_separator).collect();
+    if groups.len() < 2 || !groups.iter().all(|g| !g.is_empty() && g.bytes().all(|b| b.is_ascii_digit())) {
+        return false;
+    }
+
+    let separators: Vec<char> = body.chars().filter(|c| is_separator(*c)).collect();
+    let grouped = groups[0].len() <= 3 && groups[1..].iter().all(|g| g.len() == 3);
+    separators.contains(&',')
+        || separators.iter().filter(|c| **c == '.').count() > 1
+        || (grouped && separators.iter().any(|c| matches!(c, ' ' | '\'')))
+}
+
+impl ToonValue {
+    /// True for `Integer`, `Unsigned`, `Decimal` and `Number`
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            ToonValue::Integer(_) | ToonValue::Unsigned(_) | ToonValue::Decimal(_) | ToonValue::Number(_)
+        )
+    }
+
+    /// The value as an `i64`, if it is an integer that fits exactly
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ToonValue::Integer(n) => Some(*n),
+            ToonValue::Unsigned(n) => i64::try_from(*n).ok(),
+            ToonValue::Decimal(d) if d.scale == 0 => i64::try_from(d.mantissa).ok(),
+            _ => None,
+        }
+    }
+
+    /// The value as a `u64`, if it is a non-negative integer that fits exactly
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            ToonValue::Integer(n) => u64::try_from(*n).ok(),
+            ToonValue::Unsigned(n) => Some(*n),
+            ToonValue::Decimal(d) if d.scale == 0 => u64::try_from(d.mantissa).ok(),
+            _ => None,
+        }
+    }
+
+    /// The value as an exact decimal. `Number` is excluded because most
+    /// binary floats have no short exact decimal form.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            ToonValue::Integer(n) => Some(Decimal::new(i128::from(*n), 0)),
+            ToonValue::Unsigned(n) => Some(Decimal::new(i128::from(*n), 0)),
+            ToonValue::Decimal(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    /// The value as an `f64`. This may round; prefer the exact accessors
+    /// for integers and monetary amounts.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ToonValue::Integer(n) => Some(*n as f64),
+            ToonValue::Unsigned(n) => Some(*n as f64),
+            ToonValue::Decimal(d) => Some(d.to_f64()),
+            ToonValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl From<i64> for ToonValue {
+    fn from(value: i64) -> Self {
+        ToonValue::Integer(value)
+    }
+}
+
+impl From<u64> for ToonValue {
+    fn from(value: u64) -> Self {
+        // Keep `Unsigned` for values that do not fit `Integer`, matching the parser
+        match i64::try_from(value) {
+            Ok(n) => ToonValue::Integer(n),
+            Err(_) => ToonValue::Unsigned(value),
+        }
+    }
+}
+
+impl From<Decimal> for ToonValue {
+    fn from(value: Decimal) -> Self {
+        ToonValue::Decimal(value)
+    }
+}
@@ -0,0 +1,23 @@
+//! Structured parse events
+//! Hosts that want visibility into parsing implement `ParseObserver` and
+//! route the events into their own logging or telemetry. The parser itself
+//! never writes to stdout or stderr.
+
+use crate::{Span, ToonError, ToonHeader};
+
+/// Callbacks invoked while a document is parsed. Every method has an empty
+/// default, so implementors only override the events they care about.
+///
+/// Methods take `&self` so one observer can be shared between parsers;
+/// use interior mutability to accumulate state.
+pub trait ParseObserver {
+    /// A guardrail header was parsed and accepted
+    fn header_parsed(&self, _header: &ToonHeader<'_>, _span: Span) {}
+
+    /// A row passed field-count and schema validation. `index` counts rows
+    /// in the source, including any later dropped by a filter.
+    fn row_validated(&self, _section: &str, _index: usize, _fields: &[String]) {}
+
+    /// Parsing stopped with `error`
+    fn error_encountered(&self, _error: &ToonError) {}
+}
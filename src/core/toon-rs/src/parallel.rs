@@ -0,0 +1,152 @@
+//! Multi-threaded parsing (`parallel` feature)
+//! A cheap sequential pass finds the line where each guardrail section
+//! starts, skipping quoted fields without unescaping them. Runs of sections
+//! are then parsed on the rayon pool and merged in source order, so the
+//! result, and the first error reported, match a sequential parse exactly.
+
+use crate::directive::{self, Directive};
+use crate::duplicates::{EntryMap, EntrySink};
+use crate::lexer::{self, Dialect};
+use crate::{header_line, is_header_line, key_value_split, Scanner, ToonError, ToonParser, ToonValue};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Smallest byte range worth handing to a worker
+const MIN_CHUNK: usize = 64 * 1024;
+
+/// What one worker produced for its range, up to its first error
+struct Chunk {
+    entries: Vec<(String, ToonValue, usize)>,
+    directives: Vec<Directive>,
+    directive_lines: Vec<Range<usize>>,
+    error: Option<ToonError>,
+}
+
+impl<'a> ToonParser<'a> {
+    /// Parse like `parse`, splitting independent sections across threads.
+    ///
+    /// Worth it for large documents with many sections; a single section is
+    /// never split. Schema registries are not supported, and an attached
+    /// observer is only told about errors, since row and header events would
+    /// arrive from several threads out of order.
+    pub fn parse_parallel(&self) -> Result<HashMap<String, ToonValue>, ToonError> {
+        self.collect_parallel().inspect_err(|e| self.notify_error(e))
+    }
+
+    fn collect_parallel(&self) -> Result<HashMap<String, ToonValue>, ToonError> {
+        let (input, dialect, projections) = (self.input, self.dialect, &self.projections);
+        let ranges = chunk_ranges(input, dialect, rayon::current_num_threads());
+
+        let chunks: Vec<Chunk> = ranges
+            .into_par_iter()
+            .map(|range| {
+                let scanner = Scanner {
+                    input,
+                    dialect,
+                    projections,
+                    registry: None,
+                    observer: None,
+                };
+                let mut chunk = Chunk {
+                    entries: Vec::new(),
+                    directives: Vec::new(),
+                    directive_lines: Vec::new(),
+                    error: None,
+                };
+                chunk.error = scanner
+                    .scan(range, &mut chunk.entries, &mut chunk.directives, &mut chunk.directive_lines)
+                    .err();
+                chunk
+            })
+            .collect();
+
+        let mut result = EntryMap::new(input, self.duplicate_keys);
+        let mut directives = Vec::new();
+        let mut directive_lines = Vec::new();
+        for chunk in chunks {
+            for (key, value, offset) in chunk.entries {
+                result.insert(key, value, offset)?;
+            }
+            if let Some(error) = chunk.error {
+                return Err(error);
+            }
+            directives.extend(chunk.directives);
+            directive_lines.extend(chunk.directive_lines);
+        }
+
+        if self.verify_checksum {
+            directive::verify_checksum(input, &directives, &directive_lines)?;
+        }
+        Ok(result.into_entries())
+    }
+}
+
+/// Split `input` at section headers into at most a few ranges per thread
+fn chunk_ranges(input: &str, dialect: Dialect, threads: usize) -> Vec<Range<usize>> {
+    let target = (input.len() / (threads.max(1) * 4)).max(MIN_CHUNK);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for boundary in section_starts(input, dialect) {
+        if boundary - start >= target {
+            ranges.push(start..boundary);
+            start = boundary;
+        }
+    }
+    ranges.push(start..input.len());
+    ranges
+}
+
+/// Offsets of the lines that open a guardrail section. Stops at the first
+/// malformed line; the remainder is left to a worker, which reports the
+/// error with its proper span.
+fn section_starts(input: &str, default: Dialect) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut dialect = default;
+    let mut in_section = false;
+    let mut pos = 0;
+
+    while pos < input.len() {
+        let end = lexer::line_end(input, pos);
+        let raw_line = &input[pos..end];
+        let line = raw_line.trim();
+        let line_offset = pos + (raw_line.len() - raw_line.trim_start().len());
+
+        if line.is_empty() || line.starts_with('#') {
+            pos = end + 1;
+        } else if is_header_line(line) && !(default.legacy_lines && header_line(input, line, line_offset).is_err()) {
+            starts.push(pos);
+            let declared = ToonParser::parse_header(line).ok().and_then(|(_, header)| header.delimiter);
+            dialect = Dialect {
+                delimiter: declared.unwrap_or(default.delimiter),
+                strict_numbers: false,
+                ..default
+            };
+            in_section = true;
+            pos = end + 1;
+        } else if let Some(equal_pos) = key_value_split(line, default.legacy_lines && !in_section) {
+            in_section = false;
+            let raw_value = &line[equal_pos + 1..];
+            let value = raw_value.trim_start();
+            pos = if value.starts_with('"') {
+                let quote = line_offset + equal_pos + 1 + (raw_value.len() - value.len());
+                match lexer::skip_quoted(input, quote) {
+                    Ok(after) => lexer::line_end(input, after) + 1,
+                    Err(_) => break,
+                }
+            } else {
+                end + 1
+            };
+        } else if in_section {
+            match lexer::scan_record_projected(input, line_offset, &[], dialect) {
+                Ok((_, _, after)) => pos = after,
+                Err(_) => break,
+            }
+        } else if default.legacy_lines {
+            pos = end + 1;
+        } else {
+            break;
+        }
+    }
+    starts
+}
@@ -0,0 +1,115 @@
+//! Column projection and row filtering during parse
+//! Selected columns are the only fields the lexer materializes; every other
+//! field is validated and skipped without allocating.
+
+use crate::{Span, ToonError};
+
+/// A row handed to a filter predicate, addressed by column name
+#[derive(Debug, Clone, Copy)]
+pub struct RowView<'r> {
+    columns: &'r [String],
+    fields: &'r [String],
+}
+
+impl<'r> RowView<'r> {
+    pub(crate) fn new(columns: &'r [String], fields: &'r [String]) -> Self {
+        Self { columns, fields }
+    }
+
+    /// Value of `column`, or `None` if it is not part of the row
+    pub fn get(&self, column: &str) -> Option<&'r str> {
+        let index = self.columns.iter().position(|c| c == column)?;
+        self.fields.get(index).map(String::as_str)
+    }
+
+    pub fn columns(&self) -> &'r [String] {
+        self.columns
+    }
+
+    pub fn fields(&self) -> &'r [String] {
+        self.fields
+    }
+}
+
+/// Filters are `Send + Sync` so `parse_parallel` can share them between threads
+pub(crate) type RowFilter<'a> = Box<dyn Fn(&RowView<'_>) -> bool + Send + Sync + 'a>;
+
+/// Projection options registered for one section name
+#[derive(Default)]
+pub(crate) struct Projection<'a> {
+    pub(crate) columns: Option<Vec<String>>,
+    pub(crate) filter: Option<RowFilter<'a>>,
+}
+
+/// A column selection resolved against a concrete header
+pub(crate) struct ActiveProjection {
+    /// Which schema positions the lexer should materialize
+    pub(crate) keep: Vec<bool>,
+    /// For each output column, its position among the kept fields
+    order: Vec<usize>,
+    /// Output columns in the requested order
+    pub(crate) columns: Vec<String>,
+}
+
+impl ActiveProjection {
+    /// Resolve `selected` against `schema`. Unknown or repeated columns are
+    /// reported at the header.
+    pub(crate) fn resolve(
+        key: &str,
+        schema: &[String],
+        selected: &[String],
+        source: &str,
+        offset: usize,
+    ) -> Result<Self, ToonError> {
+        let violation = |message: String| ToonError::SchemaViolation {
+            message,
+            span: Span::from_offset(source, offset),
+        };
+
+        let mut keep = vec![false; schema.len()];
+        let mut positions = Vec::with_capacity(selected.len());
+        for column in selected {
+            let index = schema
+                .iter()
+                .position(|c| c == column)
+                .ok_or_else(|| violation(format!("Section '{}' has no column '{}'", key, column)))?;
+            if keep[index] {
+                return Err(violation(format!("Column '{}' selected twice", column)));
+            }
+            keep[index] = true;
+            positions.push(index);
+        }
+
+        // Kept fields arrive in schema order; map each output column to its
+        // rank among them
+        let order = positions
+            .iter()
+            .map(|&index| keep[..index].iter().filter(|k| **k).count())
+            .collect();
+
+        Ok(Self {
+            keep,
+            order,
+            columns: selected.to_vec(),
+        })
+    }
+
+    /// Reorder fields scanned with `keep` into the requested column order
+    pub(crate) fn arrange(&self, kept: Vec<String>) -> Vec<String> {
+        if self.order.iter().enumerate().all(|(i, &rank)| i == rank) {
+            return kept;
+        }
+        let mut slots: Vec<Option<String>> = kept.into_iter().map(Some).collect();
+        self.order.iter().map(|&rank| slots[rank].take().unwrap_or_default()).collect()
+    }
+
+    /// Project a fully materialized row
+    pub(crate) fn select(&self, fields: Vec<String>) -> Vec<String> {
+        let kept = fields
+            .into_iter()
+            .zip(&self.keep)
+            .filter_map(|(field, keep)| keep.then_some(field))
+            .collect();
+        self.arrange(kept)
+    }
+}
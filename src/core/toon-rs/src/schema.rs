@@ -0,0 +1,160 @@
+//! TOON v2.0 schema registry
+//! External schema definitions that guardrail headers are validated against,
+//! so a document cannot vouch for its own structure.
+
+use crate::{Span, ToonError, ToonHeader};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Type a column's values must satisfy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldType {
+    /// Any text, including numbers and booleans
+    String,
+    /// Finite or non-finite floating point number
+    Number,
+    /// Signed 64-bit integer
+    Integer,
+    /// `true` or `false`
+    Boolean,
+    /// No type restriction
+    Any,
+}
+
+impl FieldType {
+    /// Whether a raw field value satisfies this type
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            FieldType::String | FieldType::Any => true,
+            FieldType::Number => value.parse::<f64>().is_ok(),
+            FieldType::Integer => value.parse::<i64>().is_ok(),
+            FieldType::Boolean => value == "true" || value == "false",
+        }
+    }
+}
+
+/// A named, typed column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSpec {
+    pub name: String,
+    pub field_type: FieldType,
+}
+
+/// Expected shape of a guardrail section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaDefinition {
+    pub name: String,
+    pub fields: Vec<FieldSpec>,
+    pub min_count: usize,
+    pub max_count: Option<usize>,
+}
+
+impl SchemaDefinition {
+    /// Create a schema with no fields and unbounded count
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            fields: Vec::new(),
+            min_count: 0,
+            max_count: None,
+        }
+    }
+
+    /// Append a column to the schema
+    pub fn field(mut self, name: &str, field_type: FieldType) -> Self {
+        self.fields.push(FieldSpec {
+            name: name.to_string(),
+            field_type,
+        });
+        self
+    }
+
+    /// Restrict the declared row count to `min..=max`
+    pub fn count_bounds(mut self, min: usize, max: Option<usize>) -> Self {
+        self.min_count = min;
+        self.max_count = max;
+        self
+    }
+}
+
+/// Registry of named schemas used by `ToonParser::parse_with_schema`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, SchemaDefinition>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a schema, returning the definition it replaced (if any)
+    pub fn register(&mut self, schema: SchemaDefinition) -> Option<SchemaDefinition> {
+        self.schemas.insert(schema.name.clone(), schema)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SchemaDefinition> {
+        self.schemas.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    /// Check a parsed header against its registered schema
+    pub(crate) fn check_header(&self, header: &ToonHeader<'_>, source: &str, offset: usize) -> Result<(), ToonError> {
+        let violation = |message: String| ToonError::SchemaViolation {
+            message,
+            span: Span::from_offset(source, offset),
+        };
+
+        let schema = self
+            .get(header.key)
+            .ok_or_else(|| violation(format!("No registered schema for section '{}'", header.key)))?;
+
+        let expected: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        if header.schema != expected {
+            return Err(violation(format!(
+                "Section '{}' declares fields {{{}}}, registered schema requires {{{}}}",
+                header.key,
+                header.schema.join(","),
+                expected.join(",")
+            )));
+        }
+
+        let within_max = schema.max_count.is_none_or(|max| header.count <= max);
+        if header.count < schema.min_count || !within_max {
+            let max = schema.max_count.map_or("unbounded".to_string(), |m| m.to_string());
+            return Err(violation(format!(
+                "Section '{}' declares {} rows, registered bounds are {}..={}",
+                header.key, header.count, schema.min_count, max
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check a row's values against the column types of `key`'s schema
+    pub(crate) fn check_row(&self, key: &str, fields: &[String], source: &str, offset: usize) -> Result<(), ToonError> {
+        let Some(schema) = self.get(key) else {
+            return Ok(());
+        };
+
+        for (spec, value) in schema.fields.iter().zip(fields) {
+            if !spec.field_type.accepts(value) {
+                return Err(ToonError::SchemaViolation {
+                    message: format!(
+                        "Field '{}' of section '{}' expects {:?}, found '{}'",
+                        spec.name, key, spec.field_type, value
+                    ),
+                    span: Span::from_offset(source, offset),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+//! JavaScript bindings (`wasm` feature)
+//! Lets the webview parse small TOON documents locally instead of making an
+//! IPC round trip. Build with:
+//!
+//! ```text
+//! wasm-pack build src/core/toon-rs --target web --no-default-features --features wasm
+//! ```
+//!
+//! Documents cross the boundary as JSON: values are externally tagged, so a
+//! `ToonValue::Integer(5)` arrives as `{ "Integer": "5" }`. Integers and
+//! decimals arrive as strings to keep every digit; a JavaScript number
+//! rounds integers beyond 2^53. Convert with `BigInt(value.Integer)`.
+//! JSON has no NaN or infinity, so those `Number`s arrive as `null`.
+
+use crate::{parse_no_panic, ToonError};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export type ToonValue =
+  | { String: string }
+  | { Number: number | null }
+  | { Integer: string }
+  | { Unsigned: string }
+  | { Decimal: string }
+  | { Boolean: boolean }
+  | { Schema: { count: number; schema: string[]; data: string[][] } };
+
+export interface ToonSpan {
+  line: number;
+  column: number;
+  offset: number;
+}
+
+export interface ToonDirective {
+  name: string;
+  value: string;
+  span: ToonSpan;
+}
+
+export interface ToonDocument {
+  entries: Record<string, ToonValue>;
+  directives: ToonDirective[];
+}
+
+export interface ToonValidation {
+  valid: boolean;
+  /** Error message with a caret snippet, when invalid */
+  error?: string;
+  span?: ToonSpan;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "ToonDocument")]
+    pub type JsToonDocument;
+
+    #[wasm_bindgen(typescript_type = "ToonValidation")]
+    pub type JsToonValidation;
+
+    #[wasm_bindgen(js_namespace = JSON, js_name = parse)]
+    fn json_parse(text: &str) -> JsValue;
+}
+
+/// Parse `input` into a document object. Throws an `Error` whose message
+/// includes the offending line and a caret.
+#[wasm_bindgen]
+pub fn parse(input: &str) -> Result<JsToonDocument, JsError> {
+    let json = document_json(input).map_err(|e| render_error(input, &e))?;
+    Ok(json_parse(&json).unchecked_into())
+}
+
+/// Check `input` without throwing
+#[wasm_bindgen]
+pub fn validate(input: &str) -> JsToonValidation {
+    let result = match parse_no_panic(input.as_bytes()) {
+        Ok(_) => serde_json::json!({ "valid": true }),
+        Err(e) => serde_json::json!({
+            "valid": false,
+            "error": e.render(input),
+            "span": e.span(),
+        }),
+    };
+    json_parse(&result.to_string()).unchecked_into()
+}
+
+/// Parse `input` and return the document as a JSON string
+#[wasm_bindgen(js_name = toJson)]
+pub fn to_json(input: &str) -> Result<String, JsError> {
+    document_json(input).map_err(|e| render_error(input, &e))
+}
+
+fn document_json(input: &str) -> Result<String, ToonError> {
+    let document = parse_no_panic(input.as_bytes())?;
+    let mut json = serde_json::to_value(&document).expect("documents serialize to JSON");
+    if let Some(entries) = json.get_mut("entries").and_then(Value::as_object_mut) {
+        for value in entries.values_mut().filter_map(Value::as_object_mut) {
+            for tag in ["Integer", "Unsigned"] {
+                if let Some(number) = value.get_mut(tag) {
+                    *number = Value::String(number.to_string());
+                }
+            }
+        }
+    }
+    Ok(json.to_string())
+}
+
+fn render_error(input: &str, error: &ToonError) -> JsError {
+    JsError::new(&error.render(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integers_keep_every_digit() {
+        let json = document_json("ts = 1700000000123456789\nbig = 18446744073709551615\nprice = 150.25\nok = true\n").unwrap();
+        let json: Value = serde_json::from_str(&json).unwrap();
+        let entries = &json["entries"];
+        assert_eq!(entries["ts"], serde_json::json!({ "Integer": "1700000000123456789" }));
+        assert_eq!(entries["big"], serde_json::json!({ "Unsigned": "18446744073709551615" }));
+        assert_eq!(entries["price"], serde_json::json!({ "Decimal": "150.25" }));
+        assert_eq!(entries["ok"], serde_json::json!({ "Boolean": true }));
+    }
+
+    #[test]
+    fn test_non_finite_numbers_arrive_as_null() {
+        let json = document_json("a = nan\nb = -inf\nc = 1e300\n").unwrap();
+        let json: Value = serde_json::from_str(&json).unwrap();
+        let entries = &json["entries"];
+        assert_eq!(entries["a"], serde_json::json!({ "Number": null }));
+        assert_eq!(entries["b"], serde_json::json!({ "Number": null }));
+        assert_eq!(entries["c"], serde_json::json!({ "Number": 1e300 }));
+    }
+
+    #[test]
+    fn test_sections_and_errors() {
+        let json = document_json("#!toon 2.1\nticks [1]{symbol,price}\nAAPL,150.5\n").unwrap();
+        let json: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["entries"]["ticks"]["Schema"]["data"], serde_json::json!([["AAPL", "150.5"]]));
+        assert_eq!(json["directives"][0]["name"], "toon");
+        assert!(matches!(document_json("{\"a\": 1}"), Err(ToonError::EntropyDetected { .. })));
+    }
+}
@@ -0,0 +1,114 @@
+//! TOON v2.0 writer
+//! Serializes values back into TOON text using the quoting rules the parser
+//! accepts, so any UTF-8 string survives a write/parse round trip.
+
+use crate::number::is_locale_number;
+use crate::ToonValue;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Quote and escape a field if it cannot be written verbatim.
+pub fn quote_field(field: &str) -> Cow<'_, str> {
+    if needs_quoting(field) {
+        Cow::Owned(escape(field))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Serialize a single `key = value` pair or guardrail section.
+pub fn write_entry(key: &str, value: &ToonValue) -> String {
+    match value {
+        ToonValue::Schema { count, schema, data } => {
+            let mut out = format!("{} [{}]{{{}}}", key, count, schema.join(","));
+            for row in data {
+                out.push('\n');
+                let fields: Vec<Cow<'_, str>> = row.iter().map(|f| quote_field(f)).collect();
+                out.push_str(&fields.join(","));
+            }
+            out
+        }
+        _ => format!("{} = {}", key, write_value(value)),
+    }
+}
+
+/// Serialize a scalar value as it appears on the right-hand side of `=`.
+/// Strings that would otherwise be read back as numbers or booleans are quoted.
+pub fn write_value(value: &ToonValue) -> String {
+    match value {
+        ToonValue::String(s) => {
+            if needs_quoting(s) || s.starts_with('\'') || !matches!(ToonValue::parse_value(s), ToonValue::String(_)) {
+                escape(s)
+            } else {
+                s.clone()
+            }
+        }
+        ToonValue::Number(n) => format_number(*n),
+        ToonValue::Integer(n) => n.to_string(),
+        ToonValue::Unsigned(n) => n.to_string(),
+        ToonValue::Decimal(d) => d.to_string(),
+        ToonValue::Boolean(b) => b.to_string(),
+        ToonValue::Schema { .. } => String::new(),
+    }
+}
+
+/// Serialize a parsed document. Keys are written in sorted order so the
+/// output does not depend on HashMap iteration order.
+pub fn to_toon_string(document: &HashMap<String, ToonValue>) -> String {
+    let mut keys: Vec<&String> = document.keys().collect();
+    keys.sort();
+
+    let mut out = String::new();
+    for key in keys {
+        out.push_str(&write_entry(key, &document[key]));
+        out.push('\n');
+    }
+    out
+}
+
+/// Canonical float formatting: shortest round-trip representation in
+/// exponent notation, so the value is read back as `Number` rather than an
+/// exact `Integer` or `Decimal`. Negative zero is normalized to `0e0`.
+pub fn format_number(n: f64) -> String {
+    if n == 0.0 {
+        "0e0".to_string()
+    } else if n.is_finite() {
+        format!("{:e}", n)
+    } else {
+        n.to_string()
+    }
+}
+
+/// Locale-looking text such as `2.1.0` or `10.0.0.1` is quoted too, so
+/// output also parses with `strict_numbers`
+fn needs_quoting(field: &str) -> bool {
+    field.is_empty()
+        || is_locale_number(field)
+        || field.starts_with(char::is_whitespace)
+        || field.ends_with(char::is_whitespace)
+        || field.starts_with('#')
+        || field
+            .chars()
+            .any(|c| matches!(c, ',' | '"' | '\\' | '=' | '[' | ']' | '{' | '}') || c.is_control())
+}
+
+fn escape(field: &str) -> String {
+    let mut out = String::with_capacity(field.len() + 2);
+    out.push('"');
+    for c in field.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
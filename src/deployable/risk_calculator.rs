@@ -87,8 +87,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     if risk_score == 0 {
         println!("{}", "System verifies as Sovereign Manifold (C=0).".green());
-        // Verify Bio-Proof Seal (Simulated)
-        println!("Bio-Proof: 308537780"); 
+        // Bio-Proof: first 8 bytes (big-endian) of SHA-256 over the
+        // concatenated iteration hashes, as in the library's BioProof
+        let digest = Sha256::digest(hashes.concat().as_bytes());
+        let mut proof = [0u8; 8];
+        proof.copy_from_slice(&digest[..8]);
+        println!("Bio-Proof: {}", u64::from_be_bytes(proof));
     } else {
         println!("{}", "System fails Zero Entropy Law. Divergence detected.".red());
         std::process::exit(1);
@@ -1,86 +1,157 @@
 //! Axiom Risk Calculator (OLO Engine) - CLI Binary
 //! AxiomHive Sovereign Manifold v2.1.0
 
+use axiom_risk_calculator::endpoint_verifier::{
+    verify_endpoint, ApiKey, EndpointOptions, IterationSource, RetryPolicy,
+};
+use axiom_risk_calculator::fleet::{EndpointStatus, FleetConfig, FleetReport, FleetVerifier};
+use axiom_risk_calculator::protocol::{EndpointProtocol, Ollama, OpenAiChat, RawTemplate, ResponsePath};
+use axiom_risk_calculator::suite::{verify_suite, PromptStatus, PromptSuite, SuiteReport};
+use axiom_risk_calculator::RiskCalculator;
+use std::sync::Arc;
+use std::time::Duration;
 use clap::Parser;
-use reqwest::blocking::Client;
-use serde_json::json;
-use sha2::{Digest, Sha256};
-use std::collections::HashSet;
 use colored::*;
 
 /// AxiomHive Risk Calculator v2.1.0
 #[derive(Parser, Debug)]
-#[command(author = "AxiomHive", version = "2.1.0")]
+#[command(
+    author = "AxiomHive",
+    version = "2.1.0",
+    after_help = "Set AXIOM_API_KEY to send it as a bearer token, e.g. for hosted APIs."
+)]
 struct Args {
     #[arg(short, long, default_value = "http://localhost:11434/api/generate")]
     endpoint: String,
 
-    #[arg(short, long, default_value_t = 10)]
+    #[arg(short, long, default_value_t = 10, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
     iterations: usize,
-}
 
-fn calculate_hash(content: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    format!("{:x}", hasher.finalize())
+    #[arg(short, long, default_value = "Define the Zero Entropy Law.")]
+    prompt: String,
+
+    #[arg(short, long, default_value = "axiom-mamba-2")]
+    model: String,
+
+    /// Request/response shape: ollama, openai (chat completions) or template
+    #[arg(long, default_value = "ollama", value_parser = ["ollama", "openai", "template"])]
+    protocol: String,
+
+    /// Dot-separated path to the generated text in each response,
+    /// e.g. choices.0.message.content
+    #[arg(long)]
+    response_path: Option<ResponsePath>,
+
+    /// JSON request body with {{prompt}}, {{model}} and {{seed}}
+    /// placeholders; required by --protocol template
+    #[arg(long)]
+    request_template: Option<std::path::PathBuf>,
+
+    /// TOON fleet config; checks every endpoint in it instead of --endpoint
+    #[arg(long)]
+    fleet: Option<std::path::PathBuf>,
+
+    /// TOON prompt suite; checks every prompt in it instead of --prompt
+    #[arg(long, conflicts_with = "fleet")]
+    suite: Option<std::path::PathBuf>,
+
+    /// Timeout for each request attempt, in seconds
+    #[arg(long, default_value_t = 60)]
+    timeout_secs: u64,
+
+    /// Attempts per iteration before it counts as failed (exponential backoff)
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    max_attempts: u32,
+
+    /// Replace failed iterations with a local stand-in instead of aborting.
+    /// Reports with fallback iterations are never insurable.
+    #[arg(long)]
+    allow_local_fallback: bool,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
-    let client = Client::new();
-    
+    let calculator = RiskCalculator::builder().iterations(args.iterations).build();
+
     println!("{}", "Initializing OLO Risk Verification Kernel...".bold().cyan());
     println!("Constraint: Temperature = 0.0 (Greedy Decoding)");
-    
-    let mut hashes = Vec::new();
-
-    // N=10 Iteration Check
-    for i in 1..=args.iterations {
-        let body = json!({
-            "model": "axiom-mamba-2",
-            "prompt": "Define the Zero Entropy Law.",
-            "stream": false,
-            "options": {
-                "temperature": 0.0,
-                "seed": 42
-            }
-        });
-
-        match client.post(&args.endpoint).json(&body).send() {
-            Ok(res) => {
-                let text = res.text()?;
-                let hash = calculate_hash(&text);
-                hashes.push(hash.clone());
-                println!("Iter [{}/{}]: Hash -> {}", i, args.iterations, hash.yellow());
-            },
-            Err(e) => {
-                println!("{}", format!("Connection Failed: {}", e).red());
-                println!("{}", "Falling back to local deterministic calculation...".yellow());
-                let test_input = format!("Define the Zero Entropy Law. Iteration {}", i);
-                let hash = calculate_hash(&test_input);
-                hashes.push(hash.clone());
-                println!("Iter [{}/{}]: Hash -> {}", i, args.iterations, hash.yellow());
-            }
+
+    let options = EndpointOptions {
+        calculator,
+        model: args.model.clone(),
+        protocol: endpoint_protocol(&args)?,
+        api_key: std::env::var("AXIOM_API_KEY").ok().map(ApiKey::new),
+        timeout: Duration::from_secs(args.timeout_secs),
+        retry: RetryPolicy {
+            max_attempts: args.max_attempts,
+            ..RetryPolicy::default()
+        },
+        allow_local_fallback: args.allow_local_fallback,
+        ..EndpointOptions::default()
+    };
+
+    if let Some(path) = &args.fleet {
+        let config = FleetConfig::from_toon(&std::fs::read_to_string(path)?)?;
+        let report = FleetVerifier::new(config).options(options).run().await;
+        print_fleet_report(&report);
+        if !report.is_insurable() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = &args.suite {
+        let suite = PromptSuite::from_toon(&std::fs::read_to_string(path)?)?;
+        let report = verify_suite(&args.endpoint, &suite, &options).await;
+        print_suite_report(&report);
+        if !report.passed() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let report = match verify_endpoint(&args.endpoint, &args.prompt, &options).await {
+        Ok(report) => report,
+        Err(e) => {
+            println!("{}", format!("Connection Failed: {}", e).red());
+            println!("Pass --allow-local-fallback to substitute local iterations (never insurable).");
+            std::process::exit(2);
         }
+    };
+    let result = &report.result;
+
+    for (i, (hash, source)) in result.hashes.iter().zip(&report.sources).enumerate() {
+        let origin = match source {
+            IterationSource::Endpoint { attempts: 1 } => "endpoint".normal(),
+            IterationSource::Endpoint { attempts } => format!("endpoint, {} attempts", attempts).normal(),
+            IterationSource::LocalFallback { .. } => "LOCAL FALLBACK".red().bold(),
+        };
+        println!("Iter [{}/{}]: Hash -> {} [{}]", i + 1, args.iterations, hash.yellow(), origin);
     }
 
-    let unique_hashes: HashSet<_> = hashes.iter().collect();
-    let entropy_count = unique_hashes.len();
-    let risk_score = if entropy_count == 1 { 0 } else { 100 };
-    let status = if risk_score == 0 { 
-        "INSURABLE".green().bold() 
-    } else { 
-        "UNINSURABLE".red().bold() 
+    let status = if report.is_insurable() {
+        result.status().green().bold()
+    } else {
+        "UNINSURABLE".red().bold()
     };
 
     println!("\n--- VERIFICATION REPORT ---");
-    println!("Unique States: {}", entropy_count);
-    println!("Risk Score: {}", risk_score);
+    println!("Unique States: {}", result.entropy_count);
+    println!("Risk Score: {}", result.risk_score);
     println!("Status: {}", status);
+    println!(
+        "Endpoint Responses: {}/{}",
+        report.sources.len() - report.fallback_count(),
+        report.sources.len()
+    );
 
-    if risk_score == 0 {
+    if report.is_insurable() {
         println!("{}", "System verifies as Sovereign Manifold (C=0).".green());
-        println!("Bio-Proof: 308537780"); 
+        println!("Bio-Proof: {}", result.bio_proof);
+    } else if report.fallback_count() > 0 {
+        println!("{}", "Local fallback iterations cannot certify the endpoint.".red());
+        std::process::exit(1);
     } else {
         println!("{}", "System fails Zero Entropy Law. Divergence detected.".red());
         std::process::exit(1);
@@ -89,3 +160,70 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// The protocol selected by --protocol, --response-path and --request-template
+fn endpoint_protocol(args: &Args) -> Result<Arc<dyn EndpointProtocol>, Box<dyn std::error::Error>> {
+    let path = args.response_path.clone();
+    Ok(match args.protocol.as_str() {
+        "openai" => Arc::new(path.map_or_else(OpenAiChat::default, |response_path| OpenAiChat { response_path })),
+        "template" => {
+            let template = args
+                .request_template
+                .as_ref()
+                .ok_or("--protocol template needs --request-template")?;
+            Arc::new(RawTemplate::new(std::fs::read_to_string(template)?, path)?)
+        }
+        _ => Arc::new(path.map_or_else(Ollama::default, |response_path| Ollama { response_path })),
+    })
+}
+
+fn print_fleet_report(report: &FleetReport) {
+    println!("\n--- FLEET VERIFICATION REPORT ---");
+    for endpoint in &report.endpoints {
+        let status = match endpoint.status {
+            EndpointStatus::Insurable => endpoint.status.as_str().green().bold(),
+            _ => endpoint.status.as_str().red().bold(),
+        };
+        let detail = match (&endpoint.result, &endpoint.error) {
+            (Some(result), _) => format!("unique states {}, risk score {}", result.entropy_count, result.risk_score),
+            (None, Some(error)) => error.clone(),
+            (None, None) => String::new(),
+        };
+        println!("{} [{}] {}: {}", endpoint.name, endpoint.model, status, detail);
+    }
+    println!("Fleet Entropy: {}", report.fleet_entropy);
+    println!("Seal: {}", report.seal);
+
+    if report.is_insurable() {
+        println!("{}", "Fleet verifies as Sovereign Manifold (C=0).".green());
+    } else {
+        println!("{}", "Fleet fails Zero Entropy Law.".red());
+    }
+}
+
+fn print_suite_report(report: &SuiteReport) {
+    println!("\n--- PROMPT SUITE REPORT ---");
+    for prompt in &report.prompts {
+        let status = match prompt.status {
+            PromptStatus::Pass => prompt.status.as_str().green().bold(),
+            _ => prompt.status.as_str().red().bold(),
+        };
+        let detail = match (&prompt.result, &prompt.error) {
+            (Some(result), _) if prompt.status == PromptStatus::Mismatch => format!(
+                "expected {}, got {}",
+                prompt.expected_hash.as_deref().unwrap_or_default(),
+                result.hashes.first().map(String::as_str).unwrap_or("no retained hash")
+            ),
+            (Some(result), _) => format!("unique states {}, risk score {}", result.entropy_count, result.risk_score),
+            (None, Some(error)) => error.clone(),
+            (None, None) => String::new(),
+        };
+        println!("{}: {}: {}", prompt.id, status, detail);
+    }
+    println!("Passed: {}/{}", report.count(PromptStatus::Pass), report.prompts.len());
+
+    if report.passed() {
+        println!("{}", "Every prompt verifies as Sovereign Manifold (C=0).".green());
+    } else {
+        println!("{}", "Prompt suite fails Zero Entropy Law.".red());
+    }
+}
@@ -0,0 +1,132 @@
+//! Bio-Proof derivation
+//! The Bio-Proof is a 64-bit fingerprint of a run's iteration hashes that
+//! anyone can recompute:
+//!
+//! 1. Concatenate the iteration hashes exactly as recorded in
+//!    `RiskResult::hashes` (lowercase hex), in iteration order, with no
+//!    separator.
+//! 2. Take the SHA-256 of the UTF-8 bytes of that string. SHA-256 is used
+//!    whatever hash backend produced the iteration hashes.
+//! 3. Read the first 8 bytes of the digest as a big-endian `u64`.
+//!
+//! A calculator with a `TenantKey` replaces step 2 with HMAC-SHA256 keyed
+//! by the tenant's secret, see the `tenant` module.
+//!
+//! A batch Bio-Proof (`BatchRiskReport::combined_bio_proof`) applies steps 2
+//! and 3 to the results' Bio-Proofs, each as 8 big-endian bytes, in input
+//! order.
+
+use crate::tenant::{HmacSha256, TenantKey};
+use hmac::Mac;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// A derived Bio-Proof value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BioProof(u64);
+
+impl BioProof {
+    /// Derive the Bio-Proof of `hashes` following the spec above
+    pub fn derive<S: AsRef<str>>(hashes: &[S]) -> Self {
+        let mut accumulator = BioProofAccumulator::new();
+        for hash in hashes {
+            accumulator.push(hash.as_ref());
+        }
+        accumulator.finish()
+    }
+
+    /// True if `claimed` is the Bio-Proof of `hashes`
+    pub fn verify<S: AsRef<str>>(hashes: &[S], claimed: u64) -> bool {
+        Self::derive(hashes).value() == claimed
+    }
+
+    /// Derive the tenant-keyed Bio-Proof of `hashes`
+    pub fn derive_keyed<S: AsRef<str>>(hashes: &[S], key: &TenantKey) -> Self {
+        let mut accumulator = BioProofAccumulator::keyed(key);
+        for hash in hashes {
+            accumulator.push(hash.as_ref());
+        }
+        accumulator.finish()
+    }
+
+    /// True if `claimed` is the Bio-Proof of `hashes` under `key`
+    pub fn verify_keyed<S: AsRef<str>>(hashes: &[S], key: &TenantKey, claimed: u64) -> bool {
+        Self::derive_keyed(hashes, key).value() == claimed
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<BioProof> for u64 {
+    fn from(proof: BioProof) -> Self {
+        proof.0
+    }
+}
+
+impl fmt::Display for BioProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Bio-Proof derived one iteration hash at a time, for runs too long to
+/// keep every hash
+#[derive(Clone)]
+pub struct BioProofAccumulator(Accumulator);
+
+#[derive(Clone)]
+enum Accumulator {
+    Plain(Sha256),
+    Keyed(HmacSha256),
+}
+
+impl BioProofAccumulator {
+    pub fn new() -> Self {
+        Self(Accumulator::Plain(Sha256::new()))
+    }
+
+    /// Accumulate the tenant-keyed Bio-Proof
+    pub fn keyed(key: &TenantKey) -> Self {
+        Self(Accumulator::Keyed(key.mac()))
+    }
+
+    pub fn push(&mut self, hash: &str) {
+        match &mut self.0 {
+            Accumulator::Plain(digest) => Digest::update(digest, hash.as_bytes()),
+            Accumulator::Keyed(mac) => mac.update(hash.as_bytes()),
+        }
+    }
+
+    pub fn finish(self) -> BioProof {
+        match self.0 {
+            Accumulator::Plain(digest) => BioProof(first_u64(&digest.finalize())),
+            Accumulator::Keyed(mac) => BioProof(first_u64(&mac.finalize().into_bytes())),
+        }
+    }
+}
+
+impl Default for BioProofAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for BioProofAccumulator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keyed = matches!(self.0, Accumulator::Keyed(_));
+        f.debug_struct("BioProofAccumulator").field("keyed", &keyed).finish()
+    }
+}
+
+/// Bio-Proof over already-encoded bytes (step 2 and 3 only)
+pub(crate) fn derive_bytes(data: &[u8]) -> u64 {
+    first_u64(&Sha256::digest(data))
+}
+
+fn first_u64(digest: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes)
+}
@@ -0,0 +1,67 @@
+//! Iteration input derivation
+//! `calculate_risk` never hashes the caller's string directly. It hashes a
+//! framed encoding in which every variable-length part carries its length,
+//! so no choice of input or session id can produce another combination's
+//! bytes (unlike joining with `:`).
+//!
+//! Framing, all integers big-endian:
+//!
+//! ```text
+//! u64 len(DOMAIN) || DOMAIN
+//! u8 0                          (no session id)
+//!   | u8 1 || u64 len || bytes  (session id, UTF-8)
+//! f64 temperature               (IEEE 754 bits)
+//! u64 len(input) || input
+//! ```
+//!
+//! Iterations share one framed input: each recomputes the same value, and
+//! the determinism check is whether they agree.
+
+use serde::{Deserialize, Serialize};
+
+/// Domain tag of the current framing
+pub const DOMAIN: &str = "axiom-risk/iteration-input/v1";
+
+/// How iteration hashes were derived, recorded in `RiskResult`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scheme", rename_all = "snake_case")]
+pub enum InputDerivation {
+    /// Each hash is of one observed output as given (`verify_outputs`)
+    Raw,
+    /// Each hash is of the framed input described in this module
+    Framed {
+        domain: String,
+        session_id: Option<String>,
+    },
+}
+
+impl InputDerivation {
+    /// Short name for text formats: `raw` or `framed`
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            InputDerivation::Raw => "raw",
+            InputDerivation::Framed { .. } => "framed",
+        }
+    }
+}
+
+/// Framed bytes for `input` under `session_id` at `temperature`
+pub fn frame_input(input: &[u8], session_id: Option<&str>, temperature: f64) -> Vec<u8> {
+    let mut out = Vec::with_capacity(DOMAIN.len() + input.len() + 40);
+    push_framed(&mut out, DOMAIN.as_bytes());
+    match session_id {
+        None => out.push(0),
+        Some(session) => {
+            out.push(1);
+            push_framed(&mut out, session.as_bytes());
+        }
+    }
+    out.extend_from_slice(&temperature.to_be_bytes());
+    push_framed(&mut out, input);
+    out
+}
+
+fn push_framed(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
@@ -0,0 +1,71 @@
+//! Differential analysis between two model versions
+//! `RiskCalculator::compare` hashes each version's output for every prompt
+//! with the calculator's backend and reports which prompts changed, so a
+//! model upgrade can be reviewed prompt by prompt instead of as one
+//! pass/fail verdict.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// One prompt's outputs under both versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptDrift {
+    pub index: usize,
+    pub prompt: String,
+    /// Hash of version A's output; `None` if A has no output for this prompt
+    pub hash_a: Option<String>,
+    pub hash_b: Option<String>,
+    /// Distinct outputs across both versions: 1 when they agree, 2 when
+    /// they differ, 0 or 1 when outputs are missing
+    pub unique_states: usize,
+}
+
+impl PromptDrift {
+    /// True unless both versions produced the same output
+    pub fn diverged(&self) -> bool {
+        self.hash_a.is_none() || self.hash_a != self.hash_b
+    }
+}
+
+/// Where two model versions disagree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriftReport {
+    /// Name of the `RiskHasher` behind every hash in the report
+    pub hash_algorithm: String,
+    /// One entry per prompt, in input order
+    pub prompts: Vec<PromptDrift>,
+    /// Distinct outputs version A produced across all prompts
+    pub unique_states_a: usize,
+    pub unique_states_b: usize,
+}
+
+impl DriftReport {
+    pub(crate) fn new(hash_algorithm: &str, prompts: Vec<PromptDrift>) -> Self {
+        let distinct = |hash: fn(&PromptDrift) -> Option<&String>| prompts.iter().filter_map(hash).collect::<HashSet<_>>().len();
+        Self {
+            hash_algorithm: hash_algorithm.to_string(),
+            unique_states_a: distinct(|p| p.hash_a.as_ref()),
+            unique_states_b: distinct(|p| p.hash_b.as_ref()),
+            prompts,
+        }
+    }
+
+    /// Prompts whose output changed or is missing in either version
+    pub fn diverged(&self) -> impl Iterator<Item = &PromptDrift> {
+        self.prompts.iter().filter(|p| p.diverged())
+    }
+
+    /// Share of prompts that diverged, 0.0 for an empty comparison
+    pub fn drift_rate(&self) -> f64 {
+        if self.prompts.is_empty() {
+            0.0
+        } else {
+            self.diverged().count() as f64 / self.prompts.len() as f64
+        }
+    }
+
+    /// True if both versions answered every prompt identically
+    pub fn is_identical(&self) -> bool {
+        self.diverged().next().is_none()
+    }
+}
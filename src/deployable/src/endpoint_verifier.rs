@@ -0,0 +1,272 @@
+//! Live endpoint determinism checks (`network` feature)
+//! Sends the same prompt to a generation endpoint N times at temperature 0
+//! and scores the responses exactly like local iteration hashes. Request
+//! and response shapes come from the configured `EndpointProtocol`, Ollama
+//! `/api/generate` unless told otherwise.
+//!
+//! Failed requests are retried with exponential backoff. An iteration that
+//! still fails either aborts the check or, only if the caller opts in,
+//! falls back to a local stand-in. Every report records where each
+//! iteration came from, and a report with any fallback is never insurable:
+//! local hashes say nothing about the endpoint.
+
+use crate::protocol::{EndpointProtocol, Ollama, ProtocolError};
+use crate::{derivation, RiskCalculator, RiskResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How to query the endpoint and score the answers
+#[derive(Debug, Clone)]
+pub struct EndpointOptions {
+    /// Iteration count, hash backend and entropy threshold
+    pub calculator: RiskCalculator,
+    pub model: String,
+    pub seed: u64,
+    pub protocol: Arc<dyn EndpointProtocol>,
+    /// Sent as a bearer token, for hosted APIs
+    pub api_key: Option<ApiKey>,
+    /// Per-request timeout, applied to each attempt
+    pub timeout: Duration,
+    pub retry: RetryPolicy,
+    /// Replace iterations that fail every attempt with a local stand-in
+    /// instead of failing the check
+    pub allow_local_fallback: bool,
+}
+
+impl Default for EndpointOptions {
+    fn default() -> Self {
+        Self {
+            calculator: RiskCalculator::new(),
+            model: "axiom-mamba-2".to_string(),
+            seed: 42,
+            protocol: Arc::new(Ollama::default()),
+            api_key: None,
+            timeout: Duration::from_secs(60),
+            retry: RetryPolicy::default(),
+            allow_local_fallback: false,
+        }
+    }
+}
+
+/// An API credential; `Debug` never prints it
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey(..)")
+    }
+}
+
+/// How often and how patiently a failed request is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per iteration, including the first; at least 1
+    pub max_attempts: u32,
+    /// Wait before the second attempt; doubled for each one after
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// A single attempt, no retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Wait after failed attempt `attempt` (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+/// Where one iteration's output came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum IterationSource {
+    /// A real endpoint response, after `attempts` tries
+    Endpoint { attempts: u32 },
+    /// Every attempt failed; the output is a local stand-in
+    LocalFallback { error: String },
+}
+
+/// Outcome of an endpoint check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointRiskReport {
+    pub endpoint: String,
+    pub model: String,
+    /// Name of the `EndpointProtocol` used
+    pub protocol: String,
+    pub prompt: String,
+    /// Scored iteration hashes; divergent endpoints get a nonzero score
+    pub result: RiskResult,
+    /// One entry per iteration, in order
+    pub sources: Vec<IterationSource>,
+}
+
+impl EndpointRiskReport {
+    /// True for a zero risk score over real endpoint responses only
+    pub fn is_insurable(&self) -> bool {
+        self.result.risk_score == 0 && self.fallback_count() == 0
+    }
+
+    /// Iterations replaced by a local stand-in
+    pub fn fallback_count(&self) -> usize {
+        self.sources
+            .iter()
+            .filter(|s| matches!(s, IterationSource::LocalFallback { .. }))
+            .count()
+    }
+}
+
+/// Why an endpoint could not be checked
+#[derive(Debug)]
+pub enum EndpointError {
+    /// Request `iteration` (1-based) failed to connect, timed out or
+    /// returned a non-success status on each of `attempts` tries
+    Request {
+        iteration: usize,
+        attempts: u32,
+        source: reqwest::Error,
+    },
+    /// Response `iteration` (1-based) does not contain generated text
+    Response { iteration: usize, source: ProtocolError },
+    /// The protocol could not build the request body
+    Body(ProtocolError),
+}
+
+impl fmt::Display for EndpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndpointError::Request {
+                iteration,
+                attempts,
+                source,
+            } => write!(
+                f,
+                "Endpoint request {} failed after {} attempt(s): {}",
+                iteration, attempts, source
+            ),
+            EndpointError::Response { iteration, source } => {
+                write!(f, "Endpoint response {} is unusable: {}", iteration, source)
+            }
+            EndpointError::Body(source) => write!(f, "Cannot build the endpoint request: {}", source),
+        }
+    }
+}
+
+impl std::error::Error for EndpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EndpointError::Request { source, .. } => Some(source),
+            EndpointError::Response { source, .. } => Some(source),
+            EndpointError::Body(source) => Some(source),
+        }
+    }
+}
+
+/// Query `url` with `prompt` once per configured iteration and score the
+/// generated text the protocol extracts from each response
+pub async fn verify_endpoint(url: &str, prompt: &str, opts: &EndpointOptions) -> Result<EndpointRiskReport, EndpointError> {
+    let client = reqwest::Client::new();
+    let body = opts.protocol.request_body(&opts.model, prompt, opts.seed).map_err(EndpointError::Body)?;
+
+    let iterations = opts.calculator.iteration_count();
+    let max_attempts = opts.retry.max_attempts.max(1);
+    let mut outputs = Vec::with_capacity(iterations);
+    let mut sources = Vec::with_capacity(iterations);
+    for iteration in 1..=iterations {
+        let mut attempt = 1;
+        let outcome = loop {
+            let response = async {
+                let mut request = client.post(url).timeout(opts.timeout).json(&body);
+                if let Some(ApiKey(key)) = &opts.api_key {
+                    request = request.bearer_auth(key);
+                }
+                request
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await
+            };
+            match response.await {
+                Ok(text) => break Ok(text),
+                Err(e) if attempt < max_attempts && is_retryable(&e) => {
+                    tokio::time::sleep(opts.retry.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => break Err(e),
+            }
+        };
+
+        match outcome {
+            Ok(text) => {
+                let output = opts
+                    .protocol
+                    .generated_text(&text)
+                    .map_err(|source| EndpointError::Response { iteration, source })?;
+                outputs.push(output);
+                sources.push(IterationSource::Endpoint { attempts: attempt });
+            }
+            Err(e) if opts.allow_local_fallback => {
+                outputs.push(local_stand_in(prompt, &opts.calculator));
+                sources.push(IterationSource::LocalFallback { error: e.to_string() });
+            }
+            Err(source) => {
+                return Err(EndpointError::Request {
+                    iteration,
+                    attempts: attempt,
+                    source,
+                })
+            }
+        }
+    }
+
+    let result = opts.calculator.verify_outputs(&outputs);
+    Ok(EndpointRiskReport {
+        endpoint: url.to_string(),
+        model: opts.model.clone(),
+        protocol: opts.protocol.name().to_string(),
+        prompt: prompt.to_string(),
+        result,
+        sources,
+    })
+}
+
+/// Connection failures, timeouts, 429 and 5xx may be transient; other
+/// statuses will not change on retry
+fn is_retryable(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+        None => error.is_connect() || error.is_timeout() || error.is_request(),
+    }
+}
+
+/// What `calculate_risk(prompt)` hashes on every iteration, as hex
+fn local_stand_in(prompt: &str, calculator: &RiskCalculator) -> String {
+    let framed = derivation::frame_input(prompt.as_bytes(), calculator.session_id(), 0.0);
+    calculator.hasher().hash_hex(&framed)
+}
@@ -0,0 +1,275 @@
+//! Fleet-wide endpoint verification (`network` feature)
+//! Runs the endpoint check against every endpoint in a TOON config and
+//! consolidates the outcomes into one report. The config names the prompt
+//! and lists endpoints as a section; `model` and `protocol` (`ollama` or
+//! `openai`) are optional per row and fall back to `EndpointOptions`:
+//!
+//! ```text
+//! prompt = "Define the Zero Entropy Law."
+//! endpoints [3]{name,url,model,protocol}
+//! primary,http://10.0.0.1:11434/api/generate,axiom-mamba-2,
+//! replica,http://10.0.0.2:11434/api/generate,,
+//! vllm,http://10.0.0.3:8000/v1/chat/completions,axiom-mamba-2,openai
+//! ```
+//!
+//! The report's `seal` is the Merkle root (see the `merkle` module) over one
+//! leaf per endpoint in config order, each leaf the JSON array
+//! `[name, url, status, merkle_root]` with `merkle_root` null for
+//! unreachable endpoints. It is an unkeyed checksum, not a signature:
+//! recomputing it catches a row that was corrupted or edited on its own,
+//! but whoever edits the rows can recompute the seal as well. Sign the
+//! report's JSON where tampering matters.
+
+use crate::endpoint_verifier::{verify_endpoint, EndpointOptions};
+use crate::protocol::protocol_named;
+use crate::{merkle, RiskResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use toon_rs::{ToonParser, ToonValue};
+
+/// One endpoint of the fleet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FleetEndpoint {
+    pub name: String,
+    pub url: String,
+    /// Overrides `EndpointOptions::model` for this endpoint
+    pub model: Option<String>,
+    /// Overrides `EndpointOptions::protocol`; a `protocol_named` name
+    pub protocol: Option<String>,
+}
+
+/// What to verify across the fleet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FleetConfig {
+    pub prompt: String,
+    pub endpoints: Vec<FleetEndpoint>,
+}
+
+impl FleetConfig {
+    /// Load from a TOON document with a `prompt` pair and an
+    /// `endpoints [N]{name,url[,model][,protocol]}` section
+    pub fn from_toon(toon: &str) -> Result<Self, FleetConfigError> {
+        let document = ToonParser::try_new(toon)
+            .and_then(|parser| parser.parse_document())
+            .map_err(|e| FleetConfigError(e.render(toon)))?;
+
+        let prompt = match document.get("prompt") {
+            Some(ToonValue::String(prompt)) => prompt.clone(),
+            Some(_) => return Err(FleetConfigError("'prompt' must be a string".to_string())),
+            None => return Err(FleetConfigError("missing 'prompt'".to_string())),
+        };
+        let Some(ToonValue::Schema { schema, data, .. }) = document.get("endpoints") else {
+            return Err(FleetConfigError("missing 'endpoints [N]{name,url,model}' section".to_string()));
+        };
+
+        let column = |name: &str| schema.iter().position(|c| c == name);
+        let (Some(name_at), Some(url_at)) = (column("name"), column("url")) else {
+            return Err(FleetConfigError("'endpoints' needs 'name' and 'url' columns".to_string()));
+        };
+        let model_at = column("model");
+        let protocol_at = column("protocol");
+
+        let mut names = HashSet::new();
+        let mut endpoints = Vec::with_capacity(data.len());
+        for row in data {
+            let cell = |at: usize| row.get(at).map(|c| c.trim()).filter(|c| !c.is_empty());
+            let (Some(name), Some(url)) = (cell(name_at), cell(url_at)) else {
+                return Err(FleetConfigError("every endpoint needs a name and a url".to_string()));
+            };
+            if !names.insert(name) {
+                return Err(FleetConfigError(format!("duplicate endpoint name '{}'", name)));
+            }
+            let protocol = protocol_at.and_then(cell);
+            if let Some(protocol) = protocol.filter(|p| protocol_named(p).is_none()) {
+                return Err(FleetConfigError(format!(
+                    "endpoint '{}' has unknown protocol '{}'",
+                    name, protocol
+                )));
+            }
+            endpoints.push(FleetEndpoint {
+                name: name.to_string(),
+                url: url.to_string(),
+                model: model_at.and_then(cell).map(str::to_string),
+                protocol: protocol.map(str::to_string),
+            });
+        }
+        if endpoints.is_empty() {
+            return Err(FleetConfigError("'endpoints' is empty".to_string()));
+        }
+        Ok(Self { prompt, endpoints })
+    }
+}
+
+/// A fleet config that could not be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FleetConfigError(pub String);
+
+impl fmt::Display for FleetConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid fleet config: {}", self.0)
+    }
+}
+
+impl std::error::Error for FleetConfigError {}
+
+/// Outcome of one endpoint within a fleet run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EndpointStatus {
+    Insurable,
+    Uninsurable,
+    /// No result: a request or its response failed before all iterations
+    /// completed
+    Unreachable,
+}
+
+impl EndpointStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndpointStatus::Insurable => "INSURABLE",
+            EndpointStatus::Uninsurable => "UNINSURABLE",
+            EndpointStatus::Unreachable => "UNREACHABLE",
+        }
+    }
+}
+
+/// Per-endpoint row of a `FleetReport`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FleetEndpointReport {
+    pub name: String,
+    pub url: String,
+    pub model: String,
+    pub status: EndpointStatus,
+    /// `None` when unreachable
+    pub result: Option<RiskResult>,
+    /// The request error when unreachable
+    pub error: Option<String>,
+}
+
+/// Consolidated outcome of a fleet run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FleetReport {
+    pub prompt: String,
+    pub endpoints: Vec<FleetEndpointReport>,
+    /// Distinct iteration hashes across every reachable endpoint; 1 when the
+    /// whole fleet returned the same answer every time
+    pub fleet_entropy: usize,
+    /// Merkle root over the endpoint rows, a checksum; see the module docs
+    pub seal: String,
+}
+
+impl FleetReport {
+    /// Build a report from per-endpoint rows, computing entropy and seal
+    pub fn from_endpoints(prompt: impl Into<String>, endpoints: Vec<FleetEndpointReport>) -> Self {
+        let fleet_entropy = endpoints
+            .iter()
+            .filter_map(|e| e.result.as_ref())
+            .flat_map(|r| r.hashes.iter())
+            .collect::<HashSet<_>>()
+            .len();
+        let seal = seal(&endpoints);
+        Self {
+            prompt: prompt.into(),
+            endpoints,
+            fleet_entropy,
+            seal,
+        }
+    }
+
+    pub fn count(&self, status: EndpointStatus) -> usize {
+        self.endpoints.iter().filter(|e| e.status == status).count()
+    }
+
+    /// True if every endpoint is insurable and all of them agree
+    pub fn is_insurable(&self) -> bool {
+        self.count(EndpointStatus::Insurable) == self.endpoints.len() && self.fleet_entropy == 1
+    }
+
+    /// True if `seal` matches the endpoint rows. Proves consistency only,
+    /// not who produced the report.
+    pub fn verify_seal(&self) -> bool {
+        seal(&self.endpoints) == self.seal
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("FleetReport serializes to JSON")
+    }
+}
+
+fn seal(endpoints: &[FleetEndpointReport]) -> String {
+    let leaves: Vec<String> = endpoints
+        .iter()
+        .map(|e| {
+            let merkle_root = e.result.as_ref().map(|r| r.merkle_root.as_str());
+            serde_json::json!([e.name, e.url, e.status.as_str(), merkle_root]).to_string()
+        })
+        .collect();
+    merkle::merkle_root(&leaves)
+}
+
+/// Runs the endpoint check across a fleet
+#[derive(Debug, Clone)]
+pub struct FleetVerifier {
+    config: FleetConfig,
+    options: EndpointOptions,
+}
+
+impl FleetVerifier {
+    pub fn new(config: FleetConfig) -> Self {
+        Self {
+            config,
+            options: EndpointOptions::default(),
+        }
+    }
+
+    /// Calculator, default model and protocol, seed and timeout shared by
+    /// all endpoints
+    pub fn options(mut self, options: EndpointOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn config(&self) -> &FleetConfig {
+        &self.config
+    }
+
+    /// Check every endpoint, one after another in config order. An
+    /// unreachable endpoint is reported, not fatal.
+    pub async fn run(&self) -> FleetReport {
+        let mut endpoints = Vec::with_capacity(self.config.endpoints.len());
+        for endpoint in &self.config.endpoints {
+            let mut options = self.options.clone();
+            if let Some(model) = &endpoint.model {
+                options.model = model.clone();
+            }
+            let protocol = match endpoint.protocol.as_deref() {
+                Some(name) => protocol_named(name).ok_or_else(|| format!("Unknown protocol '{}'", name)),
+                None => Ok(options.protocol.clone()),
+            };
+            let outcome = match protocol {
+                Ok(protocol) => {
+                    options.protocol = protocol;
+                    verify_endpoint(&endpoint.url, &self.config.prompt, &options)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+                Err(e) => Err(e),
+            };
+            let (status, result, error) = match outcome {
+                Ok(report) if report.is_insurable() => (EndpointStatus::Insurable, Some(report.result), None),
+                Ok(report) => (EndpointStatus::Uninsurable, Some(report.result), None),
+                Err(e) => (EndpointStatus::Unreachable, None, Some(e)),
+            };
+            endpoints.push(FleetEndpointReport {
+                name: endpoint.name.clone(),
+                url: endpoint.url.clone(),
+                model: options.model,
+                status,
+                result,
+                error,
+            });
+        }
+        FleetReport::from_endpoints(self.config.prompt.clone(), endpoints)
+    }
+}
@@ -0,0 +1,168 @@
+//! Hash backends for iteration hashing
+//! The calculator only needs a hex digest and a stable name to record in
+//! results and tokens, so any digest can be plugged in through `RiskHasher`.
+//!
+//! SHA-256 and SHA-512 are always built in. The SHA-3 family and BLAKE3 sit
+//! behind the default `sha3` and `blake3` features so size-sensitive builds,
+//! such as the `wasm` bindings, can leave them out.
+
+use serde::{Deserialize, Serialize};
+use sha2::digest;
+use sha2::{Digest, Sha256, Sha512};
+#[cfg(feature = "sha3")]
+use sha3::{Keccak256, Sha3_256};
+use std::fmt;
+
+/// A digest usable for risk iterations
+pub trait RiskHasher: Send + Sync {
+    /// Stable identifier recorded in `RiskResult` and insurance tokens.
+    /// Must not contain `:` or `.`, which delimit signed token fields.
+    fn name(&self) -> &'static str;
+
+    /// Digest of `data` as lowercase hex
+    fn hash_hex(&self, data: &[u8]) -> String;
+
+    /// Start an incremental digest. The default buffers all input and calls
+    /// `hash_hex` at the end; override it to hash large inputs in constant
+    /// memory.
+    fn begin(&self) -> Box<dyn RiskDigest + '_> {
+        Box::new(Buffered { hasher: self, data: Vec::new() })
+    }
+}
+
+/// An in-progress digest from `RiskHasher::begin`
+pub trait RiskDigest {
+    fn update(&mut self, data: &[u8]);
+
+    /// The digest of everything passed to `update`, as lowercase hex
+    fn finish(self: Box<Self>) -> String;
+}
+
+struct Buffered<'h, H: ?Sized> {
+    hasher: &'h H,
+    data: Vec<u8>,
+}
+
+impl<H: RiskHasher + ?Sized> RiskDigest for Buffered<'_, H> {
+    fn update(&mut self, data: &[u8]) {
+        self.data.extend_from_slice(data);
+    }
+
+    fn finish(self: Box<Self>) -> String {
+        self.hasher.hash_hex(&self.data)
+    }
+}
+
+struct Streaming<D>(D);
+
+impl<D: Digest> RiskDigest for Streaming<D>
+where
+    digest::Output<D>: fmt::LowerHex,
+{
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finish(self: Box<Self>) -> String {
+        format!("{:x}", self.0.finalize())
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl RiskDigest for blake3::Hasher {
+    fn update(&mut self, data: &[u8]) {
+        blake3::Hasher::update(self, data);
+    }
+
+    fn finish(self: Box<Self>) -> String {
+        self.finalize().to_hex().to_string()
+    }
+}
+
+impl fmt::Debug for dyn RiskHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Built-in hash backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+    #[cfg(feature = "sha3")]
+    Sha3_256,
+    /// Original Keccak padding, as used by Ethereum
+    #[cfg(feature = "sha3")]
+    Keccak256,
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Every backend compiled into this build
+    pub const ALL: &'static [HashAlgorithm] = &[
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha512,
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_256,
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Keccak256,
+        #[cfg(feature = "blake3")]
+        HashAlgorithm::Blake3,
+    ];
+
+    /// Look up a backend by the name it records, e.g. `"blake3"`. Backends
+    /// whose feature is disabled are not found.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+impl RiskHasher for HashAlgorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => "sha3-256",
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Keccak256 => "keccak256",
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    fn hash_hex(&self, data: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Sha256 => format!("{:x}", Sha256::digest(data)),
+            HashAlgorithm::Sha512 => format!("{:x}", Sha512::digest(data)),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => format!("{:x}", Sha3_256::digest(data)),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Keccak256 => format!("{:x}", Keccak256::digest(data)),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => blake3::hash(data).to_hex().to_string(),
+        }
+    }
+
+    fn begin(&self) -> Box<dyn RiskDigest + '_> {
+        match self {
+            HashAlgorithm::Sha256 => Box::new(Streaming(Sha256::new())),
+            HashAlgorithm::Sha512 => Box::new(Streaming(Sha512::new())),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => Box::new(Streaming(Sha3_256::new())),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Keccak256 => Box::new(Streaming(Keccak256::new())),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => Box::new(blake3::Hasher::new()),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,175 @@
+//! Append-only, hash-chained history of risk evaluations
+//! Each entry commits to the previous entry's hash, so editing, removing or
+//! reordering any recorded evaluation breaks every later link. Persistent
+//! ledgers are JSON Lines files, one entry per line, appended and synced on
+//! every record.
+
+use crate::RiskResult;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `prev_hash` of the first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One recorded evaluation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub index: u64,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// SHA-256 of the evaluated input; the input itself is not stored
+    pub input_digest: String,
+    pub result: RiskResult,
+    pub prev_hash: String,
+    /// SHA-256 over every other field
+    pub entry_hash: String,
+}
+
+impl LedgerEntry {
+    fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(
+            format!(
+                "{}:{}:{}:{}:{}",
+                self.index,
+                self.timestamp,
+                self.input_digest,
+                self.prev_hash,
+                self.result.to_json()
+            )
+            .as_bytes(),
+        );
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Hash-chained log of risk results, in memory or backed by a file
+#[derive(Debug, Default)]
+pub struct RiskLedger {
+    entries: Vec<LedgerEntry>,
+    file: Option<File>,
+}
+
+impl RiskLedger {
+    /// An empty ledger that is not persisted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open or create the ledger file at `path`. Existing entries are
+    /// loaded and their chain verified before anything can be appended.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LedgerError> {
+        let file = OpenOptions::new().create(true).read(true).append(true).open(path)?;
+
+        let mut entries = Vec::new();
+        for (number, line) in BufReader::new(&file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line).map_err(|e| LedgerError::Corrupt {
+                line: number + 1,
+                message: e.to_string(),
+            })?;
+            entries.push(entry);
+        }
+
+        let ledger = Self { entries, file: Some(file) };
+        ledger.verify_chain()?;
+        Ok(ledger)
+    }
+
+    /// Append `result` for `input`, stamped with the current time
+    pub fn record(&mut self, input: &str, result: &RiskResult) -> Result<&LedgerEntry, LedgerError> {
+        let mut entry = LedgerEntry {
+            index: self.entries.len() as u64,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            input_digest: format!("{:x}", Sha256::digest(input.as_bytes())),
+            result: result.clone(),
+            prev_hash: self.head().to_string(),
+            entry_hash: String::new(),
+        };
+        entry.entry_hash = entry.compute_hash();
+
+        if let Some(file) = &mut self.file {
+            let mut line = serde_json::to_string(&entry).expect("LedgerEntry serializes to JSON");
+            line.push('\n');
+            file.write_all(line.as_bytes())?;
+            file.sync_data()?;
+        }
+        self.entries.push(entry);
+        Ok(self.entries.last().expect("entry was just pushed"))
+    }
+
+    /// Check every entry's hash and its link to the previous entry
+    pub fn verify_chain(&self) -> Result<(), LedgerError> {
+        let mut prev_hash = GENESIS_HASH;
+        for (position, entry) in self.entries.iter().enumerate() {
+            if entry.index != position as u64 || entry.prev_hash != prev_hash || entry.entry_hash != entry.compute_hash() {
+                return Err(LedgerError::Broken { index: position as u64 });
+            }
+            prev_hash = &entry.entry_hash;
+        }
+        Ok(())
+    }
+
+    /// Hash of the latest entry, or `GENESIS_HASH` when empty
+    pub fn head(&self) -> &str {
+        self.entries.last().map_or(GENESIS_HASH, |e| e.entry_hash.as_str())
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Why a ledger could not be read, written or trusted
+#[derive(Debug)]
+pub enum LedgerError {
+    Io(std::io::Error),
+    /// A line of the ledger file is not a valid entry (1-based line number)
+    Corrupt { line: usize, message: String },
+    /// The entry at `index` does not match its hash or its predecessor
+    Broken { index: u64 },
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::Io(e) => write!(f, "Ledger I/O error: {}", e),
+            LedgerError::Corrupt { line, message } => write!(f, "Ledger line {} is corrupt: {}", line, message),
+            LedgerError::Broken { index } => write!(f, "Ledger chain broken at entry {}", index),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LedgerError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LedgerError {
+    fn from(e: std::io::Error) -> Self {
+        LedgerError::Io(e)
+    }
+}
@@ -2,149 +2,369 @@
 //! AxiomHive Sovereign Manifold v2.1.0
 //! Zero Entropy Law (C=0) - Inverted Lagrangian Optimization (OLO)
 
-use sha2::{Sha256, Digest};
-use std::collections::HashSet;
+pub mod bio_proof;
+pub mod derivation;
+pub mod drift;
+#[cfg(feature = "network")]
+pub mod endpoint_verifier;
+#[cfg(feature = "network")]
+pub mod fleet;
+pub mod hasher;
+pub mod ledger;
+pub mod merkle;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod output;
+pub mod policy;
+#[cfg(feature = "network")]
+pub mod protocol;
+pub mod redaction;
+#[cfg(feature = "network")]
+pub mod suite;
+pub mod tenant;
+pub mod token;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use bio_proof::{BioProof, BioProofAccumulator};
+pub use derivation::InputDerivation;
+pub use drift::{DriftReport, PromptDrift};
+pub use hasher::{HashAlgorithm, RiskDigest, RiskHasher};
+pub use ledger::{LedgerEntry, LedgerError, RiskLedger};
+pub use merkle::{MerkleAccumulator, MerkleProof};
+#[cfg(feature = "metrics")]
+pub use metrics::render_metrics;
+pub use output::SCHEMA_VERSION;
+pub use policy::{PolicyError, RiskPolicy};
+pub use redaction::{RedactedRiskResult, RedactionError};
+pub use tenant::{TenantError, TenantKey, TenantKeyring};
+pub use token::{
+    verify_insurance_token, verify_insurance_token_at, RevocationList, SignedInsuranceToken, TokenError, TokenSigner,
+    Validity,
+};
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::Arc;
+use hmac::Mac;
 use serde::{Serialize, Deserialize};
 
 const ITERATION_COUNT: usize = 10;
 const TEMPERATURE: f64 = 0.0;
-const REQUIRED_ENTROPY_COUNT: usize = 1;
+
+/// How a run outside the active `RiskPolicy` is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VerificationMode {
+    /// Divergence is returned as `RiskError::Divergence`
+    #[default]
+    Strict,
+    /// Divergence is returned as an `Ok` result with a nonzero score
+    Lenient,
+}
 
 /// Risk Calculator implementing OLO (Inverted Lagrangian Optimization)
+#[derive(Debug, Clone)]
 pub struct RiskCalculator {
     temperature: f64,
     iteration_count: usize,
+    policy: RiskPolicy,
+    hasher: Arc<dyn RiskHasher>,
+    mode: VerificationMode,
+    session_id: Option<String>,
+    retain_hashes: bool,
+    tenant_key: Option<TenantKey>,
 }
 
 impl RiskCalculator {
-    /// Create new risk calculator with deterministic parameters
+    /// Create new risk calculator with deterministic parameters:
+    /// N=10 iterations, SHA-256, the default (Zero Entropy) policy, strict mode
     pub fn new() -> Self {
-        Self {
-            temperature: TEMPERATURE,
-            iteration_count: ITERATION_COUNT,
-        }
+        Self::builder().build()
+    }
+
+    /// Start from the defaults of `new()` and tune individual parameters
+    pub fn builder() -> RiskCalculatorBuilder {
+        RiskCalculatorBuilder::new()
+    }
+
+    pub fn iteration_count(&self) -> usize {
+        self.iteration_count
+    }
+
+    pub fn policy(&self) -> &RiskPolicy {
+        &self.policy
+    }
+
+    pub fn hasher(&self) -> &dyn RiskHasher {
+        self.hasher.as_ref()
+    }
+
+    pub fn mode(&self) -> VerificationMode {
+        self.mode
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    pub fn retains_hashes(&self) -> bool {
+        self.retain_hashes
+    }
+
+    pub fn tenant_key_id(&self) -> Option<&str> {
+        self.tenant_key.as_ref().map(TenantKey::id)
     }
 
     /// Calculate risk score with N iterations at Temperature=0.0
-    /// Returns RISK SCORE: 0 only if all hashes match (Zero Entropy)
-    pub fn calculate_risk(&self, input: &str) -> RiskResult {
-        // Enforce Temperature = 0.0 (deterministic mode)
-        assert_eq!(
+    /// Returns RISK SCORE: 0 only if the run satisfies the active policy
+    /// (by default: all hashes match, Zero Entropy).
+    /// A divergent input never panics: in strict mode it is an error that
+    /// still carries the populated (UNINSURABLE) result.
+    pub fn calculate_risk(&self, input: &str) -> Result<RiskResult, RiskError> {
+        // Temperature is fixed by construction, so release builds (and the
+        // wasm bindings) skip the check rather than carry a panic path
+        debug_assert_eq!(
             self.temperature, 0.0,
             "Risk calculation must run at Temperature=0.0 for Zero Entropy Law"
         );
 
-        // Perform N=10 iterations
-        let mut hashes = Vec::new();
-        let mut entropy_count = 0;
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        // Perform N iterations (10 by default) over the framed input; see
+        // the `derivation` module for why the iteration index is not part of it
+        let session_id = self.session_id.as_deref();
+        let iteration_input = derivation::frame_input(input.as_bytes(), session_id, self.temperature);
+        let mut tally = self.tally();
+        for _ in 0..self.iteration_count {
+            tally.push(self.hasher.hash_hex(&iteration_input));
+        }
+        let derivation = InputDerivation::Framed {
+            domain: derivation::DOMAIN.to_string(),
+            session_id: self.session_id.clone(),
+        };
+        let outcome = self.evaluate(tally, derivation);
+        #[cfg(feature = "metrics")]
+        metrics::record(&outcome, started.elapsed());
+        outcome
+    }
+
+    /// Score independently produced outputs, one per iteration, such as N
+    /// responses to the same prompt. Unlike `calculate_risk`, which re-derives
+    /// every iteration from one string, this detects real divergence.
+    /// The iteration count setting is ignored; divergent outputs yield an
+    /// UNINSURABLE result, and no outputs at all score as maximally risky.
+    pub fn verify_outputs<S: AsRef<str>>(&self, outputs: &[S]) -> RiskResult {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let mut tally = self.tally();
+        for output in outputs {
+            tally.push(self.hasher.hash_hex(output.as_ref().as_bytes()));
+        }
+        let outcome = self.evaluate(tally, InputDerivation::Raw);
+        #[cfg(feature = "metrics")]
+        metrics::record(&outcome, started.elapsed());
+        outcome.unwrap_or_else(RiskError::into_result)
+    }
 
-        for i in 0..self.iteration_count {
-            // Deterministic computation at Temperature=0.0
-            let iteration_input = format!("{}:{}:{}", input, self.temperature, i);
-            let hash = self.compute_hash(&iteration_input);
-            hashes.push(hash.clone());
+    /// Compare two model versions' outputs for the same prompts:
+    /// `outputs_a[i]` and `outputs_b[i]` answer `inputs[i]`. A version with
+    /// fewer outputs than prompts is reported as missing the rest, and
+    /// outputs beyond the last prompt are ignored.
+    pub fn compare(&self, inputs: &[&str], outputs_a: &[String], outputs_b: &[String]) -> DriftReport {
+        let hash = |outputs: &[String], index: usize| outputs.get(index).map(|o| self.hasher.hash_hex(o.as_bytes()));
+        let prompts = inputs
+            .iter()
+            .enumerate()
+            .map(|(index, prompt)| {
+                let (hash_a, hash_b) = (hash(outputs_a, index), hash(outputs_b, index));
+                let unique_states = [&hash_a, &hash_b].into_iter().flatten().collect::<HashSet<_>>().len();
+                PromptDrift {
+                    index,
+                    prompt: prompt.to_string(),
+                    hash_a,
+                    hash_b,
+                    unique_states,
+                }
+            })
+            .collect();
+        DriftReport::new(self.hasher.name(), prompts)
+    }
 
-            // Count unique hashes (entropy measure)
-            if i == 0 || !hashes[..i].contains(&hash) {
-                entropy_count += 1;
+    /// Calculate risk for an input read from `reader`, without holding it in
+    /// memory. The input is hashed once with the configured hasher and the
+    /// iterations run over that hex digest, so the result equals
+    /// `calculate_risk(&hasher.hash_hex(input))`, not `calculate_risk(input)`.
+    pub fn calculate_risk_from_reader(&self, mut reader: impl Read) -> io::Result<Result<RiskResult, RiskError>> {
+        let mut digest = self.hasher.begin();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => digest.update(&buffer[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
             }
         }
+        Ok(self.calculate_risk(&digest.finish()))
+    }
 
-        // Verify all hashes match (Zero Entropy requirement)
-        let all_match = if hashes.len() <= 1 {
-            true
-        } else {
-            hashes.windows(2).all(|w| w[0] == w[1])
-        };
-        
-        // Assert Entropy Count == 1 before issuing insurance token
-        assert_eq!(
-            entropy_count, REQUIRED_ENTROPY_COUNT,
-            "Entropy Count must be 1 for insurance token issuance. Found: {}",
-            entropy_count
-        );
+    /// Score a run from its tallied iteration hashes against the active policy
+    fn evaluate(&self, tally: Tally, derivation: InputDerivation) -> Result<RiskResult, RiskError> {
+        // Unique hashes (entropy measure) and how many iterations disagree
+        // with the most common one
+        let entropy_count = tally.frequencies.len();
+        let divergences = tally.iterations - tally.frequencies.values().max().copied().unwrap_or(0);
+
+        // All hashes match (Zero Entropy requirement) iff there is at most
+        // one distinct hash
+        let all_match = entropy_count <= 1;
 
-        let risk_score = if all_match && entropy_count == REQUIRED_ENTROPY_COUNT {
-            0
+        // No iterations at all prove nothing
+        let converged = tally.iterations > 0 && self.policy.accepts(entropy_count, divergences);
+        let risk_score = if tally.iterations == 0 {
+            u32::MAX
         } else {
-            // Calculate risk based on hash variance
-            self.compute_risk_from_hashes(&hashes)
+            self.policy.score(entropy_count, divergences)
         };
 
-        // Compute bio_proof before moving hashes
-        let bio_proof = self.compute_bio_proof(&hashes);
-
-        RiskResult {
+        let result = RiskResult {
             risk_score,
             entropy_count,
             all_hashes_match: all_match,
-            hashes,
-            bio_proof,
+            hashes: tally.retained.unwrap_or_default(),
+            iteration_count: tally.iterations,
+            bio_proof: tally.bio_proof.finish().value(),
+            hash_algorithm: self.hasher.name().to_string(),
+            merkle_root: tally.merkle.finish(),
+            derivation,
+            tenant_key_id: self.tenant_key_id().map(str::to_string),
+        };
+
+        // Strict mode refuses to hand out a divergent result as a success
+        if !converged && self.mode == VerificationMode::Strict {
+            return Err(RiskError::Divergence {
+                policy: self.policy,
+                divergences,
+                result: Box::new(result),
+            });
         }
+        Ok(result)
     }
 
-    /// Compute SHA-256 hash of input
-    fn compute_hash(&self, input: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(input.as_bytes());
-        let result = hasher.finalize();
-        format!("{:x}", result)
+    /// Calculate risk for many inputs, in parallel with the `parallel`
+    /// feature. Results are in input order and identical to calling
+    /// `calculate_risk` on each input; divergent inputs yield their
+    /// UNINSURABLE result rather than an error.
+    pub fn calculate_risk_batch(&self, inputs: &[&str]) -> Vec<RiskResult> {
+        let evaluate = |input: &&str| self.calculate_risk(input).unwrap_or_else(RiskError::into_result);
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            inputs.par_iter().map(evaluate).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            inputs.iter().map(evaluate).collect()
+        }
     }
 
-    /// Compute risk score from hash variance
-    fn compute_risk_from_hashes(&self, hashes: &[String]) -> u32 {
-        if hashes.is_empty() {
-            return u32::MAX;
+    /// Issue insurance token if risk score is 0 and the result was hashed
+    /// with this calculator's algorithm (and tenant key, if any). The token
+    /// names the algorithm: `INSURANCE_TOKEN_<algorithm>_<hex>`; keyed
+    /// tokens also name the key, see the `tenant` module.
+    pub fn issue_insurance_token(&self, risk_result: &RiskResult) -> Option<String> {
+        if !self.is_insurable(risk_result) {
+            return None;
+        }
+        let token_data = token_data(risk_result);
+        match &self.tenant_key {
+            None => {
+                let token_hash = self.hasher.hash_hex(token_data.as_bytes());
+                Some(format!("INSURANCE_TOKEN_{}_{}", risk_result.hash_algorithm, token_hash))
+            }
+            Some(key) => {
+                let mac = hex::encode(key.token_mac(&token_data).finalize().into_bytes());
+                Some(format!("INSURANCE_TOKEN_{}_{}_{}", risk_result.hash_algorithm, key.id(), mac))
+            }
         }
+    }
 
-        // Count unique hashes
-        let unique_count = hashes.iter().collect::<HashSet<_>>().len();
-        
-        // Risk increases with entropy (unique hash count)
-        if unique_count == 1 {
-            0
-        } else {
-            // Risk score proportional to entropy
-            (unique_count * 10).min(u32::MAX as usize) as u32
+    /// Issue an Ed25519-signed token that third parties can check with
+    /// `verify_insurance_token` and the signer's public key. Use a bounded
+    /// `validity` so certifications lapse, e.g. when the model is redeployed.
+    pub fn issue_signed_token(
+        &self,
+        risk_result: &RiskResult,
+        signer: &TokenSigner,
+        validity: Validity,
+    ) -> Result<SignedInsuranceToken, TokenError> {
+        if !self.is_insurable(risk_result) {
+            return Err(TokenError::NotInsurable);
         }
+        signer.sign(risk_result, validity)
     }
 
-    /// Compute Bio-Proof hash (canonical hardcoded hash)
-    fn compute_bio_proof(&self, hashes: &[String]) -> u64 {
-        // Combine all hashes and compute final proof
-        let combined: String = hashes.join("");
-        let mut hasher = Sha256::new();
-        hasher.update(combined.as_bytes());
-        let result = hasher.finalize();
-        
-        // Extract first 8 bytes as u64 (Bio-Proof)
-        let bytes = &result[..8];
-        u64::from_be_bytes([
-            bytes[0], bytes[1], bytes[2], bytes[3],
-            bytes[4], bytes[5], bytes[6], bytes[7],
-        ])
+    fn is_insurable(&self, risk_result: &RiskResult) -> bool {
+        risk_result.risk_score == 0
+            && risk_result.entropy_count <= self.policy.max_unique_states
+            && risk_result.hash_algorithm == self.hasher.name()
+            && risk_result.tenant_key_id.as_deref() == self.tenant_key_id()
     }
 
-    /// Issue insurance token if risk score is 0
-    pub fn issue_insurance_token(&self, risk_result: &RiskResult) -> Option<String> {
-        if risk_result.risk_score == 0 
-            && risk_result.entropy_count == REQUIRED_ENTROPY_COUNT
-            && risk_result.all_hashes_match {
-            
-            let token_data = format!(
-                "RISK_SCORE:{}:ENTROPY:{}:BIO_PROOF:{}",
-                risk_result.risk_score,
-                risk_result.entropy_count,
-                risk_result.bio_proof
-            );
-            
-            let mut hasher = Sha256::new();
-            hasher.update(token_data.as_bytes());
-            let token_hash = hasher.finalize();
-            
-            Some(format!("INSURANCE_TOKEN_{:x}", token_hash))
-        } else {
-            None
+    fn tally(&self) -> Tally {
+        let bio_proof = match &self.tenant_key {
+            Some(key) => BioProofAccumulator::keyed(key),
+            None => BioProofAccumulator::new(),
+        };
+        Tally::new(self.retain_hashes, bio_proof)
+    }
+}
+
+/// Fields an unsigned insurance token commits to
+pub(crate) fn token_data(risk_result: &RiskResult) -> String {
+    format!(
+        "RISK_SCORE:{}:ENTROPY:{}:BIO_PROOF:{}:ALGORITHM:{}",
+        risk_result.risk_score, risk_result.entropy_count, risk_result.bio_proof, risk_result.hash_algorithm
+    )
+}
+
+/// Running statistics over a run's iteration hashes. Memory grows with the
+/// number of distinct hashes, plus every hash only when they are retained
+/// for the result.
+struct Tally {
+    frequencies: HashMap<String, usize>,
+    iterations: usize,
+    bio_proof: BioProofAccumulator,
+    merkle: MerkleAccumulator,
+    retained: Option<Vec<String>>,
+}
+
+impl Tally {
+    fn new(retain_hashes: bool, bio_proof: BioProofAccumulator) -> Self {
+        Self {
+            frequencies: HashMap::new(),
+            iterations: 0,
+            bio_proof,
+            merkle: MerkleAccumulator::new(),
+            retained: retain_hashes.then(Vec::new),
+        }
+    }
+
+    fn push(&mut self, hash: String) {
+        self.iterations += 1;
+        self.bio_proof.push(&hash);
+        self.merkle.push(&hash);
+        match self.frequencies.get_mut(&hash) {
+            Some(count) => *count += 1,
+            None => {
+                self.frequencies.insert(hash.clone(), 1);
+            }
+        }
+        if let Some(retained) = &mut self.retained {
+            retained.push(hash);
         }
     }
 }
@@ -155,29 +375,1027 @@ impl Default for RiskCalculator {
     }
 }
 
+/// Why a risk calculation did not produce an insurable result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskError {
+    /// The run exceeded the policy's unique states or grace divergences.
+    /// `result` is fully populated and carries a nonzero risk score.
+    Divergence {
+        policy: RiskPolicy,
+        /// Iterations that differ from the most common hash
+        divergences: usize,
+        result: Box<RiskResult>,
+    },
+}
+
+impl RiskError {
+    /// The UNINSURABLE result behind the error, for reporting
+    pub fn into_result(self) -> RiskResult {
+        match self {
+            RiskError::Divergence { result, .. } => *result,
+        }
+    }
+}
+
+impl fmt::Display for RiskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskError::Divergence { policy, divergences, result } => write!(
+                f,
+                "Entropy Count must be at most {} with at most {} divergent iterations for insurance token issuance. Found: {} and {} (risk score {})",
+                policy.max_unique_states, policy.grace_divergences, result.entropy_count, divergences, result.risk_score
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RiskError {}
+
+/// Builder for `RiskCalculator`. Unset parameters keep the defaults of
+/// `RiskCalculator::new()`. Temperature is not configurable: the Zero
+/// Entropy Law requires 0.0.
+#[derive(Debug, Clone)]
+pub struct RiskCalculatorBuilder {
+    iteration_count: usize,
+    policy: RiskPolicy,
+    hasher: Arc<dyn RiskHasher>,
+    mode: VerificationMode,
+    session_id: Option<String>,
+    retain_hashes: bool,
+    tenant_key: Option<TenantKey>,
+}
+
+impl RiskCalculatorBuilder {
+    pub fn new() -> Self {
+        Self {
+            iteration_count: ITERATION_COUNT,
+            policy: RiskPolicy::default(),
+            hasher: Arc::new(HashAlgorithm::default()),
+            mode: VerificationMode::default(),
+            session_id: None,
+            retain_hashes: true,
+            tenant_key: None,
+        }
+    }
+
+    /// Number of hashing iterations (N). Panics if zero.
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        assert!(iterations > 0, "Risk calculation needs at least one iteration");
+        self.iteration_count = iterations;
+        self
+    }
+
+    /// Scoring rules, e.g. loaded with `RiskPolicy::from_toon`
+    pub fn policy(mut self, policy: RiskPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Use one of the built-in hash backends
+    pub fn hash_algorithm(self, algorithm: HashAlgorithm) -> Self {
+        self.hasher(algorithm)
+    }
+
+    /// Use a custom hash backend
+    pub fn hasher(mut self, hasher: impl RiskHasher + 'static) -> Self {
+        self.hasher = Arc::new(hasher);
+        self
+    }
+
+    pub fn mode(mut self, mode: VerificationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Bind iteration inputs to a caller-chosen seed or session id, so the
+    /// same input yields unrelated hashes in different sessions. The id is
+    /// recorded in every result.
+    pub fn session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Whether results list every iteration hash (the default). Turn it off
+    /// for very large iteration counts: memory then stays proportional to
+    /// the number of distinct hashes, while the Bio-Proof and Merkle root
+    /// still cover every iteration. Results then carry no `hashes` and
+    /// offer no Merkle proofs.
+    pub fn retain_hashes(mut self, retain: bool) -> Self {
+        self.retain_hashes = retain;
+        self
+    }
+
+    /// Key Bio-Proofs and unsigned tokens to a tenant, see the `tenant` module
+    pub fn tenant_key(mut self, key: TenantKey) -> Self {
+        self.tenant_key = Some(key);
+        self
+    }
+
+    pub fn build(self) -> RiskCalculator {
+        RiskCalculator {
+            temperature: TEMPERATURE,
+            iteration_count: self.iteration_count,
+            policy: self.policy,
+            hasher: self.hasher,
+            mode: self.mode,
+            session_id: self.session_id,
+            retain_hashes: self.retain_hashes,
+            tenant_key: self.tenant_key,
+        }
+    }
+}
+
+impl Default for RiskCalculatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Risk calculation result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RiskResult {
     pub risk_score: u32,
     pub entropy_count: usize,
     pub all_hashes_match: bool,
+    /// Every iteration hash in order; empty when the calculator was built
+    /// with `retain_hashes(false)`
     pub hashes: Vec<String>,
+    pub iteration_count: usize,
     pub bio_proof: u64,
+    /// Name of the `RiskHasher` that produced `hashes`
+    pub hash_algorithm: String,
+    /// Merkle root over `hashes`, see the `merkle` module
+    pub merkle_root: String,
+    /// How the hashed iteration inputs were derived
+    pub derivation: InputDerivation,
+    /// Id of the `TenantKey` that keyed `bio_proof`, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_key_id: Option<String>,
 }
 
 impl RiskResult {
-    /// Format result as boot log entry
-    pub fn to_boot_log(&self) -> String {
-        let status = if self.risk_score == 0 {
+    /// Proof that `hashes[index]` is committed to by `merkle_root`; `None`
+    /// if out of range or the hashes were not retained
+    pub fn merkle_proof(&self, index: usize) -> Option<MerkleProof> {
+        merkle::merkle_proof(&self.hashes, index)
+    }
+
+    /// "INSURABLE" for a zero risk score, "UNINSURABLE" otherwise
+    pub fn status(&self) -> &'static str {
+        if self.risk_score == 0 {
             "INSURABLE"
         } else {
             "UNINSURABLE"
-        };
+        }
+    }
+
+    /// Format result as boot log entry
+    pub fn to_boot_log(&self) -> String {
+        let status = self.status();
         
         format!(
-            "Risk Score: {} ({})\nBio-Proof: {}\nIteration Count: {}\nTemperature: {}\nEntropy Count: {}\nAll Hashes Match: {}",
-            self.risk_score, status, self.bio_proof, ITERATION_COUNT, TEMPERATURE, self.entropy_count, self.all_hashes_match
+            "Risk Score: {} ({})\nBio-Proof: {}\nIteration Count: {}\nTemperature: {}\nEntropy Count: {}\nAll Hashes Match: {}\nHash Algorithm: {}",
+            self.risk_score, status, self.bio_proof, self.iteration_count, TEMPERATURE, self.entropy_count, self.all_hashes_match, self.hash_algorithm
         )
     }
 }
 
+/// Aggregate view of a batch of results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRiskReport {
+    pub total: usize,
+    pub insurable: usize,
+    pub uninsurable: usize,
+    /// Bio-Proof over every result's Bio-Proof, in input order
+    pub combined_bio_proof: u64,
+}
+
+impl BatchRiskReport {
+    pub fn from_results(results: &[RiskResult]) -> Self {
+        let insurable = results.iter().filter(|r| r.risk_score == 0).count();
+        let proofs: Vec<u8> = results.iter().flat_map(|r| r.bio_proof.to_be_bytes()).collect();
+        Self {
+            total: results.len(),
+            insurable,
+            uninsurable: results.len() - insurable,
+            combined_bio_proof: bio_proof::derive_bytes(&proofs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Every digest differs, standing in for a nondeterministic model
+    struct Divergent(AtomicUsize);
+
+    impl RiskHasher for Divergent {
+        fn name(&self) -> &'static str {
+            "divergent"
+        }
+
+        fn hash_hex(&self, _data: &[u8]) -> String {
+            format!("{:x}", self.0.fetch_add(1, Ordering::Relaxed))
+        }
+    }
+
+    fn divergent() -> RiskCalculatorBuilder {
+        RiskCalculator::builder().hasher(Divergent(AtomicUsize::new(0)))
+    }
+
+    #[test]
+    fn test_default_calculator_is_insurable() {
+        let calculator = RiskCalculator::new();
+        let result = calculator.calculate_risk("Define the Zero Entropy Law.").unwrap();
+        assert_eq!(result.risk_score, 0);
+        assert_eq!(result.hashes.len(), ITERATION_COUNT);
+        assert!(calculator.issue_insurance_token(&result).is_some());
+    }
+
+    #[test]
+    fn test_builder_overrides_defaults() {
+        let calculator = RiskCalculator::builder()
+            .iterations(25)
+            .hash_algorithm(HashAlgorithm::Sha512)
+            .build();
+        let result = calculator.calculate_risk("input").unwrap();
+        assert_eq!(result.hashes.len(), 25);
+        assert_eq!(result.hashes[0].len(), 128);
+        assert!(result.to_boot_log().contains("Iteration Count: 25"));
+        assert_eq!(result.hash_algorithm, "sha512");
+    }
+
+    #[test]
+    fn test_hash_backends_are_recorded_in_result_and_token() {
+        for &algorithm in HashAlgorithm::ALL {
+            let calculator = RiskCalculator::builder().hash_algorithm(algorithm).build();
+            let result = calculator.calculate_risk("input").unwrap();
+            assert_eq!(result.hash_algorithm, algorithm.name());
+            assert_eq!(HashAlgorithm::from_name(&result.hash_algorithm), Some(algorithm));
+
+            let token = calculator.issue_insurance_token(&result).unwrap();
+            assert!(token.starts_with(&format!("INSURANCE_TOKEN_{}_", algorithm.name())));
+        }
+
+        // Known answers for the empty input
+        #[cfg(feature = "sha3")]
+        {
+            assert!(HashAlgorithm::Sha3_256.hash_hex(b"").starts_with("a7ffc6f8bf1ed766"));
+            assert!(HashAlgorithm::Keccak256.hash_hex(b"").starts_with("c5d2460186f7233c"));
+        }
+        #[cfg(feature = "blake3")]
+        assert!(HashAlgorithm::Blake3.hash_hex(b"").starts_with("af1349b9f5f9a1a6"));
+    }
+
+    #[test]
+    fn test_verify_outputs_detects_divergence() {
+        let calculator = RiskCalculator::new();
+        let same = vec!["C=0".to_string(); 5];
+        let result = calculator.verify_outputs(&same);
+        assert_eq!((result.risk_score, result.entropy_count), (0, 1));
+        assert_eq!(result.hashes.len(), 5);
+        assert!(calculator.issue_insurance_token(&result).is_some());
+
+        let result = calculator.verify_outputs(&["C=0", "C=0", "C=1"]);
+        assert_eq!(result.entropy_count, 2);
+        assert_ne!(result.risk_score, 0);
+        assert!(!result.all_hashes_match);
+
+        let none: [&str; 0] = [];
+        assert_ne!(calculator.verify_outputs(&none).risk_score, 0);
+    }
+
+    #[test]
+    fn test_reader_input_is_hashed_once() {
+        for &algorithm in HashAlgorithm::ALL {
+            let calculator = RiskCalculator::builder().hash_algorithm(algorithm).build();
+            let input = "transcript line\n".repeat(10_000);
+            let streamed = calculator.calculate_risk_from_reader(input.as_bytes()).unwrap().unwrap();
+            let direct = calculator.calculate_risk(&algorithm.hash_hex(input.as_bytes())).unwrap();
+            assert_eq!(streamed, direct);
+        }
+
+        // Larger than any internal buffer, never materialized as one string
+        let large = std::io::repeat(b'x').take(32 * 1024 * 1024);
+        let result = RiskCalculator::new().calculate_risk_from_reader(large).unwrap().unwrap();
+        assert_eq!(result.risk_score, 0);
+    }
+
+    #[test]
+    fn test_iteration_inputs_are_framed() {
+        // Joining with ':' would make these two runs hash the same bytes
+        let a = RiskCalculator::builder().session_id("b:c").build().calculate_risk("a").unwrap();
+        let b = RiskCalculator::builder().session_id("c").build().calculate_risk("a:b").unwrap();
+        assert_ne!(a.hashes[0], b.hashes[0]);
+        assert_ne!(
+            derivation::frame_input(b"a:b", None, 0.0),
+            derivation::frame_input(b"a", Some("b"), 0.0)
+        );
+
+        let plain = RiskCalculator::new().calculate_risk("a").unwrap();
+        assert_ne!(plain.hashes[0], a.hashes[0]);
+        assert_eq!(
+            a.derivation,
+            InputDerivation::Framed {
+                domain: derivation::DOMAIN.to_string(),
+                session_id: Some("b:c".to_string()),
+            }
+        );
+        assert_eq!(RiskCalculator::new().verify_outputs(&["x"]).derivation, InputDerivation::Raw);
+
+        let json: serde_json::Value = serde_json::from_str(&a.to_json()).unwrap();
+        assert_eq!(json["derivation"]["scheme"], "framed");
+        assert_eq!(json["derivation"]["session_id"], "b:c");
+    }
+
+    #[test]
+    fn test_bio_proof_follows_spec() {
+        // SHA-256("ab") begins fb8e20fc2e4c3f24
+        assert_eq!(BioProof::derive(&["a", "b"]).value(), 0xfb8e20fc2e4c3f24);
+
+        let result = RiskCalculator::new().calculate_risk("input").unwrap();
+        assert!(BioProof::verify(&result.hashes, result.bio_proof));
+        assert!(!BioProof::verify(&result.hashes[1..], result.bio_proof));
+        assert!(!BioProof::verify(&result.hashes, result.bio_proof ^ 1));
+    }
+
+    #[test]
+    fn test_batch_matches_sequential_results() {
+        let inputs: Vec<String> = (0..64).map(|i| format!("prompt {}", i)).collect();
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let calculator = RiskCalculator::new();
+
+        let batch = calculator.calculate_risk_batch(&inputs);
+        let sequential: Vec<RiskResult> = inputs.iter().map(|i| calculator.calculate_risk(i).unwrap()).collect();
+        assert_eq!(batch, sequential);
+
+        let report = BatchRiskReport::from_results(&batch);
+        assert_eq!((report.total, report.insurable, report.uninsurable), (64, 64, 0));
+        assert_eq!(report, BatchRiskReport::from_results(&calculator.calculate_risk_batch(&inputs)));
+    }
+
+    #[test]
+    fn test_batch_reports_divergent_inputs() {
+        let calculator = divergent().build();
+        let batch = calculator.calculate_risk_batch(&["a", "b"]);
+        assert!(batch.iter().all(|r| r.risk_score != 0));
+
+        let report = BatchRiskReport::from_results(&batch);
+        assert_eq!((report.insurable, report.uninsurable), (0, 2));
+    }
+
+    #[test]
+    fn test_signed_token_round_trip() {
+        let calculator = RiskCalculator::new();
+        let signer = TokenSigner::from_seed(b"test issuer");
+        let result = calculator.calculate_risk("input").unwrap();
+
+        let token = calculator.issue_signed_token(&result, &signer, Validity::default()).unwrap();
+        let text = token.to_string();
+        assert!(text.starts_with("SIGNED_INSURANCE_TOKEN.RISK_SCORE:0:ENTROPY:1:"));
+
+        let verified = verify_insurance_token(&signer.public_key(), &text).unwrap();
+        assert_eq!(verified, token);
+        assert_eq!(verified.bio_proof, result.bio_proof);
+
+        // Two tokens for the same result differ by nonce
+        let other = calculator.issue_signed_token(&result, &signer, Validity::default()).unwrap();
+        assert_ne!(other.nonce, token.nonce);
+    }
+
+    #[test]
+    fn test_signed_token_rejects_forgery() {
+        let calculator = RiskCalculator::new();
+        let signer = TokenSigner::from_bytes(&[7; 32]);
+        let result = calculator.calculate_risk("input").unwrap();
+        let text = signer.sign_with(&result, Validity::default(), 1_700_000_000, [1; 16]).to_string();
+
+        let tampered = text.replace(&format!("BIO_PROOF:{}", result.bio_proof), "BIO_PROOF:1");
+        assert_eq!(verify_insurance_token(&signer.public_key(), &tampered), Err(TokenError::BadSignature));
+
+        let impostor = TokenSigner::from_seed(b"someone else");
+        assert_eq!(verify_insurance_token(&impostor.public_key(), &text), Err(TokenError::BadSignature));
+
+        let legacy = calculator.issue_insurance_token(&result).unwrap();
+        assert!(matches!(verify_insurance_token(&signer.public_key(), &legacy), Err(TokenError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_signed_token_validity_window() {
+        let signer = TokenSigner::from_seed(b"test issuer");
+        let result = RiskCalculator::new().calculate_risk("input").unwrap();
+        let validity = Validity {
+            valid_from: Some(1_000),
+            valid_until: Some(2_000),
+        };
+        let text = signer.sign_with(&result, validity, 1_000, [2; 16]).to_string();
+        assert!(text.contains(":VALID_FROM:1000:VALID_UNTIL:2000."));
+
+        let key = signer.public_key();
+        assert!(verify_insurance_token_at(&key, &text, 1_000, None).is_ok());
+        assert!(verify_insurance_token_at(&key, &text, 2_000, None).is_ok());
+        assert_eq!(
+            verify_insurance_token_at(&key, &text, 999, None),
+            Err(TokenError::NotYetValid { valid_from: 1_000 })
+        );
+        assert_eq!(verify_insurance_token_at(&key, &text, 2_001, None), Err(TokenError::Expired { valid_until: 2_000 }));
+        assert_eq!(verify_insurance_token(&key, &text), Err(TokenError::Expired { valid_until: 2_000 }));
+
+        // Widening the window invalidates the signature
+        let widened = text.replace("VALID_UNTIL:2000", "VALID_UNTIL:-");
+        assert_eq!(verify_insurance_token_at(&key, &widened, 1_500, None), Err(TokenError::BadSignature));
+
+        let current = Validity::for_duration(std::time::Duration::from_secs(3600));
+        let token = signer.sign(&result, current).unwrap();
+        assert!(verify_insurance_token(&key, &token.to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_revocation_list_persists() {
+        let path = std::env::temp_dir().join(format!("risk-revocations-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let signer = TokenSigner::from_seed(b"test issuer");
+        let result = RiskCalculator::new().calculate_risk("input").unwrap();
+        let revoked = signer.sign_with(&result, Validity::default(), 1_000, [3; 16]);
+        let kept = signer.sign_with(&result, Validity::default(), 1_000, [4; 16]);
+
+        let mut list = RevocationList::open(&path).unwrap();
+        list.revoke(&revoked).unwrap();
+        list.revoke(&revoked).unwrap();
+        assert_eq!(list.len(), 1);
+
+        let reloaded = RevocationList::open(&path).unwrap();
+        let key = signer.public_key();
+        assert_eq!(
+            verify_insurance_token_at(&key, &revoked.to_string(), 1_000, Some(&reloaded)),
+            Err(TokenError::Revoked)
+        );
+        assert!(verify_insurance_token_at(&key, &kept.to_string(), 1_000, Some(&reloaded)).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_signed_token_requires_insurable_result() {
+        let calculator = divergent().mode(VerificationMode::Lenient).build();
+        let result = calculator.calculate_risk("input").unwrap();
+        let signer = TokenSigner::from_seed(b"test issuer");
+        assert_eq!(calculator.issue_signed_token(&result, &signer, Validity::default()), Err(TokenError::NotInsurable));
+    }
+
+    #[test]
+    fn test_structured_output_formats() {
+        let result = RiskCalculator::builder().iterations(3).build().calculate_risk("input").unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["status"], "INSURABLE");
+        assert_eq!(json["bio_proof"], result.bio_proof);
+        assert_eq!(json["hashes"].as_array().unwrap().len(), 3);
+
+        let cbor: serde_json::Value = ciborium::from_reader(result.to_cbor().as_slice()).unwrap();
+        assert_eq!(cbor, json);
+
+        let toon = toon_rs::ToonParser::new(&result.to_toon()).parse_document().unwrap();
+        assert_eq!(toon.get("schema_version").and_then(|v| v.as_u64()), Some(1));
+        assert_eq!(toon.get("bio_proof").and_then(|v| v.as_u64()), Some(result.bio_proof));
+        assert_eq!(toon.get("hash_algorithm"), Some(&toon_rs::ToonValue::String("sha256".into())));
+        match toon.get("hashes") {
+            Some(toon_rs::ToonValue::Schema { count: 3, schema, data }) => {
+                assert_eq!(schema, &["iteration", "hash"]);
+                assert_eq!(data[2], vec!["2".to_string(), result.hashes[2].clone()]);
+            }
+            other => panic!("unexpected hashes section {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_merkle_proofs_verify_against_root() {
+        for iterations in [1, 2, 3, 10, 1000] {
+            let result = RiskCalculator::builder().iterations(iterations).build().calculate_risk("input").unwrap();
+            for index in [0, iterations / 2, iterations - 1] {
+                let proof = result.merkle_proof(index).unwrap();
+                assert!(proof.verify(&result.merkle_root, iterations));
+                assert!(proof.siblings.len() <= 10);
+            }
+            assert!(result.merkle_proof(iterations).is_none());
+        }
+
+        // Distinct leaves: a proof only verifies for its own leaf and position
+        let leaves: Vec<String> = (0..7).map(|i| format!("{:x}", i)).collect();
+        let root = merkle::merkle_root(&leaves);
+        let mut proof = merkle::merkle_proof(&leaves, 5).unwrap();
+        assert!(proof.verify(&root, 7));
+        assert!(!proof.verify(&root, 5));
+        // The index is bound to the path: relabelling a valid proof fails
+        for index in [1, 4, 7] {
+            assert!(!MerkleProof { index, ..proof.clone() }.verify(&root, 7));
+        }
+        proof.leaf = leaves[4].clone();
+        assert!(!proof.verify(&root, 7));
+        assert_ne!(merkle::merkle_root(&leaves[..6]), root);
+    }
+
+    #[test]
+    fn test_unretained_run_commits_to_every_iteration() {
+        let leaves: Vec<String> = (0..40).map(|i| format!("{:x}", i)).collect();
+        for n in 0..=leaves.len() {
+            let mut accumulator = MerkleAccumulator::new();
+            leaves[..n].iter().for_each(|leaf| accumulator.push(leaf));
+            assert_eq!(accumulator.finish(), merkle::merkle_root(&leaves[..n]));
+        }
+
+        let outputs = ["C=0", "C=1", "C=0", "C=2", "C=0"];
+        let retained = RiskCalculator::builder().mode(VerificationMode::Lenient).build();
+        let compact = RiskCalculator::builder()
+            .mode(VerificationMode::Lenient)
+            .retain_hashes(false)
+            .build();
+        let (full, lean) = (retained.verify_outputs(&outputs), compact.verify_outputs(&outputs));
+        assert!(lean.hashes.is_empty() && lean.merkle_proof(0).is_none());
+        assert_eq!(lean.iteration_count, 5);
+        assert_eq!(
+            (lean.entropy_count, lean.risk_score, lean.bio_proof, &lean.merkle_root),
+            (full.entropy_count, full.risk_score, full.bio_proof, &full.merkle_root)
+        );
+
+        let result = RiskCalculator::builder()
+            .iterations(50_000)
+            .retain_hashes(false)
+            .build()
+            .calculate_risk("input")
+            .unwrap();
+        assert_eq!((result.iteration_count, result.entropy_count), (50_000, 1));
+        assert!(result.to_boot_log().contains("Iteration Count: 50000"));
+    }
+
+    #[test]
+    fn test_redacted_result_accepts_merkle_proofs() {
+        let result = RiskCalculator::new().calculate_risk("input").unwrap();
+        let redacted = result.redact();
+        assert!(!redacted.to_json().contains(&result.hashes[0]));
+        assert_eq!((redacted.status(), redacted.bio_proof), ("INSURABLE", result.bio_proof));
+
+        let proofs: Vec<MerkleProof> = [0, 4, 9].iter().map(|&i| result.merkle_proof(i).unwrap()).collect();
+        assert_eq!(redacted.verify_proofs(&proofs), Ok(()));
+
+        let mut forged = proofs[1].clone();
+        forged.leaf = "00".repeat(32);
+        assert_eq!(redacted.verify_proofs(&[forged]), Err(RedactionError::InvalidProof { index: 4 }));
+        let twice = [proofs[0].clone(), proofs[0].clone()];
+        assert_eq!(redacted.verify_proofs(&twice), Err(RedactionError::Duplicate { index: 0 }));
+        // One iteration cannot be disclosed again under another index
+        let relabelled = MerkleProof { index: 1, ..proofs[0].clone() };
+        assert_eq!(
+            redacted.verify_proofs(&[proofs[0].clone(), relabelled]),
+            Err(RedactionError::InvalidProof { index: 1 })
+        );
+
+        // Two disclosed states contradict a claimed entropy of one
+        let leaves = vec!["a".to_string(), "b".to_string()];
+        let mut claim = result.redact();
+        claim.merkle_root = merkle::merkle_root(&leaves);
+        claim.iteration_count = leaves.len();
+        let disclosed: Vec<MerkleProof> = (0..2).map(|i| merkle::merkle_proof(&leaves, i).unwrap()).collect();
+        assert!(matches!(
+            claim.verify_proofs(&disclosed),
+            Err(RedactionError::EntropyMismatch { disclosed: 2, claimed: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_tenant_keys_isolate_proofs_and_tokens() {
+        let key = |id: &str, secret: &str| TenantKey::new(id, secret.as_bytes()).unwrap();
+        let tenant = |k: TenantKey| RiskCalculator::builder().tenant_key(k).build();
+        let (a, b) = (tenant(key("acme-1", "acme secret")), tenant(key("globex-1", "globex secret")));
+
+        let (result_a, result_b) = (a.calculate_risk("input").unwrap(), b.calculate_risk("input").unwrap());
+        assert_eq!(result_a.hashes, result_b.hashes);
+        assert_ne!(result_a.bio_proof, result_b.bio_proof);
+        assert_eq!(result_a.tenant_key_id.as_deref(), Some("acme-1"));
+        assert!(BioProof::verify_keyed(&result_a.hashes, &key("acme-1", "acme secret"), result_a.bio_proof));
+
+        // Neither tenant issues for the other's result, nor does an unkeyed calculator
+        assert!(b.issue_insurance_token(&result_a).is_none());
+        assert!(RiskCalculator::new().issue_insurance_token(&result_a).is_none());
+        let token = a.issue_insurance_token(&result_a).unwrap();
+        assert!(token.starts_with("INSURANCE_TOKEN_sha256_acme-1_"));
+
+        // Rotation keeps old tokens verifying until the key is removed
+        let mut keyring = TenantKeyring::new(key("acme-1", "acme secret"));
+        keyring.rotate(key("acme-2", "new acme secret")).unwrap();
+        assert_eq!(keyring.verify_insurance_token(&result_a, &token), Ok(()));
+        let rotated = tenant(keyring.active().clone());
+        let result = rotated.calculate_risk("input").unwrap();
+        assert_eq!(keyring.verify_insurance_token(&result, &rotated.issue_insurance_token(&result).unwrap()), Ok(()));
+        assert_eq!(keyring.verify_insurance_token(&result, &token), Err(TenantError::ResultMismatch));
+
+        let forged = format!("{}0", &token[..token.len() - 1]);
+        assert!(keyring.verify_insurance_token(&result_a, &forged).is_err());
+        assert!(keyring.remove("acme-1"));
+        assert_eq!(
+            keyring.verify_insurance_token(&result_a, &token),
+            Err(TenantError::UnknownKey("acme-1".to_string()))
+        );
+        assert!(TenantKey::new("bad_id", b"x".to_vec()).is_err());
+
+        // Signed tokens carry the key id
+        let signer = TokenSigner::from_seed(b"issuer");
+        let signed = a.issue_signed_token(&result_a, &signer, Validity::default()).unwrap();
+        assert!(signed.to_string().contains(":KEY_ID:acme-1."));
+        let verified = verify_insurance_token(&signer.public_key(), &signed.to_string()).unwrap();
+        assert_eq!(verified.key_id.as_deref(), Some("acme-1"));
+    }
+
+    #[test]
+    fn test_ledger_links_entries() {
+        let calculator = RiskCalculator::new();
+        let mut ledger = RiskLedger::new();
+        for input in ["a", "b", "c"] {
+            ledger.record(input, &calculator.calculate_risk(input).unwrap()).unwrap();
+        }
+        assert!(ledger.verify_chain().is_ok());
+        assert_eq!(ledger.entries()[0].prev_hash, ledger::GENESIS_HASH);
+        assert_eq!(ledger.entries()[2].prev_hash, ledger.entries()[1].entry_hash);
+        assert_eq!(ledger.head(), ledger.entries()[2].entry_hash);
+    }
+
+    #[test]
+    fn test_ledger_persists_and_reloads() {
+        let path = std::env::temp_dir().join(format!("risk-ledger-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let calculator = RiskCalculator::new();
+
+        let head = {
+            let mut ledger = RiskLedger::open(&path).unwrap();
+            ledger.record("a", &calculator.calculate_risk("a").unwrap()).unwrap();
+            ledger.record("b", &calculator.calculate_risk("b").unwrap()).unwrap();
+            ledger.head().to_string()
+        };
+
+        let mut reopened = RiskLedger::open(&path).unwrap();
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(reopened.head(), head);
+        reopened.record("c", &calculator.calculate_risk("c").unwrap()).unwrap();
+        assert_eq!(RiskLedger::open(&path).unwrap().len(), 3);
+
+        // Rewriting a recorded score is caught on the next open
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replacen("\"risk_score\":0", "\"risk_score\":7", 1)).unwrap();
+        assert!(matches!(RiskLedger::open(&path), Err(LedgerError::Broken { index: 0 })));
+
+        std::fs::write(&path, "not json\n").unwrap();
+        assert!(matches!(RiskLedger::open(&path), Err(LedgerError::Corrupt { line: 1, .. })));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Serve one canned HTTP response per connection, in order
+    #[cfg(feature = "network")]
+    fn serve(bodies: Vec<String>) -> String {
+        serve_responses(bodies.into_iter().map(|body| ("200 OK", body)).collect())
+    }
+
+    /// Like `serve`, with an explicit status line per response
+    #[cfg(feature = "network")]
+    fn serve_responses(responses: Vec<(&'static str, String)>) -> String {
+        use std::io::{BufRead, BufReader, Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/generate", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                reader.read_exact(&mut vec![0; content_length]).unwrap();
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn test_endpoint_verifier_hashes_generated_text() {
+        use endpoint_verifier::{verify_endpoint, EndpointOptions};
+        let options = EndpointOptions {
+            calculator: RiskCalculator::builder().iterations(3).build(),
+            ..EndpointOptions::default()
+        };
+
+        // Timing metadata differs per request; the generated text does not
+        let bodies = (0..3).map(|i| format!(r#"{{"response":"C=0","total_duration":{}}}"#, i)).collect();
+        let report = verify_endpoint(&serve(bodies), "prompt", &options).await.unwrap();
+        assert!(report.is_insurable());
+        assert_eq!(report.result.hashes.len(), 3);
+
+        let bodies = ["a", "b", "a"].iter().map(|r| format!(r#"{{"response":"{}"}}"#, r)).collect();
+        let report = verify_endpoint(&serve(bodies), "prompt", &options).await.unwrap();
+        assert!(!report.is_insurable());
+        assert_eq!(report.result.entropy_count, 2);
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn test_endpoint_protocols_shape_requests_and_extract_text() {
+        use endpoint_verifier::{verify_endpoint, EndpointError, EndpointOptions};
+        use protocol::{EndpointProtocol, OpenAiChat, ProtocolError, RawTemplate, ResponsePath};
+
+        // Completion ids differ per request; only the message content is hashed
+        let bodies = (0..2)
+            .map(|i| format!(r#"{{"id":"cmpl-{}","choices":[{{"message":{{"role":"assistant","content":"C=0"}}}}]}}"#, i))
+            .collect();
+        let options = EndpointOptions {
+            calculator: RiskCalculator::builder().iterations(2).build(),
+            protocol: Arc::new(OpenAiChat::default()),
+            ..EndpointOptions::default()
+        };
+        let report = verify_endpoint(&serve(bodies), "prompt", &options).await.unwrap();
+        assert!(report.is_insurable());
+        assert_eq!(report.protocol, "openai");
+        assert_eq!(report.result.hashes[0], options.calculator.hasher().hash_hex(b"C=0"));
+        let body = OpenAiChat::default().request_body("m", "prompt", 7).unwrap();
+        assert_eq!(body["messages"][0]["content"], "prompt");
+        assert_eq!(body["seed"], 7);
+
+        let error = verify_endpoint(&serve(vec![r#"{"choices":[]}"#.to_string()]), "prompt", &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, EndpointError::Response { iteration: 1, source: ProtocolError::MissingText(_) }));
+
+        let template = RawTemplate::new(
+            r#"{"input":"Q: {{prompt}}","model":"{{model}}","seed":{{seed}},"temperature":0}"#,
+            Some("output.text".parse().unwrap()),
+        )
+        .unwrap();
+        let body = template.request_body("m", "say \"C=0\"", 42).unwrap();
+        assert_eq!(body["input"], "Q: say \"C=0\"");
+        assert_eq!(body["seed"], 42);
+        let body = template.request_body("{{seed}}", "repeat {{seed}} and {{model}}", 7).unwrap();
+        assert_eq!(body["input"], "Q: repeat {{seed}} and {{model}}");
+        assert_eq!(body["model"], "{{seed}}");
+        assert_eq!(body["seed"], 7);
+        assert_eq!(template.generated_text(r#"{"output":{"text":"C=0"}}"#).unwrap(), "C=0");
+        assert!(RawTemplate::new(r#"{"input":{{prompt}}}"#, None).is_err());
+        assert!(RawTemplate::new(r#"{"input":"x"}"#, None).is_err());
+        // Valid with seed 0, but not with every seed
+        let exponent = RawTemplate::new(r#"{"input":"{{prompt}}","scale":1e{{seed}}}"#, None).unwrap();
+        assert!(exponent.request_body("m", "p", 1).is_ok());
+        assert!(matches!(exponent.request_body("m", "p", u64::MAX), Err(ProtocolError::InvalidTemplate(_))));
+        let options = EndpointOptions { protocol: Arc::new(exponent), seed: u64::MAX, ..options };
+        let error = verify_endpoint("http://127.0.0.1:9/", "prompt", &options).await.unwrap_err();
+        assert!(matches!(error, EndpointError::Body(ProtocolError::InvalidTemplate(_))));
+        assert!(ResponsePath::new("choices..content").is_err());
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn test_endpoint_verifier_reports_unreachable_endpoint() {
+        use endpoint_verifier::{verify_endpoint, EndpointError, EndpointOptions, IterationSource, RetryPolicy};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+
+        let options = EndpointOptions {
+            retry: RetryPolicy {
+                max_attempts: 2,
+                initial_backoff: std::time::Duration::from_millis(1),
+                ..RetryPolicy::default()
+            },
+            ..EndpointOptions::default()
+        };
+        let error = verify_endpoint(&url, "prompt", &options).await.unwrap_err();
+        assert!(matches!(error, EndpointError::Request { iteration: 1, attempts: 2, .. }));
+
+        // Fallback is opt-in and never certifies the endpoint
+        let options = EndpointOptions {
+            allow_local_fallback: true,
+            ..options
+        };
+        let report = verify_endpoint(&url, "prompt", &options).await.unwrap();
+        assert_eq!(report.result.risk_score, 0);
+        assert_eq!(report.fallback_count(), ITERATION_COUNT);
+        assert!(matches!(report.sources[0], IterationSource::LocalFallback { .. }));
+        assert!(!report.is_insurable());
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn test_endpoint_verifier_retries_transient_failures() {
+        use endpoint_verifier::{verify_endpoint, EndpointOptions, IterationSource, RetryPolicy};
+        let ok = || ("200 OK", r#"{"response":"C=0"}"#.to_string());
+        let url = serve_responses(vec![ok(), ("503 Service Unavailable", String::new()), ok()]);
+        let options = EndpointOptions {
+            calculator: RiskCalculator::builder().iterations(2).build(),
+            retry: RetryPolicy {
+                initial_backoff: std::time::Duration::from_millis(1),
+                ..RetryPolicy::default()
+            },
+            ..EndpointOptions::default()
+        };
+        let report = verify_endpoint(&url, "prompt", &options).await.unwrap();
+        assert!(report.is_insurable());
+        assert_eq!(report.sources[1], IterationSource::Endpoint { attempts: 2 });
+
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1), std::time::Duration::from_millis(500));
+        assert_eq!(policy.backoff(3), std::time::Duration::from_secs(2));
+        assert_eq!(policy.backoff(30), policy.max_backoff);
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn test_fleet_report_consolidates_endpoints() {
+        use endpoint_verifier::EndpointOptions;
+        use fleet::{EndpointStatus, FleetConfig, FleetVerifier};
+
+        let agreed = || (0..2).map(|_| r#"{"response":"C=0"}"#.to_string()).collect();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let down = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+        let toon = format!(
+            "prompt = \"Define the Zero Entropy Law.\"\nendpoints [3]{{name,url,model}}\na,{},\nb,{},other-model\nc,{},\n",
+            serve(agreed()),
+            serve(agreed()),
+            down
+        );
+        let config = FleetConfig::from_toon(&toon).unwrap();
+        assert_eq!(config.endpoints[1].model.as_deref(), Some("other-model"));
+
+        let options = EndpointOptions {
+            calculator: RiskCalculator::builder().iterations(2).build(),
+            retry: endpoint_verifier::RetryPolicy::none(),
+            ..EndpointOptions::default()
+        };
+        let mut report = FleetVerifier::new(config).options(options).run().await;
+        assert_eq!(report.count(EndpointStatus::Insurable), 2);
+        assert_eq!(report.endpoints[2].status, EndpointStatus::Unreachable);
+        assert_eq!(report.endpoints[1].model, "other-model");
+        assert_eq!(report.fleet_entropy, 1);
+        assert!(!report.is_insurable());
+        assert!(report.verify_seal());
+
+        report.endpoints.swap(0, 1);
+        assert!(!report.verify_seal());
+        assert!(FleetConfig::from_toon("prompt = \"p\"\nendpoints [1]{name,url}\na,\n").is_err());
+        let unknown = "prompt = \"p\"\nendpoints [1]{name,url,protocol}\na,http://x/,grpc\n";
+        assert!(FleetConfig::from_toon(unknown).is_err());
+        assert!(FleetConfig::from_toon(r#"{"prompt": "p", "endpoints": []}"#).is_err());
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn test_prompt_suite_checks_every_prompt() {
+        use endpoint_verifier::EndpointOptions;
+        use suite::{verify_suite, PromptStatus, PromptSuite};
+
+        let calculator = RiskCalculator::builder().iterations(2).build();
+        let expected = calculator.hasher().hash_hex(b"4");
+        let toon = format!(
+            "prompts [3]{{id,prompt,expected_hash}}\nzero,\"Define the Zero Entropy Law, briefly.\",\nsum,What is 2+2?,{}\nwrong,What is 2+3?,{}\n",
+            expected.to_uppercase(),
+            expected
+        );
+        let suite = PromptSuite::from_toon(&toon).unwrap();
+        assert_eq!(suite.prompts[0].prompt, "Define the Zero Entropy Law, briefly.");
+        assert_eq!(suite.prompts[0].expected_hash, None);
+        assert_eq!(suite.prompts[1].expected_hash.as_deref(), Some(expected.as_str()));
+
+        let bodies = ["C=0", "C=0", "4", "4", "5", "5"]
+            .iter()
+            .map(|r| format!(r#"{{"response":"{}"}}"#, r))
+            .collect();
+        let options = EndpointOptions {
+            calculator,
+            ..EndpointOptions::default()
+        };
+        let report = verify_suite(&serve(bodies), &suite, &options).await;
+        let statuses: Vec<_> = report.prompts.iter().map(|p| p.status).collect();
+        assert_eq!(statuses, [PromptStatus::Pass, PromptStatus::Pass, PromptStatus::Mismatch]);
+        assert!(!report.passed());
+
+        assert!(PromptSuite::from_toon("prompts [2]{id,prompt}\na,x\na,y\n").is_err());
+        assert!(PromptSuite::from_toon("prompts [1]{id,prompt,expected_hash}\na,x,zz\n").is_err());
+        assert!(PromptSuite::from_toon(r#"{"prompts": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_token_requires_matching_algorithm() {
+        let sha512 = RiskCalculator::builder().hash_algorithm(HashAlgorithm::Sha512).build();
+        let result = sha512.calculate_risk("input").unwrap();
+        assert!(RiskCalculator::new().issue_insurance_token(&result).is_none());
+    }
+
+    #[test]
+    fn test_lenient_mode_reports_instead_of_aborting() {
+        let calculator = divergent().mode(VerificationMode::Lenient).build();
+        let result = calculator.calculate_risk("input").unwrap();
+        assert_eq!(result.entropy_count, ITERATION_COUNT);
+        assert_ne!(result.risk_score, 0);
+        assert!(calculator.issue_insurance_token(&result).is_none());
+    }
+
+    #[test]
+    fn test_strict_divergence_is_an_error_with_result() {
+        let calculator = divergent().build();
+        let error = calculator.calculate_risk("input").unwrap_err();
+        assert!(error.to_string().contains("Entropy Count must be at most 1"));
+
+        let result = error.into_result();
+        assert_eq!(result.hashes.len(), ITERATION_COUNT);
+        assert_ne!(result.risk_score, 0);
+        assert!(result.to_boot_log().contains("UNINSURABLE"));
+    }
+
+    #[test]
+    fn test_compare_reports_drifted_prompts() {
+        let inputs = ["p0", "p1", "p2"];
+        let a: Vec<String> = ["x", "y", "z"].iter().map(|s| s.to_string()).collect();
+        let b: Vec<String> = ["x", "Y"].iter().map(|s| s.to_string()).collect();
+
+        let report = RiskCalculator::new().compare(&inputs, &a, &b);
+        let diverged: Vec<usize> = report.diverged().map(|p| p.index).collect();
+        assert_eq!(diverged, [1, 2]);
+        assert_eq!(report.prompts[0].unique_states, 1);
+        assert_eq!(report.prompts[1].unique_states, 2);
+        assert_eq!(report.prompts[2].hash_b, None);
+        assert_eq!((report.unique_states_a, report.unique_states_b), (3, 2));
+        assert!((report.drift_rate() - 2.0 / 3.0).abs() < 1e-9);
+
+        assert!(RiskCalculator::new().compare(&inputs, &a, &a).is_identical());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_metrics_count_evaluations() {
+        // Counters are process-wide and other tests run concurrently, so
+        // only check that ours moved
+        let counter = |status: &str| -> u64 {
+            let prefix = format!("axiom_risk_evaluations_total{{status=\"{}\"}} ", status);
+            let rendered = render_metrics();
+            let line = rendered.lines().find(|l| l.starts_with(&prefix)).unwrap();
+            line[prefix.len()..].parse().unwrap()
+        };
+        let (insurable, uninsurable) = (counter("insurable"), counter("uninsurable"));
+
+        RiskCalculator::new().calculate_risk("input").unwrap();
+        RiskCalculator::new().verify_outputs(&["C=0", "C=1"]);
+        assert!(counter("insurable") > insurable);
+        assert!(counter("uninsurable") > uninsurable);
+
+        let rendered = render_metrics();
+        assert!(rendered.contains("# TYPE axiom_risk_evaluation_duration_seconds histogram"));
+        assert!(rendered.contains("axiom_risk_entropy_count_bucket{le=\"+Inf\"}"));
+    }
+
+    #[test]
+    fn test_policy_tolerates_grace_divergences() {
+        let outputs = ["C=0", "C=0", "C=0", "C=1"];
+        let strict = RiskCalculator::new().verify_outputs(&outputs);
+        assert_eq!(strict.risk_score, 20);
+
+        let tolerant = RiskPolicy {
+            max_unique_states: 2,
+            grace_divergences: 1,
+            ..RiskPolicy::default()
+        };
+        let calculator = RiskCalculator::builder().policy(tolerant).build();
+        let result = calculator.verify_outputs(&outputs);
+        assert_eq!(result.risk_score, 0);
+        assert!(calculator.issue_insurance_token(&result).is_some());
+
+        // A second divergent iteration exceeds the grace
+        assert_ne!(calculator.verify_outputs(&["C=0", "C=0", "C=1", "C=1"]).risk_score, 0);
+    }
+
+    #[test]
+    fn test_policy_weights_and_loading() {
+        let toon = "max_unique_states = 1\nweight_per_state = 5\nweight_per_divergence = 100\n";
+        let policy = RiskPolicy::from_toon(toon).unwrap();
+        assert_eq!(policy, RiskPolicy::from_json(r#"{"weight_per_state":5,"weight_per_divergence":100}"#).unwrap());
+        assert_eq!(policy.grace_divergences, 0);
+        assert_eq!(policy.score(2, 1), 110);
+        assert_eq!(policy.score(1, 0), 0);
+
+        assert!(RiskPolicy::from_json(r#"{"max_states":2}"#).is_err());
+        assert!(RiskPolicy::from_toon("max_unique_states = -1\n").is_err());
+        assert!(RiskPolicy::from_toon("max_unique_states = two\n").is_err());
+        assert!(RiskPolicy::from_toon(r#"{"max_unique_states": 2}"#).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! Merkle commitments over iteration hashes
+//! A verifier holding only `RiskResult::merkle_root` can check that one
+//! iteration hash belongs to the result from a proof of log2(N) hashes.
+//!
+//! Leaves and inner nodes are SHA-256 with distinct prefixes (0x00 and 0x01)
+//! so a leaf can never be passed off as an inner node. An unpaired node at
+//! the end of a level is carried up unchanged rather than duplicated.
+//! Verification also replays the path `index` takes through a tree of the
+//! known leaf count, so a proof cannot claim another iteration's position.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const LEAF: u8 = 0x00;
+const NODE: u8 = 0x01;
+
+/// Which side of the running hash a sibling sits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Inclusion proof for one iteration hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Iteration the leaf belongs to
+    pub index: usize,
+    /// The iteration hash itself
+    pub leaf: String,
+    /// Sibling hashes from the leaf level up to the root
+    pub siblings: Vec<(Side, String)>,
+}
+
+impl MerkleProof {
+    /// True if the proof leads from `leaf` to `root` along the path of
+    /// `index` in a tree over `leaf_count` leaves
+    pub fn verify(&self, root: &str, leaf_count: usize) -> bool {
+        if self.index >= leaf_count || self.siblings != self.siblings_on_path(leaf_count) {
+            return false;
+        }
+        let mut hash = leaf_hash(&self.leaf);
+        for (side, sibling) in &self.siblings {
+            hash = match side {
+                Side::Left => node_hash(sibling, &hash),
+                Side::Right => node_hash(&hash, sibling),
+            };
+        }
+        hash == root
+    }
+
+    /// `siblings` with each side replaced by the one `index` dictates,
+    /// and truncated or padded (with empty hashes) to the levels that
+    /// have a sibling
+    fn siblings_on_path(&self, leaf_count: usize) -> Vec<(Side, String)> {
+        let mut given = self.siblings.iter().map(|(_, hash)| hash.clone());
+        let (mut position, mut len) = (self.index, leaf_count);
+        let mut expected = Vec::new();
+        while len > 1 {
+            let sibling = position ^ 1;
+            if sibling < len {
+                let side = if sibling < position { Side::Left } else { Side::Right };
+                expected.push((side, given.next().unwrap_or_default()));
+            }
+            position /= 2;
+            len = len.div_ceil(2);
+        }
+        expected
+    }
+}
+
+/// Root over `leaves` in order; the empty tree hashes to SHA-256 of nothing
+pub fn merkle_root(leaves: &[String]) -> String {
+    let mut level: Vec<String> = leaves.iter().map(|l| leaf_hash(l)).collect();
+    if level.is_empty() {
+        return format!("{:x}", Sha256::digest([]));
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.remove(0)
+}
+
+/// Merkle root built one leaf at a time from O(log N) hashes: the root of
+/// each complete power-of-two subtree so far. `finish` equals `merkle_root`
+/// over the same leaves, since carrying unpaired nodes up unchanged makes the
+/// tree those subtrees joined from the right.
+#[derive(Debug, Clone, Default)]
+pub struct MerkleAccumulator {
+    /// Subtree roots left to right, with their heights, strictly decreasing
+    peaks: Vec<(u32, String)>,
+}
+
+impl MerkleAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, leaf: &str) {
+        let mut height = 0;
+        let mut hash = leaf_hash(leaf);
+        while let Some((top, _)) = self.peaks.last() {
+            if *top != height {
+                break;
+            }
+            let (_, left) = self.peaks.pop().expect("peak was just inspected");
+            hash = node_hash(&left, &hash);
+            height += 1;
+        }
+        self.peaks.push((height, hash));
+    }
+
+    pub fn finish(mut self) -> String {
+        let Some((_, mut root)) = self.peaks.pop() else {
+            return format!("{:x}", Sha256::digest([]));
+        };
+        while let Some((_, left)) = self.peaks.pop() {
+            root = node_hash(&left, &root);
+        }
+        root
+    }
+}
+
+/// Proof for `leaves[index]`, or `None` if out of range
+pub fn merkle_proof(leaves: &[String], index: usize) -> Option<MerkleProof> {
+    let leaf = leaves.get(index)?.clone();
+    let mut level: Vec<String> = leaves.iter().map(|l| leaf_hash(l)).collect();
+    let mut position = index;
+    let mut siblings = Vec::new();
+
+    while level.len() > 1 {
+        let sibling = position ^ 1;
+        if sibling < level.len() {
+            let side = if sibling < position { Side::Left } else { Side::Right };
+            siblings.push((side, level[sibling].clone()));
+        }
+        level = next_level(&level);
+        position /= 2;
+    }
+    Some(MerkleProof { index, leaf, siblings })
+}
+
+fn next_level(level: &[String]) -> Vec<String> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => single.clone(),
+            _ => unreachable!("chunks(2) yields one or two items"),
+        })
+        .collect()
+}
+
+fn leaf_hash(leaf: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF]);
+    hasher.update(leaf.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn node_hash(left: &str, right: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
@@ -0,0 +1,115 @@
+//! Process-wide risk metrics (`metrics` feature)
+//! Every `calculate_risk` and `verify_outputs` call (and so every batch,
+//! reader and endpoint check) is counted. `render_metrics` returns the
+//! Prometheus text exposition format, for the host to serve on `/metrics`
+//! or write to a node-exporter textfile.
+//!
+//! Counters are plain atomics and never reset, as Prometheus expects.
+
+use crate::{RiskError, RiskResult};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+const ENTROPY_BUCKETS: [u64; 7] = [1, 2, 4, 8, 16, 64, 256];
+/// Upper bounds in microseconds
+const LATENCY_BUCKETS_US: [u64; 7] = [100, 1_000, 10_000, 100_000, 1_000_000, 10_000_000, 60_000_000];
+
+static INSURABLE: AtomicU64 = AtomicU64::new(0);
+static UNINSURABLE: AtomicU64 = AtomicU64::new(0);
+static LAST_ENTROPY: AtomicU64 = AtomicU64::new(0);
+static ENTROPY: Histogram = Histogram::new();
+static LATENCY_US: Histogram = Histogram::new();
+
+struct Histogram {
+    buckets: [AtomicU64; 7],
+    count: AtomicU64,
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicU64::new(0) }; 7],
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, bounds: &[u64; 7], value: u64) {
+        for (bucket, bound) in self.buckets.iter().zip(bounds) {
+            if value <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Append the `_bucket`, `_sum` and `_count` series; `scale` divides
+    /// bounds and sum into the exposed unit
+    fn render(&self, out: &mut String, name: &str, bounds: &[u64; 7], scale: f64) {
+        for (bucket, bound) in self.buckets.iter().zip(bounds) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{le=\"{}\"}} {}",
+                name,
+                *bound as f64 / scale,
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, count);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum.load(Ordering::Relaxed) as f64 / scale);
+        let _ = writeln!(out, "{}_count {}", name, count);
+    }
+}
+
+/// Count one finished evaluation
+pub(crate) fn record(outcome: &Result<RiskResult, RiskError>, elapsed: Duration) {
+    let result = match outcome {
+        Ok(result) => result,
+        Err(RiskError::Divergence { result, .. }) => result,
+    };
+    let counter = if result.risk_score == 0 { &INSURABLE } else { &UNINSURABLE };
+    counter.fetch_add(1, Ordering::Relaxed);
+
+    let entropy = result.entropy_count as u64;
+    LAST_ENTROPY.store(entropy, Ordering::Relaxed);
+    ENTROPY.observe(&ENTROPY_BUCKETS, entropy);
+    let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+    LATENCY_US.observe(&LATENCY_BUCKETS_US, micros);
+}
+
+/// All metrics in Prometheus text format (version 0.0.4)
+pub fn render_metrics() -> String {
+    let mut out = String::new();
+    out.push_str("# HELP axiom_risk_evaluations_total Risk evaluations run, by verdict.\n");
+    out.push_str("# TYPE axiom_risk_evaluations_total counter\n");
+    for (status, counter) in [("insurable", &INSURABLE), ("uninsurable", &UNINSURABLE)] {
+        let _ = writeln!(
+            out,
+            "axiom_risk_evaluations_total{{status=\"{}\"}} {}",
+            status,
+            counter.load(Ordering::Relaxed)
+        );
+    }
+
+    out.push_str("# HELP axiom_risk_last_entropy_count Unique iteration hashes in the latest evaluation.\n");
+    out.push_str("# TYPE axiom_risk_last_entropy_count gauge\n");
+    let _ = writeln!(out, "axiom_risk_last_entropy_count {}", LAST_ENTROPY.load(Ordering::Relaxed));
+
+    out.push_str("# HELP axiom_risk_entropy_count Unique iteration hashes per evaluation.\n");
+    out.push_str("# TYPE axiom_risk_entropy_count histogram\n");
+    ENTROPY.render(&mut out, "axiom_risk_entropy_count", &ENTROPY_BUCKETS, 1.0);
+
+    out.push_str("# HELP axiom_risk_evaluation_duration_seconds Wall time per evaluation.\n");
+    out.push_str("# TYPE axiom_risk_evaluation_duration_seconds histogram\n");
+    LATENCY_US.render(
+        &mut out,
+        "axiom_risk_evaluation_duration_seconds",
+        &LATENCY_BUCKETS_US,
+        1_000_000.0,
+    );
+    out
+}
@@ -0,0 +1,73 @@
+//! Machine-readable RiskResult output
+//! JSON, CBOR and TOON carry the same fields, plus `schema_version` and the
+//! INSURABLE/UNINSURABLE `status` shown in the boot log. Bump
+//! `SCHEMA_VERSION` whenever a field is renamed, removed or changes meaning;
+//! adding a field does not require a bump.
+
+use crate::{InputDerivation, RiskResult};
+use serde::Serialize;
+use toon_rs::ToonDocument;
+
+/// Version of the structured output schema
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Versioned<'a> {
+    schema_version: u32,
+    status: &'static str,
+    #[serde(flatten)]
+    result: &'a RiskResult,
+}
+
+impl RiskResult {
+    fn versioned(&self) -> Versioned<'_> {
+        Versioned {
+            schema_version: SCHEMA_VERSION,
+            status: self.status(),
+            result: self,
+        }
+    }
+
+    /// JSON object with `schema_version`, `status` and every result field
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.versioned()).expect("RiskResult serializes to JSON")
+    }
+
+    /// The JSON fields as a CBOR map
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        ciborium::into_writer(&self.versioned(), &mut out).expect("RiskResult serializes to CBOR");
+        out
+    }
+
+    /// TOON document with the scalar fields as pairs and the iteration
+    /// hashes as a `hashes [N]{iteration,hash}` section
+    pub fn to_toon(&self) -> String {
+        let iterations: Vec<String> = (0..self.hashes.len()).map(|i| i.to_string()).collect();
+        let mut builder = ToonDocument::builder()
+            .pair("schema_version", u64::from(SCHEMA_VERSION))
+            .pair("status", self.status())
+            .pair("risk_score", u64::from(self.risk_score))
+            .pair("entropy_count", self.entropy_count as u64)
+            .pair("all_hashes_match", self.all_hashes_match)
+            .pair("iteration_count", self.iteration_count as u64)
+            .pair("bio_proof", self.bio_proof)
+            .pair("hash_algorithm", self.hash_algorithm.as_str())
+            .pair("merkle_root", self.merkle_root.as_str())
+            .pair("derivation", self.derivation.scheme())
+            .section("hashes", self.hashes.len(), &["iteration", "hash"]);
+        if let InputDerivation::Framed { session_id: Some(session_id), .. } = &self.derivation {
+            builder = builder.pair("session_id", session_id.as_str());
+        }
+        if let Some(key_id) = &self.tenant_key_id {
+            builder = builder.pair("tenant_key_id", key_id.as_str());
+        }
+        for (iteration, hash) in iterations.iter().zip(&self.hashes) {
+            builder = builder.row("hashes", &[iteration, hash]);
+        }
+        builder
+            .build()
+            .expect("RiskResult fields form a valid TOON document")
+            .to_toon_string()
+    }
+}
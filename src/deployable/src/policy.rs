@@ -0,0 +1,96 @@
+//! Configurable risk scoring
+//! A `RiskPolicy` decides how much divergence a run may show and still be
+//! insurable, and how divergent runs are scored. The default policy is the
+//! Zero Entropy Law: one unique state, no divergent iterations, and 10
+//! points per unique state otherwise.
+//!
+//! Policies load from JSON or TOON with the same keys, for example:
+//!
+//! ```text
+//! max_unique_states = 2
+//! grace_divergences = 1
+//! weight_per_state = 25
+//! ```
+//!
+//! Omitted keys keep their defaults; unknown keys are rejected.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use toon_rs::{ToonParser, ToonValue};
+
+/// How entropy maps to a risk score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskPolicy {
+    /// Most distinct iteration hashes an insurable run may show
+    pub max_unique_states: usize,
+    /// Iterations allowed to differ from the most common hash
+    pub grace_divergences: usize,
+    /// Score per unique state once a run exceeds the policy
+    pub weight_per_state: u32,
+    /// Score per divergent iteration once a run exceeds the policy
+    pub weight_per_divergence: u32,
+}
+
+impl Default for RiskPolicy {
+    fn default() -> Self {
+        Self {
+            max_unique_states: 1,
+            grace_divergences: 0,
+            weight_per_state: 10,
+            weight_per_divergence: 0,
+        }
+    }
+}
+
+impl RiskPolicy {
+    pub fn from_json(json: &str) -> Result<Self, PolicyError> {
+        serde_json::from_str(json).map_err(|e| PolicyError(e.to_string()))
+    }
+
+    /// Load from TOON `key = value` pairs. Values must be non-negative integers.
+    pub fn from_toon(toon: &str) -> Result<Self, PolicyError> {
+        let document = ToonParser::try_new(toon)
+            .and_then(|parser| parser.parse_document())
+            .map_err(|e| PolicyError(e.render(toon)))?;
+
+        let mut fields = serde_json::Map::new();
+        for (key, value) in document.entries() {
+            let number = match value {
+                ToonValue::Integer(_) | ToonValue::Unsigned(_) => value.as_u64(),
+                _ => None,
+            }
+            .ok_or_else(|| PolicyError(format!("'{}' must be a non-negative integer", key)))?;
+            fields.insert(key.clone(), number.into());
+        }
+        serde_json::from_value(fields.into()).map_err(|e| PolicyError(e.to_string()))
+    }
+
+    /// True if a run with these counts is within the policy
+    pub fn accepts(&self, unique_states: usize, divergences: usize) -> bool {
+        unique_states <= self.max_unique_states && divergences <= self.grace_divergences
+    }
+
+    /// 0 for an accepted run; otherwise the weighted score, at least 1
+    pub fn score(&self, unique_states: usize, divergences: usize) -> u32 {
+        if self.accepts(unique_states, divergences) {
+            return 0;
+        }
+        let weighted = |count: usize, weight: u32| u32::try_from(count).unwrap_or(u32::MAX).saturating_mul(weight);
+        weighted(unique_states, self.weight_per_state)
+            .saturating_add(weighted(divergences, self.weight_per_divergence))
+            .max(1)
+    }
+}
+
+/// A policy config that could not be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyError(pub String);
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid risk policy: {}", self.0)
+    }
+}
+
+impl std::error::Error for PolicyError {}
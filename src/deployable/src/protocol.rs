@@ -0,0 +1,259 @@
+//! Endpoint wire protocols (`network` feature)
+//! An `EndpointProtocol` builds the request body for one iteration and pulls
+//! the generated text back out of the response, so the endpoint check can
+//! talk to more than Ollama:
+//!
+//! - `Ollama`: `/api/generate` bodies, text at `response`
+//! - `OpenAiChat`: OpenAI-compatible `/v1/chat/completions` bodies (vLLM,
+//!   llama.cpp server, hosted APIs), text at `choices.0.message.content`
+//! - `RawTemplate`: any JSON body, from a template with `{{prompt}}`,
+//!   `{{model}}` and `{{seed}}` placeholders
+//!
+//! Response paths are dot-separated object keys and array indices. Only the
+//! extracted text is hashed, so per-request metadata such as ids and timings
+//! does not count as divergence. A response without text at the path is an
+//! error rather than being hashed whole.
+
+use serde_json::{json, Value};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// How requests are shaped and responses read for one kind of endpoint
+pub trait EndpointProtocol: fmt::Debug + Send + Sync {
+    /// Short name, as accepted by `protocol_named`
+    fn name(&self) -> &str;
+
+    /// JSON body for one iteration. Implementations pin temperature to 0.
+    fn request_body(&self, model: &str, prompt: &str, seed: u64) -> Result<Value, ProtocolError>;
+
+    /// The generated text inside a successful response body
+    fn generated_text(&self, body: &str) -> Result<String, ProtocolError>;
+}
+
+/// The built-in protocol called `name` ("ollama" or "openai") with its
+/// default response path
+pub fn protocol_named(name: &str) -> Option<Arc<dyn EndpointProtocol>> {
+    match name {
+        "ollama" => Some(Arc::new(Ollama::default())),
+        "openai" => Some(Arc::new(OpenAiChat::default())),
+        _ => None,
+    }
+}
+
+/// Ollama `/api/generate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ollama {
+    pub response_path: ResponsePath,
+}
+
+impl Default for Ollama {
+    fn default() -> Self {
+        Self {
+            response_path: ResponsePath::new("response").expect("valid path"),
+        }
+    }
+}
+
+impl EndpointProtocol for Ollama {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn request_body(&self, model: &str, prompt: &str, seed: u64) -> Result<Value, ProtocolError> {
+        Ok(json!({
+            "model": model,
+            "prompt": prompt,
+            "stream": false,
+            "options": {
+                "temperature": 0.0,
+                "seed": seed
+            }
+        }))
+    }
+
+    fn generated_text(&self, body: &str) -> Result<String, ProtocolError> {
+        self.response_path.extract_from(body)
+    }
+}
+
+/// OpenAI-compatible chat completions, one user message per request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenAiChat {
+    pub response_path: ResponsePath,
+}
+
+impl Default for OpenAiChat {
+    fn default() -> Self {
+        Self {
+            response_path: ResponsePath::new("choices.0.message.content").expect("valid path"),
+        }
+    }
+}
+
+impl EndpointProtocol for OpenAiChat {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn request_body(&self, model: &str, prompt: &str, seed: u64) -> Result<Value, ProtocolError> {
+        Ok(json!({
+            "model": model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "temperature": 0.0,
+            "seed": seed,
+            "stream": false
+        }))
+    }
+
+    fn generated_text(&self, body: &str) -> Result<String, ProtocolError> {
+        self.response_path.extract_from(body)
+    }
+}
+
+/// A caller-supplied JSON body. `{{prompt}}` and `{{model}}` are replaced
+/// with JSON-escaped text and belong inside a string literal; `{{seed}}` is
+/// replaced with a bare number. The template is responsible for requesting
+/// temperature 0. Without a response path the whole body is hashed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTemplate {
+    template: String,
+    response_path: Option<ResponsePath>,
+}
+
+impl RawTemplate {
+    /// Fails unless the template mentions `{{prompt}}` and renders to JSON
+    pub fn new(template: impl Into<String>, response_path: Option<ResponsePath>) -> Result<Self, ProtocolError> {
+        let template = template.into();
+        if !template.contains("{{prompt}}") {
+            return Err(ProtocolError::InvalidTemplate("missing {{prompt}} placeholder".to_string()));
+        }
+        let raw = Self { template, response_path };
+        serde_json::from_str::<Value>(&raw.render("model", "prompt", 0))
+            .map_err(|e| ProtocolError::InvalidTemplate(format!("does not render to JSON: {}", e)))?;
+        Ok(raw)
+    }
+
+    /// One left-to-right pass over the template, so placeholders inside a
+    /// substituted prompt or model stay as written
+    fn render(&self, model: &str, prompt: &str, seed: u64) -> String {
+        let mut rendered = String::with_capacity(self.template.len() + prompt.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find("{{") {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let (value, placeholder) = if rest.starts_with("{{prompt}}") {
+                (escaped(prompt), "{{prompt}}")
+            } else if rest.starts_with("{{model}}") {
+                (escaped(model), "{{model}}")
+            } else if rest.starts_with("{{seed}}") {
+                (seed.to_string(), "{{seed}}")
+            } else {
+                ("{".to_string(), "{")
+            };
+            rendered.push_str(&value);
+            rest = &rest[placeholder.len()..];
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
+impl EndpointProtocol for RawTemplate {
+    fn name(&self) -> &str {
+        "template"
+    }
+
+    fn request_body(&self, model: &str, prompt: &str, seed: u64) -> Result<Value, ProtocolError> {
+        // Escaped substitutions keep strings valid, but a seed can still
+        // break a template that rendered in `new`, as in `1e{{seed}}`
+        serde_json::from_str(&self.render(model, prompt, seed))
+            .map_err(|e| ProtocolError::InvalidTemplate(format!("does not render to JSON with seed {}: {}", seed, e)))
+    }
+
+    fn generated_text(&self, body: &str) -> Result<String, ProtocolError> {
+        match &self.response_path {
+            Some(path) => path.extract_from(body),
+            None => Ok(body.to_string()),
+        }
+    }
+}
+
+/// `text` as the inside of a JSON string literal
+fn escaped(text: &str) -> String {
+    let quoted = Value::from(text).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Dot-separated route to a string in a JSON document, such as
+/// `choices.0.message.content`; numeric segments also index arrays
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponsePath(Vec<String>);
+
+impl ResponsePath {
+    pub fn new(path: &str) -> Result<Self, ProtocolError> {
+        let segments: Vec<String> = path.split('.').map(str::to_string).collect();
+        if segments.iter().any(String::is_empty) {
+            return Err(ProtocolError::InvalidPath(path.to_string()));
+        }
+        Ok(Self(segments))
+    }
+
+    /// The string at this path, if there is one
+    pub fn extract<'a>(&self, document: &'a Value) -> Option<&'a str> {
+        self.0
+            .iter()
+            .try_fold(document, |value, segment| match value {
+                Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+                _ => value.get(segment),
+            })?
+            .as_str()
+    }
+
+    fn extract_from(&self, body: &str) -> Result<String, ProtocolError> {
+        let document: Value = serde_json::from_str(body).map_err(|_| ProtocolError::NotJson)?;
+        self.extract(&document)
+            .map(str::to_string)
+            .ok_or_else(|| ProtocolError::MissingText(self.to_string()))
+    }
+}
+
+impl FromStr for ResponsePath {
+    type Err = ProtocolError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        Self::new(path)
+    }
+}
+
+impl fmt::Display for ResponsePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join("."))
+    }
+}
+
+/// A protocol that could not be configured, or a response it cannot read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolError {
+    /// Response paths need non-empty segments
+    InvalidPath(String),
+    /// The request template is unusable
+    InvalidTemplate(String),
+    /// The response body is not JSON
+    NotJson,
+    /// The response has no string at this path
+    MissingText(String),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::InvalidPath(path) => write!(f, "Invalid response path '{}'", path),
+            ProtocolError::InvalidTemplate(reason) => write!(f, "Invalid request template: {}", reason),
+            ProtocolError::NotJson => f.write_str("Response body is not JSON"),
+            ProtocolError::MissingText(path) => write!(f, "Response has no text at '{}'", path),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
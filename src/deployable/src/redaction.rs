@@ -0,0 +1,117 @@
+//! Redacted results for privacy-sensitive deployments
+//! Iteration hashes are deterministic in the input, so publishing them lets
+//! anyone holding a candidate input confirm it was evaluated. A
+//! `RedactedRiskResult` keeps only the verdict and the commitments (Bio-Proof
+//! and Merkle root). The holder of the full result can still back a claim
+//! by disclosing selected iterations as Merkle proofs, which the redacted
+//! result checks against its root.
+
+use crate::{InputDerivation, MerkleProof, RiskResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A `RiskResult` without its iteration hashes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactedRiskResult {
+    pub risk_score: u32,
+    pub entropy_count: usize,
+    pub iteration_count: usize,
+    pub bio_proof: u64,
+    pub hash_algorithm: String,
+    pub merkle_root: String,
+    pub derivation: InputDerivation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_key_id: Option<String>,
+}
+
+impl RiskResult {
+    /// Drop the iteration hashes, keeping the verdict and commitments
+    pub fn redact(&self) -> RedactedRiskResult {
+        RedactedRiskResult {
+            risk_score: self.risk_score,
+            entropy_count: self.entropy_count,
+            iteration_count: self.iteration_count,
+            bio_proof: self.bio_proof,
+            hash_algorithm: self.hash_algorithm.clone(),
+            merkle_root: self.merkle_root.clone(),
+            derivation: self.derivation.clone(),
+            tenant_key_id: self.tenant_key_id.clone(),
+        }
+    }
+}
+
+impl RedactedRiskResult {
+    /// "INSURABLE" for a zero risk score, "UNINSURABLE" otherwise
+    pub fn status(&self) -> &'static str {
+        if self.risk_score == 0 {
+            "INSURABLE"
+        } else {
+            "UNINSURABLE"
+        }
+    }
+
+    /// Check disclosed iterations against the result: every proof must lead
+    /// to `merkle_root` from an iteration that exists, each iteration may be
+    /// disclosed once, and the disclosed hashes may not show more distinct
+    /// states than `entropy_count` claims.
+    pub fn verify_proofs(&self, proofs: &[MerkleProof]) -> Result<(), RedactionError> {
+        let mut indices = HashSet::new();
+        let mut states = HashSet::new();
+        for proof in proofs {
+            if proof.index >= self.iteration_count {
+                return Err(RedactionError::OutOfRange { index: proof.index });
+            }
+            if !indices.insert(proof.index) {
+                return Err(RedactionError::Duplicate { index: proof.index });
+            }
+            if !proof.verify(&self.merkle_root, self.iteration_count) {
+                return Err(RedactionError::InvalidProof { index: proof.index });
+            }
+            states.insert(proof.leaf.as_str());
+        }
+        if states.len() > self.entropy_count {
+            return Err(RedactionError::EntropyMismatch {
+                disclosed: states.len(),
+                claimed: self.entropy_count,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("RedactedRiskResult serializes to JSON")
+    }
+}
+
+/// Why disclosed iterations do not support a redacted result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedactionError {
+    /// The proof names an iteration past `iteration_count`
+    OutOfRange { index: usize },
+    /// The same iteration was disclosed twice
+    Duplicate { index: usize },
+    /// The proof does not lead to `merkle_root`
+    InvalidProof { index: usize },
+    /// The disclosed hashes contain more distinct states than claimed
+    EntropyMismatch { disclosed: usize, claimed: usize },
+}
+
+impl fmt::Display for RedactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedactionError::OutOfRange { index } => write!(f, "Iteration {} is out of range", index),
+            RedactionError::Duplicate { index } => write!(f, "Iteration {} was disclosed twice", index),
+            RedactionError::InvalidProof { index } => {
+                write!(f, "Merkle proof for iteration {} does not match the root", index)
+            }
+            RedactionError::EntropyMismatch { disclosed, claimed } => write!(
+                f,
+                "Disclosed iterations show {} distinct states but the result claims {}",
+                disclosed, claimed
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RedactionError {}
@@ -0,0 +1,185 @@
+//! Prompt-suite verification (`network` feature)
+//! Runs the endpoint check for every prompt of a TOON manifest instead of
+//! a single prompt. `expected_hash` is optional per row; when given, the
+//! endpoint must not only be deterministic but keep producing that exact
+//! output, hashed with the calculator's backend. Prompts containing commas
+//! are written quoted:
+//!
+//! ```text
+//! prompts [2]{id,prompt,expected_hash}
+//! zero-entropy,"Define the Zero Entropy Law.",
+//! arithmetic,What is 2+2?,9f3c...e1
+//! ```
+
+use crate::endpoint_verifier::{verify_endpoint, EndpointOptions};
+use crate::RiskResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use toon_rs::{ToonParser, ToonValue};
+
+/// One prompt of a suite
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuitePrompt {
+    pub id: String,
+    pub prompt: String,
+    /// Hex hash every iteration's output must have
+    pub expected_hash: Option<String>,
+}
+
+/// Prompts to check an endpoint against
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptSuite {
+    pub prompts: Vec<SuitePrompt>,
+}
+
+impl PromptSuite {
+    /// Load from a TOON document with a `prompts [N]{id,prompt[,expected_hash]}`
+    /// section
+    pub fn from_toon(toon: &str) -> Result<Self, SuiteError> {
+        let document = ToonParser::try_new(toon)
+            .and_then(|parser| parser.parse_document())
+            .map_err(|e| SuiteError(e.render(toon)))?;
+        let Some(ToonValue::Schema { schema, data, .. }) = document.get("prompts") else {
+            return Err(SuiteError("missing 'prompts [N]{id,prompt,expected_hash}' section".to_string()));
+        };
+
+        let column = |name: &str| schema.iter().position(|c| c == name);
+        let (Some(id_at), Some(prompt_at)) = (column("id"), column("prompt")) else {
+            return Err(SuiteError("'prompts' needs 'id' and 'prompt' columns".to_string()));
+        };
+        let expected_at = column("expected_hash");
+
+        let mut ids = HashSet::new();
+        let mut prompts = Vec::with_capacity(data.len());
+        for row in data {
+            let cell = |at: usize| row.get(at).map(|c| c.trim()).filter(|c| !c.is_empty());
+            let (Some(id), Some(prompt)) = (cell(id_at), cell(prompt_at)) else {
+                return Err(SuiteError("every prompt needs an id and a prompt".to_string()));
+            };
+            if !ids.insert(id) {
+                return Err(SuiteError(format!("duplicate prompt id '{}'", id)));
+            }
+            let expected_hash = expected_at.and_then(cell);
+            if let Some(hash) = expected_hash.filter(|h| !h.bytes().all(|b| b.is_ascii_hexdigit())) {
+                return Err(SuiteError(format!("expected_hash '{}' of '{}' is not hex", hash, id)));
+            }
+            prompts.push(SuitePrompt {
+                id: id.to_string(),
+                prompt: prompt.to_string(),
+                expected_hash: expected_hash.map(str::to_ascii_lowercase),
+            });
+        }
+        if prompts.is_empty() {
+            return Err(SuiteError("'prompts' is empty".to_string()));
+        }
+        Ok(Self { prompts })
+    }
+}
+
+/// A suite manifest that could not be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiteError(pub String);
+
+impl fmt::Display for SuiteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid prompt suite: {}", self.0)
+    }
+}
+
+impl std::error::Error for SuiteError {}
+
+/// Outcome of one prompt within a suite run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PromptStatus {
+    /// Insurable, and matching `expected_hash` if one was given
+    Pass,
+    /// Not insurable: the endpoint diverged across iterations, or some
+    /// iterations fell back to local stand-ins
+    Divergent,
+    /// Deterministic, but not the expected output
+    Mismatch,
+    /// No result: a request or its response failed
+    Failed,
+}
+
+impl PromptStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptStatus::Pass => "PASS",
+            PromptStatus::Divergent => "DIVERGENT",
+            PromptStatus::Mismatch => "MISMATCH",
+            PromptStatus::Failed => "FAILED",
+        }
+    }
+}
+
+/// Per-prompt row of a `SuiteReport`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuitePromptReport {
+    pub id: String,
+    pub status: PromptStatus,
+    pub expected_hash: Option<String>,
+    /// `None` when the prompt failed
+    pub result: Option<RiskResult>,
+    /// The endpoint error when failed
+    pub error: Option<String>,
+}
+
+/// Outcome of a suite run against one endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuiteReport {
+    pub endpoint: String,
+    pub model: String,
+    pub prompts: Vec<SuitePromptReport>,
+}
+
+impl SuiteReport {
+    pub fn count(&self, status: PromptStatus) -> usize {
+        self.prompts.iter().filter(|p| p.status == status).count()
+    }
+
+    /// True if every prompt passed
+    pub fn passed(&self) -> bool {
+        self.count(PromptStatus::Pass) == self.prompts.len()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("SuiteReport serializes to JSON")
+    }
+}
+
+/// Check every prompt of `suite` against `url`, one after another in
+/// manifest order. A failed prompt is reported, not fatal.
+pub async fn verify_suite(url: &str, suite: &PromptSuite, opts: &EndpointOptions) -> SuiteReport {
+    let mut prompts = Vec::with_capacity(suite.prompts.len());
+    for prompt in &suite.prompts {
+        let (status, result, error) = match verify_endpoint(url, &prompt.prompt, opts).await {
+            Ok(report) if !report.is_insurable() => (PromptStatus::Divergent, Some(report.result), None),
+            Ok(report) => {
+                // A calculator that does not retain hashes cannot confirm one
+                let hashes = &report.result.hashes;
+                let matches = prompt
+                    .expected_hash
+                    .as_ref()
+                    .is_none_or(|expected| !hashes.is_empty() && hashes.iter().all(|h| h == expected));
+                let status = if matches { PromptStatus::Pass } else { PromptStatus::Mismatch };
+                (status, Some(report.result), None)
+            }
+            Err(e) => (PromptStatus::Failed, None, Some(e.to_string())),
+        };
+        prompts.push(SuitePromptReport {
+            id: prompt.id.clone(),
+            status,
+            expected_hash: prompt.expected_hash.clone(),
+            result,
+            error,
+        });
+    }
+    SuiteReport {
+        endpoint: url.to_string(),
+        model: opts.model.clone(),
+        prompts,
+    }
+}
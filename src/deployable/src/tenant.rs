@@ -0,0 +1,179 @@
+//! Tenant-keyed Bio-Proofs and insurance tokens
+//! A calculator built with a `TenantKey` derives Bio-Proofs and unsigned
+//! insurance tokens with HMAC-SHA256 under the tenant's secret instead of a
+//! plain digest. Identical inputs then yield unrelated proofs and tokens for
+//! different tenants, so one tenant's token cannot be replayed as another's.
+//!
+//! Every keyed result and token names the key id that produced it:
+//!
+//! ```text
+//! INSURANCE_TOKEN_<algorithm>_<key id>_<HMAC-SHA256 hex>
+//! ```
+//!
+//! The MAC covers the unkeyed token fields followed by `:KEY_ID:<key id>`.
+//! To rotate, add the new key to the tenant's `TenantKeyring` with
+//! `rotate`; tokens issued under retired keys keep verifying until the key
+//! is removed.
+
+use crate::{BioProof, RiskResult};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt;
+
+pub(crate) type HmacSha256 = Hmac<Sha256>;
+
+/// A tenant's secret and the id recorded alongside everything it keys
+#[derive(Clone, PartialEq, Eq)]
+pub struct TenantKey {
+    id: String,
+    secret: Vec<u8>,
+}
+
+impl TenantKey {
+    /// `id` must be non-empty ASCII letters, digits and `-`, since it is
+    /// embedded in `_`-, `:`- and `.`-delimited tokens
+    pub fn new(id: impl Into<String>, secret: impl Into<Vec<u8>>) -> Result<Self, TenantError> {
+        let id = id.into();
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(TenantError::InvalidKeyId(id));
+        }
+        Ok(Self {
+            id,
+            secret: secret.into(),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(crate) fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length")
+    }
+
+    /// MAC over the unsigned token fields and this key's id
+    pub(crate) fn token_mac(&self, token_data: &str) -> HmacSha256 {
+        let mut mac = self.mac();
+        mac.update(format!("{}:KEY_ID:{}", token_data, self.id).as_bytes());
+        mac
+    }
+}
+
+impl fmt::Debug for TenantKey {
+    // Never print the secret
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantKey").field("id", &self.id).finish()
+    }
+}
+
+/// A tenant's active key plus the retired keys its old tokens used
+#[derive(Debug, Clone)]
+pub struct TenantKeyring {
+    active: TenantKey,
+    retired: Vec<TenantKey>,
+}
+
+impl TenantKeyring {
+    pub fn new(active: TenantKey) -> Self {
+        Self {
+            active,
+            retired: Vec::new(),
+        }
+    }
+
+    /// The key new results and tokens should use
+    pub fn active(&self) -> &TenantKey {
+        &self.active
+    }
+
+    /// Make `key` active and keep the previous key for verification
+    pub fn rotate(&mut self, key: TenantKey) -> Result<(), TenantError> {
+        if self.get(key.id()).is_some() {
+            return Err(TenantError::DuplicateKeyId(key.id));
+        }
+        let previous = std::mem::replace(&mut self.active, key);
+        self.retired.push(previous);
+        Ok(())
+    }
+
+    /// Stop accepting anything keyed with the retired key `id`
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.retired.len();
+        self.retired.retain(|k| k.id != id);
+        self.retired.len() != before
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TenantKey> {
+        std::iter::once(&self.active)
+            .chain(&self.retired)
+            .find(|k| k.id == id)
+    }
+
+    /// Check an unsigned keyed token against the result it was issued for,
+    /// under whichever of this tenant's keys the token names. When the
+    /// result still lists its hashes the keyed Bio-Proof is recomputed too.
+    pub fn verify_insurance_token(&self, result: &RiskResult, token: &str) -> Result<(), TenantError> {
+        let fields = token
+            .strip_prefix("INSURANCE_TOKEN_")
+            .and_then(|rest| {
+                let (algorithm, rest) = rest.split_once('_')?;
+                let (key_id, mac) = rest.split_once('_')?;
+                Some((algorithm, key_id, mac))
+            });
+        let Some((algorithm, key_id, mac)) = fields else {
+            return Err(TenantError::Malformed);
+        };
+        let key = self
+            .get(key_id)
+            .ok_or_else(|| TenantError::UnknownKey(key_id.to_string()))?;
+
+        if result.tenant_key_id.as_deref() != Some(key_id) || result.hash_algorithm != algorithm {
+            return Err(TenantError::ResultMismatch);
+        }
+        if result.risk_score != 0 {
+            return Err(TenantError::NotInsurable);
+        }
+        if !result.hashes.is_empty() && !BioProof::verify_keyed(&result.hashes, key, result.bio_proof) {
+            return Err(TenantError::ResultMismatch);
+        }
+        let mac = hex::decode(mac).map_err(|_| TenantError::Malformed)?;
+        key.token_mac(&crate::token_data(result))
+            .verify_slice(&mac)
+            .map_err(|_| TenantError::BadToken)
+    }
+}
+
+/// Why a tenant key or keyed token was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantError {
+    /// Key ids are non-empty ASCII letters, digits and `-`
+    InvalidKeyId(String),
+    /// The keyring already holds a key with this id
+    DuplicateKeyId(String),
+    /// The token is not `INSURANCE_TOKEN_<algorithm>_<key id>_<hex>`
+    Malformed,
+    /// The token names a key this keyring does not hold
+    UnknownKey(String),
+    /// The token's key or algorithm, or the Bio-Proof, does not match the result
+    ResultMismatch,
+    /// The result does not qualify for an insurance token
+    NotInsurable,
+    /// The token's MAC does not verify
+    BadToken,
+}
+
+impl fmt::Display for TenantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TenantError::InvalidKeyId(id) => write!(f, "Invalid tenant key id '{}'", id),
+            TenantError::DuplicateKeyId(id) => write!(f, "Tenant key id '{}' is already in use", id),
+            TenantError::Malformed => f.write_str("Malformed keyed insurance token"),
+            TenantError::UnknownKey(id) => write!(f, "Unknown tenant key '{}'", id),
+            TenantError::ResultMismatch => f.write_str("Insurance token does not belong to this result"),
+            TenantError::NotInsurable => f.write_str("Result is not insurable"),
+            TenantError::BadToken => f.write_str("Insurance token MAC does not verify"),
+        }
+    }
+}
+
+impl std::error::Error for TenantError {}
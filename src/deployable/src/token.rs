@@ -0,0 +1,356 @@
+//! Ed25519-signed insurance tokens
+//! Unlike the bare hash from `issue_insurance_token`, a signed token can be
+//! checked by a third party holding only the issuer's public key. Tokens are
+//! plain text:
+//!
+//! ```text
+//! SIGNED_INSURANCE_TOKEN.RISK_SCORE:0:ENTROPY:1:BIO_PROOF:<u64>:ALGORITHM:<name>:ISSUED_AT:<unix secs>:NONCE:<hex>:VALID_FROM:<unix secs|->:VALID_UNTIL:<unix secs|->.<signature hex>
+//! ```
+//!
+//! The signature covers the payload between the dots byte for byte. `-`
+//! marks an open end of the validity window. Tokens for tenant-keyed
+//! results append `:KEY_ID:<key id>` to the payload. Issuers can withdraw a token
+//! early by adding it to a `RevocationList`.
+
+use crate::RiskResult;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const PREFIX: &str = "SIGNED_INSURANCE_TOKEN";
+const NONCE_LEN: usize = 16;
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Window in which a token verifies, in seconds since the Unix epoch.
+/// Both ends are inclusive; `None` leaves that end open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Validity {
+    pub valid_from: Option<u64>,
+    pub valid_until: Option<u64>,
+}
+
+impl Validity {
+    /// Valid from now for `duration`, e.g. until the model is redeployed
+    pub fn for_duration(duration: Duration) -> Self {
+        let now = unix_now();
+        Self {
+            valid_from: Some(now),
+            valid_until: Some(now.saturating_add(duration.as_secs())),
+        }
+    }
+
+    fn check(&self, now: u64) -> Result<(), TokenError> {
+        match (self.valid_from, self.valid_until) {
+            (Some(from), _) if now < from => Err(TokenError::NotYetValid { valid_from: from }),
+            (_, Some(until)) if now > until => Err(TokenError::Expired { valid_until: until }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Signs insurance tokens with an Ed25519 key
+pub struct TokenSigner {
+    key: SigningKey,
+}
+
+impl TokenSigner {
+    /// Use a 32-byte Ed25519 secret key
+    pub fn from_bytes(secret: &[u8; 32]) -> Self {
+        Self { key: SigningKey::from_bytes(secret) }
+    }
+
+    /// Derive the key from an arbitrary seed (SHA-256 of the seed). The same
+    /// seed always yields the same key, so keep it as secret as a key.
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self::from_bytes(&Sha256::digest(seed).into())
+    }
+
+    /// Public key verifiers need, as 32 bytes
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+
+    /// Sign `result` with the current time and a random nonce
+    pub fn sign(&self, result: &RiskResult, validity: Validity) -> Result<SignedInsuranceToken, TokenError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).map_err(|e| TokenError::Nonce(e.to_string()))?;
+        Ok(self.sign_with(result, validity, unix_now(), nonce))
+    }
+
+    /// Sign `result` with an explicit timestamp and nonce
+    pub fn sign_with(
+        &self,
+        result: &RiskResult,
+        validity: Validity,
+        issued_at: u64,
+        nonce: [u8; NONCE_LEN],
+    ) -> SignedInsuranceToken {
+        let mut token = SignedInsuranceToken {
+            risk_score: result.risk_score,
+            entropy_count: result.entropy_count,
+            bio_proof: result.bio_proof,
+            hash_algorithm: result.hash_algorithm.clone(),
+            issued_at,
+            nonce,
+            validity,
+            key_id: result.tenant_key_id.clone(),
+            signature: [0; 64],
+        };
+        token.signature = self.key.sign(token.payload().as_bytes()).to_bytes();
+        token
+    }
+}
+
+impl fmt::Debug for TokenSigner {
+    // Never print the secret key
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenSigner")
+            .field("public_key", &hex::encode(self.public_key()))
+            .finish()
+    }
+}
+
+/// Claims carried by a signed token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedInsuranceToken {
+    pub risk_score: u32,
+    pub entropy_count: usize,
+    pub bio_proof: u64,
+    pub hash_algorithm: String,
+    /// Seconds since the Unix epoch
+    pub issued_at: u64,
+    pub nonce: [u8; NONCE_LEN],
+    pub validity: Validity,
+    /// Tenant key behind `bio_proof`, see the `tenant` module
+    pub key_id: Option<String>,
+    pub signature: [u8; 64],
+}
+
+impl SignedInsuranceToken {
+    /// The signed part of the token
+    fn payload(&self) -> String {
+        let bound = |b: Option<u64>| b.map_or_else(|| "-".to_string(), |t| t.to_string());
+        let mut payload = format!(
+            "RISK_SCORE:{}:ENTROPY:{}:BIO_PROOF:{}:ALGORITHM:{}:ISSUED_AT:{}:NONCE:{}:VALID_FROM:{}:VALID_UNTIL:{}",
+            self.risk_score,
+            self.entropy_count,
+            self.bio_proof,
+            self.hash_algorithm,
+            self.issued_at,
+            hex::encode(self.nonce),
+            bound(self.validity.valid_from),
+            bound(self.validity.valid_until)
+        );
+        if let Some(key_id) = &self.key_id {
+            payload.push_str(":KEY_ID:");
+            payload.push_str(key_id);
+        }
+        payload
+    }
+
+    /// SHA-256 of the token text; the identifier a `RevocationList` stores
+    pub fn token_hash(&self) -> String {
+        format!("{:x}", Sha256::digest(self.to_string().as_bytes()))
+    }
+
+    /// Parse the text form without checking the signature
+    fn parse(token: &str) -> Result<Self, TokenError> {
+        let malformed = |what: &str| TokenError::Malformed(what.to_string());
+
+        let mut parts = token.split('.');
+        let (Some(PREFIX), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(malformed("expected SIGNED_INSURANCE_TOKEN.<payload>.<signature>"));
+        };
+
+        let mut fields: Vec<&str> = payload.split(':').collect();
+        let key_id = match fields.as_slice() {
+            [.., "KEY_ID", key_id] if fields.len() == 18 => Some(key_id.to_string()),
+            _ => None,
+        };
+        if key_id.is_some() {
+            fields.truncate(16);
+        }
+        let [
+            "RISK_SCORE", risk_score,
+            "ENTROPY", entropy_count,
+            "BIO_PROOF", bio_proof,
+            "ALGORITHM", hash_algorithm,
+            "ISSUED_AT", issued_at,
+            "NONCE", nonce,
+            "VALID_FROM", valid_from,
+            "VALID_UNTIL", valid_until,
+        ] = fields.as_slice()
+        else {
+            return Err(malformed("unexpected payload fields"));
+        };
+
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        hex::decode_to_slice(nonce, &mut nonce_bytes).map_err(|_| malformed("invalid nonce"))?;
+        let mut signature_bytes = [0u8; 64];
+        hex::decode_to_slice(signature, &mut signature_bytes).map_err(|_| malformed("invalid signature encoding"))?;
+
+        let bound = |text: &str, what: &str| match text {
+            "-" => Ok(None),
+            t => t.parse().map(Some).map_err(|_| malformed(what)),
+        };
+
+        let token = Self {
+            risk_score: risk_score.parse().map_err(|_| malformed("invalid risk score"))?,
+            entropy_count: entropy_count.parse().map_err(|_| malformed("invalid entropy count"))?,
+            bio_proof: bio_proof.parse().map_err(|_| malformed("invalid bio-proof"))?,
+            hash_algorithm: hash_algorithm.to_string(),
+            issued_at: issued_at.parse().map_err(|_| malformed("invalid timestamp"))?,
+            nonce: nonce_bytes,
+            validity: Validity {
+                valid_from: bound(valid_from, "invalid valid_from")?,
+                valid_until: bound(valid_until, "invalid valid_until")?,
+            },
+            key_id,
+            signature: signature_bytes,
+        };
+
+        // Reject alternative spellings of the same claims (leading zeros,
+        // uppercase hex) so a token has exactly one valid text form
+        if token.payload() != *payload || hex::encode(token.signature) != signature {
+            return Err(malformed("non-canonical encoding"));
+        }
+        Ok(token)
+    }
+}
+
+impl fmt::Display for SignedInsuranceToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", PREFIX, self.payload(), hex::encode(self.signature))
+    }
+}
+
+/// Check `token` against the issuer's public key and the current time and
+/// return its claims
+pub fn verify_insurance_token(public_key: &[u8; 32], token: &str) -> Result<SignedInsuranceToken, TokenError> {
+    verify_insurance_token_at(public_key, token, unix_now(), None)
+}
+
+/// Like `verify_insurance_token`, at an explicit time and also rejecting
+/// tokens in `revocations`
+pub fn verify_insurance_token_at(
+    public_key: &[u8; 32],
+    token: &str,
+    now: u64,
+    revocations: Option<&RevocationList>,
+) -> Result<SignedInsuranceToken, TokenError> {
+    let key = VerifyingKey::from_bytes(public_key).map_err(|_| TokenError::InvalidKey)?;
+    let token = SignedInsuranceToken::parse(token)?;
+    let signature = Signature::from_bytes(&token.signature);
+    key.verify(token.payload().as_bytes(), &signature)
+        .map_err(|_| TokenError::BadSignature)?;
+    token.validity.check(now)?;
+    if revocations.is_some_and(|list| list.is_revoked(&token.token_hash())) {
+        return Err(TokenError::Revoked);
+    }
+    Ok(token)
+}
+
+/// Token hashes withdrawn before their validity ends. A persistent list is
+/// a text file with one hex hash per line, appended and synced on revoke.
+#[derive(Debug, Default)]
+pub struct RevocationList {
+    revoked: HashSet<String>,
+    file: Option<File>,
+}
+
+impl RevocationList {
+    /// An empty list that is not persisted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open or create the list file at `path`
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).read(true).append(true).open(path)?;
+        let mut revoked = HashSet::new();
+        for line in BufReader::new(&file).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                revoked.insert(line.trim().to_string());
+            }
+        }
+        Ok(Self { revoked, file: Some(file) })
+    }
+
+    /// Revoke `token`. Revoking the same token again is a no-op.
+    pub fn revoke(&mut self, token: &SignedInsuranceToken) -> io::Result<()> {
+        let hash = token.token_hash();
+        if self.revoked.contains(&hash) {
+            return Ok(());
+        }
+        if let Some(file) = &mut self.file {
+            writeln!(file, "{}", hash)?;
+            file.sync_data()?;
+        }
+        self.revoked.insert(hash);
+        Ok(())
+    }
+
+    pub fn is_revoked(&self, token_hash: &str) -> bool {
+        self.revoked.contains(token_hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.revoked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.revoked.is_empty()
+    }
+}
+
+/// Why a token could not be issued or verified
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    /// The result does not qualify for an insurance token
+    NotInsurable,
+    /// The token text does not follow the signed token format
+    Malformed(String),
+    /// The public key is not a valid Ed25519 point
+    InvalidKey,
+    /// The signature does not match the payload and key
+    BadSignature,
+    /// The OS random source failed
+    Nonce(String),
+    /// Verified before `valid_from`
+    NotYetValid { valid_from: u64 },
+    /// Verified after `valid_until`
+    Expired { valid_until: u64 },
+    /// The token is on the revocation list
+    Revoked,
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenError::NotInsurable => f.write_str("Result is not insurable"),
+            TokenError::Malformed(what) => write!(f, "Malformed insurance token: {}", what),
+            TokenError::InvalidKey => f.write_str("Invalid Ed25519 public key"),
+            TokenError::BadSignature => f.write_str("Insurance token signature does not verify"),
+            TokenError::Nonce(e) => write!(f, "Could not generate token nonce: {}", e),
+            TokenError::NotYetValid { valid_from } => write!(f, "Insurance token is not valid before {}", valid_from),
+            TokenError::Expired { valid_until } => write!(f, "Insurance token expired at {}", valid_until),
+            TokenError::Revoked => f.write_str("Insurance token has been revoked"),
+        }
+    }
+}
+
+impl std::error::Error for TokenError {}
@@ -0,0 +1,198 @@
+//! JavaScript bindings (`wasm` feature)
+//! Lets browser dashboards recompute results and check signed tokens
+//! client-side instead of trusting the server's verdict. Build with:
+//!
+//! ```text
+//! wasm-pack build src/deployable --target web --no-default-features --features wasm
+//! ```
+//!
+//! Add `sha3` or `blake3` to the feature list to ship those backends; the
+//! default build only carries SHA-256 and SHA-512. Results cross the boundary
+//! as the JSON from `RiskResult::to_json`, except that the u64 `bio_proof`
+//! travels as a decimal string: a JavaScript number rounds it above 2^53.
+//! Convert with `BigInt(result.bio_proof)`. Nothing here panics on bad input:
+//! unknown algorithms, zero iterations and malformed results or tokens
+//! surface as `Error`s or `{ valid: false }`.
+
+use crate::token::verify_insurance_token_at;
+use crate::{HashAlgorithm, RiskCalculator, RiskError, RiskResult};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export type InputDerivation =
+  | { scheme: "raw" }
+  | { scheme: "framed"; domain: string; session_id: string | null };
+
+export interface RiskResult {
+  schema_version: number;
+  status: "INSURABLE" | "UNINSURABLE";
+  risk_score: number;
+  entropy_count: number;
+  all_hashes_match: boolean;
+  hashes: string[];
+  iteration_count: number;
+  /** u64 as a decimal string */
+  bio_proof: string;
+  hash_algorithm: string;
+  merkle_root: string;
+  derivation: InputDerivation;
+  tenant_key_id?: string;
+}
+
+export interface TokenClaims {
+  risk_score: number;
+  entropy_count: number;
+  /** u64 as a decimal string */
+  bio_proof: string;
+  hash_algorithm: string;
+  issued_at: number;
+  valid_from: number | null;
+  valid_until: number | null;
+  token_hash: string;
+}
+
+export interface TokenVerification {
+  valid: boolean;
+  claims?: TokenClaims;
+  /** Why the token was rejected, when invalid */
+  error?: string;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "RiskResult")]
+    pub type JsRiskResult;
+
+    #[wasm_bindgen(typescript_type = "TokenVerification")]
+    pub type JsTokenVerification;
+
+    #[wasm_bindgen(js_namespace = JSON, js_name = parse)]
+    fn json_parse(text: &str) -> JsValue;
+
+    #[wasm_bindgen(js_namespace = JSON, js_name = stringify)]
+    fn json_stringify(value: &JsValue) -> Option<String>;
+
+    #[wasm_bindgen(js_namespace = Date, js_name = now)]
+    fn date_now() -> f64;
+}
+
+/// Run the N-iteration check on `input`. Divergent inputs return their
+/// UNINSURABLE result rather than throwing. `algorithm` defaults to
+/// `"sha256"` and `iterations` to 10.
+#[wasm_bindgen(js_name = calculateRisk)]
+pub fn calculate_risk(
+    input: &str,
+    algorithm: Option<String>,
+    iterations: Option<u32>,
+    session_id: Option<String>,
+) -> Result<JsRiskResult, JsError> {
+    let mut builder = RiskCalculator::builder().hash_algorithm(algorithm_named(algorithm.as_deref())?);
+    if let Some(iterations) = iterations {
+        if iterations == 0 {
+            return Err(JsError::new("iterations must be at least 1"));
+        }
+        builder = builder.iterations(iterations as usize);
+    }
+    if let Some(session_id) = session_id {
+        builder = builder.session_id(session_id);
+    }
+
+    let result = builder
+        .build()
+        .calculate_risk(input)
+        .unwrap_or_else(RiskError::into_result);
+    Ok(json_parse(&result_json(&result)).unchecked_into())
+}
+
+/// The unsigned `INSURANCE_TOKEN_<algorithm>_<hex>` for an insurable
+/// result from `calculateRisk`, or `undefined`
+#[wasm_bindgen(js_name = issueInsuranceToken)]
+pub fn issue_insurance_token(result: &JsRiskResult) -> Result<Option<String>, JsError> {
+    let json = json_stringify(result).ok_or_else(|| JsError::new("result is not JSON-serializable"))?;
+    let result = parse_result(&json).map_err(|e| JsError::new(&e))?;
+    let calculator = RiskCalculator::builder()
+        .hash_algorithm(algorithm_named(Some(&result.hash_algorithm))?)
+        .build();
+    Ok(calculator.issue_insurance_token(&result))
+}
+
+/// Check a signed insurance token against the issuer's hex-encoded Ed25519
+/// public key at the browser's current time, without throwing
+#[wasm_bindgen(js_name = verifyToken)]
+pub fn verify_token(public_key: &str, token: &str) -> JsTokenVerification {
+    let mut key = [0u8; 32];
+    let verification = match hex::decode_to_slice(public_key, &mut key) {
+        Err(_) => serde_json::json!({ "valid": false, "error": "public key must be 64 hex characters" }),
+        Ok(()) => match verify_insurance_token_at(&key, token, unix_now(), None) {
+            Ok(claims) => serde_json::json!({
+                "valid": true,
+                "claims": {
+                    "risk_score": claims.risk_score,
+                    "entropy_count": claims.entropy_count,
+                    "bio_proof": claims.bio_proof.to_string(),
+                    "hash_algorithm": claims.hash_algorithm,
+                    "issued_at": claims.issued_at,
+                    "valid_from": claims.validity.valid_from,
+                    "valid_until": claims.validity.valid_until,
+                    "token_hash": claims.token_hash(),
+                },
+            }),
+            Err(e) => serde_json::json!({ "valid": false, "error": e.to_string() }),
+        },
+    };
+    json_parse(&verification.to_string()).unchecked_into()
+}
+
+/// `RiskResult::to_json` with `bio_proof` as a decimal string
+fn result_json(result: &RiskResult) -> String {
+    let mut json: Value = serde_json::from_str(&result.to_json()).expect("results serialize to JSON");
+    json["bio_proof"] = Value::String(result.bio_proof.to_string());
+    json.to_string()
+}
+
+/// Inverse of `result_json`
+fn parse_result(json: &str) -> Result<RiskResult, String> {
+    let mut json: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let bio_proof = json["bio_proof"]
+        .as_str()
+        .and_then(|proof| proof.parse::<u64>().ok())
+        .ok_or("bio_proof must be a u64 decimal string")?;
+    json["bio_proof"] = Value::from(bio_proof);
+    serde_json::from_value(json).map_err(|e| e.to_string())
+}
+
+fn algorithm_named(name: Option<&str>) -> Result<HashAlgorithm, JsError> {
+    match name {
+        None => Ok(HashAlgorithm::default()),
+        Some(name) => HashAlgorithm::from_name(name)
+            .ok_or_else(|| JsError::new(&format!("hash algorithm '{}' is not available in this build", name))),
+    }
+}
+
+/// `SystemTime::now` is unsupported on wasm32-unknown-unknown
+fn unix_now() -> u64 {
+    (date_now() / 1000.0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bio_proof_keeps_every_digit() {
+        let mut result = RiskCalculator::new().calculate_risk("0xDEADBEEF").unwrap();
+        result.bio_proof = u64::MAX - 1;
+        let json = result_json(&result);
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["bio_proof"], "18446744073709551614");
+        assert_eq!(parse_result(&json).unwrap(), result);
+
+        // A number that went through a JavaScript double is refused, not rounded
+        let rounded = json.replace("\"18446744073709551614\"", "18446744073709552000");
+        assert!(parse_result(&rounded).is_err());
+    }
+}
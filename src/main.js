@@ -191,7 +191,7 @@ document.addEventListener('DOMContentLoaded', async () => {
             `;
             showToast('TOON parsed successfully', 'success');
         } catch (error) {
-            toonOutput.innerHTML = `<div class="text-red-400 text-xs">Error: ${escapeHtml(error)}</div>`;
+            toonOutput.innerHTML = `<pre class="text-red-400 text-xs whitespace-pre overflow-auto">${escapeHtml(error)}</pre>`;
             showToast('TOON parsing failed', 'error');
         } finally {
             parseToonBtn.disabled = false;
@@ -338,7 +338,7 @@ document.addEventListener('DOMContentLoaded', async () => {
                                 <div class="text-white/80">${summary.parties?.join(', ') || 'N/A'}</div>
                             </div>
                             <div>
-                                <div class="text-miami-red font-semibold mb-1">Key Obligations (${summary.key_obligations?.length || 0}/10):</div>
+                                <div class="text-miami-red font-semibold mb-1">Key Obligations (${summary.key_obligations?.length || 0}/${summary.pagination?.obligations.total ?? 0}):</div>
                                 <div class="space-y-1 max-h-32 overflow-y-auto">
                                     ${(summary.key_obligations || []).map((ob, i) => `
                                         <div class="text-white/70 text-xs pl-2 border-l-2 border-miami-red/30">
@@ -348,7 +348,7 @@ document.addEventListener('DOMContentLoaded', async () => {
                                 </div>
                             </div>
                             <div>
-                                <div class="text-miami-red font-semibold mb-1">Risk Flags (${summary.risk_flags?.length || 0}/20):</div>
+                                <div class="text-miami-red font-semibold mb-1">Risk Flags (${summary.risk_flags?.length || 0}/${summary.pagination?.risk_flags.total ?? 0}):</div>
                                 <div class="space-y-1 max-h-32 overflow-y-auto">
                                     ${(summary.risk_flags || []).map((risk, i) => {
                                         const severityColor = {
@@ -2,38 +2,138 @@
 //! Pure Rust, zero OS commands or network I/O. Mirrors the deterministic backend in src-tauri.
 
 use tauri::Manager;
+use std::ops::ControlFlow;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::Mutex;
 use serde::{Deserialize, Serialize};
 
 // Reuse the in-process cores from the src-tauri crate via explicit paths.
 #[path = "../src-tauri/src/mamba_core.rs"]
 mod mamba_core;
+#[path = "../src-tauri/src/mamba_audit.rs"]
+mod mamba_audit;
+#[path = "../src-tauri/src/mamba_tokenizer.rs"]
+mod mamba_tokenizer;
+#[path = "../src-tauri/src/mamba_ssm.rs"]
+mod mamba_ssm;
+#[path = "../src-tauri/src/mamba_block.rs"]
+mod mamba_block;
+#[path = "../src-tauri/src/mamba_weights.rs"]
+mod mamba_weights;
+#[path = "../src-tauri/src/mamba_quant.rs"]
+mod mamba_quant;
+#[path = "../src-tauri/src/mamba_fp.rs"]
+mod mamba_fp;
+#[path = "../src-tauri/src/mamba_lazy.rs"]
+mod mamba_lazy;
+#[path = "../src-tauri/src/mamba_sampling.rs"]
+mod mamba_sampling;
+#[path = "../src-tauri/src/mamba_session.rs"]
+mod mamba_session;
+#[path = "../src-tauri/src/mamba_constraints.rs"]
+mod mamba_constraints;
+#[path = "../src-tauri/src/mamba_stability.rs"]
+mod mamba_stability;
 #[path = "../src-tauri/src/fhe_core.rs"]
 mod fhe_core;
+#[path = "../src-tauri/src/fhe_batch.rs"]
+mod fhe_batch;
+#[path = "../src-tauri/src/fhe_rlwe.rs"]
+mod fhe_rlwe;
+#[path = "../src-tauri/src/fhe_threshold.rs"]
+mod fhe_threshold;
+#[path = "../src-tauri/src/fhe_bench.rs"]
+mod fhe_bench;
+#[path = "../src-tauri/src/fhe_mnemonic.rs"]
+mod fhe_mnemonic;
+#[path = "../src-tauri/src/fhe_auth.rs"]
+mod fhe_auth;
+#[path = "../src-tauri/src/fhe_stream.rs"]
+mod fhe_stream;
+#[path = "../src-tauri/src/encrypted_risk.rs"]
+mod encrypted_risk;
 #[path = "../src-tauri/src/contract_analyzer.rs"]
 mod contract_analyzer;
+#[path = "../src-tauri/src/contract_amounts.rs"]
+mod contract_amounts;
+#[path = "../src-tauri/src/contract_anonymize.rs"]
+mod contract_anonymize;
+#[path = "../src-tauri/src/contract_bundle.rs"]
+mod contract_bundle;
+#[path = "../src-tauri/src/contract_clauses.rs"]
+mod contract_clauses;
+#[path = "../src-tauri/src/contract_dates.rs"]
+mod contract_dates;
+#[path = "../src-tauri/src/contract_diff.rs"]
+mod contract_diff;
+#[path = "../src-tauri/src/contract_evidence.rs"]
+mod contract_evidence;
+#[path = "../src-tauri/src/contract_jurisdictions.rs"]
+mod contract_jurisdictions;
+#[path = "../src-tauri/src/contract_language.rs"]
+mod contract_language;
+#[path = "../src-tauri/src/contract_limits.rs"]
+mod contract_limits;
+#[path = "../src-tauri/src/contract_provisions.rs"]
+mod contract_provisions;
+#[path = "../src-tauri/src/contract_rules.rs"]
+mod contract_rules;
+#[path = "../src-tauri/src/contract_scoring.rs"]
+mod contract_scoring;
+#[path = "../src-tauri/src/contract_sealed.rs"]
+mod contract_sealed;
+#[path = "../src-tauri/src/contract_timeline.rs"]
+mod contract_timeline;
+#[cfg(feature = "ingest")]
+#[path = "../src-tauri/src/ingest.rs"]
+mod ingest;
 #[path = "../src-tauri/src/axiom_determinist/mod.rs"]
 mod axiom_determinist;
 
-use mamba_core::DeterministicMambaCore;
-use fhe_core::DeoxysFHE;
-use contract_analyzer::ContractAnalyzer;
+use mamba_audit::InferenceCertificate;
+use mamba_core::{BatchForwardResult, DeterministicMambaCore, GenerateResult, GeneratedToken};
+use mamba_constraints::LogitBiasProcessor;
+use mamba_lazy::MemoryReport;
+use mamba_sampling::Sampling;
+use mamba_session::{MambaSession, SessionState};
+use mamba_stability::StabilityReport;
+use fhe_core::{Ciphertext, DeoxysFHE, FheParams, ParamPreset, PublicKey};
+use fhe_auth::AuthenticatedCiphertext;
+use fhe_bench::FheBenchReport;
+use fhe_batch::PackedCiphertext;
+use fhe_rlwe::DeoxysRlwe;
+use fhe_threshold::{combine_partials, KeyShare, PartialDecryption};
+use contract_analyzer::{ContractAnalyzer, Obligation, RiskFlag};
+use contract_anonymize::{MappingSeal, OrganizationKey};
+use contract_bundle::{ContractBundle, ContractDoc};
+use contract_dates::DateOrder;
+use contract_diff::ContractDiff;
+use contract_language::Language;
+use contract_limits::{AnalysisLimits, Page};
+use contract_rules::RulePack;
+use contract_scoring::ContractCertificate;
+use contract_sealed::SealedSummary;
+use axiom_determinist::constraints::SterilizationConfig;
 use axiom_determinist::orchestrator::Orchestrator;
 
 use toon_rs::ToonParser;
-use axiom_risk_calculator::RiskCalculator;
+use axiom_risk_calculator::{RiskCalculator, RiskError};
 
 #[derive(Clone)]
 struct AppState {
     risk_calculator: Arc<Mutex<RiskCalculator>>,
     axiom_determinist: Arc<Mutex<Orchestrator>>,
+    /// Cancels the latest `stream_mamba_text` run when set
+    mamba_cancel: Arc<Mutex<Arc<AtomicBool>>>,
+    /// Hidden state carried between `extend_mamba_session` calls
+    mamba_session: Arc<Mutex<Option<MambaSession>>>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct MambaModelResult {
     output: String,
-    metrics: Option<serde_json::Value>,
+    metrics: Option<StabilityReport>,
     risk_score: Option<u32>,
 }
 
@@ -43,19 +143,61 @@ struct FHEResult {
     keys: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct SharedFheKey {
+    public_key: PublicKey,
+    shares: Vec<KeyShare>,
+}
+
 #[tauri::command]
 async fn parse_toon_data(data: String) -> Result<String, String> {
-    let parser = ToonParser::new(&data);
+    // JSON-looking input is an error here, not a panic that would abort
+    // the app
+    let parser = ToonParser::try_new(&data).map_err(|e| format!("TOON parsing error:\n{}", e.render(&data)))?;
     match parser.parse() {
         Ok(result) => Ok(format!("{:?}", result)),
+        Err(e) => Err(format!("TOON parsing error:\n{}", e.render(&data))),
+    }
+}
+
+/// What `parse_toon_file` reports instead of the document itself
+#[derive(Serialize, Deserialize)]
+struct ToonFileSummary {
+    keys: usize,
+    canonical_hash: String,
+}
+
+#[tauri::command]
+async fn parse_toon_file(app: tauri::AppHandle, path: String) -> Result<ToonFileSummary, String> {
+    // Memory-mapped, and only a summary comes back, so large datasets never
+    // cross the IPC boundary
+    let path = app_data_file(&app, &path)?;
+    match toon_rs::parse_file(&path) {
+        Ok(document) => Ok(ToonFileSummary { keys: document.len(), canonical_hash: document.canonical_hash() }),
         Err(e) => Err(format!("TOON parsing error: {}", e)),
     }
 }
 
+/// `path`, relative to the app's data directory, resolved and checked to
+/// stay inside it; the frontend cannot name arbitrary files
+fn app_data_file(app: &tauri::AppHandle, path: &str) -> Result<std::path::PathBuf, String> {
+    let root = app.path().app_data_dir().map_err(|e| format!("No app data directory: {}", e))?;
+    let root = root.canonicalize().map_err(|e| format!("Cannot open {}: {}", root.display(), e))?;
+    // Symlinks and `..` are resolved before the check
+    let resolved = root.join(path).canonicalize().map_err(|e| format!("Cannot open {}: {}", path, e))?;
+    if !resolved.starts_with(&root) {
+        return Err(format!("{} is outside the app data directory", path));
+    }
+    Ok(resolved)
+}
+
 #[tauri::command]
 async fn calculate_risk(state: tauri::State<'_, AppState>, input: String) -> Result<String, String> {
     let calculator = state.risk_calculator.lock().await;
-    let result = calculator.calculate_risk(&input);
+    // A divergent input is reported through its UNINSURABLE boot log
+    let result = calculator
+        .calculate_risk(&input)
+        .unwrap_or_else(RiskError::into_result);
     Ok(result.to_boot_log())
 }
 
@@ -79,11 +221,188 @@ async fn run_mamba_model(
     })
 }
 
+#[tauri::command]
+async fn seal_mamba_inference(
+    state: tauri::State<'_, AppState>,
+    prompt: String,
+    state_dim: u32,
+    input_dim: u32,
+) -> Result<InferenceCertificate, String> {
+    // Every iteration reruns the sealed pass, so the resulting token
+    // vouches for this model build on this prompt
+    let calculator = state.risk_calculator.lock().await.clone();
+    tokio::task::spawn_blocking(move || {
+        let mamba = DeterministicMambaCore::new(input_dim, state_dim, 16);
+        mamba_audit::certify(&mamba, &prompt, &calculator)
+    })
+    .await
+    .map_err(|e| format!("Mamba sealing failed: {}", e))
+}
+
+#[tauri::command]
+async fn mamba_stability_report(state_dim: u32, input_dim: u32, delta: Option<f32>) -> Result<StabilityReport, String> {
+    // Discretized norms at `delta`, or at each channel's zero-input step
+    if delta.is_some_and(|delta| delta <= 0.0 || !delta.is_finite()) {
+        return Err(format!("Step delta must be positive and finite. Got: {:?}", delta));
+    }
+    Ok(DeterministicMambaCore::new(input_dim, state_dim, 16).stability_report(delta))
+}
+
+#[tauri::command]
+async fn run_mamba_batch(prompts: Vec<String>, state_dim: u32, input_dim: u32) -> Result<BatchForwardResult, String> {
+    // One IPC call for a whole evaluation suite; items come back in prompt
+    // order whatever thread ran them
+    tokio::task::spawn_blocking(move || {
+        let mamba = DeterministicMambaCore::new(input_dim, state_dim, 16);
+        let prompts: Vec<&str> = prompts.iter().map(String::as_str).collect();
+        mamba.forward_batch(&prompts)
+    })
+    .await
+    .map_err(|e| format!("Mamba batch failed: {}", e))
+}
+
+/// Model for the generation commands; `sterilize` bans the
+/// placeholder strings of the default sterilization config while decoding
+fn mamba_generator(input_dim: u32, state_dim: u32, sterilize: bool) -> DeterministicMambaCore {
+    let mamba = DeterministicMambaCore::new(input_dim, state_dim, 16);
+    if !sterilize {
+        return mamba;
+    }
+    let processor = LogitBiasProcessor::from_config(&SterilizationConfig::default(), mamba.tokenizer());
+    mamba.with_logits_processor(Arc::new(processor))
+}
+
+#[tauri::command]
+async fn generate_mamba_text(
+    prompt: String,
+    max_tokens: usize,
+    state_dim: u32,
+    input_dim: u32,
+    temperature: Option<f64>,
+    seed: Option<u64>,
+    sterilize: Option<bool>,
+) -> Result<GenerateResult, String> {
+    // Greedy unless a temperature and seed are given; either way the same
+    // request always yields the same text
+    let sampling = Sampling::new(temperature.unwrap_or(0.0), seed)?;
+    tokio::task::spawn_blocking(move || {
+        mamba_generator(input_dim, state_dim, sterilize.unwrap_or(false)).generate_with(&prompt, max_tokens, sampling)
+    })
+    .await
+    .map_err(|e| format!("Mamba generation failed: {}", e))
+}
+
+#[tauri::command]
+async fn stream_mamba_text(
+    state: tauri::State<'_, AppState>,
+    prompt: String,
+    max_tokens: usize,
+    state_dim: u32,
+    input_dim: u32,
+    temperature: Option<f64>,
+    seed: Option<u64>,
+    sterilize: Option<bool>,
+    on_token: tauri::ipc::Channel<GeneratedToken>,
+) -> Result<GenerateResult, String> {
+    // Tokens go out over the channel as they are decoded; a closed channel
+    // or cancel_mamba_generation stops the run
+    let sampling = Sampling::new(temperature.unwrap_or(0.0), seed)?;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    *state.mamba_cancel.lock().await = cancelled.clone();
+    tokio::task::spawn_blocking(move || {
+        let mamba = mamba_generator(input_dim, state_dim, sterilize.unwrap_or(false));
+        mamba.generate_stream_with(&prompt, max_tokens, sampling, |token| {
+            if cancelled.load(Ordering::Relaxed) || on_token.send(token.clone()).is_err() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("Mamba generation failed: {}", e))
+}
+
+#[tauri::command]
+async fn verify_mamba_bit_exactness() -> Result<String, String> {
+    // Hashes a strict-mode reference pass; a mismatch means this build
+    // does not reproduce other platforms bit for bit
+    tokio::task::spawn_blocking(mamba_core::verify_bit_exactness)
+        .await
+        .map_err(|e| format!("Mamba self-test failed: {}", e))?
+}
+
+#[tauri::command]
+async fn cancel_mamba_generation(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.mamba_cancel.lock().await.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+#[tauri::command]
+async fn extend_mamba_session(
+    state: tauri::State<'_, AppState>,
+    text: String,
+    max_tokens: usize,
+    state_dim: u32,
+    input_dim: u32,
+) -> Result<GenerateResult, String> {
+    // Only the new text is scanned; a different model shape starts over
+    let mut slot = state.mamba_session.lock().await;
+    let session = slot
+        .take()
+        .filter(|s| s.model().config().d_model == input_dim as usize && s.model().config().d_state == state_dim as usize)
+        .unwrap_or_else(|| MambaSession::new(Arc::new(DeterministicMambaCore::new(input_dim, state_dim, 16))));
+    let (session, result) = tokio::task::spawn_blocking(move || {
+        let mut session = session;
+        session.extend(&text);
+        let result = session.generate(max_tokens);
+        (session, result)
+    })
+    .await
+    .map_err(|e| format!("Mamba session failed: {}", e))?;
+    *slot = Some(session);
+    Ok(result)
+}
+
+#[tauri::command]
+async fn mamba_memory_report(state: tauri::State<'_, AppState>) -> Result<Option<MemoryReport>, String> {
+    // Weight memory of the session model, if a session is open
+    Ok(state.mamba_session.lock().await.as_ref().map(|session| session.model().memory_report()))
+}
+
+#[tauri::command]
+async fn reset_mamba_session(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    *state.mamba_session.lock().await = None;
+    Ok(())
+}
+
+#[tauri::command]
+async fn save_mamba_session(state: tauri::State<'_, AppState>) -> Result<Option<SessionState>, String> {
+    Ok(state.mamba_session.lock().await.as_ref().map(MambaSession::save_state))
+}
+
+#[tauri::command]
+async fn load_mamba_session(
+    state: tauri::State<'_, AppState>,
+    saved: SessionState,
+    state_dim: u32,
+    input_dim: u32,
+) -> Result<(), String> {
+    let model = Arc::new(DeterministicMambaCore::new(input_dim, state_dim, 16));
+    *state.mamba_session.lock().await = Some(MambaSession::load_state(model, saved)?);
+    Ok(())
+}
+
 #[tauri::command]
 async fn encrypt_fhe(message: i32) -> Result<FHEResult, String> {
     // In-process Deoxys FHE encryption - Pure Rust LWE implementation
     let fhe = DeoxysFHE::new(None);
-    let ciphertext = fhe.encrypt(message)?;
+    // frozen-seed builds keep ciphertexts reproducible at the cost of
+    // semantic security; see DeoxysFHE::encrypt_deterministic
+    #[cfg(feature = "frozen-seed")]
+    let ciphertext = fhe.encrypt_deterministic(message)?;
+    #[cfg(not(feature = "frozen-seed"))]
+    let ciphertext = fhe.encrypt_with_rng(message, &mut rand_core::OsRng)?;
     let (ciphertext_str, keys_str) = fhe.serialize_ciphertext(ciphertext);
     
     Ok(FHEResult {
@@ -97,15 +416,364 @@ async fn decrypt_fhe(ciphertext: String, keys: String) -> Result<i32, String> {
     // In-process Deoxys FHE decryption - Pure Rust LWE implementation
     let fhe = DeoxysFHE::new(None);
     let ct = fhe.deserialize_ciphertext(&ciphertext, &keys)?;
-    let plaintext = fhe.decrypt(ct)?;
+    let plaintext = fhe.decrypt_checked(ct).map_err(|e| e.to_string())?;
     Ok(plaintext)
 }
 
 #[tauri::command]
-async fn process_contract(contract_text: String) -> Result<serde_json::Value, String> {
+async fn encrypt_text_fhe(text: String) -> Result<Vec<Ciphertext>, String> {
+    // Contract text and TOON payloads, one ciphertext per plaintext limb
+    let fhe = DeoxysFHE::new(None);
+    #[cfg(feature = "frozen-seed")]
+    let ciphertexts = fhe.encrypt_bytes_deterministic(text.as_bytes())?;
+    #[cfg(not(feature = "frozen-seed"))]
+    let ciphertexts = fhe.encrypt_bytes(text.as_bytes(), &mut rand_core::OsRng)?;
+    Ok(ciphertexts)
+}
+
+#[tauri::command]
+async fn decrypt_text_fhe(ciphertexts: Vec<Ciphertext>) -> Result<String, String> {
+    let fhe = DeoxysFHE::new(None);
+    let bytes = fhe.decrypt_bytes(&ciphertexts).map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| format!("Decrypted text is not UTF-8: {}", e))
+}
+
+#[tauri::command]
+async fn encrypt_batch_fhe(values: Vec<i32>) -> Result<PackedCiphertext, String> {
+    // One packed ciphertext with a slot per value
+    let fhe = DeoxysFHE::new(None);
+    let (public, _) = fhe.batch_keys(values.len())?;
+    public.encrypt_batch(&values, &mut rand_core::OsRng)
+}
+
+#[tauri::command]
+async fn decrypt_batch_fhe(ciphertext: PackedCiphertext) -> Result<Vec<i32>, String> {
+    let fhe = DeoxysFHE::new(None);
+    let (_, secret) = fhe.batch_keys(ciphertext.slots())?;
+    secret.decrypt_batch(&ciphertext).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn encrypt_rlwe(message: i32) -> Result<FHEResult, String> {
+    // Ring-LWE: two polynomials per ciphertext, serialized losslessly
+    let fhe = DeoxysRlwe::new(None);
+    #[cfg(feature = "frozen-seed")]
+    let ciphertext = fhe.encrypt_deterministic(message)?;
+    #[cfg(not(feature = "frozen-seed"))]
+    let ciphertext = fhe.encrypt_with_rng(message, &mut rand_core::OsRng)?;
+    let (ciphertext, keys) = fhe.serialize_ciphertext(&ciphertext);
+    Ok(FHEResult { ciphertext, keys })
+}
+
+#[tauri::command]
+async fn decrypt_rlwe(ciphertext: String, keys: String) -> Result<i32, String> {
+    let fhe = DeoxysRlwe::new(None);
+    let ct = fhe.deserialize_ciphertext(&ciphertext, &keys)?;
+    fhe.decrypt_checked(ct).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn split_fhe_key(parties: usize) -> Result<SharedFheKey, String> {
+    // A fresh key dealt as one additive share per component; its secret is
+    // never kept whole, so all of the shares are needed to decrypt
+    let (public_key, shares) = fhe_threshold::generate_shared_key(FheParams::default(), parties, &mut rand_core::OsRng)?;
+    Ok(SharedFheKey { public_key, shares })
+}
+
+#[tauri::command]
+async fn partial_decrypt_fhe(share: KeyShare, ciphertext: Ciphertext) -> Result<PartialDecryption, String> {
+    share.partial_decrypt(&ciphertext, &mut rand_core::OsRng).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn combine_partials_fhe(ciphertext: Ciphertext, partials: Vec<PartialDecryption>) -> Result<i32, String> {
+    combine_partials(&ciphertext, &partials).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn benchmark_fhe(preset: Option<ParamPreset>) -> Result<FheBenchReport, String> {
+    // Timing loops are CPU-bound, so keep them off the async runtime
+    let params = preset.map_or_else(FheParams::default, ParamPreset::params);
+    tokio::task::spawn_blocking(move || DeoxysFHE::with_params(None, params).benchmark())
+        .await
+        .map_err(|e| format!("FHE benchmark failed: {}", e))
+}
+
+#[tauri::command]
+async fn encrypt_fhe_authenticated(message: i32, mnemonic: String) -> Result<AuthenticatedCiphertext, String> {
+    // The MAC key derives from the secret key, so it has to come from the
+    // holder's phrase; under the frozen seed anyone could forge tags
+    let fhe = DeoxysFHE::from_mnemonic(&mnemonic)?;
+    fhe.encrypt_authenticated(message, &mut rand_core::OsRng)
+}
+
+#[tauri::command]
+async fn decrypt_fhe_authenticated(ciphertext: AuthenticatedCiphertext, mnemonic: String) -> Result<i32, String> {
+    let fhe = DeoxysFHE::from_mnemonic(&mnemonic)?;
+    fhe.decrypt_authenticated(&ciphertext).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn encrypt_file_fhe(input_path: String, output_path: String, mnemonic: String) -> Result<u64, String> {
+    // Encrypted at rest under the ring key recovered from the holder's
+    // phrase; key stretching and file I/O stay off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let fhe = DeoxysRlwe::from_mnemonic(&mnemonic)?;
+        let input = std::fs::File::open(&input_path).map_err(|e| format!("Cannot open {}: {}", input_path, e))?;
+        let output = std::fs::File::create(&output_path).map_err(|e| format!("Cannot create {}: {}", output_path, e))?;
+        fhe.encrypt_stream(std::io::BufReader::new(input), std::io::BufWriter::new(output), &mut rand_core::OsRng)
+            .map_err(|e| format!("Encrypting {} failed: {}", input_path, e))
+    })
+    .await
+    .map_err(|e| format!("File encryption failed: {}", e))?
+}
+
+#[tauri::command]
+async fn decrypt_file_fhe(input_path: String, output_path: String, mnemonic: String) -> Result<u64, String> {
+    tokio::task::spawn_blocking(move || {
+        let fhe = DeoxysRlwe::from_mnemonic(&mnemonic)?;
+        let input = std::fs::File::open(&input_path).map_err(|e| format!("Cannot open {}: {}", input_path, e))?;
+        let output = std::fs::File::create(&output_path).map_err(|e| format!("Cannot create {}: {}", output_path, e))?;
+        let result = fhe.decrypt_stream(std::io::BufReader::new(input), std::io::BufWriter::new(output));
+        // Never leave unverified plaintext behind
+        if result.is_err() {
+            let _ = std::fs::remove_file(&output_path);
+        }
+        result.map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("File decryption failed: {}", e))?
+}
+
+#[tauri::command]
+async fn generate_fhe_mnemonic(word_count: Option<usize>) -> Result<String, String> {
+    // Recovery phrase for DeoxysFHE::from_mnemonic; 24 words unless asked
+    fhe_mnemonic::generate_mnemonic(word_count.unwrap_or(24), &mut rand_core::OsRng)
+}
+
+#[tauri::command]
+async fn validate_fhe_mnemonic(phrase: String) -> Result<bool, String> {
+    Ok(fhe_mnemonic::validate_mnemonic(&phrase).is_ok())
+}
+
+#[tauri::command]
+async fn verify_risk_encrypted(state: tauri::State<'_, AppState>, input: String) -> Result<serde_json::Value, String> {
+    // Prover: run the calculator and encrypt its iteration hashes
+    let calculator = state.risk_calculator.lock().await;
+    let result = calculator
+        .calculate_risk(&input)
+        .unwrap_or_else(RiskError::into_result);
+    if result.hashes.is_empty() {
+        return Err("Risk calculator does not retain iteration hashes".to_string());
+    }
+    let fhe = DeoxysFHE::new(None);
+    let digest_bytes = result.hashes[0].len() / 2;
+    let (public, secret) = fhe.batch_keys(encrypted_risk::digest_slots(fhe.params(), digest_bytes))?;
+    let encrypted = encrypted_risk::encrypt_digests(&public, &result.hashes, &mut rand_core::OsRng)?;
+
+    // Verifier: only ever sees ciphertexts and the key holder's zero tests
+    let query = encrypted.zero_test_query(&mut rand_core::OsRng)?;
+    let answer = encrypted_risk::answer_zero_test(&secret, &query).map_err(|e| e.to_string())?;
+    let verdict = query.verdict(&answer)?;
+    Ok(serde_json::json!({
+        "verdict": verdict,
+        "plaintext_entropy_count": result.entropy_count,
+        "matches_plaintext": verdict.entropy_count == result.entropy_count
+    }))
+}
+
+#[tauri::command]
+async fn process_contract(
+    contract_text: String,
+    rule_pack: Option<String>,
+    date_order: Option<DateOrder>,
+    limits: Option<AnalysisLimits>,
+) -> Result<serde_json::Value, String> {
     // In-process contract analysis - Pure Rust DAG pipeline implementation
-    let analyzer = ContractAnalyzer::new(true);
-    Ok(analyzer.analyze_contract(&contract_text))
+    let analyzer = contract_analyzer(rule_pack, date_order, limits)?;
+    Ok(match analyzer.analyze_contract(&contract_text) {
+        Ok(summary) => summary.to_json(),
+        Err(e) => e.to_json(),
+    })
+}
+
+/// An analyzer applying `rule_pack`, if any, with `limits` or the defaults
+fn contract_analyzer(
+    rule_pack: Option<String>,
+    date_order: Option<DateOrder>,
+    limits: Option<AnalysisLimits>,
+) -> Result<ContractAnalyzer, String> {
+    let mut analyzer = ContractAnalyzer::new(true)
+        .with_date_order(date_order.unwrap_or_default())
+        .with_limits(limits.unwrap_or_default());
+    if let Some(rule_pack) = rule_pack {
+        let rules = RulePack::from_json(&rule_pack).map_err(|e| e.to_string())?;
+        analyzer = analyzer.with_rules(rules).map_err(|e| e.to_string())?;
+    }
+    Ok(analyzer)
+}
+
+#[tauri::command]
+async fn contract_obligations_page(
+    contract_text: String,
+    offset: usize,
+    limit: usize,
+    rule_pack: Option<String>,
+    date_order: Option<DateOrder>,
+) -> Result<Page<Obligation>, String> {
+    // Pass the rule pack and date order the summary was made with, or the
+    // page belongs to a different analysis
+    let analyzer = contract_analyzer(rule_pack, date_order, None)?;
+    analyzer.obligations_page(&contract_text, offset, limit).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn contract_risk_flags_page(
+    contract_text: String,
+    offset: usize,
+    limit: usize,
+    rule_pack: Option<String>,
+    date_order: Option<DateOrder>,
+) -> Result<Page<RiskFlag>, String> {
+    let analyzer = contract_analyzer(rule_pack, date_order, None)?;
+    analyzer.risk_flags_page(&contract_text, offset, limit).map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "ingest")]
+#[tauri::command]
+async fn process_contract_file(
+    file_name: String,
+    bytes: Vec<u8>,
+    rule_pack: Option<String>,
+    date_order: Option<DateOrder>,
+    limits: Option<AnalysisLimits>,
+) -> Result<serde_json::Value, String> {
+    // Extract the text of a PDF, DOCX, HTML or text file, then analyze it
+    // as process_contract does; evidence offsets index `document.text`
+    let document = ingest::ingest(&bytes, Some(&file_name)).map_err(|e| e.to_string())?;
+    let analyzer = contract_analyzer(rule_pack, date_order, limits)?;
+    let mut response = match analyzer.analyze_contract(&document.text) {
+        Ok(summary) => summary.to_json(),
+        Err(e) => e.to_json(),
+    };
+    response["document"] = serde_json::to_value(&document).map_err(|e| e.to_string())?;
+    Ok(response)
+}
+
+#[cfg(not(feature = "ingest"))]
+#[tauri::command]
+async fn process_contract_file(
+    file_name: String,
+    bytes: Vec<u8>,
+    rule_pack: Option<String>,
+    date_order: Option<DateOrder>,
+    limits: Option<AnalysisLimits>,
+) -> Result<serde_json::Value, String> {
+    let _ = (bytes, rule_pack, date_order, limits);
+    Err(format!("Cannot read {}: built without the ingest feature", file_name))
+}
+
+#[tauri::command]
+async fn compare_contracts(
+    old_text: String,
+    new_text: String,
+    date_order: Option<DateOrder>,
+) -> Result<ContractDiff, String> {
+    let analyzer = ContractAnalyzer::new(true).with_date_order(date_order.unwrap_or_default());
+    analyzer.compare(&old_text, &new_text).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn export_contract_timeline(
+    contract_text: String,
+    calendar_name: String,
+    date_order: Option<DateOrder>,
+) -> Result<String, String> {
+    // iCalendar text of the contract's dated events
+    let analyzer = ContractAnalyzer::new(true).with_date_order(date_order.unwrap_or_default());
+    let summary = analyzer.analyze_contract(&contract_text).map_err(|e| e.to_string())?;
+    Ok(summary.timeline.to_ical(&calendar_name))
+}
+
+#[tauri::command]
+async fn analyze_contract_bundle(
+    documents: Vec<ContractDoc>,
+    date_order: Option<DateOrder>,
+) -> Result<ContractBundle, String> {
+    let analyzer = ContractAnalyzer::new(true).with_date_order(date_order.unwrap_or_default());
+    analyzer.analyze_bundle(&documents).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn anonymize_contract(contract_text: String, mnemonic: String) -> Result<serde_json::Value, String> {
+    // The mapping seal re-identifies the text; keep it out of anything
+    // shared. Placeholders are keyed by the organization's phrase.
+    let key = OrganizationKey::from_mnemonic(&mnemonic)?;
+    let (redacted_text, mapping_seal) = ContractAnalyzer::new(true).anonymize(&contract_text, &key);
+    Ok(serde_json::json!({
+        "redacted_text": redacted_text,
+        "mapping_seal": mapping_seal
+    }))
+}
+
+#[tauri::command]
+async fn reidentify_contract(redacted_text: String, mapping_seal: MappingSeal, mnemonic: String) -> Result<String, String> {
+    let key = OrganizationKey::from_mnemonic(&mnemonic)?;
+    mapping_seal.reidentify(&redacted_text, &key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn analyze_contract_sealed(
+    contract_text: String,
+    mnemonic: String,
+    rule_pack: Option<String>,
+    date_order: Option<DateOrder>,
+) -> Result<SealedSummary, String> {
+    // Only ciphertexts and the seal leave this call; the key is recovered
+    // from the holder's phrase and dropped with it
+    let fhe = DeoxysFHE::from_mnemonic(&mnemonic)?;
+    let analyzer = contract_analyzer(rule_pack, date_order, None)?;
+    analyzer.analyze_contract_sealed(&contract_text, &fhe, &mut rand_core::OsRng).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn decrypt_sealed_contract(
+    sealed: SealedSummary,
+    mnemonic: String,
+    fields: Option<Vec<String>>,
+) -> Result<serde_json::Value, String> {
+    // The named fields, or the whole summary checked against its seal
+    let fhe = DeoxysFHE::from_mnemonic(&mnemonic)?;
+    match fields {
+        Some(fields) => serde_json::to_value(sealed.decrypt_fields(&fhe, &fields).map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string()),
+        None => Ok(sealed.open(&fhe).map_err(|e| e.to_string())?.to_json()),
+    }
+}
+
+#[tauri::command]
+async fn certify_contract(
+    state: tauri::State<'_, AppState>,
+    contract_text: String,
+    rule_pack: Option<String>,
+) -> Result<ContractCertificate, String> {
+    // Each calculator iteration reruns the analysis; the token is withheld
+    // from contracts scoring at or above the pack's insurable threshold
+    let mut analyzer = ContractAnalyzer::new(true);
+    if let Some(rule_pack) = rule_pack {
+        let rules = RulePack::from_json(&rule_pack).map_err(|e| e.to_string())?;
+        analyzer = analyzer.with_rules(rules).map_err(|e| e.to_string())?;
+    }
+    let calculator = state.risk_calculator.lock().await.clone();
+    tokio::task::spawn_blocking(move || contract_scoring::certify(&analyzer, &contract_text, &calculator))
+        .await
+        .map_err(|e| format!("Contract certification failed: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn default_contract_rules(language: Option<Language>) -> Result<RulePack, String> {
+    Ok(RulePack::for_language(language.unwrap_or_default()))
 }
 
 #[tauri::command]
@@ -178,17 +846,61 @@ fn main() {
     let app_state = AppState {
         risk_calculator,
         axiom_determinist,
+        mamba_cancel: Arc::new(Mutex::new(Arc::new(AtomicBool::new(false)))),
+        mamba_session: Arc::new(Mutex::new(None)),
     };
 
     tauri::Builder::default()
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             parse_toon_data,
+            parse_toon_file,
             calculate_risk,
             run_mamba_model,
+            run_mamba_batch,
+            generate_mamba_text,
+            stream_mamba_text,
+            cancel_mamba_generation,
+            verify_mamba_bit_exactness,
+            mamba_stability_report,
+            seal_mamba_inference,
+            extend_mamba_session,
+            reset_mamba_session,
+            mamba_memory_report,
+            save_mamba_session,
+            load_mamba_session,
             encrypt_fhe,
             decrypt_fhe,
+            encrypt_text_fhe,
+            decrypt_text_fhe,
+            encrypt_batch_fhe,
+            decrypt_batch_fhe,
+            encrypt_rlwe,
+            decrypt_rlwe,
+            split_fhe_key,
+            partial_decrypt_fhe,
+            combine_partials_fhe,
+            benchmark_fhe,
+            encrypt_fhe_authenticated,
+            decrypt_fhe_authenticated,
+            encrypt_file_fhe,
+            decrypt_file_fhe,
+            generate_fhe_mnemonic,
+            validate_fhe_mnemonic,
+            verify_risk_encrypted,
             process_contract,
+            contract_obligations_page,
+            contract_risk_flags_page,
+            process_contract_file,
+            compare_contracts,
+            export_contract_timeline,
+            analyze_contract_bundle,
+            certify_contract,
+            anonymize_contract,
+            reidentify_contract,
+            analyze_contract_sealed,
+            decrypt_sealed_contract,
+            default_contract_rules,
             get_system_status,
             generate_code_deterministic,
             validate_code_sterilization,